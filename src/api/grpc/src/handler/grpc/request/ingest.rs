@@ -13,17 +13,24 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
+use std::pin::Pin;
+
 use config::{
+    cluster::LOCAL_NODE,
     meta::{otlp::OtlpRequestType, stream::StreamType},
     metrics,
     utils::json,
 };
+use futures::Stream;
 use http::StatusCode;
 use infra::errors::{Error, Result};
 use ingestion_common::{IngestUser, SystemJobType};
 use proto::cluster_rpc::{
-    IngestionRequest, IngestionResponse, IngestionType, ingest_server::Ingest,
+    IngestionRequest, IngestionResponse, IngestionType, TailRequest, TailResponse,
+    ingest_server::Ingest,
 };
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
 use tonic::{Request, Response, Status};
 
 use crate::service::ingestion::create_log_ingestion_req;
@@ -33,6 +40,8 @@ pub struct Ingester;
 
 #[tonic::async_trait]
 impl Ingest for Ingester {
+    type TailStream = Pin<Box<dyn Stream<Item = Result<TailResponse, Status>> + Send + 'static>>;
+
     async fn ingest(
         &self,
         request: Request<IngestionRequest>,
@@ -210,6 +219,21 @@ impl Ingest for Ingester {
 
         Ok(Response::new(reply))
     }
+
+    async fn tail(
+        &self,
+        request: Request<TailRequest>,
+    ) -> Result<Response<Self::TailStream>, Status> {
+        if !LOCAL_NODE.is_ingester() {
+            return Err(Status::failed_precondition(
+                "live tail is served by ingesters",
+            ));
+        }
+        let (tx, rx) = mpsc::channel::<Result<TailResponse, Status>>(16);
+        tokio::task::spawn(search_service::tail::serve(request.into_inner(), tx));
+        let out_stream = ReceiverStream::new(rx);
+        Ok(Response::new(Box::pin(out_stream) as Self::TailStream))
+    }
 }
 
 #[cfg(test)]
@@ -105,7 +105,10 @@ impl Metrics for MetricsQuerier {
         let cap = std::cmp::max(2, config::get_config().limit.cpu_num);
         let (tx, rx) = mpsc::channel::<Result<MetricsQueryResponse, Status>>(cap);
         let mut req: MetricsQueryRequest = req.into_inner();
-        req.query.as_mut().unwrap().query_data = true;
+        // raw data requests keep the series hash, a shard of a sharded query is
+        // merged by labels and evaluated like a regular query
+        let query = req.query.as_mut().unwrap();
+        query.query_data = query.shard_count <= 1;
 
         log::info!(
             "[trace_id {}] promql->data->grpc: org_id: {}, use_cache: {}, time_range: [{},{}), step: {}, query: {}, label_selector: {:?}",
@@ -29,6 +29,9 @@ impl QueryCache for QueryCacheServerImpl {
         request: Request<DeleteResultCacheRequest>,
     ) -> Result<Response<DeleteResultCacheResponse>, Status> {
         let req: DeleteResultCacheRequest = request.into_inner();
+        if let Err(e) = promql_service::search::delete_cache(&req.path, req.ts).await {
+            log::error!("delete metrics result cache for {} error: {e}", req.path);
+        }
         let deleted = cacher::delete_cache(&req.path, req.ts, None, None)
             .await
             .is_ok();
@@ -819,6 +819,14 @@ pub fn service_routes() -> Router {
         .route("/{org_id}/prometheus/api/v1/labels", get(promql::labels_get).post(promql::labels_post))
         .route("/{org_id}/prometheus/api/v1/label/{label_name}/values", get(promql::label_values))
         .route("/{org_id}/prometheus/api/v1/format_query", get(promql::format_query_get).post(promql::format_query_post))
+        .route("/{org_id}/prometheus/api/v1/status/tsdb", get(promql::status::tsdb_status))
+        .route("/{org_id}/prometheus/api/v1/status/series_churn", get(promql::status::series_churn))
+        .route("/{org_id}/prometheus/api/v1/rules", get(promql::rules::rules_get))
+        .route("/{org_id}/prometheus/api/v1/alerts", get(promql::rules::alerts_get))
+        .route("/{org_id}/prometheus/config/v1/rules", get(promql::rules::list_rule_groups))
+        .route("/{org_id}/prometheus/config/v1/rules/{namespace}", post(promql::rules::import_rule_groups).delete(promql::rules::delete_namespace))
+        .route("/{org_id}/prometheus/config/v1/rules/{namespace}/{group}", delete(promql::rules::delete_rule_group))
+        .route("/{org_id}/prometheus/config/v1/ingest_rules", get(promql::ingest_rules::get_ingest_rules).put(promql::ingest_rules::set_ingest_rules).delete(promql::ingest_rules::delete_ingest_rules))
 
         // Search
         .route("/{org_id}/_search", post(search::search))
@@ -838,11 +846,16 @@ pub fn service_routes() -> Router {
         // HTTP/2 streaming
         .route("/{org_id}/_search_stream", post(search::search_stream::search_http2_stream))
         .route("/{org_id}/_values_stream", post(search::search_stream::values_http2_stream))
+        .route("/{org_id}/{stream_name}/_tail", get(search::tail::live_tail))
 
         // Saved views
         .route("/{org_id}/savedviews", get(search::saved_view::get_views).post(search::saved_view::create_view))
         .route("/{org_id}/savedviews/{view_id}", get(search::saved_view::get_view).put(search::saved_view::update_view).delete(search::saved_view::delete_view))
 
+        // External tables
+        .route("/{org_id}/external_tables", get(search::external_tables::list_tables).post(search::external_tables::create_table))
+        .route("/{org_id}/external_tables/{name}", get(search::external_tables::get_table).delete(search::external_tables::delete_table))
+
         // Functions
         .route("/{org_id}/functions", get(functions::list_functions).post(functions::save_function))
         .route("/{org_id}/functions/test", post(functions::test_function))
@@ -903,6 +916,10 @@ pub fn service_routes() -> Router {
         .route("/v2/{org_id}/folders/{folder_type}/name/{folder_name}", get(folders::get_folder_by_name))
 
         // Alerts (v2)
+        .route("/{org_id}/alertmanager/api/v2/alerts", get(alerts::alertmanager::get_alerts).post(alerts::alertmanager::post_alerts))
+        .route("/{org_id}/alertmanager/api/v2/silences", get(alerts::alertmanager::get_silences).post(alerts::alertmanager::post_silence))
+        .route("/{org_id}/alertmanager/api/v2/silence/{silence_id}", get(alerts::alertmanager::get_silence).delete(alerts::alertmanager::delete_silence))
+        .route("/{org_id}/alertmanager/api/v2/status", get(alerts::alertmanager::get_status))
         .route("/v2/{org_id}/alerts", get(alerts::list_alerts).post(alerts::create_alert))
         .route("/v2/{org_id}/alerts/composites/validate", post(alerts::validate_composite_alert))
         .route("/v2/{org_id}/alerts/{alert_id}/composite-references", get(alerts::get_composite_references))
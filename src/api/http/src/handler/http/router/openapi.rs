@@ -90,6 +90,17 @@ use crate::{
         openobserve_api_search::promql::labels_get,
         openobserve_api_search::promql::label_values,
         openobserve_api_search::promql::format_query_get,
+        openobserve_api_search::promql::status::tsdb_status,
+        openobserve_api_search::promql::status::series_churn,
+        openobserve_api_search::promql::rules::rules_get,
+        openobserve_api_search::promql::rules::alerts_get,
+        openobserve_api_search::promql::rules::list_rule_groups,
+        openobserve_api_search::promql::rules::import_rule_groups,
+        openobserve_api_search::promql::rules::delete_namespace,
+        openobserve_api_search::promql::rules::delete_rule_group,
+        openobserve_api_search::promql::ingest_rules::get_ingest_rules,
+        openobserve_api_search::promql::ingest_rules::set_ingest_rules,
+        openobserve_api_search::promql::ingest_rules::delete_ingest_rules,
         enrichment_table::save_enrichment_table,
         enrichment_table::save_enrichment_table_from_url,
         rum::ingest::log,
@@ -107,6 +118,10 @@ use crate::{
         openobserve_api_search::search::saved_view::get_view,
         openobserve_api_search::search::saved_view::get_views,
         openobserve_api_search::search::saved_view::update_view,
+        openobserve_api_search::search::external_tables::list_tables,
+        openobserve_api_search::search::external_tables::get_table,
+        openobserve_api_search::search::external_tables::create_table,
+        openobserve_api_search::search::external_tables::delete_table,
         openobserve_api_management::request::folders::delete_folder,
         openobserve_api_management::request::folders::create_folder,
         openobserve_api_management::request::folders::list_folders,
@@ -158,6 +173,13 @@ use crate::{
         openobserve_api_management::request::alerts::move_alerts,
         openobserve_api_management::request::alerts::list_alert_tags,
         openobserve_api_management::request::alerts::history::get_alert_history,
+        openobserve_api_management::request::alerts::alertmanager::post_alerts,
+        openobserve_api_management::request::alerts::alertmanager::get_alerts,
+        openobserve_api_management::request::alerts::alertmanager::get_silences,
+        openobserve_api_management::request::alerts::alertmanager::post_silence,
+        openobserve_api_management::request::alerts::alertmanager::get_silence,
+        openobserve_api_management::request::alerts::alertmanager::delete_silence,
+        openobserve_api_management::request::alerts::alertmanager::get_status,
         openobserve_api_management::request::alerts::incidents::list_incidents,
         openobserve_api_management::request::alerts::incidents::get_incident,
         openobserve_api_management::request::alerts::incidents::update_incident,
@@ -257,6 +279,7 @@ use crate::{
         openobserve_api_search::search::search_job::retry_job,
         openobserve_api_search::search::search_stream::search_http2_stream,
         openobserve_api_search::search::search_stream::values_http2_stream,
+        openobserve_api_search::search::tail::live_tail,
         patterns::extract_patterns,
         openobserve_core::traces::service_graph::api::get_current_topology,
         service_streams::list_services,
@@ -379,6 +402,19 @@ use crate::{
             openobserve_api_management::models::alerts::Condition,
             openobserve_api_management::models::alerts::Operator,
             // Incidents
+            config::meta::alerts::alertmanager::Matcher,
+            config::meta::alerts::alertmanager::PostableSilence,
+            config::meta::alerts::alertmanager::GettableSilence,
+            config::meta::alerts::alertmanager::SilenceStatus,
+            config::meta::alerts::alertmanager::SilenceState,
+            config::meta::alerts::alertmanager::PostSilenceResponse,
+            config::meta::alerts::alertmanager::GettableAlert,
+            config::meta::alerts::alertmanager::AlertStatus,
+            config::meta::alerts::alertmanager::AlertState,
+            config::meta::alerts::alertmanager::Receiver,
+            config::meta::alerts::alertmanager::AlertmanagerStatus,
+            config::meta::alerts::alertmanager::ClusterStatus,
+            config::meta::alerts::alertmanager::AlertmanagerConfig,
             openobserve_api_management::request::alerts::incidents::ListIncidentsQuery,
             openobserve_api_management::request::alerts::incidents::ListIncidentsResponse,
             openobserve_api_management::request::alerts::incidents::UpdatePayload,
@@ -436,6 +472,8 @@ use crate::{
             meta::saved_view::DeleteViewResponse,
             meta::saved_view::CreateViewResponse,
             meta::saved_view::UpdateViewRequest,
+            config::meta::external_table::ExternalTable,
+            config::meta::external_table::ExternalTableFormat,
             meta::user::UpdateUser,
             meta::user::UserRoleRequest,
             meta::user::PostUserRequest,
@@ -508,6 +546,7 @@ use crate::{
         (name = "Dashboards", description = "Dashboard operations"),
         (name = "Search", description = "Search/Query operations"),
         (name = "Saved Views", description = "Collection of saved search views for easy retrieval"),
+        (name = "External Tables", description = "Parquet files and Iceberg tables in the object storage queried with SQL"),
         (name = "Alerts", description = "Alerts retrieval & management operations"),
         (name = "Incidents", description = "Alert incident correlation & management operations"),
         (name = "AI", description = "AI agent chat analysis and SRE agent operations (enterprise)"),
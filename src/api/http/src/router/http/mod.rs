@@ -720,6 +720,7 @@ const STREAMING_ENDPOINTS: &[&str] = &[
     "/_search_stream",
     "/_values_stream",
     "/_search_multi_stream",
+    "/_tail",
     "/ai/chat_stream",
     "/prometheus/api/v1/query_range",
 ];
@@ -849,6 +850,7 @@ mod tests {
         assert!(is_streaming_endpoint("/api/org/_values_stream"));
         assert!(is_streaming_endpoint("/api/org/_search_multi_stream"));
         assert!(is_streaming_endpoint("/api/org/ai/chat_stream"));
+        assert!(is_streaming_endpoint("/api/org/default/_tail"));
         assert!(is_streaming_endpoint(
             "/api/org/prometheus/api/v1/query_range"
         ));
@@ -0,0 +1,391 @@
+// Copyright 2026 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Alertmanager v2 API, so Prometheus, vmalert, amtool and Grafana can use
+//! OpenObserve in place of an Alertmanager. Registered per org: point a
+//! Prometheus `alertmanagers` entry at `path_prefix: /api/{org_id}/alertmanager`
+//! with the credentials of a user of the org.
+//!
+//! Received alerts go through the org's default incident integration, into
+//! incident correlation and the integration's destinations. Silenced alerts
+//! are stored but not correlated.
+
+use std::sync::LazyLock;
+
+use axum::{
+    Json,
+    extract::{Path, RawQuery},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use config::{
+    meta::alerts::alertmanager::{
+        AlertmanagerConfig, AlertmanagerStatus, AlertsFilter, ClusterStatus, PostSilenceResponse,
+        PostableSilence,
+    },
+    utils::time::now_micros,
+};
+use openobserve_api_common::extractors::Headers;
+use openobserve_core::{
+    alerts::alertmanager::{self, AlertmanagerError},
+    auth::UserEmail,
+};
+
+use crate::common::meta::http::HttpResponse as MetaHttpResponse;
+
+/// Reported as the Alertmanager start time: when this node first served the
+/// status, as there is no process start time to report.
+static STATUS_SINCE: LazyLock<i64> = LazyLock::new(now_micros);
+
+/// PostAlertmanagerAlerts
+#[utoipa::path(
+    post,
+    path = "/{org_id}/alertmanager/api/v2/alerts",
+    context_path = "/api",
+    tag = "Alerts",
+    operation_id = "PostAlertmanagerAlerts",
+    summary = "Receive alerts from Prometheus",
+    description = "Alertmanager v2 compatible endpoint receiving the alerts of Prometheus, vmalert and other rule evaluators. Alerts go through the organization's default incident integration into incident correlation and its destinations; an alert is resolved once its `endsAt` has passed. Alerts matching an active silence are stored but do not notify.",
+    security(
+        ("Authorization"= [])
+    ),
+    params(
+        ("org_id" = String, Path, description = "Organization name"),
+    ),
+    request_body(content = Object, description = "List of Alertmanager postable alerts", content_type = "application/json"),
+    responses(
+        (status = 200, description = "Success"),
+        (status = 400, description = "Invalid alerts", content_type = "application/json", body = ()),
+        (status = 403, description = "Incidents are not enabled", content_type = "application/json", body = ()),
+    ),
+    extensions(
+        ("x-o2-ratelimit" = json!({"module": "Alerts", "operation": "create"})),
+        ("x-o2-mcp" = json!({"enabled": false}))
+    )
+)]
+pub async fn post_alerts(
+    Path(org_id): Path<String>,
+    Headers(user_email): Headers<UserEmail>,
+    Json(body): Json<serde_json::Value>,
+) -> Response {
+    #[cfg(feature = "enterprise")]
+    {
+        receive_alerts(&org_id, &user_email.user_id, &body).await
+    }
+    #[cfg(not(feature = "enterprise"))]
+    {
+        let _ = (org_id, user_email, body);
+        MetaHttpResponse::forbidden("Not Supported")
+    }
+}
+
+#[cfg(feature = "enterprise")]
+async fn receive_alerts(org_id: &str, user_id: &str, body: &serde_json::Value) -> Response {
+    use o2_enterprise::enterprise::common::config::get_config as o2_config;
+    use openobserve_core::alerts::external_alerts::{
+        DetectedSource, alertmanager::normalize_postable,
+    };
+
+    use super::external_events::{MAX_ALERTS_PER_REQUEST, process_events};
+
+    if !o2_config().incidents.enabled {
+        return MetaHttpResponse::forbidden("External alert sources not enabled");
+    }
+    let now = now_micros();
+    let resolve_timeout = config::get_config().limit.alertmanager_resolve_timeout * 1_000_000;
+    let events = match normalize_postable(body, now, resolve_timeout) {
+        Ok(evs) => evs,
+        Err(e) => return MetaHttpResponse::bad_request(e),
+    };
+    if events.len() > MAX_ALERTS_PER_REQUEST {
+        return MetaHttpResponse::bad_request(format!(
+            "too many alerts in one request: {} > {MAX_ALERTS_PER_REQUEST}",
+            events.len()
+        ));
+    }
+    let integration =
+        match infra::table::incident_integrations::ensure_default_for_org(org_id, user_id).await {
+            Ok(i) => i,
+            Err(e) => return MetaHttpResponse::internal_error(e),
+        };
+    if !integration.enabled {
+        return MetaHttpResponse::forbidden("default incident integration is disabled");
+    }
+    let silences = match alertmanager::active_silences(org_id, now).await {
+        Ok(v) => v,
+        Err(e) => return MetaHttpResponse::internal_error(e),
+    };
+
+    let processed = process_events(
+        org_id,
+        &integration,
+        DetectedSource::Alertmanager,
+        &events,
+        now,
+        |ev| {
+            !alertmanager::silenced_by(&silences, &alertmanager::payload_labels(&ev.raw)).is_empty()
+        },
+    )
+    .await;
+    if processed.rejected > 0 {
+        log::warn!(
+            "[ALERTMANAGER] org {org_id}: {} of {} alerts rejected: {:?}",
+            processed.rejected,
+            events.len(),
+            processed.errors
+        );
+    }
+    StatusCode::OK.into_response()
+}
+
+/// GetAlertmanagerAlerts
+#[utoipa::path(
+    get,
+    path = "/{org_id}/alertmanager/api/v2/alerts",
+    context_path = "/api",
+    tag = "Alerts",
+    operation_id = "GetAlertmanagerAlerts",
+    summary = "List firing Alertmanager alerts",
+    description = "Alertmanager v2 compatible list of the firing alerts received from Alertmanager senders, with the silences suppressing them.",
+    security(
+        ("Authorization"= [])
+    ),
+    params(
+        ("org_id" = String, Path, description = "Organization name"),
+        ("filter" = Option<Vec<String>>, Query, description = "Label matchers, e.g. `alertname=\"HighCPU\"`"),
+        ("active" = Option<bool>, Query, description = "Include alerts that are not silenced, default true"),
+        ("silenced" = Option<bool>, Query, description = "Include silenced alerts, default true"),
+    ),
+    responses(
+        (status = 200, description = "Success", content_type = "application/json", body = Vec<config::meta::alerts::alertmanager::GettableAlert>),
+        (status = 400, description = "Invalid filter", content_type = "application/json", body = ()),
+    ),
+    extensions(
+        ("x-o2-ratelimit" = json!({"module": "Alerts", "operation": "list"})),
+        ("x-o2-mcp" = json!({"enabled": false}))
+    )
+)]
+pub async fn get_alerts(Path(org_id): Path<String>, RawQuery(query): RawQuery) -> Response {
+    let filter = match AlertsFilter::from_query(query.as_deref().unwrap_or_default()) {
+        Ok(f) => f,
+        Err(e) => return MetaHttpResponse::bad_request(e),
+    };
+    match alertmanager::list_alerts(&org_id, &filter, now_micros()).await {
+        Ok(alerts) => MetaHttpResponse::json(alerts),
+        Err(e) => MetaHttpResponse::internal_error(e),
+    }
+}
+
+/// GetAlertmanagerSilences
+#[utoipa::path(
+    get,
+    path = "/{org_id}/alertmanager/api/v2/silences",
+    context_path = "/api",
+    tag = "Alerts",
+    operation_id = "GetAlertmanagerSilences",
+    summary = "List silences",
+    description = "Alertmanager v2 compatible list of silences, including pending ones and those expired within the retention period.",
+    security(
+        ("Authorization"= [])
+    ),
+    params(
+        ("org_id" = String, Path, description = "Organization name"),
+        ("filter" = Option<Vec<String>>, Query, description = "Label matchers; a silence is listed when it has each of them"),
+    ),
+    responses(
+        (status = 200, description = "Success", content_type = "application/json", body = Vec<config::meta::alerts::alertmanager::GettableSilence>),
+        (status = 400, description = "Invalid filter", content_type = "application/json", body = ()),
+    ),
+    extensions(
+        ("x-o2-ratelimit" = json!({"module": "Alerts", "operation": "list"})),
+        ("x-o2-mcp" = json!({"enabled": false}))
+    )
+)]
+pub async fn get_silences(Path(org_id): Path<String>, RawQuery(query): RawQuery) -> Response {
+    let filter = match AlertsFilter::from_query(query.as_deref().unwrap_or_default()) {
+        Ok(f) => f,
+        Err(e) => return MetaHttpResponse::bad_request(e),
+    };
+    let now = now_micros();
+    match alertmanager::list_silences(&org_id, now).await {
+        Ok(silences) => MetaHttpResponse::json(
+            silences
+                .into_iter()
+                // as in Alertmanager, the filter selects silences having
+                // these matchers, not silences matching these labels
+                .filter(|s| filter.matchers.iter().all(|m| s.matchers.contains(m)))
+                .map(|s| s.to_gettable(now))
+                .collect::<Vec<_>>(),
+        ),
+        Err(e) => MetaHttpResponse::internal_error(e),
+    }
+}
+
+/// PostAlertmanagerSilence
+#[utoipa::path(
+    post,
+    path = "/{org_id}/alertmanager/api/v2/silences",
+    context_path = "/api",
+    tag = "Alerts",
+    operation_id = "PostAlertmanagerSilence",
+    summary = "Create or update a silence",
+    description = "Alertmanager v2 compatible silence creation. With an `id`, updates that silence; updating an expired silence creates a new one.",
+    security(
+        ("Authorization"= [])
+    ),
+    params(
+        ("org_id" = String, Path, description = "Organization name"),
+    ),
+    request_body(content = config::meta::alerts::alertmanager::PostableSilence, description = "Silence", content_type = "application/json"),
+    responses(
+        (status = 200, description = "Success", content_type = "application/json", body = PostSilenceResponse),
+        (status = 400, description = "Invalid silence", content_type = "application/json", body = ()),
+        (status = 404, description = "Silence not found", content_type = "application/json", body = ()),
+    ),
+    extensions(
+        ("x-o2-ratelimit" = json!({"module": "Alerts", "operation": "create"})),
+        ("x-o2-mcp" = json!({"enabled": false}))
+    )
+)]
+pub async fn post_silence(
+    Path(org_id): Path<String>,
+    Headers(user_email): Headers<UserEmail>,
+    Json(silence): Json<PostableSilence>,
+) -> Response {
+    match alertmanager::save_silence(&org_id, silence, &user_email.user_id, now_micros()).await {
+        Ok(silence_id) => MetaHttpResponse::json(PostSilenceResponse { silence_id }),
+        Err(e) => error_response(e),
+    }
+}
+
+/// GetAlertmanagerSilence
+#[utoipa::path(
+    get,
+    path = "/{org_id}/alertmanager/api/v2/silence/{silence_id}",
+    context_path = "/api",
+    tag = "Alerts",
+    operation_id = "GetAlertmanagerSilence",
+    summary = "Get a silence",
+    description = "Alertmanager v2 compatible silence lookup.",
+    security(
+        ("Authorization"= [])
+    ),
+    params(
+        ("org_id" = String, Path, description = "Organization name"),
+        ("silence_id" = String, Path, description = "Silence id"),
+    ),
+    responses(
+        (status = 200, description = "Success", content_type = "application/json", body = config::meta::alerts::alertmanager::GettableSilence),
+        (status = 404, description = "Silence not found", content_type = "application/json", body = ()),
+    ),
+    extensions(
+        ("x-o2-ratelimit" = json!({"module": "Alerts", "operation": "get"})),
+        ("x-o2-mcp" = json!({"enabled": false}))
+    )
+)]
+pub async fn get_silence(Path((org_id, silence_id)): Path<(String, String)>) -> Response {
+    match alertmanager::get_silence(&org_id, &silence_id).await {
+        Ok(s) => MetaHttpResponse::json(s.to_gettable(now_micros())),
+        Err(e) => error_response(e),
+    }
+}
+
+/// DeleteAlertmanagerSilence
+#[utoipa::path(
+    delete,
+    path = "/{org_id}/alertmanager/api/v2/silence/{silence_id}",
+    context_path = "/api",
+    tag = "Alerts",
+    operation_id = "DeleteAlertmanagerSilence",
+    summary = "Expire a silence",
+    description = "Alertmanager v2 compatible silence deletion: the silence expires now and stays listed until the retention period passes.",
+    security(
+        ("Authorization"= [])
+    ),
+    params(
+        ("org_id" = String, Path, description = "Organization name"),
+        ("silence_id" = String, Path, description = "Silence id"),
+    ),
+    responses(
+        (status = 200, description = "Success"),
+        (status = 400, description = "Silence already expired", content_type = "application/json", body = ()),
+        (status = 404, description = "Silence not found", content_type = "application/json", body = ()),
+    ),
+    extensions(
+        ("x-o2-ratelimit" = json!({"module": "Alerts", "operation": "delete"})),
+        ("x-o2-mcp" = json!({"enabled": false}))
+    )
+)]
+pub async fn delete_silence(Path((org_id, silence_id)): Path<(String, String)>) -> Response {
+    match alertmanager::expire_silence(&org_id, &silence_id, now_micros()).await {
+        Ok(()) => StatusCode::OK.into_response(),
+        Err(e) => error_response(e),
+    }
+}
+
+/// GetAlertmanagerStatus
+#[utoipa::path(
+    get,
+    path = "/{org_id}/alertmanager/api/v2/status",
+    context_path = "/api",
+    tag = "Alerts",
+    operation_id = "GetAlertmanagerStatus",
+    summary = "Alertmanager status",
+    description = "Alertmanager v2 compatible status, used by Grafana and amtool to check the connection.",
+    security(
+        ("Authorization"= [])
+    ),
+    params(
+        ("org_id" = String, Path, description = "Organization name"),
+    ),
+    responses(
+        (status = 200, description = "Success", content_type = "application/json", body = AlertmanagerStatus),
+    ),
+    extensions(
+        ("x-o2-ratelimit" = json!({"module": "Alerts", "operation": "get"})),
+        ("x-o2-mcp" = json!({"enabled": false}))
+    )
+)]
+pub async fn get_status(Path(_org_id): Path<String>) -> Response {
+    MetaHttpResponse::json(AlertmanagerStatus {
+        cluster: ClusterStatus {
+            status: "disabled".to_string(),
+            peers: Vec::new(),
+        },
+        version_info: [
+            ("version", config::VERSION),
+            ("revision", config::COMMIT_HASH),
+            ("branch", ""),
+            ("buildUser", ""),
+            ("buildDate", config::BUILD_DATE),
+            ("goVersion", ""),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect(),
+        config: AlertmanagerConfig {
+            original: String::new(),
+        },
+        uptime: config::meta::alerts::alertmanager::format_time(*STATUS_SINCE),
+    })
+}
+
+fn error_response(err: AlertmanagerError) -> Response {
+    match err {
+        AlertmanagerError::Invalid(e) => MetaHttpResponse::bad_request(e),
+        AlertmanagerError::NotFound => MetaHttpResponse::not_found(err),
+        AlertmanagerError::Storage(e) => MetaHttpResponse::internal_error(e),
+    }
+}
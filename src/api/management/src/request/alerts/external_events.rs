@@ -127,6 +127,44 @@ async fn handle_events(
         )); // 400; sources should configure max_alerts/grouping
     }
 
+    let processed = process_events(&org_id, &integration, detected, &events, now, |_| false).await;
+
+    Response::builder()
+        .status(axum::http::StatusCode::ACCEPTED)
+        .header(axum::http::header::CONTENT_TYPE, "application/json")
+        .body(
+            serde_json::json!({
+                "accepted": processed.accepted,
+                "rejected": processed.rejected,
+                "errors": processed.errors,
+            })
+            .to_string()
+            .into(),
+        )
+        .unwrap()
+}
+
+/// Per-request counts of [`process_events`], reported back to the sender.
+#[cfg(feature = "enterprise")]
+pub(crate) struct ProcessedEvents {
+    pub accepted: u32,
+    pub rejected: u32,
+    pub errors: Vec<serde_json::Value>,
+}
+
+/// Stores normalized events for `integration`, correlates new, refreshed and
+/// reopened ones into incidents unless `is_silenced` holds them back, checks
+/// resolved ones for incident auto-resolve, and records the request in the
+/// integration's sender stats.
+#[cfg(feature = "enterprise")]
+pub(crate) async fn process_events(
+    org_id: &str,
+    integration: &infra::table::incident_integrations::IncidentIntegrationRecord,
+    detected: openobserve_core::alerts::external_alerts::DetectedSource,
+    events: &[config::meta::alerts::incidents::ExternalAlertEvent],
+    now: i64,
+    is_silenced: impl Fn(&config::meta::alerts::incidents::ExternalAlertEvent) -> bool,
+) -> ProcessedEvents {
     let base_destinations: Vec<String> = integration.destinations.clone();
 
     let (mut accepted, mut rejected, mut saw_resolved) = (0u32, 0u32, false);
@@ -136,7 +174,7 @@ async fn handle_events(
             saw_resolved = true;
         }
         match infra::table::external_alerts::upsert_event(
-            &org_id,
+            org_id,
             &integration.id,
             detected.as_str(),
             ev,
@@ -147,8 +185,9 @@ async fn handle_events(
                 accepted += 1;
                 use infra::table::external_alerts::UpsertOutcome::*;
                 if matches!(outcome, Inserted | Refreshed | Reopened)
+                    && !is_silenced(ev)
                     && let Err(e) = openobserve_core::alerts::incidents::correlate_external_event(
-                        &org_id,
+                        org_id,
                         &record,
                         base_destinations.clone(),
                     )
@@ -161,7 +200,7 @@ async fn handle_events(
                 } else if matches!(outcome, ResolvedApplied)
                     && let Err(e) =
                         openobserve_core::alerts::incidents::try_auto_resolve_incident_for_external_alert(
-                            &org_id, &record.id,
+                            org_id, &record.id,
                         )
                         .await
                 {
@@ -177,7 +216,7 @@ async fn handle_events(
             }
         }
     }
-    let sender_label = openobserve_core::alerts::external_alerts::derive_sender_label(&events);
+    let sender_label = openobserve_core::alerts::external_alerts::derive_sender_label(events);
     let _ = infra::table::incident_integrations::touch_sender(
         infra::table::incident_integrations::TouchSenderParams {
             integration_id: &integration.id,
@@ -191,15 +230,11 @@ async fn handle_events(
     )
     .await;
 
-    Response::builder()
-        .status(axum::http::StatusCode::ACCEPTED)
-        .header(axum::http::header::CONTENT_TYPE, "application/json")
-        .body(
-            serde_json::json!({"accepted": accepted, "rejected": rejected, "errors": errors})
-                .to_string()
-                .into(),
-        )
-        .unwrap()
+    ProcessedEvents {
+        accepted,
+        rejected,
+        errors,
+    }
 }
 
 #[cfg(not(feature = "enterprise"))]
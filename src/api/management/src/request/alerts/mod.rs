@@ -72,6 +72,7 @@ use crate::{
     },
 };
 
+pub mod alertmanager;
 pub mod chart_render;
 pub mod dedup_stats;
 pub mod deduplication;
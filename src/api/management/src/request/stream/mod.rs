@@ -670,7 +670,7 @@ pub async fn delete_stream_cache(
         format!("{org_id}/{stream_type}/{stream_name}")
     };
 
-    match search_service::cluster::cacher::delete_cached_results(path, delete_ts).await {
+    match promql_service::search::delete_cached_results(path, delete_ts).await {
         true => (
             StatusCode::OK,
             Json(MetaHttpResponse::message(
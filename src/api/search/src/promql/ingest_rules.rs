@@ -0,0 +1,183 @@
+// Copyright 2026 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Ingest rules of metrics: relabeling and series limits enforced when
+//! samples are written.
+
+use axum::{
+    body::Bytes,
+    extract::Path,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use config::meta::promql::ApiFuncResponse;
+use openobserve_core::metrics::ingest_rules::{self, IngestRulesError};
+
+/// Get ingest rules
+
+#[utoipa::path(
+    get,
+    path = "/{org_id}/prometheus/config/v1/ingest_rules",
+    context_path = "/api",
+    tag = "Metrics",
+    operation_id = "PrometheusGetIngestRules",
+    summary = "Get metric ingest rules",
+    description = "Returns the metric relabel configs and series limits applied to the metrics written to the organization, empty when none are set.",
+    security(
+        ("Authorization"= [])
+    ),
+    params(
+        ("org_id" = String, Path, description = "Organization name"),
+    ),
+    responses(
+        (status = 200, description = "Success", content_type = "application/json", body = Object, example = json!({
+            "status": "success",
+            "data": {
+                "metric_relabel_configs": [{"action": "labeldrop", "regex": "request_id"}],
+                "metrics": {
+                    "http_requests_total": {"series_limit": 10000, "limit_policy": "drop"}
+                }
+            }
+        })),
+        (status = 500, description = "Failure", content_type = "application/json", body = ()),
+    ),
+    extensions(
+        ("x-o2-ratelimit" = json!({"module": "Metrics", "operation": "get"})),
+        ("x-o2-mcp" = json!({"enabled": false}))
+    )
+)]
+pub async fn get_ingest_rules(Path(org_id): Path<String>) -> Response {
+    match ingest_rules::get(&org_id).await {
+        Ok(rules) => (
+            StatusCode::OK,
+            axum::Json(ApiFuncResponse::ok(rules.unwrap_or_default(), None)),
+        )
+            .into_response(),
+        Err(e) => {
+            log::error!("[METRICS:INGEST_RULES] reading ingest rules of org {org_id} failed: {e}");
+            internal_error(e)
+        }
+    }
+}
+
+/// Set ingest rules
+
+#[utoipa::path(
+    put,
+    path = "/{org_id}/prometheus/config/v1/ingest_rules",
+    context_path = "/api",
+    tag = "Metrics",
+    operation_id = "PrometheusSetIngestRules",
+    summary = "Set metric ingest rules",
+    description = "Replaces the ingest rules of the organization. `metric_relabel_configs` apply to every metric, the `metrics` entries to one metric each: their own `metric_relabel_configs`, then a `series_limit` of active series per ingester. New series over the limit are dropped, or with the `aggregate` policy folded into one series without their `aggregate_labels` and with `__aggregated__=\"true\"`, summing the samples of one request that share a timestamp. Rules may not replace `__name__`, which names the stream. Rules are applied before the series hash is computed and take effect on the ingesters within `ZO_METRICS_INGEST_RULES_CACHE_TTL`.",
+    security(
+        ("Authorization"= [])
+    ),
+    params(
+        ("org_id" = String, Path, description = "Organization name"),
+    ),
+    request_body(content = String, description = "Ingest rules as YAML or JSON", content_type = "application/yaml"),
+    responses(
+        (status = 200, description = "Success", content_type = "application/json", body = Object, example = json!({
+            "status": "success",
+            "data": {
+                "metrics": {
+                    "http_requests_total": {
+                        "metric_relabel_configs": [{
+                            "source_labels": ["path"],
+                            "regex": "/users/[0-9]+",
+                            "target_label": "path",
+                            "replacement": "/users/:id"
+                        }],
+                        "series_limit": 10000,
+                        "limit_policy": "aggregate",
+                        "aggregate_labels": ["path"]
+                    }
+                }
+            }
+        })),
+        (status = 400, description = "Invalid rules", content_type = "application/json", body = ()),
+        (status = 500, description = "Failure", content_type = "application/json", body = ()),
+    ),
+    extensions(
+        ("x-o2-ratelimit" = json!({"module": "Metrics", "operation": "update"})),
+        ("x-o2-mcp" = json!({"enabled": false}))
+    )
+)]
+pub async fn set_ingest_rules(Path(org_id): Path<String>, body: Bytes) -> Response {
+    let body = match std::str::from_utf8(&body) {
+        Ok(v) => v,
+        Err(e) => return bad_request(e),
+    };
+    match ingest_rules::set(&org_id, body).await {
+        Ok(rules) => (StatusCode::OK, axum::Json(ApiFuncResponse::ok(rules, None))).into_response(),
+        Err(e @ IngestRulesError::Invalid(_)) => bad_request(e),
+        Err(IngestRulesError::Storage(e)) => {
+            log::error!("[METRICS:INGEST_RULES] saving ingest rules of org {org_id} failed: {e}");
+            internal_error(e)
+        }
+    }
+}
+
+/// Delete ingest rules
+
+#[utoipa::path(
+    delete,
+    path = "/{org_id}/prometheus/config/v1/ingest_rules",
+    context_path = "/api",
+    tag = "Metrics",
+    operation_id = "PrometheusDeleteIngestRules",
+    summary = "Delete metric ingest rules",
+    description = "Removes every ingest rule of the organization.",
+    security(
+        ("Authorization"= [])
+    ),
+    params(
+        ("org_id" = String, Path, description = "Organization name"),
+    ),
+    responses(
+        (status = 200, description = "Success", content_type = "application/json", body = Object, example = json!({"status": "success", "data": null})),
+        (status = 500, description = "Failure", content_type = "application/json", body = ()),
+    ),
+    extensions(
+        ("x-o2-ratelimit" = json!({"module": "Metrics", "operation": "delete"})),
+        ("x-o2-mcp" = json!({"enabled": false}))
+    )
+)]
+pub async fn delete_ingest_rules(Path(org_id): Path<String>) -> Response {
+    match ingest_rules::delete(&org_id).await {
+        Ok(()) => (StatusCode::OK, axum::Json(ApiFuncResponse::ok((), None))).into_response(),
+        Err(e) => {
+            log::error!("[METRICS:INGEST_RULES] deleting ingest rules of org {org_id} failed: {e}");
+            internal_error(e)
+        }
+    }
+}
+
+fn bad_request(err: impl ToString) -> Response {
+    (
+        StatusCode::BAD_REQUEST,
+        axum::Json(ApiFuncResponse::<()>::err_bad_data(err, None)),
+    )
+        .into_response()
+}
+
+fn internal_error(err: impl ToString) -> Response {
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        axum::Json(ApiFuncResponse::<()>::err_internal(err, None)),
+    )
+        .into_response()
+}
@@ -13,6 +13,10 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
+pub mod ingest_rules;
+pub mod rules;
+pub mod status;
+
 use ::promql;
 use axum::{
     body::{Body, Bytes},
@@ -1680,6 +1684,58 @@ impl promql_parser::util::ExprVisitor for MaxLookbackWindowVisitor {
     }
 }
 
+/// Checks that the user may read the org's metrics, or `metric_name` when
+/// given, as `label_values` does. Returns the forbidden response otherwise.
+#[cfg(feature = "enterprise")]
+pub(crate) async fn check_metrics_permission(
+    org_id: &str,
+    user_id: &str,
+    metric_name: Option<&str>,
+) -> Option<Response> {
+    use db::org_users::get_cached_user_org;
+    use openobserve_core::auth::AuthExtractor;
+
+    if db::user::is_root_user(user_id) {
+        return None;
+    }
+    let user = match get_cached_user_org(org_id, user_id) {
+        Some(u) => u,
+        None => return Some(MetaHttpResponse::forbidden("Unauthorized Access")),
+    };
+    let stream_type_str = StreamType::Metrics.as_str();
+    let object = match metric_name {
+        Some(name) => openobserve_core::auth::into_ofga_supported_format(name),
+        None => org_id.to_string(),
+    };
+    if !openobserve_core::authz::check_permissions(
+        user_id,
+        AuthExtractor {
+            auth: "".to_string(),
+            method: "GET".to_string(),
+            o2_type: format!(
+                "{}:{}",
+                OFGA_MODELS
+                    .get(stream_type_str)
+                    .map_or(stream_type_str, |model| model.key),
+                object
+            ),
+            org_id: org_id.to_string(),
+            bypass_check: false,
+            parent_id: "".to_string(),
+            use_all_org: false,
+            use_self_context: false,
+            use_self_parent: true,
+        },
+        user.role,
+        user.is_external,
+    )
+    .await
+    {
+        return Some(MetaHttpResponse::forbidden("Unauthorized Access"));
+    }
+    None
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,379 @@
+// Copyright 2026 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Prometheus rule groups: the read-only `api/v1/rules` and `api/v1/alerts`
+//! endpoints, and a ruler-style config API to import, list and delete rule
+//! files.
+
+use std::collections::HashMap;
+
+use axum::{
+    body::Bytes,
+    extract::{Path, Query, RawQuery},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use config::meta::{
+    folder::DEFAULT_FOLDER,
+    promql::{ApiErrorType, ApiFuncResponse, rules::RulesFilter},
+};
+use openobserve_api_common::extractors::Headers;
+use openobserve_core::{
+    auth::UserEmail,
+    metrics::rules::{self, ImportOptions, RulesError},
+};
+
+/// prometheus rules endpoint
+
+// refer: https://prometheus.io/docs/prometheus/latest/querying/api/#rules
+#[utoipa::path(
+    get,
+    path = "/{org_id}/prometheus/api/v1/rules",
+    context_path = "/api",
+    tag = "Metrics",
+    operation_id = "PrometheusRules",
+    summary = "List Prometheus rules",
+    description = "Returns the imported Prometheus rule groups with the health and last evaluation of each rule. Alerting rules include their firing alerts. Supports the `type`, `rule_name[]`, `rule_group[]`, `file[]` and `exclude_alerts` filters of the Prometheus API.",
+    security(
+        ("Authorization"= [])
+    ),
+    params(
+        ("org_id" = String, Path, description = "Organization name"),
+        ("type" = Option<String>, Query, description = "Only return `alert` or `record` rules"),
+        ("exclude_alerts" = Option<bool>, Query, description = "Omit the active alerts of alerting rules"),
+    ),
+    responses(
+        (status = 200, description = "Success", content_type = "application/json", body = Object, example = json!({
+            "status": "success",
+            "data": {
+                "groups": [{
+                    "name": "example",
+                    "file": "team-a",
+                    "interval": 60,
+                    "rules": [{
+                        "type": "recording",
+                        "name": "job:up:sum",
+                        "query": "sum by (job) (up)",
+                        "health": "ok",
+                        "lastEvaluation": "2026-01-01T00:00:00.000Z"
+                    }]
+                }]
+            }
+        })),
+        (status = 500, description = "Failure", content_type = "application/json", body = ()),
+    ),
+    extensions(
+        ("x-o2-ratelimit" = json!({"module": "Metrics", "operation": "get"})),
+        ("x-o2-mcp" = json!({"description": "List Prometheus rules", "category": "metrics"}))
+    )
+)]
+pub async fn rules_get(
+    Path(org_id): Path<String>,
+    RawQuery(query): RawQuery,
+    Headers(_user_email): Headers<UserEmail>,
+) -> Response {
+    #[cfg(feature = "enterprise")]
+    if let Some(resp) =
+        crate::promql::check_metrics_permission(&org_id, &_user_email.user_id, None).await
+    {
+        return resp;
+    }
+    let filter = RulesFilter::from_query(query.as_deref().unwrap_or_default());
+    match rules::rules(&org_id, &filter).await {
+        Ok(resp) => (StatusCode::OK, axum::Json(ApiFuncResponse::ok(resp, None))).into_response(),
+        Err(e) => {
+            log::error!("[PROM_RULES] listing rules of org {org_id} failed: {e}");
+            internal_error(e)
+        }
+    }
+}
+
+/// prometheus alerts endpoint
+
+// refer: https://prometheus.io/docs/prometheus/latest/querying/api/#alerts
+#[utoipa::path(
+    get,
+    path = "/{org_id}/prometheus/api/v1/alerts",
+    context_path = "/api",
+    tag = "Metrics",
+    operation_id = "PrometheusAlerts",
+    summary = "List active Prometheus alerts",
+    description = "Returns the firing alerts of all imported alerting rules, one per alerting series, with the labels and annotations of the rule.",
+    security(
+        ("Authorization"= [])
+    ),
+    params(
+        ("org_id" = String, Path, description = "Organization name"),
+    ),
+    responses(
+        (status = 200, description = "Success", content_type = "application/json", body = Object, example = json!({
+            "status": "success",
+            "data": {
+                "alerts": [{
+                    "labels": {"alertname": "InstanceDown", "instance": "host:9100"},
+                    "annotations": {"summary": "instance down"},
+                    "state": "firing",
+                    "activeAt": "2026-01-01T00:00:00.000Z"
+                }]
+            }
+        })),
+        (status = 500, description = "Failure", content_type = "application/json", body = ()),
+    ),
+    extensions(
+        ("x-o2-ratelimit" = json!({"module": "Metrics", "operation": "get"})),
+        ("x-o2-mcp" = json!({"description": "List active Prometheus alerts", "category": "metrics"}))
+    )
+)]
+pub async fn alerts_get(
+    Path(org_id): Path<String>,
+    Headers(_user_email): Headers<UserEmail>,
+) -> Response {
+    #[cfg(feature = "enterprise")]
+    if let Some(resp) =
+        crate::promql::check_metrics_permission(&org_id, &_user_email.user_id, None).await
+    {
+        return resp;
+    }
+    match rules::alerts(&org_id).await {
+        Ok(resp) => (StatusCode::OK, axum::Json(ApiFuncResponse::ok(resp, None))).into_response(),
+        Err(e) => {
+            log::error!("[PROM_RULES] listing alerts of org {org_id} failed: {e}");
+            internal_error(e)
+        }
+    }
+}
+
+/// List imported rule files
+
+#[utoipa::path(
+    get,
+    path = "/{org_id}/prometheus/config/v1/rules",
+    context_path = "/api",
+    tag = "Metrics",
+    operation_id = "PrometheusListRuleGroups",
+    summary = "List Prometheus rule files",
+    description = "Returns all imported rule groups as YAML, keyed by namespace, in the format accepted by the import endpoint.",
+    security(
+        ("Authorization"= [])
+    ),
+    params(
+        ("org_id" = String, Path, description = "Organization name"),
+    ),
+    responses(
+        (status = 200, description = "Success", content_type = "application/yaml", body = String),
+        (status = 500, description = "Failure", content_type = "application/json", body = ()),
+    ),
+    extensions(
+        ("x-o2-ratelimit" = json!({"module": "Metrics", "operation": "get"})),
+        ("x-o2-mcp" = json!({"enabled": false}))
+    )
+)]
+pub async fn list_rule_groups(Path(org_id): Path<String>) -> Response {
+    let files = match rules::list_files(&org_id).await {
+        Ok(v) => v,
+        Err(e) => {
+            log::error!("[PROM_RULES] listing rule files of org {org_id} failed: {e}");
+            return internal_error(e);
+        }
+    };
+    match serde_yaml_ng::to_string(&files) {
+        Ok(body) => (
+            StatusCode::OK,
+            [(http::header::CONTENT_TYPE, "application/yaml")],
+            body,
+        )
+            .into_response(),
+        Err(e) => internal_error(e),
+    }
+}
+
+/// Import a rule file
+
+#[utoipa::path(
+    post,
+    path = "/{org_id}/prometheus/config/v1/rules/{namespace}",
+    context_path = "/api",
+    tag = "Metrics",
+    operation_id = "PrometheusImportRuleGroups",
+    summary = "Import Prometheus rule groups",
+    description = "Imports a Prometheus rule file, or a single rule group, into a namespace. Groups replace existing groups of the same name in the namespace. Recording rules write their output as metrics; each alerting rule becomes a PromQL alert notifying the given destinations. The whole file is validated before anything is stored.",
+    security(
+        ("Authorization"= [])
+    ),
+    params(
+        ("org_id" = String, Path, description = "Organization name"),
+        ("namespace" = String, Path, description = "Namespace of the rule file"),
+        ("destination" = Option<String>, Query, description = "Comma separated alert destinations, required when the file has alerting rules"),
+        ("folder" = Option<String>, Query, description = "Alert folder id, `default` if not given"),
+    ),
+    request_body(content = String, description = "Prometheus rule file", content_type = "application/yaml"),
+    responses(
+        (status = 200, description = "Success", content_type = "application/json", body = Object, example = json!({
+            "status": "success",
+            "data": {
+                "groups": [{
+                    "namespace": "team-a",
+                    "name": "example",
+                    "recording_rules": 1,
+                    "alerting_rules": 1
+                }]
+            }
+        })),
+        (status = 400, description = "Invalid rule file", content_type = "application/json", body = ()),
+        (status = 500, description = "Failure", content_type = "application/json", body = ()),
+    ),
+    extensions(
+        ("x-o2-ratelimit" = json!({"module": "Metrics", "operation": "create"})),
+        ("x-o2-mcp" = json!({"enabled": false}))
+    )
+)]
+pub async fn import_rule_groups(
+    Path((org_id, namespace)): Path<(String, String)>,
+    Query(query): Query<HashMap<String, String>>,
+    Headers(user_email): Headers<UserEmail>,
+    body: Bytes,
+) -> Response {
+    let body = match std::str::from_utf8(&body) {
+        Ok(v) => v,
+        Err(e) => return bad_request(e),
+    };
+    let opts = ImportOptions {
+        destinations: query
+            .get("destination")
+            .map(|v| {
+                v.split(',')
+                    .map(|d| d.trim().to_string())
+                    .filter(|d| !d.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default(),
+        folder_id: query
+            .get("folder")
+            .filter(|v| !v.is_empty())
+            .cloned()
+            .unwrap_or_else(|| DEFAULT_FOLDER.to_string()),
+        user_id: user_email.user_id,
+    };
+    match rules::import(&org_id, &namespace, body, &opts).await {
+        Ok(resp) => (StatusCode::OK, axum::Json(ApiFuncResponse::ok(resp, None))).into_response(),
+        Err(e) => rules_error(&org_id, e),
+    }
+}
+
+/// Delete a namespace
+
+#[utoipa::path(
+    delete,
+    path = "/{org_id}/prometheus/config/v1/rules/{namespace}",
+    context_path = "/api",
+    tag = "Metrics",
+    operation_id = "PrometheusDeleteRuleNamespace",
+    summary = "Delete Prometheus rule namespace",
+    description = "Deletes all rule groups of a namespace together with the alerts of their alerting rules.",
+    security(
+        ("Authorization"= [])
+    ),
+    params(
+        ("org_id" = String, Path, description = "Organization name"),
+        ("namespace" = String, Path, description = "Namespace of the rule file"),
+    ),
+    responses(
+        (status = 200, description = "Success", content_type = "application/json", body = Object, example = json!({"status": "success", "data": null})),
+        (status = 404, description = "Not found", content_type = "application/json", body = ()),
+        (status = 500, description = "Failure", content_type = "application/json", body = ()),
+    ),
+    extensions(
+        ("x-o2-ratelimit" = json!({"module": "Metrics", "operation": "delete"})),
+        ("x-o2-mcp" = json!({"enabled": false}))
+    )
+)]
+pub async fn delete_namespace(Path((org_id, namespace)): Path<(String, String)>) -> Response {
+    match rules::delete(&org_id, &namespace, None).await {
+        Ok(()) => (StatusCode::OK, axum::Json(ApiFuncResponse::ok((), None))).into_response(),
+        Err(e) => rules_error(&org_id, e),
+    }
+}
+
+/// Delete a rule group
+
+#[utoipa::path(
+    delete,
+    path = "/{org_id}/prometheus/config/v1/rules/{namespace}/{group}",
+    context_path = "/api",
+    tag = "Metrics",
+    operation_id = "PrometheusDeleteRuleGroup",
+    summary = "Delete Prometheus rule group",
+    description = "Deletes one rule group of a namespace together with the alerts of its alerting rules.",
+    security(
+        ("Authorization"= [])
+    ),
+    params(
+        ("org_id" = String, Path, description = "Organization name"),
+        ("namespace" = String, Path, description = "Namespace of the rule file"),
+        ("group" = String, Path, description = "Rule group name"),
+    ),
+    responses(
+        (status = 200, description = "Success", content_type = "application/json", body = Object, example = json!({"status": "success", "data": null})),
+        (status = 404, description = "Not found", content_type = "application/json", body = ()),
+        (status = 500, description = "Failure", content_type = "application/json", body = ()),
+    ),
+    extensions(
+        ("x-o2-ratelimit" = json!({"module": "Metrics", "operation": "delete"})),
+        ("x-o2-mcp" = json!({"enabled": false}))
+    )
+)]
+pub async fn delete_rule_group(
+    Path((org_id, namespace, group)): Path<(String, String, String)>,
+) -> Response {
+    match rules::delete(&org_id, &namespace, Some(&group)).await {
+        Ok(()) => (StatusCode::OK, axum::Json(ApiFuncResponse::ok((), None))).into_response(),
+        Err(e) => rules_error(&org_id, e),
+    }
+}
+
+fn rules_error(org_id: &str, err: RulesError) -> Response {
+    match err {
+        RulesError::Invalid(_) | RulesError::DestinationMissing => bad_request(err),
+        RulesError::NotFound => (
+            StatusCode::NOT_FOUND,
+            axum::Json(ApiFuncResponse::<()>::Error {
+                error_type: ApiErrorType::NotFound,
+                error: err.to_string(),
+                trace_id: None,
+            }),
+        )
+            .into_response(),
+        RulesError::Storage(e) => {
+            log::error!("[PROM_RULES] updating rule groups of org {org_id} failed: {e}");
+            internal_error(e)
+        }
+    }
+}
+
+fn bad_request(err: impl ToString) -> Response {
+    (
+        StatusCode::BAD_REQUEST,
+        axum::Json(ApiFuncResponse::<()>::err_bad_data(err, None)),
+    )
+        .into_response()
+}
+
+fn internal_error(err: impl ToString) -> Response {
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        axum::Json(ApiFuncResponse::<()>::err_internal(err, None)),
+    )
+        .into_response()
+}
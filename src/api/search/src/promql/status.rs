@@ -0,0 +1,201 @@
+// Copyright 2026 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//! Cardinality statistics of metrics: the Prometheus TSDB status and the new
+//! series per hour of each metric, read from the metrics indexes.
+
+use axum::{
+    extract::{Path, Query},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use config::{
+    meta::promql::{
+        ApiFuncResponse,
+        status::{DEFAULT_STATUS_LIMIT, RequestSeriesChurn, RequestTsdbStatus},
+    },
+    utils::time::{now_micros, parse_str_to_timestamp_micros},
+};
+use openobserve_api_common::extractors::Headers;
+use openobserve_core::auth::UserEmail;
+use promql_service::status;
+
+/// Default range of the statistics, ending now.
+const DEFAULT_RANGE_MICROS: i64 = 24 * 3_600_000_000;
+
+/// Prometheus TSDB status
+
+// refer: https://prometheus.io/docs/prometheus/latest/querying/api/#tsdb-stats
+#[utoipa::path(
+    get,
+    path = "/{org_id}/prometheus/api/v1/status/tsdb",
+    context_path = "/api",
+    tag = "Metrics",
+    operation_id = "PrometheusTsdbStatus",
+    summary = "Get metrics cardinality statistics",
+    description = "Returns the metric names with the most series, the label names with the most values and the label pairs with the most series over the time range, last 24 hours by default. Statistics are read from the metrics index; files without an index are not counted and reported in `unindexedFiles`.",
+    security(
+        ("Authorization"= [])
+    ),
+    params(
+        ("org_id" = String, Path, description = "Organization name"),
+        ("limit" = Option<usize>, Query, description = "Number of entries of each list, 10 by default"),
+        ("start" = Option<String>, Query, description = "<rfc3339 | unix_timestamp>: Start timestamp"),
+        ("end" = Option<String>, Query, description = "<rfc3339 | unix_timestamp>: End timestamp"),
+    ),
+    responses(
+        (status = 200, description = "Success", content_type = "application/json", body = Object, example = json!({
+            "status": "success",
+            "data": {
+                "headStats": {"numSeries": 508, "numLabelPairs": 1234, "minTime": 1591516800000_i64, "maxTime": 1591603200000_i64},
+                "seriesCountByMetricName": [{"name": "net_conntrack_dialer_conn_failed_total", "value": 20}],
+                "labelValueCountByLabelName": [{"name": "__name__", "value": 211}],
+                "memoryInBytesByLabelName": [{"name": "__name__", "value": 8266}],
+                "seriesCountByLabelValuePair": [{"name": "job=prometheus", "value": 425}],
+                "unindexedFiles": 0
+            }
+        })),
+        (status = 400, description = "Bad request", content_type = "application/json", body = ()),
+        (status = 500, description = "Failure", content_type = "application/json", body = ()),
+    ),
+    extensions(
+        ("x-o2-ratelimit" = json!({"module": "Metrics", "operation": "get"})),
+        ("x-o2-mcp" = json!({"description": "Get metrics cardinality statistics", "category": "metrics"}))
+    )
+)]
+pub async fn tsdb_status(
+    Path(org_id): Path<String>,
+    Query(req): Query<RequestTsdbStatus>,
+    Headers(_user_email): Headers<UserEmail>,
+) -> Response {
+    #[cfg(feature = "enterprise")]
+    if let Some(resp) =
+        crate::promql::check_metrics_permission(&org_id, &_user_email.user_id, None).await
+    {
+        return resp;
+    }
+
+    let (start, end) = match time_range(req.start, req.end) {
+        Ok(v) => v,
+        Err(e) => return bad_request(e),
+    };
+    let limit = req.limit.unwrap_or(DEFAULT_STATUS_LIMIT);
+    let trace_id = config::ider::generate_trace_id();
+    match status::tsdb_status(&trace_id, &org_id, start, end, limit).await {
+        Ok(resp) => (StatusCode::OK, axum::Json(ApiFuncResponse::ok(resp, None))).into_response(),
+        Err(e) => {
+            log::error!("[trace_id {trace_id}] tsdb status of org {org_id} failed: {e}");
+            internal_error(e)
+        }
+    }
+}
+
+/// Series churn
+
+#[utoipa::path(
+    get,
+    path = "/{org_id}/prometheus/api/v1/status/series_churn",
+    context_path = "/api",
+    tag = "Metrics",
+    operation_id = "PrometheusSeriesChurn",
+    summary = "Get new series per hour by metric",
+    description = "Returns the metrics with the most new series over the time range, last 24 hours by default, with the new and distinct series of each hour. Series of the first hour all count as new. Statistics are read from the metrics index; files without an index are not counted.",
+    security(
+        ("Authorization"= [])
+    ),
+    params(
+        ("org_id" = String, Path, description = "Organization name"),
+        ("metric" = Option<String>, Query, description = "Only return the churn of this metric"),
+        ("limit" = Option<usize>, Query, description = "Number of metrics to return, 10 by default"),
+        ("start" = Option<String>, Query, description = "<rfc3339 | unix_timestamp>: Start timestamp"),
+        ("end" = Option<String>, Query, description = "<rfc3339 | unix_timestamp>: End timestamp"),
+    ),
+    responses(
+        (status = 200, description = "Success", content_type = "application/json", body = Object, example = json!({
+            "status": "success",
+            "data": [{
+                "metric": "http_requests_total",
+                "new_series": 1200,
+                "hours": [
+                    {"time": 1591516800, "new_series": 1000, "series": 1000},
+                    {"time": 1591520400, "new_series": 200, "series": 1050}
+                ]
+            }]
+        })),
+        (status = 400, description = "Bad request", content_type = "application/json", body = ()),
+        (status = 500, description = "Failure", content_type = "application/json", body = ()),
+    ),
+    extensions(
+        ("x-o2-ratelimit" = json!({"module": "Metrics", "operation": "get"})),
+        ("x-o2-mcp" = json!({"description": "Get new series per hour by metric", "category": "metrics"}))
+    )
+)]
+pub async fn series_churn(
+    Path(org_id): Path<String>,
+    Query(req): Query<RequestSeriesChurn>,
+    Headers(_user_email): Headers<UserEmail>,
+) -> Response {
+    let (start, end) = match time_range(req.start, req.end) {
+        Ok(v) => v,
+        Err(e) => return bad_request(e),
+    };
+    let limit = req.limit.unwrap_or(DEFAULT_STATUS_LIMIT);
+    let metric = req.metric.filter(|metric| !metric.is_empty());
+    #[cfg(feature = "enterprise")]
+    if let Some(resp) =
+        crate::promql::check_metrics_permission(&org_id, &_user_email.user_id, metric.as_deref())
+            .await
+    {
+        return resp;
+    }
+    let trace_id = config::ider::generate_trace_id();
+    match status::series_churn(&trace_id, &org_id, metric.as_deref(), start, end, limit).await {
+        Ok(resp) => (StatusCode::OK, axum::Json(ApiFuncResponse::ok(resp, None))).into_response(),
+        Err(e) => {
+            log::error!("[trace_id {trace_id}] series churn of org {org_id} failed: {e}");
+            internal_error(e)
+        }
+    }
+}
+
+fn time_range(start: Option<String>, end: Option<String>) -> Result<(i64, i64), String> {
+    let end = match end.filter(|end| !end.is_empty()) {
+        Some(end) => parse_str_to_timestamp_micros(&end).map_err(|e| e.to_string())?,
+        None => now_micros(),
+    };
+    let start = match start.filter(|start| !start.is_empty()) {
+        Some(start) => parse_str_to_timestamp_micros(&start).map_err(|e| e.to_string())?,
+        None => end - DEFAULT_RANGE_MICROS,
+    };
+    if start > end {
+        return Err("end timestamp must not be before start time".to_string());
+    }
+    Ok((start, end))
+}
+
+fn bad_request(err: impl ToString) -> Response {
+    (
+        StatusCode::BAD_REQUEST,
+        axum::Json(ApiFuncResponse::<()>::err_bad_data(err, None)),
+    )
+        .into_response()
+}
+
+fn internal_error(err: impl ToString) -> Response {
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        axum::Json(ApiFuncResponse::<()>::err_internal(err, None)),
+    )
+        .into_response()
+}
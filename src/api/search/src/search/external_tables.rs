@@ -0,0 +1,181 @@
+// Copyright 2026 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use axum::{Json, extract::Path, response::Response};
+use config::meta::external_table::ExternalTable;
+use infra::schema::external_table;
+use search::datafusion::table_provider::external_table::infer_schema;
+
+use crate::common::meta::http::HttpResponse as MetaHttpResponse;
+
+/// ListExternalTables - Retrieve the external tables of the org.
+
+#[utoipa::path(
+    get,
+    path = "/{org_id}/external_tables",
+    context_path = "/api",
+    tag = "External Tables",
+    operation_id = "ListExternalTables",
+    summary = "List external tables",
+    description = "Retrieves the external tables of the organization. An external table reads Parquet files or an Iceberg table from the object storage and can be queried as `external.\"name\"` in SQL.",
+    security(
+        ("Authorization"= [])
+    ),
+    params(
+        ("org_id" = String, Path, description = "Organization name"),
+    ),
+    responses(
+        (status = 200, description = "Success", content_type = "application/json", body = Vec<ExternalTable>),
+        (status = 400, description = "Failure", content_type = "application/json", body = ()),
+    ),
+    extensions(
+        ("x-o2-ratelimit" = json!({"module": "External Tables", "operation": "list"})),
+        ("x-o2-mcp" = json!({"description": "List external tables", "category": "search"}))
+    )
+)]
+pub async fn list_tables(Path(org_id): Path<String>) -> Response {
+    match external_table::list(&org_id).await {
+        Ok(tables) => MetaHttpResponse::json(tables),
+        Err(e) => MetaHttpResponse::bad_request(e),
+    }
+}
+
+/// GetExternalTable - Retrieve an external table with its schema.
+
+#[utoipa::path(
+    get,
+    path = "/{org_id}/external_tables/{name}",
+    context_path = "/api",
+    tag = "External Tables",
+    operation_id = "GetExternalTable",
+    summary = "Get external table",
+    description = "Retrieves an external table with the schema inferred when it was registered.",
+    security(
+        ("Authorization"= [])
+    ),
+    params(
+        ("org_id" = String, Path, description = "Organization name"),
+        ("name" = String, Path, description = "External table name"),
+    ),
+    responses(
+        (status = 200, description = "Success", content_type = "application/json", body = ExternalTable),
+        (status = 404, description = "NotFound", content_type = "application/json", body = ()),
+    ),
+    extensions(
+        ("x-o2-ratelimit" = json!({"module": "External Tables", "operation": "get"})),
+        ("x-o2-mcp" = json!({"description": "Get external table details", "category": "search"}))
+    )
+)]
+pub async fn get_table(Path((org_id, name)): Path<(String, String)>) -> Response {
+    match external_table::get(&org_id, &name).await {
+        Ok(Some(table)) => MetaHttpResponse::json(table),
+        Ok(None) => MetaHttpResponse::not_found(format!("External table {name} not found")),
+        Err(e) => MetaHttpResponse::bad_request(e),
+    }
+}
+
+/// CreateExternalTable - Register an external table.
+
+#[utoipa::path(
+    post,
+    path = "/{org_id}/external_tables",
+    context_path = "/api",
+    tag = "External Tables",
+    operation_id = "CreateExternalTable",
+    summary = "Create external table",
+    description = "Registers the Parquet files under a prefix, or an Iceberg table, as an external table. The location must be in the bucket of the storage account and under one of the prefixes of ZO_S3_EXTERNAL_TABLE_PREFIXES. The schema is inferred from the first data file, registering an existing name again refreshes the schema.",
+    security(
+        ("Authorization"= [])
+    ),
+    params(
+        ("org_id" = String, Path, description = "Organization name"),
+    ),
+    request_body(content = ExternalTable, description = "External table", content_type = "application/json", example = json!({
+        "name": "orders",
+        "format": "iceberg",
+        "location": "external/default/sales/orders"
+    })),
+    responses(
+        (status = 200, description = "Success", content_type = "application/json", body = ExternalTable),
+        (status = 400, description = "Failure", content_type = "application/json", body = ()),
+    ),
+    extensions(
+        ("x-o2-ratelimit" = json!({"module": "External Tables", "operation": "create"})),
+        ("x-o2-mcp" = json!({"description": "Create an external table", "category": "search"}))
+    )
+)]
+pub async fn create_table(
+    Path(org_id): Path<String>,
+    Json(mut table): Json<ExternalTable>,
+) -> Response {
+    if let Err(e) = table.validate(&org_id) {
+        return MetaHttpResponse::bad_request(e);
+    }
+    let schema = match infer_schema(&table).await {
+        Ok(schema) => schema,
+        Err(e) => {
+            return MetaHttpResponse::bad_request(format!(
+                "Failed to read the schema of {}: {e}",
+                table.location
+            ));
+        }
+    };
+    table.schema = Some(schema.with_metadata(Default::default()));
+    table.created_at = config::utils::time::now_micros();
+    match db::external_table::set(&org_id, &table).await {
+        Ok(_) => MetaHttpResponse::json(table),
+        Err(e) => MetaHttpResponse::bad_request(e),
+    }
+}
+
+/// DeleteExternalTable - Remove an external table, the files are not deleted.
+
+#[utoipa::path(
+    delete,
+    path = "/{org_id}/external_tables/{name}",
+    context_path = "/api",
+    tag = "External Tables",
+    operation_id = "DeleteExternalTable",
+    summary = "Delete external table",
+    description = "Removes the external table from the organization. The files in the object storage are not deleted.",
+    security(
+        ("Authorization"= [])
+    ),
+    params(
+        ("org_id" = String, Path, description = "Organization name"),
+        ("name" = String, Path, description = "External table name"),
+    ),
+    responses(
+        (status = 200, description = "Success", content_type = "application/json", body = ()),
+        (status = 404, description = "NotFound", content_type = "application/json", body = ()),
+    ),
+    extensions(
+        ("x-o2-ratelimit" = json!({"module": "External Tables", "operation": "delete"})),
+        ("x-o2-mcp" = json!({"description": "Delete an external table", "category": "search", "requires_confirmation": true}))
+    )
+)]
+pub async fn delete_table(Path((org_id, name)): Path<(String, String)>) -> Response {
+    match external_table::get(&org_id, &name).await {
+        Ok(Some(_)) => {}
+        Ok(None) => {
+            return MetaHttpResponse::not_found(format!("External table {name} not found"));
+        }
+        Err(e) => return MetaHttpResponse::bad_request(e),
+    }
+    match db::external_table::delete(&org_id, &name).await {
+        Ok(_) => MetaHttpResponse::ok("External table deleted"),
+        Err(e) => MetaHttpResponse::bad_request(e),
+    }
+}
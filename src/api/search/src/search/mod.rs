@@ -27,7 +27,7 @@ use config::{
     META_ORG_ID, TIMESTAMP_COL_NAME, get_config,
     meta::{
         search::{
-            AgentSearchMode, Request, ResultSchemaResponse, SearchEventType,
+            AgentSearchMode, ExportFormat, Request, ResultSchemaResponse, SearchEventType,
             SearchHistoryHitResponse, SearchHistoryRequest, SearchPartitionRequest,
             default_use_cache,
         },
@@ -73,6 +73,7 @@ use crate::common::{
 pub mod agent_format;
 pub(crate) mod around;
 pub mod error_utils;
+pub mod external_tables;
 pub mod multi_streams;
 pub mod patterns;
 pub mod query_functions;
@@ -82,6 +83,7 @@ pub mod saved_view;
 pub mod search_inspector;
 pub mod search_job;
 pub mod search_stream;
+pub mod tail;
 pub mod utils;
 
 /// SearchStreamData
@@ -103,6 +105,7 @@ pub mod utils;
         ("is_ui_histogram" = Option<bool>, Query, description = "Whether to return histogram data for UI (default: false)"),
         ("is_multi_stream_search" = Option<bool>, Query, description = "Indicate is search is for multi stream (default: false)"),
         ("validate" = Option<bool>, Query, description = "Validate query fields against stream schema and User-Defined Schema (UDS). When enabled, returns error if queried fields are not in schema or not allowed by UDS (default: false)"),
+        ("format" = Option<String>, Query, description = "Stream the result as a file instead of a JSON response. one of: parquet, arrow (Arrow IPC stream), ndjson_gz. Rows are capped by `size` like a regular search, set `size` to -1 to export the full time range; VRL functions are not supported"),
     ),
     request_body(content = inline(Request), description = "Search query", content_type = "application/json", example = json!({
         "query": {
@@ -401,6 +404,28 @@ pub async fn search(
         }
     }
 
+    // export the result as a file, encoded straight from the record batches
+    if let Some(format) = url_query.get("format") {
+        let format = match ExportFormat::try_from(format.as_str()) {
+            Ok(v) => v,
+            Err(e) => return MetaHttpResponse::bad_request(e),
+        };
+        return match SearchService::export::search_export(
+            &trace_id,
+            &org_id,
+            stream_type,
+            Some(user_id.to_string()),
+            &req,
+            format,
+        )
+        .instrument(http_span)
+        .await
+        {
+            Ok(rx) => utils::export_response(rx, format, &trace_id),
+            Err(e) => map_error_to_http_response(&e, Some(trace_id)),
+        };
+    }
+
     // run search with cache; `agent_options.mode = partition` instead drives
     // the partitioned streaming pipeline (per-partition early termination,
     // streaming-aggs cache) and collects it into a single response
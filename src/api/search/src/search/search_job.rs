@@ -34,13 +34,13 @@ use {
     },
     crate::search::{
         query_manager::cancel_query_inner,
-        utils::{StreamPermissionResourceType, check_stream_permissions},
+        utils::{StreamPermissionResourceType, check_stream_permissions, export_response},
     },
     axum::http::HeaderMap,
     config::{
         get_config,
         meta::{
-            search::{Response as SearchResponse, SearchEventType},
+            search::{ExportFormat, Response as SearchResponse, SearchEventType},
             sql::resolve_stream_names,
             stream::StreamType,
         },
@@ -49,7 +49,7 @@ use {
     db::search_job::{search_job_partitions::*, search_jobs::*},
     hashbrown::HashMap,
     infra::table::entity::search_jobs::Model as JobModel,
-    search_service::search_jobs::{get_result, merge_response},
+    search_service::search_jobs::{export_result, get_result, merge_response},
     tracing::Span,
 };
 
@@ -443,7 +443,8 @@ pub async fn cancel_job(
         ("org_id" = String, Path, description = "Organization name"),
         ("job_id" = String, Path, description = "Search job ID"),
         ("from" = Option<i64>, Query, description = "Pagination start offset"),
-        ("size" = Option<i64>, Query, description = "Number of results to return")
+        ("size" = Option<i64>, Query, description = "Number of results to return"),
+        ("format" = Option<String>, Query, description = "Download the whole result as a file instead of a page of hits. one of: parquet, arrow (Arrow IPC stream), ndjson_gz")
     ),
     responses(
         (status = 200, description = "Search job results", body = Object, example = json!({
@@ -488,6 +489,8 @@ pub async fn get_job_result(
 
         if let Some(msg) = model.error_message {
             MetaHttpResponse::internal_error(format!("job_id: {job_id} error: {msg}",))
+        } else if let Some(format) = req.format {
+            export_job_result(&model, format).await
         } else if model.status == 1 && model.partition_num != Some(1) {
             get_partition_result(&model, from, size).await
         } else if model.result_path.is_none() || model.cluster.is_none() {
@@ -711,6 +714,43 @@ async fn get_partition_result(job: &JobModel, from: i64, size: i64) -> Response
     let response = response.unwrap();
     apply_pagination(response, from, size)
 }
+// stream all the stored partition results of the job as one file
+#[cfg(feature = "enterprise")]
+async fn export_job_result(job: &JobModel, format: ExportFormat) -> Response {
+    let (results, offset, limit) = if job.status == 1 && job.partition_num != Some(1) {
+        let req: Request = match json::from_str(&job.payload) {
+            Ok(v) => v,
+            Err(e) => return MetaHttpResponse::internal_error(e),
+        };
+        let limit = if req.query.size > 0 {
+            req.query.size
+        } else {
+            get_config().limit.query_default_limit
+        };
+        let partition_jobs = match get_partition_jobs(&job.id).await {
+            Ok(v) => v,
+            Err(e) => return MetaHttpResponse::internal_error(e),
+        };
+        let results = partition_jobs
+            .into_iter()
+            .filter_map(|p| Some((p.result_path?, p.cluster?)))
+            .collect::<Vec<_>>();
+        (results, req.query.from, Some(limit))
+    } else {
+        match (job.result_path.clone(), job.cluster.clone()) {
+            (Some(path), Some(cluster)) => (vec![(path, cluster)], 0, None),
+            _ => {
+                return MetaHttpResponse::not_found(format!(
+                    "[Job_Id: {}] don't have result_path or cluster",
+                    job.id
+                ));
+            }
+        }
+    };
+    let rx = export_result(results, offset, limit, format);
+    export_response(rx, format, &job.id)
+}
+
 #[cfg(feature = "enterprise")]
 fn apply_pagination(response: SearchResponse, from: i64, size: i64) -> Response {
     let mut res = response;
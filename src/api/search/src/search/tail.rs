@@ -0,0 +1,152 @@
+// Copyright 2026 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use axum::{
+    body::Bytes,
+    extract::{Path, Query},
+    http::HeaderMap,
+    response::Response,
+};
+use config::{get_config, utils::json};
+use futures::stream::StreamExt;
+use hashbrown::HashMap;
+use openobserve_api_common::extractors::Headers;
+use openobserve_core::auth::UserEmail;
+use search_service::tail::TailEvent;
+use tracing::Span;
+
+#[cfg(feature = "enterprise")]
+use crate::search::utils::{StreamPermissionResourceType, check_stream_permissions};
+use crate::{
+    common::{
+        meta::http::HttpResponse as MetaHttpResponse,
+        utils::http::{get_or_create_trace_id, get_stream_type_from_request},
+    },
+    search::error_utils::map_error_to_http_response,
+};
+
+/// LiveTail
+
+#[utoipa::path(
+    get,
+    path = "/{org_id}/{stream_name}/_tail",
+    context_path = "/api",
+    tag = "Search",
+    operation_id = "LiveTail",
+    summary = "Live tail a stream",
+    description = "Streams records as they are written on the ingesters, as server-sent events. Records can be narrowed with a SQL WHERE clause. Matching records are sent as `tail_hits` events; records dropped by the per-client rate limit, or skipped because the client fell behind, are reported in `tail_dropped` events. The stream ends when the client disconnects or the maximum tail duration is reached.",
+    security(
+        ("Authorization"= [])
+    ),
+    params(
+        ("org_id" = String, Path, description = "Organization name"),
+        ("stream_name" = String, Path, description = "Stream name"),
+        ("type" = Option<String>, Query, description = "Stream type. Must be one of: logs, metrics, traces. Defaults to logs if not specified."),
+        ("filter" = Option<String>, Query, description = "SQL WHERE clause, e.g. `level = 'error' AND code >= 500`"),
+    ),
+    responses(
+        (status = 200, description = "Success", content_type = "text/event-stream"),
+        (status = 400, description = "Failure", content_type = "application/json", body = ()),
+    ),
+    extensions(
+        ("x-o2-ratelimit" = json!({"module": "Search", "operation": "get"})),
+        ("x-o2-mcp" = json!({"enabled": false}))
+    )
+)]
+pub async fn live_tail(
+    Path((org_id, stream_name)): Path<(String, String)>,
+    Query(query): Query<HashMap<String, String>>,
+    Headers(user_email): Headers<UserEmail>,
+    headers: HeaderMap,
+) -> Response {
+    let cfg = get_config();
+    if !cfg.http_streaming.live_tail_enabled {
+        return MetaHttpResponse::bad_request("live tail is disabled");
+    }
+
+    let http_span = if cfg.common.should_create_span() {
+        tracing::info_span!(
+            "/api/{org_id}/{stream_name}/_tail",
+            org_id = org_id.clone(),
+            stream_name = stream_name.clone()
+        )
+    } else {
+        Span::none()
+    };
+    let trace_id = get_or_create_trace_id(&headers, &http_span);
+
+    let stream_type = get_stream_type_from_request(&query).unwrap_or_default();
+    let filter = query.get("filter").map(|f| f.as_str()).unwrap_or_default();
+
+    #[cfg(feature = "enterprise")]
+    if let Some(res) = check_stream_permissions(
+        &stream_name,
+        &org_id,
+        &user_email.user_id,
+        &stream_type,
+        StreamPermissionResourceType::Search,
+    )
+    .await
+    {
+        return res;
+    }
+    #[cfg(not(feature = "enterprise"))]
+    let _ = user_email;
+
+    let rx = match search_service::tail::tail(&trace_id, &org_id, stream_type, &stream_name, filter)
+        .await
+    {
+        Ok(rx) => rx,
+        Err(e) => {
+            log::error!("[trace_id {trace_id}] live tail error: {e}");
+            return map_error_to_http_response(&e, Some(trace_id));
+        }
+    };
+
+    let stream = tokio_stream::wrappers::ReceiverStream::new(rx)
+        .map(|event| Ok::<_, std::io::Error>(Bytes::from(format_event(&event))));
+
+    axum::response::Response::builder()
+        .header("content-type", "text/event-stream")
+        .body(axum::body::Body::from_stream(stream))
+        .unwrap()
+}
+
+fn format_event(event: &TailEvent) -> String {
+    let (name, data) = match event {
+        TailEvent::Hits(hits) => ("tail_hits", json::json!({ "hits": hits })),
+        TailEvent::Dropped(n) => ("tail_dropped", json::json!({ "dropped": n })),
+        TailEvent::Error(message) => ("error", json::json!({ "message": message })),
+    };
+    format!("event: {name}\ndata: {data}\n\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_event() {
+        let hits = TailEvent::Hits(vec![json::json!({"a": 1})]);
+        assert_eq!(
+            format_event(&hits),
+            "event: tail_hits\ndata: {\"hits\":[{\"a\":1}]}\n\n"
+        );
+        assert_eq!(
+            format_event(&TailEvent::Dropped(3)),
+            "event: tail_dropped\ndata: {\"dropped\":3}\n\n"
+        );
+    }
+}
@@ -13,16 +13,44 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
+use axum::{body::Body, http::header, response::Response};
+use bytes::Bytes;
 use config::{
     ALL_VALUES_COL_NAME, ID_COL_NAME, INDEX_FIELD_NAME_FOR_ALL, ORIGINAL_DATA_COL_NAME,
     TIMESTAMP_COL_NAME,
-    meta::{sql::TableReferenceExt, stream::StreamType},
+    meta::{search::ExportFormat, sql::TableReferenceExt, stream::StreamType},
 };
+use futures::StreamExt;
 use hashbrown::HashMap;
 use infra::errors::{Error, ErrorCodes};
 #[cfg(feature = "enterprise")]
 pub use openobserve_core::authz::{StreamPermissionResourceType, check_stream_permissions};
 use search::sql::Sql;
+use tokio::sync::mpsc;
+
+/// Builds the streaming file response of a search export, `file_name` is used
+/// without extension for the attachment name.
+pub fn export_response(
+    rx: mpsc::Receiver<Result<Bytes, Error>>,
+    format: ExportFormat,
+    file_name: &str,
+) -> Response {
+    // an error in the middle of the stream aborts the body, the client sees a
+    // truncated file instead of a silently incomplete one
+    let stream =
+        tokio_stream::wrappers::ReceiverStream::new(rx).map(|r| r.map_err(std::io::Error::other));
+    Response::builder()
+        .header(header::CONTENT_TYPE, format.content_type())
+        .header(
+            header::CONTENT_DISPOSITION,
+            format!(
+                "attachment; filename=\"{file_name}.{}\"",
+                format.file_extension()
+            ),
+        )
+        .body(Body::from_stream(stream))
+        .unwrap()
+}
 
 // ============================================================================
 // Query Validation Helpers
@@ -92,6 +92,14 @@ pub async fn run_retention() -> Result<(), anyhow::Error> {
             log::error!(
                 "[COMPACTOR] delete: delete [{org_id}/{stream_type}/{stream_name}] error: {e}"
             );
+        } else if stream_type == StreamType::Metrics && get_config().common.result_cache_enabled {
+            // PromQL results cached before the deletion may still carry the deleted samples
+            let path = format!("{org_id}/{stream_type}/{stream_name}");
+            if !promql_service::search::delete_cached_results(path, 0).await {
+                log::warn!(
+                    "[COMPACTOR] delete: failed to delete result cache of [{org_id}/{stream_type}/{stream_name}]"
+                );
+            }
         }
     }
 
@@ -19,7 +19,7 @@ use ::datafusion::{arrow::datatypes::Schema, error::DataFusionError};
 use bytes::Bytes;
 use config::{
     FileFormat, get_config, ider, is_local_disk_storage,
-    meta::stream::{FileKey, FileMeta, StorageType, StreamType},
+    meta::stream::{FileKey, FileMeta, FtsTokenizer, StorageType, StreamType, VectorField},
     metrics,
     utils::{parquet::read_schema_from_bytes, schema_ext::SchemaExt},
 };
@@ -30,7 +30,8 @@ use infra::{
     runtime::DATAFUSION_RUNTIME,
     schema::{
         SchemaCache, get_stream_setting_bloom_filter_fields, get_stream_setting_fts_fields,
-        get_stream_setting_index_fields,
+        get_stream_setting_fts_tokenizer, get_stream_setting_index_fields,
+        get_stream_setting_range_index_fields, get_stream_setting_vector_fields,
     },
     storage,
 };
@@ -39,6 +40,7 @@ use schema::generate_schema_for_defined_schema_fields;
 use search::datafusion::{
     exec::TableBuilder,
     merge::{self, MergeMode, MergeOutput, MergeParquetResult, MergedFile},
+    sort_order::clustered_file_name,
 };
 use tantivy_utils::index_builder::create_tantivy_index;
 use tokio::sync::Semaphore;
@@ -149,7 +151,10 @@ pub async fn merge_files(
     let stream_settings = infra::schema::unwrap_stream_settings(&latest_schema);
     let bloom_filter_fields = get_stream_setting_bloom_filter_fields(&stream_settings);
     let full_text_search_fields = get_stream_setting_fts_fields(&stream_settings);
+    let fts_tokenizer = get_stream_setting_fts_tokenizer(&stream_settings);
     let index_fields = get_stream_setting_index_fields(&stream_settings);
+    let range_index_fields = get_stream_setting_range_index_fields(&stream_settings);
+    let vector_fields = get_stream_setting_vector_fields(&stream_settings);
     let (defined_schema_fields, need_original, index_original_data, index_all_values, storage_type) =
         match stream_settings {
             Some(s) => (
@@ -276,6 +281,8 @@ pub async fn merge_files(
     let need_index = full_text_search_fields
         .iter()
         .chain(index_fields.iter())
+        .chain(range_index_fields.iter())
+        .chain(vector_fields.iter().map(|f| &f.name))
         .any(|f| latest_schema_fields.contains(f));
     if !need_index {
         log::debug!("skip index generation for stream: {org_id}/{stream_type}/{stream_name}");
@@ -307,7 +314,11 @@ pub async fn merge_files(
         }
 
         let id = ider::generate_file_name();
-        let new_file_key = format!("{prefix}/{}", layout.file_name(&id, file_format));
+        let mut file_name = layout.file_name(&id, file_format);
+        if mode.is_clustered() {
+            file_name = clustered_file_name(&file_name);
+        }
+        let new_file_key = format!("{prefix}/{file_name}");
 
         // upload file to storage
         let buf = Bytes::from(buf);
@@ -353,7 +364,10 @@ pub async fn merge_files(
                 org_id,
                 &new_file_key,
                 &full_text_search_fields,
+                &fts_tokenizer,
                 &index_fields,
+                &range_index_fields,
+                &vector_fields,
                 &retain_file_list,
                 &mut new_file_meta,
                 latest_schema.clone(),
@@ -384,7 +398,10 @@ async fn generate_inverted_index(
     org_id: &str,
     new_file_key: &str,
     fts_fields: &[String],
+    fts_tokenizer: &FtsTokenizer,
     index_fields: &[String],
+    range_index_fields: &[String],
+    vector_fields: &[VectorField],
     retain_file_list: &[FileKey],
     new_file_meta: &mut FileMeta,
     latest_schema: Arc<Schema>,
@@ -395,7 +412,10 @@ async fn generate_inverted_index(
         org_id,
         new_file_key,
         fts_fields,
+        fts_tokenizer,
         index_fields,
+        range_index_fields,
+        vector_fields,
         latest_schema, // Use stream schema to include all configured fields
         buf,
     )
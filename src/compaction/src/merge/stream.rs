@@ -22,7 +22,10 @@ use config::{
     utils::time::hour_micros,
 };
 use hashbrown::{HashMap, HashSet};
-use infra::{file_list as infra_file_list, schema::get_partition_time_level};
+use infra::{
+    file_list as infra_file_list,
+    schema::{get_partition_time_level, get_stream_setting_sort_keys, unwrap_stream_settings},
+};
 use metrics_index::MetricsFileLayout;
 use search::datafusion::merge::MergeMode;
 use search_service::file_list;
@@ -98,13 +101,15 @@ pub async fn merge_by_stream(
     // the merge only read it — from the end of the range, so a whole-hour
     // mode applies to the hour as a unit.
     let range_end_ts = job_range_end(offset, partition_time_level);
+    let sort_keys = get_stream_setting_sort_keys(&unwrap_stream_settings(&schema));
     let mode = MergeMode::for_compactor(
         stream_type,
         stream_name,
         &schema,
         range_end_ts,
         !is_incremental,
-    );
+    )
+    .with_sort_keys(&sort_keys, &schema);
     // a whole-hour merge needs every file of the hour, even those already
     // above the size target that a normal merge would leave alone
     let max_original_size = if mode.merges_whole_batch() {
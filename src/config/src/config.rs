@@ -1107,6 +1107,30 @@ pub struct HttpStreaming {
         help = "Enable streaming"
     )]
     pub streaming_enabled: bool,
+    #[env_config(
+        name = "ZO_LIVE_TAIL_ENABLED",
+        default = true,
+        help = "Enable the live tail endpoint"
+    )]
+    pub live_tail_enabled: bool,
+    #[env_config(
+        name = "ZO_LIVE_TAIL_MAX_RECORDS_PER_SEC",
+        default = 1000,
+        help = "Maximum records per second pushed to a single live tail client, excess records are dropped"
+    )]
+    pub live_tail_max_records_per_sec: usize,
+    #[env_config(
+        name = "ZO_LIVE_TAIL_MAX_DURATION",
+        default = 3600,
+        help = "Maximum duration in seconds of a live tail session"
+    )]
+    pub live_tail_max_duration: u64,
+    #[env_config(
+        name = "ZO_LIVE_TAIL_CHANNEL_SIZE",
+        default = 1024,
+        help = "Number of written batches buffered per stream for live tail subscribers on an ingester"
+    )]
+    pub live_tail_channel_size: usize,
 }
 
 #[derive(Serialize, EnvConfig, Default)]
@@ -1519,6 +1543,54 @@ pub struct Search {
         help = "Maximum aggregate TopK limit that uses the heap implementation"
     )]
     pub aggregation_topk_heap_max_limit: u64,
+    #[env_config(
+        name = "ZO_SEARCH_FAIR_SCHEDULER_ENABLED",
+        default = false,
+        help = "Admit searches on the coordinating node by weighted fair share across orgs and users, before the work group queue"
+    )]
+    pub fair_scheduler_enabled: bool,
+    #[env_config(
+        name = "ZO_SEARCH_FAIR_SCHEDULER_MAX_CONCURRENT",
+        default = 0,
+        help = "Searches the fair scheduler runs at the same time on one node, default is cpu_num"
+    )]
+    pub fair_scheduler_max_concurrent: usize,
+    #[env_config(
+        name = "ZO_SEARCH_FAIR_SCHEDULER_USER_MAX_CONCURRENT",
+        default = 0,
+        help = "Searches one user can run at the same time, 0 is unlimited"
+    )]
+    pub fair_scheduler_user_max_concurrent: usize,
+    #[env_config(
+        name = "ZO_SEARCH_FAIR_SCHEDULER_ORG_WEIGHTS",
+        default = "",
+        help = "Share of each org, as org_id=weight pairs separated by comma, orgs not listed have weight 1"
+    )]
+    pub fair_scheduler_org_weights: String,
+    #[env_config(
+        name = "ZO_SEARCH_FAIR_SCHEDULER_ROLE_WEIGHTS",
+        default = "",
+        help = "Multiplier on the org weight by user role, as role=weight pairs separated by comma, e.g. admin=2,viewer=1"
+    )]
+    pub fair_scheduler_role_weights: String,
+    #[env_config(
+        name = "ZO_SEARCH_FAIR_SCHEDULER_QUEUE_SLO",
+        default = "alerts=5,ui=30,dashboards=30,download=300",
+        help = "Queue time SLO in seconds by search event type, as type=seconds pairs separated by comma. Searches waiting past their SLO are admitted first"
+    )]
+    pub fair_scheduler_queue_slo: String,
+    #[env_config(
+        name = "ZO_SEARCH_FAIR_SCHEDULER_PREEMPT_POLICY",
+        default = "none",
+        help = "What to do with long running background searches when a higher priority search misses its queue SLO: none, release (stop counting it against the slots) or cancel"
+    )]
+    pub fair_scheduler_preempt_policy: String,
+    #[env_config(
+        name = "ZO_SEARCH_FAIR_SCHEDULER_PREEMPT_MIN_RUNTIME",
+        default = 60,
+        help = "Seconds a background search must have been running before it can be preempted"
+    )]
+    pub fair_scheduler_preempt_min_runtime: u64,
 }
 
 #[derive(Serialize, EnvConfig, Default)]
@@ -2240,10 +2312,30 @@ pub struct Limit {
     pub metrics_max_series_response: usize,
     #[env_config(name = "ZO_METRICS_CACHE_MAX_ENTRIES", default = 10000)]
     pub metrics_cache_max_entries: usize,
+    // Seconds at the end of a cached PromQL range result that are evaluated again on a
+    // cache hit, so samples which arrived late are picked up.
+    #[env_config(name = "ZO_METRICS_CACHE_LOOKBACK_SECS", default = 60)]
+    pub metrics_cache_lookback_secs: i64,
+    // How often ingesters push the metrics streams that received backfilled samples
+    // to the queriers for result cache invalidation. 0 disables it.
+    #[env_config(name = "ZO_METRICS_CACHE_INVALIDATION_INTERVAL", default = 30)] // seconds
+    pub metrics_cache_invalidation_interval: i64,
+    // Number of series hash shards an aggregating PromQL query is split into across
+    // the queriers. 0 or 1 disables sharding, the range is split by time instead.
+    #[env_config(name = "ZO_METRICS_QUERY_SHARDS", default = 0)]
+    pub metrics_query_shards: usize,
     // Memory budget in MB for the PromQL series label cache. 0 (default)
     // means auto: 5% of total memory, clamped to [100, 1024] MB.
     #[env_config(name = "ZO_METRICS_LABEL_CACHE_MAX_SIZE", default = 0)]
     pub metrics_label_cache_max_size: usize,
+    // How long a series counts as active for the ingest-time series limits
+    // after its last sample, in seconds.
+    #[env_config(name = "ZO_METRICS_ACTIVE_SERIES_WINDOW", default = 3600)]
+    pub metrics_active_series_window: i64,
+    // How long an ingester uses the ingest rules of an org before reading them
+    // again, in seconds.
+    #[env_config(name = "ZO_METRICS_INGEST_RULES_CACHE_TTL", default = 30)]
+    pub metrics_ingest_rules_cache_ttl: i64,
     #[env_config(name = "ZO_COLS_PER_RECORD_LIMIT", default = 1000)]
     pub req_cols_per_record_limit: usize,
     #[env_config(name = "ZO_NODE_HEARTBEAT_TTL", default = 30)] // seconds
@@ -2338,6 +2430,24 @@ pub struct Limit {
         help = "Global switch for chart images in alert notifications (per-template opt-in still required via the content template's chart toggle)."
     )]
     pub alert_chart_enabled: bool,
+    #[env_config(
+        name = "ZO_ALERTMANAGER_RESOLVE_TIMEOUT",
+        default = 300,
+        help = "Seconds after which an alert received on the Alertmanager API without an end time is considered resolved, unless it is sent again. Prometheus and vmalert always send an end time and re-send firing alerts before it passes."
+    )]
+    pub alertmanager_resolve_timeout: i64,
+    #[env_config(
+        name = "ZO_ALERTMANAGER_EXPIRY_SWEEP_INTERVAL",
+        default = 60,
+        help = "How often alerts received on the Alertmanager API whose end time passed are resolved, in seconds. 0 disables the sweep, leaving such alerts firing until their sender resolves them."
+    )]
+    pub alertmanager_expiry_sweep_interval: u64,
+    #[env_config(
+        name = "ZO_ALERTMANAGER_SILENCE_RETENTION_HOURS",
+        default = 120,
+        help = "How long expired Alertmanager silences are kept and listed, in hours."
+    )]
+    pub alertmanager_silence_retention_hours: i64,
     #[env_config(name = "ZO_REPORT_SCHEDULE_TIMEOUT", default = 300)] // seconds
     pub report_schedule_timeout: i64,
     #[env_config(name = "ZO_DERIVED_STREAM_SCHEDULE_INTERVAL", default = 300)] // seconds
@@ -2553,6 +2663,12 @@ pub struct Limit {
         help = "Maximum length of a token in the inverted index."
     )]
     pub inverted_index_max_token_length: usize,
+    #[env_config(
+        name = "ZO_INVERTED_INDEX_POSITIONS_ENABLED",
+        default = false,
+        help = "Store token positions for full text search fields in the inverted index, required to answer match_phrase() from the index. Increases index size."
+    )]
+    pub inverted_index_positions_enabled: bool,
     #[env_config(
         name = "ZO_INDEX_ALL_MAX_VALUE_LENGTH",
         default = 0,
@@ -2932,6 +3048,12 @@ pub struct S3 {
     pub bucket_name: String,
     #[env_config(name = "ZO_S3_BUCKET_PREFIX", default = "")]
     pub bucket_prefix: String,
+    #[env_config(
+        name = "ZO_S3_EXTERNAL_TABLE_PREFIXES",
+        default = "external/{org_id}/",
+        help = "comma separated list of the key prefixes the external tables of an org can read, {org_id} is replaced by the org, empty allows any key outside of the OpenObserve data"
+    )]
+    pub external_table_prefixes: String,
     #[env_config(name = "ZO_S3_CONNECT_TIMEOUT", default = 10)] // seconds
     pub connect_timeout: u64,
     #[env_config(name = "ZO_S3_REQUEST_TIMEOUT", default = 3600)] // seconds
@@ -2983,10 +3105,53 @@ pub struct Prometheus {
     pub ha_cluster_label: String,
     #[env_config(name = "ZO_PROMETHEUS_HA_REPLICA", default = "__replica__")]
     pub ha_replica_label: String,
+    /// Also write native histograms as classic `_count`/`_sum`/`_bucket` series, for
+    /// dashboards and alerts written against classic histograms.
+    #[env_config(
+        name = "ZO_PROMETHEUS_NATIVE_HISTOGRAM_CLASSIC",
+        default = true,
+        help = "Also store native histograms as classic _count/_sum/_bucket series"
+    )]
+    pub native_histogram_classic: bool,
     /// Max `le` labels (buckets + gap markers + inf) a native histogram sample may
-    /// expand to; over-limit samples are downscaled (adjacent buckets merged).
+    /// expand to in classic series; over-limit samples are downscaled (adjacent
+    /// buckets merged).
     #[env_config(name = "ZO_PROMETHEUS_NATIVE_HISTOGRAM_MAX_BUCKETS", default = 16)]
     pub native_histogram_max_buckets: usize,
+    /// Evaluation interval of imported rule groups that do not set their own
+    /// `interval`, like Prometheus' `global.evaluation_interval`.
+    #[env_config(
+        name = "ZO_PROMETHEUS_RULES_EVALUATION_INTERVAL",
+        default = 60,
+        help = "Default evaluation interval of imported Prometheus rule groups, in seconds"
+    )]
+    pub rules_evaluation_interval: u64,
+    #[env_config(
+        name = "ZO_PROMETHEUS_RULES_TICK_INTERVAL",
+        default = 10,
+        help = "How often the recording rule evaluator checks for due rule groups, in seconds. 0 disables recording rules."
+    )]
+    pub rules_tick_interval: u64,
+    /// Prometheus style configuration file with `scrape_configs`, scraped by the
+    /// ingesters. Empty disables the scrape agent.
+    #[env_config(
+        name = "ZO_PROMETHEUS_SCRAPE_CONFIG_FILE",
+        default = "",
+        help = "Path of a Prometheus configuration file whose scrape_configs the ingesters scrape. Empty disables scraping."
+    )]
+    pub scrape_config_file: String,
+    #[env_config(
+        name = "ZO_PROMETHEUS_SCRAPE_ORG_ID",
+        default = "default",
+        help = "Organization the scraped samples are written to"
+    )]
+    pub scrape_org_id: String,
+    #[env_config(
+        name = "ZO_PROMETHEUS_SCRAPE_SYNC_INTERVAL",
+        default = 30,
+        help = "How often the scrape configuration is reloaded and targets are discovered again, in seconds"
+    )]
+    pub scrape_sync_interval: u64,
 }
 
 #[derive(Serialize, Debug, EnvConfig, Default)]
@@ -3333,6 +3498,11 @@ pub fn init() -> Config {
         panic!("inverted index config error: {e}");
     }
 
+    // check fair scheduler config
+    if let Err(e) = check_fair_scheduler_config(&mut cfg) {
+        panic!("fair scheduler config error: {e}");
+    }
+
     // check synthetics config — infallible on purpose, see the function
     check_synthetics_config(&mut cfg);
 
@@ -3444,6 +3614,11 @@ fn check_limit_config(cfg: &mut Config) -> Result<(), anyhow::Error> {
         cfg.limit.max_dashboard_series = 100;
     }
 
+    // broadcast channels panic on zero capacity
+    if cfg.http_streaming.live_tail_channel_size == 0 {
+        cfg.http_streaming.live_tail_channel_size = 1024;
+    }
+
     // check query timeout
     if cfg.limit.query_timeout == 0 {
         cfg.limit.query_timeout = 600;
@@ -4330,6 +4505,24 @@ fn check_nats_config(cfg: &mut Config) -> Result<(), anyhow::Error> {
     Ok(())
 }
 
+fn check_fair_scheduler_config(cfg: &mut Config) -> Result<(), anyhow::Error> {
+    if cfg.search.fair_scheduler_max_concurrent == 0 {
+        cfg.search.fair_scheduler_max_concurrent = cfg.limit.cpu_num.max(1);
+    }
+    let policy = cfg
+        .search
+        .fair_scheduler_preempt_policy
+        .trim()
+        .to_lowercase();
+    if !["none", "release", "cancel"].contains(&policy.as_str()) {
+        return Err(anyhow::anyhow!(
+            "invalid ZO_SEARCH_FAIR_SCHEDULER_PREEMPT_POLICY `{policy}`, expected one of `none`, `release`, `cancel`"
+        ));
+    }
+    cfg.search.fair_scheduler_preempt_policy = policy;
+    Ok(())
+}
+
 fn check_inverted_index_config(cfg: &mut Config) -> Result<(), anyhow::Error> {
     if cfg.search.inverted_index_result_cache_max_entries == 0 {
         cfg.search.inverted_index_result_cache_max_entries = 10000;
@@ -5222,6 +5415,20 @@ mod tests {
         assert_eq!(cfg.limit.inverted_index_max_token_length, 32);
     }
 
+    #[test]
+    fn test_check_fair_scheduler_config() {
+        let mut cfg = Config::default();
+        cfg.limit.cpu_num = 8;
+        cfg.search.fair_scheduler_max_concurrent = 0;
+        cfg.search.fair_scheduler_preempt_policy = " Cancel ".to_string();
+        check_fair_scheduler_config(&mut cfg).unwrap();
+        assert_eq!(cfg.search.fair_scheduler_max_concurrent, 8);
+        assert_eq!(cfg.search.fair_scheduler_preempt_policy, "cancel");
+
+        cfg.search.fair_scheduler_preempt_policy = "kill".to_string();
+        assert!(check_fair_scheduler_config(&mut cfg).is_err());
+    }
+
     #[test]
     fn test_check_health_check_config_defaults() {
         let mut cfg = Config::default();
@@ -0,0 +1,488 @@
+// Copyright 2026 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Alertmanager v2 API model: label matchers, silences, and the alert and
+//! status views returned to Prometheus, vmalert, amtool and Grafana.
+//!
+//! Times are RFC 3339 strings on the wire and epoch micros everywhere else.
+
+use std::{collections::HashMap, sync::LazyLock};
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// KV prefix of the silences of an org, followed by the silence id.
+pub const SILENCES_KV_PREFIX: &str = "alertmanager_silences/";
+
+/// `name`, operator and value of a textual matcher such as `job=~"api.*"`.
+static MATCHER_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"^\s*([a-zA-Z_][a-zA-Z0-9_]*)\s*(=~|!~|!=|=)\s*(.*?)\s*$"#).unwrap()
+});
+
+fn default_true() -> bool {
+    true
+}
+
+/// A label matcher. A missing label matches as the empty string, and regexes
+/// are anchored, as in Alertmanager.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Matcher {
+    pub name: String,
+    pub value: String,
+    #[serde(default)]
+    pub is_regex: bool,
+    #[serde(default = "default_true")]
+    pub is_equal: bool,
+}
+
+impl Matcher {
+    /// Parses `name="value"`, with `=`, `!=`, `=~` or `!~`. The value may be
+    /// unquoted.
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let caps = MATCHER_RE
+            .captures(s)
+            .ok_or_else(|| format!("bad matcher format: {s}"))?;
+        let raw = &caps[3];
+        let value = if raw.len() >= 2 && raw.starts_with('"') && raw.ends_with('"') {
+            raw[1..raw.len() - 1].replace("\\\"", "\"")
+        } else {
+            raw.to_string()
+        };
+        let (is_regex, is_equal) = match &caps[2] {
+            "=" => (false, true),
+            "!=" => (false, false),
+            "=~" => (true, true),
+            _ => (true, false),
+        };
+        Ok(Matcher {
+            name: caps[1].to_string(),
+            value,
+            is_regex,
+            is_equal,
+        })
+    }
+
+    /// Parses a `filter` query value: one matcher, or several in braces,
+    /// `{a="1", b=~"2"}`.
+    pub fn parse_list(s: &str) -> Result<Vec<Self>, String> {
+        let s = s.trim();
+        let inner = s
+            .strip_prefix('{')
+            .and_then(|s| s.strip_suffix('}'))
+            .unwrap_or(s);
+        let mut matchers = Vec::new();
+        let mut start = 0;
+        let mut in_quotes = false;
+        let mut escaped = false;
+        for (i, c) in inner.char_indices() {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_quotes = !in_quotes,
+                ',' if !in_quotes => {
+                    matchers.push(Matcher::parse(&inner[start..i])?);
+                    start = i + 1;
+                }
+                _ => {}
+            }
+        }
+        if !inner[start..].trim().is_empty() {
+            matchers.push(Matcher::parse(&inner[start..])?);
+        }
+        Ok(matchers)
+    }
+}
+
+/// Matchers compiled for repeated use. All must match.
+#[derive(Debug, Clone)]
+pub struct MatcherSet(Vec<(Matcher, Option<Regex>)>);
+
+impl MatcherSet {
+    pub fn new(matchers: &[Matcher]) -> Result<Self, String> {
+        let mut set = Vec::with_capacity(matchers.len());
+        for m in matchers {
+            if m.name.is_empty() {
+                return Err("matcher name must not be empty".to_string());
+            }
+            let re = if m.is_regex {
+                Some(
+                    Regex::new(&format!("^(?:{})$", m.value))
+                        .map_err(|e| format!("invalid regex in matcher {}: {e}", m.name))?,
+                )
+            } else {
+                None
+            };
+            set.push((m.clone(), re));
+        }
+        Ok(MatcherSet(set))
+    }
+
+    pub fn matches(&self, labels: &HashMap<String, String>) -> bool {
+        self.0.iter().all(|(m, re)| {
+            let v = labels.get(&m.name).map(String::as_str).unwrap_or_default();
+            let found = match re {
+                Some(re) => re.is_match(v),
+                None => v == m.value,
+            };
+            found == m.is_equal
+        })
+    }
+
+    /// Whether a label set with no labels at all would match. A silence like
+    /// that would silence everything.
+    pub fn matches_empty(&self) -> bool {
+        self.matches(&HashMap::new())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SilenceState {
+    Pending,
+    Active,
+    Expired,
+}
+
+/// A stored silence.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Silence {
+    pub id: String,
+    pub matchers: Vec<Matcher>,
+    pub starts_at: i64,
+    pub ends_at: i64,
+    pub updated_at: i64,
+    pub created_by: String,
+    pub comment: String,
+}
+
+impl Silence {
+    pub fn state(&self, now: i64) -> SilenceState {
+        if now < self.starts_at {
+            SilenceState::Pending
+        } else if now < self.ends_at {
+            SilenceState::Active
+        } else {
+            SilenceState::Expired
+        }
+    }
+
+    pub fn to_gettable(&self, now: i64) -> GettableSilence {
+        GettableSilence {
+            id: self.id.clone(),
+            status: SilenceStatus {
+                state: self.state(now),
+            },
+            updated_at: format_time(self.updated_at),
+            matchers: self.matchers.clone(),
+            starts_at: format_time(self.starts_at),
+            ends_at: format_time(self.ends_at),
+            created_by: self.created_by.clone(),
+            comment: self.comment.clone(),
+        }
+    }
+}
+
+/// Body of `POST /api/v2/silences`. With an `id`, the silence of that id is
+/// updated.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PostableSilence {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    pub matchers: Vec<Matcher>,
+    pub starts_at: String,
+    pub ends_at: String,
+    #[serde(default)]
+    pub created_by: String,
+    #[serde(default)]
+    pub comment: String,
+}
+
+impl PostableSilence {
+    /// Checks the silence and converts it, `now` being the update time.
+    pub fn into_silence(self, id: String, now: i64) -> Result<Silence, String> {
+        if self.matchers.is_empty() {
+            return Err("silence needs at least one matcher".to_string());
+        }
+        if MatcherSet::new(&self.matchers)?.matches_empty() {
+            return Err(
+                "at least one matcher must not match the empty string, or the silence would match every alert"
+                    .to_string(),
+            );
+        }
+        let starts_at = parse_time(&self.starts_at)?;
+        let ends_at = parse_time(&self.ends_at)?;
+        if ends_at <= starts_at {
+            return Err("silence ends before it starts".to_string());
+        }
+        if ends_at <= now {
+            return Err("silence end time is in the past".to_string());
+        }
+        Ok(Silence {
+            id,
+            matchers: self.matchers,
+            // a silence cannot start in the past, as in Alertmanager
+            starts_at: starts_at.max(now),
+            ends_at,
+            updated_at: now,
+            created_by: self.created_by,
+            comment: self.comment,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct SilenceStatus {
+    pub state: SilenceState,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct GettableSilence {
+    pub id: String,
+    pub status: SilenceStatus,
+    pub updated_at: String,
+    pub matchers: Vec<Matcher>,
+    pub starts_at: String,
+    pub ends_at: String,
+    pub created_by: String,
+    pub comment: String,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PostSilenceResponse {
+    #[serde(rename = "silenceID")]
+    pub silence_id: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum AlertState {
+    Unprocessed,
+    Active,
+    Suppressed,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AlertStatus {
+    pub state: AlertState,
+    pub silenced_by: Vec<String>,
+    pub inhibited_by: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct Receiver {
+    pub name: String,
+}
+
+/// An alert of `GET /api/v2/alerts`.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct GettableAlert {
+    pub labels: HashMap<String, String>,
+    pub annotations: HashMap<String, String>,
+    pub starts_at: String,
+    pub ends_at: String,
+    pub updated_at: String,
+    #[serde(rename = "generatorURL", skip_serializing_if = "String::is_empty")]
+    pub generator_url: String,
+    pub fingerprint: String,
+    pub receivers: Vec<Receiver>,
+    pub status: AlertStatus,
+}
+
+/// Filters of `GET /api/v2/alerts`.
+#[derive(Debug, Clone)]
+pub struct AlertsFilter {
+    pub matchers: Vec<Matcher>,
+    pub active: bool,
+    pub silenced: bool,
+}
+
+impl Default for AlertsFilter {
+    fn default() -> Self {
+        AlertsFilter {
+            matchers: Vec::new(),
+            active: true,
+            silenced: true,
+        }
+    }
+}
+
+impl AlertsFilter {
+    /// Reads the raw query string, where `filter` may repeat. `inhibited`,
+    /// `unprocessed` and `receiver` are accepted and ignored: there is no
+    /// inhibition, alerts are processed on receipt, and every alert goes to
+    /// the one receiver.
+    pub fn from_query(query: &str) -> Result<Self, String> {
+        let mut filter = AlertsFilter::default();
+        for (k, v) in url::form_urlencoded::parse(query.as_bytes()) {
+            match k.as_ref() {
+                "filter" => filter.matchers.extend(Matcher::parse_list(&v)?),
+                "active" => filter.active = parse_bool(&v)?,
+                "silenced" => filter.silenced = parse_bool(&v)?,
+                _ => {}
+            }
+        }
+        Ok(filter)
+    }
+}
+
+fn parse_bool(v: &str) -> Result<bool, String> {
+    v.parse().map_err(|_| format!("invalid boolean: {v}"))
+}
+
+/// `GET /api/v2/status`, enough for Grafana's data source check and amtool.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AlertmanagerStatus {
+    pub cluster: ClusterStatus,
+    pub version_info: HashMap<String, String>,
+    pub config: AlertmanagerConfig,
+    pub uptime: String,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ClusterStatus {
+    pub status: String,
+    pub peers: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct AlertmanagerConfig {
+    pub original: String,
+}
+
+/// Formats epoch micros as RFC 3339 with millisecond precision.
+pub fn format_time(micros: i64) -> String {
+    chrono::DateTime::from_timestamp_micros(micros)
+        .unwrap_or_default()
+        .to_rfc3339_opts(chrono::SecondsFormat::Millis, true)
+}
+
+/// Parses an RFC 3339 time to epoch micros.
+pub fn parse_time(s: &str) -> Result<i64, String> {
+    chrono::DateTime::parse_from_rfc3339(s)
+        .map(|t| t.timestamp_micros())
+        .map_err(|e| format!("invalid time {s}: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn labels(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_matcher_parse() {
+        let m = Matcher::parse(r#"job=~"api.*""#).unwrap();
+        assert_eq!(m.name, "job");
+        assert_eq!(m.value, "api.*");
+        assert!(m.is_regex && m.is_equal);
+        let m = Matcher::parse("severity != critical").unwrap();
+        assert_eq!(m.value, "critical");
+        assert!(!m.is_regex && !m.is_equal);
+        assert!(Matcher::parse("no operator").is_err());
+
+        let list = Matcher::parse_list(r#"{alertname="A,B", env!~"dev|test"}"#).unwrap();
+        assert_eq!(list.len(), 2);
+        assert_eq!(list[0].value, "A,B");
+        assert_eq!(list[1].name, "env");
+        assert!(Matcher::parse_list("{}").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_matcher_set() {
+        let set = MatcherSet::new(&Matcher::parse_list(r#"{job=~"api|web", env!="dev"}"#).unwrap())
+            .unwrap();
+        assert!(set.matches(&labels(&[("job", "api"), ("env", "prod")])));
+        assert!(set.matches(&labels(&[("job", "web")])));
+        // anchored
+        assert!(!set.matches(&labels(&[("job", "api2")])));
+        assert!(!set.matches(&labels(&[("job", "api"), ("env", "dev")])));
+        assert!(!set.matches_empty());
+
+        let set = MatcherSet::new(&[Matcher::parse(r#"env!="dev""#).unwrap()]).unwrap();
+        assert!(set.matches_empty());
+        assert!(MatcherSet::new(&[Matcher::parse(r#"a=~"(""#).unwrap()]).is_err());
+    }
+
+    #[test]
+    fn test_postable_silence() {
+        let now = parse_time("2026-01-01T00:00:00Z").unwrap();
+        let post = |matcher: &str, start: &str, end: &str| PostableSilence {
+            id: None,
+            matchers: vec![Matcher::parse(matcher).unwrap()],
+            starts_at: start.to_string(),
+            ends_at: end.to_string(),
+            created_by: "me".to_string(),
+            comment: "maintenance".to_string(),
+        };
+        let s = post(
+            r#"alertname="A""#,
+            "2025-12-31T00:00:00Z",
+            "2026-01-01T02:00:00Z",
+        )
+        .into_silence("id".to_string(), now)
+        .unwrap();
+        assert_eq!(s.starts_at, now);
+        assert_eq!(s.state(now), SilenceState::Active);
+        assert_eq!(s.state(s.ends_at), SilenceState::Expired);
+        assert_eq!(s.state(now - 1), SilenceState::Pending);
+        let g = s.to_gettable(now);
+        assert_eq!(g.ends_at, "2026-01-01T02:00:00.000Z");
+        assert_eq!(g.status.state, SilenceState::Active);
+
+        // matches everything
+        assert!(
+            post(r#"a=~".*""#, "2026-01-01T00:00:00Z", "2026-01-01T01:00:00Z")
+                .into_silence(String::new(), now)
+                .is_err()
+        );
+        // already over
+        assert!(
+            post(r#"a="b""#, "2025-12-31T00:00:00Z", "2025-12-31T01:00:00Z")
+                .into_silence(String::new(), now)
+                .is_err()
+        );
+        // ends before it starts
+        assert!(
+            post(r#"a="b""#, "2026-01-02T00:00:00Z", "2026-01-01T01:00:00Z")
+                .into_silence(String::new(), now)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_alerts_filter() {
+        let f = AlertsFilter::from_query(
+            "filter=alertname%3D%22A%22&filter=%7Benv%3D~%22prod%22%7D&silenced=false",
+        )
+        .unwrap();
+        assert_eq!(f.matchers.len(), 2);
+        assert!(f.active);
+        assert!(!f.silenced);
+        assert!(AlertsFilter::from_query("active=maybe").is_err());
+    }
+}
@@ -32,6 +32,7 @@ use crate::{
 
 pub mod aggregation_level;
 pub mod alert;
+pub mod alertmanager;
 pub mod composite;
 pub mod content_spec;
 pub mod deduplication;
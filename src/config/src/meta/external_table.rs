@@ -0,0 +1,402 @@
+// Copyright 2026 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::HashMap;
+
+use arrow_schema::Schema;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// The SQL schema of the external tables, `SELECT * FROM external."events"`.
+pub const EXTERNAL_TABLE_SCHEMA: &str = "external";
+
+/// The key prefixes of the data OpenObserve writes to the object storage, the
+/// stream and index files, the file list dumps, the WAL and the caches.
+const RESERVED_PREFIXES: [&str; 7] = [
+    "files/",
+    "file_list/",
+    "wal/",
+    "index/",
+    "results/",
+    "cardinality/",
+    "synthetics/",
+];
+
+const DEFAULT_ACCOUNT: &str = "default";
+const ORG_ID_PLACEHOLDER: &str = "{org_id}";
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, ToSchema, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ExternalTableFormat {
+    /// Every Parquet file under the location prefix.
+    #[default]
+    Parquet,
+    /// The data files of the current snapshot of an Iceberg table.
+    Iceberg,
+}
+
+impl std::fmt::Display for ExternalTableFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ExternalTableFormat::Parquet => write!(f, "parquet"),
+            ExternalTableFormat::Iceberg => write!(f, "iceberg"),
+        }
+    }
+}
+
+/// A table over files in the object storage which are not written by
+/// OpenObserve, it can be queried and joined with the streams of the org.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema, PartialEq)]
+pub struct ExternalTable {
+    pub name: String,
+    #[serde(default)]
+    pub format: ExternalTableFormat,
+    /// The prefix of the Parquet files, or for Iceberg the table location or
+    /// the path of a `*.metadata.json` file. Either a key in the bucket of the
+    /// storage account or a `s3://bucket/key` URI.
+    pub location: String,
+    /// The storage account of `ZO_S3_ACCOUNTS` holding the files, the default
+    /// account when empty.
+    #[serde(default)]
+    pub account: String,
+    /// The hive style partition columns of the Parquet files, `year=2026/month=01/`.
+    /// For Iceberg the identity partitions of the table are used.
+    #[serde(default)]
+    pub partition_columns: Vec<String>,
+    #[serde(default)]
+    pub description: String,
+    /// The schema inferred when the table is registered.
+    #[serde(default)]
+    #[schema(value_type = Object)]
+    pub schema: Option<Schema>,
+    #[serde(default)]
+    pub created_at: i64,
+}
+
+impl ExternalTable {
+    /// Check the table of the org, the location must be in the bucket of the
+    /// storage account and under one of the `ZO_S3_EXTERNAL_TABLE_PREFIXES`.
+    pub fn validate(&self, org_id: &str) -> Result<(), String> {
+        self.validate_with(org_id, &crate::get_config().s3)
+    }
+
+    fn validate_with(&self, org_id: &str, s3: &crate::config::S3) -> Result<(), String> {
+        if self.name.is_empty()
+            || !self
+                .name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        {
+            return Err(
+                "External table name must only contain letters, numbers, '_' and '-'".to_string(),
+            );
+        }
+        if self.location.trim_matches('/').is_empty() {
+            return Err("External table location is required".to_string());
+        }
+        if self.location.contains("..") {
+            return Err("External table location must not contain '..'".to_string());
+        }
+        if self.format == ExternalTableFormat::Iceberg && !self.partition_columns.is_empty() {
+            return Err(
+                "The partitions of an Iceberg table are read from its metadata".to_string(),
+            );
+        }
+
+        let accounts = s3.accounts.split(',').map(|s| s.trim()).collect::<Vec<_>>();
+        let buckets = s3
+            .bucket_name
+            .split(',')
+            .map(|s| s.trim())
+            .collect::<Vec<_>>();
+        let account_idx = if self.account.is_empty() || self.account == DEFAULT_ACCOUNT {
+            Some(0)
+        } else {
+            accounts.iter().position(|a| *a == self.account)
+        };
+        let Some(account_idx) = account_idx else {
+            return Err(format!(
+                "Storage account {} is not configured in ZO_S3_ACCOUNTS",
+                self.account
+            ));
+        };
+        if let Some(bucket) = object_bucket(&self.location) {
+            let account_bucket = buckets.get(account_idx).copied().unwrap_or_default();
+            if bucket != account_bucket {
+                let hint = match buckets.iter().position(|b| *b == bucket) {
+                    Some(idx) => format!(", it is the bucket of the account {}", accounts[idx]),
+                    None => String::new(),
+                };
+                return Err(format!(
+                    "External table location bucket {bucket} is not the bucket of the storage account{hint}"
+                ));
+            }
+        }
+
+        let key = self.root_key();
+        if let Some(prefix) = RESERVED_PREFIXES.iter().find(|p| key.starts_with(*p)) {
+            return Err(format!(
+                "External table location must not be under the OpenObserve data prefix {prefix}"
+            ));
+        }
+        let allowed = s3
+            .external_table_prefixes
+            .split(',')
+            .map(|p| p.trim())
+            .filter(|p| !p.is_empty())
+            .map(|p| p.replace(ORG_ID_PLACEHOLDER, org_id))
+            .collect::<Vec<_>>();
+        if !allowed.is_empty() && !allowed.iter().any(|p| key.starts_with(p.as_str())) {
+            return Err(format!(
+                "External table location must be under one of the prefixes: {}",
+                allowed.join(", ")
+            ));
+        }
+        Ok(())
+    }
+
+    /// The key prefix of the table, the files of the table, and for Iceberg
+    /// its metadata and manifests, must be under it.
+    pub fn root_key(&self) -> String {
+        let key = self.location_key();
+        let root = if key.ends_with(".metadata.json") {
+            match key.rsplit_once("/metadata/") {
+                Some((root, _)) => root,
+                None => key.rsplit_once('/').map(|(dir, _)| dir).unwrap_or_default(),
+            }
+        } else {
+            key.trim_end_matches('/')
+        };
+        // collapse the empty path segments, the object storage keys don't have them
+        let root = root
+            .split('/')
+            .filter(|p| !p.is_empty())
+            .collect::<Vec<_>>()
+            .join("/");
+        format!("{root}/")
+    }
+
+    /// Check if the key of a file referenced by the table is under its root.
+    pub fn contains_key(&self, key: &str) -> bool {
+        !key.contains("..") && key.starts_with(&self.root_key())
+    }
+
+    /// The location as a key in the bucket of the storage account.
+    pub fn location_key(&self) -> String {
+        let key = object_key(&self.location);
+        if self.format == ExternalTableFormat::Parquet && !key.is_empty() && !key.ends_with('/') {
+            format!("{key}/")
+        } else {
+            key
+        }
+    }
+
+    /// Get the hive style partition values from the path of a data file.
+    pub fn partition_values(&self, key: &str) -> HashMap<String, String> {
+        let mut values = HashMap::new();
+        if self.partition_columns.is_empty() {
+            return values;
+        }
+        let (dirs, _) = key.rsplit_once('/').unwrap_or_default();
+        for part in dirs.split('/') {
+            if let Some((column, value)) = part.split_once('=')
+                && self.partition_columns.iter().any(|c| c == column)
+            {
+                values.insert(column.to_string(), value.to_string());
+            }
+        }
+        values
+    }
+}
+
+/// The bucket of an object storage URI, `None` for a plain key.
+pub fn object_bucket(location: &str) -> Option<&str> {
+    location.split_once("://").map(|(_, rest)| {
+        rest.split_once('/')
+            .map(|(bucket, _)| bucket)
+            .unwrap_or(rest)
+    })
+}
+
+/// Strip the scheme and the bucket of an object storage URI, a plain key is
+/// returned without the leading `/`.
+pub fn object_key(location: &str) -> String {
+    match location.split_once("://") {
+        Some((_, rest)) => rest
+            .split_once('/')
+            .map(|(_, key)| key)
+            .unwrap_or_default()
+            .to_string(),
+        None => location.trim_start_matches('/').to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn s3() -> crate::config::S3 {
+        crate::config::S3 {
+            accounts: "main,archive".to_string(),
+            bucket_name: "o2data,o2archive".to_string(),
+            external_table_prefixes: "external/{org_id}/,shared/".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_external_table_validate() {
+        let s3 = s3();
+        let mut table = ExternalTable {
+            name: "events".to_string(),
+            location: "external/org1/events".to_string(),
+            ..Default::default()
+        };
+        assert!(table.validate_with("org1", &s3).is_ok());
+        table.name = "ev ents".to_string();
+        assert!(table.validate_with("org1", &s3).is_err());
+        table.name = "events".to_string();
+        table.location = "/".to_string();
+        assert!(table.validate_with("org1", &s3).is_err());
+        table.location = "external/org1/../other".to_string();
+        assert!(table.validate_with("org1", &s3).is_err());
+    }
+
+    #[test]
+    fn test_external_table_validate_location() {
+        let s3 = s3();
+        let table = |location: &str, account: &str| ExternalTable {
+            name: "events".to_string(),
+            location: location.to_string(),
+            account: account.to_string(),
+            ..Default::default()
+        };
+        // the prefix of another org, or outside of the allowed prefixes
+        assert!(
+            table("external/org2/events", "")
+                .validate_with("org1", &s3)
+                .is_err()
+        );
+        assert!(
+            table("warehouse/events", "")
+                .validate_with("org1", &s3)
+                .is_err()
+        );
+        assert!(
+            table("shared/events", "")
+                .validate_with("org1", &s3)
+                .is_ok()
+        );
+        // the data of openobserve
+        let mut open = s3;
+        open.external_table_prefixes = String::new();
+        for location in [
+            "files/org2/logs/default/",
+            "//files/org2/logs/default/",
+            "s3://o2data/files",
+            "file_list/",
+            "wal/files/",
+            "index/org2/",
+        ] {
+            assert!(
+                table(location, "").validate_with("org1", &open).is_err(),
+                "{location}"
+            );
+        }
+        assert!(
+            table("warehouse/events", "")
+                .validate_with("org1", &open)
+                .is_ok()
+        );
+        // the bucket must be the bucket of the account
+        assert!(
+            table("s3://o2data/warehouse/events", "")
+                .validate_with("org1", &open)
+                .is_ok()
+        );
+        assert!(
+            table("s3://o2archive/warehouse/events", "archive")
+                .validate_with("org1", &open)
+                .is_ok()
+        );
+        assert!(
+            table("s3://o2archive/warehouse/events", "")
+                .validate_with("org1", &open)
+                .is_err()
+        );
+        assert!(
+            table("s3://other/warehouse/events", "")
+                .validate_with("org1", &open)
+                .is_err()
+        );
+        assert!(
+            table("warehouse/events", "unknown")
+                .validate_with("org1", &open)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_external_table_root_key() {
+        let table = |location: &str, format| ExternalTable {
+            location: location.to_string(),
+            format,
+            ..Default::default()
+        };
+        let parquet = table("s3://bucket//warehouse/t", ExternalTableFormat::Parquet);
+        assert_eq!(parquet.root_key(), "warehouse/t/");
+        assert!(parquet.contains_key("warehouse/t/part-0.parquet"));
+        assert!(!parquet.contains_key("warehouse/t2/part-0.parquet"));
+        assert!(!parquet.contains_key("warehouse/t/../t2/part-0.parquet"));
+        let iceberg = table(
+            "warehouse/orders/metadata/v3.metadata.json",
+            ExternalTableFormat::Iceberg,
+        );
+        assert_eq!(iceberg.root_key(), "warehouse/orders/");
+        assert!(iceberg.contains_key("warehouse/orders/data/part-0.parquet"));
+        assert!(!iceberg.contains_key("files/org1/logs/default/a.parquet"));
+    }
+
+    #[test]
+    fn test_object_key() {
+        assert_eq!(object_key("s3://bucket/warehouse/t"), "warehouse/t");
+        assert_eq!(object_key("s3://bucket"), "");
+        assert_eq!(object_key("/warehouse/t/"), "warehouse/t/");
+
+        let table = ExternalTable {
+            location: "s3://bucket/warehouse/t".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(table.location_key(), "warehouse/t/");
+    }
+
+    #[test]
+    fn test_partition_values() {
+        let table = ExternalTable {
+            location: "warehouse/t".to_string(),
+            partition_columns: vec!["year".to_string(), "region".to_string()],
+            ..Default::default()
+        };
+        let values = table.partition_values("warehouse/t/year=2026/region=eu/part-0.parquet");
+        assert_eq!(values.len(), 2);
+        assert_eq!(values["year"], "2026");
+        assert_eq!(values["region"], "eu");
+        assert!(
+            table
+                .partition_values("warehouse/t/part-0.parquet")
+                .is_empty()
+        );
+    }
+}
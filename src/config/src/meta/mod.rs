@@ -22,6 +22,7 @@ pub mod correlation;
 pub mod dashboards;
 pub mod destinations;
 pub mod enrichment_table;
+pub mod external_table;
 pub mod folder;
 pub mod function;
 pub mod gen_ai;
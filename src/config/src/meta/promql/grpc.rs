@@ -17,7 +17,10 @@ use std::sync::Arc;
 
 use proto::cluster_rpc;
 
-use super::value::*;
+use super::{
+    histogram::{HistogramSample, NativeHistogram},
+    value::*,
+};
 
 impl From<&cluster_rpc::Label> for Label {
     fn from(req: &cluster_rpc::Label) -> Self {
@@ -72,6 +75,48 @@ impl From<&cluster_rpc::Exemplar> for Exemplar {
     }
 }
 
+impl From<&HistogramSample> for cluster_rpc::HistogramSample {
+    fn from(req: &HistogramSample) -> Self {
+        let h = req.histogram.as_ref();
+        let buckets = |b: &[(i64, f64)]| {
+            b.iter()
+                .map(|&(index, count)| cluster_rpc::HistogramBucket { index, count })
+                .collect()
+        };
+        cluster_rpc::HistogramSample {
+            time: req.timestamp,
+            schema: h.schema,
+            zero_threshold: h.zero_threshold,
+            zero_count: h.zero_count,
+            count: h.count,
+            sum: h.sum,
+            positive: buckets(&h.positive),
+            negative: buckets(&h.negative),
+            custom_values: h.custom_values.clone(),
+        }
+    }
+}
+
+impl From<&cluster_rpc::HistogramSample> for HistogramSample {
+    fn from(req: &cluster_rpc::HistogramSample) -> Self {
+        let buckets =
+            |b: &[cluster_rpc::HistogramBucket]| b.iter().map(|b| (b.index, b.count)).collect();
+        HistogramSample::new(
+            req.time,
+            Arc::new(NativeHistogram {
+                schema: req.schema,
+                zero_threshold: req.zero_threshold,
+                zero_count: req.zero_count,
+                count: req.count,
+                sum: req.sum,
+                positive: buckets(&req.positive),
+                negative: buckets(&req.negative),
+                custom_values: req.custom_values.clone(),
+            }),
+        )
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use proto::cluster_rpc;
@@ -193,4 +238,27 @@ mod tests {
         assert_eq!(rpc.labels.len(), 1);
         assert_eq!(rpc.labels[0].name, "job");
     }
+
+    #[test]
+    fn test_histogram_sample_roundtrip() {
+        let sample = HistogramSample::new(
+            1_000_000,
+            Arc::new(NativeHistogram {
+                schema: 3,
+                zero_threshold: 0.001,
+                zero_count: 1.0,
+                count: 6.0,
+                sum: 12.5,
+                positive: vec![(-2, 2.0), (5, 1.0)],
+                negative: vec![(1, 2.0)],
+                custom_values: vec![],
+            }),
+        );
+        let rpc = cluster_rpc::HistogramSample::from(&sample);
+        assert_eq!(rpc.time, 1_000_000);
+        assert_eq!(rpc.positive.len(), 2);
+        let back = HistogramSample::from(&rpc);
+        assert_eq!(back.timestamp, sample.timestamp);
+        assert_eq!(back.histogram, sample.histogram);
+    }
 }
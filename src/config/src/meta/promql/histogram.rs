@@ -0,0 +1,834 @@
+// Copyright 2026 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Prometheus native (sparse) histograms as a first-class sample type.
+//!
+//! A [`NativeHistogram`] is stored as JSON in the [`super::NATIVE_HISTOGRAM_LABEL`] column of
+//! the metric's own stream and evaluated natively by PromQL: exponential interpolation for
+//! `histogram_quantile`/`histogram_fraction`, bucket-wise `rate` and `sum`.
+
+use std::{ops::RangeInclusive, sync::Arc};
+
+use proto::prometheus_rpc;
+use serde::{
+    Deserialize, Deserializer, Serialize,
+    ser::{SerializeSeq, SerializeStruct, Serializer},
+};
+
+/// Exponential schemas (`base = 2^(2^-schema)`).
+pub const EXPONENTIAL_SCHEMA_RANGE: RangeInclusive<i32> = -4..=8;
+
+/// Native histograms with custom bucket boundaries (NHCB): the upper bounds are carried in
+/// `custom_values` instead of being derived from the schema.
+pub const CUSTOM_BUCKETS_SCHEMA: i32 = -53;
+
+/// Prometheus's stale-marker bit pattern in `sum`; an ordinary NaN is NOT stale.
+pub const STALE_NAN_BITS: u64 = 0x7ff0_0000_0000_0002;
+
+/// Whether a float sample is a stale marker. Stale markers are stored with a
+/// null `value` and read back as this NaN.
+pub fn is_stale_nan(v: f64) -> bool {
+    v.to_bits() == STALE_NAN_BITS
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct NativeHistogram {
+    pub schema: i32,
+    #[serde(default)]
+    pub zero_threshold: f64,
+    #[serde(default)]
+    pub zero_count: f64,
+    pub count: f64,
+    /// JSON has no NaN, serde_json writes it as `null`
+    #[serde(default = "nan", deserialize_with = "nan_if_null")]
+    pub sum: f64,
+    /// Populated buckets as `(index, count)`, ascending by index. Bucket `idx` covers
+    /// `(base^(idx-1), base^idx]`, mirrored on the negative side.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub positive: Vec<(i64, f64)>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub negative: Vec<(i64, f64)>,
+    /// Upper bounds for [`CUSTOM_BUCKETS_SCHEMA`]; positive bucket `idx` covers
+    /// `(custom_values[idx-1], custom_values[idx]]`, the last one is open to `+Inf`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub custom_values: Vec<f64>,
+}
+
+fn nan() -> f64 {
+    f64::NAN
+}
+
+fn nan_if_null<'de, D>(deserializer: D) -> Result<f64, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(Option::<f64>::deserialize(deserializer)?.unwrap_or(f64::NAN))
+}
+
+/// One bucket of a [`NativeHistogram`] with its absolute bounds.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HistogramBucket {
+    pub lower: f64,
+    pub upper: f64,
+    pub count: f64,
+    /// Boundary rule of the Prometheus JSON format: 0 `(lower, upper]`, 1 `[lower, upper)`,
+    /// 3 `[lower, upper]`.
+    pub boundaries: u8,
+}
+
+impl NativeHistogram {
+    /// Decodes a remote-write histogram. `None` for stale markers and unsupported schemas.
+    pub fn from_proto(hp: &prometheus_rpc::Histogram) -> Option<Self> {
+        let custom = hp.schema == CUSTOM_BUCKETS_SCHEMA;
+        if !custom && !EXPONENTIAL_SCHEMA_RANGE.contains(&hp.schema) {
+            return None;
+        }
+        if custom && hp.custom_values.is_empty() {
+            return None;
+        }
+        // a stale marker terminates the series, it is not an observation
+        if hp.sum.to_bits() == STALE_NAN_BITS {
+            return None;
+        }
+
+        let count = match hp.count {
+            Some(prometheus_rpc::histogram::Count::CountInt(v)) => v as f64,
+            Some(prometheus_rpc::histogram::Count::CountFloat(v)) => v,
+            None => 0.0,
+        };
+        let zero_count = match hp.zero_count {
+            Some(prometheus_rpc::histogram::ZeroCount::ZeroCountInt(v)) => v as f64,
+            Some(prometheus_rpc::histogram::ZeroCount::ZeroCountFloat(v)) => v,
+            None => 0.0,
+        };
+
+        let mut positive =
+            span_buckets(&hp.positive_spans, &hp.positive_deltas, &hp.positive_counts);
+        let (negative, zero_threshold, zero_count) = if custom {
+            // NHCB has no negative or zero buckets, and no bucket past `+Inf`
+            let last_idx = hp.custom_values.len() as i64;
+            positive.retain(|(idx, _)| (0..=last_idx).contains(idx));
+            (vec![], 0.0, 0.0)
+        } else {
+            (
+                span_buckets(&hp.negative_spans, &hp.negative_deltas, &hp.negative_counts),
+                hp.zero_threshold,
+                zero_count,
+            )
+        };
+
+        Some(Self {
+            schema: hp.schema,
+            zero_threshold,
+            zero_count,
+            count,
+            sum: hp.sum,
+            positive,
+            negative,
+            custom_values: if custom {
+                hp.custom_values.clone()
+            } else {
+                vec![]
+            },
+        })
+    }
+
+    pub fn uses_custom_buckets(&self) -> bool {
+        self.schema == CUSTOM_BUCKETS_SCHEMA
+    }
+
+    /// All populated buckets in ascending order: negative, zero, positive.
+    pub fn buckets(&self) -> Vec<HistogramBucket> {
+        let mut buckets = Vec::with_capacity(self.negative.len() + self.positive.len() + 1);
+        if self.uses_custom_buckets() {
+            for &(idx, count) in &self.positive {
+                let bound = |i: i64| match i {
+                    i if i < 0 => f64::NEG_INFINITY,
+                    i => self
+                        .custom_values
+                        .get(i as usize)
+                        .copied()
+                        .unwrap_or(f64::INFINITY),
+                };
+                buckets.push(HistogramBucket {
+                    lower: bound(idx - 1),
+                    upper: bound(idx),
+                    count,
+                    boundaries: 0,
+                });
+            }
+            return buckets;
+        }
+
+        for &(idx, count) in self.negative.iter().rev() {
+            buckets.push(HistogramBucket {
+                lower: -bucket_bound(self.schema, idx),
+                upper: -bucket_bound(self.schema, idx - 1),
+                count,
+                boundaries: 1,
+            });
+        }
+        if self.zero_count > 0.0 || self.zero_threshold > 0.0 {
+            buckets.push(HistogramBucket {
+                lower: -self.zero_threshold,
+                upper: self.zero_threshold,
+                count: self.zero_count,
+                boundaries: 3,
+            });
+        }
+        for &(idx, count) in &self.positive {
+            buckets.push(HistogramBucket {
+                lower: bucket_bound(self.schema, idx - 1),
+                upper: bucket_bound(self.schema, idx),
+                count,
+                boundaries: 0,
+            });
+        }
+        buckets
+    }
+
+    /// Estimates the `q` quantile. Exponential schemas interpolate exponentially within a
+    /// bucket (the observations are assumed evenly spread in log space), custom buckets and
+    /// the zero bucket linearly.
+    ///
+    /// cf. `HistogramQuantile` in prometheus/promql/quantile.go
+    pub fn quantile(&self, q: f64) -> f64 {
+        if q < 0.0 {
+            return f64::NEG_INFINITY;
+        }
+        if q > 1.0 {
+            return f64::INFINITY;
+        }
+        if self.count == 0.0 || q.is_nan() {
+            return f64::NAN;
+        }
+
+        let buckets = self.buckets();
+        let rank = q * self.count;
+        let mut count = 0.0;
+        let mut found = None;
+        for bucket in &buckets {
+            if bucket.count == 0.0 {
+                continue;
+            }
+            count += bucket.count;
+            found = Some(*bucket);
+            if count >= rank {
+                break;
+            }
+        }
+        let Some(mut bucket) = found else {
+            return f64::NAN;
+        };
+
+        if self.uses_custom_buckets() {
+            if bucket.lower == f64::NEG_INFINITY {
+                if bucket.upper <= 0.0 {
+                    return bucket.upper;
+                }
+                bucket.lower = 0.0;
+            }
+            if bucket.upper == f64::INFINITY {
+                return bucket.lower;
+            }
+        } else if bucket.lower < 0.0 && bucket.upper > 0.0 {
+            // a zero bucket next to buckets of only one sign only covers that side
+            if self.negative.is_empty() && !self.positive.is_empty() {
+                bucket.lower = 0.0;
+            } else if self.positive.is_empty() && !self.negative.is_empty() {
+                bucket.upper = 0.0;
+            }
+        }
+
+        // float histograms can sum up to slightly more than `count`
+        let count = count.min(self.count);
+        let fraction = (rank - (count - bucket.count)) / bucket.count;
+
+        if self.uses_custom_buckets() || (bucket.lower <= 0.0 && bucket.upper >= 0.0) {
+            return bucket.lower + (bucket.upper - bucket.lower) * fraction;
+        }
+        let log_lower = bucket.lower.abs().log2();
+        let log_upper = bucket.upper.abs().log2();
+        if bucket.lower > 0.0 {
+            (log_lower + (log_upper - log_lower) * fraction).exp2()
+        } else {
+            -(log_upper + (log_lower - log_upper) * (1.0 - fraction)).exp2()
+        }
+    }
+
+    /// Estimates the fraction of observations between `lower` and `upper`, interpolating
+    /// like [`Self::quantile`].
+    ///
+    /// cf. `HistogramFraction` in prometheus/promql/quantile.go
+    pub fn fraction(&self, lower: f64, upper: f64) -> f64 {
+        if self.count == 0.0 || lower.is_nan() || upper.is_nan() {
+            return f64::NAN;
+        }
+        if lower >= upper {
+            return 0.0;
+        }
+
+        let mut rank = 0.0;
+        let mut lower_rank = None;
+        let mut upper_rank = None;
+        for mut bucket in self.buckets() {
+            let mut linear = self.uses_custom_buckets();
+            if !self.uses_custom_buckets() && bucket.lower < 0.0 && bucket.upper > 0.0 {
+                if self.negative.is_empty() && !self.positive.is_empty() {
+                    bucket.lower = 0.0;
+                } else if self.positive.is_empty() && !self.negative.is_empty() {
+                    bucket.upper = 0.0;
+                }
+                linear = true;
+            }
+            let interpolate = |v: f64| {
+                if bucket.lower.is_infinite() || bucket.upper.is_infinite() {
+                    // nothing is known about the spread within an unbounded bucket
+                    return rank
+                        + if bucket.lower.is_infinite() {
+                            bucket.count
+                        } else {
+                            0.0
+                        };
+                }
+                let fraction = if linear {
+                    (v - bucket.lower) / (bucket.upper - bucket.lower)
+                } else {
+                    let log_lower = bucket.lower.abs().log2();
+                    let log_upper = bucket.upper.abs().log2();
+                    let log_v = v.abs().log2();
+                    if v > 0.0 {
+                        (log_v - log_lower) / (log_upper - log_lower)
+                    } else {
+                        1.0 - (log_v - log_upper) / (log_lower - log_upper)
+                    }
+                };
+                rank + bucket.count * fraction
+            };
+
+            if lower_rank.is_none() && bucket.lower >= lower {
+                lower_rank = Some(rank);
+            }
+            if upper_rank.is_none() && bucket.lower >= upper {
+                upper_rank = Some(rank);
+            }
+            if lower_rank.is_none() && bucket.lower < lower && bucket.upper > lower {
+                lower_rank = Some(interpolate(lower));
+            }
+            if upper_rank.is_none() && bucket.lower < upper && bucket.upper > upper {
+                upper_rank = Some(interpolate(upper));
+            }
+            if lower_rank.is_some() && upper_rank.is_some() {
+                break;
+            }
+            rank += bucket.count;
+        }
+        let lower_rank = lower_rank.unwrap_or(self.count).min(self.count);
+        let upper_rank = upper_rank.unwrap_or(self.count).min(self.count);
+        (upper_rank - lower_rank) / self.count
+    }
+
+    /// The arithmetic mean of the observations.
+    pub fn avg(&self) -> f64 {
+        self.sum / self.count
+    }
+
+    /// Estimates the population variance, placing every observation at its bucket's
+    /// geometric mean (arithmetic mean for custom buckets, zero for the zero bucket).
+    ///
+    /// cf. `funcHistogramStdVar` in prometheus/promql/functions.go
+    pub fn stdvar(&self) -> f64 {
+        if self.count == 0.0 {
+            return f64::NAN;
+        }
+        let mean = self.avg();
+        let mut variance = 0.0;
+        for bucket in self.buckets() {
+            if bucket.count == 0.0 {
+                continue;
+            }
+            let value = if self.uses_custom_buckets() {
+                match (bucket.lower.is_finite(), bucket.upper.is_finite()) {
+                    (true, true) => (bucket.lower + bucket.upper) / 2.0,
+                    (false, _) => bucket.upper,
+                    (_, false) => bucket.lower,
+                }
+            } else if bucket.lower <= 0.0 && bucket.upper >= 0.0 {
+                0.0
+            } else {
+                let value = (bucket.upper * bucket.lower).sqrt();
+                if bucket.upper < 0.0 { -value } else { value }
+            };
+            let delta = value - mean;
+            variance += bucket.count * delta * delta;
+        }
+        variance / self.count
+    }
+
+    pub fn stddev(&self) -> f64 {
+        self.stdvar().sqrt()
+    }
+
+    /// Scales every count and the sum by `factor`.
+    pub fn mul(&self, factor: f64) -> Self {
+        let scale = |b: &[(i64, f64)]| b.iter().map(|&(i, c)| (i, c * factor)).collect();
+        Self {
+            zero_count: self.zero_count * factor,
+            count: self.count * factor,
+            sum: self.sum * factor,
+            positive: scale(&self.positive),
+            negative: scale(&self.negative),
+            ..self.clone()
+        }
+    }
+
+    /// Bucket-wise sum, reduced to the coarser schema and the wider zero bucket. `None` if
+    /// the layouts cannot be combined: custom buckets with exponential ones, or custom
+    /// buckets with different bounds.
+    pub fn add(&self, other: &Self) -> Option<Self> {
+        if self.uses_custom_buckets() != other.uses_custom_buckets()
+            || self.custom_values != other.custom_values
+        {
+            return None;
+        }
+        let schema = self.schema.min(other.schema);
+        let zero_threshold = self.zero_threshold.max(other.zero_threshold);
+        let mut a = self.to_schema(schema);
+        let mut b = other.to_schema(schema);
+        a.widen_zero_bucket(zero_threshold);
+        b.widen_zero_bucket(zero_threshold);
+
+        a.zero_count += b.zero_count;
+        a.count += b.count;
+        a.sum += b.sum;
+        a.positive = merge_buckets(&a.positive, &b.positive);
+        a.negative = merge_buckets(&a.negative, &b.negative);
+        Some(a)
+    }
+
+    /// `self - other`, see [`Self::add`].
+    pub fn sub(&self, other: &Self) -> Option<Self> {
+        self.add(&other.mul(-1.0))
+    }
+
+    /// Whether `self`, the successor of `prev` in a counter series, follows a reset: any
+    /// count went down, or the layout got finer (which a counter never does).
+    pub fn detect_reset(&self, prev: &Self) -> bool {
+        if self.count < prev.count
+            || self.uses_custom_buckets() != prev.uses_custom_buckets()
+            || self.custom_values != prev.custom_values
+            || self.schema > prev.schema
+            || self.zero_threshold < prev.zero_threshold
+        {
+            return true;
+        }
+        let mut prev = prev.to_schema(self.schema);
+        prev.widen_zero_bucket(self.zero_threshold);
+        self.zero_count < prev.zero_count
+            || buckets_decreased(&self.positive, &prev.positive)
+            || buckets_decreased(&self.negative, &prev.negative)
+    }
+
+    /// A copy at the coarser `schema`; finer schemas are left as they are.
+    fn to_schema(&self, schema: i32) -> Self {
+        let mut h = self.clone();
+        if h.uses_custom_buckets() {
+            return h;
+        }
+        while h.schema > schema {
+            h.schema -= 1;
+            h.positive = downscale(std::mem::take(&mut h.positive));
+            h.negative = downscale(std::mem::take(&mut h.negative));
+        }
+        h
+    }
+
+    /// Moves every bucket lying entirely within `[-threshold, threshold]` into the zero
+    /// bucket.
+    fn widen_zero_bucket(&mut self, threshold: f64) {
+        if self.uses_custom_buckets() || threshold <= self.zero_threshold {
+            return;
+        }
+        let schema = self.schema;
+        let mut moved = 0.0;
+        for buckets in [&mut self.positive, &mut self.negative] {
+            buckets.retain(|&(idx, count)| {
+                let inside = bucket_bound(schema, idx) <= threshold;
+                if inside {
+                    moved += count;
+                }
+                !inside
+            });
+        }
+        self.zero_count += moved;
+        self.zero_threshold = threshold;
+    }
+}
+
+/// A native histogram observed at one timestamp.
+#[derive(Debug, Clone)]
+pub struct HistogramSample {
+    /// Time in microseconds
+    pub timestamp: i64,
+    pub histogram: Arc<NativeHistogram>,
+}
+
+impl HistogramSample {
+    pub fn new(timestamp: i64, histogram: Arc<NativeHistogram>) -> Self {
+        Self {
+            timestamp,
+            histogram,
+        }
+    }
+}
+
+/// `[<unix_time>, {"count": ..., "sum": ..., "buckets": [...]}]`, the shape of the
+/// Prometheus HTTP API.
+impl Serialize for HistogramSample {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut seq = serializer.serialize_seq(Some(2))?;
+        seq.serialize_element(&(self.timestamp / 1_000_000))?;
+        seq.serialize_element(&PromHistogram(&self.histogram))?;
+        seq.end()
+    }
+}
+
+struct PromHistogram<'a>(&'a NativeHistogram);
+
+impl Serialize for PromHistogram<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let buckets = self
+            .0
+            .buckets()
+            .into_iter()
+            .filter(|b| b.count != 0.0)
+            .map(|b| {
+                (
+                    b.boundaries,
+                    format_float(b.lower),
+                    format_float(b.upper),
+                    format_float(b.count),
+                )
+            })
+            .collect::<Vec<_>>();
+        let mut s = serializer.serialize_struct("histogram", 3)?;
+        s.serialize_field("count", &format_float(self.0.count))?;
+        s.serialize_field("sum", &format_float(self.0.sum))?;
+        if !buckets.is_empty() {
+            s.serialize_field("buckets", &buckets)?;
+        }
+        s.end()
+    }
+}
+
+/// Prometheus spells the non-finite values `+Inf`, `-Inf` and `NaN`.
+fn format_float(v: f64) -> String {
+    match v {
+        f64::INFINITY => "+Inf".to_string(),
+        f64::NEG_INFINITY => "-Inf".to_string(),
+        v if v.is_nan() => "NaN".to_string(),
+        v => v.to_string(),
+    }
+}
+
+/// Upper bound of bucket `idx`: `2^(idx * 2^-schema)` via `exp2`, so powers of two are
+/// exact and a boundary shared between schemas is the identical f64. Clamped finite so
+/// the last representable bucket cannot collide with `+Inf`.
+pub fn bucket_bound(schema: i32, idx: i64) -> f64 {
+    let bound = ((idx as f64) * 2f64.powi(-schema)).exp2();
+    bound.clamp(f64::MIN_POSITIVE, f64::MAX)
+}
+
+/// Merges adjacent bucket pairs: `idx` at schema `s` maps to `ceil(idx / 2)` at
+/// schema `s - 1`.
+pub fn downscale(buckets: Vec<(i64, f64)>) -> Vec<(i64, f64)> {
+    let mut out: Vec<(i64, f64)> = Vec::with_capacity(buckets.len() / 2 + 1);
+    for (idx, c) in buckets {
+        let merged_idx = (idx + 1).div_euclid(2);
+        match out.last_mut() {
+            Some((last_idx, last_c)) if *last_idx == merged_idx => *last_c += c,
+            _ => out.push((merged_idx, c)),
+        }
+    }
+    out
+}
+
+/// Decodes the sparse layout (spans + integer deltas or absolute float counts) into
+/// `(bucket index, absolute count)` pairs for every populated bucket.
+pub fn span_buckets(
+    spans: &[prometheus_rpc::BucketSpan],
+    deltas: &[i64],
+    counts: &[f64],
+) -> Vec<(i64, f64)> {
+    let use_float_counts = !counts.is_empty();
+    let mut buckets = Vec::new();
+    let mut idx: i64 = 0;
+    let mut pos = 0;
+    let mut cumulative_delta: i64 = 0;
+    for span in spans {
+        idx += span.offset as i64;
+        for _ in 0..span.length {
+            let count = if use_float_counts {
+                let Some(c) = counts.get(pos) else {
+                    return buckets;
+                };
+                *c
+            } else {
+                let Some(d) = deltas.get(pos) else {
+                    return buckets;
+                };
+                cumulative_delta += *d;
+                cumulative_delta as f64
+            };
+            pos += 1;
+            // skips zero-count buckets and NaN float counts
+            if count > 0.0 {
+                buckets.push((idx, count));
+            }
+            idx += 1;
+        }
+    }
+    buckets
+}
+
+/// Index-wise sum of two ascending bucket lists.
+fn merge_buckets(a: &[(i64, f64)], b: &[(i64, f64)]) -> Vec<(i64, f64)> {
+    let mut out = Vec::with_capacity(a.len().max(b.len()));
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() || j < b.len() {
+        match (a.get(i), b.get(j)) {
+            (Some(&(ia, ca)), Some(&(ib, cb))) if ia == ib => {
+                out.push((ia, ca + cb));
+                i += 1;
+                j += 1;
+            }
+            (Some(&(ia, ca)), Some(&(ib, _))) if ia < ib => {
+                out.push((ia, ca));
+                i += 1;
+            }
+            (_, Some(&bucket)) => {
+                out.push(bucket);
+                j += 1;
+            }
+            (Some(&bucket), None) => {
+                out.push(bucket);
+                i += 1;
+            }
+            (None, None) => unreachable!(),
+        }
+    }
+    out
+}
+
+/// Whether any bucket of `prev` holds more than the same bucket of `cur`.
+fn buckets_decreased(cur: &[(i64, f64)], prev: &[(i64, f64)]) -> bool {
+    prev.iter().any(|&(idx, prev_count)| {
+        let cur_count = cur
+            .binary_search_by_key(&idx, |&(i, _)| i)
+            .map_or(0.0, |pos| cur[pos].1);
+        cur_count < prev_count
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::json;
+
+    /// schema 0 (base 2): buckets (0.5,1]:2, (1,2]:4, (2,4]:2, zero bucket 2
+    fn histogram() -> NativeHistogram {
+        NativeHistogram {
+            schema: 0,
+            zero_threshold: 0.001,
+            zero_count: 2.0,
+            count: 10.0,
+            sum: 17.0,
+            positive: vec![(0, 2.0), (1, 4.0), (2, 2.0)],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_from_proto_integer_and_custom_buckets() {
+        let hp = prometheus_rpc::Histogram {
+            sum: 100.0,
+            zero_threshold: 0.001,
+            count: Some(prometheus_rpc::histogram::Count::CountInt(12)),
+            zero_count: Some(prometheus_rpc::histogram::ZeroCount::ZeroCountInt(2)),
+            positive_spans: vec![
+                prometheus_rpc::BucketSpan {
+                    offset: 0,
+                    length: 2,
+                },
+                prometheus_rpc::BucketSpan {
+                    offset: 1,
+                    length: 1,
+                },
+            ],
+            positive_deltas: vec![3, 1, -1],
+            ..Default::default()
+        };
+        let h = NativeHistogram::from_proto(&hp).unwrap();
+        assert_eq!(h.positive, vec![(0, 3.0), (1, 4.0), (3, 3.0)]);
+        assert_eq!(h.count, 12.0);
+        assert_eq!(h.zero_count, 2.0);
+
+        let nhcb = prometheus_rpc::Histogram {
+            schema: CUSTOM_BUCKETS_SCHEMA,
+            count: Some(prometheus_rpc::histogram::Count::CountInt(6)),
+            positive_spans: vec![prometheus_rpc::BucketSpan {
+                offset: 0,
+                length: 3,
+            }],
+            positive_deltas: vec![1, 2, 0],
+            custom_values: vec![0.1, 0.5],
+            ..Default::default()
+        };
+        let h = NativeHistogram::from_proto(&nhcb).unwrap();
+        let bounds = h
+            .buckets()
+            .iter()
+            .map(|b| (b.lower, b.upper, b.count))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            bounds,
+            vec![
+                (f64::NEG_INFINITY, 0.1, 1.0),
+                (0.1, 0.5, 3.0),
+                (0.5, f64::INFINITY, 3.0),
+            ]
+        );
+
+        let stale = prometheus_rpc::Histogram {
+            sum: f64::from_bits(STALE_NAN_BITS),
+            ..Default::default()
+        };
+        assert!(NativeHistogram::from_proto(&stale).is_none());
+        let nhcb_without_bounds = prometheus_rpc::Histogram {
+            schema: CUSTOM_BUCKETS_SCHEMA,
+            ..Default::default()
+        };
+        assert!(NativeHistogram::from_proto(&nhcb_without_bounds).is_none());
+    }
+
+    #[test]
+    fn test_quantile_interpolates_exponentially() {
+        let h = histogram();
+        // rank 5 lands in (1,2] at fraction 0.25: 2^0.25
+        assert!((h.quantile(0.5) - 2f64.powf(0.25)).abs() < 1e-12);
+        // rank 1 lands in the zero bucket, which only covers [0, 0.001] here
+        assert_eq!(h.quantile(0.1), 0.0005);
+        assert_eq!(h.quantile(1.0), 4.0);
+        assert_eq!(h.quantile(1.5), f64::INFINITY);
+        assert!(NativeHistogram::default().quantile(0.5).is_nan());
+    }
+
+    #[test]
+    fn test_fraction() {
+        let h = histogram();
+        assert_eq!(h.fraction(1.0, f64::INFINITY), 0.6);
+        assert_eq!(h.fraction(f64::NEG_INFINITY, f64::INFINITY), 1.0);
+        // half of (1,2] in log space
+        assert!((h.fraction(1.0, 2f64.sqrt()) - 0.2).abs() < 1e-12);
+        assert_eq!(h.fraction(2.0, 1.0), 0.0);
+    }
+
+    #[test]
+    fn test_avg_and_stdvar() {
+        let h = histogram();
+        assert_eq!(h.avg(), 1.7);
+        let geometric = |l: f64, u: f64| (l * u).sqrt();
+        let expected = (2.0 * 1.7f64.powi(2)
+            + 2.0 * (geometric(0.5, 1.0) - 1.7).powi(2)
+            + 4.0 * (geometric(1.0, 2.0) - 1.7).powi(2)
+            + 2.0 * (geometric(2.0, 4.0) - 1.7).powi(2))
+            / 10.0;
+        assert!((h.stdvar() - expected).abs() < 1e-12);
+        assert!((h.stddev() - expected.sqrt()).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_add_reduces_to_coarser_schema() {
+        let fine = NativeHistogram {
+            schema: 1,
+            count: 3.0,
+            sum: 3.0,
+            positive: vec![(1, 1.0), (2, 2.0)], // (1,1.41], (1.41,2]
+            ..Default::default()
+        };
+        let sum = histogram().add(&fine).unwrap();
+        assert_eq!(sum.schema, 0);
+        assert_eq!(sum.count, 13.0);
+        assert_eq!(sum.positive, vec![(0, 2.0), (1, 7.0), (2, 2.0)]);
+
+        let custom = NativeHistogram {
+            schema: CUSTOM_BUCKETS_SCHEMA,
+            custom_values: vec![1.0],
+            ..Default::default()
+        };
+        assert!(histogram().add(&custom).is_none());
+    }
+
+    #[test]
+    fn test_detect_reset() {
+        let prev = histogram();
+        let mut cur = histogram().mul(2.0);
+        assert!(!cur.detect_reset(&prev));
+        // the total grew but one bucket shrank
+        cur.positive[0].1 = 1.0;
+        assert!(cur.detect_reset(&prev));
+        assert!(prev.mul(0.5).detect_reset(&prev));
+    }
+
+    #[test]
+    fn test_histogram_sample_serializes_prometheus_shape() {
+        let sample = HistogramSample::new(1_700_000_000_000_000, Arc::new(histogram()));
+        let json = json::to_value(&sample).unwrap();
+        assert_eq!(
+            json,
+            json::json!([
+                1_700_000_000i64,
+                {
+                    "count": "10",
+                    "sum": "17",
+                    "buckets": [
+                        [3, "-0.001", "0.001", "2"],
+                        [0, "0.5", "1", "2"],
+                        [0, "1", "2", "4"],
+                        [0, "2", "4", "2"],
+                    ]
+                }
+            ])
+        );
+    }
+
+    #[test]
+    fn test_storage_roundtrip_keeps_nan_sum() {
+        let h = NativeHistogram {
+            sum: f64::NAN,
+            ..histogram()
+        };
+        let stored = json::to_string(&h).unwrap();
+        let loaded: NativeHistogram = json::from_str(&stored).unwrap();
+        assert!(loaded.sum.is_nan());
+        assert_eq!(loaded.positive, h.positive);
+    }
+}
@@ -0,0 +1,207 @@
+// Copyright 2026 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Ingest rules of metrics: relabeling and series limits, applied to every
+//! sample written to an org before its series hash is computed.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    NAME_LABEL,
+    relabel::{RelabelAction, RelabelConfig},
+};
+
+/// KV key of the ingest rules of an org.
+pub const INGEST_RULES_KV_KEY: &str = "prometheus_ingest_rules";
+
+/// Label set to `true` on the series the `aggregate` policy writes, so they
+/// never share a series with samples that lack the aggregated labels.
+pub const AGGREGATED_LABEL: &str = "__aggregated__";
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct IngestRules {
+    /// Applied to the samples of every metric, before the rules of the metric.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub metric_relabel_configs: Vec<RelabelConfig>,
+    /// Rules by metric (stream) name.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub metrics: BTreeMap<String, MetricIngestRules>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MetricIngestRules {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub metric_relabel_configs: Vec<RelabelConfig>,
+    /// Max active series of the metric per ingester, 0 is unlimited.
+    #[serde(default)]
+    pub series_limit: usize,
+    #[serde(default)]
+    pub limit_policy: SeriesLimitPolicy,
+    /// Labels removed from new series over the limit with the `aggregate`
+    /// policy. Empty removes every label but `__name__`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub aggregate_labels: Vec<String>,
+}
+
+/// What happens to the samples of a new series once the metric reached its
+/// series limit.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SeriesLimitPolicy {
+    /// The samples are rejected.
+    #[default]
+    Drop,
+    /// The `aggregate_labels` are removed and [`AGGREGATED_LABEL`] is set, so
+    /// every series over the limit is folded into one coarser series. Samples
+    /// of one request folded into the same series at the same timestamp are
+    /// summed.
+    Aggregate,
+}
+
+impl IngestRules {
+    /// Parses rules written as YAML or JSON.
+    pub fn parse(body: &str) -> Result<Self, String> {
+        let rules: IngestRules = serde_yaml_ng::from_str(body).map_err(|e| e.to_string())?;
+        rules.validate()?;
+        Ok(rules)
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        for config in self.metric_relabel_configs.iter() {
+            validate_relabel(config)?;
+        }
+        for (name, rules) in self.metrics.iter() {
+            for config in rules.metric_relabel_configs.iter() {
+                validate_relabel(config).map_err(|e| format!("metric {name}: {e}"))?;
+            }
+            if rules.limit_policy == SeriesLimitPolicy::Aggregate && rules.series_limit == 0 {
+                return Err(format!(
+                    "metric {name}: the aggregate policy requires a series_limit"
+                ));
+            }
+            if rules
+                .aggregate_labels
+                .iter()
+                .any(|label| label == NAME_LABEL)
+            {
+                return Err(format!(
+                    "metric {name}: {NAME_LABEL} can not be aggregated away"
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether samples of `metric` are affected by any rule.
+    pub fn applies_to(&self, metric: &str) -> bool {
+        !self.metric_relabel_configs.is_empty() || self.metrics.contains_key(metric)
+    }
+}
+
+/// `__name__` names the stream a sample is written to, so ingest rules can not
+/// replace it.
+fn validate_relabel(config: &RelabelConfig) -> Result<(), String> {
+    use RelabelAction::*;
+
+    config.validate()?;
+    let replaces_name = match config.action {
+        Replace | HashMod | Lowercase | Uppercase => config.target_label == NAME_LABEL,
+        LabelMap => config.replacement == NAME_LABEL,
+        _ => false,
+    };
+    if replaces_name {
+        return Err(format!(
+            "{:?} action can not replace {NAME_LABEL} in ingest rules",
+            config.action
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ingest_rules() {
+        let rules = IngestRules::parse(
+            r#"
+metric_relabel_configs:
+  - action: labeldrop
+    regex: request_id
+metrics:
+  http_requests_total:
+    metric_relabel_configs:
+      - source_labels: [path]
+        regex: "/users/[0-9]+"
+        target_label: path
+        replacement: /users/:id
+    series_limit: 1000
+    limit_policy: aggregate
+    aggregate_labels: [path]
+"#,
+        )
+        .unwrap();
+        assert_eq!(
+            rules.metric_relabel_configs[0].action,
+            RelabelAction::LabelDrop
+        );
+        let metric = &rules.metrics["http_requests_total"];
+        assert_eq!(metric.series_limit, 1000);
+        assert_eq!(metric.limit_policy, SeriesLimitPolicy::Aggregate);
+        assert_eq!(metric.aggregate_labels, ["path"]);
+        assert!(rules.applies_to("http_requests_total"));
+        assert!(rules.applies_to("up"));
+        assert!(!IngestRules::default().applies_to("up"));
+
+        // JSON is YAML
+        let json = crate::utils::json::to_string(&rules).unwrap();
+        assert_eq!(IngestRules::parse(&json).unwrap(), rules);
+    }
+
+    #[test]
+    fn test_validate_ingest_rules() {
+        assert!(IngestRules::parse("metrics: {up: {limit_policy: aggregate}}").is_err());
+        assert!(
+            IngestRules::parse(
+                "metrics: {up: {series_limit: 1, limit_policy: aggregate, aggregate_labels: [__name__]}}"
+            )
+            .is_err()
+        );
+        assert!(
+            IngestRules::parse("metrics: {up: {series_limit: 1, limit_policy: aggregate}}").is_ok()
+        );
+        assert!(
+            IngestRules::parse(
+                "metrics: {up: {metric_relabel_configs: [{target_label: __name__, replacement: x}]}}"
+            )
+            .is_err()
+        );
+        assert!(
+            IngestRules::parse("metric_relabel_configs: [{action: labelmap, regex: 'x_(.*)', replacement: __name__}]")
+                .is_err()
+        );
+        assert!(IngestRules::parse("metrics: {up: {series_limit: 1, unknown: 1}}").is_err());
+        assert!(
+            IngestRules::parse("metric_relabel_configs: [{action: hashmod, target_label: x}]")
+                .is_err()
+        );
+        assert!(IngestRules::parse("{}").is_ok());
+    }
+}
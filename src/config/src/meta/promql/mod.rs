@@ -66,6 +66,12 @@ where
 }
 
 pub mod grpc;
+pub mod histogram;
+pub mod ingest_rules;
+pub mod relabel;
+pub mod rules;
+pub mod scrape;
+pub mod status;
 pub mod value;
 
 pub const NAME_LABEL: &str = "__name__";
@@ -76,6 +82,8 @@ pub const BUCKET_LABEL: &str = "le";
 pub const QUANTILE_LABEL: &str = "quantile";
 pub const METADATA_LABEL: &str = "prom_metadata"; // for schema metadata key
 pub const EXEMPLARS_LABEL: &str = "exemplars";
+/// JSON-encoded [`histogram::NativeHistogram`] of a native histogram sample
+pub const NATIVE_HISTOGRAM_LABEL: &str = "native_histogram";
 
 /// Columns that metrics ingestion may exclude when deriving [`HASH_LABEL`].
 ///
@@ -87,6 +95,7 @@ pub const METRICS_HASH_EXCLUDED_LABELS: &[&str] = &[
     VALUE_LABEL,
     HASH_LABEL,
     EXEMPLARS_LABEL,
+    NATIVE_HISTOGRAM_LABEL,
     "is_monotonic",
     "trace_id",
     "span_id",
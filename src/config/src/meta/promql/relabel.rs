@@ -0,0 +1,404 @@
+// Copyright 2026 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Prometheus relabeling, as used by `relabel_configs` and
+//! `metric_relabel_configs`.
+//!
+//! cf. https://prometheus.io/docs/prometheus/latest/configuration/configuration/#relabel_config
+
+use std::collections::BTreeMap;
+
+use regex::Regex;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use super::rules::is_valid_label_name;
+
+/// Labels of a series or a target, sorted by name.
+pub type LabelSet = BTreeMap<String, String>;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RelabelAction {
+    #[default]
+    Replace,
+    Keep,
+    Drop,
+    KeepEqual,
+    DropEqual,
+    HashMod,
+    LabelMap,
+    LabelDrop,
+    LabelKeep,
+    Lowercase,
+    Uppercase,
+}
+
+/// A relabel regex, anchored at both ends like in Prometheus.
+#[derive(Debug, Clone)]
+pub struct RelabelRegex {
+    source: String,
+    regex: Regex,
+}
+
+impl RelabelRegex {
+    pub fn new(source: &str) -> Result<Self, regex::Error> {
+        Ok(Self {
+            source: source.to_string(),
+            regex: Regex::new(&format!("^(?s:{source})$"))?,
+        })
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+}
+
+impl Default for RelabelRegex {
+    fn default() -> Self {
+        Self::new("(.*)").unwrap()
+    }
+}
+
+impl PartialEq for RelabelRegex {
+    fn eq(&self, other: &Self) -> bool {
+        self.source == other.source
+    }
+}
+
+impl Serialize for RelabelRegex {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.source)
+    }
+}
+
+impl<'de> Deserialize<'de> for RelabelRegex {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let source = String::deserialize(deserializer)?;
+        Self::new(&source).map_err(serde::de::Error::custom)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RelabelConfig {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub source_labels: Vec<String>,
+    #[serde(default = "default_separator")]
+    pub separator: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub target_label: String,
+    #[serde(default)]
+    pub regex: RelabelRegex,
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub modulus: u64,
+    #[serde(default = "default_replacement")]
+    pub replacement: String,
+    #[serde(default)]
+    pub action: RelabelAction,
+}
+
+impl Default for RelabelConfig {
+    fn default() -> Self {
+        Self {
+            source_labels: vec![],
+            separator: default_separator(),
+            target_label: String::new(),
+            regex: RelabelRegex::default(),
+            modulus: 0,
+            replacement: default_replacement(),
+            action: RelabelAction::default(),
+        }
+    }
+}
+
+fn default_separator() -> String {
+    ";".to_string()
+}
+
+fn default_replacement() -> String {
+    "$1".to_string()
+}
+
+fn is_zero(v: &u64) -> bool {
+    *v == 0
+}
+
+impl RelabelConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        use RelabelAction::*;
+
+        let needs_target = matches!(
+            self.action,
+            Replace | HashMod | Lowercase | Uppercase | KeepEqual | DropEqual
+        );
+        if needs_target && self.target_label.is_empty() {
+            return Err(format!(
+                "relabel configuration for {:?} action requires 'target_label' value",
+                self.action
+            ));
+        }
+        if self.action == HashMod && self.modulus == 0 {
+            return Err("relabel configuration for hashmod requires non-zero modulus".to_string());
+        }
+        if matches!(
+            self.action,
+            Lowercase | Uppercase | KeepEqual | DropEqual | HashMod
+        ) && !is_valid_label_name(&self.target_label)
+        {
+            return Err(format!(
+                "{:?} is invalid 'target_label' for {:?} action",
+                self.target_label, self.action
+            ));
+        }
+        if matches!(self.action, LabelDrop | LabelKeep)
+            && (!self.source_labels.is_empty()
+                || !self.target_label.is_empty()
+                || self.modulus != 0
+                || self.separator != default_separator()
+                || self.replacement != default_replacement())
+        {
+            return Err(format!(
+                "{:?} action requires only 'regex', and no other fields",
+                self.action
+            ));
+        }
+        Ok(())
+    }
+
+    /// Applies the rule to `labels`, `false` if the series or target is
+    /// dropped.
+    fn apply(&self, labels: &mut LabelSet) -> bool {
+        let value = || {
+            self.source_labels
+                .iter()
+                .map(|name| labels.get(name).map(String::as_str).unwrap_or_default())
+                .collect::<Vec<_>>()
+                .join(&self.separator)
+        };
+        let regex = &self.regex.regex;
+        match self.action {
+            RelabelAction::Drop => return !regex.is_match(&value()),
+            RelabelAction::Keep => return regex.is_match(&value()),
+            RelabelAction::DropEqual => {
+                return labels.get(&self.target_label) != Some(&value());
+            }
+            RelabelAction::KeepEqual => {
+                return labels.get(&self.target_label) == Some(&value());
+            }
+            RelabelAction::Replace => {
+                let value = value();
+                let Some(caps) = regex.captures(&value) else {
+                    return true;
+                };
+                let mut target = String::new();
+                caps.expand(&self.target_label, &mut target);
+                if !is_valid_label_name(&target) && target != super::NAME_LABEL {
+                    return true;
+                }
+                let mut replaced = String::new();
+                caps.expand(&self.replacement, &mut replaced);
+                if replaced.is_empty() {
+                    labels.remove(&target);
+                } else {
+                    labels.insert(target, replaced);
+                }
+            }
+            RelabelAction::Lowercase => {
+                let value = value().to_lowercase();
+                labels.insert(self.target_label.clone(), value);
+            }
+            RelabelAction::Uppercase => {
+                let value = value().to_uppercase();
+                labels.insert(self.target_label.clone(), value);
+            }
+            RelabelAction::HashMod => {
+                let digest = md5::compute(value().as_bytes());
+                let hash = u64::from_be_bytes(digest[8..].try_into().unwrap());
+                labels.insert(self.target_label.clone(), (hash % self.modulus).to_string());
+            }
+            RelabelAction::LabelMap => {
+                let mapped = labels
+                    .iter()
+                    .filter(|(name, _)| regex.is_match(name))
+                    .map(|(name, value)| {
+                        let name = regex.replace(name, self.replacement.as_str());
+                        (name.into_owned(), value.clone())
+                    })
+                    .collect::<Vec<_>>();
+                labels.extend(mapped);
+            }
+            RelabelAction::LabelDrop => labels.retain(|name, _| !regex.is_match(name)),
+            RelabelAction::LabelKeep => labels.retain(|name, _| regex.is_match(name)),
+        }
+        true
+    }
+}
+
+/// Applies `configs` in order, `false` if the series or target is dropped.
+/// Labels left with an empty value are removed.
+pub fn relabel(labels: &mut LabelSet, configs: &[RelabelConfig]) -> bool {
+    for config in configs {
+        if !config.apply(labels) {
+            return false;
+        }
+    }
+    labels.retain(|_, value| !value.is_empty());
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn labels(pairs: &[(&str, &str)]) -> LabelSet {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    fn configs(yaml: &str) -> Vec<RelabelConfig> {
+        let configs: Vec<RelabelConfig> = serde_yaml_ng::from_str(yaml).unwrap();
+        for config in configs.iter() {
+            config.validate().unwrap();
+        }
+        configs
+    }
+
+    #[test]
+    fn test_relabel_replace() {
+        let configs = configs(
+            r#"
+- source_labels: [__address__]
+  regex: "(.*):\\d+"
+  target_label: host
+- source_labels: [__meta_env, __meta_team]
+  separator: "-"
+  target_label: owner
+- source_labels: [missing]
+  regex: "(.*)"
+  target_label: zone
+- source_labels: [host]
+  regex: "(db)-(.*)"
+  target_label: "${1}_role"
+  replacement: primary
+"#,
+        );
+        let mut l = labels(&[
+            ("__address__", "db-1:9100"),
+            ("__meta_env", "prod"),
+            ("__meta_team", "core"),
+            ("zone", "eu"),
+        ]);
+        assert!(relabel(&mut l, &configs));
+        assert_eq!(l.get("host").unwrap(), "db-1");
+        assert_eq!(l.get("owner").unwrap(), "prod-core");
+        // an empty replacement removes the target label
+        assert!(!l.contains_key("zone"));
+        assert_eq!(l.get("db_role").unwrap(), "primary");
+    }
+
+    #[test]
+    fn test_relabel_keep_drop() {
+        let keep = configs("[{source_labels: [job], regex: 'node|api', action: keep}]");
+        assert!(relabel(&mut labels(&[("job", "node")]), &keep));
+        assert!(!relabel(&mut labels(&[("job", "nodes")]), &keep));
+        assert!(!relabel(&mut labels(&[]), &keep));
+
+        let drop = configs("[{source_labels: [__name__], regex: 'go_.*', action: drop}]");
+        assert!(!relabel(
+            &mut labels(&[("__name__", "go_goroutines")]),
+            &drop
+        ));
+        assert!(relabel(&mut labels(&[("__name__", "up")]), &drop));
+
+        let keep_equal =
+            configs("[{source_labels: [port], target_label: expected, action: keepequal}]");
+        assert!(relabel(
+            &mut labels(&[("port", "80"), ("expected", "80")]),
+            &keep_equal
+        ));
+        assert!(!relabel(
+            &mut labels(&[("port", "80"), ("expected", "81")]),
+            &keep_equal
+        ));
+        let drop_equal =
+            configs("[{source_labels: [port], target_label: expected, action: dropequal}]");
+        assert!(!relabel(
+            &mut labels(&[("port", "80"), ("expected", "80")]),
+            &drop_equal
+        ));
+    }
+
+    #[test]
+    fn test_relabel_hashmod() {
+        let configs = configs(
+            "[{source_labels: [__address__], modulus: 4, target_label: __tmp_hash, action: hashmod}]",
+        );
+        let mut a = labels(&[("__address__", "10.0.0.1:9100")]);
+        let mut b = a.clone();
+        assert!(relabel(&mut a, &configs));
+        assert!(relabel(&mut b, &configs));
+        let shard: u64 = a.get("__tmp_hash").unwrap().parse().unwrap();
+        assert!(shard < 4);
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn test_relabel_label_actions() {
+        let configs = configs(
+            r#"
+- regex: "__meta_kubernetes_pod_label_(.+)"
+  action: labelmap
+- regex: "__meta_.*"
+  action: labeldrop
+- source_labels: [app]
+  target_label: app_upper
+  action: uppercase
+"#,
+        );
+        let mut l = labels(&[
+            ("__meta_kubernetes_pod_label_app", "web"),
+            ("__meta_kubernetes_namespace", "default"),
+            ("instance", "a"),
+        ]);
+        assert!(relabel(&mut l, &configs));
+        assert_eq!(
+            l,
+            labels(&[("app", "web"), ("app_upper", "WEB"), ("instance", "a")])
+        );
+
+        let keep = self::configs("[{regex: 'instance|job', action: labelkeep}]");
+        let mut l = labels(&[("instance", "a"), ("job", "b"), ("env", "c")]);
+        assert!(relabel(&mut l, &keep));
+        assert_eq!(l, labels(&[("instance", "a"), ("job", "b")]));
+    }
+
+    #[test]
+    fn test_relabel_validate() {
+        let invalid = [
+            "{source_labels: [a], action: replace}",
+            "{source_labels: [a], target_label: b, action: hashmod}",
+            "{regex: a, target_label: b, action: labeldrop}",
+            "{source_labels: [a], target_label: '1x', action: lowercase}",
+        ];
+        for yaml in invalid {
+            let config: RelabelConfig = serde_yaml_ng::from_str(yaml).unwrap();
+            assert!(config.validate().is_err(), "{yaml}");
+        }
+        assert!(serde_yaml_ng::from_str::<RelabelConfig>("{regex: '(', action: drop}").is_err());
+        assert!(serde_yaml_ng::from_str::<RelabelConfig>("{action: rewrite}").is_err());
+    }
+}
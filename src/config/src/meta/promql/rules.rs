@@ -0,0 +1,708 @@
+// Copyright 2026 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Prometheus rule groups: the rule file format accepted by the importer, and
+//! the `/api/v1/rules` and `/api/v1/alerts` response bodies.
+//!
+//! cf. https://prometheus.io/docs/prometheus/latest/configuration/recording_rules/
+
+use std::{collections::BTreeMap, sync::LazyLock};
+
+use chrono::{DateTime, SecondsFormat};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::utils::time::parse_milliseconds;
+
+/// KV key prefix rule groups are stored under, as `{prefix}{namespace}/{group}`.
+pub const RULES_KV_PREFIX: &str = "prometheus_rules/";
+/// KV key prefix of the recording rule evaluation errors of a group, same
+/// layout as [`RULES_KV_PREFIX`]. Written only when a group's health changes.
+pub const RULES_HEALTH_KV_PREFIX: &str = "prometheus_rules_health/";
+
+/// The value Prometheus reports for a timestamp that was never set.
+const ZERO_TIME: &str = "0001-01-01T00:00:00Z";
+
+static RE_METRIC_NAME: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^[a-zA-Z_:][a-zA-Z0-9_:]*$").unwrap());
+static RE_LABEL_NAME: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^[a-zA-Z_][a-zA-Z0-9_]*$").unwrap());
+static RE_NAMESPACE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^[a-zA-Z0-9_.\-]+$").unwrap());
+
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum RuleError {
+    #[error("invalid rule file: {0}")]
+    Yaml(String),
+    #[error("invalid namespace {0:?}: only letters, digits, '_', '.' and '-' are allowed")]
+    Namespace(String),
+    #[error("groupname must not be empty")]
+    GroupNameMissing,
+    #[error("groupname: {0:?} is repeated in the same file")]
+    GroupRepeated(String),
+    #[error("group {group:?}: {reason}")]
+    Group { group: String, reason: String },
+    #[error("group {group:?}, rule {index}, {name:?}: {reason}")]
+    Rule {
+        group: String,
+        /// 1-based, like `promtool check rules`
+        index: usize,
+        name: String,
+        reason: String,
+    },
+}
+
+impl RuleError {
+    pub fn rule(group: &RuleGroup, index: usize, reason: impl ToString) -> Self {
+        RuleError::Rule {
+            group: group.name.clone(),
+            index: index + 1,
+            name: group.rules[index].name().to_string(),
+            reason: reason.to_string(),
+        }
+    }
+}
+
+/// A Prometheus rule file.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RuleGroups {
+    pub groups: Vec<RuleGroup>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RuleGroup {
+    pub name: String,
+    /// Evaluation interval, a Prometheus duration. Defaults to
+    /// `ZO_PROMETHEUS_RULES_EVALUATION_INTERVAL`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub interval: Option<String>,
+    /// Max series a recording rule may produce; 0 is unlimited.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub limit: usize,
+    /// Labels added to every rule of the group.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub labels: BTreeMap<String, String>,
+    #[serde(default)]
+    pub rules: Vec<Rule>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Rule {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub record: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alert: Option<String>,
+    pub expr: String,
+    #[serde(default, rename = "for", skip_serializing_if = "Option::is_none")]
+    pub for_: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keep_firing_for: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub labels: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub annotations: BTreeMap<String, String>,
+}
+
+fn is_zero(v: &usize) -> bool {
+    *v == 0
+}
+
+/// Parses a rule file. Besides Prometheus' `groups:` document a bare group is
+/// accepted, which is what the Cortex/Mimir ruler API posts per namespace.
+///
+/// Only the structure is validated here; expressions are checked by the
+/// caller, which owns the PromQL parser.
+pub fn parse_rule_groups(body: &str) -> Result<Vec<RuleGroup>, RuleError> {
+    let doc: serde_yaml_ng::Value =
+        serde_yaml_ng::from_str(body).map_err(|e| RuleError::Yaml(e.to_string()))?;
+    let groups = if doc.get("groups").is_some() {
+        serde_yaml_ng::from_value::<RuleGroups>(doc)
+            .map_err(|e| RuleError::Yaml(e.to_string()))?
+            .groups
+    } else {
+        vec![
+            serde_yaml_ng::from_value::<RuleGroup>(doc)
+                .map_err(|e| RuleError::Yaml(e.to_string()))?,
+        ]
+    };
+
+    let mut seen = hashbrown::HashSet::with_capacity(groups.len());
+    for group in groups.iter() {
+        if !seen.insert(group.name.as_str()) {
+            return Err(RuleError::GroupRepeated(group.name.clone()));
+        }
+        group.validate()?;
+    }
+    Ok(groups)
+}
+
+/// Parses a Prometheus duration such as `1h30m` into seconds.
+pub fn parse_duration(s: &str) -> Result<u64, String> {
+    let s = s.trim();
+    if s.is_empty() {
+        return Err("empty duration string".to_string());
+    }
+    // a bare number is seconds for `parse_milliseconds`, but not valid here
+    if s.chars().all(|c| c.is_ascii_digit()) && s != "0" {
+        return Err(format!("not a valid duration string: {s:?}"));
+    }
+    parse_milliseconds(s)
+        .map(|ms| ms / 1000)
+        .map_err(|_| format!("not a valid duration string: {s:?}"))
+}
+
+pub fn validate_namespace(namespace: &str) -> Result<(), RuleError> {
+    if RE_NAMESPACE.is_match(namespace) {
+        Ok(())
+    } else {
+        Err(RuleError::Namespace(namespace.to_string()))
+    }
+}
+
+/// KV key of a stored group, relative to `prefix`.
+pub fn kv_key(prefix: &str, namespace: &str, group: &str) -> String {
+    format!("{prefix}{namespace}/{}", urlencoding::encode(group))
+}
+
+impl RuleGroup {
+    pub fn validate(&self) -> Result<(), RuleError> {
+        if self.name.trim().is_empty() {
+            return Err(RuleError::GroupNameMissing);
+        }
+        let group_err = |reason: String| RuleError::Group {
+            group: self.name.clone(),
+            reason,
+        };
+        if let Some(interval) = self.interval.as_deref() {
+            match parse_duration(interval) {
+                Ok(0) => return Err(group_err("interval must be greater than 0".to_string())),
+                Ok(_) => {}
+                Err(e) => return Err(group_err(e)),
+            }
+        }
+        for name in self.labels.keys() {
+            if !is_valid_label_name(name) {
+                return Err(group_err(format!("invalid label name: {name}")));
+            }
+        }
+        for (i, rule) in self.rules.iter().enumerate() {
+            rule.validate()
+                .map_err(|reason| RuleError::rule(self, i, reason))?;
+        }
+        Ok(())
+    }
+
+    /// Evaluation interval in seconds, `default` when the group sets none.
+    pub fn interval_secs(&self, default: u64) -> u64 {
+        self.interval
+            .as_deref()
+            .and_then(|v| parse_duration(v).ok())
+            .filter(|v| *v > 0)
+            .unwrap_or(default)
+    }
+
+    /// Names of the alerts backing the alerting rules, by rule index.
+    ///
+    /// Alert names must be unique and free of the characters the alert model
+    /// rejects, while Prometheus routinely reuses an alert name within a group
+    /// for different severities, so repeats get a `-2`, `-3`... suffix.
+    pub fn alert_names(&self, namespace: &str) -> Vec<Option<String>> {
+        let mut seen: hashbrown::HashMap<String, usize> = hashbrown::HashMap::new();
+        self.rules
+            .iter()
+            .map(|rule| {
+                let alert = rule.alert.as_deref()?;
+                let name = sanitize_name(&format!("{namespace}-{}-{alert}", self.name));
+                let n = seen.entry(name.clone()).or_default();
+                *n += 1;
+                Some(if *n == 1 { name } else { format!("{name}-{n}") })
+            })
+            .collect()
+    }
+
+    /// The rule's labels with the group's labels underneath, as Prometheus
+    /// merges them.
+    pub fn rule_labels(&self, rule: &Rule) -> BTreeMap<String, String> {
+        let mut labels = self.labels.clone();
+        labels.extend(rule.labels.clone());
+        labels
+    }
+}
+
+fn sanitize_name(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+pub fn is_valid_label_name(name: &str) -> bool {
+    name != super::NAME_LABEL && RE_LABEL_NAME.is_match(name)
+}
+
+impl Rule {
+    pub fn name(&self) -> &str {
+        self.record
+            .as_deref()
+            .or(self.alert.as_deref())
+            .unwrap_or_default()
+    }
+
+    pub fn is_alerting(&self) -> bool {
+        self.alert.is_some()
+    }
+
+    pub fn for_secs(&self) -> u64 {
+        self.for_
+            .as_deref()
+            .and_then(|v| parse_duration(v).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn keep_firing_for_secs(&self) -> u64 {
+        self.keep_firing_for
+            .as_deref()
+            .and_then(|v| parse_duration(v).ok())
+            .unwrap_or_default()
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        match (&self.record, &self.alert) {
+            (Some(_), Some(_)) => {
+                return Err("only one of 'record' and 'alert' must be set".to_string());
+            }
+            (None, None) => return Err("one of 'record' or 'alert' must be set".to_string()),
+            (Some(record), None) => {
+                if !RE_METRIC_NAME.is_match(record) {
+                    return Err(format!("invalid recording rule name: {record}"));
+                }
+                if !self.annotations.is_empty() {
+                    return Err("invalid field 'annotations' in recording rule".to_string());
+                }
+                if self.for_.is_some() {
+                    return Err("invalid field 'for' in recording rule".to_string());
+                }
+                if self.keep_firing_for.is_some() {
+                    return Err("invalid field 'keep_firing_for' in recording rule".to_string());
+                }
+            }
+            (None, Some(alert)) => {
+                if alert.trim().is_empty() {
+                    return Err("field 'alert' must be set in rule".to_string());
+                }
+            }
+        }
+        if self.expr.trim().is_empty() {
+            return Err("field 'expr' must be set in rule".to_string());
+        }
+        for v in [&self.for_, &self.keep_firing_for].into_iter().flatten() {
+            parse_duration(v)?;
+        }
+        for name in self.labels.keys() {
+            if !is_valid_label_name(name) {
+                return Err(format!("invalid label name: {name}"));
+            }
+        }
+        for name in self.annotations.keys() {
+            if !RE_LABEL_NAME.is_match(name) {
+                return Err(format!("invalid annotation name: {name}"));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The expression an alerting rule is evaluated as.
+///
+/// Alerts are evaluated statelessly, so `for` and `keep_firing_for` are
+/// expressed in the query: a series fires once it was returned at every
+/// group interval of the last `for`, and keeps firing while it did so at any
+/// interval of the last `keep_firing_for`. Both are exact up to one interval,
+/// which is also Prometheus' own granularity.
+pub fn alert_expr(
+    expr: &str,
+    for_secs: u64,
+    keep_firing_for_secs: u64,
+    interval_secs: u64,
+) -> String {
+    let interval_secs = interval_secs.max(1);
+    let mut query = format!("({})", expr.trim());
+    if for_secs > 0 {
+        let points = for_secs.div_ceil(interval_secs);
+        query = format!(
+            "{query} and (count_over_time({query}[{for_secs}s:{interval_secs}s]) >= {points})"
+        );
+    }
+    if keep_firing_for_secs > 0 {
+        query = format!("last_over_time(({query})[{keep_firing_for_secs}s:{interval_secs}s])");
+    }
+    query
+}
+
+/// A rule group as stored, with the alerts created for its alerting rules.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct StoredRuleGroup {
+    pub namespace: String,
+    pub group: RuleGroup,
+    /// Id of the alert backing each rule, by rule index. `None` for recording
+    /// rules and for alerting rules whose alert could not be saved.
+    #[serde(default)]
+    pub alert_ids: Vec<Option<String>>,
+}
+
+/// Recording rule evaluation errors of a group, by rule index.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RuleGroupHealth {
+    #[serde(default)]
+    pub errors: BTreeMap<usize, String>,
+}
+
+/// Query parameters of `/api/v1/rules`.
+///
+/// Parsed from the raw query string because the `[]` parameters repeat.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RulesFilter {
+    /// `alert` or `record`
+    pub rule_type: Option<String>,
+    pub rule_names: Vec<String>,
+    pub rule_groups: Vec<String>,
+    pub files: Vec<String>,
+    pub exclude_alerts: bool,
+}
+
+impl RulesFilter {
+    pub fn from_query(query: &str) -> Self {
+        let mut filter = RulesFilter::default();
+        for (k, v) in url::form_urlencoded::parse(query.as_bytes()) {
+            match k.as_ref() {
+                "type" => filter.rule_type = Some(v.into_owned()),
+                "rule_name[]" => filter.rule_names.push(v.into_owned()),
+                "rule_group[]" => filter.rule_groups.push(v.into_owned()),
+                "file[]" => filter.files.push(v.into_owned()),
+                "exclude_alerts" => filter.exclude_alerts = v == "true",
+                _ => {}
+            }
+        }
+        filter
+    }
+
+    pub fn matches_group(&self, namespace: &str, group: &str) -> bool {
+        (self.files.is_empty() || self.files.iter().any(|f| f == namespace))
+            && (self.rule_groups.is_empty() || self.rule_groups.iter().any(|g| g == group))
+    }
+
+    pub fn matches_rule(&self, rule: &Rule) -> bool {
+        let type_matches = match self.rule_type.as_deref() {
+            Some("alert") => rule.is_alerting(),
+            Some("record") => !rule.is_alerting(),
+            _ => true,
+        };
+        type_matches
+            && (self.rule_names.is_empty() || self.rule_names.iter().any(|n| n == rule.name()))
+    }
+}
+
+/// Body of `/api/v1/rules`.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RuleDiscovery {
+    pub groups: Vec<RuleGroupStatus>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuleGroupStatus {
+    pub name: String,
+    pub file: String,
+    pub rules: Vec<RuleStatus>,
+    /// seconds
+    pub interval: f64,
+    pub limit: usize,
+    pub evaluation_time: f64,
+    pub last_evaluation: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum RuleStatus {
+    Alerting(AlertingRuleStatus),
+    Recording(RecordingRuleStatus),
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AlertingRuleStatus {
+    /// `firing` or `inactive`
+    pub state: String,
+    pub name: String,
+    pub query: String,
+    /// `for`, seconds
+    pub duration: f64,
+    /// seconds
+    pub keep_firing_for: f64,
+    pub labels: BTreeMap<String, String>,
+    pub annotations: BTreeMap<String, String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub alerts: Option<Vec<ActiveAlert>>,
+    pub health: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub last_error: String,
+    pub evaluation_time: f64,
+    pub last_evaluation: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordingRuleStatus {
+    pub name: String,
+    pub query: String,
+    pub labels: BTreeMap<String, String>,
+    pub health: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub last_error: String,
+    pub evaluation_time: f64,
+    pub last_evaluation: String,
+}
+
+/// Body of `/api/v1/alerts`.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct AlertDiscovery {
+    pub alerts: Vec<ActiveAlert>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActiveAlert {
+    pub labels: BTreeMap<String, String>,
+    pub annotations: BTreeMap<String, String>,
+    pub state: String,
+    pub active_at: String,
+    /// The value is not kept in the alert state, so it is left out.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+}
+
+/// RFC 3339 form of a microsecond timestamp, Prometheus' zero time for `None`.
+pub fn format_time(micros: Option<i64>) -> String {
+    micros
+        .and_then(DateTime::from_timestamp_micros)
+        .map(|t| t.to_rfc3339_opts(SecondsFormat::Millis, true))
+        .unwrap_or_else(|| ZERO_TIME.to_string())
+}
+
+/// Reads back the `k=v,k=v` display form alerts keep a series' labels in.
+///
+/// The form is ambiguous for values containing `,`; a part without `=` is
+/// taken as the continuation of the previous value, which recovers every
+/// value that does not also contain `=`.
+pub fn parse_rendered_labels(rendered: &str) -> BTreeMap<String, String> {
+    let mut labels = BTreeMap::new();
+    let mut last: Option<String> = None;
+    for part in rendered.split(',').filter(|p| !p.is_empty()) {
+        match part.split_once('=') {
+            Some((k, v)) if RE_LABEL_NAME.is_match(k) => {
+                labels.insert(k.to_string(), v.to_string());
+                last = Some(k.to_string());
+            }
+            _ => {
+                if let Some(value) = last.as_ref().and_then(|k| labels.get_mut(k)) {
+                    value.push(',');
+                    value.push_str(part);
+                }
+            }
+        }
+    }
+    labels
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RULE_FILE: &str = r#"
+groups:
+  - name: example
+    interval: 30s
+    labels:
+      team: infra
+    rules:
+      - record: job:http_inprogress_requests:sum
+        expr: sum by (job) (http_inprogress_requests)
+      - alert: HighRequestLatency
+        expr: job:request_latency_seconds:mean5m{job="myjob"} > 0.5
+        for: 10m
+        keep_firing_for: 5m
+        labels:
+          severity: page
+        annotations:
+          summary: High request latency
+      - alert: HighRequestLatency
+        expr: job:request_latency_seconds:mean5m{job="myjob"} > 1
+        labels:
+          severity: critical
+"#;
+
+    #[test]
+    fn test_parse_rule_file() {
+        let groups = parse_rule_groups(RULE_FILE).unwrap();
+        assert_eq!(groups.len(), 1);
+        let group = &groups[0];
+        assert_eq!(group.interval_secs(60), 30);
+        assert_eq!(group.rules.len(), 3);
+        assert!(!group.rules[0].is_alerting());
+        assert_eq!(group.rules[0].name(), "job:http_inprogress_requests:sum");
+        assert_eq!(group.rules[1].for_secs(), 600);
+        assert_eq!(group.rules[1].keep_firing_for_secs(), 300);
+        assert_eq!(
+            group.rule_labels(&group.rules[1]),
+            BTreeMap::from([
+                ("severity".to_string(), "page".to_string()),
+                ("team".to_string(), "infra".to_string()),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_parse_bare_group() {
+        let groups =
+            parse_rule_groups("name: g\nrules:\n  - record: a:b\n    expr: sum(up)\n").unwrap();
+        assert_eq!(groups[0].name, "g");
+        assert_eq!(groups[0].interval_secs(60), 60);
+    }
+
+    #[test]
+    fn test_parse_rule_file_errors() {
+        let repeated = "groups:\n  - name: g\n    rules: []\n  - name: g\n    rules: []\n";
+        assert_eq!(
+            parse_rule_groups(repeated),
+            Err(RuleError::GroupRepeated("g".to_string()))
+        );
+
+        let both = "groups:\n  - name: g\n    rules:\n      - record: a\n        alert: b\n        expr: up\n";
+        assert_eq!(
+            parse_rule_groups(both).unwrap_err().to_string(),
+            r#"group "g", rule 1, "a": only one of 'record' and 'alert' must be set"#
+        );
+
+        let bad_name = "groups:\n  - name: g\n    rules:\n      - record: a-b\n        expr: up\n";
+        assert!(parse_rule_groups(bad_name).is_err());
+
+        let for_on_record = "groups:\n  - name: g\n    rules:\n      - record: a\n        expr: up\n        for: 1m\n";
+        assert!(parse_rule_groups(for_on_record).is_err());
+
+        let bad_for = "groups:\n  - name: g\n    rules:\n      - alert: a\n        expr: up\n        for: 5\n";
+        assert!(parse_rule_groups(bad_for).is_err());
+
+        let typo = "groups:\n  - name: g\n    rules:\n      - alert: a\n        expr: up\n        fro: 5m\n";
+        assert!(matches!(parse_rule_groups(typo), Err(RuleError::Yaml(_))));
+
+        let name_label = "groups:\n  - name: g\n    rules:\n      - alert: a\n        expr: up\n        labels:\n          __name__: x\n";
+        assert!(parse_rule_groups(name_label).is_err());
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("0"), Ok(0));
+        assert_eq!(parse_duration("90s"), Ok(90));
+        assert_eq!(parse_duration("1h30m"), Ok(5400));
+        assert!(parse_duration("10").is_err());
+        assert!(parse_duration("").is_err());
+        assert!(parse_duration("1x").is_err());
+    }
+
+    #[test]
+    fn test_alert_names() {
+        let group = &parse_rule_groups(RULE_FILE).unwrap()[0];
+        assert_eq!(
+            group.alert_names("my ns"),
+            vec![
+                None,
+                Some("my_ns-example-HighRequestLatency".to_string()),
+                Some("my_ns-example-HighRequestLatency-2".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_alert_expr() {
+        assert_eq!(alert_expr("up == 0", 0, 0, 60), "(up == 0)");
+        assert_eq!(
+            alert_expr("up == 0", 300, 0, 60),
+            "(up == 0) and (count_over_time((up == 0)[300s:60s]) >= 5)"
+        );
+        assert_eq!(
+            alert_expr("up == 0", 90, 120, 60),
+            "last_over_time(((up == 0) and (count_over_time((up == 0)[90s:60s]) >= 2))[120s:60s])"
+        );
+    }
+
+    #[test]
+    fn test_rules_filter() {
+        let filter = RulesFilter::from_query(
+            "type=alert&rule_name[]=a&rule_name%5B%5D=b&file[]=ns&exclude_alerts=true",
+        );
+        assert_eq!(filter.rule_type.as_deref(), Some("alert"));
+        assert_eq!(filter.rule_names, vec!["a", "b"]);
+        assert!(filter.exclude_alerts);
+        assert!(filter.matches_group("ns", "any"));
+        assert!(!filter.matches_group("other", "any"));
+
+        let alert = Rule {
+            alert: Some("b".to_string()),
+            expr: "up".to_string(),
+            ..Default::default()
+        };
+        let record = Rule {
+            record: Some("a".to_string()),
+            expr: "up".to_string(),
+            ..Default::default()
+        };
+        assert!(filter.matches_rule(&alert));
+        assert!(!filter.matches_rule(&record));
+    }
+
+    #[test]
+    fn test_parse_rendered_labels() {
+        assert_eq!(
+            parse_rendered_labels("host=a,path=/x,y,env=prod"),
+            BTreeMap::from([
+                ("env".to_string(), "prod".to_string()),
+                ("host".to_string(), "a".to_string()),
+                ("path".to_string(), "/x,y".to_string()),
+            ])
+        );
+        assert!(parse_rendered_labels("").is_empty());
+    }
+
+    #[test]
+    fn test_format_time() {
+        assert_eq!(format_time(None), ZERO_TIME);
+        assert_eq!(format_time(Some(1_500_000)), "1970-01-01T00:00:01.500Z");
+    }
+
+    #[test]
+    fn test_kv_key() {
+        assert_eq!(
+            kv_key(RULES_KV_PREFIX, "ns", "a/b c"),
+            "prometheus_rules/ns/a%2Fb%20c"
+        );
+        assert!(validate_namespace("team-a.rules_1").is_ok());
+        assert!(validate_namespace("a/b").is_err());
+    }
+}
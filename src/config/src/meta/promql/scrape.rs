@@ -0,0 +1,520 @@
+// Copyright 2026 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Scrape configuration of the built-in Prometheus scrape agent, the
+//! `scrape_configs` section of a Prometheus configuration file.
+//!
+//! cf. https://prometheus.io/docs/prometheus/latest/configuration/configuration/#scrape_config
+
+use std::collections::{BTreeMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use super::{relabel::RelabelConfig, rules::is_valid_label_name};
+use crate::utils::time::parse_milliseconds;
+
+const DEFAULT_SCRAPE_INTERVAL: &str = "1m";
+const DEFAULT_SCRAPE_TIMEOUT: &str = "10s";
+const DEFAULT_REFRESH_INTERVAL: &str = "5m";
+const DEFAULT_DNS_REFRESH_INTERVAL: &str = "30s";
+/// Scrape responses are read into memory, so unlike Prometheus an unset
+/// `body_size_limit` is not unlimited.
+const DEFAULT_BODY_SIZE_LIMIT: &str = "100MB";
+
+/// The parts of a Prometheus configuration file the scrape agent reads, every
+/// other section is ignored.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ScrapeFile {
+    #[serde(default)]
+    pub global: GlobalConfig,
+    #[serde(default)]
+    pub scrape_configs: Vec<ScrapeConfig>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GlobalConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scrape_interval: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scrape_timeout: Option<String>,
+    /// Labels added to every scraped series.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub external_labels: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ScrapeConfig {
+    pub job_name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scrape_interval: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scrape_timeout: Option<String>,
+    #[serde(default = "default_metrics_path")]
+    pub metrics_path: String,
+    #[serde(default = "default_scheme")]
+    pub scheme: String,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub params: BTreeMap<String, Vec<String>>,
+    #[serde(default)]
+    pub honor_labels: bool,
+    #[serde(default = "default_true")]
+    pub honor_timestamps: bool,
+    /// Max samples a scrape may return after metric relabeling, the scrape
+    /// fails above it. 0 is unlimited.
+    #[serde(default)]
+    pub sample_limit: usize,
+    /// Max uncompressed size of a scrape response, like `10MB`, the scrape
+    /// fails above it. `0` is unlimited.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body_size_limit: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub basic_auth: Option<BasicAuth>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub authorization: Option<Authorization>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub static_configs: Vec<StaticConfig>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub file_sd_configs: Vec<FileSdConfig>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dns_sd_configs: Vec<DnsSdConfig>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub kubernetes_sd_configs: Vec<KubernetesSdConfig>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub relabel_configs: Vec<RelabelConfig>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub metric_relabel_configs: Vec<RelabelConfig>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BasicAuth {
+    pub username: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password_file: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Authorization {
+    #[serde(default = "default_auth_type", rename = "type")]
+    pub type_: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub credentials: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub credentials_file: Option<String>,
+}
+
+/// A group of targets, as written in `static_configs` and `file_sd_configs`
+/// files.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct StaticConfig {
+    #[serde(default)]
+    pub targets: Vec<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub labels: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FileSdConfig {
+    /// JSON or YAML files with a list of [`StaticConfig`], `*` may be used in
+    /// the file name.
+    pub files: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refresh_interval: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum DnsRecordType {
+    #[default]
+    Srv,
+    A,
+    Aaaa,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DnsSdConfig {
+    pub names: Vec<String>,
+    #[serde(default, rename = "type")]
+    pub type_: DnsRecordType,
+    /// Port of the targets of `A` and `AAAA` records.
+    #[serde(default)]
+    pub port: u16,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refresh_interval: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KubernetesRole {
+    #[default]
+    Endpoints,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct KubernetesSdConfig {
+    pub role: KubernetesRole,
+    /// API server URL, the in-cluster configuration of the pod when not set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_server: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub namespaces: Option<KubernetesNamespaces>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bearer_token_file: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct KubernetesNamespaces {
+    #[serde(default)]
+    pub names: Vec<String>,
+}
+
+fn default_metrics_path() -> String {
+    "/metrics".to_string()
+}
+
+fn default_scheme() -> String {
+    "http".to_string()
+}
+
+fn default_auth_type() -> String {
+    "Bearer".to_string()
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Parses a duration, `field` names it in the error.
+fn duration_ms(value: Option<&str>, default: &str, field: &str) -> Result<u64, String> {
+    let value = value.unwrap_or(default);
+    match parse_milliseconds(value) {
+        Ok(0) | Err(_) => Err(format!("invalid {field} {value:?}")),
+        Ok(ms) => Ok(ms),
+    }
+}
+
+/// Parses a size like `512KB` or `10MB`, units are powers of 1024 as in
+/// Prometheus and a plain number is bytes.
+fn parse_size(value: &str) -> Result<u64, String> {
+    let value = value.trim();
+    let split = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+    let shift = match unit {
+        "" | "B" => 0,
+        "KB" | "KiB" => 10,
+        "MB" | "MiB" => 20,
+        "GB" | "GiB" => 30,
+        "TB" | "TiB" => 40,
+        _ => return Err(format!("invalid body_size_limit {value:?}")),
+    };
+    number
+        .parse::<u64>()
+        .ok()
+        .and_then(|n| n.checked_mul(1 << shift))
+        .ok_or_else(|| format!("invalid body_size_limit {value:?}"))
+}
+
+impl ScrapeConfig {
+    /// Max size of a scrape response in bytes, 0 is unlimited.
+    pub fn body_size_limit_bytes(&self) -> u64 {
+        parse_size(
+            self.body_size_limit
+                .as_deref()
+                .unwrap_or(DEFAULT_BODY_SIZE_LIMIT),
+        )
+        .unwrap_or_default()
+    }
+
+    /// Scrape interval in milliseconds.
+    pub fn interval_ms(&self, global: &GlobalConfig) -> u64 {
+        self.try_interval_ms(global).unwrap_or(60_000)
+    }
+
+    /// Scrape timeout in milliseconds, at most the interval.
+    pub fn timeout_ms(&self, global: &GlobalConfig) -> u64 {
+        let timeout = duration_ms(
+            self.scrape_timeout
+                .as_deref()
+                .or(global.scrape_timeout.as_deref()),
+            DEFAULT_SCRAPE_TIMEOUT,
+            "scrape_timeout",
+        )
+        .unwrap_or(10_000);
+        timeout.min(self.interval_ms(global))
+    }
+
+    fn try_interval_ms(&self, global: &GlobalConfig) -> Result<u64, String> {
+        duration_ms(
+            self.scrape_interval
+                .as_deref()
+                .or(global.scrape_interval.as_deref()),
+            DEFAULT_SCRAPE_INTERVAL,
+            "scrape_interval",
+        )
+    }
+
+    pub fn validate(&self, global: &GlobalConfig) -> Result<(), String> {
+        let err = |reason: String| Err(format!("job {:?}: {reason}", self.job_name));
+        if self.job_name.trim().is_empty() {
+            return Err("job_name is empty".to_string());
+        }
+        let interval = match self.try_interval_ms(global) {
+            Ok(v) => v,
+            Err(e) => return err(e),
+        };
+        let timeout = match duration_ms(
+            self.scrape_timeout
+                .as_deref()
+                .or(global.scrape_timeout.as_deref()),
+            DEFAULT_SCRAPE_TIMEOUT,
+            "scrape_timeout",
+        ) {
+            Ok(v) => v,
+            Err(e) => return err(e),
+        };
+        // the global timeout is capped by each job's interval, like in Prometheus
+        if self.scrape_timeout.is_some() && timeout > interval {
+            return err("scrape_timeout greater than scrape_interval".to_string());
+        }
+        if self.scheme != "http" && self.scheme != "https" {
+            return err(format!("unsupported scheme {:?}", self.scheme));
+        }
+        if !self.metrics_path.starts_with('/') {
+            return err(format!("invalid metrics_path {:?}", self.metrics_path));
+        }
+        if self.basic_auth.is_some() && self.authorization.is_some() {
+            return err("at most one of basic_auth and authorization must be set".to_string());
+        }
+        if let Some(limit) = self.body_size_limit.as_deref()
+            && let Err(e) = parse_size(limit)
+        {
+            return err(e);
+        }
+        for group in self.static_configs.iter() {
+            if let Err(e) = group.validate() {
+                return err(e);
+            }
+        }
+        for sd in self.file_sd_configs.iter() {
+            if sd.files.is_empty() {
+                return err("file_sd_configs without files".to_string());
+            }
+            if let Err(e) = duration_ms(
+                sd.refresh_interval.as_deref(),
+                DEFAULT_REFRESH_INTERVAL,
+                "refresh_interval",
+            ) {
+                return err(e);
+            }
+        }
+        for sd in self.dns_sd_configs.iter() {
+            if sd.names.is_empty() {
+                return err("dns_sd_configs without names".to_string());
+            }
+            if sd.type_ != DnsRecordType::Srv && sd.port == 0 {
+                return err(
+                    "a port is required in dns_sd_configs of A and AAAA records".to_string()
+                );
+            }
+            if let Err(e) = duration_ms(
+                sd.refresh_interval.as_deref(),
+                DEFAULT_DNS_REFRESH_INTERVAL,
+                "refresh_interval",
+            ) {
+                return err(e);
+            }
+        }
+        for config in self
+            .relabel_configs
+            .iter()
+            .chain(self.metric_relabel_configs.iter())
+        {
+            if let Err(e) = config.validate() {
+                return err(e);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl StaticConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        for target in self.targets.iter() {
+            if target.is_empty() || target.contains('/') {
+                return Err(format!("invalid target {target:?}, expected host:port"));
+            }
+        }
+        for name in self.labels.keys() {
+            if !is_valid_label_name(name) && !name.starts_with("__") {
+                return Err(format!("invalid label name: {name}"));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FileSdConfig {
+    pub fn refresh_interval_ms(&self) -> u64 {
+        duration_ms(
+            self.refresh_interval.as_deref(),
+            DEFAULT_REFRESH_INTERVAL,
+            "refresh_interval",
+        )
+        .unwrap_or(300_000)
+    }
+}
+
+impl DnsSdConfig {
+    pub fn refresh_interval_ms(&self) -> u64 {
+        duration_ms(
+            self.refresh_interval.as_deref(),
+            DEFAULT_DNS_REFRESH_INTERVAL,
+            "refresh_interval",
+        )
+        .unwrap_or(30_000)
+    }
+}
+
+/// Parses and validates a Prometheus configuration file.
+pub fn parse_scrape_file(body: &str) -> Result<ScrapeFile, String> {
+    let file: ScrapeFile = serde_yaml_ng::from_str(body).map_err(|e| e.to_string())?;
+    let mut seen = HashSet::with_capacity(file.scrape_configs.len());
+    for config in file.scrape_configs.iter() {
+        if !seen.insert(config.job_name.as_str()) {
+            return Err(format!(
+                "found multiple scrape configs with job name {:?}",
+                config.job_name
+            ));
+        }
+        config.validate(&file.global)?;
+    }
+    for name in file.global.external_labels.keys() {
+        if !is_valid_label_name(name) {
+            return Err(format!("invalid external label name: {name}"));
+        }
+    }
+    Ok(file)
+}
+
+/// Parses a `file_sd_configs` file, JSON or YAML.
+pub fn parse_target_groups(body: &str) -> Result<Vec<StaticConfig>, String> {
+    // JSON is valid YAML
+    let groups: Vec<StaticConfig> = serde_yaml_ng::from_str(body).map_err(|e| e.to_string())?;
+    for group in groups.iter() {
+        group.validate()?;
+    }
+    Ok(groups)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::meta::promql::relabel::RelabelAction;
+
+    #[test]
+    fn test_parse_scrape_file() {
+        let file = parse_scrape_file(
+            r#"
+global:
+  scrape_interval: 30s
+  external_labels:
+    site: edge-1
+rule_files: [rules.yml]
+scrape_configs:
+  - job_name: node
+    static_configs:
+      - targets: ["localhost:9100", "10.0.0.2:9100"]
+        labels:
+          env: prod
+    relabel_configs:
+      - source_labels: [__address__]
+        regex: "10\\..*"
+        action: drop
+  - job_name: api
+    scrape_interval: 10s
+    scrape_timeout: 5s
+    body_size_limit: 10MB
+    scheme: https
+    metrics_path: /internal/metrics
+    params:
+      format: [prometheus]
+    authorization:
+      credentials: secret
+    file_sd_configs:
+      - files: [/etc/targets/*.json]
+    dns_sd_configs:
+      - names: [_metrics._tcp.api.local]
+    kubernetes_sd_configs:
+      - role: endpoints
+        namespaces:
+          names: [default]
+"#,
+        )
+        .unwrap();
+        assert_eq!(file.scrape_configs.len(), 2);
+        let node = &file.scrape_configs[0];
+        assert_eq!(node.interval_ms(&file.global), 30_000);
+        assert_eq!(node.timeout_ms(&file.global), 10_000);
+        assert_eq!(node.metrics_path, "/metrics");
+        assert!(node.honor_timestamps);
+        assert_eq!(node.relabel_configs[0].action, RelabelAction::Drop);
+        let api = &file.scrape_configs[1];
+        assert_eq!(api.interval_ms(&file.global), 10_000);
+        assert_eq!(api.timeout_ms(&file.global), 5_000);
+        assert_eq!(api.body_size_limit_bytes(), 10 << 20);
+        assert_eq!(node.body_size_limit_bytes(), 100 << 20);
+        assert_eq!(api.authorization.as_ref().unwrap().type_, "Bearer");
+        assert_eq!(api.dns_sd_configs[0].type_, DnsRecordType::Srv);
+        assert_eq!(api.file_sd_configs[0].refresh_interval_ms(), 300_000);
+        assert_eq!(file.global.external_labels["site"], "edge-1");
+    }
+
+    #[test]
+    fn test_parse_scrape_file_invalid() {
+        let cases = [
+            "scrape_configs: [{job_name: a}, {job_name: a}]",
+            "scrape_configs: [{job_name: ''}]",
+            "scrape_configs: [{job_name: a, scrape_interval: 5s, scrape_timeout: 10s}]",
+            "scrape_configs: [{job_name: a, scrape_interval: soon}]",
+            "scrape_configs: [{job_name: a, scheme: ftp}]",
+            "scrape_configs: [{job_name: a, body_size_limit: 10XB}]",
+            "scrape_configs: [{job_name: a, static_configs: [{targets: ['http://a:80/metrics']}]}]",
+            "scrape_configs: [{job_name: a, dns_sd_configs: [{names: [a.local], type: A}]}]",
+            "scrape_configs: [{job_name: a, kubernetes_sd_configs: [{role: pod}]}]",
+            "scrape_configs: [{job_name: a, relabel_configs: [{action: hashmod, target_label: x}]}]",
+        ];
+        for yaml in cases {
+            assert!(parse_scrape_file(yaml).is_err(), "{yaml}");
+        }
+    }
+
+    #[test]
+    fn test_parse_target_groups() {
+        let groups = parse_target_groups(
+            r#"[{"targets": ["a:80", "b:80"], "labels": {"team": "core"}}, {"targets": ["c:80"]}]"#,
+        )
+        .unwrap();
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].targets, vec!["a:80", "b:80"]);
+        assert_eq!(groups[0].labels["team"], "core");
+
+        let groups = parse_target_groups("- targets: [c:80]\n  labels: {team: edge}\n").unwrap();
+        assert_eq!(groups[0].targets, vec!["c:80"]);
+        assert!(parse_target_groups("- targets: ['a:80/x']").is_err());
+    }
+}
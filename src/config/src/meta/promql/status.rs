@@ -0,0 +1,128 @@
+// Copyright 2026 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//! Request and response bodies of the `/api/v1/status/tsdb` cardinality
+//! statistics and the series churn endpoint.
+//!
+//! cf. https://prometheus.io/docs/prometheus/latest/querying/api/#tsdb-stats
+
+use serde::{Deserialize, Serialize};
+
+/// Default number of entries of each top list.
+pub const DEFAULT_STATUS_LIMIT: usize = 10;
+
+/// Request cardinality statistics over a time range.
+#[derive(Debug, Deserialize)]
+pub struct RequestTsdbStatus {
+    /// Number of entries of each top list, 10 by default.
+    pub limit: Option<usize>,
+    /// Start timestamp, 24 hours before `end` by default.
+    pub start: Option<String>,
+    /// End timestamp, now by default.
+    pub end: Option<String>,
+}
+
+/// Request the new series per hour of each metric over a time range.
+#[derive(Debug, Deserialize)]
+pub struct RequestSeriesChurn {
+    /// Number of metrics to return, by descending churn, 10 by default.
+    pub limit: Option<usize>,
+    /// Start timestamp, 24 hours before `end` by default.
+    pub start: Option<String>,
+    /// End timestamp, now by default.
+    pub end: Option<String>,
+    /// Only return the churn of this metric.
+    pub metric: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TsdbStatus {
+    pub head_stats: HeadStats,
+    pub series_count_by_metric_name: Vec<StatusEntry>,
+    pub label_value_count_by_label_name: Vec<StatusEntry>,
+    pub memory_in_bytes_by_label_name: Vec<StatusEntry>,
+    pub series_count_by_label_value_pair: Vec<StatusEntry>,
+    /// Files in the range without a metrics index. Their series are not
+    /// counted, so the statistics are a lower bound when this is not zero.
+    pub unindexed_files: usize,
+}
+
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HeadStats {
+    pub num_series: u64,
+    pub num_label_pairs: u64,
+    /// Earliest sample of the counted files, in milliseconds.
+    pub min_time: i64,
+    /// Latest sample of the counted files, in milliseconds.
+    pub max_time: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct StatusEntry {
+    pub name: String,
+    pub value: u64,
+}
+
+impl StatusEntry {
+    /// The `limit` entries with the largest values, ties ordered by name.
+    pub fn top(entries: impl IntoIterator<Item = (String, u64)>, limit: usize) -> Vec<Self> {
+        let mut entries = entries
+            .into_iter()
+            .map(|(name, value)| Self { name, value })
+            .collect::<Vec<_>>();
+        entries.sort_by(|a, b| b.value.cmp(&a.value).then_with(|| a.name.cmp(&b.name)));
+        entries.truncate(limit);
+        entries
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct SeriesChurn {
+    pub metric: String,
+    /// Series first seen in the range. Series of the first hour all count as
+    /// new.
+    pub new_series: u64,
+    pub hours: Vec<SeriesChurnHour>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SeriesChurnHour {
+    /// Hour start, in seconds.
+    pub time: i64,
+    pub new_series: u64,
+    /// Distinct series present in the hour.
+    pub series: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn top_sorts_by_value_then_name_and_truncates() {
+        let top = StatusEntry::top(
+            [
+                ("b".to_string(), 3),
+                ("a".to_string(), 3),
+                ("c".to_string(), 7),
+                ("d".to_string(), 1),
+            ],
+            3,
+        );
+        let names = top.iter().map(|e| e.name.as_str()).collect::<Vec<_>>();
+        assert_eq!(names, ["c", "a", "b"]);
+    }
+}
@@ -28,6 +28,7 @@ use serde::{
     ser::{SerializeSeq, SerializeStruct, Serializer},
 };
 
+pub use super::histogram::{HistogramSample, NativeHistogram};
 use crate::{
     FxIndexMap,
     meta::{promql::NAME_LABEL, search::SearchEventType},
@@ -355,6 +356,8 @@ impl From<&json::Map<String, json::Value>> for Exemplar {
 pub struct InstantValue {
     pub labels: Labels,
     pub sample: Sample,
+    /// Set for native histogram samples, which are served instead of `sample`
+    pub histogram: Option<HistogramSample>,
 }
 
 impl Serialize for InstantValue {
@@ -369,7 +372,10 @@ impl Serialize for InstantValue {
             .map(|l| (l.name.as_str(), l.value.as_str()))
             .collect::<FxIndexMap<_, _>>();
         seq.serialize_field("metric", &labels_map)?;
-        seq.serialize_field("value", &self.sample)?;
+        match &self.histogram {
+            Some(histogram) => seq.serialize_field("histogram", histogram)?,
+            None => seq.serialize_field("value", &self.sample)?,
+        }
         seq.end()
     }
 }
@@ -446,6 +452,26 @@ pub struct QueryContext {
     pub regions: Vec<String>,
     pub clusters: Vec<String>,
     pub is_super_cluster: bool,
+    /// Restricts series selection to one shard of the `__hash__` space.
+    pub shard: Option<SeriesShard>,
+}
+
+/// One slice of the series hash space, used to split a query horizontally
+/// across queriers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SeriesShard {
+    pub index: u64,
+    pub count: u64,
+}
+
+impl SeriesShard {
+    pub fn new(index: u64, count: u64) -> Option<Self> {
+        (count > 1 && index < count).then_some(Self { index, count })
+    }
+
+    pub fn contains(&self, hash: u64) -> bool {
+        hash % self.count == self.index
+    }
 }
 
 #[derive(Debug, Default, Clone)]
@@ -454,6 +480,8 @@ pub struct RangeValue {
     pub samples: Vec<Sample>,
     pub exemplars: Option<Vec<Arc<Exemplar>>>,
     pub time_window: Option<TimeWindow>,
+    /// Native histogram samples, kept apart from the float `samples`
+    pub histograms: Vec<HistogramSample>,
 }
 
 impl RangeValue {
@@ -466,6 +494,9 @@ impl RangeValue {
         if !other.samples.is_empty() {
             self.samples.extend(other.samples);
         }
+        if !other.histograms.is_empty() {
+            self.histograms.extend(other.histograms);
+        }
         // check exemplars
         if let Some(exemplars) = other.exemplars
             && !exemplars.is_empty()
@@ -504,7 +535,12 @@ impl Serialize for RangeValue {
                     .map(|l| (l.name.as_str(), l.value.as_str()))
                     .collect::<FxIndexMap<_, _>>();
                 seq.serialize_field("metric", &labels_map)?;
-                seq.serialize_field("values", &self.samples)?;
+                if self.histograms.is_empty() || !self.samples.is_empty() {
+                    seq.serialize_field("values", &self.samples)?;
+                }
+                if !self.histograms.is_empty() {
+                    seq.serialize_field("histograms", &self.histograms)?;
+                }
                 seq.end()
             }
         }
@@ -562,6 +598,7 @@ impl<'de> Deserialize<'de> for RangeValue {
                     samples: samples.unwrap_or_default(),
                     exemplars,
                     time_window: None,
+                    histograms: vec![],
                 })
             }
         }
@@ -580,6 +617,7 @@ impl RangeValue {
             samples: Vec::from_iter(samples),
             exemplars: None,
             time_window: None,
+            histograms: vec![],
         }
     }
 
@@ -592,6 +630,7 @@ impl RangeValue {
             samples: vec![],
             exemplars: Some(Vec::from_iter(exemplars)),
             time_window: None,
+            histograms: vec![],
         }
     }
 }
@@ -689,30 +728,107 @@ pub fn extrapolated_rate(
         }
     }
 
-    // Duration between first/last samples and boundary of range.
-    let mut duration_to_start = (first.timestamp - start) as f64 / 1_000.0;
-    let duration_to_end = (end - last.timestamp) as f64 / 1_000.0;
-
     let sampled_interval = (last.timestamp - first.timestamp) as f64 / 1_000.0;
-    let avg_duration_between_samples = sampled_interval / (samples.len() - 1) as f64;
-
-    if is_counter && result > 0.0 && first.value >= 0.0 {
-        // Counters cannot be negative. If we have any slope at all
-        // (i.e. `result` went up), we can extrapolate the zero point
-        // of the counter. If the duration to the zero point is shorter
-        // than the `duration_to_start`, we take the zero point as the start
-        // of the series, thereby avoiding extrapolation to negative
-        // counter values.
-        let duration_to_zero = sampled_interval * (first.value / result);
-        if duration_to_zero < duration_to_start {
-            duration_to_start = duration_to_zero;
+    // Counters cannot be negative. If we have any slope at all
+    // (i.e. `result` went up), we can extrapolate the zero point
+    // of the counter. If the duration to the zero point is shorter
+    // than the duration to the start, we take the zero point as the start
+    // of the series, thereby avoiding extrapolation to negative
+    // counter values.
+    let duration_to_zero = (is_counter && result > 0.0 && first.value >= 0.0)
+        .then(|| sampled_interval * (first.value / result));
+    let factor = extrapolation_factor(
+        start,
+        end,
+        first.timestamp,
+        last.timestamp,
+        samples.len(),
+        duration_to_zero,
+    );
+    if matches!(kind, ExtrapolationKind::Rate) {
+        result *= factor / range.as_secs_f64();
+    } else {
+        result *= factor;
+    }
+
+    Some(result)
+}
+
+/// [`extrapolated_rate`] over native histogram samples. Buckets are reduced to the
+/// coarsest schema seen in the window; `None` if there are fewer than two samples or
+/// their layouts cannot be combined.
+///
+/// Unlike float counters, there is no zero point adjustment: every bucket would need
+/// its own, so the window is always extrapolated to its boundaries.
+pub fn extrapolated_histogram_rate(
+    samples: &[HistogramSample],
+    eval_ts: i64,
+    range: Duration,
+    kind: ExtrapolationKind,
+) -> Option<NativeHistogram> {
+    if samples.len() < 2 {
+        return None;
+    }
+    let range_micros: i64 = range
+        .as_micros()
+        .try_into()
+        .expect("BUG: integer conversion failed");
+    let first = &samples[0];
+    let last = samples.last().unwrap();
+
+    let mut result = last.histogram.sub(&first.histogram)?;
+    if matches!(kind, ExtrapolationKind::Rate | ExtrapolationKind::Increase) {
+        for pair in samples.windows(2) {
+            if pair[1].histogram.detect_reset(&pair[0].histogram) {
+                result = result.add(&pair[0].histogram)?;
+            }
         }
     }
 
-    // If the first/last samples are close to the boundaries of the range,
-    // extrapolate the result. This is as we expect that another sample
-    // will exist given the spacing between samples we've seen thus far,
-    // with an allowance for noise.
+    let factor = extrapolation_factor(
+        eval_ts - range_micros,
+        eval_ts,
+        first.timestamp,
+        last.timestamp,
+        samples.len(),
+        None,
+    );
+    Some(if matches!(kind, ExtrapolationKind::Rate) {
+        result.mul(factor / range.as_secs_f64())
+    } else {
+        result.mul(factor)
+    })
+}
+
+/// The factor by which the change between the first and last sample is scaled to
+/// cover the range `[start, end]`.
+///
+/// If the first/last samples are close to the boundaries of the range, the result
+/// is extrapolated to them. This is as we expect that another sample will exist
+/// given the spacing between samples we've seen thus far, with an allowance for
+/// noise. `duration_to_zero` (in milliseconds) caps the extrapolation towards the
+/// start.
+fn extrapolation_factor(
+    start: i64,
+    end: i64,
+    first_ts: i64,
+    last_ts: i64,
+    len: usize,
+    duration_to_zero: Option<f64>,
+) -> f64 {
+    // Duration between first/last samples and boundary of range.
+    let mut duration_to_start = (first_ts - start) as f64 / 1_000.0;
+    let duration_to_end = (end - last_ts) as f64 / 1_000.0;
+
+    let sampled_interval = (last_ts - first_ts) as f64 / 1_000.0;
+    let avg_duration_between_samples = sampled_interval / (len - 1) as f64;
+
+    if let Some(duration_to_zero) = duration_to_zero
+        && duration_to_zero < duration_to_start
+    {
+        duration_to_start = duration_to_zero;
+    }
+
     let extrapolation_threshold = avg_duration_between_samples * 1.1;
     let mut extrapolate_to_interval = sampled_interval;
 
@@ -726,14 +842,7 @@ pub fn extrapolated_rate(
     } else {
         extrapolate_to_interval += avg_duration_between_samples / 2.0;
     }
-    let factor = extrapolate_to_interval / sampled_interval;
-    if matches!(kind, ExtrapolationKind::Rate) {
-        result *= factor / range.as_secs_f64();
-    } else {
-        result *= factor;
-    }
-
-    Some(result)
+    extrapolate_to_interval / sampled_interval
 }
 
 pub fn labels_value(labels: &Labels, name: &str) -> Option<String> {
@@ -1207,6 +1316,7 @@ mod tests {
                 labels: vec![],
             })]),
             time_window: None,
+            histograms: vec![],
         };
 
         let range_value2 = RangeValue {
@@ -1218,6 +1328,7 @@ mod tests {
                 labels: vec![],
             })]),
             time_window: None,
+            histograms: vec![],
         };
 
         range_value1.extend(range_value2);
@@ -1253,6 +1364,7 @@ mod tests {
         let instant_value = InstantValue {
             labels: vec![],
             sample: Sample::new(1000, 1.0),
+            histogram: None,
         };
         let vector = vec![instant_value.clone()];
         let range_value = RangeValue::new(vec![], vec![Sample::new(1000, 1.0)]);
@@ -1278,7 +1390,8 @@ mod tests {
         assert_eq!(
             Value::Instant(InstantValue {
                 labels: vec![],
-                sample: Sample::new(1000, 1.0)
+                sample: Sample::new(1000, 1.0),
+                histogram: None,
             })
             .get_type(),
             "vector"
@@ -1306,10 +1419,12 @@ mod tests {
             InstantValue {
                 labels: label1.clone(),
                 sample: Sample::new(1000, 1.0),
+                histogram: None,
             },
             InstantValue {
                 labels: label2.clone(),
                 sample: Sample::new(2000, 2.0),
+                histogram: None,
             },
         ]);
         assert!(!vector_unique.contains_same_label_set());
@@ -1319,10 +1434,12 @@ mod tests {
             InstantValue {
                 labels: label1.clone(),
                 sample: Sample::new(1000, 1.0),
+                histogram: None,
             },
             InstantValue {
                 labels: label1_dup.clone(),
                 sample: Sample::new(2000, 2.0),
+                histogram: None,
             },
         ]);
         assert!(vector_duplicate.contains_same_label_set());
@@ -1345,6 +1462,7 @@ mod tests {
         let single_vector = Value::Vector(vec![InstantValue {
             labels: label1.clone(),
             sample: Sample::new(1000, 1.0),
+            histogram: None,
         }]);
         assert!(!single_vector.contains_same_label_set());
 
@@ -1361,14 +1479,17 @@ mod tests {
             InstantValue {
                 labels: vec![],
                 sample: Sample::new(1000, 1.0),
+                histogram: None,
             },
             InstantValue {
                 labels: vec![],
                 sample: Sample::new(2000, 3.0),
+                histogram: None,
             },
             InstantValue {
                 labels: vec![],
                 sample: Sample::new(3000, 2.0),
+                histogram: None,
             },
         ]);
 
@@ -1479,6 +1600,63 @@ mod tests {
         assert!(result.is_none());
     }
 
+    #[test]
+    fn test_extrapolated_histogram_rate() {
+        let sample = |ts: i64, count: f64| {
+            HistogramSample::new(
+                ts,
+                Arc::new(NativeHistogram {
+                    count,
+                    sum: count,
+                    positive: vec![(0, count)],
+                    ..Default::default()
+                }),
+            )
+        };
+        // the third sample follows a counter reset
+        let samples = [
+            sample(15_000_000, 1.0),
+            sample(30_000_000, 2.0),
+            sample(45_000_000, 1.0),
+            sample(60_000_000, 3.0),
+        ];
+
+        let increase = extrapolated_histogram_rate(
+            &samples,
+            60_000_000,
+            Duration::from_secs(60),
+            ExtrapolationKind::Increase,
+        )
+        .unwrap();
+        assert!(approx_eq!(f64, increase.count, 5.3333, epsilon = 0.0001));
+        assert_eq!(increase.positive.len(), 1);
+        assert!(approx_eq!(
+            f64,
+            increase.positive[0].1,
+            5.3333,
+            epsilon = 0.0001
+        ));
+
+        let rate = extrapolated_histogram_rate(
+            &samples,
+            60_000_000,
+            Duration::from_secs(60),
+            ExtrapolationKind::Rate,
+        )
+        .unwrap();
+        assert!(approx_eq!(f64, rate.count * 60.0, increase.count));
+
+        assert!(
+            extrapolated_histogram_rate(
+                &samples[..1],
+                60_000_000,
+                Duration::from_secs(60),
+                ExtrapolationKind::Rate,
+            )
+            .is_none()
+        );
+    }
+
     #[test]
     fn test_sample_is_nan() {
         let normal = Sample::new(1000, 42.0);
@@ -1532,6 +1710,7 @@ mod tests {
         let iv = InstantValue {
             labels: vec![],
             sample: Sample::new(1000, 5.0),
+            histogram: None,
         };
         let val = Value::Vector(vec![iv]);
         assert!(val.get_vector().is_some());
@@ -1606,14 +1785,17 @@ mod tests {
             InstantValue {
                 labels: label_a,
                 sample: Sample::new(1, 1.0),
+                histogram: None,
             },
             InstantValue {
                 labels: label_b,
                 sample: Sample::new(2, 2.0),
+                histogram: None,
             },
             InstantValue {
                 labels: label_c,
                 sample: Sample::new(3, 3.0),
+                histogram: None,
             },
         ]);
         assert!(!v.contains_same_label_set());
@@ -1628,14 +1810,17 @@ mod tests {
             InstantValue {
                 labels: label_a,
                 sample: Sample::new(1, 1.0),
+                histogram: None,
             },
             InstantValue {
                 labels: label_b,
                 sample: Sample::new(2, 2.0),
+                histogram: None,
             },
             InstantValue {
                 labels: label_a2,
                 sample: Sample::new(3, 3.0),
+                histogram: None,
             },
         ]);
         assert!(v.contains_same_label_set());
@@ -906,6 +906,9 @@ pub struct QueryStatus {
     pub scan_stats: Option<ScanStats>,
     pub search_type: Option<SearchEventType>,
     pub search_event_context: Option<search::SearchEventContext>,
+    /// 1 based position in the fair scheduler queue while waiting
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub queue_position: Option<i64>,
 }
 
 #[derive(Clone, Debug, Default, Serialize, Deserialize, ToSchema)]
@@ -1127,6 +1130,61 @@ impl SearchEventType {
     }
 }
 
+/// Columnar formats a search result can be streamed out as, instead of the
+/// default JSON response.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+    /// Apache Parquet, ZSTD compressed.
+    Parquet,
+    /// Arrow IPC stream format.
+    Arrow,
+    /// Newline delimited JSON, gzip compressed.
+    NdjsonGz,
+}
+
+impl ExportFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Self::Parquet => "application/vnd.apache.parquet",
+            Self::Arrow => "application/vnd.apache.arrow.stream",
+            Self::NdjsonGz => "application/gzip",
+        }
+    }
+
+    pub fn file_extension(&self) -> &'static str {
+        match self {
+            Self::Parquet => "parquet",
+            Self::Arrow => "arrows",
+            Self::NdjsonGz => "ndjson.gz",
+        }
+    }
+}
+
+impl std::fmt::Display for ExportFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Parquet => write!(f, "parquet"),
+            Self::Arrow => write!(f, "arrow"),
+            Self::NdjsonGz => write!(f, "ndjson_gz"),
+        }
+    }
+}
+
+impl TryFrom<&str> for ExportFormat {
+    type Error = String;
+    fn try_from(s: &str) -> std::result::Result<Self, Self::Error> {
+        match s.to_lowercase().as_str() {
+            "parquet" => Ok(Self::Parquet),
+            "arrow" | "arrows" | "ipc" => Ok(Self::Arrow),
+            "ndjson_gz" | "ndjson.gz" | "jsonl_gz" => Ok(Self::NdjsonGz),
+            _ => Err(format!(
+                "invalid export format `{s}`, expected one of `parquet`, `arrow`, `ndjson_gz`"
+            )),
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Default, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "snake_case")]
 pub struct ValuesEventContext {
@@ -1412,6 +1470,9 @@ impl MultiStreamRequest {
 pub struct PaginationQuery {
     pub from: Option<i64>,
     pub size: Option<i64>,
+    /// Stream the whole result as a file instead of a page of hits
+    #[serde(default)]
+    pub format: Option<ExportFormat>,
 }
 
 #[derive(Debug, Deserialize, Clone, Serialize)]
@@ -2471,6 +2532,31 @@ mod tests {
         assert!(SearchEventType::try_from("invalid").is_err());
     }
 
+    #[test]
+    fn test_export_format_try_from() {
+        assert_eq!(
+            ExportFormat::try_from("parquet").unwrap(),
+            ExportFormat::Parquet
+        );
+        assert_eq!(
+            ExportFormat::try_from("Arrow").unwrap(),
+            ExportFormat::Arrow
+        );
+        assert_eq!(ExportFormat::try_from("ipc").unwrap(), ExportFormat::Arrow);
+        assert_eq!(
+            ExportFormat::try_from("ndjson_gz").unwrap(),
+            ExportFormat::NdjsonGz
+        );
+        assert!(ExportFormat::try_from("csv").is_err());
+        for f in [
+            ExportFormat::Parquet,
+            ExportFormat::Arrow,
+            ExportFormat::NdjsonGz,
+        ] {
+            assert_eq!(ExportFormat::try_from(f.to_string().as_str()).unwrap(), f);
+        }
+    }
+
     #[test]
     fn test_search_event_type_display() {
         assert_eq!(SearchEventType::UI.to_string(), "ui");
@@ -2963,9 +3049,13 @@ mod tests {
         let query = PaginationQuery {
             from: Some(10),
             size: Some(20),
+            format: None,
         };
         assert_eq!(query.from, Some(10));
         assert_eq!(query.size, Some(20));
+
+        let query: PaginationQuery = json::from_str(r#"{"format":"ndjson_gz"}"#).unwrap();
+        assert_eq!(query.format, Some(ExportFormat::NdjsonGz));
     }
 
     #[test]
@@ -3065,6 +3155,7 @@ mod tests {
                 scan_stats: Some(ScanStats::new()),
                 search_type: Some(SearchEventType::UI),
                 search_event_context: None,
+                queue_position: None,
             }],
         };
         assert_eq!(response.status.len(), 1);
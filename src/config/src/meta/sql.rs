@@ -15,7 +15,11 @@
 
 use std::sync::LazyLock;
 
-use datafusion::sql::{TableReference, parser::DFParser, resolve::resolve_table_references};
+use datafusion::sql::{
+    TableReference,
+    parser::{DFParser, Statement as DFStatement},
+    resolve::resolve_table_references,
+};
 use serde::{Deserialize, Serialize};
 use sqlparser::{
     ast::{Expr, SelectItem, SetExpr, Statement},
@@ -26,6 +30,7 @@ use sqlparser::{
 use utoipa::ToSchema;
 
 use super::stream::StreamType;
+use crate::utils::sql::table_function::resolve_table_functions;
 
 pub const MAX_LIMIT: i64 = 100000;
 pub const MAX_OFFSET: i64 = 100000;
@@ -92,8 +97,12 @@ pub fn resolve_stream_names(sql: &str) -> Result<Vec<String>, anyhow::Error> {
         .pop_back()
         .ok_or(anyhow::anyhow!("Failed to parse sql"))?;
     let (table_refs, _) = resolve_table_references(&statement, true)?;
+    let functions = table_function_names(&statement);
     let mut tables = Vec::new();
     for table in table_refs {
+        if is_table_function(&table, &functions) {
+            continue;
+        }
         tables.push(table.table().to_string());
     }
     Ok(tables)
@@ -105,18 +114,37 @@ pub fn resolve_stream_names_with_type(sql: &str) -> Result<Vec<TableReference>,
         .pop_back()
         .ok_or(anyhow::anyhow!("Failed to parse sql"))?;
     let (table_refs, _) = resolve_table_references(&statement, true)?;
+    let functions = table_function_names(&statement);
     let mut tables = Vec::new();
     for table in table_refs {
+        if is_table_function(&table, &functions) {
+            continue;
+        }
         tables.push(table);
     }
     Ok(tables)
 }
 
+// table function calls show up as table references, but they are not streams
+fn table_function_names(statement: &DFStatement) -> Vec<String> {
+    match statement {
+        DFStatement::Statement(statement) => resolve_table_functions(statement)
+            .map(|calls| calls.into_iter().map(|c| c.name).collect())
+            .unwrap_or_default(),
+        _ => vec![],
+    }
+}
+
+fn is_table_function(table: &TableReference, functions: &[String]) -> bool {
+    table.schema().is_none() && functions.iter().any(|f| f == table.table())
+}
+
 pub trait TableReferenceExt {
     fn stream_type(&self) -> String;
     fn stream_name(&self) -> String;
     fn has_stream_type(&self) -> bool;
     fn get_stream_type(&self, stream_type: StreamType) -> StreamType;
+    fn is_external_table(&self) -> bool;
 }
 
 impl TableReferenceExt for TableReference {
@@ -139,6 +167,10 @@ impl TableReferenceExt for TableReference {
             stream_type
         }
     }
+
+    fn is_external_table(&self) -> bool {
+        self.schema() == Some(super::external_table::EXTERNAL_TABLE_SCHEMA)
+    }
 }
 
 #[cfg(test)]
@@ -177,6 +209,26 @@ mod tests {
         assert_eq!(st, super::StreamType::Metrics);
     }
 
+    #[test]
+    fn test_resolve_stream_names_skip_table_functions() {
+        let sql = "select * from \"default\" l join trace_spans('abc') t on l.span_id = t.span_id";
+        assert_eq!(
+            resolve_stream_names(sql).unwrap(),
+            vec!["default".to_string()]
+        );
+        let refs = resolve_stream_names_with_type(sql).unwrap();
+        assert_eq!(refs.len(), 1);
+        assert_eq!(refs[0].stream_name(), "default");
+    }
+
+    #[test]
+    fn test_table_reference_is_external_table() {
+        let sql = "select * from external.events join \"default\" on true";
+        let refs = resolve_stream_names_with_type(sql).unwrap();
+        assert!(refs.iter().any(|r| r.is_external_table()));
+        assert!(refs.iter().any(|r| !r.is_external_table()));
+    }
+
     #[test]
     fn test_table_reference_get_stream_type_without_schema() {
         let sql = "select * from cpu_usage";
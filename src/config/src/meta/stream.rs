@@ -414,6 +414,32 @@ impl StorageType {
     }
 }
 
+/// Tokenizer used to index and search the full text search fields of a stream.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum FtsTokenizer {
+    /// Split on whitespace and punctuation, camel case words are also split.
+    #[default]
+    O2,
+    /// Character n-grams of every word, between `min_gram` and `max_gram`
+    /// characters long.
+    Ngram { min_gram: usize, max_gram: usize },
+    /// Overlapping bigrams for Chinese, Japanese and Korean text, other words
+    /// are kept whole.
+    CjkBigram,
+    /// Every ancestor of a URL or file path, e.g. `/var`, `/var/log` and
+    /// `/var/log/app.log`.
+    PathHierarchy,
+}
+
+impl FtsTokenizer {
+    /// Whether the index hits match the search exactly. N-gram and bigram
+    /// tokens can match out of order, so their hits need to be verified.
+    pub fn is_exact(&self) -> bool {
+        matches!(self, FtsTokenizer::O2 | FtsTokenizer::PathHierarchy)
+    }
+}
+
 #[derive(Serialize, Debug, Default, Clone, PartialEq)]
 pub enum QueryPartitionStrategy {
     #[default]
@@ -770,6 +796,8 @@ pub struct UpdateStreamSettings {
     pub index_fields: UpdateSettingsWrapper<String>,
     #[serde(default)]
     pub bloom_filter_fields: UpdateSettingsWrapper<String>,
+    #[serde(default)]
+    pub range_index_fields: UpdateSettingsWrapper<String>,
     #[serde(skip_serializing_if = "Option::None", default)]
     pub data_retention: Option<i64>,
     #[serde(skip_serializing_if = "Option::None", default)]
@@ -802,6 +830,14 @@ pub struct UpdateStreamSettings {
     pub storage_type: Option<StorageType>,
     #[serde(default)]
     pub is_llm_stream: Option<bool>,
+    #[serde(default)]
+    pub fts_tokenizer: Option<FtsTokenizer>,
+    /// Replaces the whole sort key list (order matters); an empty list turns
+    /// clustering off.
+    #[serde(default)]
+    pub sort_keys: Option<Vec<String>>,
+    #[serde(default)]
+    pub vector_fields: UpdateSettingsWrapper<VectorField>,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
@@ -856,6 +892,31 @@ pub struct CrossLinkField {
     pub alias: Option<String>,
 }
 
+/// Maximum number of dimensions accepted for a vector field.
+pub const MAX_VECTOR_DIMENSIONS: usize = 4096;
+
+/// A field holding fixed-dimension float embeddings, ingested as a JSON array
+/// of numbers and stored as a `FixedSizeList<Float32>` column of `dimensions`
+/// values. Each parquet file gets an ANN index on it so that `knn()` queries
+/// can be answered without scanning every row.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, ToSchema, PartialEq, Eq, Hash)]
+pub struct VectorField {
+    pub name: String,
+    pub dimensions: usize,
+}
+
+impl Display for VectorField {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}[{}]", self.name, self.dimensions)
+    }
+}
+
+impl MemorySize for VectorField {
+    fn mem_size(&self) -> usize {
+        std::mem::size_of::<VectorField>() + self.name.mem_size()
+    }
+}
+
 #[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema, PartialEq, Eq, Hash)]
 pub struct TimeRange {
     /// Start timestamp in microseconds
@@ -950,6 +1011,18 @@ pub struct StreamSettings {
     pub index_fields: Vec<String>,
     #[serde(default)]
     pub bloom_filter_fields: Vec<String>,
+    /// Numeric fields indexed as tantivy fast fields so that range predicates
+    /// (`>`, `>=`, `<`, `<=`, `BETWEEN`) can prune files and rows.
+    #[serde(default)]
+    pub range_index_fields: Vec<String>,
+    /// Fields the compactor clusters rows by (in this order, then
+    /// `_timestamp DESC`), so row group and page statistics of those fields
+    /// become tight enough to prune on.
+    #[serde(default)]
+    pub sort_keys: Vec<String>,
+    /// Embedding fields with a per-file approximate nearest-neighbour index.
+    #[serde(default)]
+    pub vector_fields: Vec<VectorField>,
     #[serde(default)]
     pub defined_schema_fields: Vec<String>,
     #[serde(default)]
@@ -971,6 +1044,12 @@ pub struct StreamSettings {
     #[serde(default)]
     pub index_all_values: bool,
     #[serde(default)]
+    pub fts_tokenizer: FtsTokenizer,
+    /// When `fts_tokenizer` was last changed, in microseconds. Files written
+    /// before keep the full text index of the previous tokenizer.
+    #[serde(default)]
+    pub fts_tokenizer_updated_at: i64,
+    #[serde(default)]
     pub enable_distinct_fields: bool,
     #[serde(default)]
     pub enable_log_patterns_extraction: bool,
@@ -993,6 +1072,9 @@ impl Default for StreamSettings {
             full_text_search_keys: Vec::new(),
             index_fields: Vec::new(),
             bloom_filter_fields: Vec::new(),
+            range_index_fields: Vec::new(),
+            sort_keys: Vec::new(),
+            vector_fields: Vec::new(),
             data_retention: 0,
             flatten_level: None,
             defined_schema_fields: Vec::new(),
@@ -1005,6 +1087,8 @@ impl Default for StreamSettings {
             extended_retention_days: Vec::new(),
             index_original_data: false,
             index_all_values: false,
+            fts_tokenizer: FtsTokenizer::O2,
+            fts_tokenizer_updated_at: 0,
             enable_distinct_fields: true,
             enable_log_patterns_extraction: false,
             is_llm_stream: false,
@@ -1050,6 +1134,13 @@ impl Serialize for StreamSettings {
         state.serialize_field("full_text_search_keys", &self.full_text_search_keys)?;
         state.serialize_field("index_fields", &self.index_fields)?;
         state.serialize_field("bloom_filter_fields", &self.bloom_filter_fields)?;
+        state.serialize_field("range_index_fields", &self.range_index_fields)?;
+        state.serialize_field("sort_keys", &self.sort_keys)?;
+        if !self.vector_fields.is_empty() {
+            state.serialize_field("vector_fields", &self.vector_fields)?;
+        } else {
+            state.skip_field("vector_fields")?;
+        }
         state.serialize_field("distinct_value_fields", &self.distinct_value_fields)?;
         state.serialize_field("data_retention", &self.data_retention)?;
         state.serialize_field("max_query_range", &self.max_query_range)?;
@@ -1064,6 +1155,12 @@ impl Serialize for StreamSettings {
         state.serialize_field("extended_retention_days", &self.extended_retention_days)?;
         state.serialize_field("index_original_data", &self.index_original_data)?;
         state.serialize_field("index_all_values", &self.index_all_values)?;
+        state.serialize_field("fts_tokenizer", &self.fts_tokenizer)?;
+        if self.fts_tokenizer_updated_at > 0 {
+            state.serialize_field("fts_tokenizer_updated_at", &self.fts_tokenizer_updated_at)?;
+        } else {
+            state.skip_field("fts_tokenizer_updated_at")?;
+        }
         state.serialize_field("enable_distinct_fields", &self.enable_distinct_fields)?;
         state.serialize_field(
             "enable_log_patterns_extraction",
@@ -1147,6 +1244,33 @@ impl From<&str> for StreamSettings {
             }
         }
 
+        let mut range_index_fields = Vec::new();
+        let fields = settings.get("range_index_fields");
+        if let Some(value) = fields {
+            let v: Vec<_> = value.as_array().unwrap().iter().collect();
+            for item in v {
+                range_index_fields.push(item.as_str().unwrap().to_string())
+            }
+        }
+
+        let mut sort_keys = Vec::new();
+        let fields = settings.get("sort_keys");
+        if let Some(value) = fields {
+            let v: Vec<_> = value.as_array().unwrap().iter().collect();
+            for item in v {
+                sort_keys.push(item.as_str().unwrap().to_string())
+            }
+        }
+
+        let mut vector_fields = Vec::new();
+        if let Some(value) = settings.get("vector_fields").and_then(|v| v.as_array()) {
+            for item in value {
+                if let Ok(field) = json::from_value::<VectorField>(item.clone()) {
+                    vector_fields.push(field);
+                }
+            }
+        }
+
         let mut data_retention = 0;
         if let Some(v) = settings.get("data_retention") {
             data_retention = v.as_i64().unwrap();
@@ -1237,6 +1361,14 @@ impl From<&str> for StreamSettings {
             .get("index_all_values")
             .and_then(Value::as_bool)
             .unwrap_or_default();
+        let fts_tokenizer = settings
+            .get("fts_tokenizer")
+            .and_then(|v| json::from_value::<FtsTokenizer>(v.clone()).ok())
+            .unwrap_or_default();
+        let fts_tokenizer_updated_at = settings
+            .get("fts_tokenizer_updated_at")
+            .and_then(Value::as_i64)
+            .unwrap_or_default();
         let enable_distinct_fields = settings
             .get("enable_distinct_fields")
             .and_then(Value::as_bool)
@@ -1267,6 +1399,9 @@ impl From<&str> for StreamSettings {
             full_text_search_keys,
             index_fields,
             bloom_filter_fields,
+            range_index_fields,
+            sort_keys,
+            vector_fields,
             data_retention,
             max_query_range,
             flatten_level,
@@ -1279,6 +1414,8 @@ impl From<&str> for StreamSettings {
             extended_retention_days,
             index_original_data,
             index_all_values,
+            fts_tokenizer,
+            fts_tokenizer_updated_at,
             enable_distinct_fields,
             enable_log_patterns_extraction,
             is_llm_stream,
@@ -1295,6 +1432,9 @@ impl MemorySize for StreamSettings {
             + self.full_text_search_keys.mem_size()
             + self.index_fields.mem_size()
             + self.bloom_filter_fields.mem_size()
+            + self.range_index_fields.mem_size()
+            + self.sort_keys.mem_size()
+            + self.vector_fields.mem_size()
             + self.defined_schema_fields.mem_size()
             + self.distinct_value_fields.mem_size()
             + self.extended_retention_days.mem_size()
@@ -1484,6 +1624,88 @@ mod tests {
         assert!(!payload.contains("index_fields_updated_at"));
     }
 
+    #[test]
+    fn test_stream_settings_fts_tokenizer() {
+        // legacy payload without the tokenizer uses the default one
+        let settings = StreamSettings::from(r#"{"index_updated_at": 100}"#);
+        assert_eq!(settings.fts_tokenizer, FtsTokenizer::O2);
+
+        let settings = StreamSettings {
+            fts_tokenizer: FtsTokenizer::Ngram {
+                min_gram: 2,
+                max_gram: 3,
+            },
+            ..Default::default()
+        };
+        let payload = json::to_string(&settings).unwrap();
+        assert!(payload.contains(r#""fts_tokenizer":{"type":"ngram","min_gram":2,"max_gram":3}"#));
+        let parsed = StreamSettings::from(payload.as_str());
+        assert_eq!(parsed.fts_tokenizer, settings.fts_tokenizer);
+
+        let settings = StreamSettings::from(r#"{"fts_tokenizer": {"type": "cjk_bigram"}}"#);
+        assert_eq!(settings.fts_tokenizer, FtsTokenizer::CjkBigram);
+        assert!(!settings.fts_tokenizer.is_exact());
+        assert!(FtsTokenizer::PathHierarchy.is_exact());
+
+        let settings = StreamSettings::from(r#"{"fts_tokenizer_updated_at": 100}"#);
+        assert_eq!(settings.fts_tokenizer_updated_at, 100);
+        let payload = json::to_string(&settings).unwrap();
+        assert!(payload.contains(r#""fts_tokenizer_updated_at":100"#));
+    }
+
+    #[test]
+    fn test_stream_settings_range_index_fields() {
+        let settings = StreamSettings::from(r#"{"index_updated_at": 100}"#);
+        assert!(settings.range_index_fields.is_empty());
+
+        let settings = StreamSettings {
+            range_index_fields: vec!["latency_ms".to_string(), "status_code".to_string()],
+            ..Default::default()
+        };
+        let payload = json::to_string(&settings).unwrap();
+        assert!(payload.contains(r#""range_index_fields":["latency_ms","status_code"]"#));
+        let parsed = StreamSettings::from(payload.as_str());
+        assert_eq!(parsed.range_index_fields, settings.range_index_fields);
+    }
+
+    #[test]
+    fn test_stream_settings_sort_keys() {
+        let settings = StreamSettings::from(r#"{"index_updated_at": 100}"#);
+        assert!(settings.sort_keys.is_empty());
+
+        let settings = StreamSettings {
+            sort_keys: vec!["service".to_string(), "host".to_string()],
+            ..Default::default()
+        };
+        let payload = json::to_string(&settings).unwrap();
+        assert!(payload.contains(r#""sort_keys":["service","host"]"#));
+        let parsed = StreamSettings::from(payload.as_str());
+        assert_eq!(parsed.sort_keys, settings.sort_keys);
+    }
+
+    #[test]
+    fn test_stream_settings_vector_fields() {
+        let settings = StreamSettings::from(r#"{"index_updated_at": 100}"#);
+        assert!(settings.vector_fields.is_empty());
+        assert!(
+            !json::to_string(&settings)
+                .unwrap()
+                .contains("vector_fields")
+        );
+
+        let settings = StreamSettings {
+            vector_fields: vec![VectorField {
+                name: "embedding".to_string(),
+                dimensions: 384,
+            }],
+            ..Default::default()
+        };
+        let payload = json::to_string(&settings).unwrap();
+        let parsed = StreamSettings::from(payload.as_str());
+        assert_eq!(parsed.vector_fields, settings.vector_fields);
+        assert_eq!(parsed.vector_fields[0].to_string(), "embedding[384]");
+    }
+
     #[tokio::test]
     async fn test_get_file_meta() {
         let file_meta = FileMeta {
@@ -138,6 +138,19 @@ pub static INGEST_ERRORS: Lazy<IntCounterVec> = Lazy::new(|| {
     )
     .expect("Metric created")
 });
+pub static INGEST_METRICS_REJECTED_SERIES: Lazy<IntCounterVec> = Lazy::new(|| {
+    IntCounterVec::new(
+        Opts::new(
+            "ingest_metrics_rejected_series",
+            "Metric samples rejected by the ingest rules of an org, by reason".to_owned()
+                + HELP_SUFFIX,
+        )
+        .namespace(NAMESPACE)
+        .const_labels(create_const_labels()),
+        &["organization", "stream", "reason"],
+    )
+    .expect("Metric created")
+});
 pub static INGEST_WAL_USED_BYTES: Lazy<IntGaugeVec> = Lazy::new(|| {
     IntGaugeVec::new(
         Opts::new(
@@ -2241,6 +2254,9 @@ fn register_metrics(registry: &Registry) {
     registry
         .register(Box::new(INGEST_ERRORS.clone()))
         .expect("Metric registered");
+    registry
+        .register(Box::new(INGEST_METRICS_REJECTED_SERIES.clone()))
+        .expect("Metric registered");
     registry
         .register(Box::new(INGEST_WAL_USED_BYTES.clone()))
         .expect("Metric registered");
@@ -2881,6 +2897,7 @@ mod tests {
         let _ = INGEST_RECORDS.clone();
         let _ = INGEST_BYTES.clone();
         let _ = INGEST_ERRORS.clone();
+        let _ = INGEST_METRICS_REJECTED_SERIES.clone();
         let _ = INGEST_WAL_USED_BYTES.clone();
         let _ = INGEST_PARQUET_FILES.clone();
         let _ = SYNTHETICS_PENDING_JOBS.clone();
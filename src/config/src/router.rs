@@ -14,7 +14,7 @@
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
 /// usize indicates the number of parts to skip based on their actual paths.
-const QUERIER_ROUTES: [(&str, usize); 34] = [
+const QUERIER_ROUTES: [(&str, usize); 35] = [
     ("config", 0),               // /config
     ("summary", 2),              // /api/{org_id}/summary
     ("organizations", 1),        // /api/organizations
@@ -35,6 +35,7 @@ const QUERIER_ROUTES: [(&str, usize); 34] = [
     ("_values_stream", 2),       // /api/{org_id}/_values_stream
     ("_around", 3),              // /api/{org_id}/{stream_name}/_around
     ("_values", 3),              // /api/{org_id}/{stream_name}/_values
+    ("_tail", 3),                // /api/{org_id}/{stream_name}/_tail
     ("patterns/extract", 3),     /* /api/{org_id}/streams/{stream_name}/patterns/
                                   * extract */
     ("functions?page_num=", 2),               // /api/{org_id}/functions
@@ -153,6 +154,7 @@ mod tests {
         // Test streams route
         assert!(is_querier_route("/api/org1/streams"));
         assert!(is_querier_route("/api/org1/streams/mystream"));
+        assert!(is_querier_route("/api/org1/mystream/_tail"));
 
         // Test prometheus routes
         assert!(is_querier_route("/api/org1/prometheus/api/v1/query"));
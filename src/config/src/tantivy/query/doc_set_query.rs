@@ -0,0 +1,158 @@
+// Copyright 2026 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+use std::sync::Arc;
+
+use tantivy::{
+    DocId, DocSet, Score, SegmentReader, TERMINATED,
+    error::TantivyError,
+    query::{ConstScorer, EnableScoring, Explanation, Query, Scorer, Weight},
+};
+
+/// A query matching a fixed set of doc ids, computed outside of tantivy (e.g.
+/// the hits of a vector index). The index is expected to have a single
+/// segment, which is how every per-file index is built.
+#[derive(Debug, Clone)]
+pub struct DocSetQuery {
+    doc_ids: Arc<Vec<DocId>>,
+}
+
+impl DocSetQuery {
+    pub fn new(mut doc_ids: Vec<DocId>) -> Self {
+        doc_ids.sort_unstable();
+        doc_ids.dedup();
+        Self {
+            doc_ids: Arc::new(doc_ids),
+        }
+    }
+}
+
+impl Query for DocSetQuery {
+    fn weight(&self, _enabled_scoring: EnableScoring<'_>) -> tantivy::Result<Box<dyn Weight>> {
+        Ok(Box::new(DocSetWeight {
+            doc_ids: self.doc_ids.clone(),
+        }))
+    }
+}
+
+struct DocSetWeight {
+    doc_ids: Arc<Vec<DocId>>,
+}
+
+impl Weight for DocSetWeight {
+    fn scorer(&self, reader: &SegmentReader, boost: Score) -> tantivy::Result<Box<dyn Scorer>> {
+        let max_doc = reader.max_doc();
+        let end = self.doc_ids.partition_point(|&doc| doc < max_doc);
+        let docset = SortedDocSet {
+            doc_ids: self.doc_ids.clone(),
+            end,
+            cursor: 0,
+        };
+        Ok(Box::new(ConstScorer::new(docset, boost)))
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> tantivy::Result<Explanation> {
+        if doc >= reader.max_doc() || self.doc_ids.binary_search(&doc).is_err() {
+            return Err(TantivyError::InvalidArgument(format!(
+                "Document #({doc}) does not match"
+            )));
+        }
+        Ok(Explanation::new("DocSetQuery", 1.0))
+    }
+}
+
+struct SortedDocSet {
+    doc_ids: Arc<Vec<DocId>>,
+    /// Only ids below `end` exist in the segment.
+    end: usize,
+    cursor: usize,
+}
+
+impl DocSet for SortedDocSet {
+    fn advance(&mut self) -> DocId {
+        if self.cursor < self.end {
+            self.cursor += 1;
+        }
+        self.doc()
+    }
+
+    fn seek(&mut self, target: DocId) -> DocId {
+        if self.doc() < target {
+            self.cursor += self.doc_ids[self.cursor..self.end].partition_point(|&doc| doc < target);
+        }
+        self.doc()
+    }
+
+    fn doc(&self) -> DocId {
+        if self.cursor < self.end {
+            self.doc_ids[self.cursor]
+        } else {
+            TERMINATED
+        }
+    }
+
+    fn size_hint(&self) -> u32 {
+        (self.end - self.cursor) as u32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tantivy::{
+        Index, IndexWriter,
+        collector::{Count, DocSetCollector},
+        doc,
+        query::{BooleanQuery, Occur, TermQuery},
+        schema::{IndexRecordOption, STRING, Schema},
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_doc_set_query() -> tantivy::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let field = schema_builder.add_text_field("status", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        {
+            let mut index_writer: IndexWriter = index.writer(15_000_000)?;
+            for i in 0..10 {
+                let status = if i % 2 == 0 { "ok" } else { "error" };
+                index_writer.add_document(doc!(field => status))?;
+            }
+            index_writer.commit()?;
+        }
+        let searcher = index.reader()?.searcher();
+
+        // unsorted and out of range ids are fine
+        let query = DocSetQuery::new(vec![7, 2, 3, 2, 42]);
+        assert_eq!(searcher.search(&query, &Count)?, 3);
+
+        let ok = TermQuery::new(
+            tantivy::Term::from_field_text(field, "ok"),
+            IndexRecordOption::Basic,
+        );
+        let query = BooleanQuery::new(vec![
+            (Occur::Must, Box::new(query) as Box<dyn Query>),
+            (Occur::Must, Box::new(ok)),
+        ]);
+        let docs = searcher.search(&query, &DocSetCollector)?;
+        assert_eq!(
+            docs.into_iter().map(|d| d.doc_id).collect::<Vec<_>>(),
+            vec![2]
+        );
+
+        assert_eq!(searcher.search(&DocSetQuery::new(vec![]), &Count)?, 0);
+        Ok(())
+    }
+}
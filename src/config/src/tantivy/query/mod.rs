@@ -14,6 +14,7 @@
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
 pub mod contains_query;
+pub mod doc_set_query;
 pub mod histogram_collector;
 pub mod ids_collector;
 pub mod topn_collector;
@@ -0,0 +1,64 @@
+// Copyright 2026 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use tantivy::tokenizer::{Token, TokenStream};
+
+/// TokenStream over tokens collected up front, used by the tokenizers that
+/// emit several overlapping tokens for one word.
+pub struct BufferedTokenStream {
+    tokens: Vec<Token>,
+    cursor: usize,
+}
+
+impl BufferedTokenStream {
+    pub(super) fn new(tokens: Vec<Token>) -> Self {
+        Self { tokens, cursor: 0 }
+    }
+}
+
+impl TokenStream for BufferedTokenStream {
+    fn advance(&mut self) -> bool {
+        if self.cursor < self.tokens.len() {
+            self.cursor += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn token(&self) -> &Token {
+        &self.tokens[self.cursor - 1]
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        &mut self.tokens[self.cursor - 1]
+    }
+}
+
+// append a token for `text[offset_from..offset_to]` at the next position
+pub(super) fn push_token(
+    tokens: &mut Vec<Token>,
+    text: &str,
+    offset_from: usize,
+    offset_to: usize,
+) {
+    tokens.push(Token {
+        offset_from,
+        offset_to,
+        position: tokens.len(),
+        text: text[offset_from..offset_to].to_string(),
+        position_length: 1,
+    });
+}
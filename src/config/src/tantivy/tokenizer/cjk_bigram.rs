@@ -0,0 +1,129 @@
+// Copyright 2026 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use tantivy::tokenizer::{Token, Tokenizer};
+
+use super::buffered::{BufferedTokenStream, push_token};
+
+/// Tokenize Chinese, Japanese and Korean text into overlapping bigrams, as
+/// those languages don't separate words with whitespace. Other words are split
+/// on whitespace and punctuation and kept whole.
+///
+/// Ingest and search produce the same tokens.
+#[derive(Clone, Default)]
+pub struct CjkBigramTokenizer;
+
+impl Tokenizer for CjkBigramTokenizer {
+    type TokenStream<'a> = BufferedTokenStream;
+
+    fn token_stream<'a>(&'a mut self, text: &'a str) -> BufferedTokenStream {
+        let mut tokens: Vec<Token> = Vec::new();
+        // byte offsets of the chars of the current CJK run
+        let mut cjk_run: Vec<usize> = Vec::new();
+        let mut word_start: Option<usize> = None;
+        for (offset, c) in text.char_indices() {
+            if is_cjk(c) {
+                if let Some(start) = word_start.take() {
+                    push_token(&mut tokens, text, start, offset);
+                }
+                cjk_run.push(offset);
+                continue;
+            }
+            flush_cjk_run(&mut tokens, text, &mut cjk_run, offset);
+            if c.is_alphanumeric() {
+                word_start.get_or_insert(offset);
+            } else if let Some(start) = word_start.take() {
+                push_token(&mut tokens, text, start, offset);
+            }
+        }
+        flush_cjk_run(&mut tokens, text, &mut cjk_run, text.len());
+        if let Some(start) = word_start {
+            push_token(&mut tokens, text, start, text.len());
+        }
+        BufferedTokenStream::new(tokens)
+    }
+}
+
+// emit the bigrams of a CJK run ending at `end`, a single char is kept as is
+fn flush_cjk_run(tokens: &mut Vec<Token>, text: &str, run: &mut Vec<usize>, end: usize) {
+    match run.len() {
+        0 => return,
+        1 => push_token(tokens, text, run[0], end),
+        _ => {
+            for (i, &from) in run[..run.len() - 1].iter().enumerate() {
+                let to = run.get(i + 2).copied().unwrap_or(end);
+                push_token(tokens, text, from, to);
+            }
+        }
+    }
+    run.clear();
+}
+
+/// Whether the char is a Han, Hiragana, Katakana or Hangul character.
+pub fn is_cjk(c: char) -> bool {
+    matches!(c as u32,
+        0x1100..=0x11FF // Hangul Jamo
+        | 0x3040..=0x309F // Hiragana
+        | 0x30A0..=0x30FF // Katakana
+        | 0x3130..=0x318F // Hangul Compatibility Jamo
+        | 0x31F0..=0x31FF // Katakana Phonetic Extensions
+        | 0x3400..=0x4DBF // CJK Unified Ideographs Extension A
+        | 0x4E00..=0x9FFF // CJK Unified Ideographs
+        | 0xAC00..=0xD7AF // Hangul Syllables
+        | 0xF900..=0xFAFF // CJK Compatibility Ideographs
+        | 0x20000..=0x2A6DF // CJK Unified Ideographs Extension B
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use tantivy::tokenizer::TokenStream;
+
+    use super::*;
+
+    fn collect(text: &str) -> Vec<String> {
+        let mut tokenizer = CjkBigramTokenizer;
+        let mut stream = tokenizer.token_stream(text);
+        let mut tokens = Vec::new();
+        while stream.advance() {
+            tokens.push(stream.token().text.clone());
+        }
+        tokens
+    }
+
+    #[test]
+    fn test_cjk_bigram() {
+        assert_eq!(collect("数据库连接"), vec!["数据", "据库", "库连", "连接"]);
+        assert_eq!(collect("错"), vec!["错"]);
+    }
+
+    #[test]
+    fn test_cjk_bigram_mixed() {
+        assert_eq!(
+            collect("error:接続失敗 code=500"),
+            vec!["error", "接続", "続失", "失敗", "code", "500"]
+        );
+        assert_eq!(collect("서버오류"), vec!["서버", "버오", "오류"]);
+    }
+
+    #[test]
+    fn test_is_cjk() {
+        assert!(is_cjk('中'));
+        assert!(is_cjk('カ'));
+        assert!(is_cjk('한'));
+        assert!(!is_cjk('a'));
+        assert!(!is_cjk('é'));
+    }
+}
@@ -13,18 +13,31 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
+mod buffered;
+mod cjk_bigram;
+mod ngram;
 mod o2_tokenizer;
+mod path_hierarchy;
 mod remove_short;
 
+pub use cjk_bigram::{CjkBigramTokenizer, is_cjk};
+pub use ngram::NgramTokenizer;
 pub use o2_tokenizer::{CollectType, O2Tokenizer};
+pub use path_hierarchy::PathHierarchyTokenizer;
 use remove_short::RemoveShortFilter;
-use tantivy::tokenizer::{TextAnalyzer, Token};
+use tantivy::tokenizer::{LowerCaser, RemoveLongFilter, TextAnalyzer, Token};
 
-use crate::get_config;
+use crate::{get_config, meta::stream::FtsTokenizer};
 
 pub const O2_TOKENIZER: &str = "o2";
+const NGRAM_TOKENIZER_PREFIX: &str = "o2_ngram_";
+const CJK_BIGRAM_TOKENIZER: &str = "o2_cjk_bigram";
+const PATH_HIERARCHY_TOKENIZER: &str = "o2_path_hierarchy";
 const MIN_TOKEN_LENGTH: usize = 2;
 const MAX_TOKEN_LENGTH: usize = 64;
+const MAX_PATH_TOKEN_LENGTH: usize = 1024;
+/// The longest n-gram a stream can be configured with.
+pub const NGRAM_MAX_GRAM: usize = 10;
 
 pub fn o2_tokenizer_build(collect_type: CollectType) -> TextAnalyzer {
     let cfg = get_config();
@@ -41,8 +54,100 @@ pub fn o2_tokenizer_build(collect_type: CollectType) -> TextAnalyzer {
         .build()
 }
 
+/// Name the tokenizer is registered with in the tantivy index. The name is
+/// stored in the index schema, so an index is always searched with the
+/// tokenizer it was built with, even after the stream settings changed.
+pub fn fts_tokenizer_name(tokenizer: &FtsTokenizer) -> String {
+    match tokenizer {
+        FtsTokenizer::O2 => O2_TOKENIZER.to_string(),
+        FtsTokenizer::Ngram { min_gram, max_gram } => {
+            format!("{NGRAM_TOKENIZER_PREFIX}{min_gram}_{max_gram}")
+        }
+        FtsTokenizer::CjkBigram => CJK_BIGRAM_TOKENIZER.to_string(),
+        FtsTokenizer::PathHierarchy => PATH_HIERARCHY_TOKENIZER.to_string(),
+    }
+}
+
+/// Parse the name from [`fts_tokenizer_name`] back, unknown names fall back to
+/// the default tokenizer.
+pub fn fts_tokenizer_from_name(name: &str) -> FtsTokenizer {
+    match name {
+        CJK_BIGRAM_TOKENIZER => FtsTokenizer::CjkBigram,
+        PATH_HIERARCHY_TOKENIZER => FtsTokenizer::PathHierarchy,
+        _ => name
+            .strip_prefix(NGRAM_TOKENIZER_PREFIX)
+            .and_then(|v| v.split_once('_'))
+            .and_then(|(min, max)| Some((min.parse().ok()?, max.parse().ok()?)))
+            .map(|(min_gram, max_gram)| FtsTokenizer::Ngram { min_gram, max_gram })
+            .unwrap_or_default(),
+    }
+}
+
+pub fn fts_tokenizer_build(tokenizer: &FtsTokenizer, collect_type: CollectType) -> TextAnalyzer {
+    let cfg = get_config();
+    let max_token_length =
+        std::cmp::max(cfg.limit.inverted_index_max_token_length, MAX_TOKEN_LENGTH);
+    match tokenizer {
+        FtsTokenizer::O2 => o2_tokenizer_build(collect_type),
+        FtsTokenizer::Ngram { min_gram, max_gram } => {
+            TextAnalyzer::builder(NgramTokenizer::new(*min_gram, *max_gram, collect_type))
+                .filter(RemoveLongFilter::limit(max_token_length))
+                .filter(LowerCaser)
+                .build()
+        }
+        FtsTokenizer::CjkBigram => TextAnalyzer::builder(CjkBigramTokenizer)
+            .filter(RemoveLongFilter::limit(max_token_length))
+            .filter(LowerCaser)
+            .build(),
+        FtsTokenizer::PathHierarchy => {
+            TextAnalyzer::builder(PathHierarchyTokenizer::new(collect_type))
+                .filter(RemoveLongFilter::limit(std::cmp::max(
+                    max_token_length,
+                    MAX_PATH_TOKEN_LENGTH,
+                )))
+                .filter(LowerCaser)
+                .build()
+        }
+    }
+}
+
+/// Register the tokenizer the full text search field of the schema was built
+/// with.
+pub fn register_fts_tokenizer(
+    manager: &tantivy::tokenizer::TokenizerManager,
+    schema: &tantivy::schema::Schema,
+    collect_type: CollectType,
+) {
+    let Some(name) = schema_fts_tokenizer_name(schema) else {
+        return;
+    };
+    let tokenizer = fts_tokenizer_from_name(&name);
+    manager.register(&name, fts_tokenizer_build(&tokenizer, collect_type));
+}
+
+/// Get the tokenizer the full text search field of the schema was built with.
+pub fn schema_fts_tokenizer(schema: &tantivy::schema::Schema) -> FtsTokenizer {
+    schema_fts_tokenizer_name(schema)
+        .map(|name| fts_tokenizer_from_name(&name))
+        .unwrap_or_default()
+}
+
+fn schema_fts_tokenizer_name(schema: &tantivy::schema::Schema) -> Option<String> {
+    let field = schema.get_field(crate::INDEX_FIELD_NAME_FOR_ALL).ok()?;
+    match schema.get_field_entry(field).field_type() {
+        tantivy::schema::FieldType::Str(opts) => opts
+            .get_indexing_options()
+            .map(|opts| opts.tokenizer().to_string()),
+        _ => None,
+    }
+}
+
 pub fn o2_collect_search_tokens(text: &str) -> Vec<String> {
-    let mut a = o2_tokenizer_build(CollectType::Search);
+    collect_search_tokens(&FtsTokenizer::O2, text)
+}
+
+pub fn collect_search_tokens(tokenizer: &FtsTokenizer, text: &str) -> Vec<String> {
+    let mut a = fts_tokenizer_build(tokenizer, CollectType::Search);
     let mut token_stream = a.token_stream(text);
 
     let mut tokens: Vec<String> = Vec::new();
@@ -53,6 +158,25 @@ pub fn o2_collect_search_tokens(text: &str) -> Vec<String> {
     tokens
 }
 
+/// Collect the tokens of a phrase with their positions. Tokenizes the same way
+/// as ingestion so the relative positions line up with the ones stored in the
+/// index, e.g. camel case splits take up positions after their root token.
+pub fn o2_collect_phrase_tokens(text: &str) -> Vec<(usize, String)> {
+    collect_phrase_tokens(&FtsTokenizer::O2, text)
+}
+
+pub fn collect_phrase_tokens(tokenizer: &FtsTokenizer, text: &str) -> Vec<(usize, String)> {
+    let mut a = fts_tokenizer_build(tokenizer, CollectType::Ingest);
+    let mut token_stream = a.token_stream(text);
+
+    let mut tokens: Vec<(usize, String)> = Vec::new();
+    let mut add_token = |token: &Token| {
+        tokens.push((token.position, token.text.to_lowercase()));
+    };
+    token_stream.process(&mut add_token);
+    tokens
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -76,6 +200,69 @@ mod tests {
         assert!(tokens.is_empty());
     }
 
+    #[test]
+    fn test_o2_collect_phrase_tokens_positions() {
+        let tokens = o2_collect_phrase_tokens("Connection reset by peer");
+        assert_eq!(
+            tokens,
+            vec![
+                (0, "connection".to_string()),
+                (1, "reset".to_string()),
+                (2, "by".to_string()),
+                (3, "peer".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_o2_collect_phrase_tokens_camel_case() {
+        // splits follow the root token like they do at ingestion
+        let tokens = o2_collect_phrase_tokens("fooBar baz");
+        let positions: Vec<_> = tokens.iter().map(|(p, _)| *p).collect();
+        assert_eq!(positions, vec![0, 1, 2, 3]);
+        assert_eq!(tokens[3].1, "baz");
+    }
+
+    #[test]
+    fn test_fts_tokenizer_name_round_trip() {
+        for tokenizer in [
+            FtsTokenizer::O2,
+            FtsTokenizer::Ngram {
+                min_gram: 2,
+                max_gram: 4,
+            },
+            FtsTokenizer::CjkBigram,
+            FtsTokenizer::PathHierarchy,
+        ] {
+            assert_eq!(
+                fts_tokenizer_from_name(&fts_tokenizer_name(&tokenizer)),
+                tokenizer
+            );
+        }
+        assert_eq!(fts_tokenizer_from_name("raw"), FtsTokenizer::O2);
+        assert_eq!(fts_tokenizer_from_name("o2_ngram_x"), FtsTokenizer::O2);
+    }
+
+    #[test]
+    fn test_collect_search_tokens_with_tokenizer() {
+        let ngram = FtsTokenizer::Ngram {
+            min_gram: 2,
+            max_gram: 3,
+        };
+        assert_eq!(
+            collect_search_tokens(&ngram, "TimeOut"),
+            vec!["tim", "ime", "meo", "eou", "out"]
+        );
+        assert_eq!(
+            collect_search_tokens(&FtsTokenizer::CjkBigram, "数据库"),
+            vec!["数据", "据库"]
+        );
+        assert_eq!(
+            collect_search_tokens(&FtsTokenizer::PathHierarchy, "/API/Users"),
+            vec!["/api/users"]
+        );
+    }
+
     #[test]
     fn test_o2_collect_search_tokens_short_words_filtered() {
         let tokens = o2_collect_search_tokens("a b hello");
@@ -0,0 +1,148 @@
+// Copyright 2026 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use tantivy::tokenizer::{Token, Tokenizer};
+
+use super::{
+    CollectType,
+    buffered::{BufferedTokenStream, push_token},
+};
+
+/// Tokenize every word into character n-grams, so a word can be found by any
+/// part of it.
+///
+/// Ingest emits all the grams between `min_gram` and `max_gram` characters,
+/// search only emits the longest grams of the searched word, which is enough
+/// to find the documents containing all of them. Words shorter than
+/// `min_gram` are kept whole.
+#[derive(Clone)]
+pub struct NgramTokenizer {
+    min_gram: usize,
+    max_gram: usize,
+    collect_type: CollectType,
+}
+
+impl NgramTokenizer {
+    pub fn new(min_gram: usize, max_gram: usize, collect_type: CollectType) -> Self {
+        let min_gram = min_gram.max(1);
+        Self {
+            min_gram,
+            max_gram: max_gram.max(min_gram),
+            collect_type,
+        }
+    }
+}
+
+impl Tokenizer for NgramTokenizer {
+    type TokenStream<'a> = BufferedTokenStream;
+
+    fn token_stream<'a>(&'a mut self, text: &'a str) -> BufferedTokenStream {
+        let mut tokens: Vec<Token> = Vec::new();
+        for (offset, word) in split_words(text) {
+            // byte offset of every char, plus the end of the word
+            let bounds: Vec<usize> = word
+                .char_indices()
+                .map(|(i, _)| offset + i)
+                .chain(std::iter::once(offset + word.len()))
+                .collect();
+            let num_chars = bounds.len() - 1;
+            match self.collect_type {
+                CollectType::Ingest => {
+                    if num_chars < self.min_gram {
+                        push_token(&mut tokens, text, bounds[0], bounds[num_chars]);
+                        continue;
+                    }
+                    for start in 0..num_chars {
+                        for len in self.min_gram..=self.max_gram {
+                            if start + len > num_chars {
+                                break;
+                            }
+                            push_token(&mut tokens, text, bounds[start], bounds[start + len]);
+                        }
+                    }
+                }
+                CollectType::Search => {
+                    if num_chars <= self.max_gram {
+                        push_token(&mut tokens, text, bounds[0], bounds[num_chars]);
+                        continue;
+                    }
+                    for start in 0..=num_chars - self.max_gram {
+                        push_token(
+                            &mut tokens,
+                            text,
+                            bounds[start],
+                            bounds[start + self.max_gram],
+                        );
+                    }
+                }
+            }
+        }
+        BufferedTokenStream::new(tokens)
+    }
+}
+
+// split the text into runs of alphanumeric chars, with their byte offset
+fn split_words(text: &str) -> impl Iterator<Item = (usize, &str)> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(move |word| (word.as_ptr() as usize - text.as_ptr() as usize, word))
+}
+
+#[cfg(test)]
+mod tests {
+    use tantivy::tokenizer::TokenStream;
+
+    use super::*;
+
+    fn collect(
+        min_gram: usize,
+        max_gram: usize,
+        collect_type: CollectType,
+        text: &str,
+    ) -> Vec<String> {
+        let mut tokenizer = NgramTokenizer::new(min_gram, max_gram, collect_type);
+        let mut stream = tokenizer.token_stream(text);
+        let mut tokens = Vec::new();
+        while stream.advance() {
+            tokens.push(stream.token().text.clone());
+        }
+        tokens
+    }
+
+    #[test]
+    fn test_ngram_ingest() {
+        assert_eq!(
+            collect(2, 3, CollectType::Ingest, "abcd x"),
+            vec!["ab", "abc", "bc", "bcd", "cd", "x"]
+        );
+    }
+
+    #[test]
+    fn test_ngram_search() {
+        assert_eq!(
+            collect(2, 3, CollectType::Search, "abcd"),
+            vec!["abc", "bcd"]
+        );
+        assert_eq!(collect(2, 3, CollectType::Search, "ab x"), vec!["ab", "x"]);
+    }
+
+    #[test]
+    fn test_ngram_multibyte() {
+        assert_eq!(
+            collect(2, 2, CollectType::Ingest, "数据库"),
+            vec!["数据", "据库"]
+        );
+    }
+}
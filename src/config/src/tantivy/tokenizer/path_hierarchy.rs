@@ -0,0 +1,105 @@
+// Copyright 2026 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use tantivy::tokenizer::{Token, Tokenizer};
+
+use super::{
+    CollectType,
+    buffered::{BufferedTokenStream, push_token},
+};
+
+/// Tokenize URLs and file paths into their ancestors, so a path can be found
+/// by any of its parent directories.
+///
+/// The text is split on whitespace and quotes, then ingest emits every prefix
+/// of a path ending before a `/` or `?`, e.g. `/var/log/app.log` becomes
+/// `/var`, `/var/log` and `/var/log/app.log`. Search emits the searched paths
+/// whole.
+#[derive(Clone, Default)]
+pub struct PathHierarchyTokenizer {
+    collect_type: CollectType,
+}
+
+impl PathHierarchyTokenizer {
+    pub fn new(collect_type: CollectType) -> Self {
+        Self { collect_type }
+    }
+}
+
+impl Tokenizer for PathHierarchyTokenizer {
+    type TokenStream<'a> = BufferedTokenStream;
+
+    fn token_stream<'a>(&'a mut self, text: &'a str) -> BufferedTokenStream {
+        let mut tokens: Vec<Token> = Vec::new();
+        let paths = text
+            .split(|c: char| c.is_whitespace() || matches!(c, '"' | '\'' | ',' | ';'))
+            .map(|path| path.trim_end_matches('/'))
+            .filter(|path| !path.is_empty());
+        for path in paths {
+            let offset = path.as_ptr() as usize - text.as_ptr() as usize;
+            if matches!(self.collect_type, CollectType::Ingest) {
+                let bytes = path.as_bytes();
+                for (i, b) in bytes.iter().copied().enumerate() {
+                    // skip the leading `/` and the empty segments, like `//`
+                    if matches!(b, b'/' | b'?') && i > 0 && bytes[i - 1] != b'/' {
+                        push_token(&mut tokens, text, offset, offset + i);
+                    }
+                }
+            }
+            push_token(&mut tokens, text, offset, offset + path.len());
+        }
+        BufferedTokenStream::new(tokens)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tantivy::tokenizer::TokenStream;
+
+    use super::*;
+
+    fn collect(collect_type: CollectType, text: &str) -> Vec<String> {
+        let mut tokenizer = PathHierarchyTokenizer::new(collect_type);
+        let mut stream = tokenizer.token_stream(text);
+        let mut tokens = Vec::new();
+        while stream.advance() {
+            tokens.push(stream.token().text.clone());
+        }
+        tokens
+    }
+
+    #[test]
+    fn test_path_hierarchy_ingest() {
+        assert_eq!(
+            collect(CollectType::Ingest, "open /var/log/app.log failed"),
+            vec!["open", "/var", "/var/log", "/var/log/app.log", "failed"]
+        );
+        assert_eq!(
+            collect(CollectType::Ingest, "https://example.com/api/users?id=1"),
+            vec![
+                "https:",
+                "https://example.com",
+                "https://example.com/api",
+                "https://example.com/api/users",
+                "https://example.com/api/users?id=1",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_path_hierarchy_search() {
+        assert_eq!(collect(CollectType::Search, "/var/log/"), vec!["/var/log"]);
+    }
+}
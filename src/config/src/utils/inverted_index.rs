@@ -59,10 +59,27 @@ pub fn to_tantivy_name(from: &str) -> Option<String> {
     Some(parts.join("/"))
 }
 
+/// Suffix of the tantivy numeric fast field that backs a range index, kept
+/// apart from the raw-text term field the same column may also have.
+pub const RANGE_INDEX_FIELD_SUFFIX: &str = ".__range";
+
+/// Returns the tantivy field name used for the range index of `field`.
+pub fn to_range_index_field_name(field: &str) -> String {
+    format!("{field}{RANGE_INDEX_FIELD_SUFFIX}")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_to_range_index_field_name() {
+        assert_eq!(
+            to_range_index_field_name("latency_ms"),
+            "latency_ms.__range"
+        );
+    }
+
     #[test]
     fn test_to_tantivy_name() {
         let test_cases = vec![
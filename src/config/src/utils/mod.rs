@@ -45,3 +45,4 @@ pub mod sysinfo;
 pub mod time;
 pub mod took_watcher;
 pub mod util;
+pub mod vector;
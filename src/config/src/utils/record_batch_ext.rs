@@ -28,7 +28,10 @@ use arrow_schema::{ArrowError, DataType, Schema};
 use datafusion::{error::DataFusionError, physical_plan::spill::get_record_batch_memory_size};
 use hashbrown::{HashMap, HashSet};
 
-use super::schema_ext::SchemaExt;
+use super::{
+    schema_ext::SchemaExt,
+    vector::{append_vector, json_to_vector, parse_vector},
+};
 use crate::{FxIndexMap, TIMESTAMP_COL_NAME};
 
 const USIZE_SIZE: usize = std::mem::size_of::<usize>();
@@ -258,6 +261,23 @@ pub fn convert_vrl_to_record_batch(
                         let b = builder.as_any_mut().downcast_mut::<NullBuilder>().unwrap();
                         b.append_null();
                     }
+                    DataType::FixedSizeList(_, dimensions) => {
+                        let vector = match v {
+                            vrl::value::Value::Bytes(bytes) => {
+                                parse_vector(&String::from_utf8_lossy(bytes))
+                            }
+                            vrl::value::Value::Array(values) => values
+                                .iter()
+                                .map(|v| match v {
+                                    vrl::value::Value::Integer(i) => Some(*i as f32),
+                                    vrl::value::Value::Float(f) => Some(f.into_inner() as f32),
+                                    _ => None,
+                                })
+                                .collect(),
+                            _ => None,
+                        };
+                        append_vector(builder.as_mut(), *dimensions as usize, vector.as_deref());
+                    }
                     _ => {
                         return Err(ArrowError::SchemaError(
                             "Cannot convert VRL to RecordBatch from non-basic type value"
@@ -326,6 +346,9 @@ pub fn convert_vrl_to_record_batch(
                             .unwrap()
                             .append_null();
                     }
+                    DataType::FixedSizeList(_, dimensions) => {
+                        append_vector(b.as_mut(), *dimensions as usize, None);
+                    }
                     _ => {}
                 }
             }
@@ -1159,6 +1182,11 @@ fn append_value_optimized(
             b.append_null();
             Ok(())
         }
+        DataType::FixedSizeList(_, dimensions) => {
+            let vector = json_to_vector(value);
+            append_vector(builder.as_mut(), *dimensions as usize, vector.as_deref());
+            Ok(())
+        }
         _ => Err(ArrowError::SchemaError(
             "Cannot convert json to RecordBatch from non-basic type value".to_string(),
         )),
@@ -1232,6 +1260,9 @@ fn append_null_optimized(builder: &mut Box<dyn ArrayBuilder>, data_type: &DataTy
                 .unwrap()
                 .append_null();
         }
+        DataType::FixedSizeList(_, dimensions) => {
+            append_vector(builder.as_mut(), *dimensions as usize, None);
+        }
         _ => {}
     }
 }
@@ -2259,6 +2290,29 @@ mod test {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_convert_json_to_record_batch_vector() {
+        use crate::utils::vector::{array_to_vectors, vector_data_type};
+
+        let schema = Arc::new(Schema::new(vec![Field::new(
+            "embedding",
+            vector_data_type(2),
+            true,
+        )]));
+        let data = vec![
+            Arc::new(serde_json::json!({"embedding": [0.5, 1]})),
+            Arc::new(serde_json::json!({"embedding": "[1, 0]"})),
+            Arc::new(serde_json::json!({"embedding": [1, 2, 3]})),
+            Arc::new(serde_json::json!({"other": 1})),
+        ];
+        let batch = convert_json_to_record_batch(&schema, &data).unwrap();
+        assert_eq!(batch.column(0).data_type(), &vector_data_type(2));
+        assert_eq!(
+            array_to_vectors(batch.column(0)).unwrap(),
+            vec![Some(vec![0.5, 1.0]), Some(vec![1.0, 0.0]), None, None]
+        );
+    }
+
     #[test]
     fn test_convert_json_to_record_batch_large_utf8() {
         let schema = Arc::new(Schema::new(vec![Field::new(
@@ -0,0 +1,925 @@
+// Copyright 2026 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::{
+    cmp::Ordering,
+    collections::{BTreeMap, HashMap, HashSet},
+    ops::ControlFlow,
+};
+
+use sqlparser::{
+    ast::{
+        Expr, FunctionArg, FunctionArgExpr, FunctionArguments, GroupByExpr, Ident, LimitClause,
+        ObjectName, OrderByKind, Query, Select, SelectItem, SetExpr, Statement, UnaryOperator,
+        Value, ValueWithSpan, VisitMut, VisitorMut,
+    },
+    dialect::PostgreSqlDialect,
+    parser::{Parser, ParserError},
+};
+
+use crate::utils::{json, time::parse_timezone_to_offset_at};
+
+pub const TIME_BUCKET_GAPFILL: &str = "time_bucket_gapfill";
+pub const LOCF: &str = "locf";
+pub const INTERPOLATE: &str = "interpolate";
+pub const FILL_VALUE: &str = "fill_value";
+
+/// The origin of the histogram buckets, 2001-01-01T00:00:00Z.
+const BUCKET_ORIGIN_MICROS: i64 = 978_307_200_000_000;
+/// Don't materialize more buckets than this per series.
+const MAX_GAPFILL_BUCKETS: i64 = 100_000;
+
+/// How a column is filled in the missing buckets.
+#[derive(Debug, Clone, PartialEq)]
+pub enum GapFillStrategy {
+    /// `locf(expr)`, the last non null value of the series.
+    Locf,
+    /// `interpolate(expr)`, linear interpolation between the surrounding values.
+    Interpolate,
+    /// `fill_value(expr, constant)`, a constant.
+    Value(json::Value),
+}
+
+/// Gap filling of a `time_bucket_gapfill()` query.
+///
+/// The gap filling functions are rewritten to plain SQL before the query is
+/// executed, `time_bucket_gapfill(interval, _timestamp)` becomes
+/// `histogram(_timestamp, interval)` and the fill functions are removed, so
+/// the result cache and the partitions work as for any histogram query. The
+/// missing buckets are added to the merged result of the cached hits and the
+/// partitions by [`GapFill::fill`], which then applies the ORDER BY, OFFSET and
+/// LIMIT of the query, the LIMIT and OFFSET are removed from the executed SQL.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GapFill {
+    /// The alias of the bucket column.
+    pub bucket_column: String,
+    /// The timezone argument of `time_bucket_gapfill()`.
+    pub timezone: Option<String>,
+    /// The group by columns, each distinct combination is filled separately.
+    pub series_columns: Vec<String>,
+    /// The columns with a fill function, the other columns are null in the
+    /// added buckets.
+    pub fill_columns: Vec<(String, GapFillStrategy)>,
+    /// The ORDER BY of the query, applied again after the buckets are added.
+    pub order_by: Vec<GapFillOrder>,
+    /// The LIMIT of the query.
+    pub limit: Option<usize>,
+    /// The OFFSET of the query.
+    pub offset: usize,
+}
+
+/// An ORDER BY term of a gap filling query.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GapFillOrder {
+    pub column: String,
+    pub descending: bool,
+    pub nulls_first: bool,
+}
+
+/// Rewrite the gap filling functions of the query to plain SQL.
+///
+/// Returns `None` when the query doesn't use gap filling. The bucket and the
+/// filled columns must be selected with an alias in the outermost query.
+pub fn rewrite_gapfill(sql: &str) -> Result<Option<(String, GapFill)>, ParserError> {
+    let lower = sql.to_lowercase();
+    if ![TIME_BUCKET_GAPFILL, LOCF, INTERPOLATE, FILL_VALUE]
+        .iter()
+        .any(|name| lower.contains(name))
+    {
+        return Ok(None);
+    }
+    let mut statements = Parser::parse_sql(&PostgreSqlDialect {}, sql)?;
+    if statements.len() != 1 {
+        return Ok(None);
+    }
+    let statement = &mut statements[0];
+    let mut visitor = GapFillVisitor::default();
+    let _ = statement.visit(&mut visitor);
+    if let Some(e) = visitor.error {
+        return Err(ParserError::ParserError(e));
+    }
+    if !visitor.found {
+        return Ok(None);
+    }
+    let mut gapfill = gapfill_from_statement(statement)?;
+    // the limit is applied after the missing buckets are added
+    if let Statement::Query(query) = statement {
+        let (limit, offset) = take_limit(query)?;
+        gapfill.limit = limit;
+        gapfill.offset = offset;
+    }
+    // the spec is collected from the original projection, rewrite afterwards
+    let mut rewriter = GapFillRewriter::default();
+    let _ = statement.visit(&mut rewriter);
+    if let Some(e) = rewriter.error {
+        return Err(ParserError::ParserError(e));
+    }
+    Ok(Some((statement.to_string(), gapfill)))
+}
+
+fn function_name(expr: &Expr) -> Option<String> {
+    match expr {
+        Expr::Function(func) => Some(func.name.to_string().to_lowercase()),
+        _ => None,
+    }
+}
+
+fn function_args(expr: &Expr) -> Vec<&Expr> {
+    let Expr::Function(func) = expr else {
+        return vec![];
+    };
+    let FunctionArguments::List(list) = &func.args else {
+        return vec![];
+    };
+    list.args
+        .iter()
+        .filter_map(|arg| match arg {
+            FunctionArg::Unnamed(FunctionArgExpr::Expr(e)) => Some(e),
+            _ => None,
+        })
+        .collect()
+}
+
+fn literal_to_json(expr: &Expr) -> Option<json::Value> {
+    match expr {
+        Expr::Value(ValueWithSpan { value, .. }) => match value {
+            Value::Number(n, _) => n
+                .parse::<i64>()
+                .map(json::Value::from)
+                .or_else(|_| n.parse::<f64>().map(json::Value::from))
+                .ok(),
+            Value::SingleQuotedString(s) | Value::DoubleQuotedString(s) => {
+                Some(json::Value::String(s.clone()))
+            }
+            Value::Boolean(b) => Some(json::Value::Bool(*b)),
+            Value::Null => Some(json::Value::Null),
+            _ => None,
+        },
+        Expr::UnaryOp {
+            op: UnaryOperator::Minus,
+            expr,
+        } => match literal_to_json(expr)? {
+            json::Value::Number(n) => n
+                .as_i64()
+                .map(|v| json::Value::from(-v))
+                .or_else(|| n.as_f64().map(|v| json::Value::from(-v))),
+            _ => None,
+        },
+        Expr::Nested(expr) => literal_to_json(expr),
+        _ => None,
+    }
+}
+
+fn fill_strategy(expr: &Expr) -> Result<Option<GapFillStrategy>, ParserError> {
+    let Some(name) = function_name(expr) else {
+        return Ok(None);
+    };
+    let args = function_args(expr);
+    let strategy = match name.as_str() {
+        LOCF if args.len() == 1 => GapFillStrategy::Locf,
+        INTERPOLATE if args.len() == 1 => GapFillStrategy::Interpolate,
+        FILL_VALUE if args.len() == 2 => match literal_to_json(args[1]) {
+            Some(value) => GapFillStrategy::Value(value),
+            None => {
+                return Err(ParserError::ParserError(
+                    "fill_value() needs a constant as the second argument".to_string(),
+                ));
+            }
+        },
+        LOCF | INTERPOLATE | FILL_VALUE => {
+            return Err(ParserError::ParserError(format!(
+                "Invalid number of arguments for {name}()"
+            )));
+        }
+        _ => return Ok(None),
+    };
+    Ok(Some(strategy))
+}
+
+fn gapfill_from_statement(statement: &Statement) -> Result<GapFill, ParserError> {
+    let query_select = match statement {
+        Statement::Query(query) => match query.body.as_ref() {
+            SetExpr::Select(select) => Some((query, select)),
+            _ => None,
+        },
+        _ => None,
+    };
+    let Some((query, select)) = query_select else {
+        return Err(ParserError::ParserError(
+            "time_bucket_gapfill() is only supported in a SELECT query".to_string(),
+        ));
+    };
+    let group_by: HashSet<String> = match &select.group_by {
+        GroupByExpr::Expressions(exprs, _) => exprs.iter().map(|e| e.to_string()).collect(),
+        _ => HashSet::new(),
+    };
+
+    let mut gapfill = GapFill::default();
+    let mut columns = Vec::with_capacity(select.projection.len());
+    for item in select.projection.iter() {
+        let (expr, name) = match item {
+            SelectItem::ExprWithAlias { expr, alias } => (expr, Some(alias.value.clone())),
+            SelectItem::UnnamedExpr(expr) => {
+                let name = match expr {
+                    Expr::Identifier(ident) => Some(ident.value.clone()),
+                    Expr::CompoundIdentifier(idents) => idents.last().map(|i| i.value.clone()),
+                    _ => None,
+                };
+                (expr, name)
+            }
+            _ => {
+                columns.push((String::new(), None));
+                continue;
+            }
+        };
+        columns.push((expr.to_string(), name.clone()));
+        let is_bucket = function_name(expr).as_deref() == Some(TIME_BUCKET_GAPFILL);
+        let strategy = fill_strategy(expr)?;
+        if is_bucket || strategy.is_some() {
+            let Some(name) = name.filter(|_| matches!(item, SelectItem::ExprWithAlias { .. }))
+            else {
+                return Err(ParserError::ParserError(format!(
+                    "{} must be selected with an alias",
+                    function_name(expr).unwrap_or_default()
+                )));
+            };
+            if is_bucket {
+                if !gapfill.bucket_column.is_empty() {
+                    return Err(ParserError::ParserError(
+                        "time_bucket_gapfill() can only be selected once".to_string(),
+                    ));
+                }
+                gapfill.bucket_column = name;
+                gapfill.timezone = function_args(expr).get(2).and_then(|tz| {
+                    literal_to_json(tz).and_then(|v| v.as_str().map(|s| s.to_string()))
+                });
+            } else if let Some(strategy) = strategy {
+                gapfill.fill_columns.push((name, strategy));
+            }
+        } else if let Some(name) = name
+            && (group_by.contains(&expr.to_string()) || group_by.contains(&name))
+        {
+            gapfill.series_columns.push(name);
+        }
+    }
+    if gapfill.bucket_column.is_empty() {
+        return Err(ParserError::ParserError(
+            "Gap filling needs time_bucket_gapfill() in the outermost SELECT".to_string(),
+        ));
+    }
+    gapfill.order_by = order_by_columns(query, select, &columns)?;
+    Ok(gapfill)
+}
+
+/// Resolve the ORDER BY terms to the selected columns, by name, by the
+/// selected expression or by position.
+fn order_by_columns(
+    query: &Query,
+    select: &Select,
+    columns: &[(String, Option<String>)],
+) -> Result<Vec<GapFillOrder>, ParserError> {
+    let Some(order_by) = query.order_by.as_ref() else {
+        return Ok(vec![]);
+    };
+    let OrderByKind::Expressions(exprs) = &order_by.kind else {
+        return Err(ParserError::ParserError(
+            "ORDER BY ALL is not supported with time_bucket_gapfill()".to_string(),
+        ));
+    };
+    let mut order = Vec::with_capacity(exprs.len());
+    for expr in exprs.iter() {
+        let column = match &expr.expr {
+            Expr::Value(ValueWithSpan {
+                value: Value::Number(n, _),
+                ..
+            }) => n
+                .to_string()
+                .parse::<usize>()
+                .ok()
+                .and_then(|n| n.checked_sub(1))
+                .and_then(|i| columns.get(i))
+                .and_then(|(_, name)| name.clone()),
+            Expr::Identifier(ident) => Some(ident.value.clone()),
+            Expr::CompoundIdentifier(idents) => idents.last().map(|i| i.value.clone()),
+            e => {
+                let e = e.to_string();
+                columns
+                    .iter()
+                    .find(|(expr, _)| expr == &e)
+                    .and_then(|(_, name)| name.clone())
+            }
+        };
+        let Some(column) = column.filter(|c| {
+            columns.iter().any(|(_, name)| name.as_ref() == Some(c))
+                || select.projection.iter().any(|item| {
+                    matches!(
+                        item,
+                        SelectItem::Wildcard(_) | SelectItem::QualifiedWildcard(..)
+                    )
+                })
+        }) else {
+            return Err(ParserError::ParserError(format!(
+                "ORDER BY {} must reference a selected column with time_bucket_gapfill()",
+                expr.expr
+            )));
+        };
+        let descending = !expr.options.asc.unwrap_or(true);
+        order.push(GapFillOrder {
+            column,
+            descending,
+            nulls_first: expr.options.nulls_first.unwrap_or(descending),
+        });
+    }
+    Ok(order)
+}
+
+/// Remove the LIMIT and OFFSET of the query, they are applied to the filled
+/// result.
+fn take_limit(query: &mut Query) -> Result<(Option<usize>, usize), ParserError> {
+    let (limit, offset) = match query.limit_clause.take() {
+        None => (None, None),
+        Some(LimitClause::LimitOffset {
+            limit,
+            offset,
+            limit_by,
+        }) => {
+            if !limit_by.is_empty() {
+                return Err(ParserError::ParserError(
+                    "LIMIT BY is not supported with time_bucket_gapfill()".to_string(),
+                ));
+            }
+            (limit, offset.map(|o| o.value))
+        }
+        Some(LimitClause::OffsetCommaLimit { offset, limit }) => (Some(limit), Some(offset)),
+    };
+    let number = |expr: Option<Expr>| -> Result<Option<usize>, ParserError> {
+        let Some(expr) = expr else {
+            return Ok(None);
+        };
+        match &expr {
+            Expr::Value(ValueWithSpan {
+                value: Value::Null, ..
+            }) => return Ok(None),
+            Expr::Value(ValueWithSpan {
+                value: Value::Number(n, _),
+                ..
+            }) => {
+                if let Ok(n) = n.to_string().parse::<usize>() {
+                    return Ok(Some(n));
+                }
+            }
+            _ => {}
+        }
+        Err(ParserError::ParserError(format!(
+            "LIMIT and OFFSET must be constants with time_bucket_gapfill(), got {expr}"
+        )))
+    };
+    Ok((number(limit)?, number(offset)?.unwrap_or_default()))
+}
+
+#[derive(Default)]
+struct GapFillVisitor {
+    found: bool,
+    error: Option<String>,
+}
+
+impl VisitorMut for GapFillVisitor {
+    type Break = ();
+
+    fn pre_visit_expr(&mut self, expr: &mut Expr) -> ControlFlow<Self::Break> {
+        if let Some(name) = function_name(expr) {
+            match name.as_str() {
+                TIME_BUCKET_GAPFILL => {
+                    let args = function_args(expr).len();
+                    if !(2..=3).contains(&args) {
+                        self.error = Some(
+                            "time_bucket_gapfill() needs an interval and a timestamp column"
+                                .to_string(),
+                        );
+                        return ControlFlow::Break(());
+                    }
+                    self.found = true;
+                }
+                LOCF | INTERPOLATE | FILL_VALUE => self.found = true,
+                _ => {}
+            }
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+#[derive(Default)]
+struct GapFillRewriter {
+    error: Option<String>,
+}
+
+impl VisitorMut for GapFillRewriter {
+    type Break = ();
+
+    fn post_visit_expr(&mut self, expr: &mut Expr) -> ControlFlow<Self::Break> {
+        let Some(name) = function_name(expr) else {
+            return ControlFlow::Continue(());
+        };
+        match name.as_str() {
+            TIME_BUCKET_GAPFILL => {
+                // time_bucket_gapfill(interval, ts[, tz]) -> histogram(ts, interval[, tz])
+                if let Expr::Function(func) = expr
+                    && let FunctionArguments::List(list) = &mut func.args
+                {
+                    list.args.swap(0, 1);
+                    func.name = ObjectName::from(vec![Ident::new("histogram")]);
+                }
+            }
+            LOCF | INTERPOLATE | FILL_VALUE => match function_args(expr).first() {
+                Some(inner) => *expr = (*inner).clone(),
+                None => {
+                    self.error = Some(format!("{name}() needs an argument"));
+                    return ControlFlow::Break(());
+                }
+            },
+            _ => {}
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+impl GapFill {
+    /// Add the missing buckets of `time_range` to the hits.
+    ///
+    /// `interval` is the bucket width in microseconds, the bucket timestamps
+    /// are shifted by the timezone like the histogram buckets are. The result
+    /// is ordered by the ORDER BY of the query, or else by the bucket, and the
+    /// OFFSET and LIMIT of the query are applied to it.
+    pub fn fill(
+        &self,
+        hits: &mut Vec<json::Value>,
+        time_range: (i64, i64),
+        interval: i64,
+        default_timezone: Option<&str>,
+        descending: bool,
+    ) {
+        self.add_buckets(hits, time_range, interval, default_timezone, descending);
+        if self.offset > 0 {
+            hits.drain(..self.offset.min(hits.len()));
+        }
+        if let Some(limit) = self.limit {
+            hits.truncate(limit);
+        }
+    }
+
+    fn add_buckets(
+        &self,
+        hits: &mut Vec<json::Value>,
+        time_range: (i64, i64),
+        interval: i64,
+        default_timezone: Option<&str>,
+        descending: bool,
+    ) {
+        let (start_time, end_time) = time_range;
+        if interval <= 0 || end_time <= start_time {
+            return;
+        }
+        let offset = self
+            .timezone
+            .as_deref()
+            .or(default_timezone)
+            .and_then(|tz| parse_timezone_to_offset_at(tz, end_time))
+            .unwrap_or_default()
+            * 1_000_000;
+        let bin = |ts: i64| {
+            BUCKET_ORIGIN_MICROS + (ts - BUCKET_ORIGIN_MICROS).div_euclid(interval) * interval
+        };
+        let first = bin(start_time + offset);
+        let last = bin(end_time + offset - 1);
+        if (last - first) / interval >= MAX_GAPFILL_BUCKETS {
+            log::warn!(
+                "[GAPFILL] skip gap filling, too many buckets: {}",
+                (last - first) / interval + 1
+            );
+            return;
+        }
+        let numeric_bucket = hits
+            .first()
+            .and_then(|hit| hit.get(&self.bucket_column))
+            .is_some_and(|v| v.is_number());
+
+        // group the hits by series, keeping the order the series first appear
+        let mut series_index: HashMap<String, usize> = HashMap::new();
+        let mut series: Vec<(Vec<json::Value>, BTreeMap<i64, Vec<json::Value>>)> = Vec::new();
+        for hit in hits.drain(..) {
+            let key: Vec<json::Value> = self
+                .series_columns
+                .iter()
+                .map(|col| hit.get(col).cloned().unwrap_or(json::Value::Null))
+                .collect();
+            let ts = bucket_micros(hit.get(&self.bucket_column));
+            let idx = *series_index
+                .entry(json::Value::Array(key.clone()).to_string())
+                .or_insert_with(|| {
+                    series.push((key, BTreeMap::new()));
+                    series.len() - 1
+                });
+            series[idx].1.entry(ts).or_default().push(hit);
+        }
+        if series.is_empty() && self.series_columns.is_empty() {
+            series.push((vec![], BTreeMap::new()));
+        }
+
+        let mut rows = Vec::new();
+        for (key, mut buckets) in series {
+            let known: Vec<BTreeMap<i64, json::Value>> = self
+                .fill_columns
+                .iter()
+                .map(|(col, _)| {
+                    buckets
+                        .iter()
+                        .filter_map(|(ts, hits)| {
+                            hits.iter()
+                                .rev()
+                                .find_map(|hit| hit.get(col).filter(|v| !v.is_null()))
+                                .map(|v| (*ts, v.clone()))
+                        })
+                        .collect()
+                })
+                .collect();
+            let mut ts = first;
+            while ts <= last {
+                if !buckets.contains_key(&ts) {
+                    let mut row = json::Map::new();
+                    let bucket = if numeric_bucket {
+                        json::Value::from(ts)
+                    } else {
+                        json::Value::String(format_bucket(ts))
+                    };
+                    row.insert(self.bucket_column.clone(), bucket);
+                    for (col, value) in self.series_columns.iter().zip(key.iter()) {
+                        row.insert(col.clone(), value.clone());
+                    }
+                    for ((col, strategy), known) in self.fill_columns.iter().zip(known.iter()) {
+                        row.insert(col.clone(), fill_value(strategy, known, ts));
+                    }
+                    buckets.insert(ts, vec![json::Value::Object(row)]);
+                }
+                ts += interval;
+            }
+            rows.extend(
+                buckets
+                    .into_iter()
+                    .flat_map(|(ts, hits)| hits.into_iter().map(move |hit| (ts, hit))),
+            );
+        }
+        // stable, the series keep their order within a bucket
+        if !self.order_by.is_empty() {
+            rows.sort_by(|a, b| {
+                self.order_by
+                    .iter()
+                    .map(|order| {
+                        compare_values(a.1.get(&order.column), b.1.get(&order.column), order)
+                    })
+                    .find(|o| o.is_ne())
+                    .unwrap_or(Ordering::Equal)
+            });
+        } else if descending {
+            rows.sort_by(|a, b| b.0.cmp(&a.0));
+        } else {
+            rows.sort_by(|a, b| a.0.cmp(&b.0));
+        }
+        hits.extend(rows.into_iter().map(|(_, hit)| hit));
+    }
+}
+
+/// Compare two values of an ORDER BY column, numbers numerically and the
+/// other values by their type and then by value.
+fn compare_values(
+    a: Option<&json::Value>,
+    b: Option<&json::Value>,
+    order: &GapFillOrder,
+) -> Ordering {
+    let a = a.filter(|v| !v.is_null());
+    let b = b.filter(|v| !v.is_null());
+    let ordering = match (a, b) {
+        (None, None) => return Ordering::Equal,
+        (None, Some(_)) if order.nulls_first => return Ordering::Less,
+        (None, Some(_)) => return Ordering::Greater,
+        (Some(_), None) if order.nulls_first => return Ordering::Greater,
+        (Some(_), None) => return Ordering::Less,
+        (Some(a), Some(b)) => match (a, b) {
+            (json::Value::Number(x), json::Value::Number(y)) => match (x.as_i64(), y.as_i64()) {
+                (Some(x), Some(y)) => x.cmp(&y),
+                _ => x
+                    .as_f64()
+                    .unwrap_or_default()
+                    .total_cmp(&y.as_f64().unwrap_or_default()),
+            },
+            (json::Value::String(x), json::Value::String(y)) => x.cmp(y),
+            (json::Value::Bool(x), json::Value::Bool(y)) => x.cmp(y),
+            _ => value_rank(a)
+                .cmp(&value_rank(b))
+                .then_with(|| a.to_string().cmp(&b.to_string())),
+        },
+    };
+    if order.descending {
+        ordering.reverse()
+    } else {
+        ordering
+    }
+}
+
+fn value_rank(value: &json::Value) -> u8 {
+    match value {
+        json::Value::Null => 0,
+        json::Value::Bool(_) => 1,
+        json::Value::Number(_) => 2,
+        json::Value::String(_) => 3,
+        json::Value::Array(_) => 4,
+        json::Value::Object(_) => 5,
+    }
+}
+
+fn bucket_micros(value: Option<&json::Value>) -> i64 {
+    match value {
+        Some(json::Value::Number(n)) => n.as_i64().unwrap_or_default(),
+        Some(json::Value::String(s)) => {
+            crate::utils::time::parse_str_to_timestamp_micros_as_option(s).unwrap_or_default()
+        }
+        _ => 0,
+    }
+}
+
+/// Format the bucket like the arrow json writer formats a timestamp.
+fn format_bucket(ts: i64) -> String {
+    chrono::DateTime::from_timestamp_micros(ts)
+        .map(|t| t.naive_utc().format("%Y-%m-%dT%H:%M:%S%.f").to_string())
+        .unwrap_or_default()
+}
+
+fn fill_value(
+    strategy: &GapFillStrategy,
+    known: &BTreeMap<i64, json::Value>,
+    ts: i64,
+) -> json::Value {
+    match strategy {
+        GapFillStrategy::Value(value) => value.clone(),
+        GapFillStrategy::Locf => known
+            .range(..ts)
+            .next_back()
+            .map(|(_, v)| v.clone())
+            .unwrap_or(json::Value::Null),
+        GapFillStrategy::Interpolate => {
+            let prev = known.range(..ts).next_back();
+            let next = known.range(ts + 1..).next();
+            match (prev, next) {
+                (Some((t0, v0)), Some((t1, v1))) => match (v0.as_f64(), v1.as_f64()) {
+                    (Some(v0), Some(v1)) => {
+                        let v = v0 + (v1 - v0) * (ts - t0) as f64 / (t1 - t0) as f64;
+                        json::Number::from_f64(v)
+                            .map(json::Value::Number)
+                            .unwrap_or(json::Value::Null)
+                    }
+                    _ => json::Value::Null,
+                },
+                _ => json::Value::Null,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MIN: i64 = 60_000_000;
+    // 2024-01-01T00:00:00Z
+    const T0: i64 = 1_704_067_200_000_000;
+
+    #[test]
+    fn test_rewrite_gapfill() {
+        let sql = "SELECT time_bucket_gapfill('1 minute', _timestamp) AS ts, host, \
+                   locf(avg(cpu)) AS cpu, interpolate(max(mem)) AS mem, \
+                   fill_value(count(*), 0) AS cnt, sum(x) AS x \
+                   FROM \"default\" GROUP BY ts, host ORDER BY ts";
+        let (sql, gapfill) = rewrite_gapfill(sql).unwrap().unwrap();
+        assert_eq!(
+            sql,
+            "SELECT histogram(_timestamp, '1 minute') AS ts, host, avg(cpu) AS cpu, \
+             max(mem) AS mem, count(*) AS cnt, sum(x) AS x FROM \"default\" \
+             GROUP BY ts, host ORDER BY ts"
+        );
+        assert_eq!(
+            gapfill,
+            GapFill {
+                bucket_column: "ts".to_string(),
+                timezone: None,
+                series_columns: vec!["host".to_string()],
+                fill_columns: vec![
+                    ("cpu".to_string(), GapFillStrategy::Locf),
+                    ("mem".to_string(), GapFillStrategy::Interpolate),
+                    ("cnt".to_string(), GapFillStrategy::Value(json::json!(0))),
+                ],
+                order_by: vec![GapFillOrder {
+                    column: "ts".to_string(),
+                    descending: false,
+                    nulls_first: false,
+                }],
+                limit: None,
+                offset: 0,
+            }
+        );
+
+        let sql = "SELECT time_bucket_gapfill('1 hour', _timestamp, '+08:00') AS ts, \
+                   fill_value(count(*), -1.5) AS cnt FROM t GROUP BY ts";
+        let (_, gapfill) = rewrite_gapfill(sql).unwrap().unwrap();
+        assert_eq!(gapfill.timezone.as_deref(), Some("+08:00"));
+        assert_eq!(
+            gapfill.fill_columns[0].1,
+            GapFillStrategy::Value(json::json!(-1.5))
+        );
+
+        // the limit is applied to the filled result
+        let sql = "SELECT time_bucket_gapfill('1 minute', _timestamp) AS ts, \
+                   fill_value(count(*), 0) AS cnt FROM t GROUP BY ts \
+                   ORDER BY 2 DESC, ts LIMIT 10 OFFSET 5";
+        let (sql, gapfill) = rewrite_gapfill(sql).unwrap().unwrap();
+        assert_eq!(
+            sql,
+            "SELECT histogram(_timestamp, '1 minute') AS ts, count(*) AS cnt FROM t \
+             GROUP BY ts ORDER BY 2 DESC, ts"
+        );
+        assert_eq!(
+            gapfill.order_by,
+            vec![
+                GapFillOrder {
+                    column: "cnt".to_string(),
+                    descending: true,
+                    nulls_first: true,
+                },
+                GapFillOrder {
+                    column: "ts".to_string(),
+                    descending: false,
+                    nulls_first: false,
+                },
+            ]
+        );
+        assert_eq!(gapfill.limit, Some(10));
+        assert_eq!(gapfill.offset, 5);
+    }
+
+    #[test]
+    fn test_rewrite_gapfill_errors() {
+        assert!(
+            rewrite_gapfill("SELECT histogram(_timestamp) AS ts FROM t")
+                .unwrap()
+                .is_none()
+        );
+        for sql in [
+            "SELECT time_bucket_gapfill('1 minute', _timestamp) FROM t GROUP BY 1",
+            "SELECT time_bucket_gapfill(_timestamp) AS ts FROM t GROUP BY ts",
+            "SELECT time_bucket_gapfill('1 minute', _timestamp) AS ts, locf(avg(a)) \
+             FROM t GROUP BY ts",
+            "SELECT histogram(_timestamp) AS ts, locf(avg(a)) AS a FROM t GROUP BY ts",
+            "SELECT time_bucket_gapfill('1 minute', _timestamp) AS ts, \
+             fill_value(count(*), a) AS c FROM t GROUP BY ts",
+            "SELECT time_bucket_gapfill('1 minute', _timestamp) AS ts, \
+             fill_value(count(*), 0) AS c FROM t GROUP BY ts ORDER BY max(a)",
+            "SELECT time_bucket_gapfill('1 minute', _timestamp) AS ts, \
+             fill_value(count(*), 0) AS c FROM t GROUP BY ts LIMIT $1",
+        ] {
+            assert!(rewrite_gapfill(sql).is_err(), "{sql}");
+        }
+    }
+
+    fn gapfill(series: &[&str], fill: Vec<(&str, GapFillStrategy)>) -> GapFill {
+        GapFill {
+            bucket_column: "ts".to_string(),
+            timezone: None,
+            series_columns: series.iter().map(|s| s.to_string()).collect(),
+            fill_columns: fill.into_iter().map(|(c, s)| (c.to_string(), s)).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_gapfill_fill() {
+        let gapfill = gapfill(
+            &[],
+            vec![
+                ("a", GapFillStrategy::Locf),
+                ("b", GapFillStrategy::Interpolate),
+                ("c", GapFillStrategy::Value(json::json!(0))),
+            ],
+        );
+        let mut hits = vec![
+            json::json!({"ts": "2024-01-01T00:01:00", "a": 1, "b": 10, "c": 5}),
+            json::json!({"ts": "2024-01-01T00:04:00", "a": 4, "b": 40, "c": 6}),
+        ];
+        gapfill.fill(&mut hits, (T0, T0 + 6 * MIN), MIN, None, false);
+        assert_eq!(
+            hits,
+            vec![
+                json::json!({"ts": "2024-01-01T00:00:00", "a": null, "b": null, "c": 0}),
+                json::json!({"ts": "2024-01-01T00:01:00", "a": 1, "b": 10, "c": 5}),
+                json::json!({"ts": "2024-01-01T00:02:00", "a": 1, "b": 20.0, "c": 0}),
+                json::json!({"ts": "2024-01-01T00:03:00", "a": 1, "b": 30.0, "c": 0}),
+                json::json!({"ts": "2024-01-01T00:04:00", "a": 4, "b": 40, "c": 6}),
+                json::json!({"ts": "2024-01-01T00:05:00", "a": 4, "b": null, "c": 0}),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_gapfill_fill_series() {
+        let gapfill = gapfill(
+            &["host"],
+            vec![("n", GapFillStrategy::Value(json::json!(0)))],
+        );
+        let mut hits = vec![
+            json::json!({"ts": T0 + 2 * MIN, "host": "a", "n": 1}),
+            json::json!({"ts": T0, "host": "b", "n": 2}),
+        ];
+        gapfill.fill(&mut hits, (T0, T0 + 3 * MIN), MIN, None, true);
+        assert_eq!(
+            hits,
+            vec![
+                json::json!({"ts": T0 + 2 * MIN, "host": "a", "n": 1}),
+                json::json!({"ts": T0 + 2 * MIN, "host": "b", "n": 0}),
+                json::json!({"ts": T0 + MIN, "host": "a", "n": 0}),
+                json::json!({"ts": T0 + MIN, "host": "b", "n": 0}),
+                json::json!({"ts": T0, "host": "a", "n": 0}),
+                json::json!({"ts": T0, "host": "b", "n": 2}),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_gapfill_fill_empty_and_timezone() {
+        let gapfill = gapfill(&[], vec![("n", GapFillStrategy::Value(json::json!(0)))]);
+        let mut hits = vec![];
+        gapfill.fill(
+            &mut hits,
+            (T0, T0 + 2 * 60 * MIN),
+            60 * MIN,
+            Some("+05:30"),
+            false,
+        );
+        // the buckets are local, 05:30 falls in the 05:00 bucket
+        assert_eq!(
+            hits,
+            vec![
+                json::json!({"ts": "2024-01-01T05:00:00", "n": 0}),
+                json::json!({"ts": "2024-01-01T06:00:00", "n": 0}),
+                json::json!({"ts": "2024-01-01T07:00:00", "n": 0}),
+            ]
+        );
+
+        // nothing to fill per series without any hit
+        let gapfill = self::gapfill(&["host"], vec![]);
+        let mut hits = vec![];
+        gapfill.fill(&mut hits, (T0, T0 + 2 * MIN), MIN, None, false);
+        assert!(hits.is_empty());
+    }
+
+    #[test]
+    fn test_gapfill_fill_order_by_and_limit() {
+        // ORDER BY cnt DESC, ts LIMIT 3 OFFSET 1, over the whole filled range
+        let mut gapfill = gapfill(&[], vec![("cnt", GapFillStrategy::Value(json::json!(0)))]);
+        gapfill.order_by = vec![
+            GapFillOrder {
+                column: "cnt".to_string(),
+                descending: true,
+                nulls_first: true,
+            },
+            GapFillOrder {
+                column: "ts".to_string(),
+                descending: false,
+                nulls_first: false,
+            },
+        ];
+        gapfill.limit = Some(3);
+        gapfill.offset = 1;
+        let mut hits = vec![
+            json::json!({"ts": T0 + 3 * MIN, "cnt": 7}),
+            json::json!({"ts": T0 + MIN, "cnt": 2}),
+        ];
+        gapfill.fill(&mut hits, (T0, T0 + 5 * MIN), MIN, None, false);
+        assert_eq!(
+            hits,
+            vec![
+                json::json!({"ts": T0 + MIN, "cnt": 2}),
+                json::json!({"ts": T0, "cnt": 0}),
+                json::json!({"ts": T0 + 2 * MIN, "cnt": 0}),
+            ]
+        );
+    }
+}
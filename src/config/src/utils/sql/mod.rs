@@ -16,9 +16,11 @@
 mod complex_query;
 mod eligible_for_histogram;
 mod explain_query;
+mod gapfill;
 mod helpers;
 mod simple_aggregate_query;
 mod simple_distinct_query;
+pub mod table_function;
 mod timestamp_selected;
 mod visitors;
 mod where_fragment;
@@ -26,13 +28,14 @@ mod where_fragment;
 pub use complex_query::{is_complex_query, is_complex_query_stmt};
 pub use eligible_for_histogram::is_eligible_for_histogram;
 pub use explain_query::is_explain_query;
+pub use gapfill::{GapFill, GapFillOrder, GapFillStrategy, rewrite_gapfill};
 pub use simple_aggregate_query::is_simple_aggregate_query;
 pub use simple_distinct_query::is_simple_distinct_query;
 pub use timestamp_selected::is_timestamp_selected;
 pub use visitors::TimestampVisitor;
 pub use where_fragment::validate_where_fragment;
 
-pub const AGGREGATE_UDF_LIST: [&str; 17] = [
+pub const AGGREGATE_UDF_LIST: [&str; 24] = [
     "min",
     "max",
     "avg",
@@ -50,4 +53,11 @@ pub const AGGREGATE_UDF_LIST: [&str; 17] = [
     "approx_percentile_cont_with_weight",
     "approx_topk",
     "approx_topk_distinct",
+    "hll_sketch",
+    "hll_merge",
+    "hll_estimate",
+    "tdigest_sketch",
+    "quantile_from_sketch",
+    "window_funnel",
+    "retention",
 ];
@@ -0,0 +1,174 @@
+// Copyright 2026 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Table functions that can be used in the FROM clause of a search, e.g.
+//! `SELECT * FROM "default" l JOIN trace_spans('abc') t ON l.span_id = t.span_id`.
+
+use std::ops::ControlFlow;
+
+use sqlparser::{
+    ast::{
+        Expr, FunctionArg, FunctionArgExpr, Statement, TableFactor, UnaryOperator, Value,
+        ValueWithSpan, Visit, Visitor,
+    },
+    dialect::PostgreSqlDialect,
+    parser::Parser,
+};
+
+pub const TRACE_SPANS_UDTF_NAME: &str = "trace_spans";
+pub const LOGS_FOR_TRACE_UDTF_NAME: &str = "logs_for_trace";
+pub const PROMQL_RANGE_UDTF_NAME: &str = "promql_range";
+
+pub const TABLE_FUNCTION_NAMES: [&str; 3] = [
+    TRACE_SPANS_UDTF_NAME,
+    LOGS_FOR_TRACE_UDTF_NAME,
+    PROMQL_RANGE_UDTF_NAME,
+];
+
+/// A table function call found in the sql, the arguments are the literal
+/// values as written in the query.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct TableFunctionCall {
+    pub name: String,
+    pub args: Vec<String>,
+}
+
+/// Returns the table function calls of the statement, table function arguments
+/// must be literals.
+pub fn resolve_table_functions(statement: &Statement) -> Result<Vec<TableFunctionCall>, String> {
+    let mut visitor = TableFunctionVisitor::default();
+    if let ControlFlow::Break(e) = statement.visit(&mut visitor) {
+        return Err(e);
+    }
+    Ok(visitor.calls)
+}
+
+/// Same as [`resolve_table_functions`] but from the sql text, returns an empty
+/// list if the sql can't be parsed.
+pub fn resolve_table_functions_from_sql(sql: &str) -> Result<Vec<TableFunctionCall>, String> {
+    let lower = sql.to_lowercase();
+    if !TABLE_FUNCTION_NAMES.iter().any(|name| lower.contains(name)) {
+        return Ok(vec![]);
+    }
+    let Ok(mut statements) = Parser::parse_sql(&PostgreSqlDialect {}, sql) else {
+        return Ok(vec![]);
+    };
+    match statements.pop() {
+        Some(statement) => resolve_table_functions(&statement),
+        None => Ok(vec![]),
+    }
+}
+
+#[derive(Default)]
+struct TableFunctionVisitor {
+    calls: Vec<TableFunctionCall>,
+}
+
+impl Visitor for TableFunctionVisitor {
+    type Break = String;
+
+    fn pre_visit_table_factor(&mut self, table_factor: &TableFactor) -> ControlFlow<Self::Break> {
+        let TableFactor::Table {
+            name,
+            args: Some(args),
+            ..
+        } = table_factor
+        else {
+            return ControlFlow::Continue(());
+        };
+        let name = name.to_string().to_lowercase();
+        if !TABLE_FUNCTION_NAMES.contains(&name.as_str()) {
+            return ControlFlow::Continue(());
+        }
+        let mut values = Vec::with_capacity(args.args.len());
+        for arg in args.args.iter() {
+            let FunctionArg::Unnamed(FunctionArgExpr::Expr(expr)) = arg else {
+                return ControlFlow::Break(format!(
+                    "{name}: only positional arguments are supported"
+                ));
+            };
+            match literal_to_string(expr) {
+                Some(v) => values.push(v),
+                None => {
+                    return ControlFlow::Break(format!(
+                        "{name}: arguments must be literals, got `{expr}`"
+                    ));
+                }
+            }
+        }
+        let call = TableFunctionCall { name, args: values };
+        if !self.calls.contains(&call) {
+            self.calls.push(call);
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+fn literal_to_string(expr: &Expr) -> Option<String> {
+    match expr {
+        Expr::Value(ValueWithSpan { value, .. }) => match value {
+            Value::SingleQuotedString(s) | Value::DoubleQuotedString(s) => Some(s.clone()),
+            Value::Number(n, _) => Some(n.to_string()),
+            _ => None,
+        },
+        Expr::UnaryOp {
+            op: UnaryOperator::Minus,
+            expr,
+        } => literal_to_string(expr).map(|v| format!("-{v}")),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_table_functions() {
+        let sql = "SELECT l.message, t.duration FROM \"default\" l \
+                   JOIN trace_spans('abc123') t ON l.span_id = t.span_id \
+                   JOIN promql_range('rate(cpu[5m])', 60) p ON true";
+        let calls = resolve_table_functions_from_sql(sql).unwrap();
+        assert_eq!(
+            calls,
+            vec![
+                TableFunctionCall {
+                    name: TRACE_SPANS_UDTF_NAME.to_string(),
+                    args: vec!["abc123".to_string()],
+                },
+                TableFunctionCall {
+                    name: PROMQL_RANGE_UDTF_NAME.to_string(),
+                    args: vec!["rate(cpu[5m])".to_string(), "60".to_string()],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_resolve_table_functions_not_literal() {
+        let sql = "SELECT * FROM \"default\" l JOIN logs_for_trace(l.trace_id, '5m') t ON true";
+        assert!(resolve_table_functions_from_sql(sql).is_err());
+    }
+
+    #[test]
+    fn test_resolve_table_functions_none() {
+        let sql = "SELECT * FROM trace_spans WHERE trace_id = 'abc'";
+        assert!(resolve_table_functions_from_sql(sql).unwrap().is_empty());
+        // the sessionize window function isn't a table function
+        let sql = "SELECT sessionize(_timestamp, 10) OVER (PARTITION BY user_id \
+                   ORDER BY _timestamp) AS session FROM \"default\"";
+        assert!(resolve_table_functions_from_sql(sql).unwrap().is_empty());
+    }
+}
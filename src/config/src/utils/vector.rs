@@ -0,0 +1,245 @@
+// Copyright 2026 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Float vectors (embeddings) of the fields declared in the stream setting
+//! `vector_fields`. A vector field is stored as a `FixedSizeList<Float32>`
+//! column of the declared dimension, see [`vector_data_type`]. Ingested
+//! values arrive as JSON arrays, or as the `[0.1, 0.2, …]` strings the
+//! flattener makes of them, and query vectors are written the same way.
+
+use std::sync::Arc;
+
+use arrow::{
+    array::{Array, ArrayBuilder, ArrayRef, AsArray, FixedSizeListBuilder, Float32Builder},
+    compute::cast,
+    datatypes::Float32Type,
+};
+use arrow_schema::{ArrowError, DataType, Field};
+
+use super::json;
+
+/// The column type of a vector field with `dimensions` values.
+pub fn vector_data_type(dimensions: usize) -> DataType {
+    DataType::FixedSizeList(
+        Arc::new(Field::new_list_field(DataType::Float32, true)),
+        dimensions as i32,
+    )
+}
+
+/// The dimensions of a vector column type, `None` for any other type.
+pub fn vector_dimensions(data_type: &DataType) -> Option<usize> {
+    match data_type {
+        DataType::FixedSizeList(field, size) if field.data_type() == &DataType::Float32 => {
+            Some(*size as usize)
+        }
+        _ => None,
+    }
+}
+
+/// A vector from an ingested JSON value: an array of numbers or its string
+/// form.
+pub fn json_to_vector(value: &json::Value) -> Option<Vec<f32>> {
+    match value {
+        json::Value::String(s) => parse_vector(s),
+        json::Value::Array(values) => {
+            let vector = values
+                .iter()
+                .map(|v| v.as_f64().map(|v| v as f32))
+                .collect::<Option<Vec<_>>>()?;
+            (!vector.is_empty() && vector.iter().all(|v| v.is_finite())).then_some(vector)
+        }
+        _ => None,
+    }
+}
+
+/// Append one row to a builder created for [`vector_data_type`]. A value that
+/// is not a vector of exactly `dimensions` numbers is stored as null.
+pub fn append_vector(builder: &mut dyn ArrayBuilder, dimensions: usize, vector: Option<&[f32]>) {
+    let builder = builder
+        .as_any_mut()
+        .downcast_mut::<FixedSizeListBuilder<Box<dyn ArrayBuilder>>>()
+        .expect("vector columns use a FixedSizeListBuilder");
+    let values = builder
+        .values()
+        .as_any_mut()
+        .downcast_mut::<Float32Builder>()
+        .expect("vector columns hold Float32 values");
+    match vector {
+        Some(vector) if vector.len() == dimensions => {
+            values.append_slice(vector);
+            builder.append(true);
+        }
+        _ => {
+            values.append_nulls(dimensions);
+            builder.append(false);
+        }
+    }
+}
+
+/// The vectors of a column, one per row: list columns of numbers and strings
+/// in the `[..]` form are accepted. Rows that are null, hold a null or
+/// non-finite number or are not a vector are `None`.
+pub fn array_to_vectors(array: &ArrayRef) -> Result<Vec<Option<Vec<f32>>>, ArrowError> {
+    match array.data_type() {
+        DataType::FixedSizeList(..) | DataType::List(_) | DataType::LargeList(_) => {
+            let list_type =
+                DataType::List(Arc::new(Field::new_list_field(DataType::Float32, true)));
+            let array = cast(array, &list_type)?;
+            Ok(array
+                .as_list::<i32>()
+                .iter()
+                .map(|values| {
+                    let values = values?;
+                    let values = values.as_primitive::<Float32Type>();
+                    if values.is_empty() || values.null_count() > 0 {
+                        return None;
+                    }
+                    let vector = values.values().to_vec();
+                    vector.iter().all(|v| v.is_finite()).then_some(vector)
+                })
+                .collect())
+        }
+        _ => {
+            let array = cast(array, &DataType::Utf8)?;
+            Ok(array
+                .as_string::<i32>()
+                .iter()
+                .map(|value| value.and_then(parse_vector))
+                .collect())
+        }
+    }
+}
+
+/// Parse a vector stored as a JSON array of numbers. Returns `None` for
+/// anything else, including an empty array or non-finite values.
+pub fn parse_vector(value: &str) -> Option<Vec<f32>> {
+    let value = value.trim();
+    if !value.starts_with('[') {
+        return None;
+    }
+    let vector: Vec<f32> = json::from_str(value).ok()?;
+    if vector.is_empty() || vector.iter().any(|v| !v.is_finite()) {
+        return None;
+    }
+    Some(vector)
+}
+
+/// Canonical string form of a vector, the inverse of [`parse_vector`].
+pub fn format_vector(vector: &[f32]) -> String {
+    let values = vector
+        .iter()
+        .map(|v| v.to_string())
+        .collect::<Vec<_>>()
+        .join(",");
+    format!("[{values}]")
+}
+
+/// Scale `vector` to unit length in place. Returns false (and leaves the
+/// vector unchanged) for a zero vector, which has no direction.
+pub fn normalize(vector: &mut [f32]) -> bool {
+    let norm = dot(vector, vector).sqrt();
+    if norm == 0.0 || !norm.is_finite() {
+        return false;
+    }
+    vector.iter_mut().for_each(|v| *v /= norm);
+    true
+}
+
+pub fn dot(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(a, b)| a * b).sum()
+}
+
+/// Cosine similarity in `[-1, 1]`; `None` when the dimensions differ or
+/// either vector is zero.
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> Option<f32> {
+    if a.len() != b.len() {
+        return None;
+    }
+    let norm = (dot(a, a) * dot(b, b)).sqrt();
+    if norm == 0.0 || !norm.is_finite() {
+        return None;
+    }
+    Some((dot(a, b) / norm).clamp(-1.0, 1.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_vector() {
+        assert_eq!(parse_vector("[1, 2.5, -3]"), Some(vec![1.0, 2.5, -3.0]));
+        assert_eq!(parse_vector(" [0.5] "), Some(vec![0.5]));
+        assert_eq!(parse_vector("[]"), None);
+        assert_eq!(parse_vector("[1, \"a\"]"), None);
+        assert_eq!(parse_vector("1, 2"), None);
+        assert_eq!(parse_vector("{\"a\": 1}"), None);
+        assert_eq!(
+            parse_vector(&format_vector(&[0.25, -1.0, 3.5])),
+            Some(vec![0.25, -1.0, 3.5])
+        );
+    }
+
+    #[test]
+    fn test_vector_column() {
+        let data_type = vector_data_type(2);
+        assert_eq!(vector_dimensions(&data_type), Some(2));
+        assert_eq!(vector_dimensions(&DataType::Utf8), None);
+
+        let mut builder = arrow::array::make_builder(&data_type, 4);
+        let values = [
+            json::json!([1, 0.5]),
+            json::json!("[0, 2]"),
+            json::json!([1, 2, 3]),
+            json::json!(["a", "b"]),
+        ];
+        for value in values.iter() {
+            let vector = json_to_vector(value);
+            append_vector(builder.as_mut(), 2, vector.as_deref());
+        }
+        let array = builder.finish();
+        assert_eq!(array.data_type(), &data_type);
+        assert_eq!(
+            array_to_vectors(&array).unwrap(),
+            vec![Some(vec![1.0, 0.5]), Some(vec![0.0, 2.0]), None, None]
+        );
+
+        let strings =
+            Arc::new(arrow::array::StringArray::from(vec![Some("[1, 2]"), None])) as ArrayRef;
+        assert_eq!(
+            array_to_vectors(&strings).unwrap(),
+            vec![Some(vec![1.0, 2.0]), None]
+        );
+    }
+
+    #[test]
+    fn test_cosine_similarity() {
+        assert_eq!(cosine_similarity(&[1.0, 0.0], &[2.0, 0.0]), Some(1.0));
+        assert_eq!(cosine_similarity(&[1.0, 0.0], &[0.0, 3.0]), Some(0.0));
+        assert_eq!(cosine_similarity(&[1.0, 1.0], &[-1.0, -1.0]), Some(-1.0));
+        assert_eq!(cosine_similarity(&[1.0, 0.0], &[1.0]), None);
+        assert_eq!(cosine_similarity(&[0.0, 0.0], &[1.0, 0.0]), None);
+    }
+
+    #[test]
+    fn test_normalize() {
+        let mut v = vec![3.0, 4.0];
+        assert!(normalize(&mut v));
+        assert_eq!(v, vec![0.6, 0.8]);
+        let mut zero = vec![0.0, 0.0];
+        assert!(!normalize(&mut zero));
+        assert_eq!(zero, vec![0.0, 0.0]);
+    }
+}
@@ -0,0 +1,353 @@
+// Copyright 2026 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Alertmanager v2 API service: silences and the alerts view.
+//!
+//! Alerts received on the API are ordinary external alerts of the org's
+//! default incident integration, so they are stored in `external_alerts` and
+//! correlated into incidents like webhook alerts. Silences are kept in the KV
+//! store under `alertmanager_silences/{id}`; a silenced alert is stored but
+//! not correlated, so it neither opens an incident nor notifies.
+
+use std::collections::HashMap;
+
+use config::{
+    get_config,
+    meta::alerts::alertmanager::{
+        AlertState, AlertStatus, AlertsFilter, GettableAlert, MatcherSet, PostableSilence,
+        Receiver, SILENCES_KV_PREFIX, Silence, SilenceState, format_time,
+    },
+    utils::{json, time::hour_micros},
+};
+
+use super::external_alerts::{DetectedSource, alertmanager::ends_at};
+
+#[derive(Debug, thiserror::Error)]
+pub enum AlertmanagerError {
+    #[error("{0}")]
+    Invalid(String),
+    #[error("silence not found")]
+    NotFound,
+    #[error(transparent)]
+    Storage(#[from] anyhow::Error),
+}
+
+/// Silences of an org that are not yet past retention, dropping the others.
+pub async fn list_silences(org_id: &str, now: i64) -> Result<Vec<Silence>, anyhow::Error> {
+    let retention = hour_micros(get_config().limit.alertmanager_silence_retention_hours);
+    let mut silences = Vec::new();
+    for key in crate::kv::list(org_id, SILENCES_KV_PREFIX).await? {
+        let Some(silence) = get_stored(org_id, &key).await? else {
+            continue;
+        };
+        if silence.ends_at + retention < now {
+            if let Err(e) = crate::kv::delete(org_id, &key).await {
+                log::warn!("[ALERTMANAGER] dropping expired silence {key} failed: {e}");
+            }
+            continue;
+        }
+        silences.push(silence);
+    }
+    silences.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+    Ok(silences)
+}
+
+pub async fn get_silence(org_id: &str, id: &str) -> Result<Silence, AlertmanagerError> {
+    get_stored(org_id, &silence_key(id))
+        .await?
+        .ok_or(AlertmanagerError::NotFound)
+}
+
+/// Creates a silence, or updates the one named by `id`. An expired silence
+/// is not revived: updating it creates a new one, as in Alertmanager.
+pub async fn save_silence(
+    org_id: &str,
+    mut post: PostableSilence,
+    user_id: &str,
+    now: i64,
+) -> Result<String, AlertmanagerError> {
+    if post.created_by.is_empty() {
+        post.created_by = user_id.to_string();
+    }
+    let id = match post.id.take() {
+        Some(id) => match get_silence(org_id, &id).await? {
+            s if s.state(now) == SilenceState::Expired => config::ider::uuid(),
+            _ => id,
+        },
+        None => config::ider::uuid(),
+    };
+    let silence = post
+        .into_silence(id, now)
+        .map_err(AlertmanagerError::Invalid)?;
+    put(org_id, &silence).await?;
+    Ok(silence.id)
+}
+
+/// Expires a silence now.
+pub async fn expire_silence(org_id: &str, id: &str, now: i64) -> Result<(), AlertmanagerError> {
+    let mut silence = get_silence(org_id, id).await?;
+    match silence.state(now) {
+        SilenceState::Expired => {
+            return Err(AlertmanagerError::Invalid(
+                "silence already expired".to_string(),
+            ));
+        }
+        SilenceState::Pending => silence.starts_at = now,
+        SilenceState::Active => {}
+    }
+    silence.ends_at = now;
+    silence.updated_at = now;
+    put(org_id, &silence).await?;
+    Ok(())
+}
+
+/// The active silences of an org, compiled for matching.
+pub async fn active_silences(
+    org_id: &str,
+    now: i64,
+) -> Result<Vec<(String, MatcherSet)>, anyhow::Error> {
+    Ok(compile_active(list_silences(org_id, now).await?, now))
+}
+
+fn compile_active(silences: Vec<Silence>, now: i64) -> Vec<(String, MatcherSet)> {
+    silences
+        .into_iter()
+        .filter(|s| s.state(now) == SilenceState::Active)
+        .filter_map(|s| match MatcherSet::new(&s.matchers) {
+            Ok(set) => Some((s.id, set)),
+            Err(e) => {
+                log::warn!("[ALERTMANAGER] skipping silence {}: {e}", s.id);
+                None
+            }
+        })
+        .collect()
+}
+
+/// Ids of the silences matching `labels`.
+pub fn silenced_by(
+    silences: &[(String, MatcherSet)],
+    labels: &HashMap<String, String>,
+) -> Vec<String> {
+    silences
+        .iter()
+        .filter(|(_, set)| set.matches(labels))
+        .map(|(id, _)| id.clone())
+        .collect()
+}
+
+/// Labels of a stored alert payload, including those left out of
+/// correlation.
+pub fn payload_labels(payload: &json::Value) -> HashMap<String, String> {
+    string_map(payload.get("labels"))
+}
+
+fn string_map(v: Option<&json::Value>) -> HashMap<String, String> {
+    v.and_then(|v| v.as_object())
+        .map(|m| {
+            m.iter()
+                .map(|(k, v)| {
+                    let v = match v {
+                        json::Value::String(s) => s.clone(),
+                        other => other.to_string(),
+                    };
+                    (k.clone(), v)
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// The firing Alertmanager alerts of an org, webhook or API received.
+pub async fn list_alerts(
+    org_id: &str,
+    filter: &AlertsFilter,
+    now: i64,
+) -> Result<Vec<GettableAlert>, anyhow::Error> {
+    let matchers = MatcherSet::new(&filter.matchers).map_err(|e| anyhow::anyhow!(e))?;
+    let silences = active_silences(org_id, now).await?;
+    let receivers: HashMap<String, String> =
+        infra::table::incident_integrations::list_by_org(org_id)
+            .await?
+            .into_iter()
+            .map(|i| (i.id, i.name))
+            .collect();
+    let records = infra::table::external_alerts::list_firing(
+        Some(org_id),
+        DetectedSource::Alertmanager.as_str(),
+    )
+    .await?;
+    let mut alerts = Vec::with_capacity(records.len());
+    for r in records {
+        // past its end, but not resolved yet by the expiry sweep
+        if ends_at(&r.last_payload).is_some_and(|t| t <= now) {
+            continue;
+        }
+        let labels = payload_labels(&r.last_payload);
+        if !matchers.matches(&labels) {
+            continue;
+        }
+        let silenced = silenced_by(&silences, &labels);
+        let state = if silenced.is_empty() {
+            AlertState::Active
+        } else {
+            AlertState::Suppressed
+        };
+        if (state == AlertState::Active && !filter.active)
+            || (state == AlertState::Suppressed && !filter.silenced)
+        {
+            continue;
+        }
+        let time = |field: &str, default: i64| {
+            r.last_payload
+                .get(field)
+                .and_then(|v| v.as_str())
+                .map(str::to_string)
+                .unwrap_or_else(|| format_time(default))
+        };
+        alerts.push(GettableAlert {
+            annotations: string_map(r.last_payload.get("annotations")),
+            starts_at: time("startsAt", r.first_seen_at),
+            ends_at: time("endsAt", 0),
+            updated_at: format_time(r.last_seen_at),
+            generator_url: r.source_url.clone().unwrap_or_default(),
+            fingerprint: r.dedup_key.clone(),
+            receivers: vec![Receiver {
+                name: receivers
+                    .get(&r.integration_id)
+                    .cloned()
+                    .unwrap_or_else(|| r.integration_id.clone()),
+            }],
+            status: AlertStatus {
+                state,
+                silenced_by: silenced,
+                inhibited_by: Vec::new(),
+            },
+            labels,
+        });
+    }
+    alerts.sort_by(|a, b| a.fingerprint.cmp(&b.fingerprint));
+    Ok(alerts)
+}
+
+/// Resolves the Alertmanager alerts of every org whose `endsAt` passed
+/// without their sender resolving or refreshing them, e.g. because the
+/// sending Prometheus went away.
+#[cfg(feature = "enterprise")]
+pub async fn resolve_expired(now: i64) -> Result<usize, anyhow::Error> {
+    use config::meta::alerts::incidents::{
+        ExternalAlertEvent, ExternalAlertStatus, IncidentSeverity,
+    };
+    use infra::table::external_alerts::{UpsertOutcome, list_firing, upsert_event};
+
+    let source = DetectedSource::Alertmanager.as_str();
+    let mut resolved = 0;
+    for r in list_firing(None, source).await? {
+        let Some(ends_at) = ends_at(&r.last_payload).filter(|t| *t <= now) else {
+            continue;
+        };
+        let ev = ExternalAlertEvent {
+            status: ExternalAlertStatus::Resolved,
+            dedup_key: r.dedup_key.clone(),
+            title: r.title.clone(),
+            severity: r.severity.parse().unwrap_or(IncidentSeverity::P3),
+            labels: json::from_value(r.labels.clone()).unwrap_or_default(),
+            event_ts: ends_at,
+            source_url: r.source_url.clone(),
+            raw: r.last_payload.clone(),
+        };
+        match upsert_event(&r.org_id, &r.integration_id, source, &ev).await {
+            Ok((record, UpsertOutcome::ResolvedApplied)) => {
+                resolved += 1;
+                if let Err(e) = super::incidents::try_auto_resolve_incident_for_external_alert(
+                    &r.org_id, &record.id,
+                )
+                .await
+                {
+                    log::warn!(
+                        "[ALERTMANAGER] auto-resolve check failed for {}: {e}",
+                        record.id
+                    );
+                }
+            }
+            Ok(_) => {}
+            Err(e) => log::error!(
+                "[ALERTMANAGER] resolving expired alert {} failed: {e}",
+                r.id
+            ),
+        }
+    }
+    Ok(resolved)
+}
+
+fn silence_key(id: &str) -> String {
+    format!("{SILENCES_KV_PREFIX}{id}")
+}
+
+async fn get_stored(org_id: &str, key: &str) -> Result<Option<Silence>, anyhow::Error> {
+    match crate::kv::get_opt(org_id, key).await? {
+        Some(v) => Ok(Some(json::from_slice(&v)?)),
+        None => Ok(None),
+    }
+}
+
+async fn put(org_id: &str, silence: &Silence) -> Result<(), anyhow::Error> {
+    crate::kv::set(
+        org_id,
+        &silence_key(&silence.id),
+        json::to_vec(silence)?.into(),
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use config::meta::alerts::alertmanager::Matcher;
+
+    use super::*;
+
+    fn silence(id: &str, matchers: &str, starts_at: i64, ends_at: i64) -> Silence {
+        Silence {
+            id: id.to_string(),
+            matchers: Matcher::parse_list(matchers).unwrap(),
+            starts_at,
+            ends_at,
+            updated_at: starts_at,
+            created_by: "me".to_string(),
+            comment: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_silenced_by_active_only() {
+        let now = 1_000;
+        let active = compile_active(
+            vec![
+                silence("a", r#"{alertname="A"}"#, 0, 2_000),
+                silence("b", r#"{env=~"prod|stage"}"#, 0, 2_000),
+                silence("pending", r#"{alertname="A"}"#, 1_500, 2_000),
+                silence("expired", r#"{alertname="A"}"#, 0, 1_000),
+            ],
+            now,
+        );
+        assert_eq!(active.len(), 2);
+        let labels = payload_labels(&serde_json::json!({
+            "labels": {"alertname": "A", "env": "prod", "replica": 1}
+        }));
+        assert_eq!(labels.get("replica").unwrap(), "1");
+        assert_eq!(silenced_by(&active, &labels), vec!["a", "b"]);
+        let labels = payload_labels(&serde_json::json!({"labels": {"alertname": "B"}}));
+        assert!(silenced_by(&active, &labels).is_empty());
+    }
+}
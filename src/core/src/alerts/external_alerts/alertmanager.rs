@@ -0,0 +1,153 @@
+// Copyright 2026 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Alerts pushed to the Alertmanager v2 API (`POST /api/v2/alerts`), as
+//! Prometheus and vmalert send them.
+//!
+//! Postable alerts carry no status: an alert is resolved once its `endsAt`
+//! has passed. Each alert is completed with its status and times and then
+//! normalized like an alert of an Alertmanager webhook, so both paths produce
+//! the same events.
+
+use config::meta::alerts::{
+    alertmanager::{format_time, parse_time},
+    incidents::ExternalAlertEvent,
+};
+use serde_json::Value;
+
+use super::grafana::normalize_am_format;
+
+/// Normalize a list of postable alerts. An alert without `startsAt` starts
+/// now, one without `endsAt` ends `resolve_timeout` micros from now.
+pub fn normalize_postable(
+    body: &Value,
+    now: i64,
+    resolve_timeout: i64,
+) -> Result<Vec<ExternalAlertEvent>, String> {
+    let alerts = body
+        .as_array()
+        .ok_or_else(|| "alerts must be a JSON array".to_string())?;
+    let mut completed = Vec::with_capacity(alerts.len());
+    for (i, alert) in alerts.iter().enumerate() {
+        completed
+            .push(complete(alert, now, resolve_timeout).map_err(|e| format!("alert {i}: {e}"))?);
+    }
+    normalize_am_format(&serde_json::json!({ "alerts": completed }), now)
+}
+
+fn complete(alert: &Value, now: i64, resolve_timeout: i64) -> Result<Value, String> {
+    let mut alert = alert
+        .as_object()
+        .cloned()
+        .ok_or_else(|| "alert must be an object".to_string())?;
+    match alert.get("labels").and_then(|l| l.as_object()) {
+        Some(labels) if !labels.is_empty() => {}
+        _ => return Err("alert needs at least one label".to_string()),
+    }
+    let time = |field: &str| -> Result<Option<i64>, String> {
+        match alert.get(field).and_then(|v| v.as_str()) {
+            None | Some("") | Some("0001-01-01T00:00:00Z") => Ok(None),
+            Some(s) => parse_time(s).map(Some),
+        }
+    };
+    let starts_at = time("startsAt")?.unwrap_or(now);
+    let ends_at = time("endsAt")?.unwrap_or(now + resolve_timeout);
+    if ends_at < starts_at {
+        return Err("alert ends before it starts".to_string());
+    }
+    let status = if ends_at <= now { "resolved" } else { "firing" };
+    alert.insert("status".to_string(), Value::from(status));
+    alert.insert("startsAt".to_string(), Value::from(format_time(starts_at)));
+    alert.insert("endsAt".to_string(), Value::from(format_time(ends_at)));
+    Ok(Value::Object(alert))
+}
+
+/// The `endsAt` of a stored alert payload, if it has a real one.
+pub fn ends_at(payload: &Value) -> Option<i64> {
+    match payload.get("endsAt").and_then(|v| v.as_str()) {
+        None | Some("") | Some("0001-01-01T00:00:00Z") => None,
+        Some(s) => parse_time(s).ok(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use config::meta::alerts::incidents::ExternalAlertStatus;
+
+    use super::*;
+
+    const NOW: i64 = 1_785_405_600_000_000; // 2026-07-30T10:00:00Z
+    const TIMEOUT: i64 = 300_000_000;
+
+    #[test]
+    fn test_normalize_postable_status_from_ends_at() {
+        let body = serde_json::json!([
+            {
+                "labels": {"alertname": "HighCPU", "severity": "critical", "job": "node"},
+                "annotations": {"summary": "cpu"},
+                "startsAt": "2026-07-30T09:58:00Z",
+                "endsAt": "2026-07-30T10:04:00Z",
+                "generatorURL": "http://prom/graph"
+            },
+            {
+                "labels": {"alertname": "DiskFull"},
+                "startsAt": "2026-07-30T09:00:00Z",
+                "endsAt": "2026-07-30T09:59:00Z"
+            },
+            {"labels": {"alertname": "NoTimes"}}
+        ]);
+        let evs = normalize_postable(&body, NOW, TIMEOUT).unwrap();
+        assert_eq!(evs.len(), 3);
+        assert_eq!(evs[0].status, ExternalAlertStatus::Firing);
+        assert_eq!(evs[0].title, "HighCPU");
+        assert_eq!(evs[0].event_ts, parse_time("2026-07-30T09:58:00Z").unwrap());
+        // job is kept in the payload, only the correlation labels drop it
+        assert!(!evs[0].labels.contains_key("job"));
+        assert_eq!(evs[0].raw["labels"]["job"], "node");
+        assert_eq!(evs[0].source_url.as_deref(), Some("http://prom/graph"));
+
+        assert_eq!(evs[1].status, ExternalAlertStatus::Resolved);
+        assert_eq!(evs[1].event_ts, parse_time("2026-07-30T09:59:00Z").unwrap());
+
+        assert_eq!(evs[2].status, ExternalAlertStatus::Firing);
+        assert_eq!(evs[2].event_ts, NOW);
+        assert_eq!(ends_at(&evs[2].raw), Some(NOW + TIMEOUT));
+    }
+
+    #[test]
+    fn test_normalize_postable_dedup_key_is_stable() {
+        let body = serde_json::json!([{"labels": {"alertname": "A", "instance": "x"}}]);
+        let a = normalize_postable(&body, NOW, TIMEOUT).unwrap();
+        let b = normalize_postable(&body, NOW + 60_000_000, TIMEOUT).unwrap();
+        assert_eq!(a[0].dedup_key, b[0].dedup_key);
+    }
+
+    #[test]
+    fn test_normalize_postable_rejects_bad_alerts() {
+        for body in [
+            serde_json::json!({"alerts": []}),
+            serde_json::json!([{"labels": {}}]),
+            serde_json::json!([{"labels": {"a": "b"}, "startsAt": "yesterday"}]),
+            serde_json::json!([{
+                "labels": {"a": "b"},
+                "startsAt": "2026-07-30T10:00:00Z",
+                "endsAt": "2026-07-30T09:00:00Z"
+            }]),
+        ] {
+            assert!(normalize_postable(&body, NOW, TIMEOUT).is_err(), "{body}");
+        }
+        assert!(ends_at(&serde_json::json!({"endsAt": "0001-01-01T00:00:00Z"})).is_none());
+    }
+}
@@ -13,6 +13,7 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
+pub mod alertmanager;
 pub mod detect;
 pub mod generic;
 pub mod grafana;
@@ -20,6 +20,12 @@ pub async fn get(org_id: &str, key: &str) -> Result<bytes::Bytes, anyhow::Error>
     Ok(val)
 }
 
+/// A missing key is `None`, only a failing store is an error.
+pub async fn get_opt(org_id: &str, key: &str) -> Result<Option<bytes::Bytes>, anyhow::Error> {
+    let val = kv::get_opt(org_id, key).await?;
+    Ok(val)
+}
+
 pub async fn set(org_id: &str, key: &str, val: bytes::Bytes) -> Result<(), anyhow::Error> {
     kv::set(org_id, key, val).await?;
     Ok(())
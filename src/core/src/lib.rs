@@ -53,6 +53,7 @@ pub mod providers;
 #[cfg(feature = "enterprise")]
 pub mod ratelimit;
 use search_service as search;
+pub mod search_correlation;
 pub mod self_reporting;
 pub mod service;
 pub mod session;
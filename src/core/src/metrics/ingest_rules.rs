@@ -0,0 +1,459 @@
+// Copyright 2026 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Ingest rules of metrics: `metric_relabel_configs` and per-metric series
+//! limits, applied to every sample written to an org right before its
+//! `__hash__` is computed, so the stored series are the relabeled ones.
+//!
+//! The rules of an org are stored in the KV store under
+//! [`INGEST_RULES_KV_KEY`] and cached by every ingester for
+//! `ZO_METRICS_INGEST_RULES_CACHE_TTL`. Active series are tracked per ingester
+//! by [`label_cache`], so a series limit applies to each ingester on its own.
+
+use std::sync::{Arc, LazyLock as Lazy};
+
+use bytes::Bytes;
+use config::{
+    get_config,
+    meta::promql::{
+        HASH_LABEL, NAME_LABEL, NATIVE_HISTOGRAM_LABEL, VALUE_LABEL,
+        ingest_rules::{
+            AGGREGATED_LABEL, INGEST_RULES_KV_KEY, IngestRules, MetricIngestRules,
+            SeriesLimitPolicy,
+        },
+        relabel::{LabelSet, relabel},
+    },
+    utils::{json, time::now_micros},
+};
+use hashbrown::{HashMap, hash_map::Entry};
+use parking_lot::RwLock;
+use promql::load_series::label_cache;
+
+#[derive(Debug, thiserror::Error)]
+pub enum IngestRulesError {
+    #[error("invalid ingest rules: {0}")]
+    Invalid(String),
+    #[error(transparent)]
+    Storage(#[from] anyhow::Error),
+}
+
+/// Rules of each org with the time they were read, microseconds.
+static CACHE: Lazy<RwLock<HashMap<String, (i64, Option<Arc<IngestRules>>)>>> =
+    Lazy::new(Default::default);
+
+pub async fn get(org_id: &str) -> Result<Option<IngestRules>, anyhow::Error> {
+    match crate::kv::get_opt(org_id, INGEST_RULES_KV_KEY).await? {
+        Some(v) => Ok(Some(json::from_slice(&v)?)),
+        None => Ok(None),
+    }
+}
+
+/// Replaces the ingest rules of an org with the YAML or JSON `body`.
+pub async fn set(org_id: &str, body: &str) -> Result<IngestRules, IngestRulesError> {
+    let rules = IngestRules::parse(body).map_err(IngestRulesError::Invalid)?;
+    let value = json::to_vec(&rules).map_err(anyhow::Error::from)?;
+    crate::kv::set(org_id, INGEST_RULES_KV_KEY, Bytes::from(value)).await?;
+    CACHE.write().remove(org_id);
+    Ok(rules)
+}
+
+pub async fn delete(org_id: &str) -> Result<(), anyhow::Error> {
+    crate::kv::delete(org_id, INGEST_RULES_KV_KEY).await?;
+    CACHE.write().remove(org_id);
+    Ok(())
+}
+
+async fn cached(org_id: &str, now: i64) -> Option<Arc<IngestRules>> {
+    let ttl = get_config().limit.metrics_ingest_rules_cache_ttl * 1_000_000;
+    if let Some((read_at, rules)) = CACHE.read().get(org_id)
+        && now - *read_at < ttl
+    {
+        return rules.clone();
+    }
+    let rules = match get(org_id).await {
+        Ok(rules) => rules.map(Arc::new),
+        Err(e) => {
+            log::error!("[METRICS:INGEST_RULES] reading ingest rules of org {org_id} failed: {e}");
+            None
+        }
+    };
+    CACHE
+        .write()
+        .insert(org_id.to_string(), (now, rules.clone()));
+    rules
+}
+
+/// What [`Enforcer::apply`] did with a record.
+#[derive(Debug, PartialEq)]
+enum Applied {
+    Rejected,
+    Kept,
+    /// Folded into the coarser series of this hash by the `aggregate` policy.
+    Aggregated(u64),
+}
+
+/// Applies the ingest rules of an org to the records of one ingest request.
+pub(crate) struct Enforcer {
+    org_id: String,
+    rules: Option<Arc<IngestRules>>,
+    /// Time of the request, microseconds, for the active series window.
+    now: i64,
+}
+
+impl Enforcer {
+    pub(crate) async fn new(org_id: &str) -> Self {
+        let now = now_micros();
+        Self::with_rules(org_id, cached(org_id, now).await, now)
+    }
+
+    fn with_rules(org_id: &str, rules: Option<Arc<IngestRules>>, now: i64) -> Self {
+        Self {
+            org_id: org_id.to_string(),
+            rules,
+            now,
+        }
+    }
+
+    /// Whether records of `stream_name` are affected by any rule.
+    pub(crate) fn applies_to(&self, stream_name: &str) -> bool {
+        self.rules
+            .as_ref()
+            .is_some_and(|rules| rules.applies_to(stream_name))
+    }
+
+    /// Applies the rules to the records of `stream_name` of one request, see
+    /// [`Self::apply`], and removes the rejected ones. The samples of a series
+    /// aggregated by a series limit that share a timestamp are summed into the
+    /// first of them. Returns the number of rejected records.
+    pub(crate) fn apply_all<R>(
+        &self,
+        stream_name: &str,
+        records: &mut Vec<R>,
+        exclude: &[&str],
+        sample: impl Fn(&mut R) -> (&mut json::Map<String, json::Value>, i64),
+    ) -> usize {
+        let mut rejected = 0;
+        let mut keep = Vec::with_capacity(records.len());
+        let mut aggregated: HashMap<(u64, i64), usize> = HashMap::new();
+        for i in 0..records.len() {
+            let (record, timestamp) = sample(&mut records[i]);
+            let kept = match self.apply(stream_name, record, exclude) {
+                Applied::Rejected => {
+                    rejected += 1;
+                    false
+                }
+                Applied::Kept => true,
+                Applied::Aggregated(hash) => {
+                    let value = sample_value(record);
+                    match aggregated.entry((hash, timestamp)) {
+                        Entry::Vacant(entry) => {
+                            entry.insert(i);
+                            true
+                        }
+                        Entry::Occupied(entry) => {
+                            let (first, _) = sample(&mut records[*entry.get()]);
+                            let sum = sample_value(first) + value;
+                            first.insert(VALUE_LABEL.to_string(), json::json!(sum));
+                            false
+                        }
+                    }
+                }
+            };
+            keep.push(kept);
+        }
+        let mut keep = keep.into_iter();
+        records.retain(|_| keep.next().unwrap_or_default());
+        rejected
+    }
+
+    /// Applies the rules to a record of `stream_name` and sets its `__hash__`,
+    /// computed without the `exclude` fields.
+    ///
+    /// Only string fields are labels. `__name__` names the stream, rules that
+    /// replace it are rejected when they are set and a rule that drops it
+    /// leaves it in place.
+    fn apply(
+        &self,
+        stream_name: &str,
+        record: &mut json::Map<String, json::Value>,
+        exclude: &[&str],
+    ) -> Applied {
+        let Some(rules) = self.rules.as_ref().filter(|r| r.applies_to(stream_name)) else {
+            let hash = super::signature_without_labels(record, exclude);
+            record.insert(HASH_LABEL.to_string(), json::Value::Number(hash.into()));
+            return Applied::Kept;
+        };
+        let metric = rules.metrics.get(stream_name);
+        let is_label = |name: &str, value: &json::Value| {
+            value.is_string() && !exclude.contains(&name) && name != HASH_LABEL
+        };
+
+        let original: LabelSet = record
+            .iter()
+            .filter(|(name, value)| is_label(name, value))
+            .map(|(name, value)| (name.clone(), value.as_str().unwrap_or_default().to_string()))
+            .collect();
+        let mut labels = original.clone();
+        let kept = relabel(&mut labels, &rules.metric_relabel_configs)
+            && metric.is_none_or(|m| relabel(&mut labels, &m.metric_relabel_configs));
+        if !kept {
+            self.reject(stream_name, "dropped");
+            return Applied::Rejected;
+        }
+        match original.get(NAME_LABEL) {
+            Some(name) => labels.insert(NAME_LABEL.to_string(), name.clone()),
+            None => labels.remove(NAME_LABEL),
+        };
+        if labels != original {
+            record.retain(|name, value| !is_label(name, value) || labels.contains_key(name));
+            for (name, value) in labels {
+                record.insert(name, json::Value::String(value));
+            }
+        }
+
+        let mut hash = super::signature_without_labels(record, exclude);
+        let mut applied = Applied::Kept;
+        if let Some(metric) = metric.filter(|m| m.series_limit > 0)
+            && !label_cache::admit_series(
+                &self.org_id,
+                stream_name,
+                hash,
+                metric.series_limit,
+                self.now,
+            )
+        {
+            match metric.limit_policy {
+                SeriesLimitPolicy::Drop => {
+                    self.reject(stream_name, "series_limit");
+                    return Applied::Rejected;
+                }
+                // only float samples can be summed, so stale markers and
+                // native histograms of new series are dropped
+                SeriesLimitPolicy::Aggregate
+                    if !record.get(VALUE_LABEL).is_some_and(json::Value::is_number)
+                        || record
+                            .get(NATIVE_HISTOGRAM_LABEL)
+                            .is_some_and(|v| !v.is_null()) =>
+                {
+                    self.reject(stream_name, "series_limit");
+                    return Applied::Rejected;
+                }
+                SeriesLimitPolicy::Aggregate => {
+                    record.retain(|name, value| {
+                        !is_label(name, value) || !is_aggregated(metric, name)
+                    });
+                    record.insert(
+                        AGGREGATED_LABEL.to_string(),
+                        json::Value::String("true".to_string()),
+                    );
+                    hash = super::signature_without_labels(record, exclude);
+                    // the coarser series is written even over the limit
+                    label_cache::admit_series(&self.org_id, stream_name, hash, 0, self.now);
+                    self.reject(stream_name, "aggregated");
+                    applied = Applied::Aggregated(hash);
+                }
+            }
+        }
+        record.insert(HASH_LABEL.to_string(), json::Value::Number(hash.into()));
+        applied
+    }
+
+    fn reject(&self, stream_name: &str, reason: &str) {
+        config::metrics::INGEST_METRICS_REJECTED_SERIES
+            .with_label_values(&[self.org_id.as_str(), stream_name, reason])
+            .inc();
+    }
+}
+
+fn is_aggregated(metric: &MetricIngestRules, name: &str) -> bool {
+    name != NAME_LABEL
+        && (metric.aggregate_labels.is_empty() || metric.aggregate_labels.iter().any(|l| l == name))
+}
+
+fn sample_value(record: &json::Map<String, json::Value>) -> f64 {
+    record
+        .get(VALUE_LABEL)
+        .and_then(json::Value::as_f64)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXCLUDE: &[&str] = &[VALUE_LABEL];
+
+    fn record(labels: &[(&str, &str)]) -> json::Map<String, json::Value> {
+        let mut record = json::Map::new();
+        for (name, value) in labels {
+            record.insert(name.to_string(), json::Value::String(value.to_string()));
+        }
+        record.insert(VALUE_LABEL.to_string(), json::json!(1.0));
+        record
+    }
+
+    fn enforcer(org_id: &str, rules: &str) -> Enforcer {
+        let rules = IngestRules::parse(rules).unwrap();
+        Enforcer::with_rules(org_id, Some(Arc::new(rules)), now_micros())
+    }
+
+    #[test]
+    fn test_no_rules_keeps_hash() {
+        let mut expected = record(&[(NAME_LABEL, "up"), ("job", "node")]);
+        let hash = super::super::signature_without_labels(&expected, EXCLUDE);
+        expected.insert(HASH_LABEL.to_string(), json::Value::Number(hash.into()));
+
+        let mut r = record(&[(NAME_LABEL, "up"), ("job", "node")]);
+        let enforcer = Enforcer::with_rules("org", None, 0);
+        assert!(!enforcer.applies_to("up"));
+        assert_eq!(enforcer.apply("up", &mut r, EXCLUDE), Applied::Kept);
+        assert_eq!(r, expected);
+    }
+
+    #[test]
+    fn test_relabel() {
+        let enforcer = enforcer(
+            "test_relabel",
+            r#"
+metric_relabel_configs:
+  - action: labeldrop
+    regex: request_id
+metrics:
+  http_requests_total:
+    metric_relabel_configs:
+      - source_labels: [path]
+        regex: "/users/[0-9]+"
+        target_label: path
+        replacement: /users/:id
+      - source_labels: [path]
+        regex: /healthz
+        action: drop
+      - source_labels: [instance]
+        target_label: shard
+        modulus: 4
+        action: hashmod
+"#,
+        );
+        let mut r = record(&[
+            (NAME_LABEL, "http_requests_total"),
+            ("path", "/users/42"),
+            ("request_id", "abc"),
+            ("instance", "a:80"),
+        ]);
+        assert_eq!(
+            enforcer.apply("http_requests_total", &mut r, EXCLUDE),
+            Applied::Kept
+        );
+        assert_eq!(r["path"], "/users/:id");
+        assert_eq!(r[NAME_LABEL], "http_requests_total");
+        assert!(!r.contains_key("request_id"));
+        assert!(r["shard"].as_str().unwrap().parse::<u64>().unwrap() < 4);
+        assert_eq!(r[VALUE_LABEL], 1.0);
+        let hash = r.remove(HASH_LABEL).unwrap();
+        assert_eq!(
+            hash.as_u64().unwrap(),
+            super::super::signature_without_labels(&r, EXCLUDE)
+        );
+
+        let mut r = record(&[(NAME_LABEL, "http_requests_total"), ("path", "/healthz")]);
+        assert_eq!(
+            enforcer.apply("http_requests_total", &mut r, EXCLUDE),
+            Applied::Rejected
+        );
+    }
+
+    #[test]
+    fn test_series_limit() {
+        let enforcer = enforcer(
+            "test_series_limit",
+            r#"
+metrics:
+  dropped:
+    series_limit: 2
+"#,
+        );
+        for user in ["a", "b", "a"] {
+            let mut r = record(&[(NAME_LABEL, "dropped"), ("user", user)]);
+            assert_eq!(enforcer.apply("dropped", &mut r, EXCLUDE), Applied::Kept);
+        }
+        let mut r = record(&[(NAME_LABEL, "dropped"), ("user", "c")]);
+        assert_eq!(
+            enforcer.apply("dropped", &mut r, EXCLUDE),
+            Applied::Rejected
+        );
+    }
+
+    #[test]
+    fn test_series_limit_aggregate() {
+        let enforcer = enforcer(
+            "test_series_limit_aggregate",
+            r#"
+metrics:
+  aggregated:
+    series_limit: 1
+    limit_policy: aggregate
+    aggregate_labels: [user]
+"#,
+        );
+        let sample = |user: &str, value: f64, timestamp: i64| {
+            let mut r = record(&[(NAME_LABEL, "aggregated"), ("user", user), ("job", "x")]);
+            r.insert(VALUE_LABEL.to_string(), json::json!(value));
+            (r, timestamp)
+        };
+        let mut records = vec![
+            sample("a", 1.0, 1),
+            sample("b", 2.0, 1),
+            sample("c", 3.0, 1),
+            sample("b", 4.0, 2),
+            sample("a", 5.0, 2),
+        ];
+        let rejected = enforcer.apply_all("aggregated", &mut records, EXCLUDE, |(r, ts)| (r, *ts));
+        assert_eq!(rejected, 0);
+        // the series of `a` is under the limit, `b` and `c` are summed into
+        // one series without `user` per timestamp
+        let values: Vec<_> = records
+            .iter()
+            .map(|(r, ts)| {
+                (
+                    r.get("user").cloned(),
+                    r[VALUE_LABEL].as_f64().unwrap(),
+                    *ts,
+                )
+            })
+            .collect();
+        assert_eq!(
+            values,
+            [
+                (Some(json::json!("a")), 1.0, 1),
+                (None, 5.0, 1),
+                (None, 4.0, 2),
+                (Some(json::json!("a")), 5.0, 2),
+            ]
+        );
+        let (r, _) = &records[1];
+        assert_eq!(r[AGGREGATED_LABEL], "true");
+        assert_eq!(r["job"], "x");
+        assert_eq!(r[HASH_LABEL], records[2].0[HASH_LABEL]);
+        assert_ne!(r[HASH_LABEL], records[0].0[HASH_LABEL]);
+
+        // a stale marker of a new series can not be summed
+        let (mut r, _) = sample("d", 0.0, 3);
+        r.insert(VALUE_LABEL.to_string(), json::Value::Null);
+        assert_eq!(
+            enforcer.apply("aggregated", &mut r, EXCLUDE),
+            Applied::Rejected
+        );
+    }
+}
@@ -394,7 +394,8 @@ pub async fn ingest(
         }
     }
 
-    for (stream_name, json_data) in json_data_by_stream {
+    let ingest_rules = super::ingest_rules::Enforcer::new(org_id).await;
+    for (stream_name, mut json_data) in json_data_by_stream {
         if !stream_partitioning_map.contains_key(&stream_name) {
             let partition_det = crate::ingestion::get_stream_partition_keys(
                 org_id,
@@ -434,24 +435,42 @@ pub async fn ingest(
         let mut evaluated_alerts = HashSet::new();
         // End get stream alert
 
-        for (mut record, metric_type) in json_data {
+        for (record, _) in json_data.iter_mut() {
             // check value
             let value =
                 parse_metric_value(record.get(VALUE_LABEL).ok_or(anyhow!("missing value"))?)?;
             // reset value
             record.insert(VALUE_LABEL.to_string(), value);
+            // remove type from labels
+            record.remove(TYPE_LABEL);
+        }
+        // apply the ingest rules and add hash
+        let rejected = ingest_rules.apply_all(
+            &stream_name,
+            &mut json_data,
+            get_exclude_labels(),
+            |(record, _)| {
+                let timestamp = record
+                    .get(TIMESTAMP_COL_NAME)
+                    .and_then(|ts| ts.as_i64())
+                    .unwrap_or_default();
+                (record, timestamp)
+            },
+        );
+        if rejected > 0 {
+            let stream_status = stream_status_map
+                .entry(stream_name.clone())
+                .or_insert_with(|| StreamStatus::new(&stream_name));
+            stream_status.status.failed += rejected as u32;
+            stream_status.status.error = "rejected by ingest rules".to_string();
+        }
 
+        for (mut record, metric_type) in json_data {
             let timestamp = record
                 .get(TIMESTAMP_COL_NAME)
                 .and_then(|ts| ts.as_i64())
                 .ok_or_else(|| anyhow::anyhow!("missing timestamp"))?;
 
-            // remove type from labels
-            record.remove(TYPE_LABEL);
-            // add hash
-            let hash = super::signature_without_labels(&record, get_exclude_labels());
-            record.insert(HASH_LABEL.to_string(), json::Value::Number(hash.into()));
-
             // convert every label to string
             for (k, v) in record.iter_mut() {
                 if k == NAME_LABEL
@@ -617,6 +636,7 @@ pub async fn ingest(
         .await;
         // for performance issue, we will flush all when the app shutdown
         let fsync = false;
+        super::result_cache::track_backfill(org_id, &stream_name, &stream_data);
         let mut req_stats = write_file(&writer, org_id, &stream_name, stream_data, fsync).await?;
 
         let email_str = user.to_email();
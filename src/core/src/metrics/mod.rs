@@ -19,11 +19,15 @@ use config::{
 };
 use datafusion::arrow::datatypes::Schema;
 
+pub mod ingest_rules;
 pub mod json;
 mod native_histogram;
 pub mod otlp;
 mod otlp_json_compat;
 pub mod prom;
+pub mod result_cache;
+pub mod rules;
+pub mod scrape;
 
 /// The value policy for every metric we ingest, on every path.
 ///
@@ -55,6 +59,16 @@ pub fn metric_value(v: f64) -> Option<config::utils::json::Value> {
     sanitize_metric_value(v).map(|v| config::utils::json::json!(v))
 }
 
+/// [`metric_value`] for remote-write samples. A Prometheus stale marker is kept as a null
+/// `value`, so that PromQL ends the series there instead of looking back past it, while any
+/// other NaN is still dropped.
+pub fn remote_write_value(v: f64) -> Option<config::utils::json::Value> {
+    if config::meta::promql::histogram::is_stale_nan(v) {
+        return Some(config::utils::json::Value::Null);
+    }
+    metric_value(v)
+}
+
 pub fn get_prom_metadata_from_schema(schema: &Schema) -> Option<Metadata> {
     config::meta::promql::get_metadata_from_schema(schema)
 }
@@ -146,6 +160,13 @@ mod tests {
     #[test]
     fn test_sanitize_metric_value() {
         assert!(sanitize_metric_value(f64::NAN).is_none());
+        assert!(remote_write_value(f64::NAN).is_none());
+        assert_eq!(
+            remote_write_value(f64::from_bits(
+                config::meta::promql::histogram::STALE_NAN_BITS
+            )),
+            Some(config::utils::json::Value::Null)
+        );
         assert_eq!(sanitize_metric_value(f64::INFINITY), Some(f64::MAX));
         assert_eq!(sanitize_metric_value(f64::NEG_INFINITY), Some(f64::MIN));
         assert_eq!(sanitize_metric_value(0.0), Some(0.0));
@@ -13,29 +13,26 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
-//! Degrades Prometheus native (sparse) histograms into their classic representation:
-//! `_count`, `_sum` and cumulative `le` `_bucket` records, so existing PromQL
-//! (`histogram_quantile` etc.) works on them unchanged.
+//! Optionally degrades Prometheus native (sparse) histograms into their classic
+//! representation: `_count`, `_sum` and cumulative `le` `_bucket` records, written next to
+//! the native samples when `ZO_PROMETHEUS_NATIVE_HISTOGRAM_CLASSIC` is set, for dashboards
+//! and alerts written against classic histograms.
 //!
-//! Known limitations, inherited from classic semantics:
+//! Known limitations, inherited from classic semantics (the native samples have none):
 //! - `sum by (le)` is only sound across series sharing a bucket layout. Each native series carries
 //!   `le`s only for its own value range, so quantiles aggregated across series with different
-//!   ranges understate the cumulative tail. Per-series quantiles, homogeneous-group aggregations
-//!   and `_count`/`_sum` rates are exact.
-//! - quantiles interpolate linearly within a bucket, like every classic histogram. Prometheus >=
-//!   3.0 interpolates native buckets exponentially, so its results can differ by up to the bucket
-//!   width (~6% at schema 0, less at finer schemas, more after downscaling). This is an approximate
-//!   fallback, not a native-fidelity one.
+//!   ranges understate the cumulative tail.
+//! - quantiles interpolate linearly within a bucket, like every classic histogram.
+//! - NHCB (schema -53) is stored natively only.
 
+use config::meta::promql::histogram::{
+    EXPONENTIAL_SCHEMA_RANGE, STALE_NAN_BITS, bucket_bound, downscale, span_buckets,
+};
 use proto::prometheus_rpc;
 
 /// The classic streams a native histogram degrades into.
 pub const CLASSIC_HISTOGRAM_SUFFIXES: [&str; 3] = ["_bucket", "_count", "_sum"];
 
-/// Exponential schemas (`base = 2^(2^-schema)`). Anything outside -- notably NHCB,
-/// schema -53 -- cannot be converted: our wire type does not decode `custom_values`.
-const SCHEMA_RANGE: std::ops::RangeInclusive<i32> = -4..=8;
-
 /// Downscaling may merge below the native schema floor -- the emitted `le` bounds are
 /// plain classic bounds, not required to form a valid native schema. At -10
 /// (base 2^1024) one bucket spans all of f64, so the loop always terminates. A sample
@@ -46,9 +43,6 @@ const MIN_DOWNSCALE_SCHEMA: i32 = -10;
 /// The `__name__` suffix, the `le` label (`None` for `_count`/`_sum`), and the value.
 pub type ClassicHistogramRecord = (&'static str, Option<String>, f64);
 
-/// Prometheus's stale-marker bit pattern in `sum`; an ordinary NaN is NOT stale.
-const STALE_NAN_BITS: u64 = 0x7ff0_0000_0000_0002;
-
 /// Degrades one native histogram sample into classic records: cumulative `le` buckets
 /// closed by `le="inf"`. Empty for unsupported schemas and stale markers. A sample
 /// expanding to more than `max_buckets` `le` labels is downscaled (adjacent buckets
@@ -57,7 +51,7 @@ pub fn expand_native_histogram(
     hp: &prometheus_rpc::Histogram,
     max_buckets: usize,
 ) -> Vec<ClassicHistogramRecord> {
-    if !SCHEMA_RANGE.contains(&hp.schema) {
+    if !EXPONENTIAL_SCHEMA_RANGE.contains(&hp.schema) {
         log::warn!(
             "[METRICS:PROM] dropping native histogram with unsupported schema {}",
             hp.schema
@@ -162,15 +156,6 @@ fn push_le_point(points: &mut Vec<(String, f64)>, le: String, cumulative: f64) {
     }
 }
 
-/// Upper bound of bucket `idx`: `2^(idx * 2^-schema)` via `exp2`, so powers of two are
-/// exact and a boundary shared between schemas is the identical f64 (downscaling keeps
-/// surviving `le`s in the same series). Clamped finite so the last representable
-/// bucket cannot collide with `le="inf"`.
-fn bucket_bound(schema: i32, idx: i64) -> f64 {
-    let bound = ((idx as f64) * 2f64.powi(-schema)).exp2();
-    bound.clamp(f64::MIN_POSITIVE, f64::MAX)
-}
-
 /// Upper bound on the `le` labels a sample will emit: each populated bucket gets an
 /// upper record, each contiguous run a lower-bound gap marker, plus the zero bucket's
 /// two bounds and `inf`.
@@ -187,58 +172,6 @@ fn le_estimate(pos: &[(i64, f64)], neg: &[(i64, f64)], zero_count: f64) -> usize
     side(pos) + side(neg) + if zero_count > 0.0 { 2 } else { 0 } + 1
 }
 
-/// Merges adjacent bucket pairs: `idx` at schema `s` maps to `ceil(idx / 2)` at
-/// schema `s - 1`.
-fn downscale(buckets: Vec<(i64, f64)>) -> Vec<(i64, f64)> {
-    let mut out: Vec<(i64, f64)> = Vec::with_capacity(buckets.len() / 2 + 1);
-    for (idx, c) in buckets {
-        let merged_idx = (idx + 1).div_euclid(2);
-        match out.last_mut() {
-            Some((last_idx, last_c)) if *last_idx == merged_idx => *last_c += c,
-            _ => out.push((merged_idx, c)),
-        }
-    }
-    out
-}
-
-/// Decodes the sparse layout (spans + integer deltas or absolute float counts) into
-/// `(bucket index, absolute count)` pairs for every populated bucket.
-fn span_buckets(
-    spans: &[prometheus_rpc::BucketSpan],
-    deltas: &[i64],
-    counts: &[f64],
-) -> Vec<(i64, f64)> {
-    let use_float_counts = !counts.is_empty();
-    let mut buckets = Vec::new();
-    let mut idx: i64 = 0;
-    let mut pos = 0;
-    let mut cumulative_delta: i64 = 0;
-    for span in spans {
-        idx += span.offset as i64;
-        for _ in 0..span.length {
-            let count = if use_float_counts {
-                let Some(c) = counts.get(pos) else {
-                    return buckets;
-                };
-                *c
-            } else {
-                let Some(d) = deltas.get(pos) else {
-                    return buckets;
-                };
-                cumulative_delta += *d;
-                cumulative_delta as f64
-            };
-            pos += 1;
-            // skips zero-count buckets and NaN float counts
-            if count > 0.0 {
-                buckets.push((idx, count));
-            }
-            idx += 1;
-        }
-    }
-    buckets
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -156,6 +156,7 @@ pub async fn handle_otlp_request(
 
     let start = std::time::Instant::now();
     let started_at = Utc::now().timestamp_micros();
+    let ingest_rules = super::ingest_rules::Enforcer::new(org_id).await;
 
     let mut metric_data_map: HashMap<String, HashMap<String, SchemaRecords>> = HashMap::new();
     let mut metric_schema_map: HashMap<String, SchemaCache> = HashMap::new();
@@ -332,15 +333,41 @@ pub async fn handle_otlp_request(
                     }
                 }
 
-                // process data points
-                for mut rec in records {
-                    // flattening
-                    rec = flatten::flatten(rec)?;
-
+                // flatten the data points and apply the ingest rules of their
+                // stream. The data points are hashed as they are built, so only
+                // the ones affected by ingest rules are hashed again.
+                let mut points = Vec::with_capacity(records.len());
+                let mut ruled: HashMap<String, Vec<json::Value>> = HashMap::new();
+                for rec in records {
+                    let rec = flatten::flatten(rec)?;
                     let local_metric_name = format_stream_name(
                         rec.get(NAME_LABEL).unwrap().as_str().unwrap().to_string(),
                     );
+                    if ingest_rules.applies_to(&local_metric_name) {
+                        ruled.entry(local_metric_name).or_default().push(rec);
+                    } else {
+                        points.push((local_metric_name, rec));
+                    }
+                }
+                for (local_metric_name, mut recs) in ruled {
+                    let rejected = ingest_rules.apply_all(
+                        &local_metric_name,
+                        &mut recs,
+                        get_exclude_labels(),
+                        |rec| {
+                            let timestamp = rec
+                                .get(TIMESTAMP_COL_NAME)
+                                .and_then(|ts| ts.as_i64())
+                                .unwrap_or_default();
+                            (rec.as_object_mut().unwrap(), timestamp)
+                        },
+                    );
+                    partial_success.rejected_data_points += rejected as i64;
+                    points.extend(recs.into_iter().map(|rec| (local_metric_name.clone(), rec)));
+                }
 
+                // process data points
+                for (local_metric_name, mut rec) in points {
                     if local_metric_name != metric_name {
                         // check for schema
                         stream_schema_exists(
@@ -666,6 +693,7 @@ pub async fn handle_otlp_request(
         .await;
         // for performance issue, we will flush all when the app shutdown
         let fsync = false;
+        super::result_cache::track_backfill(org_id, &stream_name, &stream_data);
         let mut req_stats = write_file(&writer, org_id, &stream_name, stream_data, fsync).await?;
 
         let fns_length: usize = stream_executable_pipelines
@@ -26,7 +26,7 @@ use config::{
     get_config,
     meta::{
         alerts::alert,
-        promql::*,
+        promql::{histogram::NativeHistogram, *},
         search::default_use_cache,
         self_reporting::usage::UsageType,
         stream::{StreamParams, StreamPartition, StreamStats, StreamType},
@@ -185,8 +185,8 @@ pub async fn remote_write(
     for event in &request.timeseries {
         if let Some(name_label) = event.labels.iter().find(|l| l.name == NAME_LABEL) {
             let metric_name = format_stream_name(name_label.value.to_string());
-            if !event.histograms.is_empty() {
-                // native histograms degrade into classic streams; preload those too
+            if !event.histograms.is_empty() && cfg.prom.native_histogram_classic {
+                // native histograms also degrade into classic streams; preload those too
                 for suffix in CLASSIC_HISTOGRAM_SUFFIXES {
                     unique_metrics.insert(format!("{metric_name}{suffix}"));
                 }
@@ -333,9 +333,10 @@ pub async fn remote_write(
         let sample_start = std::time::Instant::now();
         for sample in event.samples {
             sample_count += 1;
-            // NaN -> no observation -> no record; infinities clamp. Shared with the OTLP
-            // writer so the two ingestion paths cannot drift apart on this.
-            let Some(sample_val) = super::sanitize_metric_value(sample.value) else {
+            // NaN -> no observation -> no record, except for stale markers which are
+            // kept as a null value; infinities clamp. Shared with the OTLP writer so
+            // the two ingestion paths cannot drift apart on this.
+            let Some(sample_val) = super::remote_write_value(sample.value) else {
                 continue;
             };
 
@@ -352,10 +353,11 @@ pub async fn remote_write(
 
             let metric = Metric {
                 labels: &labels,
-                value: sample_val,
+                value: 0.0,
             };
 
             let mut value: json::Value = json::to_value(&metric).unwrap();
+            value[VALUE_LABEL] = sample_val;
             let timestamp = parse_i64_to_timestamp_micros(sample.timestamp);
             value.as_object_mut().unwrap().insert(
                 TIMESTAMP_COL_NAME.to_string(),
@@ -374,25 +376,27 @@ pub async fn remote_write(
             );
         }
 
-        // native histograms degrade into classic `_count`/`_sum`/`le` `_bucket`
-        // records so existing PromQL works on them unchanged
+        // native histograms are stored as-is next to the float samples of the
+        // series, optionally also degraded into classic `_count`/`_sum`/`le`
+        // `_bucket` records for dashboards written against classic histograms
         if !event.histograms.is_empty() {
             // one stream name + label template per derived stream, shared by every
             // record of the event instead of cloned per record
-            let mut derived_streams = CLASSIC_HISTOGRAM_SUFFIXES.map(|suffix| {
-                let mut hist_labels = labels.clone();
-                if let Some(name) = hist_labels.get_mut(NAME_LABEL) {
-                    name.push_str(suffix);
-                }
-                (format!("{metric_name}{suffix}"), hist_labels)
+            let mut derived_streams = cfg.prom.native_histogram_classic.then(|| {
+                CLASSIC_HISTOGRAM_SUFFIXES.map(|suffix| {
+                    let mut hist_labels = labels.clone();
+                    if let Some(name) = hist_labels.get_mut(NAME_LABEL) {
+                        name.push_str(suffix);
+                    }
+                    (format!("{metric_name}{suffix}"), hist_labels)
+                })
             });
             for hp in &event.histograms {
                 sample_count += 1;
-                let records = expand_native_histogram(hp, cfg.prom.native_histogram_max_buckets);
-                if records.is_empty() {
+                let Some(histogram) = NativeHistogram::from_proto(hp) else {
                     // unsupported schema or stale marker: nothing will be written
                     continue;
-                }
+                };
 
                 // same first-writable-record election as the samples loop
                 if first_line && dedup_enabled && !cluster_name.is_empty() {
@@ -404,6 +408,36 @@ pub async fn remote_write(
                 }
 
                 let timestamp = parse_i64_to_timestamp_micros(hp.timestamp);
+                // the value column carries the observation count, so float-only
+                // consumers of the stream still see a meaningful series
+                let metric = Metric {
+                    labels: &labels,
+                    value: super::sanitize_metric_value(histogram.count).unwrap_or_default(),
+                };
+                let mut value: json::Value = json::to_value(&metric).unwrap();
+                let record = value.as_object_mut().unwrap();
+                record.insert(
+                    TIMESTAMP_COL_NAME.to_string(),
+                    json::Value::Number(timestamp.into()),
+                );
+                record.insert(
+                    NATIVE_HISTOGRAM_LABEL.to_string(),
+                    json::Value::String(json::to_string(&histogram).unwrap()),
+                );
+                buffer_metric_record(
+                    &metric_name,
+                    value,
+                    timestamp,
+                    &stream_executable_pipelines,
+                    &user_defined_schema_map,
+                    &mut stream_pipeline_inputs,
+                    &mut json_data_by_stream,
+                );
+
+                let Some(derived_streams) = derived_streams.as_mut() else {
+                    continue;
+                };
+                let records = expand_native_histogram(hp, cfg.prom.native_histogram_max_buckets);
                 for (suffix, le, value) in records {
                     let Some(value) = super::sanitize_metric_value(value) else {
                         continue;
@@ -557,8 +591,16 @@ pub async fn remote_write(
         }
     }
 
+    let ingest_rules = super::ingest_rules::Enforcer::new(org_id).await;
     let step_start = std::time::Instant::now();
-    for (stream_name, json_data) in json_data_by_stream {
+    for (stream_name, mut json_data) in json_data_by_stream {
+        ingest_rules.apply_all(
+            &stream_name,
+            &mut json_data,
+            &[VALUE_LABEL, NATIVE_HISTOGRAM_LABEL],
+            |(val_map, timestamp)| (val_map, *timestamp),
+        );
+
         // get partition keys
         let partition_keys = stream_partitioning_map
             .get(&stream_name)
@@ -577,8 +619,6 @@ pub async fn remote_write(
         let mut evaluated_alerts = HashSet::new();
 
         for (mut val_map, timestamp) in json_data {
-            let hash = super::signature_without_labels(&val_map, &[VALUE_LABEL]);
-            val_map.insert(HASH_LABEL.to_string(), json::Value::Number(hash.into()));
             val_map.insert(
                 TIMESTAMP_COL_NAME.to_string(),
                 json::Value::Number(timestamp.into()),
@@ -736,6 +776,7 @@ pub async fn remote_write(
 
         // for performance issue, we will flush all when the app shutdown
         let fsync = false;
+        super::result_cache::track_backfill(org_id, &stream_name, &stream_data);
         let t = std::time::Instant::now();
         let mut req_stats = write_file(&writer, org_id, &stream_name, stream_data, fsync).await?;
         write_file_time += t.elapsed().as_micros();
@@ -945,7 +986,12 @@ pub async fn get_series(
         .fields()
         .iter()
         .map(|f| f.name().as_str())
-        .filter(|&s| s != TIMESTAMP_COL_NAME && s != VALUE_LABEL && s != HASH_LABEL)
+        .filter(|&s| {
+            s != TIMESTAMP_COL_NAME
+                && s != VALUE_LABEL
+                && s != HASH_LABEL
+                && s != NATIVE_HISTOGRAM_LABEL
+        })
         .collect::<Vec<_>>()
         .join("\", \"");
     if label_names.is_empty() {
@@ -1052,7 +1098,12 @@ pub async fn get_labels(
                 .fields()
                 .iter()
                 .map(|f| f.name())
-                .filter(|&s| s != TIMESTAMP_COL_NAME && s != VALUE_LABEL && s != HASH_LABEL)
+                .filter(|&s| {
+                    s != TIMESTAMP_COL_NAME
+                        && s != VALUE_LABEL
+                        && s != HASH_LABEL
+                        && s != NATIVE_HISTOGRAM_LABEL
+                })
                 .cloned();
             label_names.extend(field_names);
         }
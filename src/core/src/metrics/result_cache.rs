@@ -0,0 +1,125 @@
+// Copyright 2026 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Invalidation of the cached query results of metrics streams.
+//!
+//! Queriers cache PromQL and SQL results only for data older than `ZO_CACHE_DELAY_SECS`, a
+//! sample written behind that delay can change a cached result. Ingesters remember the
+//! streams which received such samples and a job drops the results cached for them on every
+//! querier.
+
+use std::{
+    collections::{HashMap, HashSet},
+    sync::LazyLock,
+};
+
+use config::{
+    TIMESTAMP_COL_NAME, get_config,
+    meta::stream::StreamType,
+    utils::time::{now_micros, second_micros},
+};
+use parking_lot::Mutex;
+
+use crate::common::meta::stream::SchemaRecords;
+
+/// Result cache paths, `{org}/metrics/{stream}`, of the streams which received samples
+/// behind the cache delay since the last invalidation.
+static BACKFILLED_STREAMS: LazyLock<Mutex<HashSet<String>>> = LazyLock::new(Default::default);
+
+/// Remembers the stream when `data` holds samples old enough to be cached already.
+pub(crate) fn track_backfill(
+    org_id: &str,
+    stream_name: &str,
+    data: &HashMap<String, SchemaRecords>,
+) {
+    let cfg = get_config();
+    if !cfg.common.result_cache_enabled || cfg.limit.metrics_cache_invalidation_interval <= 0 {
+        return;
+    }
+    let path = format!("{org_id}/{}/{stream_name}", StreamType::Metrics);
+    if BACKFILLED_STREAMS.lock().contains(&path) {
+        return;
+    }
+
+    let cached_before = now_micros() - second_micros(cfg.limit.cache_delay_secs);
+    let backfilled = data
+        .values()
+        .flat_map(|entry| entry.records.iter())
+        .filter_map(|record| record.get(TIMESTAMP_COL_NAME)?.as_i64())
+        .any(|ts| ts < cached_before);
+    if backfilled {
+        BACKFILLED_STREAMS.lock().insert(path);
+    }
+}
+
+/// Drops the cached results of the streams which received backfilled samples.
+///
+/// A late sample can change every step whose range reaches it, so all results cached for the
+/// stream are dropped rather than the ones around the sample.
+pub async fn invalidate_backfilled() {
+    let paths = std::mem::take(&mut *BACKFILLED_STREAMS.lock());
+    for path in paths {
+        if !promql_service::search::delete_cached_results(path.clone(), 0).await {
+            // retry with the next run
+            BACKFILLED_STREAMS.lock().insert(path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use config::utils::json;
+    use datafusion::arrow::datatypes::Schema;
+
+    use super::*;
+
+    fn records(ts: &[i64]) -> HashMap<String, SchemaRecords> {
+        let records = ts
+            .iter()
+            .map(|ts| Arc::new(json::json!({ TIMESTAMP_COL_NAME: ts, "value": 1.0 })))
+            .collect();
+        HashMap::from([(
+            "2026_01_01_00".to_string(),
+            SchemaRecords {
+                schema_key: String::new(),
+                schema: Arc::new(Schema::empty()),
+                records,
+                records_size: 0,
+            },
+        )])
+    }
+
+    #[test]
+    fn test_track_backfill() {
+        let saved = config::CONFIG.load_full();
+        let mut cfg = config::Config::init().unwrap();
+        cfg.common.result_cache_enabled = true;
+        cfg.limit.metrics_cache_invalidation_interval = 60;
+        let delay = cfg.limit.cache_delay_secs;
+        config::CONFIG.store(Arc::new(cfg));
+
+        let now = now_micros();
+        let late = now - second_micros(delay) - second_micros(60);
+        track_backfill("test_org_backfill", "current", &records(&[now]));
+        track_backfill("test_org_backfill", "late", &records(&[now, late]));
+        config::CONFIG.store(saved);
+
+        let streams = BACKFILLED_STREAMS.lock();
+        assert!(!streams.contains("test_org_backfill/metrics/current"));
+        assert!(streams.contains("test_org_backfill/metrics/late"));
+    }
+}
@@ -0,0 +1,847 @@
+// Copyright 2026 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Prometheus rule groups imported from rule files.
+//!
+//! Groups are stored in the KV store per org under
+//! `prometheus_rules/{namespace}/{group}`. Alerting rules are backed by
+//! per-series PromQL alerts, so they are scheduled, notified and tracked like
+//! any other alert; recording rules are evaluated by the `prom_rules` job,
+//! which writes their output through [`evaluate_recording_rules`].
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    str::FromStr,
+};
+
+use bytes::Bytes;
+use config::{
+    TIMESTAMP_COL_NAME, get_config, ider,
+    meta::{
+        alerts::{
+            Condition, FrequencyType, Operator, QueryCondition, QueryType, TriggerCondition,
+            alert::Alert, state::ROLLUP_GROUP_KEY,
+        },
+        promql::{
+            NAME_LABEL, TYPE_LABEL, VALUE_LABEL,
+            rules::{
+                ActiveAlert, AlertDiscovery, AlertingRuleStatus, RULES_HEALTH_KV_PREFIX,
+                RULES_KV_PREFIX, RecordingRuleStatus, Rule, RuleDiscovery, RuleError, RuleGroup,
+                RuleGroupHealth, RuleGroupStatus, RuleStatus, RulesFilter, StoredRuleGroup,
+                alert_expr, format_time, kv_key, parse_rendered_labels, parse_rule_groups,
+                validate_namespace,
+            },
+            value::Value,
+        },
+        search::SearchEventType,
+        self_reporting::usage::RunOutcome,
+        stream::StreamType,
+    },
+    utils::{json, schema::format_stream_name, util::zero_or},
+};
+use infra::db::{ORM_CLIENT, connect_to_orm};
+use promql_parser::parser;
+use serde::Serialize;
+use svix_ksuid::Ksuid;
+
+use crate::alerts::alert::{self as alert_service, AlertError};
+
+#[derive(Debug, thiserror::Error)]
+pub enum RulesError {
+    #[error(transparent)]
+    Invalid(#[from] RuleError),
+    #[error("alerting rules need at least one destination")]
+    DestinationMissing,
+    #[error("rule group not found")]
+    NotFound,
+    #[error(transparent)]
+    Storage(#[from] anyhow::Error),
+}
+
+/// Where the alerts of imported alerting rules go.
+#[derive(Debug, Clone, Default)]
+pub struct ImportOptions {
+    pub destinations: Vec<String>,
+    pub folder_id: String,
+    pub user_id: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ImportResult {
+    pub groups: Vec<ImportedGroup>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ImportedGroup {
+    pub namespace: String,
+    pub name: String,
+    pub recording_rules: usize,
+    pub alerting_rules: usize,
+    /// Alerting rules whose alert could not be saved. The group is stored
+    /// regardless, so re-importing after fixing the cause picks them up.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<String>,
+}
+
+/// Imports the rule groups of a rule file into `namespace`, replacing groups
+/// of the same name. Groups of the namespace not in the file are kept.
+pub async fn import(
+    org_id: &str,
+    namespace: &str,
+    body: &str,
+    opts: &ImportOptions,
+) -> Result<ImportResult, RulesError> {
+    validate_namespace(namespace)?;
+    let groups = parse_rule_groups(body)?;
+    // Everything is checked before anything is written, so a bad file
+    // changes nothing.
+    let mut streams = Vec::with_capacity(groups.len());
+    for group in groups.iter() {
+        streams.push(check_expressions(group)?);
+    }
+    if opts.destinations.is_empty()
+        && groups
+            .iter()
+            .any(|g| g.rules.iter().any(|r| r.is_alerting()))
+    {
+        return Err(RulesError::DestinationMissing);
+    }
+
+    let mut result = ImportResult::default();
+    for (group, streams) in groups.into_iter().zip(streams) {
+        let key = kv_key(RULES_KV_PREFIX, namespace, &group.name);
+        let previous = get_stored(org_id, &key).await?;
+        let (alert_ids, errors) =
+            sync_alerts(org_id, namespace, &group, &streams, previous.as_ref(), opts).await;
+        result.groups.push(ImportedGroup {
+            namespace: namespace.to_string(),
+            name: group.name.clone(),
+            recording_rules: group.rules.iter().filter(|r| !r.is_alerting()).count(),
+            alerting_rules: group.rules.iter().filter(|r| r.is_alerting()).count(),
+            errors,
+        });
+        let stored = StoredRuleGroup {
+            namespace: namespace.to_string(),
+            group,
+            alert_ids,
+        };
+        crate::kv::set(org_id, &key, Bytes::from(json::to_vec(&stored)?)).await?;
+    }
+    Ok(result)
+}
+
+/// Parses every expression of the group, and returns the stream each alerting
+/// rule's alert is attached to, by rule index.
+fn check_expressions(group: &RuleGroup) -> Result<Vec<Option<String>>, RuleError> {
+    let mut streams = Vec::with_capacity(group.rules.len());
+    for (i, rule) in group.rules.iter().enumerate() {
+        let expr = parser::parse(&rule.expr)
+            .map_err(|e| RuleError::rule(group, i, format!("could not parse expression: {e}")))?;
+        if !rule.is_alerting() {
+            streams.push(None);
+            continue;
+        }
+        let mut visitor = promql::promql::name_visitor::MetricNameVisitor::default();
+        promql_parser::util::walk_expr(&mut visitor, &expr)
+            .map_err(|e| RuleError::rule(group, i, e))?;
+        let Some(name) = visitor.into_names().into_iter().min() else {
+            return Err(RuleError::rule(
+                group,
+                i,
+                "expression selects no metric to attach the alert to",
+            ));
+        };
+        streams.push(Some(format_stream_name(name)));
+    }
+    Ok(streams)
+}
+
+/// Creates, updates and deletes the alerts backing the group's alerting rules.
+///
+/// Alerts are matched to the previous import by name, so re-importing an
+/// unchanged rule updates its alert in place and keeps its state.
+async fn sync_alerts(
+    org_id: &str,
+    namespace: &str,
+    group: &RuleGroup,
+    streams: &[Option<String>],
+    previous: Option<&StoredRuleGroup>,
+    opts: &ImportOptions,
+) -> (Vec<Option<String>>, Vec<String>) {
+    let mut previous_ids: HashMap<String, String> = previous
+        .map(|p| {
+            p.group
+                .alert_names(namespace)
+                .into_iter()
+                .zip(p.alert_ids.iter().cloned())
+                .filter_map(|(name, id)| Some((name?, id?)))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let conn = ORM_CLIENT.get_or_init(connect_to_orm).await;
+    let cfg = get_config();
+    let interval = group.interval_secs(zero_or(cfg.prom.rules_evaluation_interval, 60));
+    let mut alert_ids = Vec::with_capacity(group.rules.len());
+    let mut errors = Vec::new();
+    for ((rule, name), stream) in group
+        .rules
+        .iter()
+        .zip(group.alert_names(namespace))
+        .zip(streams)
+    {
+        let (Some(name), Some(stream)) = (name, stream) else {
+            alert_ids.push(None);
+            continue;
+        };
+        let mut alert = rule_alert(&name, stream, group, rule, interval, opts);
+        let existing = match previous_ids
+            .remove(&name)
+            .and_then(|id| Ksuid::from_str(&id).ok())
+        {
+            Some(id) => alert_service::get_by_id_db(org_id, id).await.ok(),
+            None => None,
+        };
+        let saved = match existing {
+            Some(existing) => {
+                alert.id = existing.id;
+                alert.owner = existing.owner;
+                alert_service::update(conn, org_id, None, alert).await
+            }
+            None => alert_service::create(conn, org_id, &opts.folder_id, alert, false).await,
+        };
+        match saved {
+            Ok(alert) => alert_ids.push(alert.id.map(|id| id.to_string())),
+            Err(e) => {
+                errors.push(format!("{}: {e}", rule.name()));
+                alert_ids.push(None);
+            }
+        }
+    }
+
+    // alerts of rules no longer in the group
+    for id in previous_ids.into_values() {
+        if let Err(e) = delete_alert(org_id, &id).await {
+            errors.push(format!("deleting alert {id}: {e}"));
+        }
+    }
+    (alert_ids, errors)
+}
+
+/// The per-series PromQL alert an alerting rule is evaluated as.
+fn rule_alert(
+    name: &str,
+    stream: &str,
+    group: &RuleGroup,
+    rule: &Rule,
+    interval: u64,
+    opts: &ImportOptions,
+) -> Alert {
+    let mut alert = Alert::default();
+    alert.name = name.to_string();
+    alert.stream_type = StreamType::Metrics;
+    alert.stream_name = stream.to_string();
+    alert.destinations = opts.destinations.clone();
+    alert.enabled = true;
+    alert.owner = Some(opts.user_id.clone());
+    alert.last_edited_by = Some(opts.user_id.clone());
+    // Every series the rule expression returns is an active alert, so the
+    // condition accepts any value; `for` and `keep_firing_for` live in the
+    // query, see `alert_expr`.
+    alert.query_condition = QueryCondition {
+        query_type: QueryType::PromQL,
+        promql: Some(alert_expr(
+            &rule.expr,
+            rule.for_secs(),
+            rule.keep_firing_for_secs(),
+            interval,
+        )),
+        promql_condition: Some(Condition {
+            column: VALUE_LABEL.to_string(),
+            operator: Operator::GreaterThanEquals,
+            value: json::json!(f64::MIN),
+            ignore_case: false,
+        }),
+        promql_multi_alert: true,
+        ..Default::default()
+    };
+    alert.trigger_condition = TriggerCondition {
+        period: interval.div_ceil(60).max(1) as i64,
+        operator: Operator::GreaterThanEquals,
+        threshold: 1,
+        frequency: interval as i64,
+        frequency_type: FrequencyType::Minutes,
+        ..Default::default()
+    };
+    let mut context = group.rule_labels(rule);
+    context.insert("alertname".to_string(), rule.name().to_string());
+    alert.context_attributes = Some(context.into_iter().collect());
+    alert.description = ["summary", "description"]
+        .iter()
+        .filter_map(|k| rule.annotations.get(*k))
+        .cloned()
+        .collect::<Vec<_>>()
+        .join("\n");
+    alert
+}
+
+async fn delete_alert(org_id: &str, id: &str) -> Result<(), AlertError> {
+    let Ok(id) = Ksuid::from_str(id) else {
+        return Ok(());
+    };
+    let conn = ORM_CLIENT.get_or_init(connect_to_orm).await;
+    match alert_service::delete_by_id_user(conn, org_id, id).await {
+        Err(AlertError::AlertNotFound) => Ok(()),
+        r => r,
+    }
+}
+
+async fn get_stored(org_id: &str, key: &str) -> Result<Option<StoredRuleGroup>, anyhow::Error> {
+    match crate::kv::get_opt(org_id, key).await? {
+        Some(v) => Ok(Some(json::from_slice(&v)?)),
+        None => Ok(None),
+    }
+}
+
+/// Rule groups of an org, ordered by namespace and group name.
+pub async fn list(org_id: &str) -> Result<Vec<StoredRuleGroup>, anyhow::Error> {
+    let mut keys = crate::kv::list(org_id, RULES_KV_PREFIX).await?;
+    keys.sort();
+    let mut groups = Vec::with_capacity(keys.len());
+    for key in keys {
+        if let Some(group) = get_stored(org_id, &key).await? {
+            groups.push(group);
+        }
+    }
+    Ok(groups)
+}
+
+/// Rule groups of every org, for the recording rule evaluator.
+pub async fn list_all() -> Result<Vec<(String, StoredRuleGroup)>, anyhow::Error> {
+    let keys = db::kv::list_keys_all_orgs(RULES_KV_PREFIX).await?;
+    let mut groups = Vec::with_capacity(keys.len());
+    for (org_id, key) in keys {
+        if let Some(group) = get_stored(&org_id, &key).await? {
+            groups.push((org_id, group));
+        }
+    }
+    Ok(groups)
+}
+
+/// Rule groups of an org as a rule file per namespace.
+pub async fn list_files(org_id: &str) -> Result<BTreeMap<String, Vec<RuleGroup>>, anyhow::Error> {
+    let mut files: BTreeMap<String, Vec<RuleGroup>> = BTreeMap::new();
+    for stored in list(org_id).await? {
+        files
+            .entry(stored.namespace)
+            .or_default()
+            .push(stored.group);
+    }
+    Ok(files)
+}
+
+/// Deletes one group, or every group of the namespace when `group` is `None`,
+/// along with their alerts.
+pub async fn delete(org_id: &str, namespace: &str, group: Option<&str>) -> Result<(), RulesError> {
+    let groups: Vec<_> = list(org_id)
+        .await?
+        .into_iter()
+        .filter(|g| g.namespace == namespace && group.is_none_or(|name| g.group.name == name))
+        .collect();
+    if groups.is_empty() {
+        return Err(RulesError::NotFound);
+    }
+    for stored in groups {
+        for id in stored.alert_ids.iter().flatten() {
+            delete_alert(org_id, id)
+                .await
+                .map_err(|e| anyhow::anyhow!("deleting alert {id}: {e}"))?;
+        }
+        crate::kv::delete(
+            org_id,
+            &kv_key(RULES_KV_PREFIX, namespace, &stored.group.name),
+        )
+        .await?;
+        let _ = crate::kv::delete(
+            org_id,
+            &kv_key(RULES_HEALTH_KV_PREFIX, namespace, &stored.group.name),
+        )
+        .await;
+    }
+    Ok(())
+}
+
+pub async fn get_health(org_id: &str, namespace: &str, group: &str) -> Option<RuleGroupHealth> {
+    let key = kv_key(RULES_HEALTH_KV_PREFIX, namespace, group);
+    crate::kv::get(org_id, &key)
+        .await
+        .ok()
+        .and_then(|v| json::from_slice(&v).ok())
+}
+
+pub async fn set_health(
+    org_id: &str,
+    namespace: &str,
+    group: &str,
+    health: &RuleGroupHealth,
+) -> Result<(), anyhow::Error> {
+    let key = kv_key(RULES_HEALTH_KV_PREFIX, namespace, group);
+    crate::kv::set(org_id, &key, Bytes::from(json::to_vec(health)?)).await
+}
+
+/// Evaluates the recording rules of a group at `eval_ts` (microseconds),
+/// returning the samples to ingest and the failed rules by index.
+///
+/// Rules run in order, like Prometheus, but a rule reading the output of an
+/// earlier one sees it only once ingested data becomes searchable.
+pub async fn evaluate_recording_rules(
+    org_id: &str,
+    group: &RuleGroup,
+    eval_ts: i64,
+) -> (Vec<json::Value>, BTreeMap<usize, String>) {
+    let mut rows = Vec::new();
+    let mut errors = BTreeMap::new();
+    for (i, rule) in group.rules.iter().enumerate() {
+        let Some(record) = rule.record.as_deref() else {
+            continue;
+        };
+        match evaluate_recording_rule(org_id, group, rule, record, eval_ts).await {
+            Ok(mut v) => rows.append(&mut v),
+            Err(e) => {
+                log::warn!(
+                    "[PROM_RULES] org {org_id} group {} rule {record} failed: {e}",
+                    group.name
+                );
+                errors.insert(i, e.to_string());
+            }
+        }
+    }
+    (rows, errors)
+}
+
+async fn evaluate_recording_rule(
+    org_id: &str,
+    group: &RuleGroup,
+    rule: &Rule,
+    record: &str,
+    eval_ts: i64,
+) -> Result<Vec<json::Value>, anyhow::Error> {
+    let trace_id = ider::generate_trace_id();
+    let req = promql_service::MetricsQueryRequest {
+        query: rule.expr.clone(),
+        start: eval_ts,
+        end: eval_ts,
+        step: 300_000_000, // 5m, as for instant queries
+        query_exemplars: false,
+        use_cache: None,
+        search_type: Some(SearchEventType::DerivedStream),
+        regions: vec![],
+        clusters: vec![],
+    };
+    #[cfg(not(feature = "enterprise"))]
+    let is_super_cluster = false;
+    #[cfg(feature = "enterprise")]
+    let is_super_cluster = o2_enterprise::enterprise::common::config::get_config()
+        .super_cluster
+        .enabled;
+    let value =
+        promql_service::search::search(&trace_id, org_id, &req, "", 0, is_super_cluster).await?;
+    let samples = instant_samples(value)?;
+    if group.limit > 0 && samples.len() > group.limit {
+        anyhow::bail!(
+            "exceeded limit of {} with {} series",
+            group.limit,
+            samples.len()
+        );
+    }
+    recording_rows(record, &group.rule_labels(rule), samples, eval_ts)
+}
+
+/// The `(labels, value)` samples of an instant query result.
+fn instant_samples(value: Value) -> Result<Vec<(BTreeMap<String, String>, f64)>, anyhow::Error> {
+    let labels = |labels: &config::meta::promql::value::Labels| {
+        labels
+            .iter()
+            .map(|l| (l.name.clone(), l.value.clone()))
+            .collect::<BTreeMap<_, _>>()
+    };
+    Ok(match value {
+        Value::Vector(v) => v
+            .iter()
+            .filter(|v| v.histogram.is_none())
+            .map(|v| (labels(&v.labels), v.sample.value))
+            .collect(),
+        Value::Matrix(m) => m
+            .iter()
+            .filter_map(|v| Some((labels(&v.labels), v.samples.last()?.value)))
+            .collect(),
+        Value::Sample(s) => vec![(BTreeMap::new(), s.value)],
+        Value::Float(f) => vec![(BTreeMap::new(), f)],
+        Value::None => vec![],
+        _ => anyhow::bail!("expression must return a vector or a scalar"),
+    })
+}
+
+/// Rows for the JSON metrics ingestion of a recording rule's output.
+fn recording_rows(
+    record: &str,
+    rule_labels: &BTreeMap<String, String>,
+    samples: Vec<(BTreeMap<String, String>, f64)>,
+    eval_ts: i64,
+) -> Result<Vec<json::Value>, anyhow::Error> {
+    let mut seen = hashbrown::HashSet::with_capacity(samples.len());
+    let mut rows = Vec::with_capacity(samples.len());
+    for (mut labels, value) in samples {
+        labels.remove(NAME_LABEL);
+        labels.extend(rule_labels.clone());
+        if !seen.insert(labels.clone()) {
+            anyhow::bail!(
+                "vector contains metrics with the same labelset after applying rule labels"
+            );
+        }
+        let Some(value) = super::sanitize_metric_value(value) else {
+            continue;
+        };
+        let mut row: json::Map<String, json::Value> = labels
+            .into_iter()
+            .map(|(k, v)| (k, json::Value::String(v)))
+            .collect();
+        row.insert(NAME_LABEL.to_string(), record.into());
+        row.insert(TYPE_LABEL.to_string(), "gauge".into());
+        row.insert(TIMESTAMP_COL_NAME.to_string(), eval_ts.into());
+        row.insert(VALUE_LABEL.to_string(), value.into());
+        rows.push(json::Value::Object(row));
+    }
+    Ok(rows)
+}
+
+/// Body of `/api/v1/rules`.
+///
+/// Alerting rule state comes from the per-series state of the backing
+/// alerts, so it is exactly what notifications were based on. Alerts whose
+/// `for` has not elapsed yet are not returned by the query at all, so rules
+/// are either `firing` or `inactive`, never `pending`.
+pub async fn rules(org_id: &str, filter: &RulesFilter) -> Result<RuleDiscovery, anyhow::Error> {
+    let groups: Vec<_> = list(org_id)
+        .await?
+        .into_iter()
+        .filter(|g| filter.matches_group(&g.namespace, &g.group.name))
+        .collect();
+    let states = alert_states(&groups).await?;
+    let cfg = get_config();
+    let default_interval = zero_or(cfg.prom.rules_evaluation_interval, 60);
+
+    let mut discovery = RuleDiscovery::default();
+    for stored in groups {
+        let health = get_health(org_id, &stored.namespace, &stored.group.name).await;
+        let group = &stored.group;
+        let mut rules = Vec::new();
+        let mut last_evaluation = None;
+        for (i, rule) in group.rules.iter().enumerate() {
+            if !filter.matches_rule(rule) {
+                continue;
+            }
+            let status = if rule.is_alerting() {
+                let id = stored.alert_ids.get(i).cloned().flatten();
+                let state = id.as_deref().and_then(|id| states.get(id));
+                let alerts = state
+                    .map(|s| active_alerts(group, rule, s))
+                    .unwrap_or_default();
+                let rollup = state.and_then(|s| s.rollup.as_ref());
+                let (health, last_error) = match (id.as_ref(), rollup.and_then(|r| r.last_outcome))
+                {
+                    (None, _) => ("err", "the rule has no alert, re-import it"),
+                    (_, Some(RunOutcome::Error)) => ("err", "the last evaluation failed"),
+                    (_, None) => ("unknown", ""),
+                    _ => ("ok", ""),
+                };
+                let evaluated_at = rollup.and_then(|r| r.last_outcome_at);
+                last_evaluation = last_evaluation.max(evaluated_at);
+                RuleStatus::Alerting(AlertingRuleStatus {
+                    state: if alerts.is_empty() {
+                        "inactive"
+                    } else {
+                        "firing"
+                    }
+                    .to_string(),
+                    name: rule.name().to_string(),
+                    query: rule.expr.clone(),
+                    duration: rule.for_secs() as f64,
+                    keep_firing_for: rule.keep_firing_for_secs() as f64,
+                    labels: group.rule_labels(rule),
+                    annotations: rule.annotations.clone(),
+                    alerts: (!filter.exclude_alerts).then_some(alerts),
+                    health: health.to_string(),
+                    last_error: last_error.to_string(),
+                    evaluation_time: 0.0,
+                    last_evaluation: format_time(evaluated_at),
+                })
+            } else {
+                let (health, last_error) = match health.as_ref() {
+                    None => ("unknown".to_string(), String::new()),
+                    Some(h) => match h.errors.get(&i) {
+                        Some(e) => ("err".to_string(), e.clone()),
+                        None => ("ok".to_string(), String::new()),
+                    },
+                };
+                RuleStatus::Recording(RecordingRuleStatus {
+                    name: rule.name().to_string(),
+                    query: rule.expr.clone(),
+                    labels: group.rule_labels(rule),
+                    health,
+                    last_error,
+                    evaluation_time: 0.0,
+                    last_evaluation: format_time(None),
+                })
+            };
+            rules.push(status);
+        }
+        if rules.is_empty() && (!filter.rule_names.is_empty() || filter.rule_type.is_some()) {
+            continue;
+        }
+        discovery.groups.push(RuleGroupStatus {
+            name: group.name.clone(),
+            file: stored.namespace.clone(),
+            rules,
+            interval: group.interval_secs(default_interval) as f64,
+            limit: group.limit,
+            evaluation_time: 0.0,
+            last_evaluation: format_time(last_evaluation),
+        });
+    }
+    Ok(discovery)
+}
+
+/// Body of `/api/v1/alerts`: the firing alerts of every alerting rule.
+pub async fn alerts(org_id: &str) -> Result<AlertDiscovery, anyhow::Error> {
+    let groups = list(org_id).await?;
+    let states = alert_states(&groups).await?;
+    let mut discovery = AlertDiscovery::default();
+    for stored in groups.iter() {
+        for (rule, id) in stored.group.rules.iter().zip(stored.alert_ids.iter()) {
+            if let Some(state) = id.as_deref().and_then(|id| states.get(id)) {
+                discovery
+                    .alerts
+                    .extend(active_alerts(&stored.group, rule, state));
+            }
+        }
+    }
+    Ok(discovery)
+}
+
+#[derive(Default)]
+struct RuleAlertState {
+    rollup: Option<config::meta::alerts::state::AlertState>,
+    groups: Vec<config::meta::alerts::state::AlertState>,
+}
+
+/// State rows of the alerts backing the groups' alerting rules, by alert id.
+async fn alert_states(
+    groups: &[StoredRuleGroup],
+) -> Result<HashMap<String, RuleAlertState>, anyhow::Error> {
+    let ids: Vec<String> = groups
+        .iter()
+        .flat_map(|g| g.alert_ids.iter().flatten().cloned())
+        .collect();
+    let mut states: HashMap<String, RuleAlertState> = HashMap::new();
+    for row in infra::table::alert_states::list_all_for(&ids).await? {
+        let entry = states.entry(row.alert_id.clone()).or_default();
+        if row.group_key == ROLLUP_GROUP_KEY {
+            entry.rollup = Some(row);
+        } else {
+            entry.groups.push(row);
+        }
+    }
+    Ok(states)
+}
+
+fn active_alerts(group: &RuleGroup, rule: &Rule, state: &RuleAlertState) -> Vec<ActiveAlert> {
+    let rule_labels = group.rule_labels(rule);
+    state
+        .groups
+        .iter()
+        .filter(|s| s.level.is_some_and(|l| l.is_firing()))
+        .map(|s| {
+            let mut labels = s
+                .group_labels
+                .as_deref()
+                .map(parse_rendered_labels)
+                .unwrap_or_default();
+            labels.remove(NAME_LABEL);
+            labels.extend(rule_labels.clone());
+            labels.insert("alertname".to_string(), rule.name().to_string());
+            ActiveAlert {
+                labels,
+                annotations: rule.annotations.clone(),
+                state: "firing".to_string(),
+                active_at: format_time(s.level_since),
+                value: None,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use config::meta::{
+        alerts::level::AlertLevel,
+        promql::value::{InstantValue, Label, Sample},
+    };
+
+    use super::*;
+
+    fn group(yaml: &str) -> RuleGroup {
+        parse_rule_groups(yaml).unwrap().remove(0)
+    }
+
+    #[test]
+    fn test_check_expressions() {
+        let g = group(
+            "name: g\nrules:\n  - record: a:b\n    expr: sum(rate(http_requests_total[5m]))\n  - alert: Down\n    expr: up{job=\"x\"} == 0 or absent(node_up)\n",
+        );
+        assert_eq!(
+            check_expressions(&g).unwrap(),
+            vec![None, Some("node_up".to_string())]
+        );
+
+        let g = group("name: g\nrules:\n  - alert: A\n    expr: vector(1)\n");
+        assert!(check_expressions(&g).is_err());
+
+        let g = group("name: g\nrules:\n  - record: a\n    expr: sum(\n");
+        let err = check_expressions(&g).unwrap_err().to_string();
+        assert!(err.contains("could not parse expression"), "{err}");
+    }
+
+    #[test]
+    fn test_rule_alert() {
+        let g = group(
+            "name: g\ninterval: 2m\nlabels:\n  team: a\nrules:\n  - alert: Down\n    expr: up == 0\n    for: 5m\n    labels:\n      severity: page\n    annotations:\n      summary: target down\n",
+        );
+        let opts = ImportOptions {
+            destinations: vec!["pager".to_string()],
+            folder_id: "default".to_string(),
+            user_id: "root@example.com".to_string(),
+        };
+        let alert = rule_alert("ns-g-Down", "up", &g, &g.rules[0], 120, &opts);
+        assert_eq!(alert.stream_type, StreamType::Metrics);
+        assert_eq!(alert.trigger_condition.frequency, 120);
+        assert_eq!(alert.trigger_condition.period, 2);
+        assert!(alert.query_condition.promql_multi_alert);
+        assert_eq!(
+            alert.query_condition.promql.as_deref(),
+            Some("(up == 0) and (count_over_time((up == 0)[300s:120s]) >= 3)")
+        );
+        assert_eq!(alert.description, "target down");
+        let context = alert.context_attributes.unwrap();
+        assert_eq!(context.get("severity").map(String::as_str), Some("page"));
+        assert_eq!(context.get("team").map(String::as_str), Some("a"));
+        assert_eq!(context.get("alertname").map(String::as_str), Some("Down"));
+        // multi-alert rules must pass the per-series alert validation
+        assert!(
+            config::meta::alerts::grouping::validate_multi_alert(
+                &alert.query_condition,
+                &alert.trigger_condition,
+                alert.creates_incident,
+            )
+            .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_recording_rows() {
+        let value = Value::Vector(vec![
+            InstantValue {
+                labels: vec![
+                    std::sync::Arc::new(Label::new(NAME_LABEL, "up")),
+                    std::sync::Arc::new(Label::new("job", "a")),
+                ],
+                sample: Sample::new(0, 1.0),
+                histogram: None,
+            },
+            InstantValue {
+                labels: vec![std::sync::Arc::new(Label::new("job", "b"))],
+                sample: Sample::new(0, f64::NAN),
+                histogram: None,
+            },
+        ]);
+        let labels = BTreeMap::from([("team".to_string(), "x".to_string())]);
+        let rows = recording_rows("job:up", &labels, instant_samples(value).unwrap(), 42).unwrap();
+        assert_eq!(
+            rows,
+            vec![json::json!({
+                "__name__": "job:up",
+                "__type__": "gauge",
+                "_timestamp": 42,
+                "job": "a",
+                "team": "x",
+                "value": 1.0,
+            })]
+        );
+
+        // dropping __name__ must not merge two series
+        let value = Value::Vector(
+            ["a", "b"]
+                .into_iter()
+                .map(|name| InstantValue {
+                    labels: vec![std::sync::Arc::new(Label::new(NAME_LABEL, name))],
+                    sample: Sample::new(0, 1.0),
+                    histogram: None,
+                })
+                .collect(),
+        );
+        assert!(recording_rows("r", &BTreeMap::new(), instant_samples(value).unwrap(), 0).is_err());
+
+        let rows = recording_rows(
+            "r",
+            &BTreeMap::new(),
+            instant_samples(Value::Float(2.0)).unwrap(),
+            0,
+        )
+        .unwrap();
+        assert_eq!(rows.len(), 1);
+    }
+
+    #[test]
+    fn test_active_alerts() {
+        let g = group(
+            "name: g\nrules:\n  - alert: Down\n    expr: up == 0\n    labels:\n      severity: page\n",
+        );
+        let row = |key: &str, level, labels: &str| {
+            let mut row = config::meta::alerts::state::AlertState::empty("id", key);
+            row.level = level;
+            row.level_since = Some(1_000_000);
+            row.group_labels = Some(labels.to_string());
+            row
+        };
+        let state = RuleAlertState {
+            rollup: None,
+            groups: vec![
+                row("k1", Some(AlertLevel::Critical), "__name__=up,instance=a"),
+                row("k2", Some(AlertLevel::Ok), "__name__=up,instance=b"),
+            ],
+        };
+        let alerts = active_alerts(&g, &g.rules[0], &state);
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(
+            alerts[0].labels,
+            BTreeMap::from([
+                ("alertname".to_string(), "Down".to_string()),
+                ("instance".to_string(), "a".to_string()),
+                ("severity".to_string(), "page".to_string()),
+            ])
+        );
+        assert_eq!(alerts[0].active_at, "1970-01-01T00:00:01.000Z");
+    }
+}
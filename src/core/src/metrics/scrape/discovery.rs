@@ -0,0 +1,527 @@
+// Copyright 2026 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Target discovery: `static_configs`, `file_sd_configs`, `dns_sd_configs` and
+//! the `endpoints` role of `kubernetes_sd_configs`.
+//!
+//! Every discovered target is a label set with at least `__address__`, plus the
+//! labels of its group and the `__meta_*` labels of the mechanism, which
+//! `relabel_configs` may use.
+
+use std::{
+    collections::BTreeMap,
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use anyhow::Context;
+use config::meta::promql::{
+    relabel::LabelSet,
+    scrape::{
+        DnsRecordType, DnsSdConfig, FileSdConfig, KubernetesSdConfig, ScrapeConfig, StaticConfig,
+        parse_target_groups,
+    },
+};
+use hashbrown::HashMap;
+use hickory_resolver::TokioAsyncResolver;
+use serde::Deserialize;
+
+pub(super) const ADDRESS_LABEL: &str = "__address__";
+
+const K8S_SERVICE_ACCOUNT_DIR: &str = "/var/run/secrets/kubernetes.io/serviceaccount";
+
+/// Discovered targets of the refreshed mechanisms, so they are only queried
+/// again once their `refresh_interval` elapsed.
+#[derive(Default)]
+pub(super) struct Discovery {
+    cache: HashMap<String, (Instant, Vec<LabelSet>)>,
+}
+
+impl Discovery {
+    /// The targets of a scrape config. A mechanism that fails keeps its
+    /// previous targets.
+    pub(super) async fn targets(&mut self, config: &ScrapeConfig) -> Vec<LabelSet> {
+        let mut targets = Vec::new();
+        for group in config.static_configs.iter() {
+            targets.extend(static_targets(group, &LabelSet::new()));
+        }
+        for (i, sd) in config.file_sd_configs.iter().enumerate() {
+            let key = format!("{}/file/{i}", config.job_name);
+            let refresh = Duration::from_millis(sd.refresh_interval_ms());
+            targets.extend(self.refresh(key, refresh, file_targets(sd)).await);
+        }
+        for (i, sd) in config.dns_sd_configs.iter().enumerate() {
+            let key = format!("{}/dns/{i}", config.job_name);
+            let refresh = Duration::from_millis(sd.refresh_interval_ms());
+            targets.extend(self.refresh(key, refresh, dns_targets(sd)).await);
+        }
+        for (i, sd) in config.kubernetes_sd_configs.iter().enumerate() {
+            // listed again on every sync, there is no watch
+            let key = format!("{}/kubernetes/{i}", config.job_name);
+            targets.extend(
+                self.refresh(key, Duration::ZERO, kubernetes_targets(sd))
+                    .await,
+            );
+        }
+        targets
+    }
+
+    async fn refresh(
+        &mut self,
+        key: String,
+        refresh: Duration,
+        discover: impl Future<Output = anyhow::Result<Vec<LabelSet>>>,
+    ) -> Vec<LabelSet> {
+        if let Some((at, targets)) = self.cache.get(&key)
+            && !refresh.is_zero()
+            && at.elapsed() < refresh
+        {
+            return targets.clone();
+        }
+        match discover.await {
+            Ok(targets) => {
+                self.cache.insert(key, (Instant::now(), targets.clone()));
+                targets
+            }
+            Err(e) => {
+                log::error!("[SCRAPE] discovery {key} failed: {e:#}");
+                self.cache
+                    .get(&key)
+                    .map(|(_, targets)| targets.clone())
+                    .unwrap_or_default()
+            }
+        }
+    }
+
+    /// Forgets the mechanisms of jobs that are no longer configured.
+    pub(super) fn retain_jobs(&mut self, jobs: &[&str]) {
+        self.cache.retain(|key, _| {
+            key.rsplitn(3, '/')
+                .nth(2)
+                .is_some_and(|job| jobs.contains(&job))
+        });
+    }
+}
+
+fn static_targets(group: &StaticConfig, meta: &LabelSet) -> Vec<LabelSet> {
+    group
+        .targets
+        .iter()
+        .map(|target| {
+            let mut labels = meta.clone();
+            labels.extend(group.labels.clone());
+            labels.insert(ADDRESS_LABEL.to_string(), target.clone());
+            labels
+        })
+        .collect()
+}
+
+async fn file_targets(sd: &FileSdConfig) -> anyhow::Result<Vec<LabelSet>> {
+    let mut targets = Vec::new();
+    for pattern in sd.files.iter() {
+        for path in expand_file_pattern(pattern)? {
+            let body = tokio::fs::read_to_string(&path)
+                .await
+                .with_context(|| format!("reading {path}"))?;
+            let groups =
+                parse_target_groups(&body).map_err(|e| anyhow::anyhow!("parsing {path}: {e}"))?;
+            let meta = LabelSet::from([("__meta_filepath".to_string(), path.clone())]);
+            for group in groups.iter() {
+                targets.extend(static_targets(group, &meta));
+            }
+        }
+    }
+    Ok(targets)
+}
+
+/// The files matching `pattern`, `*` and `?` are allowed in the file name.
+fn expand_file_pattern(pattern: &str) -> anyhow::Result<Vec<String>> {
+    let path = Path::new(pattern);
+    let Some(file_name) = path.file_name().and_then(|f| f.to_str()) else {
+        anyhow::bail!("invalid file pattern {pattern:?}");
+    };
+    if !file_name.contains(['*', '?']) {
+        return Ok(vec![pattern.to_string()]);
+    }
+    let dir = path.parent().filter(|p| !p.as_os_str().is_empty());
+    let dir = dir.unwrap_or(Path::new("."));
+    let regex = regex::Regex::new(&format!(
+        "^{}$",
+        regex::escape(file_name)
+            .replace(r"\*", ".*")
+            .replace(r"\?", ".")
+    ))?;
+    let mut files = std::fs::read_dir(dir)
+        .with_context(|| format!("listing {}", dir.display()))?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_ok_and(|t| t.is_file()))
+        .filter(|entry| {
+            entry
+                .file_name()
+                .to_str()
+                .is_some_and(|n| regex.is_match(n))
+        })
+        .map(|entry| dir.join(entry.file_name()).to_string_lossy().into_owned())
+        .collect::<Vec<_>>();
+    files.sort();
+    Ok(files)
+}
+
+async fn dns_targets(sd: &DnsSdConfig) -> anyhow::Result<Vec<LabelSet>> {
+    let resolver = TokioAsyncResolver::tokio_from_system_conf()?;
+    let mut targets = Vec::new();
+    for name in sd.names.iter() {
+        let target = |address: String, extra: &[(&str, String)]| {
+            let mut labels = LabelSet::from([
+                (ADDRESS_LABEL.to_string(), address),
+                ("__meta_dns_name".to_string(), name.clone()),
+            ]);
+            for (k, v) in extra {
+                labels.insert(k.to_string(), v.clone());
+            }
+            labels
+        };
+        match sd.type_ {
+            DnsRecordType::Srv => {
+                let lookup = resolver
+                    .srv_lookup(name.as_str())
+                    .await
+                    .with_context(|| format!("resolving SRV {name}"))?;
+                for srv in lookup.iter() {
+                    let host = srv.target().to_utf8();
+                    let host = host.trim_end_matches('.');
+                    targets.push(target(
+                        format!("{host}:{}", srv.port()),
+                        &[
+                            ("__meta_dns_srv_record_target", host.to_string()),
+                            ("__meta_dns_srv_record_port", srv.port().to_string()),
+                        ],
+                    ));
+                }
+            }
+            DnsRecordType::A => {
+                let lookup = resolver
+                    .ipv4_lookup(name.as_str())
+                    .await
+                    .with_context(|| format!("resolving A {name}"))?;
+                for a in lookup.iter() {
+                    targets.push(target(format!("{}:{}", a.0, sd.port), &[]));
+                }
+            }
+            DnsRecordType::Aaaa => {
+                let lookup = resolver
+                    .ipv6_lookup(name.as_str())
+                    .await
+                    .with_context(|| format!("resolving AAAA {name}"))?;
+                for aaaa in lookup.iter() {
+                    targets.push(target(format!("[{}]:{}", aaaa.0, sd.port), &[]));
+                }
+            }
+        }
+    }
+    Ok(targets)
+}
+
+#[derive(Deserialize)]
+struct EndpointsList {
+    #[serde(default)]
+    items: Vec<Endpoints>,
+}
+
+#[derive(Deserialize)]
+struct Endpoints {
+    metadata: ObjectMeta,
+    #[serde(default)]
+    subsets: Vec<EndpointSubset>,
+}
+
+#[derive(Deserialize)]
+struct ObjectMeta {
+    name: String,
+    #[serde(default)]
+    namespace: String,
+    #[serde(default)]
+    labels: BTreeMap<String, String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EndpointSubset {
+    #[serde(default)]
+    addresses: Vec<EndpointAddress>,
+    #[serde(default)]
+    not_ready_addresses: Vec<EndpointAddress>,
+    #[serde(default)]
+    ports: Vec<EndpointPort>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EndpointAddress {
+    ip: String,
+    #[serde(default)]
+    node_name: Option<String>,
+    #[serde(default)]
+    target_ref: Option<ObjectReference>,
+}
+
+#[derive(Deserialize)]
+struct ObjectReference {
+    #[serde(default)]
+    kind: String,
+    #[serde(default)]
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct EndpointPort {
+    #[serde(default)]
+    name: Option<String>,
+    port: u16,
+    #[serde(default)]
+    protocol: Option<String>,
+}
+
+async fn kubernetes_targets(sd: &KubernetesSdConfig) -> anyhow::Result<Vec<LabelSet>> {
+    let mut client = reqwest::Client::builder().timeout(Duration::from_secs(30));
+    let api_server = match sd.api_server.as_deref() {
+        Some(url) => url.trim_end_matches('/').to_string(),
+        None => {
+            let host = std::env::var("KUBERNETES_SERVICE_HOST")
+                .context("api_server is not set and not running in a cluster")?;
+            let port = std::env::var("KUBERNETES_SERVICE_PORT").unwrap_or("443".to_string());
+            let ca = tokio::fs::read(format!("{K8S_SERVICE_ACCOUNT_DIR}/ca.crt")).await?;
+            client = client.add_root_certificate(reqwest::Certificate::from_pem(&ca)?);
+            if host.contains(':') {
+                format!("https://[{host}]:{port}")
+            } else {
+                format!("https://{host}:{port}")
+            }
+        }
+    };
+    let token_file = match (sd.bearer_token_file.as_deref(), sd.api_server.is_none()) {
+        (Some(file), _) => Some(file.to_string()),
+        (None, true) => Some(format!("{K8S_SERVICE_ACCOUNT_DIR}/token")),
+        (None, false) => None,
+    };
+    let token = match token_file {
+        Some(file) => Some(
+            tokio::fs::read_to_string(&file)
+                .await
+                .with_context(|| format!("reading {file}"))?
+                .trim()
+                .to_string(),
+        ),
+        None => None,
+    };
+    let client = client.build()?;
+
+    let urls = match sd.namespaces.as_ref().filter(|ns| !ns.names.is_empty()) {
+        None => vec![format!("{api_server}/api/v1/endpoints")],
+        Some(ns) => ns
+            .names
+            .iter()
+            .map(|ns| format!("{api_server}/api/v1/namespaces/{ns}/endpoints"))
+            .collect(),
+    };
+    let mut targets = Vec::new();
+    for url in urls {
+        let mut req = client.get(&url);
+        if let Some(token) = token.as_deref() {
+            req = req.bearer_auth(token);
+        }
+        let list: EndpointsList = req
+            .send()
+            .await?
+            .error_for_status()
+            .with_context(|| format!("listing {url}"))?
+            .json()
+            .await?;
+        for endpoints in list.items.iter() {
+            targets.extend(endpoints_targets(endpoints));
+        }
+    }
+    Ok(targets)
+}
+
+/// One target per address and port of the endpoints object.
+fn endpoints_targets(endpoints: &Endpoints) -> Vec<LabelSet> {
+    let meta = &endpoints.metadata;
+    let mut base = LabelSet::from([
+        (
+            "__meta_kubernetes_namespace".to_string(),
+            meta.namespace.clone(),
+        ),
+        (
+            "__meta_kubernetes_endpoints_name".to_string(),
+            meta.name.clone(),
+        ),
+        (
+            "__meta_kubernetes_service_name".to_string(),
+            meta.name.clone(),
+        ),
+    ]);
+    for (name, value) in meta.labels.iter() {
+        let name = sanitize_label_name(name);
+        base.insert(
+            format!("__meta_kubernetes_endpoints_label_{name}"),
+            value.clone(),
+        );
+        base.insert(
+            format!("__meta_kubernetes_endpoints_labelpresent_{name}"),
+            "true".to_string(),
+        );
+    }
+
+    let mut targets = Vec::new();
+    for subset in endpoints.subsets.iter() {
+        let addresses = subset
+            .addresses
+            .iter()
+            .map(|a| (a, true))
+            .chain(subset.not_ready_addresses.iter().map(|a| (a, false)));
+        for (address, ready) in addresses {
+            for port in subset.ports.iter() {
+                let mut labels = base.clone();
+                let host = if address.ip.contains(':') {
+                    format!("[{}]", address.ip)
+                } else {
+                    address.ip.clone()
+                };
+                labels.insert(ADDRESS_LABEL.to_string(), format!("{host}:{}", port.port));
+                labels.insert(
+                    "__meta_kubernetes_endpoint_ready".to_string(),
+                    ready.to_string(),
+                );
+                labels.insert(
+                    "__meta_kubernetes_endpoint_port_name".to_string(),
+                    port.name.clone().unwrap_or_default(),
+                );
+                labels.insert(
+                    "__meta_kubernetes_endpoint_port_protocol".to_string(),
+                    port.protocol.clone().unwrap_or_default(),
+                );
+                if let Some(node) = address.node_name.as_ref() {
+                    labels.insert(
+                        "__meta_kubernetes_endpoint_node_name".to_string(),
+                        node.clone(),
+                    );
+                }
+                if let Some(target_ref) = address.target_ref.as_ref() {
+                    labels.insert(
+                        "__meta_kubernetes_endpoint_address_target_kind".to_string(),
+                        target_ref.kind.clone(),
+                    );
+                    labels.insert(
+                        "__meta_kubernetes_endpoint_address_target_name".to_string(),
+                        target_ref.name.clone(),
+                    );
+                }
+                targets.push(labels);
+            }
+        }
+    }
+    targets
+}
+
+fn sanitize_label_name(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_file_targets() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("a.json"),
+            r#"[{"targets": ["a:80"], "labels": {"team": "core"}}]"#,
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("b.yml"), "- targets: [b:80]\n").unwrap();
+        std::fs::write(dir.path().join("c.txt"), "not targets").unwrap();
+
+        let sd = FileSdConfig {
+            files: vec![
+                dir.path().join("*.json").to_string_lossy().into_owned(),
+                dir.path().join("b.y?l").to_string_lossy().into_owned(),
+            ],
+            refresh_interval: None,
+        };
+        let targets = file_targets(&sd).await.unwrap();
+        assert_eq!(targets.len(), 2);
+        assert_eq!(targets[0][ADDRESS_LABEL], "a:80");
+        assert_eq!(targets[0]["team"], "core");
+        assert!(targets[0]["__meta_filepath"].ends_with("a.json"));
+        assert_eq!(targets[1][ADDRESS_LABEL], "b:80");
+    }
+
+    #[tokio::test]
+    async fn test_discovery_keeps_targets_on_error() {
+        let mut discovery = Discovery::default();
+        let ok = discovery
+            .refresh("job/file/0".to_string(), Duration::ZERO, async {
+                Ok(vec![LabelSet::from([(
+                    ADDRESS_LABEL.to_string(),
+                    "a:80".to_string(),
+                )])])
+            })
+            .await;
+        let failed = discovery
+            .refresh("job/file/0".to_string(), Duration::ZERO, async {
+                Err(anyhow::anyhow!("unreachable"))
+            })
+            .await;
+        assert_eq!(ok, failed);
+
+        discovery.retain_jobs(&["other"]);
+        assert!(discovery.cache.is_empty());
+    }
+
+    #[test]
+    fn test_endpoints_targets() {
+        let endpoints: Endpoints = serde_json::from_str(
+            r#"{
+                "metadata": {"name": "api", "namespace": "prod", "labels": {"app.kubernetes.io/name": "api"}},
+                "subsets": [{
+                    "addresses": [{"ip": "10.1.0.5", "nodeName": "node-1", "targetRef": {"kind": "Pod", "name": "api-0"}}],
+                    "notReadyAddresses": [{"ip": "10.1.0.6"}],
+                    "ports": [{"name": "metrics", "port": 9090, "protocol": "TCP"}]
+                }]
+            }"#,
+        )
+        .unwrap();
+        let targets = endpoints_targets(&endpoints);
+        assert_eq!(targets.len(), 2);
+        let ready = &targets[0];
+        assert_eq!(ready[ADDRESS_LABEL], "10.1.0.5:9090");
+        assert_eq!(ready["__meta_kubernetes_endpoint_ready"], "true");
+        assert_eq!(ready["__meta_kubernetes_namespace"], "prod");
+        assert_eq!(ready["__meta_kubernetes_endpoint_port_name"], "metrics");
+        assert_eq!(
+            ready["__meta_kubernetes_endpoint_address_target_name"],
+            "api-0"
+        );
+        assert_eq!(
+            ready["__meta_kubernetes_endpoints_label_app_kubernetes_io_name"],
+            "api"
+        );
+        assert_eq!(targets[1]["__meta_kubernetes_endpoint_ready"], "false");
+    }
+}
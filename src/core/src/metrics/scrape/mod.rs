@@ -0,0 +1,224 @@
+// Copyright 2026 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Built-in Prometheus scrape agent.
+//!
+//! The ingesters read the `scrape_configs` of `ZO_PROMETHEUS_SCRAPE_CONFIG_FILE`,
+//! discover the targets of every job and split them between each other by a
+//! hash of the target. Every owned target runs its own scrape loop, writing
+//! through [`super::prom::remote_write`] like a remote write client would.
+//! Configuration and targets are synced every
+//! `ZO_PROMETHEUS_SCRAPE_SYNC_INTERVAL`; loops of changed targets are
+//! restarted, loops of removed targets mark their series stale and stop.
+
+use std::sync::Arc;
+
+use config::{
+    cluster::LOCAL_NODE,
+    get_config,
+    meta::promql::scrape::{GlobalConfig, ScrapeConfig, parse_scrape_file},
+    utils::hash::{Sum64, murmur3},
+};
+use hashbrown::HashMap;
+use tokio::sync::oneshot;
+
+mod discovery;
+pub mod parser;
+mod target;
+
+use discovery::Discovery;
+use target::Target;
+
+struct Running {
+    config: Arc<ScrapeConfig>,
+    global: Arc<GlobalConfig>,
+    stop: oneshot::Sender<()>,
+}
+
+/// Keeps the scrape loops in line with the configuration file.
+#[derive(Default)]
+pub struct Manager {
+    discovery: Discovery,
+    /// Running scrape loops by job and target key.
+    running: HashMap<(String, String), Running>,
+}
+
+impl Manager {
+    /// Reloads the configuration, discovers the targets and starts, restarts
+    /// and stops scrape loops accordingly. An invalid configuration leaves the
+    /// running loops alone.
+    pub async fn sync(&mut self) -> Result<(), anyhow::Error> {
+        let cfg = get_config();
+        let body = tokio::fs::read_to_string(&cfg.prom.scrape_config_file).await?;
+        let file = parse_scrape_file(&body).map_err(|e| anyhow::anyhow!(e))?;
+        let global = Arc::new(file.global);
+
+        let mut wanted = HashMap::new();
+        for config in file.scrape_configs {
+            let config = Arc::new(config);
+            for labels in self.discovery.targets(&config).await {
+                match target::populate(&config, &global, labels) {
+                    Ok(Some(target)) => {
+                        wanted.insert(
+                            (config.job_name.clone(), target.key()),
+                            (config.clone(), target),
+                        );
+                    }
+                    Ok(None) => {}
+                    Err(e) => log::warn!("[SCRAPE] job {}: {e}", config.job_name),
+                }
+            }
+        }
+        let jobs = wanted
+            .keys()
+            .map(|(job, _)| job.as_str())
+            .collect::<Vec<_>>();
+        self.discovery.retain_jobs(&jobs);
+
+        let owner = Owner::new().await;
+        wanted.retain(|(_, key), _| owner.owns(key));
+
+        // stop removed and changed targets
+        let stopped = self
+            .running
+            .extract_if(|key, running| match wanted.get(key) {
+                Some((config, _)) => running.config != *config || running.global != global,
+                None => true,
+            })
+            .collect::<Vec<_>>();
+        for (_, running) in stopped {
+            let _ = running.stop.send(());
+        }
+
+        let org_id = cfg.prom.scrape_org_id.clone();
+        for (key, (config, target)) in wanted {
+            if self.running.contains_key(&key) {
+                continue;
+            }
+            self.start(&org_id, key, config, global.clone(), target);
+        }
+        Ok(())
+    }
+
+    fn start(
+        &mut self,
+        org_id: &str,
+        key: (String, String),
+        config: Arc<ScrapeConfig>,
+        global: Arc<GlobalConfig>,
+        target: Target,
+    ) {
+        log::debug!("[SCRAPE] job {} starting target {}", key.0, target.url);
+        let (stop, stopped) = oneshot::channel();
+        tokio::task::spawn(target::run(
+            org_id.to_string(),
+            config.clone(),
+            global.clone(),
+            target,
+            stopped,
+        ));
+        self.running.insert(
+            key,
+            Running {
+                config,
+                global,
+                stop,
+            },
+        );
+    }
+
+    /// Stops every scrape loop, e.g. when the node is no longer online.
+    pub fn stop(&mut self) {
+        for (_, running) in self.running.drain() {
+            let _ = running.stop.send(());
+        }
+    }
+}
+
+/// Decides which targets this ingester scrapes.
+#[derive(Debug, PartialEq)]
+enum Owner {
+    /// No cluster view, scrape every target.
+    All,
+    /// This node isn't among the online ingesters, e.g. while it registers,
+    /// scrape nothing so no target is scraped twice.
+    Nothing,
+    /// Position of this node among the online ingesters and their count.
+    Slot(u64, u64),
+}
+
+impl Owner {
+    async fn new() -> Self {
+        match infra::cluster::get_cached_online_ingester_nodes().await {
+            Some(nodes) if !nodes.is_empty() => Self::from_ingesters(
+                nodes.into_iter().map(|node| node.uuid).collect(),
+                &LOCAL_NODE.uuid,
+            ),
+            _ => Self::All,
+        }
+    }
+
+    fn from_ingesters(mut uuids: Vec<String>, local: &str) -> Self {
+        uuids.sort();
+        match uuids.iter().position(|uuid| uuid == local) {
+            Some(index) => Self::Slot(index as u64, uuids.len() as u64),
+            None => Self::Nothing,
+        }
+    }
+
+    fn owns(&self, key: &str) -> bool {
+        match self {
+            Self::All => true,
+            Self::Nothing => false,
+            // the hash must be the same on every node
+            Self::Slot(index, count) => murmur3::new().sum64(key) % count == *index,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_owner() {
+        let keys = (0..100)
+            .map(|i| format!("http://10.0.0.{i}:9100/metrics{{}}"))
+            .collect::<Vec<_>>();
+        assert!(keys.iter().all(|k| Owner::All.owns(k)));
+
+        let ingesters = ["c", "a", "b"].map(String::from).to_vec();
+        let owners = ["a", "b", "c"]
+            .iter()
+            .map(|uuid| Owner::from_ingesters(ingesters.clone(), uuid))
+            .collect::<Vec<_>>();
+        assert_eq!(owners[1], Owner::Slot(1, 3));
+        for key in keys.iter() {
+            assert_eq!(owners.iter().filter(|o| o.owns(key)).count(), 1);
+        }
+        // stable across nodes and restarts
+        assert_eq!(
+            keys.iter().map(|k| owners[0].owns(k)).collect::<Vec<_>>(),
+            keys.iter()
+                .map(|k| murmur3::new().sum64(k) % 3 == 0)
+                .collect::<Vec<_>>()
+        );
+
+        // a node missing from the ingesters scrapes nothing
+        let owner = Owner::from_ingesters(ingesters, "d");
+        assert_eq!(owner, Owner::Nothing);
+        assert!(keys.iter().all(|k| !owner.owns(k)));
+    }
+}
@@ -0,0 +1,408 @@
+// Copyright 2026 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Parser of the Prometheus text exposition format and of OpenMetrics text.
+//!
+//! cf. https://prometheus.io/docs/instrumenting/exposition_formats/ and
+//! https://prometheus.io/docs/specs/om/open_metrics_spec/
+
+use config::meta::promql::{NAME_LABEL, relabel::LabelSet};
+use hashbrown::HashMap;
+use proto::prometheus_rpc::metric_metadata::MetricType;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Text,
+    OpenMetrics,
+}
+
+impl Format {
+    pub fn from_content_type(content_type: &str) -> Self {
+        if content_type.starts_with("application/openmetrics-text") {
+            Format::OpenMetrics
+        } else {
+            Format::Text
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sample {
+    /// Labels including `__name__`.
+    pub labels: LabelSet,
+    pub value: f64,
+    /// Milliseconds.
+    pub timestamp: Option<i64>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Family {
+    pub name: String,
+    pub metric_type: MetricType,
+    pub help: String,
+    pub unit: String,
+}
+
+#[derive(Debug, Default)]
+pub struct Exposition {
+    pub samples: Vec<Sample>,
+    /// Families with a `TYPE`, `HELP` or `UNIT` line, in order of appearance.
+    pub families: Vec<Family>,
+}
+
+/// Parses a scrape response. Exemplars and OpenMetrics `_created` series are
+/// skipped, like Prometheus does by default.
+pub fn parse(body: &str, format: Format) -> Result<Exposition, String> {
+    let mut exposition = Exposition::default();
+    let mut family_idx: HashMap<String, usize> = HashMap::new();
+    for (n, line) in body.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let err = |reason: String| format!("line {}: {reason}", n + 1);
+        if let Some(comment) = line.strip_prefix('#') {
+            let comment = comment.trim_start();
+            if comment == "EOF" && format == Format::OpenMetrics {
+                break;
+            }
+            let mut parts = comment.splitn(3, ' ');
+            let (Some(kind), Some(name)) = (parts.next(), parts.next()) else {
+                continue;
+            };
+            let rest = parts.next().unwrap_or_default().trim();
+            if !matches!(kind, "HELP" | "TYPE" | "UNIT") {
+                continue;
+            }
+            let idx = *family_idx.entry(name.to_string()).or_insert_with(|| {
+                exposition.families.push(Family {
+                    name: name.to_string(),
+                    metric_type: MetricType::Unknown,
+                    help: String::new(),
+                    unit: String::new(),
+                });
+                exposition.families.len() - 1
+            });
+            let family = &mut exposition.families[idx];
+            match kind {
+                "HELP" => family.help = unescape(rest),
+                "UNIT" => family.unit = rest.to_string(),
+                _ => {
+                    family.metric_type = metric_type(rest)
+                        .ok_or_else(|| err(format!("invalid metric type {rest:?}")))?
+                }
+            }
+            continue;
+        }
+
+        let sample = parse_sample(line, format).map_err(err)?;
+        if format == Format::OpenMetrics && is_created_series(&sample, &exposition, &family_idx) {
+            continue;
+        }
+        exposition.samples.push(sample);
+    }
+    Ok(exposition)
+}
+
+fn metric_type(s: &str) -> Option<MetricType> {
+    Some(match s {
+        "counter" => MetricType::Counter,
+        "gauge" => MetricType::Gauge,
+        "histogram" => MetricType::Histogram,
+        "gaugehistogram" => MetricType::Gaugehistogram,
+        "summary" => MetricType::Summary,
+        "info" => MetricType::Info,
+        "stateset" => MetricType::Stateset,
+        "untyped" | "unknown" => MetricType::Unknown,
+        _ => return None,
+    })
+}
+
+/// Whether `sample` is the creation time of a counter, histogram or summary.
+fn is_created_series(
+    sample: &Sample,
+    exposition: &Exposition,
+    family_idx: &HashMap<String, usize>,
+) -> bool {
+    let Some(family) = sample.labels[NAME_LABEL]
+        .strip_suffix("_created")
+        .and_then(|name| family_idx.get(name))
+    else {
+        return false;
+    };
+    matches!(
+        exposition.families[*family].metric_type,
+        MetricType::Counter
+            | MetricType::Histogram
+            | MetricType::Gaugehistogram
+            | MetricType::Summary
+    )
+}
+
+fn parse_sample(line: &str, format: Format) -> Result<Sample, String> {
+    let name_end = line
+        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == ':'))
+        .unwrap_or(line.len());
+    let name = &line[..name_end];
+    if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) {
+        return Err(format!("invalid metric name in {line:?}"));
+    }
+    let mut labels = LabelSet::new();
+    let mut rest = &line[name_end..];
+    if let Some(inner) = rest.strip_prefix('{') {
+        rest = parse_labels(inner, &mut labels)?;
+    }
+    labels.insert(NAME_LABEL.to_string(), name.to_string());
+
+    // an OpenMetrics exemplar follows the sample after " # "
+    if let Some(pos) = rest.find('#') {
+        rest = &rest[..pos];
+    }
+    let mut fields = rest.split_ascii_whitespace();
+    let value = fields
+        .next()
+        .ok_or_else(|| format!("missing value in {line:?}"))?;
+    let value = parse_value(value).ok_or_else(|| format!("invalid value {value:?}"))?;
+    let timestamp = match fields.next() {
+        None => None,
+        Some(ts) => {
+            Some(parse_timestamp(ts, format).ok_or_else(|| format!("invalid timestamp {ts:?}"))?)
+        }
+    };
+    if fields.next().is_some() {
+        return Err(format!("unexpected fields in {line:?}"));
+    }
+    Ok(Sample {
+        labels,
+        value,
+        timestamp,
+    })
+}
+
+/// Parses the labels after `{`, returns what follows the closing `}`.
+fn parse_labels<'a>(mut s: &'a str, labels: &mut LabelSet) -> Result<&'a str, String> {
+    loop {
+        s = s.trim_start();
+        if let Some(rest) = s.strip_prefix('}') {
+            return Ok(rest);
+        }
+        let name_end = s
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .ok_or("unterminated label set")?;
+        let name = &s[..name_end];
+        if name.is_empty() {
+            return Err(format!("invalid label name at {s:?}"));
+        }
+        s = s[name_end..].trim_start();
+        s = s
+            .strip_prefix('=')
+            .ok_or_else(|| format!("expected '=' after label {name}"))?
+            .trim_start();
+        s = s
+            .strip_prefix('"')
+            .ok_or_else(|| format!("expected '\"' for the value of label {name}"))?;
+        let mut value = String::new();
+        let mut chars = s.char_indices();
+        let end = loop {
+            match chars.next() {
+                Some((i, '"')) => break i,
+                Some((_, '\\')) => match chars.next() {
+                    Some((_, 'n')) => value.push('\n'),
+                    Some((_, c)) => value.push(c),
+                    None => return Err("unterminated label value".to_string()),
+                },
+                Some((_, c)) => value.push(c),
+                None => return Err("unterminated label value".to_string()),
+            }
+        };
+        if labels.insert(name.to_string(), value).is_some() {
+            return Err(format!("duplicate label {name}"));
+        }
+        s = s[end + 1..].trim_start();
+        if let Some(rest) = s.strip_prefix(',') {
+            s = rest;
+        } else if !s.starts_with('}') {
+            return Err(format!("expected ',' or '}}' after label {name}"));
+        }
+    }
+}
+
+fn parse_value(s: &str) -> Option<f64> {
+    match s {
+        "+Inf" | "Inf" => Some(f64::INFINITY),
+        "-Inf" => Some(f64::NEG_INFINITY),
+        "NaN" => Some(f64::NAN),
+        _ => s.parse().ok().filter(|v: &f64| v.is_finite()),
+    }
+}
+
+/// Text format timestamps are milliseconds, OpenMetrics ones are seconds.
+fn parse_timestamp(s: &str, format: Format) -> Option<i64> {
+    match format {
+        Format::Text => s.parse().ok(),
+        Format::OpenMetrics => s
+            .parse::<f64>()
+            .ok()
+            .filter(|v| v.is_finite())
+            .map(|v| (v * 1000.0).round() as i64),
+    }
+}
+
+fn unescape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        match (c, chars.clone().next()) {
+            ('\\', Some('n')) => {
+                out.push('\n');
+                chars.next();
+            }
+            ('\\', Some('\\')) => {
+                out.push('\\');
+                chars.next();
+            }
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(
+        exposition: &Exposition,
+        idx: usize,
+    ) -> (String, Vec<(&str, &str)>, f64, Option<i64>) {
+        let s = &exposition.samples[idx];
+        let labels = s
+            .labels
+            .iter()
+            .filter(|(k, _)| k.as_str() != NAME_LABEL)
+            .map(|(k, v)| (k.as_str(), v.as_str()))
+            .collect();
+        (s.labels[NAME_LABEL].clone(), labels, s.value, s.timestamp)
+    }
+
+    #[test]
+    fn test_parse_text() {
+        let body = r#"
+# HELP http_requests_total The total number of HTTP requests.
+# TYPE http_requests_total counter
+http_requests_total{method="post",code="200"} 1027 1395066363000
+http_requests_total{method="post",code="400"}    3 1395066363000
+
+# Escaping in label values:
+msdos_file_access_time_seconds{path="C:\\DIR\\FILE.TXT",error="Cannot find file:\n\"FILE.TXT\""} 1.458255915e9
+
+metric_without_timestamp_and_labels 12.47
+something_weird{problem="division by zero"} +Inf -3982045
+# TYPE rpc_duration_seconds summary
+rpc_duration_seconds{quantile="0.5",} 4773
+rpc_duration_seconds_sum 1.7560473e+07
+"#;
+        let exposition = parse(body, Format::Text).unwrap();
+        assert_eq!(exposition.samples.len(), 7);
+        assert_eq!(
+            sample(&exposition, 0),
+            (
+                "http_requests_total".to_string(),
+                vec![("code", "200"), ("method", "post")],
+                1027.0,
+                Some(1395066363000)
+            )
+        );
+        assert_eq!(
+            sample(&exposition, 2).1,
+            vec![
+                ("error", "Cannot find file:\n\"FILE.TXT\""),
+                ("path", "C:\\DIR\\FILE.TXT")
+            ]
+        );
+        assert_eq!(sample(&exposition, 3).3, None);
+        assert_eq!(sample(&exposition, 4).2, f64::INFINITY);
+        assert_eq!(sample(&exposition, 4).3, Some(-3982045));
+        assert_eq!(sample(&exposition, 5).1, vec![("quantile", "0.5")]);
+
+        assert_eq!(exposition.families.len(), 2);
+        assert_eq!(exposition.families[0].metric_type, MetricType::Counter);
+        assert_eq!(
+            exposition.families[0].help,
+            "The total number of HTTP requests."
+        );
+        assert_eq!(exposition.families[1].metric_type, MetricType::Summary);
+    }
+
+    #[test]
+    fn test_parse_openmetrics() {
+        let body = r#"# TYPE acme_http_router_request_seconds summary
+# UNIT acme_http_router_request_seconds seconds
+# HELP acme_http_router_request_seconds Latency though all of ACME's HTTP request router.
+acme_http_router_request_seconds_sum{path="/api/v1",method="GET"} 9036.32 1520879607.789
+acme_http_router_request_seconds_count{path="/api/v1",method="GET"} 807283.0
+acme_http_router_request_seconds_created{path="/api/v1",method="GET"} 1605281325.0
+# TYPE foo counter
+foo_total 17.0 1520879607.789 # {trace_id="KOO5S4vxi0o"} 0.67
+foo_created 1520872607.123
+# EOF
+ignored_after_eof 1
+"#;
+        let exposition = parse(body, Format::OpenMetrics).unwrap();
+        let names = (0..exposition.samples.len())
+            .map(|i| sample(&exposition, i).0)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            vec![
+                "acme_http_router_request_seconds_sum",
+                "acme_http_router_request_seconds_count",
+                "foo_total"
+            ]
+        );
+        assert_eq!(sample(&exposition, 0).3, Some(1520879607789));
+        assert_eq!(sample(&exposition, 2).2, 17.0);
+        assert_eq!(sample(&exposition, 2).3, Some(1520879607789));
+        assert_eq!(exposition.families[0].unit, "seconds");
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        for body in [
+            "metric{a=\"1\" 1",
+            "metric{a=1} 1",
+            "metric{a=\"1\",a=\"2\"} 1",
+            "metric",
+            "metric one",
+            "metric 1 2 3",
+            "# TYPE metric sometimes",
+            "1metric 1",
+        ] {
+            assert!(parse(body, Format::Text).is_err(), "{body}");
+        }
+    }
+
+    #[test]
+    fn test_format_from_content_type() {
+        assert_eq!(
+            Format::from_content_type("application/openmetrics-text; version=1.0.0; charset=utf-8"),
+            Format::OpenMetrics
+        );
+        assert_eq!(
+            Format::from_content_type("text/plain; version=0.0.4"),
+            Format::Text
+        );
+    }
+}
@@ -0,0 +1,954 @@
+// Copyright 2026 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! A scrape target and its scrape loop.
+
+use std::{
+    hash::{DefaultHasher, Hash, Hasher},
+    sync::Arc,
+    time::Duration,
+};
+
+use config::{
+    meta::promql::{
+        NAME_LABEL,
+        histogram::STALE_NAN_BITS,
+        relabel::{LabelSet, relabel},
+        scrape::{GlobalConfig, ScrapeConfig},
+    },
+    utils::time::now_micros,
+};
+use hashbrown::HashSet;
+use ingestion_common::{IngestUser, SystemJobType};
+use prost::Message;
+use proto::prometheus_rpc;
+use tokio::sync::oneshot;
+
+use super::{
+    discovery::ADDRESS_LABEL,
+    parser::{self, Family, Format, Sample},
+};
+
+const ACCEPT_HEADER: &str =
+    "application/openmetrics-text;version=1.0.0,text/plain;version=0.0.4;q=0.5,*/*;q=0.1";
+
+/// A target after `relabel_configs`, ready to be scraped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct Target {
+    pub(super) url: String,
+    /// Labels added to every scraped series, without `__` labels.
+    pub(super) labels: LabelSet,
+}
+
+impl Target {
+    /// Identifies the target among all jobs, used to distribute targets
+    /// across ingesters.
+    pub(super) fn key(&self) -> String {
+        let labels = self
+            .labels
+            .iter()
+            .map(|(k, v)| format!("{k}={v:?}"))
+            .collect::<Vec<_>>()
+            .join(",");
+        format!("{}{{{labels}}}", self.url)
+    }
+}
+
+/// Builds the target of discovered labels, `None` if relabeling dropped it.
+pub(super) fn populate(
+    config: &ScrapeConfig,
+    global: &GlobalConfig,
+    mut labels: LabelSet,
+) -> Result<Option<Target>, String> {
+    let defaults = [
+        ("job", config.job_name.clone()),
+        ("__scheme__", config.scheme.clone()),
+        ("__metrics_path__", config.metrics_path.clone()),
+        (
+            "__scrape_interval__",
+            format_duration(config.interval_ms(global)),
+        ),
+        (
+            "__scrape_timeout__",
+            format_duration(config.timeout_ms(global)),
+        ),
+    ];
+    for (name, value) in defaults {
+        labels.entry(name.to_string()).or_insert(value);
+    }
+    for (name, values) in config.params.iter() {
+        if let Some(value) = values.first() {
+            labels
+                .entry(format!("__param_{name}"))
+                .or_insert(value.clone());
+        }
+    }
+
+    if !relabel(&mut labels, &config.relabel_configs) {
+        return Ok(None);
+    }
+    let Some(address) = labels.get(ADDRESS_LABEL).cloned() else {
+        return Err("target has no __address__ after relabeling".to_string());
+    };
+    if address.contains('/') {
+        return Err(format!("invalid __address__ {address:?}"));
+    }
+    labels
+        .entry("instance".to_string())
+        .or_insert(address.clone());
+
+    let mut query = url::form_urlencoded::Serializer::new(String::new());
+    for (name, values) in config.params.iter() {
+        // a relabeled parameter replaces the configured values
+        match labels.get(&format!("__param_{name}")) {
+            Some(value) if values.first() != Some(value) => {
+                query.append_pair(name, value);
+            }
+            _ => {
+                for value in values {
+                    query.append_pair(name, value);
+                }
+            }
+        }
+    }
+    for (name, value) in labels.iter() {
+        if let Some(name) = name.strip_prefix("__param_")
+            && !config.params.contains_key(name)
+        {
+            query.append_pair(name, value);
+        }
+    }
+    let query = query.finish();
+    let path = labels.get("__metrics_path__").cloned().unwrap_or_default();
+    let scheme = labels.get("__scheme__").cloned().unwrap_or_default();
+    let url = if query.is_empty() {
+        format!("{scheme}://{address}{path}")
+    } else {
+        format!("{scheme}://{address}{path}?{query}")
+    };
+
+    labels.retain(|name, _| !name.starts_with("__"));
+    Ok(Some(Target { url, labels }))
+}
+
+fn format_duration(ms: u64) -> String {
+    if ms % 1000 == 0 {
+        format!("{}s", ms / 1000)
+    } else {
+        format!("{ms}ms")
+    }
+}
+
+/// Scrapes `target` every interval until `stop` fires, then marks its series
+/// stale.
+pub(super) async fn run(
+    org_id: String,
+    config: Arc<ScrapeConfig>,
+    global: Arc<GlobalConfig>,
+    target: Target,
+    mut stop: oneshot::Receiver<()>,
+) {
+    let interval = Duration::from_millis(config.interval_ms(&global));
+    let timeout = Duration::from_millis(config.timeout_ms(&global));
+    let client = match reqwest::Client::builder().timeout(timeout).build() {
+        Ok(client) => client,
+        Err(e) => {
+            log::error!("[SCRAPE] creating client for {} failed: {e}", target.url);
+            return;
+        }
+    };
+
+    // spread the targets over the interval, at a stable offset per target
+    let mut hasher = DefaultHasher::new();
+    target.key().hash(&mut hasher);
+    let offset = hasher.finish() % interval.as_millis().max(1) as u64;
+    let start = tokio::time::Instant::now() + Duration::from_millis(offset);
+    let mut ticker = tokio::time::interval_at(start, interval);
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+    let mut scraper = Scraper::new(&config, &global, &target);
+    loop {
+        tokio::select! {
+            _ = ticker.tick() => {}
+            _ = &mut stop => break,
+        }
+        let started = std::time::Instant::now();
+        let ts = now_micros() / 1000;
+        let result = scrape(&client, &config, &target).await;
+        let duration = started.elapsed().as_secs_f64();
+        let (samples, families) = match result {
+            Ok((body, format)) => scraper.process(&body, format, ts, duration),
+            Err(e) => {
+                log::debug!("[SCRAPE] scraping {} failed: {e}", target.url);
+                (scraper.failed(ts, duration), Vec::new())
+            }
+        };
+        write(&org_id, &samples, &families).await;
+    }
+
+    let samples = scraper.stopped(now_micros() / 1000);
+    write(&org_id, &samples, &[]).await;
+}
+
+async fn scrape(
+    client: &reqwest::Client,
+    config: &ScrapeConfig,
+    target: &Target,
+) -> anyhow::Result<(String, Format)> {
+    let mut req = client
+        .get(&target.url)
+        .header(reqwest::header::ACCEPT, ACCEPT_HEADER);
+    if let Some(auth) = config.basic_auth.as_ref() {
+        let password = match (auth.password.as_ref(), auth.password_file.as_ref()) {
+            (Some(password), _) => Some(password.clone()),
+            (None, Some(file)) => Some(tokio::fs::read_to_string(file).await?.trim().to_string()),
+            (None, None) => None,
+        };
+        req = req.basic_auth(&auth.username, password);
+    }
+    if let Some(auth) = config.authorization.as_ref() {
+        let credentials = match (auth.credentials.as_ref(), auth.credentials_file.as_ref()) {
+            (Some(credentials), _) => credentials.clone(),
+            (None, Some(file)) => tokio::fs::read_to_string(file).await?.trim().to_string(),
+            (None, None) => String::new(),
+        };
+        req = req.header(
+            reqwest::header::AUTHORIZATION,
+            format!("{} {credentials}", auth.type_),
+        );
+    }
+    let mut resp = req.send().await?.error_for_status()?;
+    let format = resp
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .map(Format::from_content_type)
+        .unwrap_or(Format::Text);
+
+    let limit = config.body_size_limit_bytes();
+    let over_limit = |size: u64| limit > 0 && size > limit;
+    if resp.content_length().is_some_and(over_limit) {
+        anyhow::bail!("response body is over the body_size_limit of {limit} bytes");
+    }
+    let mut body = Vec::new();
+    while let Some(chunk) = resp.chunk().await? {
+        if over_limit((body.len() + chunk.len()) as u64) {
+            anyhow::bail!("response body is over the body_size_limit of {limit} bytes");
+        }
+        body.extend_from_slice(&chunk);
+    }
+    Ok((String::from_utf8_lossy(&body).into_owned(), format))
+}
+
+/// Turns scrape responses into samples, remembering the series of the last
+/// scrape to mark the disappeared ones stale.
+pub(super) struct Scraper<'a> {
+    config: &'a ScrapeConfig,
+    global: &'a GlobalConfig,
+    target: &'a Target,
+    previous: HashSet<LabelSet>,
+}
+
+impl<'a> Scraper<'a> {
+    pub(super) fn new(
+        config: &'a ScrapeConfig,
+        global: &'a GlobalConfig,
+        target: &'a Target,
+    ) -> Self {
+        Self {
+            config,
+            global,
+            target,
+            previous: HashSet::new(),
+        }
+    }
+
+    /// The samples of a successful scrape at `ts`, milliseconds, with the
+    /// report series and staleness markers.
+    pub(super) fn process(
+        &mut self,
+        body: &str,
+        format: Format,
+        ts: i64,
+        duration: f64,
+    ) -> (Vec<Sample>, Vec<Family>) {
+        let exposition = match parser::parse(body, format) {
+            Ok(exposition) => exposition,
+            Err(e) => {
+                log::debug!("[SCRAPE] parsing {} failed: {e}", self.target.url);
+                return (self.failed(ts, duration), Vec::new());
+            }
+        };
+        let scraped = exposition.samples.len();
+        let mut samples = Vec::with_capacity(scraped);
+        for mut sample in exposition.samples {
+            self.merge_target_labels(&mut sample.labels);
+            if !relabel(&mut sample.labels, &self.config.metric_relabel_configs) {
+                continue;
+            }
+            self.add_external_labels(&mut sample.labels);
+            if !self.config.honor_timestamps || sample.timestamp.is_none() {
+                sample.timestamp = Some(ts);
+            }
+            samples.push(sample);
+        }
+        let limit = self.config.sample_limit;
+        if limit > 0 && samples.len() > limit {
+            log::debug!(
+                "[SCRAPE] {} returned {} samples, over the limit of {limit}",
+                self.target.url,
+                samples.len()
+            );
+            return (self.failed(ts, duration), Vec::new());
+        }
+
+        let post_relabeling = samples.len();
+        let current = samples
+            .iter()
+            .map(|s| s.labels.clone())
+            .collect::<HashSet<_>>();
+        samples.extend(self.stale_markers(&current, ts));
+        self.previous = current;
+        samples.extend(self.report(ts, 1.0, duration, scraped, post_relabeling));
+        (samples, exposition.families)
+    }
+
+    /// The samples of a failed scrape: `up` is 0 and every series of the
+    /// last scrape is marked stale.
+    pub(super) fn failed(&mut self, ts: i64, duration: f64) -> Vec<Sample> {
+        let mut samples = self.stale_markers(&HashSet::new(), ts);
+        self.previous.clear();
+        samples.extend(self.report(ts, 0.0, duration, 0, 0));
+        samples
+    }
+
+    /// Staleness markers for the series of the last scrape and the report
+    /// series, once the target is gone.
+    pub(super) fn stopped(&mut self, ts: i64) -> Vec<Sample> {
+        let mut samples = self.stale_markers(&HashSet::new(), ts);
+        self.previous.clear();
+        samples.extend(self.report(ts, 0.0, 0.0, 0, 0).into_iter().map(|mut s| {
+            s.value = f64::from_bits(STALE_NAN_BITS);
+            s
+        }));
+        samples
+    }
+
+    fn stale_markers(&self, current: &HashSet<LabelSet>, ts: i64) -> Vec<Sample> {
+        self.previous
+            .iter()
+            .filter(|labels| !current.contains(*labels))
+            .map(|labels| Sample {
+                labels: labels.clone(),
+                value: f64::from_bits(STALE_NAN_BITS),
+                timestamp: Some(ts),
+            })
+            .collect()
+    }
+
+    fn report(
+        &self,
+        ts: i64,
+        up: f64,
+        duration: f64,
+        scraped: usize,
+        post_relabeling: usize,
+    ) -> Vec<Sample> {
+        [
+            ("up", up),
+            ("scrape_duration_seconds", duration),
+            ("scrape_samples_scraped", scraped as f64),
+            (
+                "scrape_samples_post_metric_relabeling",
+                post_relabeling as f64,
+            ),
+        ]
+        .into_iter()
+        .map(|(name, value)| {
+            let mut labels = self.target.labels.clone();
+            labels.insert(NAME_LABEL.to_string(), name.to_string());
+            self.add_external_labels(&mut labels);
+            Sample {
+                labels,
+                value,
+                timestamp: Some(ts),
+            }
+        })
+        .collect()
+    }
+
+    /// Adds the target labels to a scraped series. A conflicting label of the
+    /// series is kept with `honor_labels`, and renamed to `exported_<name>`
+    /// otherwise.
+    fn merge_target_labels(&self, labels: &mut LabelSet) {
+        for (name, value) in self.target.labels.iter() {
+            match labels.get(name) {
+                Some(_) if self.config.honor_labels => {}
+                Some(existing) => {
+                    let existing = existing.clone();
+                    let mut exported = format!("exported_{name}");
+                    while labels.contains_key(&exported) {
+                        exported = format!("exported_{exported}");
+                    }
+                    labels.insert(exported, existing);
+                    labels.insert(name.clone(), value.clone());
+                }
+                None => {
+                    labels.insert(name.clone(), value.clone());
+                }
+            }
+        }
+        labels.retain(|_, value| !value.is_empty());
+    }
+
+    fn add_external_labels(&self, labels: &mut LabelSet) {
+        for (name, value) in self.global.external_labels.iter() {
+            labels.entry(name.clone()).or_insert_with(|| value.clone());
+        }
+    }
+}
+
+/// The remote write request of scraped samples.
+pub(super) fn write_request(
+    samples: &[Sample],
+    families: &[Family],
+) -> prometheus_rpc::WriteRequest {
+    let timeseries = samples
+        .iter()
+        .map(|sample| prometheus_rpc::TimeSeries {
+            labels: sample
+                .labels
+                .iter()
+                .map(|(name, value)| prometheus_rpc::Label {
+                    name: name.clone(),
+                    value: value.clone(),
+                })
+                .collect(),
+            samples: vec![prometheus_rpc::Sample {
+                value: sample.value,
+                timestamp: sample.timestamp.unwrap_or_default(),
+            }],
+            ..Default::default()
+        })
+        .collect();
+    let metadata = families
+        .iter()
+        .map(|family| prometheus_rpc::MetricMetadata {
+            r#type: family.metric_type as i32,
+            metric_family_name: family.name.clone(),
+            help: family.help.clone(),
+            unit: family.unit.clone(),
+        })
+        .collect();
+    prometheus_rpc::WriteRequest {
+        timeseries,
+        metadata,
+        ..Default::default()
+    }
+}
+
+async fn write(org_id: &str, samples: &[Sample], families: &[Family]) {
+    if samples.is_empty() {
+        return;
+    }
+    let req = write_request(samples, families);
+    let body = match snap::raw::Encoder::new().compress_vec(&req.encode_to_vec()) {
+        Ok(body) => body,
+        Err(e) => {
+            log::error!("[SCRAPE] compressing write request failed: {e}");
+            return;
+        }
+    };
+    if let Err(e) = super::super::prom::remote_write(
+        org_id,
+        body.into(),
+        IngestUser::SystemJob(SystemJobType::PromScrape),
+    )
+    .await
+    {
+        log::error!("[SCRAPE] writing {} samples failed: {e}", samples.len());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use config::{
+        TIMESTAMP_COL_NAME,
+        meta::promql::{
+            relabel::{RelabelAction, RelabelConfig, RelabelRegex},
+            scrape::{Authorization, StaticConfig},
+        },
+    };
+
+    use super::*;
+
+    fn labels(pairs: &[(&str, &str)]) -> LabelSet {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    fn config() -> ScrapeConfig {
+        ScrapeConfig {
+            job_name: "node".to_string(),
+            metrics_path: "/metrics".to_string(),
+            scheme: "http".to_string(),
+            honor_timestamps: true,
+            static_configs: vec![StaticConfig {
+                targets: vec!["localhost:9100".to_string()],
+                ..Default::default()
+            }],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_populate() {
+        let mut config = config();
+        config
+            .params
+            .insert("module".to_string(), vec!["http_2xx".to_string()]);
+        config.relabel_configs = vec![RelabelConfig {
+            source_labels: vec!["__meta_env".to_string()],
+            target_label: "env".to_string(),
+            ..Default::default()
+        }];
+        let target = populate(
+            &config,
+            &GlobalConfig::default(),
+            labels(&[("__address__", "localhost:9100"), ("__meta_env", "prod")]),
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(target.url, "http://localhost:9100/metrics?module=http_2xx");
+        assert_eq!(
+            target.labels,
+            labels(&[
+                ("env", "prod"),
+                ("instance", "localhost:9100"),
+                ("job", "node")
+            ])
+        );
+
+        config.relabel_configs = vec![RelabelConfig {
+            source_labels: vec!["__address__".to_string()],
+            regex: RelabelRegex::new("localhost:.*").unwrap(),
+            action: RelabelAction::Drop,
+            ..Default::default()
+        }];
+        let dropped = populate(
+            &config,
+            &GlobalConfig::default(),
+            labels(&[("__address__", "localhost:9100")]),
+        )
+        .unwrap();
+        assert!(dropped.is_none());
+
+        config.relabel_configs = vec![RelabelConfig {
+            target_label: "__address__".to_string(),
+            replacement: "".to_string(),
+            ..Default::default()
+        }];
+        assert!(
+            populate(
+                &config,
+                &GlobalConfig::default(),
+                labels(&[("__address__", "localhost:9100")])
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_scraper() {
+        let mut config = config();
+        config.metric_relabel_configs = vec![RelabelConfig {
+            source_labels: vec![NAME_LABEL.to_string()],
+            regex: RelabelRegex::new("go_.*").unwrap(),
+            action: RelabelAction::Drop,
+            ..Default::default()
+        }];
+        let global = GlobalConfig {
+            external_labels: [("cluster".to_string(), "eu".to_string())].into(),
+            ..Default::default()
+        };
+        let target = Target {
+            url: "http://localhost:9100/metrics".to_string(),
+            labels: labels(&[("instance", "localhost:9100"), ("job", "node")]),
+        };
+        let mut scraper = Scraper::new(&config, &global, &target);
+
+        let body = "# TYPE requests counter\nrequests{path=\"/\",job=\"app\"} 3\nrequests{path=\"/a\"} 1 1000\ngo_goroutines 7\n";
+        let (samples, families) = scraper.process(body, Format::Text, 5000, 0.5);
+        assert_eq!(families.len(), 1);
+        // 2 series and 4 report series
+        assert_eq!(samples.len(), 6);
+        assert_eq!(
+            samples[0].labels,
+            labels(&[
+                (NAME_LABEL, "requests"),
+                ("cluster", "eu"),
+                ("exported_job", "app"),
+                ("instance", "localhost:9100"),
+                ("job", "node"),
+                ("path", "/"),
+            ])
+        );
+        assert_eq!(samples[0].timestamp, Some(5000));
+        assert_eq!(samples[1].timestamp, Some(1000));
+        let up = samples
+            .iter()
+            .find(|s| s.labels[NAME_LABEL] == "up")
+            .unwrap();
+        assert_eq!(up.value, 1.0);
+        let scraped = samples
+            .iter()
+            .find(|s| s.labels[NAME_LABEL] == "scrape_samples_scraped")
+            .unwrap();
+        assert_eq!(scraped.value, 3.0);
+
+        // the `/a` series disappeared
+        let (samples, _) = scraper.process(
+            "requests{path=\"/\",job=\"app\"} 4\n",
+            Format::Text,
+            6000,
+            0.5,
+        );
+        let stale = samples
+            .iter()
+            .filter(|s| s.value.to_bits() == STALE_NAN_BITS)
+            .collect::<Vec<_>>();
+        assert_eq!(stale.len(), 1);
+        assert_eq!(stale[0].labels["path"], "/a");
+        assert_eq!(stale[0].timestamp, Some(6000));
+
+        // a failed scrape marks everything stale and reports down
+        let samples = scraper.failed(7000, 0.1);
+        assert_eq!(
+            samples
+                .iter()
+                .filter(|s| s.value.to_bits() == STALE_NAN_BITS)
+                .count(),
+            1
+        );
+        let up = samples
+            .iter()
+            .find(|s| s.labels[NAME_LABEL] == "up")
+            .unwrap();
+        assert_eq!(up.value, 0.0);
+
+        // stopping marks the report series stale
+        let samples = scraper.stopped(8000);
+        assert_eq!(samples.len(), 4);
+        assert!(samples.iter().all(|s| s.value.to_bits() == STALE_NAN_BITS));
+    }
+
+    #[tokio::test]
+    async fn test_scrape_local_target() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buf = vec![0; 4096];
+            let n = stream.read(&mut buf).await.unwrap();
+            let request = String::from_utf8_lossy(&buf[..n]).to_lowercase();
+            assert!(request.starts_with("get /metrics?module=x "));
+            assert!(request.contains("accept: application/openmetrics-text"));
+            assert!(request.contains("authorization: bearer secret"));
+            let body = "# TYPE up_time gauge\nup_time 1\n# EOF\n";
+            let resp = format!(
+                "HTTP/1.1 200 OK\r\ncontent-type: application/openmetrics-text; version=1.0.0\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+                body.len()
+            );
+            stream.write_all(resp.as_bytes()).await.unwrap();
+        });
+
+        let mut config = config();
+        config
+            .params
+            .insert("module".to_string(), vec!["x".to_string()]);
+        config.authorization = Some(Authorization {
+            type_: "Bearer".to_string(),
+            credentials: Some("secret".to_string()),
+            credentials_file: None,
+        });
+        let target = populate(
+            &config,
+            &GlobalConfig::default(),
+            labels(&[("__address__", &address)]),
+        )
+        .unwrap()
+        .unwrap();
+        let (body, format) = scrape(&reqwest::Client::new(), &config, &target)
+            .await
+            .unwrap();
+        assert_eq!(format, Format::OpenMetrics);
+        let global = GlobalConfig::default();
+        let (samples, families) =
+            Scraper::new(&config, &global, &target).process(&body, format, 1000, 0.1);
+        assert_eq!(families[0].name, "up_time");
+        assert_eq!(samples[0].labels["instance"], address);
+    }
+
+    /// Serves the metrics stored from scraped samples the way remote write
+    /// stores them, one table per metric.
+    #[derive(Clone)]
+    struct StoredSamples(Arc<hashbrown::HashMap<String, arrow::array::RecordBatch>>);
+
+    impl StoredSamples {
+        fn new(requests: &[prometheus_rpc::WriteRequest]) -> Self {
+            use arrow::array::{ArrayRef, Float64Array, Int64Array, StringArray, UInt64Array};
+            use arrow_schema::{DataType, Field, Schema};
+            use config::meta::promql::{
+                HASH_LABEL, VALUE_LABEL,
+                value::{Label, Labels, signature},
+            };
+
+            let mut series: hashbrown::HashMap<String, Vec<(LabelSet, i64, Option<f64>)>> =
+                hashbrown::HashMap::new();
+            for ts in requests.iter().flat_map(|r| r.timeseries.iter()) {
+                let labels: LabelSet = ts
+                    .labels
+                    .iter()
+                    .map(|l| (l.name.clone(), l.value.clone()))
+                    .collect();
+                for sample in ts.samples.iter() {
+                    let Some(value) = crate::metrics::remote_write_value(sample.value) else {
+                        continue;
+                    };
+                    series.entry(labels[NAME_LABEL].clone()).or_default().push((
+                        labels.clone(),
+                        sample.timestamp * 1000,
+                        value.as_f64(),
+                    ));
+                }
+            }
+            let tables = series
+                .into_iter()
+                .map(|(name, rows)| {
+                    let label_names = rows
+                        .iter()
+                        .flat_map(|(labels, ..)| labels.keys().cloned())
+                        .collect::<std::collections::BTreeSet<_>>();
+                    let mut fields = vec![
+                        Field::new(TIMESTAMP_COL_NAME, DataType::Int64, false),
+                        Field::new(HASH_LABEL, DataType::UInt64, false),
+                        Field::new(VALUE_LABEL, DataType::Float64, true),
+                    ];
+                    let mut columns: Vec<ArrayRef> = vec![
+                        Arc::new(Int64Array::from_iter_values(rows.iter().map(|r| r.1))),
+                        Arc::new(UInt64Array::from_iter_values(rows.iter().map(|r| {
+                            let labels: Labels =
+                                r.0.iter()
+                                    .map(|(k, v)| Arc::new(Label::new(k.as_str(), v.as_str())))
+                                    .collect();
+                            signature(&labels)
+                        }))),
+                        Arc::new(Float64Array::from_iter(rows.iter().map(|r| r.2))),
+                    ];
+                    for label in label_names {
+                        columns.push(Arc::new(StringArray::from_iter(
+                            rows.iter().map(|r| r.0.get(&label).cloned()),
+                        )));
+                        fields.push(Field::new(label, DataType::Utf8, true));
+                    }
+                    let batch =
+                        arrow::array::RecordBatch::try_new(Arc::new(Schema::new(fields)), columns)
+                            .unwrap();
+                    (name, batch)
+                })
+                .collect();
+            Self(Arc::new(tables))
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl promql::TableProvider for StoredSamples {
+        async fn create_context(
+            &self,
+            _org_id: &str,
+            stream_name: &str,
+            _time_range: (i64, i64),
+            _matchers: promql_parser::label::Matchers,
+            _label_selector: HashSet<String>,
+            _filters: &mut [(String, Vec<String>)],
+        ) -> datafusion::error::Result<
+            Vec<(
+                datafusion::prelude::SessionContext,
+                Arc<arrow_schema::Schema>,
+                config::meta::search::ScanStats,
+                bool,
+            )>,
+        > {
+            let Some(batch) = self.0.get(stream_name) else {
+                return Ok(vec![]);
+            };
+            let ctx = datafusion::prelude::SessionContext::new();
+            ctx.register_batch(stream_name, batch.clone())?;
+            Ok(vec![(ctx, batch.schema(), Default::default(), true)])
+        }
+    }
+
+    async fn query_range(
+        stored: &StoredSamples,
+        query: &str,
+        (start, end, step): (u64, u64, u64),
+    ) -> Vec<(i64, f64)> {
+        use std::time::UNIX_EPOCH;
+
+        use config::meta::promql::value::{QueryContext, Value};
+
+        let stmt = promql_parser::parser::EvalStmt {
+            expr: promql_parser::parser::parse(query).unwrap(),
+            start: UNIX_EPOCH + Duration::from_secs(start),
+            end: UNIX_EPOCH + Duration::from_secs(end),
+            interval: Duration::from_secs(step),
+            lookback_delta: promql::DEFAULT_LOOKBACK,
+        };
+        let query_ctx = Arc::new(QueryContext {
+            trace_id: "scrape".to_string(),
+            org_id: "default".to_string(),
+            query_exemplars: false,
+            query_data: false,
+            need_wal: false,
+            use_cache: false,
+            timeout: 60,
+            search_event_type: None,
+            regions: vec![],
+            clusters: vec![],
+            is_super_cluster: false,
+            shard: None,
+        });
+        let mut ctx = promql::exec::PromqlContext::new(query_ctx, stored.clone(), vec![]);
+        let (value, ..) = ctx.exec("scrape", stmt).await.unwrap();
+        match value {
+            Value::Matrix(m) => m
+                .into_iter()
+                .flat_map(|s| s.samples.into_iter().map(|s| (s.timestamp, s.value)))
+                .collect(),
+            Value::Vector(v) => v
+                .into_iter()
+                .map(|v| (v.sample.timestamp, v.sample.value))
+                .collect(),
+            Value::None => vec![],
+            other => panic!("unexpected result {other:?}"),
+        }
+    }
+
+    /// Scrapes a local target that goes away, writes the samples as remote
+    /// write stores them and queries the series afterwards: the stale marker
+    /// of the failed scrape ends the series within the lookback window.
+    #[tokio::test]
+    async fn test_series_stale_after_target_disappears() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buf = vec![0; 4096];
+            let _ = stream.read(&mut buf).await.unwrap();
+            let body = "requests_total 1\n";
+            let resp = format!(
+                "HTTP/1.1 200 OK\r\ncontent-type: text/plain; version=0.0.4\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+                body.len()
+            );
+            stream.write_all(resp.as_bytes()).await.unwrap();
+            // the listener is dropped here, the target is gone
+        });
+
+        let config = config();
+        let global = GlobalConfig::default();
+        let target = populate(&config, &global, labels(&[("__address__", &address)]))
+            .unwrap()
+            .unwrap();
+        let client = reqwest::Client::new();
+        let mut scraper = Scraper::new(&config, &global, &target);
+        let mut requests = vec![];
+
+        let (body, format) = scrape(&client, &config, &target).await.unwrap();
+        let (samples, families) = scraper.process(&body, format, 10_000, 0.1);
+        requests.push(write_request(&samples, &families));
+        server.await.unwrap();
+
+        assert!(scrape(&client, &config, &target).await.is_err());
+        requests.push(write_request(&scraper.failed(70_000, 0.1), &[]));
+
+        let stored = StoredSamples::new(&requests);
+        // the sample at 10s is looked back to until the stale marker at 70s
+        assert_eq!(
+            query_range(&stored, "requests_total", (30, 120, 30)).await,
+            vec![(30_000_000, 1.0), (60_000_000, 1.0)]
+        );
+        assert_eq!(
+            query_range(
+                &stored,
+                "count_over_time(requests_total[2m])",
+                (120, 120, 0)
+            )
+            .await,
+            vec![(120_000_000, 1.0)]
+        );
+        assert_eq!(
+            query_range(&stored, "up", (90, 90, 0)).await,
+            vec![(90_000_000, 0.0)]
+        );
+    }
+
+    #[test]
+    fn test_sample_limit() {
+        let mut config = config();
+        config.sample_limit = 1;
+        let global = GlobalConfig::default();
+        let target = Target {
+            url: "http://localhost:9100/metrics".to_string(),
+            labels: labels(&[("job", "node")]),
+        };
+        let mut scraper = Scraper::new(&config, &global, &target);
+        let (samples, _) = scraper.process("a 1\nb 2\n", Format::Text, 1000, 0.1);
+        let up = samples
+            .iter()
+            .find(|s| s.labels[NAME_LABEL] == "up")
+            .unwrap();
+        assert_eq!(up.value, 0.0);
+        assert_eq!(samples.len(), 4);
+    }
+
+    #[test]
+    fn test_write_request() {
+        let samples = vec![Sample {
+            labels: labels(&[(NAME_LABEL, "up"), ("job", "node")]),
+            value: 1.0,
+            timestamp: Some(1000),
+        }];
+        let families = vec![Family {
+            name: "up".to_string(),
+            metric_type: prometheus_rpc::metric_metadata::MetricType::Gauge,
+            help: "target is up".to_string(),
+            unit: String::new(),
+        }];
+        let req = write_request(&samples, &families);
+        assert_eq!(req.timeseries.len(), 1);
+        assert_eq!(req.timeseries[0].labels[0].name, NAME_LABEL);
+        assert_eq!(req.timeseries[0].samples[0].timestamp, 1000);
+        assert_eq!(
+            req.metadata[0].r#type,
+            prometheus_rpc::metric_metadata::MetricType::Gauge as i32
+        );
+    }
+}
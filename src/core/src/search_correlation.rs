@@ -0,0 +1,156 @@
+// Copyright 2026 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Runs the searches behind the correlation table functions of
+//! `search::datafusion::udtf`.
+
+use arrow::array::RecordBatch;
+use async_trait::async_trait;
+use config::{
+    QUERY_WITH_NO_LIMIT,
+    datafusion::request::Request,
+    meta::{
+        promql::value::Value,
+        search::{Query, SearchEventType},
+        stream::StreamType,
+    },
+};
+use infra::errors::{Error, ErrorCodes, Result};
+use promql_service::MetricsQueryRequest;
+use search::datafusion::udtf::{CorrelationContext, CorrelationSource, set_correlation_source};
+
+struct SearchCorrelationSource;
+
+#[async_trait]
+impl CorrelationSource for SearchCorrelationSource {
+    async fn check_stream_permission(
+        &self,
+        ctx: &CorrelationContext,
+        stream_type: StreamType,
+        stream_name: &str,
+    ) -> Result<()> {
+        #[cfg(feature = "enterprise")]
+        if let Some(user_id) = ctx.user_id.as_deref()
+            && crate::authz::check_stream_permissions(
+                stream_name,
+                &ctx.org_id,
+                user_id,
+                &stream_type,
+                crate::authz::StreamPermissionResourceType::Search,
+            )
+            .await
+            .is_some()
+        {
+            return Err(Error::Message(format!(
+                "Unauthorized Access to stream: {stream_name}"
+            )));
+        }
+        #[cfg(not(feature = "enterprise"))]
+        let _ = (ctx, stream_type, stream_name);
+        Ok(())
+    }
+
+    async fn search(
+        &self,
+        ctx: &CorrelationContext,
+        stream_type: StreamType,
+        stream_name: &str,
+        sql: &str,
+        time_range: (i64, i64),
+    ) -> Result<Vec<RecordBatch>> {
+        self.check_stream_permission(ctx, stream_type, stream_name)
+            .await?;
+
+        let (start_time, end_time) = time_range;
+        let query = Query {
+            sql: sql.to_string(),
+            start_time,
+            end_time,
+            size: QUERY_WITH_NO_LIMIT,
+            ..Default::default()
+        };
+        let request = Request::new(
+            format!("{}-udtf", ctx.trace_id),
+            ctx.org_id.clone(),
+            stream_type,
+            0,
+            ctx.user_id.clone(),
+            Some(time_range),
+            Some(SearchEventType::Other.to_string()),
+            0,
+            false,
+        );
+        let (batches, ..) = search_service::cluster::http::search_inner(
+            request,
+            query.into(),
+            vec![],
+            vec![],
+            true,
+            None,
+        )
+        .await?;
+        Ok(batches)
+    }
+
+    async fn promql_range(
+        &self,
+        ctx: &CorrelationContext,
+        query: &str,
+        time_range: (i64, i64),
+        step: i64,
+    ) -> Result<Value> {
+        let ast = promql_parser::parser::parse(query)
+            .map_err(|e| Error::ErrorCode(ErrorCodes::SearchSQLNotValid(e.to_string())))?;
+        let mut visitor = promql::promql::name_visitor::MetricNameVisitor::default();
+        promql_parser::util::walk_expr(&mut visitor, &ast)
+            .map_err(|e| Error::Message(e.to_string()))?;
+        for name in visitor.into_names() {
+            self.check_stream_permission(ctx, StreamType::Metrics, &name)
+                .await?;
+        }
+
+        let req = MetricsQueryRequest {
+            query: query.to_string(),
+            start: time_range.0,
+            end: time_range.1,
+            step,
+            query_exemplars: false,
+            use_cache: None,
+            search_type: Some(SearchEventType::Other),
+            regions: vec![],
+            clusters: vec![],
+        };
+        #[cfg(not(feature = "enterprise"))]
+        let is_super_cluster = false;
+        #[cfg(feature = "enterprise")]
+        let is_super_cluster = o2_enterprise::enterprise::common::config::get_config()
+            .super_cluster
+            .enabled;
+
+        promql_service::search::search(
+            &ctx.trace_id,
+            &ctx.org_id,
+            &req,
+            ctx.user_id.as_deref().unwrap_or_default(),
+            0,
+            is_super_cluster,
+        )
+        .await
+    }
+}
+
+pub fn init() {
+    set_correlation_source(Box::new(SearchCorrelationSource));
+}
@@ -0,0 +1,103 @@
+// Copyright 2026 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::sync::Arc;
+
+use config::{meta::external_table::ExternalTable, utils::json};
+use infra::schema::external_table::{EXTERNAL_TABLE_KEY, EXTERNAL_TABLES, mk_key};
+
+use crate as db;
+
+pub async fn set(org_id: &str, table: &ExternalTable) -> Result<(), anyhow::Error> {
+    let key = mk_key(org_id, &table.name);
+    let val = json::to_vec(table)?;
+    db::put(&key, val.into(), db::NEED_WATCH, None).await?;
+    EXTERNAL_TABLES
+        .write()
+        .await
+        .insert(format!("{org_id}/{}", table.name), table.clone());
+    Ok(())
+}
+
+pub async fn delete(org_id: &str, name: &str) -> Result<(), anyhow::Error> {
+    let key = mk_key(org_id, name);
+    db::delete(&key, false, db::NEED_WATCH, None).await?;
+    EXTERNAL_TABLES
+        .write()
+        .await
+        .remove(&format!("{org_id}/{name}"));
+    Ok(())
+}
+
+pub async fn cache() -> Result<(), anyhow::Error> {
+    let tables = db::list(EXTERNAL_TABLE_KEY).await?;
+    let mut cache = EXTERNAL_TABLES.write().await;
+    for (item_key, item_value) in tables {
+        let item_key = item_key.strip_prefix(EXTERNAL_TABLE_KEY).unwrap();
+        match json::from_slice::<ExternalTable>(&item_value) {
+            Ok(table) => {
+                cache.insert(item_key.to_string(), table);
+            }
+            Err(e) => log::error!("Error deserializing external table {item_key}: {e}"),
+        }
+    }
+    log::info!("External tables Cached");
+    Ok(())
+}
+
+pub async fn watch() -> Result<(), anyhow::Error> {
+    let key = EXTERNAL_TABLE_KEY;
+    let cluster_coordinator = db::get_coordinator().await;
+    let mut events = cluster_coordinator.watch(key).await?;
+    let events = Arc::get_mut(&mut events).unwrap();
+    log::info!("Start watching external tables");
+    loop {
+        let ev = match events.recv().await {
+            Some(ev) => ev,
+            None => {
+                log::error!("watch_external_tables: event channel closed");
+                break;
+            }
+        };
+        match ev {
+            db::Event::Put(ev) => {
+                let item_key = ev.key.strip_prefix(key).unwrap();
+                let item_value: ExternalTable = match db::get(&ev.key).await {
+                    Ok(val) => match json::from_slice(&val) {
+                        Ok(val) => val,
+                        Err(e) => {
+                            log::error!("Error getting value: {e}");
+                            continue;
+                        }
+                    },
+                    Err(e) => {
+                        log::error!("Error getting value: {e}");
+                        continue;
+                    }
+                };
+                EXTERNAL_TABLES
+                    .write()
+                    .await
+                    .insert(item_key.to_owned(), item_value);
+            }
+            db::Event::Delete(ev) => {
+                let item_key = ev.key.strip_prefix(key).unwrap();
+                EXTERNAL_TABLES.write().await.remove(item_key);
+            }
+            db::Event::Empty => {}
+        }
+    }
+    Ok(())
+}
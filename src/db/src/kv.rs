@@ -29,19 +29,27 @@ fn mk_cache_key(org_id: &str, key: &str) -> String {
 }
 
 pub async fn get(org_id: &str, key: &str) -> Result<Bytes, anyhow::Error> {
+    get_opt(org_id, key)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("Key not found"))
+}
+
+/// Like [`get`], but a missing key is `None` so that callers can tell it
+/// apart from a failing store.
+pub async fn get_opt(org_id: &str, key: &str) -> Result<Option<Bytes>, anyhow::Error> {
     let cache_key = mk_cache_key(org_id, key);
     if let Some(it) = KVS.get(&cache_key) {
-        return Ok(it.value().clone());
+        return Ok(Some(it.value().clone()));
     }
 
     // Get from database table
-    let val = match infra::table::kv_store::get(org_id, key).await? {
-        Some(model) => Bytes::from(model.value),
-        None => return Err(anyhow::anyhow!("Key not found")),
+    let Some(model) = infra::table::kv_store::get(org_id, key).await? else {
+        return Ok(None);
     };
+    let val = Bytes::from(model.value);
 
     KVS.insert(cache_key, val.clone());
-    Ok(val)
+    Ok(Some(val))
 }
 
 pub async fn set(org_id: &str, key: &str, val: Bytes) -> Result<(), anyhow::Error> {
@@ -103,6 +111,13 @@ pub async fn list_keys(org_id: &str, prefix: &str) -> Result<Vec<String>, anyhow
     Ok(keys)
 }
 
+/// Lists `(org_id, key)` pairs under a key prefix across every org.
+pub async fn list_keys_all_orgs(prefix: &str) -> Result<Vec<(String, String)>, anyhow::Error> {
+    let prefix = prefix.strip_suffix('*').unwrap_or(prefix);
+    let keys = infra::table::kv_store::list_all_orgs(prefix).await?;
+    Ok(keys)
+}
+
 /// Watch function for KV changes
 ///
 /// This function watches the `/kv/` path in the coordinator for event notifications.
@@ -28,6 +28,7 @@ pub mod compact;
 pub mod dashboards;
 pub mod distinct_values;
 pub mod enrichment_table;
+pub mod external_table;
 pub mod file_list;
 pub mod folders;
 pub mod functions;
@@ -17,8 +17,9 @@ use std::sync::{Arc, LazyLock as Lazy};
 
 use config::{RwAHashMap, get_config, meta::cluster::NodeInfo};
 use proto::cluster_rpc::{
-    self, cluster_info_service_client::ClusterInfoServiceClient, metrics_client::MetricsClient,
-    node_service_client::NodeServiceClient, search_client::SearchClient,
+    self, cluster_info_service_client::ClusterInfoServiceClient, ingest_client::IngestClient,
+    metrics_client::MetricsClient, node_service_client::NodeServiceClient,
+    search_client::SearchClient,
 };
 use tonic::{
     Request, Status,
@@ -246,6 +247,63 @@ pub async fn make_grpc_metrics_client<T>(
     Ok(client)
 }
 
+#[tracing::instrument(name = "grpc:ingest:make_client", skip_all)]
+pub async fn make_grpc_ingest_client<T>(
+    trace_id: &str,
+    request: &mut Request<T>,
+    node: &Arc<dyn NodeInfo>,
+    timeout: u64,
+) -> Result<
+    IngestClient<
+        InterceptedService<Channel, impl Fn(Request<()>) -> Result<Request<()>, Status> + use<T>>,
+    >,
+    Error,
+> {
+    let cfg = get_config();
+    let timeout = if timeout > 0 {
+        timeout
+    } else {
+        cfg.limit.query_timeout
+    };
+    request.set_timeout(std::time::Duration::from_secs(timeout));
+
+    opentelemetry::global::get_text_map_propagator(|propagator| {
+        propagator.inject_context(
+            &tracing::Span::current().context(),
+            &mut MetadataMap(request.metadata_mut()),
+        )
+    });
+
+    let token: MetadataValue<_> = node
+        .get_auth_token()
+        .parse()
+        .map_err(|_| Error::Message("invalid token".to_string()))?;
+    let channel = get_cached_channel(&node.get_grpc_addr())
+        .await
+        .map_err(|err| {
+            log::error!(
+                "[trace_id {trace_id}] ingest->grpc: node: {}, connect err: {:?}",
+                node.get_grpc_addr(),
+                err
+            );
+            let err = ErrorCodes::from_json(err.message())
+                .unwrap_or(ErrorCodes::ServerInternalError(err.to_string()));
+            Error::ErrorCode(err)
+        })?;
+    let client = cluster_rpc::ingest_client::IngestClient::with_interceptor(
+        channel,
+        move |mut req: Request<()>| {
+            req.metadata_mut().insert("authorization", token.clone());
+            Ok(req)
+        },
+    );
+    Ok(client
+        .send_compressed(CompressionEncoding::Gzip)
+        .accept_compressed(CompressionEncoding::Gzip)
+        .max_decoding_message_size(cfg.grpc.max_message_size * 1024 * 1024)
+        .max_encoding_message_size(cfg.grpc.max_message_size * 1024 * 1024))
+}
+
 #[tracing::instrument(name = "grpc:node:make_client", skip_all)]
 pub async fn make_grpc_node_client<T>(
     trace_id: &str,
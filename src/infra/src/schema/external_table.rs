@@ -0,0 +1,66 @@
+// Copyright 2026 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::sync::LazyLock as Lazy;
+
+use config::{RwAHashMap, meta::external_table::ExternalTable, utils::json};
+
+use crate::{
+    db as infra_db,
+    errors::{DbError, Error, Result},
+};
+
+pub const EXTERNAL_TABLE_KEY: &str = "/external_table/";
+
+/// The external tables by `{org_id}/{name}`.
+pub static EXTERNAL_TABLES: Lazy<RwAHashMap<String, ExternalTable>> = Lazy::new(Default::default);
+
+pub fn mk_key(org_id: &str, name: &str) -> String {
+    format!("{EXTERNAL_TABLE_KEY}{org_id}/{name}")
+}
+
+pub async fn get(org_id: &str, name: &str) -> Result<Option<ExternalTable>> {
+    let key = mk_key(org_id, name);
+    let cache_key = key.strip_prefix(EXTERNAL_TABLE_KEY).unwrap();
+    if let Some(table) = EXTERNAL_TABLES.read().await.get(cache_key).cloned() {
+        return Ok(Some(table));
+    }
+
+    let db = infra_db::get_db().await;
+    let table: ExternalTable = match db.get(&key).await {
+        Ok(v) => json::from_slice(&v)?,
+        Err(Error::DbError(DbError::KeyNotExists(_))) => return Ok(None),
+        Err(e) => return Err(e),
+    };
+    EXTERNAL_TABLES
+        .write()
+        .await
+        .insert(cache_key.to_string(), table.clone());
+    Ok(Some(table))
+}
+
+pub async fn list(org_id: &str) -> Result<Vec<ExternalTable>> {
+    let prefix = format!("{EXTERNAL_TABLE_KEY}{org_id}/");
+    let cache_prefix = prefix.strip_prefix(EXTERNAL_TABLE_KEY).unwrap();
+    let mut tables = EXTERNAL_TABLES
+        .read()
+        .await
+        .iter()
+        .filter(|(key, _)| key.starts_with(cache_prefix))
+        .map(|(_, table)| table.clone())
+        .collect::<Vec<_>>();
+    tables.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(tables)
+}
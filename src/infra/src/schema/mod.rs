@@ -25,7 +25,7 @@ use config::{
     RwHashMap, RwHashSet, SQL_FULL_TEXT_SEARCH_FIELDS, SQL_SECONDARY_INDEX_SEARCH_FIELDS,
     TIMESTAMP_COL_NAME, get_config,
     ider::SnowflakeIdGenerator,
-    meta::stream::{PartitionTimeLevel, StreamSettings, StreamType},
+    meta::stream::{FtsTokenizer, PartitionTimeLevel, StreamSettings, StreamType, VectorField},
     stats::MemorySize,
     utils::{
         json,
@@ -41,6 +41,7 @@ use crate::{
     errors::{DbError, Error, Result},
 };
 
+pub mod external_table;
 pub mod history;
 
 pub static STREAM_SCHEMAS: Lazy<RwAHashMap<String, Vec<(i64, Schema)>>> =
@@ -422,6 +423,30 @@ pub fn get_stream_setting_fts_fields<T: std::borrow::Borrow<StreamSettings>>(
     }
 }
 
+pub fn get_stream_setting_fts_tokenizer<T: std::borrow::Borrow<StreamSettings>>(
+    settings: &Option<T>,
+) -> FtsTokenizer {
+    settings
+        .as_ref()
+        .map(|settings| settings.borrow().fts_tokenizer)
+        .unwrap_or_default()
+}
+
+/// Whether the full text index hits of every file from `start_time` on match
+/// exactly. Files written before the tokenizer was changed keep the index of
+/// the previous tokenizer, which may be an inexact one, and records are never
+/// written after their timestamp, so only a range starting after the change
+/// is guaranteed to read indexes of the current tokenizer.
+pub fn get_stream_setting_fts_index_exact<T: std::borrow::Borrow<StreamSettings>>(
+    settings: &Option<T>,
+    start_time: i64,
+) -> bool {
+    settings.as_ref().is_none_or(|settings| {
+        let settings = settings.borrow();
+        settings.fts_tokenizer.is_exact() && start_time > settings.fts_tokenizer_updated_at
+    })
+}
+
 pub fn get_stream_setting_index_fields<T: std::borrow::Borrow<StreamSettings>>(
     settings: &Option<T>,
 ) -> Vec<String> {
@@ -472,6 +497,40 @@ pub fn get_stream_setting_index_fields<T: std::borrow::Borrow<StreamSettings>>(
     fields
 }
 
+pub fn get_stream_setting_range_index_fields<T: std::borrow::Borrow<StreamSettings>>(
+    settings: &Option<T>,
+) -> Vec<String> {
+    match settings {
+        Some(settings) => {
+            let mut fields = settings.borrow().range_index_fields.clone();
+            fields.sort();
+            fields.dedup();
+            fields
+        }
+        None => vec![],
+    }
+}
+
+/// Sort keys in their configured order (the order is the clustering order, so
+/// unlike the other field lists it is not sorted).
+pub fn get_stream_setting_sort_keys<T: std::borrow::Borrow<StreamSettings>>(
+    settings: &Option<T>,
+) -> Vec<String> {
+    match settings {
+        Some(settings) => settings.borrow().sort_keys.clone(),
+        None => vec![],
+    }
+}
+
+pub fn get_stream_setting_vector_fields<T: std::borrow::Borrow<StreamSettings>>(
+    settings: &Option<T>,
+) -> Vec<VectorField> {
+    match settings {
+        Some(settings) => settings.borrow().vector_fields.clone(),
+        None => vec![],
+    }
+}
+
 pub fn get_stream_setting_bloom_filter_fields<T: std::borrow::Borrow<StreamSettings>>(
     settings: &Option<T>,
 ) -> Vec<String> {
@@ -1210,6 +1269,25 @@ mod tests {
         assert!(fields.contains(&"field2".to_string()));
     }
 
+    #[test]
+    fn test_get_stream_setting_fts_index_exact() {
+        assert!(get_stream_setting_fts_index_exact::<StreamSettings>(
+            &None, 0
+        ));
+        let settings = Some(StreamSettings {
+            fts_tokenizer_updated_at: 100,
+            ..Default::default()
+        });
+        // older files may carry the index of the previous tokenizer
+        assert!(!get_stream_setting_fts_index_exact(&settings, 100));
+        assert!(get_stream_setting_fts_index_exact(&settings, 101));
+        let settings = Some(StreamSettings {
+            fts_tokenizer: FtsTokenizer::CjkBigram,
+            ..Default::default()
+        });
+        assert!(!get_stream_setting_fts_index_exact(&settings, 101));
+    }
+
     #[test]
     fn test_get_stream_setting_fts_fields_with_settings() {
         // Test with custom FTS fields
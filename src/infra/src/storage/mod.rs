@@ -26,7 +26,10 @@ use config::{
     meta::stream::{FileKey, FileMeta},
     metrics,
 };
-use datafusion::parquet::{data_type::AsBytes, file::metadata::ParquetMetaData};
+use datafusion::{
+    arrow::datatypes::Schema,
+    parquet::{data_type::AsBytes, file::metadata::ParquetMetaData},
+};
 use futures::{StreamExt, TryStreamExt, stream::BoxStream};
 use hashbrown::HashMap;
 use object_store::{
@@ -34,7 +37,10 @@ use object_store::{
     ObjectMeta, ObjectStore, PutMultipartOptions, PutOptions, PutPayload, PutResult, Result,
     WriteMultipart, path::Path,
 };
-use parquet::file::metadata::{FooterTail, ParquetMetaDataReader};
+use parquet::{
+    arrow::parquet_to_arrow_schema,
+    file::metadata::{FooterTail, ParquetMetaDataReader},
+};
 
 pub mod accounts;
 mod local;
@@ -121,6 +127,14 @@ pub async fn list(account: &str, prefix: &str) -> Result<Vec<String>> {
     Ok(files)
 }
 
+/// List the objects under `prefix` with their size.
+pub async fn list_objects(account: &str, prefix: &str) -> Result<Vec<ObjectMeta>> {
+    MULTI_ACCOUNTS
+        .list(account, Some(&prefix.into()))
+        .try_collect::<Vec<_>>()
+        .await
+}
+
 /// List the immediate child "directories" (common prefixes) under `prefix`
 /// using a `/` delimiter, without recursing into them. This is cheap compared
 /// to [`list`] for large prefixes because it only returns directory names, not
@@ -372,6 +386,16 @@ pub async fn get_file_meta(account: &str, file: &str) -> Result<FileMeta, anyhow
     Ok(file_meta)
 }
 
+/// Read the arrow schema from the footer of a parquet file.
+pub async fn get_file_schema(account: &str, file: &str) -> Result<Schema, anyhow::Error> {
+    let (_, parquet_meta) = get_parquet_metadata(account, file).await?;
+    let file_metadata = parquet_meta.file_metadata();
+    Ok(parquet_to_arrow_schema(
+        file_metadata.schema_descr(),
+        file_metadata.key_value_metadata(),
+    )?)
+}
+
 async fn get_parquet_metadata(
     account: &str,
     file: &str,
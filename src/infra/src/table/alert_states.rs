@@ -133,6 +133,22 @@ pub async fn list_groups_with<C: sea_orm::ConnectionTrait>(
         .collect())
 }
 
+/// Every row, rollup and per-group, for a batch of alerts. One query, not N —
+/// what the Prometheus rules and alerts endpoints need for a whole org.
+pub async fn list_all_for(alert_ids: &[String]) -> Result<Vec<AlertState>, errors::Error> {
+    if alert_ids.is_empty() {
+        return Ok(vec![]);
+    }
+    let client = ORM_CLIENT.get_or_init(connect_to_orm).await;
+    Ok(alert_states::Entity::find()
+        .filter(alert_states::Column::AlertId.is_in(alert_ids.to_vec()))
+        .all(client)
+        .await?
+        .into_iter()
+        .map(Into::into)
+        .collect())
+}
+
 /// Persist a [`StateUpdate`] produced by
 /// `config::meta::alerts::state::apply_outcome`, plus this evaluation's
 /// contribution to the availability ledger (S-16) when it has one.
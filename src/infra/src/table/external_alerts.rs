@@ -217,6 +217,8 @@ pub async fn upsert_event(
                 .col_expr(Column::State, Expr::value("firing"))
                 .col_expr(Column::ResolvedAt, Expr::value(Option::<i64>::None))
                 .col_expr(Column::LastSeenAt, Expr::value(new_last_seen))
+                // the new episode's payload, e.g. its Alertmanager `endsAt`
+                .col_expr(Column::LastPayload, Expr::value(ev.raw.to_string()))
                 .filter(Column::OrgId.eq(org_id))
                 .filter(Column::Id.eq(existing.id.clone()))
                 .exec(client)
@@ -400,6 +402,25 @@ pub async fn get_by_ids(
     Ok(records.into_iter().map(ExternalAlertRecord::from).collect())
 }
 
+/// Firing alerts of one detected source, of `org_id` or of every org.
+pub async fn list_firing(
+    org_id: Option<&str>,
+    detected_source: &str,
+) -> Result<Vec<ExternalAlertRecord>, errors::Error> {
+    let client = ORM_CLIENT.get_or_init(connect_to_orm).await;
+    let mut query = Entity::find()
+        .filter(Column::State.eq("firing"))
+        .filter(Column::DetectedSource.eq(detected_source));
+    if let Some(org_id) = org_id {
+        query = query.filter(Column::OrgId.eq(org_id));
+    }
+    let records = query
+        .all(client)
+        .await
+        .map_err(|e| Error::DbError(DbError::SeaORMError(e.to_string())))?;
+    Ok(records.into_iter().map(ExternalAlertRecord::from).collect())
+}
+
 #[cfg(test)]
 mod tests {
     use config::meta::alerts::incidents::ExternalAlertStatus as S;
@@ -100,6 +100,22 @@ pub async fn list(org_id: &str, prefix: &str) -> Result<Vec<String>, errors::Err
     Ok(keys)
 }
 
+/// Lists `(org_id, key)` pairs under a key prefix across every org
+pub async fn list_all_orgs(prefix: &str) -> Result<Vec<(String, String)>, errors::Error> {
+    let client = ORM_CLIENT.get_or_init(connect_to_orm).await;
+
+    let keys = Entity::find()
+        .filter(Column::Key.like(format!("{prefix}%")))
+        .select_only()
+        .column(Column::OrgId)
+        .column(Column::Key)
+        .into_tuple::<(String, String)>()
+        .all(client)
+        .await?;
+
+    Ok(keys)
+}
+
 /// Clears all KV entries from the table
 pub async fn clear() -> Result<(), errors::Error> {
     let client = ORM_CLIENT.get_or_init(connect_to_orm).await;
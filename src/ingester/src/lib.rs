@@ -21,6 +21,7 @@ mod pack;
 mod partition;
 mod rwmap;
 mod stream;
+pub mod tail;
 mod wal;
 mod writer;
 
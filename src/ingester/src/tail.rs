@@ -0,0 +1,186 @@
+// Copyright 2026 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::sync::{
+    Arc, LazyLock as Lazy, Mutex, RwLock,
+    atomic::{AtomicUsize, Ordering},
+};
+
+use arrow::record_batch::RecordBatch;
+use config::get_config;
+use hashbrown::HashMap;
+use tokio::sync::broadcast;
+
+// key: org_id/stream_type/stream_name
+static SUBSCRIBERS: Lazy<RwLock<HashMap<String, Channel>>> = Lazy::new(Default::default);
+
+/// Broadcast channel of a stream. Every batch carries the number of rows
+/// published before it, so a subscriber that lags can tell how many rows it
+/// missed. The offset lock also keeps batches in offset order.
+struct Channel {
+    tx: broadcast::Sender<(u64, RecordBatch)>,
+    offset: Mutex<u64>,
+}
+
+// number of live subscriptions, used to skip the map lookup on the write path
+static ACTIVE: AtomicUsize = AtomicUsize::new(0);
+
+fn tail_key(org_id: &str, stream_type: &str, stream_name: &str) -> String {
+    format!("{org_id}/{stream_type}/{stream_name}")
+}
+
+/// A live tail subscription on the memtable write path of this node.
+///
+/// Every batch written into the memtable for the stream is delivered to the
+/// subscription after it has been written. The subscription is removed when
+/// the value is dropped.
+pub struct TailSubscription {
+    key: String,
+    rx: broadcast::Receiver<(u64, RecordBatch)>,
+    // offset of the next row this subscription expects
+    next: u64,
+}
+
+impl TailSubscription {
+    /// Receive the next written batch. Returns the number of rows written
+    /// since the previous batch that the subscriber missed because it fell
+    /// behind alongside the batch, or `None` once the subscription is closed.
+    pub async fn recv(&mut self) -> Option<(RecordBatch, u64)> {
+        loop {
+            match self.rx.recv().await {
+                Ok((offset, batch)) => {
+                    let skipped = offset.saturating_sub(self.next);
+                    self.next = offset + batch.num_rows() as u64;
+                    return Some((batch, skipped));
+                }
+                // the skipped rows are counted from the offset of the next batch
+                Err(broadcast::error::RecvError::Lagged(_)) => {}
+                Err(broadcast::error::RecvError::Closed) => return None,
+            }
+        }
+    }
+}
+
+impl Drop for TailSubscription {
+    fn drop(&mut self) {
+        ACTIVE.fetch_sub(1, Ordering::Relaxed);
+        let mut w = SUBSCRIBERS.write().unwrap();
+        // our own receiver is still alive at this point
+        if w.get(&self.key)
+            .is_some_and(|channel| channel.tx.receiver_count() <= 1)
+        {
+            w.remove(&self.key);
+        }
+    }
+}
+
+/// Subscribe to the records written into the memtable for a stream.
+pub fn subscribe(org_id: &str, stream_type: &str, stream_name: &str) -> TailSubscription {
+    let key = tail_key(org_id, stream_type, stream_name);
+    let mut w = SUBSCRIBERS.write().unwrap();
+    let (rx, next) = match w.get(&key) {
+        Some(channel) => (
+            channel.tx.subscribe(),
+            *channel.offset.lock().unwrap_or_else(|e| e.into_inner()),
+        ),
+        None => {
+            let (tx, rx) = broadcast::channel(get_config().http_streaming.live_tail_channel_size);
+            let offset = Mutex::new(0);
+            w.insert(key.clone(), Channel { tx, offset });
+            (rx, 0)
+        }
+    };
+    drop(w);
+    ACTIVE.fetch_add(1, Ordering::Relaxed);
+    TailSubscription { key, rx, next }
+}
+
+/// Number of live tail subscriptions on this node.
+pub fn active_subscriptions() -> usize {
+    ACTIVE.load(Ordering::Relaxed)
+}
+
+/// Deliver a batch written into the memtable to the stream's subscribers.
+pub(crate) fn publish(org_id: &str, stream_type: &str, stream_name: &str, batch: &RecordBatch) {
+    if ACTIVE.load(Ordering::Relaxed) == 0 || batch.num_rows() == 0 {
+        return;
+    }
+    let key = tail_key(org_id, stream_type, stream_name);
+    let r = SUBSCRIBERS.read().unwrap();
+    if let Some(channel) = r.get(&key) {
+        let mut offset = channel.offset.lock().unwrap_or_else(|e| e.into_inner());
+        // an error only means every receiver is gone, Drop cleans up the entry
+        let _ = channel.tx.send((*offset, batch.clone()));
+        *offset += batch.num_rows() as u64;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow::array::Int64Array;
+    use arrow_schema::{DataType, Field, Schema};
+
+    use super::*;
+
+    fn batch(values: Vec<i64>) -> RecordBatch {
+        let schema = Arc::new(Schema::new(vec![Field::new(
+            "_timestamp",
+            DataType::Int64,
+            false,
+        )]));
+        RecordBatch::try_new(schema, vec![Arc::new(Int64Array::from(values))]).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_tail_subscribe_receives_published_batch() {
+        let mut sub = subscribe("tail_org_a", "logs", "default");
+        publish("tail_org_a", "logs", "default", &batch(vec![1, 2, 3]));
+        let (got, skipped) = sub.recv().await.unwrap();
+        assert_eq!(got.num_rows(), 3);
+        assert_eq!(skipped, 0);
+    }
+
+    #[tokio::test]
+    async fn test_tail_publish_other_stream_is_ignored() {
+        let mut sub = subscribe("tail_org_b", "logs", "default");
+        publish("tail_org_b", "logs", "other", &batch(vec![1]));
+        publish("tail_org_b", "logs", "default", &batch(vec![1, 2]));
+        let (got, _) = sub.recv().await.unwrap();
+        assert_eq!(got.num_rows(), 2);
+    }
+
+    #[test]
+    fn test_tail_drop_removes_subscription() {
+        let key = tail_key("tail_org_c", "logs", "default");
+        let sub = subscribe("tail_org_c", "logs", "default");
+        assert!(SUBSCRIBERS.read().unwrap().contains_key(&key));
+        drop(sub);
+        assert!(!SUBSCRIBERS.read().unwrap().contains_key(&key));
+    }
+
+    #[tokio::test]
+    async fn test_tail_lagged_subscriber_counts_skipped_rows() {
+        let mut sub = subscribe("tail_org_d", "logs", "default");
+        let size = get_config().http_streaming.live_tail_channel_size;
+        // overflow the channel by two batches of 3 rows
+        for _ in 0..size + 2 {
+            publish("tail_org_d", "logs", "default", &batch(vec![1, 2, 3]));
+        }
+        let (_, skipped) = sub.recv().await.unwrap();
+        assert_eq!(skipped, 6);
+        let (_, skipped) = sub.recv().await.unwrap();
+        assert_eq!(skipped, 0);
+    }
+}
@@ -44,6 +44,7 @@ use crate::{
     immutable::{IMMUTABLES, Immutable},
     memtable::MemTable,
     rwmap::RwMap,
+    tail,
 };
 
 static WRITERS: Lazy<Vec<RwMap<WriterKey, Arc<Writer>>>> = Lazy::new(|| {
@@ -531,7 +532,18 @@ impl Writer {
             if batch_entry.data.num_rows() == 0 {
                 continue;
             }
+            let tail_batch = (tail::active_subscriptions() > 0).then(|| {
+                (
+                    entry.org_id.clone(),
+                    entry.stream.clone(),
+                    batch_entry.data.clone(),
+                )
+            });
             mem.write(entry.schema.clone().unwrap(), entry, batch_entry)?;
+            // feed live tail subscribers once the batch is visible in the memtable
+            if let Some((org_id, stream, data)) = tail_batch {
+                tail::publish(&org_id, &self.key.stream_type, &stream, &data);
+            }
             tokio::task::coop::consume_budget().await;
         }
         drop(mem);
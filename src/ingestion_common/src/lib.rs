@@ -32,6 +32,8 @@ pub enum SystemJobType {
     SelfReporting,
     InternalGrpc,
     AnomalyDetection,
+    RecordingRules,
+    PromScrape,
 }
 
 impl SystemJobType {
@@ -43,6 +45,8 @@ impl SystemJobType {
             SystemJobType::SelfReporting => "self_reporting",
             SystemJobType::InternalGrpc => "internal_grpc",
             SystemJobType::AnomalyDetection => "anomaly_detection",
+            SystemJobType::RecordingRules => "recording_rules",
+            SystemJobType::PromScrape => "prom_scrape",
         }
     }
 }
@@ -858,6 +862,11 @@ mod tests {
             SystemJobType::AnomalyDetection.as_email_local(),
             "anomaly_detection"
         );
+        assert_eq!(
+            SystemJobType::RecordingRules.as_email_local(),
+            "recording_rules"
+        );
+        assert_eq!(SystemJobType::PromScrape.as_email_local(), "prom_scrape");
     }
 
     #[test]
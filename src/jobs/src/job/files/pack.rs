@@ -36,7 +36,8 @@ use hashbrown::HashSet;
 use infra::{
     schema::{
         get_stream_setting_bloom_filter_fields, get_stream_setting_fts_fields,
-        get_stream_setting_index_fields,
+        get_stream_setting_fts_tokenizer, get_stream_setting_index_fields,
+        get_stream_setting_range_index_fields, get_stream_setting_vector_fields,
     },
     storage,
 };
@@ -353,7 +354,10 @@ async fn upload_chunk(
 
     let bloom_filter_fields = get_stream_setting_bloom_filter_fields(stream_settings);
     let full_text_search_fields = get_stream_setting_fts_fields(stream_settings);
+    let fts_tokenizer = get_stream_setting_fts_tokenizer(stream_settings);
     let index_fields = get_stream_setting_index_fields(stream_settings);
+    let range_index_fields = get_stream_setting_range_index_fields(stream_settings);
+    let vector_fields = get_stream_setting_vector_fields(stream_settings);
     let (defined_schema_fields, need_original, index_original_data, index_all_values) =
         match stream_settings {
             Some(s) => (
@@ -521,6 +525,8 @@ async fn upload_chunk(
         let need_index = full_text_search_fields
             .iter()
             .chain(index_fields.iter())
+            .chain(range_index_fields.iter())
+            .chain(vector_fields.iter().map(|f| &f.name))
             .any(|f| index_schema_fields.contains(f));
         if need_index {
             let index_size = create_tantivy_index(
@@ -528,7 +534,10 @@ async fn upload_chunk(
                 org_id,
                 &new_file_key,
                 &full_text_search_fields,
+                &fts_tokenizer,
                 &index_fields,
+                &range_index_fields,
+                &vector_fields,
                 index_schema.clone(),
                 buf,
             )
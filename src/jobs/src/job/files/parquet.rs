@@ -41,7 +41,8 @@ use hashbrown::HashSet;
 use infra::{
     schema::{
         SchemaCache, get_stream_setting_bloom_filter_fields, get_stream_setting_fts_fields,
-        get_stream_setting_index_fields,
+        get_stream_setting_fts_tokenizer, get_stream_setting_index_fields,
+        get_stream_setting_range_index_fields, get_stream_setting_vector_fields,
     },
     storage,
 };
@@ -730,7 +731,10 @@ async fn merge_files(
     let stream_settings = infra::schema::unwrap_stream_settings(&latest_schema);
     let bloom_filter_fields = get_stream_setting_bloom_filter_fields(&stream_settings);
     let full_text_search_fields = get_stream_setting_fts_fields(&stream_settings);
+    let fts_tokenizer = get_stream_setting_fts_tokenizer(&stream_settings);
     let index_fields = get_stream_setting_index_fields(&stream_settings);
+    let range_index_fields = get_stream_setting_range_index_fields(&stream_settings);
+    let vector_fields = get_stream_setting_vector_fields(&stream_settings);
     let (defined_schema_fields, need_original, index_original_data, index_all_values) =
         match stream_settings {
             Some(s) => (
@@ -872,6 +876,8 @@ async fn merge_files(
     let need_index = full_text_search_fields
         .iter()
         .chain(index_fields.iter())
+        .chain(range_index_fields.iter())
+        .chain(vector_fields.iter().map(|f| &f.name))
         .any(|f| latest_schema_fields.contains(f));
     if !need_index {
         log::debug!("skip index generation for stream: {org_id}/{stream_type}/{stream_name}");
@@ -883,7 +889,10 @@ async fn merge_files(
         &org_id,
         &new_file_key,
         &full_text_search_fields,
+        &fts_tokenizer,
         &index_fields,
+        &range_index_fields,
+        &vector_fields,
         latest_schema.clone(), // Use stream schema to include all configured fields
         buf,
     )
@@ -17,6 +17,7 @@
 //!
 //! Handles periodic tasks for incident lifecycle management:
 //! - Auto-resolution of stale incidents
+//! - Resolution of Alertmanager API alerts whose `endsAt` passed
 
 use config::spawn_pausable_job;
 use o2_enterprise::enterprise::common::config::get_config as get_o2_config;
@@ -30,6 +31,33 @@ pub async fn run() -> Result<(), anyhow::Error> {
             return Ok(());
         }
 
+        // Senders refresh firing alerts before their `endsAt`; one that goes
+        // away leaves them to expire here. Resolving is idempotent, so this
+        // only needs to run somewhere, not once: scheduler nodes it is.
+        if config::cluster::LOCAL_NODE.is_scheduler() {
+            spawn_pausable_job!(
+                "alertmanager_expiry",
+                config::get_config()
+                    .limit
+                    .alertmanager_expiry_sweep_interval,
+                {
+                    match openobserve_core::alerts::alertmanager::resolve_expired(
+                        config::utils::time::now_micros(),
+                    )
+                    .await
+                    {
+                        Ok(0) => {}
+                        Ok(n) => {
+                            log::info!("[INCIDENTS::JOB] Resolved {n} expired Alertmanager alerts")
+                        }
+                        Err(e) => {
+                            log::error!("[INCIDENTS::JOB] Alertmanager expiry sweep failed: {e}")
+                        }
+                    }
+                }
+            );
+        }
+
         if config.incidents.auto_resolve_after_minutes <= 0 {
             log::info!(
                 "[INCIDENTS::JOB] Auto-resolve is disabled (auto_resolve_after_minutes=0), only manual resolution allowed"
@@ -49,6 +49,8 @@ mod org_storage;
 #[cfg(feature = "enterprise")]
 pub(crate) mod pipeline;
 mod pipeline_error_cleanup;
+mod prom_rules;
+mod prom_scrape;
 mod promql;
 mod promql_self_consume;
 mod scheduler;
@@ -505,6 +507,7 @@ pub async fn init() -> Result<(), anyhow::Error> {
     let schema_watcher = schema::create_watcher()?;
     tokio::task::spawn(schema_watcher);
     tokio::task::spawn(db::functions::watch());
+    tokio::task::spawn(db::external_table::watch());
     tokio::task::spawn(db::compact::retention::watch());
     tokio::task::spawn(db::metrics::watch_prom_cluster_leader());
     tokio::task::spawn(db::system_settings::watch());
@@ -555,6 +558,9 @@ pub async fn init() -> Result<(), anyhow::Error> {
 
     // cache core metadata
     db::schema::cache().await.expect("stream cache failed");
+    db::external_table::cache()
+        .await
+        .expect("external table cache failed");
     openobserve_core::functions_cache::cache()
         .await
         .expect("functions cache failed");
@@ -1021,6 +1027,7 @@ pub async fn init() -> Result<(), anyhow::Error> {
     }
     tokio::task::spawn(metrics::run());
     let _ = promql::run();
+    let _ = promql::run_result_cache_invalidation();
     tokio::task::spawn(scheduler::run());
     #[cfg(feature = "enterprise")]
     tokio::task::spawn(alert_grouping::process_expired_batches());
@@ -1131,6 +1138,11 @@ pub async fn init() -> Result<(), anyhow::Error> {
     // climbs past what its window can hold. Also releases expired budget
     // residuals (S-14c).
     slo_maintenance::run();
+    // Recording rules of imported Prometheus rule groups; their alerting
+    // rules are plain alerts and run through the scheduler above.
+    prom_rules::run();
+    // Built-in scrape agent, ingesters only.
+    prom_scrape::run();
     // `_llm_scores` is authoritative for Workbench reviews. Repair the narrow
     // failure window where ingestion succeeded but QueueItem status did not.
     #[cfg(feature = "enterprise")]
@@ -1153,6 +1165,7 @@ pub async fn init() -> Result<(), anyhow::Error> {
 
     // load metrics disk cache
     tokio::task::spawn(promql_service::search::init());
+    openobserve_core::search_correlation::init();
 
     // start pipeline data retention
     #[cfg(feature = "enterprise")]
@@ -0,0 +1,155 @@
+// Copyright 2026 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Recording rule evaluation for imported Prometheus rule groups.
+//!
+//! Alerting rules need nothing here: they are ordinary alerts, run by the
+//! alert scheduler. Recording rules are evaluated by the leader among
+//! scheduler nodes, each group on its own interval, and their output is
+//! ingested as metrics of the group's org.
+//!
+//! Evaluation times are aligned to the group interval, so a leader change
+//! re-evaluates at most the current boundary, writing the same samples again
+//! rather than shifted ones.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use config::{
+    cluster::LOCAL_NODE,
+    get_config,
+    meta::promql::rules::RuleGroupHealth,
+    spawn_pausable_job,
+    utils::{json, time::now_micros, util::zero_or},
+};
+use ingestion_common::{IngestUser, SystemJobType};
+use openobserve_core::metrics::rules;
+
+use super::promql_self_consume::send_metrics;
+use crate::service;
+
+#[derive(Default)]
+struct Evaluator {
+    /// Last evaluated interval boundary per group, microseconds.
+    last_eval: HashMap<String, i64>,
+    /// Errors last written to the group's health, so it is written on change
+    /// only.
+    health: HashMap<String, BTreeMap<usize, String>>,
+}
+
+pub fn run() {
+    if !LOCAL_NODE.is_scheduler() {
+        log::debug!("[PROM_RULES] not a scheduler node, skipping");
+        return;
+    }
+    log::info!(
+        "[PROM_RULES] initialized with tick interval: {}s",
+        get_config().prom.rules_tick_interval
+    );
+
+    let mut evaluator = Evaluator::default();
+    spawn_pausable_job!("prom_rules", get_config().prom.rules_tick_interval, {
+        let is_leader = match infra::cluster::get_cached_nodes(|node| {
+            node.status == config::meta::cluster::NodeStatus::Online && node.is_scheduler()
+        })
+        .await
+        {
+            Some(mut nodes) if !nodes.is_empty() => {
+                nodes.sort_by(|a, b| a.uuid.cmp(&b.uuid));
+                nodes[0].uuid == LOCAL_NODE.uuid
+            }
+            // no cluster view, assume single node
+            _ => true,
+        };
+        if !is_leader {
+            // a later leadership starts from a clean slate
+            evaluator = Evaluator::default();
+            continue;
+        }
+
+        if let Err(e) = evaluator.tick().await {
+            log::error!("[PROM_RULES] evaluation failed: {e}");
+        }
+    });
+}
+
+impl Evaluator {
+    /// Evaluates the groups that reached a new interval boundary.
+    async fn tick(&mut self) -> Result<(), anyhow::Error> {
+        let default_interval = zero_or(get_config().prom.rules_evaluation_interval, 60);
+        let now = now_micros();
+        let mut live = HashSet::new();
+        for (org_id, stored) in rules::list_all().await? {
+            let group = &stored.group;
+            if group.rules.iter().all(|r| r.is_alerting()) {
+                continue;
+            }
+            let key = format!("{org_id}/{}/{}", stored.namespace, group.name);
+            live.insert(key.clone());
+
+            let interval = group.interval_secs(default_interval) as i64 * 1_000_000;
+            let eval_ts = now - now % interval;
+            if self.last_eval.get(&key).is_some_and(|t| *t >= eval_ts) {
+                continue;
+            }
+            self.last_eval.insert(key.clone(), eval_ts);
+
+            let (rows, errors) = rules::evaluate_recording_rules(&org_id, group, eval_ts).await;
+            if self.health.get(&key) != Some(&errors) {
+                let health = RuleGroupHealth {
+                    errors: errors.clone(),
+                };
+                match rules::set_health(&org_id, &stored.namespace, &group.name, &health).await {
+                    Ok(()) => {
+                        self.health.insert(key.clone(), errors);
+                    }
+                    Err(e) => log::error!("[PROM_RULES] saving health of {key} failed: {e}"),
+                }
+            }
+            if !rows.is_empty() {
+                ingest(&org_id, rows).await;
+            }
+        }
+        // forget deleted groups
+        self.last_eval.retain(|k, _| live.contains(k));
+        self.health.retain(|k, _| live.contains(k));
+        Ok(())
+    }
+}
+
+/// Ingests recording rule output, directly on an ingester and through one
+/// otherwise.
+async fn ingest(org_id: &str, rows: Vec<json::Value>) {
+    if LOCAL_NODE.is_ingester() {
+        let body = match json::to_vec(&rows) {
+            Ok(v) => bytes::Bytes::from(v),
+            Err(e) => {
+                log::error!("[PROM_RULES] encoding samples failed: {e}");
+                return;
+            }
+        };
+        if let Err(e) = service::metrics::json::ingest(
+            org_id,
+            None,
+            body,
+            IngestUser::SystemJob(SystemJobType::RecordingRules),
+        )
+        .await
+        {
+            log::error!("[PROM_RULES] ingesting samples of org {org_id} failed: {e}");
+        }
+    } else if let Err(e) = send_metrics(&get_config(), org_id, rows).await {
+        log::error!("[PROM_RULES] sending samples of org {org_id} failed: {e}");
+    }
+}
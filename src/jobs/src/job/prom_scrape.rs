@@ -0,0 +1,45 @@
+// Copyright 2026 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Built-in Prometheus scrape agent, run by the ingesters when
+//! `ZO_PROMETHEUS_SCRAPE_CONFIG_FILE` is set.
+
+use config::{cluster::LOCAL_NODE, get_config, spawn_pausable_job};
+use openobserve_core::metrics::scrape::Manager;
+
+pub fn run() {
+    let cfg = get_config();
+    if !LOCAL_NODE.is_ingester() || cfg.prom.scrape_config_file.is_empty() {
+        log::debug!("[PROM_SCRAPE] not an ingester or no scrape config, skipping");
+        return;
+    }
+    log::info!(
+        "[PROM_SCRAPE] initialized with config {} and sync interval: {}s",
+        cfg.prom.scrape_config_file,
+        cfg.prom.scrape_sync_interval
+    );
+
+    let mut manager = Manager::default();
+    spawn_pausable_job!(
+        "prom_scrape",
+        get_config().prom.scrape_sync_interval,
+        {
+            if let Err(e) = manager.sync().await {
+                log::error!("[PROM_SCRAPE] sync failed: {e}");
+            }
+        },
+        sleep_after
+    );
+}
@@ -51,3 +51,21 @@ pub fn run() -> Option<tokio::task::JoinHandle<()>> {
         pause_if: config::get_config().limit.metrics_leader_push_interval == 0 || !config::get_config().common.metrics_dedup_enabled
     ))
 }
+
+/// Pushes the metrics streams which received backfilled samples to the queriers, so the
+/// results they cached for them are dropped.
+pub fn run_result_cache_invalidation() -> Option<tokio::task::JoinHandle<()>> {
+    if !LOCAL_NODE.is_ingester() {
+        return None; // not an ingester, no need to init job
+    }
+
+    Some(spawn_pausable_job!(
+        "promql_result_cache_invalidation",
+        config::get_config().limit.metrics_cache_invalidation_interval,
+        {
+            openobserve_core::metrics::result_cache::invalidate_backfilled().await;
+        },
+        pause_if: config::get_config().limit.metrics_cache_invalidation_interval <= 0
+            || !config::get_config().common.result_cache_enabled
+    ))
+}
@@ -48,8 +48,12 @@ static METRICS_WHITELIST: Lazy<HashSet<String>> = Lazy::new(|| {
         .collect()
 });
 
-async fn send_metrics(cfg: &config::Config, metrics: Vec<Value>) -> Result<(), tonic::Status> {
-    let org = META_ORG_ID;
+/// Sends JSON metrics of `org` to one of the ingesters.
+pub(super) async fn send_metrics(
+    cfg: &config::Config,
+    org: &str,
+    metrics: Vec<Value>,
+) -> Result<(), tonic::Status> {
     let req = IngestionRequest {
         org_id: org.to_owned(),
         stream_name: "".to_owned(),
@@ -60,11 +64,14 @@ async fn send_metrics(cfg: &config::Config, metrics: Vec<Value>) -> Result<(), t
     };
     let org_header_key: MetadataKey<_> = cfg.grpc.org_header_key.parse().unwrap();
     let token: MetadataValue<_> = get_internal_grpc_token().parse().unwrap();
+    let org_header_value: MetadataValue<_> = org
+        .parse()
+        .map_err(|_| tonic::Status::invalid_argument(format!("invalid org id: {org}")))?;
     let (_, channel) = get_ingester_channel().await?;
     let mut client = IngestClient::with_interceptor(channel, move |mut req: Request<()>| {
         req.metadata_mut().insert("authorization", token.clone());
         req.metadata_mut()
-            .insert(org_header_key.clone(), org.parse().unwrap());
+            .insert(org_header_key.clone(), org_header_value.clone());
         Ok(req)
     });
     client = client
@@ -133,7 +140,7 @@ pub async fn run() -> Result<(), anyhow::Error> {
             }
         } else {
             let metrics = JsonEncoder::new().encode_to_json(&prom_data);
-            match send_metrics(&cfg, metrics).await {
+            match send_metrics(&cfg, org, metrics).await {
                 Ok(_) => {
                     log::debug!("successfully sent self-metrics for ingestion");
                 }
@@ -22,12 +22,14 @@ mod cache;
 pub mod layout;
 mod pruner;
 mod reader;
+pub mod stats;
 mod writer;
 
 pub use layout::{
     METRICS_INDEX_ROW_COUNT, MetricsFileLayout, metrics_index_enabled, metrics_index_stream,
 };
 pub use pruner::search;
+pub use stats::{SeriesStats, series_stats};
 pub use writer::MetricsIndexWriter;
 
 #[cfg(test)]
@@ -46,7 +48,11 @@ mod tests {
     use super::{
         METRICS_INDEX_ROW_COUNT,
         pruner::{create_physical_filter, metrics_index_labels},
-        reader::{MetricsIndexData, decode_metrics_index, evaluate_metrics_index},
+        reader::{
+            MetricsIndexData, decode_metrics_index, decode_metrics_index_series,
+            evaluate_metrics_index,
+        },
+        stats::SeriesStats,
     };
 
     #[test]
@@ -226,4 +232,50 @@ mod tests {
             vec![Range { start: 0, end: 6 }]
         );
     }
+
+    #[test]
+    fn series_stats_count_distinct_series_and_first_hour() {
+        let hour = 3_600_000_000;
+        // the `a` run is split at a batch boundary and repeats its label set
+        let first = sidecar_bytes(
+            &[
+                ("__name__", vec!["up", "up", "up"]),
+                ("instance", vec!["a", "a", "b"]),
+            ],
+            vec![2, 3, 1],
+        );
+        let second = sidecar_bytes(
+            &[
+                ("__name__", vec!["up", "up"]),
+                ("instance", vec!["b", "c"]),
+                ("job", vec!["node", "node"]),
+            ],
+            vec![1, 1],
+        );
+
+        let mut stats = SeriesStats::default();
+        for (time, bytes) in [(0, first), (hour, second)] {
+            let data = decode_metrics_index_series(bytes).unwrap();
+            let mut hour_series = std::collections::HashSet::new();
+            stats.add(time, &data, &mut hour_series);
+            stats.hourly_series.insert(time, hour_series.len());
+        }
+
+        // {b, job=node} is a different series than {b}
+        assert_eq!(stats.series_count(), 4);
+        assert_eq!(stats.new_series.get(&0), Some(&2));
+        assert_eq!(stats.new_series.get(&hour), Some(&2));
+        assert_eq!(stats.hourly_series.get(&0), Some(&2));
+        assert_eq!(stats.label_values["instance"].len(), 3);
+        assert_eq!(stats.label_values["job"].len(), 1);
+        assert!(!stats.label_values.contains_key(METRICS_INDEX_ROW_COUNT));
+        assert_eq!(
+            stats.label_pairs[&("instance".to_string(), "b".to_string())],
+            2
+        );
+        assert_eq!(
+            stats.label_pairs[&("__name__".to_string(), "up".to_string())],
+            4
+        );
+    }
 }
@@ -46,6 +46,27 @@ pub(super) async fn load_metrics_index_file(
         .map_err(|error| DataFusionError::External(Box::new(error)))?
 }
 
+pub(super) async fn load_metrics_index_series(
+    account: &str,
+    path: &str,
+) -> Result<MetricsIndexData> {
+    let bytes = infra::cache::file_data::get(account, path, None)
+        .await
+        .map_err(|error| DataFusionError::External(Box::new(error)))?;
+    tokio::task::spawn_blocking(move || decode_metrics_index_series(bytes))
+        .await
+        .map_err(|error| DataFusionError::External(Box::new(error)))?
+}
+
+/// Read every column of a sidecar: the row-range column and the complete label
+/// set of each series run.
+pub(super) fn decode_metrics_index_series(bytes: bytes::Bytes) -> Result<MetricsIndexData> {
+    let reader = ArrowFileReaderBuilder::new().build(Cursor::new(bytes))?;
+    let schema = reader.schema();
+    let batches = reader.collect::<std::result::Result<Vec<_>, _>>()?;
+    Ok(MetricsIndexData { schema, batches })
+}
+
 /// Read the row-range columns plus the requested labels from a sidecar.
 ///
 /// The projection is resolved by name against the sidecar's own schema: the
@@ -0,0 +1,194 @@
+// Copyright 2026 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//! Series statistics read from the `.midx` metrics indexes.
+//!
+//! A sidecar holds one row per series run with the complete label set of the
+//! series, so the distinct series of a stream, their labels and the hour they
+//! first appear in can be counted without reading the data files. Only indexed
+//! files own a sidecar; other layouts are counted and skipped.
+
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    hash::Hasher,
+};
+
+use arrow::{
+    array::{Array, AsArray, RecordBatch},
+    datatypes::DataType,
+};
+use config::{
+    meta::{promql::is_metrics_hash_excluded_label, stream::FileKey},
+    utils::hash::gxhash,
+};
+use futures::{StreamExt, stream};
+
+use crate::{
+    layout::{METRICS_INDEX_ROW_COUNT, MetricsFileLayout},
+    reader::{MetricsIndexData, load_metrics_index_series},
+};
+
+const HOUR_MICROS: i64 = 3_600_000_000;
+
+/// Series statistics of one metrics stream over a set of files.
+#[derive(Debug, Default)]
+pub struct SeriesStats {
+    series: HashSet<u64>,
+    /// Distinct values of each label name.
+    pub label_values: HashMap<String, HashSet<String>>,
+    /// Number of series carrying each label name and value pair.
+    pub label_pairs: HashMap<(String, String), usize>,
+    /// Series first seen in each hour, keyed by the hour start in
+    /// microseconds. The first hour of the range counts all of its series.
+    pub new_series: BTreeMap<i64, usize>,
+    /// Distinct series present in each hour, keyed like `new_series`.
+    pub hourly_series: BTreeMap<i64, usize>,
+    /// Files whose sidecar was read.
+    pub indexed_files: usize,
+    /// Files without a sidecar, or whose sidecar could not be read. Their
+    /// series are not counted.
+    pub unindexed_files: usize,
+}
+
+impl SeriesStats {
+    /// Distinct series over all files.
+    pub fn series_count(&self) -> usize {
+        self.series.len()
+    }
+
+    /// Fold the series of one sidecar into the statistics. Sidecars must be
+    /// added in hour order for `new_series` to be exact.
+    pub(super) fn add(
+        &mut self,
+        hour: i64,
+        data: &MetricsIndexData,
+        hour_series: &mut HashSet<u64>,
+    ) {
+        for batch in data.batches.iter() {
+            let columns = label_columns(batch);
+            let mut labels = Vec::with_capacity(columns.len());
+            for row in 0..batch.num_rows() {
+                labels.clear();
+                let mut hasher = gxhash::new_hasher();
+                for (name, column) in columns.iter() {
+                    if column.is_null(row) {
+                        continue;
+                    }
+                    let value = column.value(row);
+                    hasher.write(name.as_bytes());
+                    hasher.write_u8(0xff);
+                    hasher.write(value.as_bytes());
+                    hasher.write_u8(0xfe);
+                    labels.push((name.as_str(), value));
+                }
+                let series = hasher.finish();
+                hour_series.insert(series);
+                // runs split at batch boundaries repeat the same label set
+                if !self.series.insert(series) {
+                    continue;
+                }
+                *self.new_series.entry(hour).or_default() += 1;
+                for (name, value) in labels.iter() {
+                    self.label_values
+                        .entry(name.to_string())
+                        .or_default()
+                        .insert(value.to_string());
+                    *self
+                        .label_pairs
+                        .entry((name.to_string(), value.to_string()))
+                        .or_default() += 1;
+                }
+            }
+        }
+    }
+}
+
+/// The label columns of a sidecar batch as strings, skipping the row-range
+/// column and labels that are not part of the series identity.
+fn label_columns(batch: &RecordBatch) -> Vec<(String, arrow::array::StringArray)> {
+    let schema = batch.schema();
+    schema
+        .fields()
+        .iter()
+        .zip(batch.columns())
+        .filter(|(field, _)| {
+            field.name() != METRICS_INDEX_ROW_COUNT && !is_metrics_hash_excluded_label(field.name())
+        })
+        .filter_map(|(field, column)| {
+            let column = arrow::compute::cast(column, &DataType::Utf8).ok()?;
+            Some((field.name().to_string(), column.as_string::<i32>().clone()))
+        })
+        .collect()
+}
+
+/// Collect the series statistics of one metrics stream from the sidecars of
+/// its indexed files.
+///
+/// Sidecars are loaded `concurrency` at a time but folded in hour order, so a
+/// series is attributed to the first hour it appears in. A sidecar that cannot
+/// be read is logged and counted as unindexed instead of failing the request.
+pub async fn series_stats(trace_id: &str, files: &[FileKey], concurrency: usize) -> SeriesStats {
+    let mut stats = SeriesStats::default();
+    let mut index_files = Vec::with_capacity(files.len());
+    for file in files.iter() {
+        let sidecar_path = (MetricsFileLayout::of(&file.key) == MetricsFileLayout::Indexed)
+            .then(|| MetricsFileLayout::metrics_index_path(&file.key))
+            .flatten();
+        match sidecar_path {
+            Some(path) => index_files.push((
+                file.meta.min_ts - file.meta.min_ts.rem_euclid(HOUR_MICROS),
+                file.account.clone(),
+                path,
+            )),
+            None => stats.unindexed_files += 1,
+        }
+    }
+    index_files.sort_by(|a, b| a.0.cmp(&b.0).then_with(|| a.2.cmp(&b.2)));
+
+    let mut sidecars = stream::iter(index_files.into_iter().map(
+        |(hour, account, path)| async move {
+            let result = load_metrics_index_series(&account, &path).await;
+            (hour, path, result)
+        },
+    ))
+    .buffered(concurrency.max(1));
+
+    let mut current_hour = None;
+    let mut hour_series = HashSet::new();
+    while let Some((hour, path, result)) = sidecars.next().await {
+        if current_hour != Some(hour) {
+            if let Some(previous) = current_hour {
+                stats.hourly_series.insert(previous, hour_series.len());
+            }
+            current_hour = Some(hour);
+            hour_series.clear();
+        }
+        match result {
+            Ok(data) => {
+                stats.indexed_files += 1;
+                stats.add(hour, &data, &mut hour_series);
+            }
+            Err(error) => {
+                stats.unindexed_files += 1;
+                log::warn!(
+                    "[trace_id {trace_id}] metrics-index: failed to read {path} for series stats, skipping the file: {error}"
+                );
+            }
+        }
+    }
+    if let Some(hour) = current_hour {
+        stats.hourly_series.insert(hour, hour_series.len());
+    }
+    stats
+}
@@ -157,18 +157,21 @@ mod tests {
                 samples: vec![Sample::new(timestamp, 10.0)],
                 exemplars: None,
                 time_window: None,
+                histograms: vec![],
             },
             RangeValue {
                 labels: labels1.clone(),
                 samples: vec![Sample::new(timestamp, 20.0)],
                 exemplars: None,
                 time_window: None,
+                histograms: vec![],
             },
             RangeValue {
                 labels: labels2.clone(),
                 samples: vec![Sample::new(timestamp, 30.0)],
                 exemplars: None,
                 time_window: None,
+                histograms: vec![],
             },
         ]);
 
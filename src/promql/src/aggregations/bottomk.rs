@@ -113,18 +113,21 @@ mod tests {
                 samples: vec![Sample::new(timestamp, 15.3)], // Highest value
                 exemplars: None,
                 time_window: None,
+                histograms: vec![],
             },
             RangeValue {
                 labels: labels2.clone(),
                 samples: vec![Sample::new(timestamp, 8.2)], // Lowest value
                 exemplars: None,
                 time_window: None,
+                histograms: vec![],
             },
             RangeValue {
                 labels: labels3.clone(),
                 samples: vec![Sample::new(timestamp, 12.1)], // Middle value
                 exemplars: None,
                 time_window: None,
+                histograms: vec![],
             },
         ]);
 
@@ -182,6 +185,7 @@ mod tests {
             samples: vec![Sample::new(timestamp, 10.5)],
             exemplars: None,
             time_window: None,
+            histograms: vec![],
         }]);
 
         let eval_ctx = EvalContext::new(timestamp, timestamp + 1, 1, "test".to_string());
@@ -138,18 +138,21 @@ mod tests {
                 samples: vec![Sample::new(timestamp, 10.5)],
                 exemplars: None,
                 time_window: None,
+                histograms: vec![],
             },
             RangeValue {
                 labels: labels1.clone(),
                 samples: vec![Sample::new(timestamp, 15.3)],
                 exemplars: None,
                 time_window: None,
+                histograms: vec![],
             },
             RangeValue {
                 labels: labels2.clone(),
                 samples: vec![Sample::new(timestamp, 8.2)],
                 exemplars: None,
                 time_window: None,
+                histograms: vec![],
             },
         ]);
 
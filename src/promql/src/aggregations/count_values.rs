@@ -219,6 +219,7 @@ pub fn count_values(
             samples,
             exemplars: None,
             time_window: None,
+            histograms: vec![],
         })
         .collect();
 
@@ -276,18 +277,21 @@ mod tests {
                 samples: vec![Sample::new(timestamp, 10.0)],
                 exemplars: None,
                 time_window: None,
+                histograms: vec![],
             },
             RangeValue {
                 labels: labels2.clone(),
                 samples: vec![Sample::new(timestamp, 10.0)], // Same value
                 exemplars: None,
                 time_window: None,
+                histograms: vec![],
             },
             RangeValue {
                 labels: labels1.clone(),
                 samples: vec![Sample::new(timestamp, 20.0)],
                 exemplars: None,
                 time_window: None,
+                histograms: vec![],
             },
         ]);
 
@@ -332,6 +336,7 @@ mod tests {
             samples: vec![Sample::new(timestamp, 10.5)],
             exemplars: None,
             time_window: None,
+            histograms: vec![],
         }]);
 
         let eval_ctx = EvalContext::new(timestamp, timestamp + 1, 1, "test".to_string());
@@ -389,6 +394,7 @@ mod tests {
                 ],
                 exemplars: None,
                 time_window: None,
+                histograms: vec![],
             },
             RangeValue {
                 labels: labels2.clone(),
@@ -399,6 +405,7 @@ mod tests {
                 ],
                 exemplars: None,
                 time_window: None,
+                histograms: vec![],
             },
         ]);
 
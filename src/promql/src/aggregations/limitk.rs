@@ -0,0 +1,217 @@
+// Copyright 2026 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+use config::meta::promql::{
+    HASH_LABEL,
+    value::{EvalContext, RangeValue, Value, signature_without_labels},
+};
+use datafusion::error::{DataFusionError, Result};
+use hashbrown::{HashMap, HashSet};
+use promql_parser::parser::LabelModifier;
+use rayon::prelude::*;
+
+/// https://prometheus.io/docs/prometheus/latest/querying/operators/#aggregation-operators
+///
+/// Keeps up to k series of each group at every timestamp. Unlike topk the choice
+/// does not depend on the sample values: series are taken in label order, so the
+/// same series are returned for as long as they have samples.
+pub fn limitk(
+    k: usize,
+    modifier: &Option<LabelModifier>,
+    data: Value,
+    eval_ctx: &EvalContext,
+) -> Result<Value> {
+    let matrix = match data {
+        Value::Matrix(m) => m,
+        Value::None => return Ok(Value::None),
+        _ => {
+            return Err(DataFusionError::Plan(
+                "[limitk] function only accept matrix values".to_string(),
+            ));
+        }
+    };
+
+    if matrix.is_empty() || k == 0 {
+        return Ok(Value::None);
+    }
+
+    let eval_timestamps: HashSet<i64> = eval_ctx.timestamps().iter().cloned().collect();
+    let grouped_series = super::group_series_by_labels(&matrix, modifier);
+
+    let result: Vec<RangeValue> = grouped_series
+        .into_par_iter()
+        .flat_map(|(_, mut series_indices)| {
+            series_indices.sort_by(|&a, &b| {
+                matrix[a]
+                    .labels
+                    .partial_cmp(&matrix[b].labels)
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            });
+            select_limitk_series(&matrix, &series_indices, k, &eval_timestamps)
+        })
+        .collect();
+
+    if result.is_empty() {
+        Ok(Value::None)
+    } else {
+        Ok(Value::Matrix(result))
+    }
+}
+
+/// Keeps the samples of the first k series, in the order of `series_indices`, that
+/// have a sample at each timestamp.
+fn select_limitk_series(
+    matrix: &[RangeValue],
+    series_indices: &[usize],
+    k: usize,
+    eval_timestamps: &HashSet<i64>,
+) -> Vec<RangeValue> {
+    let mut taken: HashMap<i64, usize> = HashMap::with_capacity(eval_timestamps.len());
+    let mut result = Vec::new();
+    for &idx in series_indices {
+        let series = &matrix[idx];
+        let mut take = |ts: i64| {
+            if !eval_timestamps.contains(&ts) {
+                return false;
+            }
+            let count = taken.entry(ts).or_default();
+            if *count < k {
+                *count += 1;
+                true
+            } else {
+                false
+            }
+        };
+        let samples: Vec<_> = series
+            .samples
+            .iter()
+            .filter(|s| take(s.timestamp))
+            .copied()
+            .collect();
+        let histograms: Vec<_> = series
+            .histograms
+            .iter()
+            .filter(|h| take(h.timestamp))
+            .cloned()
+            .collect();
+        if !samples.is_empty() || !histograms.is_empty() {
+            result.push(RangeValue {
+                labels: series.labels.clone(),
+                samples,
+                exemplars: series.exemplars.clone(),
+                time_window: series.time_window.clone(),
+                histograms,
+            });
+        }
+    }
+    result
+}
+
+/// https://prometheus.io/docs/prometheus/latest/querying/operators/#aggregation-operators
+///
+/// Keeps a deterministic, pseudo-random share of the series. A series is kept when
+/// its label hash, scaled to [0, 1), is below `ratio`; a negative `ratio` keeps
+/// the complement, so `limit_ratio(r, v)` and `limit_ratio(-(1.0 - r), v)` split
+/// the series without overlap.
+pub fn limit_ratio(ratio: f64, data: Value) -> Result<Value> {
+    let matrix = match data {
+        Value::Matrix(m) => m,
+        Value::None => return Ok(Value::None),
+        _ => {
+            return Err(DataFusionError::Plan(
+                "[limit_ratio] function only accept matrix values".to_string(),
+            ));
+        }
+    };
+
+    let ratio = ratio.clamp(-1.0, 1.0);
+    let result: Vec<RangeValue> = matrix
+        .into_iter()
+        .filter(|series| {
+            let offset =
+                signature_without_labels(&series.labels, &[HASH_LABEL]) as f64 / u64::MAX as f64;
+            if ratio >= 0.0 {
+                offset < ratio
+            } else {
+                offset >= 1.0 + ratio
+            }
+        })
+        .collect();
+
+    if result.is_empty() {
+        Ok(Value::None)
+    } else {
+        Ok(Value::Matrix(result))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use config::meta::promql::value::{Label, Sample};
+
+    use super::*;
+
+    fn matrix(timestamp: i64, count: usize) -> Value {
+        Value::Matrix(
+            (0..count)
+                .map(|i| RangeValue {
+                    labels: vec![Arc::new(Label::new(
+                        "instance".to_string(),
+                        format!("server{i}"),
+                    ))],
+                    samples: vec![Sample::new(timestamp, i as f64)],
+                    exemplars: None,
+                    time_window: None,
+                    histograms: vec![],
+                })
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_limitk() {
+        let timestamp = 1640995200;
+        let eval_ctx = EvalContext::new(timestamp, timestamp + 1, 1, "test".to_string());
+
+        let Value::Matrix(result) = limitk(2, &None, matrix(timestamp, 5), &eval_ctx).unwrap()
+        else {
+            panic!("Expected Matrix result");
+        };
+        let mut instances: Vec<_> = result.iter().map(|s| s.labels[0].value.clone()).collect();
+        instances.sort();
+        assert_eq!(instances, ["server0", "server1"]);
+
+        assert!(matches!(
+            limitk(0, &None, matrix(timestamp, 5), &eval_ctx).unwrap(),
+            Value::None
+        ));
+    }
+
+    #[test]
+    fn test_limit_ratio() {
+        let count = |value: Value| match value {
+            Value::Matrix(m) => m.len(),
+            _ => 0,
+        };
+        assert_eq!(count(limit_ratio(1.0, matrix(0, 100)).unwrap()), 100);
+        assert_eq!(count(limit_ratio(0.0, matrix(0, 100)).unwrap()), 0);
+
+        // a ratio and its negative complement partition the series
+        let kept = count(limit_ratio(0.3, matrix(0, 100)).unwrap());
+        let rest = count(limit_ratio(-0.7, matrix(0, 100)).unwrap());
+        assert_eq!(kept + rest, 100);
+    }
+}
@@ -18,7 +18,7 @@ use std::{collections::HashMap, hash::Hasher, sync::Arc};
 use config::{
     meta::promql::{
         NAME_LABEL,
-        value::{EvalContext, Label, Labels, RangeValue, Sample, Value},
+        value::{EvalContext, HistogramSample, Label, Labels, RangeValue, Sample, Value},
     },
     utils::hash::gxhash,
 };
@@ -31,6 +31,7 @@ mod bottomk;
 mod count;
 mod count_values;
 mod group;
+mod limitk;
 mod max;
 mod min;
 mod quantile;
@@ -44,6 +45,7 @@ pub(crate) use bottomk::bottomk;
 pub(crate) use count::count;
 pub(crate) use count_values::count_values;
 pub(crate) use group::group;
+pub(crate) use limitk::{limit_ratio, limitk};
 pub(crate) use max::max;
 pub(crate) use min::min;
 pub(crate) use quantile::quantile;
@@ -129,6 +131,16 @@ pub trait Accumulate: Send + Sync {
     /// * `sample` - The sample to accumulate, containing a timestamp and value
     fn accumulate(&mut self, sample: &Sample);
 
+    /// Adds a native histogram sample to this accumulator. Aggregations that are
+    /// undefined for native histograms keep the default, which ignores them.
+    fn accumulate_histogram(&mut self, _sample: &HistogramSample) {}
+
+    /// Takes the aggregated native histograms, one per timestamp. Called right
+    /// before [`Self::evaluate`].
+    fn take_histograms(&mut self) -> Vec<HistogramSample> {
+        vec![]
+    }
+
     /// Folds another accumulator of the same type into this one, as if all of
     /// its samples had been accumulated here. Lets a large group be
     /// aggregated in parallel chunks whose partials are merged at the end.
@@ -300,6 +312,11 @@ where
                             acc.accumulate(sample);
                         }
                     }
+                    for sample in &matrix[series_idx].histograms {
+                        if eval_timestamps.contains(&sample.timestamp) {
+                            acc.accumulate_histogram(sample);
+                        }
+                    }
                 }
                 acc
             };
@@ -307,7 +324,7 @@ where
             // A huge group (e.g. `sum(...)` without a modifier puts every
             // series in one group) would otherwise aggregate on one thread;
             // fold it in parallel chunks and merge the partials.
-            let acc = if func.mergeable() && series_indices.len() >= 2 * AGG_PARALLEL_CHUNK {
+            let mut acc = if func.mergeable() && series_indices.len() >= 2 * AGG_PARALLEL_CHUNK {
                 series_indices
                     .par_chunks(AGG_PARALLEL_CHUNK)
                     .map(accumulate_chunk)
@@ -323,16 +340,19 @@ where
             };
 
             // Evaluate the aggregated results
+            let mut histograms = acc.take_histograms();
             let mut samples = acc.evaluate();
 
             // Sort by timestamp to maintain order
             samples.sort_by_key(|s| s.timestamp);
+            histograms.sort_by_key(|h| h.timestamp);
 
             RangeValue {
                 labels,
                 samples,
                 exemplars: None,
                 time_window: None,
+                histograms,
             }
         })
         .collect();
@@ -437,6 +457,7 @@ mod tests {
                 samples: vec![Sample::new(ts, v)],
                 exemplars: None,
                 time_window: None,
+                histograms: vec![],
             })
             .collect();
         let eval_ctx = EvalContext::new(ts, ts + 1, 1, "test".to_string());
@@ -572,6 +593,7 @@ mod tests {
                     range: Duration::from_secs(1),
                     offset: Duration::ZERO,
                 }),
+                histograms: vec![],
             },
             RangeValue {
                 labels: labels2,
@@ -581,6 +603,7 @@ mod tests {
                     range: Duration::from_secs(1),
                     offset: Duration::ZERO,
                 }),
+                histograms: vec![],
             },
         ];
 
@@ -615,6 +638,7 @@ mod tests {
                     range: Duration::from_secs(1),
                     offset: Duration::ZERO,
                 }),
+                histograms: vec![],
             },
             RangeValue {
                 labels: labels_b,
@@ -624,6 +648,7 @@ mod tests {
                     range: Duration::from_secs(1),
                     offset: Duration::ZERO,
                 }),
+                histograms: vec![],
             },
         ];
 
@@ -47,6 +47,7 @@ pub fn quantile(qtile: f64, data: Value, eval_ctx: &EvalContext) -> Result<Value
             samples,
             exemplars: None,
             time_window: None,
+            histograms: vec![],
         };
         return Ok(Value::Matrix(vec![range_value]));
     }
@@ -13,7 +13,9 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
-use config::meta::promql::value::{EvalContext, Sample, Value};
+use std::sync::Arc;
+
+use config::meta::promql::value::{EvalContext, HistogramSample, NativeHistogram, Sample, Value};
 use datafusion::error::Result;
 use hashbrown::HashMap;
 use promql_parser::parser::LabelModifier;
@@ -54,12 +56,31 @@ impl AggFunc for Sum {
 
 pub struct SumAccumulate {
     sum: HashMap<i64, (f64, f64)>,
+    /// `None` once histograms with incompatible bucket layouts met at a timestamp,
+    /// which then has no result.
+    histograms: HashMap<i64, Option<NativeHistogram>>,
 }
 
 impl SumAccumulate {
     fn new() -> Self {
         SumAccumulate {
             sum: HashMap::new(),
+            histograms: HashMap::new(),
+        }
+    }
+
+    fn add_histogram(&mut self, timestamp: i64, histogram: Option<&NativeHistogram>) {
+        match self.histograms.entry(timestamp) {
+            hashbrown::hash_map::Entry::Vacant(e) => {
+                e.insert(histogram.cloned());
+            }
+            hashbrown::hash_map::Entry::Occupied(mut e) => {
+                let sum = e.get_mut();
+                *sum = match (sum.as_ref(), histogram) {
+                    (Some(sum), Some(h)) => sum.add(h),
+                    _ => None,
+                };
+            }
         }
     }
 }
@@ -70,6 +91,17 @@ impl Accumulate for SumAccumulate {
         (*sum, *c) = kahan_sum_increment(sample.value, *sum, *c);
     }
 
+    fn accumulate_histogram(&mut self, sample: &HistogramSample) {
+        self.add_histogram(sample.timestamp, Some(&sample.histogram));
+    }
+
+    fn take_histograms(&mut self) -> Vec<HistogramSample> {
+        std::mem::take(&mut self.histograms)
+            .into_iter()
+            .filter_map(|(timestamp, h)| Some(HistogramSample::new(timestamp, Arc::new(h?))))
+            .collect()
+    }
+
     fn merge(&mut self, other: Box<dyn Accumulate>) {
         let other = other.into_any().downcast::<Self>().expect("same type");
         for (timestamp, (other_sum, other_c)) in other.sum {
@@ -80,6 +112,9 @@ impl Accumulate for SumAccumulate {
             (*sum, *c) = kahan_sum_increment(other_sum, *sum, *c);
             (*sum, *c) = kahan_sum_increment(other_c, *sum, *c);
         }
+        for (timestamp, histogram) in other.histograms {
+            self.add_histogram(timestamp, histogram.as_ref());
+        }
     }
 
     fn into_any(self: Box<Self>) -> Box<dyn std::any::Any> {
@@ -96,13 +131,59 @@ impl Accumulate for SumAccumulate {
 
 #[cfg(test)]
 mod tests {
-    use std::sync::Arc;
-
     use config::meta::promql::value::{Label, RangeValue, Sample, Value};
     use promql_parser::parser::LabelModifier;
 
     use super::*;
 
+    #[test]
+    fn test_sum_native_histograms() {
+        let ts = 1000;
+        let series = |count: f64, schema: i32| RangeValue {
+            labels: vec![],
+            samples: vec![],
+            exemplars: None,
+            time_window: None,
+            histograms: vec![HistogramSample::new(
+                ts,
+                Arc::new(NativeHistogram {
+                    schema,
+                    count,
+                    sum: count,
+                    positive: vec![(1, count)],
+                    ..Default::default()
+                }),
+            )],
+        };
+        let eval_ctx = EvalContext::new(ts, ts, 0, "test".to_string());
+
+        let Value::Matrix(result) = sum(
+            &None,
+            Value::Matrix(vec![series(1.0, 1), series(2.0, 0)]),
+            &eval_ctx,
+        )
+        .unwrap() else {
+            panic!("expected matrix");
+        };
+        assert_eq!(result.len(), 1);
+        assert!(result[0].samples.is_empty());
+        let h = &result[0].histograms[0].histogram;
+        // reduced to the coarser schema: index 1 at schema 1 falls into index 1 at schema 0
+        assert_eq!(h.schema, 0);
+        assert_eq!(h.count, 3.0);
+        assert_eq!(h.positive, vec![(1, 3.0)]);
+
+        // custom buckets cannot be summed with exponential ones
+        let mut nhcb = series(1.0, -53);
+        Arc::make_mut(&mut nhcb.histograms[0].histogram).custom_values = vec![1.0, 2.0];
+        let Value::Matrix(result) =
+            sum(&None, Value::Matrix(vec![series(1.0, 0), nhcb]), &eval_ctx).unwrap()
+        else {
+            panic!("expected matrix");
+        };
+        assert!(result[0].histograms.is_empty());
+    }
+
     #[test]
     fn test_sum_value_none_input() {
         let ts = 1640995200;
@@ -160,6 +241,7 @@ mod tests {
                 ],
                 exemplars: None,
                 time_window: None,
+                histograms: vec![],
             },
             RangeValue {
                 labels: labels2.clone(),
@@ -170,6 +252,7 @@ mod tests {
                 ],
                 exemplars: None,
                 time_window: None,
+                histograms: vec![],
             },
             RangeValue {
                 labels: labels3.clone(),
@@ -180,6 +263,7 @@ mod tests {
                 ],
                 exemplars: None,
                 time_window: None,
+                histograms: vec![],
             },
         ];
 
@@ -166,6 +166,7 @@ pub(super) fn select_topk_series(
                 samples: filtered_samples,
                 exemplars: series.exemplars.clone(),
                 time_window: series.time_window.clone(),
+                histograms: vec![],
             });
         }
     }
@@ -207,18 +208,21 @@ mod tests {
                 samples: vec![Sample::new(timestamp, 10.5)],
                 exemplars: None,
                 time_window: None,
+                histograms: vec![],
             },
             RangeValue {
                 labels: labels2.clone(),
                 samples: vec![Sample::new(timestamp, 15.3)], // Highest value
                 exemplars: None,
                 time_window: None,
+                histograms: vec![],
             },
             RangeValue {
                 labels: labels3.clone(),
                 samples: vec![Sample::new(timestamp, 8.2)], // Lowest value
                 exemplars: None,
                 time_window: None,
+                histograms: vec![],
             },
         ]);
 
@@ -276,6 +280,7 @@ mod tests {
             samples: vec![Sample::new(timestamp, 10.5)],
             exemplars: None,
             time_window: None,
+            histograms: vec![],
         }]);
 
         let eval_ctx = EvalContext::new(timestamp, timestamp + 1, 1, "test".to_string());
@@ -315,6 +320,7 @@ mod tests {
             samples: vec![Sample::new(timestamp, 1.0)],
             exemplars: None,
             time_window: None,
+            histograms: vec![],
         }];
         let eval_timestamps = HashSet::from([timestamp]);
         let result = select_topk_series(&matrix, &[], 2, &eval_timestamps, false);
@@ -331,12 +337,14 @@ mod tests {
                 samples: vec![Sample::new(timestamp, 5.0)],
                 exemplars: None,
                 time_window: None,
+                histograms: vec![],
             },
             RangeValue {
                 labels: vec![Arc::new(Label::new("i", "2"))],
                 samples: vec![Sample::new(timestamp, 3.0)],
                 exemplars: None,
                 time_window: None,
+                histograms: vec![],
             },
         ];
         let eval_timestamps = HashSet::from([timestamp]);
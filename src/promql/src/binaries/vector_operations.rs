@@ -37,6 +37,7 @@ pub static DROP_METRIC_BIN_OP: Lazy<HashSet<u8>> = Lazy::new(|| {
         token::T_MUL,
         token::T_POW,
         token::T_MOD,
+        token::T_ATAN2,
     ])
 });
 
@@ -104,6 +105,7 @@ pub async fn vector_scalar_bin_op(
                     samples: new_samples,
                     exemplars: range.exemplars,
                     time_window: range.time_window,
+                    histograms: vec![],
                 })
             }
         })
@@ -339,6 +341,7 @@ fn vector_arithmetic_operators(
                     samples: new_samples,
                     exemplars: lhs_range.exemplars,
                     time_window: lhs_range.time_window,
+                    histograms: vec![],
                 })
             }
         })
@@ -416,6 +419,7 @@ mod tests {
             samples,
             exemplars: None,
             time_window: None,
+            histograms: vec![],
         }
     }
 
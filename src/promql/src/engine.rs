@@ -16,7 +16,7 @@
 use std::{str::FromStr, sync::Arc, time::Duration};
 
 use async_recursion::async_recursion;
-use config::meta::promql::{NAME_LABEL, value::*};
+use config::meta::promql::{NAME_LABEL, histogram::is_stale_nan, value::*};
 use datafusion::error::{DataFusionError, Result};
 use futures::future::try_join_all;
 use hashbrown::{HashMap, HashSet};
@@ -24,9 +24,9 @@ use infra::errors::{Error, ErrorCodes};
 use promql_parser::{
     label::{MatchOp, Matchers},
     parser::{
-        AggregateExpr, BinModifier, BinaryExpr, Call, Expr as PromExpr, Function, FunctionArgs,
-        LabelModifier, MatrixSelector, NumberLiteral, Offset, ParenExpr, StringLiteral, UnaryExpr,
-        VectorMatchCardinality, VectorSelector, token,
+        AggregateExpr, AtModifier, BinModifier, BinaryExpr, Call, Expr as PromExpr, Function,
+        FunctionArgs, LabelModifier, MatrixSelector, NumberLiteral, Offset, ParenExpr,
+        StringLiteral, SubqueryExpr, UnaryExpr, VectorMatchCardinality, VectorSelector, token,
     },
 };
 use rayon::iter::{IntoParallelIterator, IntoParallelRefMutIterator, ParallelIterator};
@@ -36,7 +36,13 @@ use super::{
     load_series::{LoadedMetrics, PartitionedMetrics, selector_load_data_from_datafusion},
     promql::label_usage::labels_dropped_at_root,
 };
-use crate::{aggregations, binaries, functions, micros, promql::rewrite::remove_filter_all};
+use crate::{
+    aggregations, binaries, functions, micros, micros_since_epoch,
+    promql::rewrite::{remove_filter_all, resolve_at_modifiers},
+};
+
+/// The info metric `info()` enriches series with when no `__name__` is selected.
+const DEFAULT_INFO_METRIC: &str = "target_info";
 
 pub struct Engine {
     trace_id: String,
@@ -83,6 +89,12 @@ impl Engine {
     }
 
     pub async fn exec(&mut self, prom_expr: &PromExpr) -> Result<(Value, Option<String>)> {
+        // `@ start()` and `@ end()` refer to the query range, resolve them
+        // before any pinned evaluation narrows the context
+        let mut prom_expr = prom_expr.clone();
+        resolve_at_modifiers(&mut prom_expr, self.ctx.start, self.ctx.end);
+        let prom_expr = &prom_expr;
+
         self.extract_columns_from_prom_expr(prom_expr)?;
         if self.disable_label_selector {
             self.label_selector.clear();
@@ -147,7 +159,9 @@ impl Engine {
                 // source labels these functions read from and leave the
                 // newly-created label absent from the loaded data, so the
                 // aggregation groups everything together. See issue #11321.
-                if matches!(func.name, "label_replace" | "label_join") {
+                // `info` likewise adds labels of another metric and joins on
+                // `instance`/`job`.
+                if matches!(func.name, "label_replace" | "label_join" | "info") {
                     self.disable_label_selector = true;
                 }
                 _ = args
@@ -179,7 +193,9 @@ impl Engine {
         if let Some(label_modifier) = modifier {
             match op.id() {
                 // topk and bottomk query all columns when with modifiers
-                token::T_TOPK | token::T_BOTTOMK => self.label_selector.clear(),
+                token::T_TOPK | token::T_BOTTOMK | token::T_LIMITK | token::T_LIMIT_RATIO => {
+                    self.label_selector.clear()
+                }
                 _ => {
                     if let (label_selector, LabelModifier::Include(labels)) =
                         (&mut self.label_selector, label_modifier)
@@ -219,6 +235,16 @@ impl Engine {
                                     .collect(),
                                 exemplars: range.exemplars,
                                 time_window: range.time_window,
+                                histograms: range
+                                    .histograms
+                                    .into_iter()
+                                    .map(|h| {
+                                        HistogramSample::new(
+                                            h.timestamp,
+                                            Arc::new(h.histogram.mul(-1.0)),
+                                        )
+                                    })
+                                    .collect(),
                             })
                             .collect();
                         Value::Matrix(out)
@@ -278,6 +304,9 @@ impl Engine {
                 }
             }
             PromExpr::Paren(ParenExpr { expr }) => self.exec_expr(expr).await?,
+            PromExpr::Subquery(expr) if expr.at.is_some() => {
+                self.eval_pinned_subquery(expr).await?
+            }
             PromExpr::Subquery(expr) => {
                 let val = self.exec_expr(&expr.expr).await?;
                 let range = expr.range;
@@ -312,10 +341,14 @@ impl Engine {
                         "VectorSelector: or_matchers is not supported".into(),
                     ));
                 }
-                if vs.at.is_some() {
-                    return Err(DataFusionError::NotImplemented(
-                        "VectorSelector: @ modifier is not supported".into(),
-                    ));
+                if let Some(at) = vs.at.take() {
+                    let at = self.at_micros(&at);
+                    if !self.is_pinned_at(at) {
+                        // evaluate once at the pinned time and repeat the
+                        // samples at every step
+                        let value = self.exec_pinned(at, &PromExpr::VectorSelector(vs)).await?;
+                        return Ok(self.broadcast_pinned(value));
+                    }
                 }
                 let data = self.eval_vector_selector(&vs).await?;
                 if data.is_empty() {
@@ -332,10 +365,14 @@ impl Engine {
                         "MatrixSelector: or_matchers is not supported".into(),
                     ));
                 }
-                if vs.at.is_some() {
-                    return Err(DataFusionError::NotImplemented(
-                        "MatrixSelector: @ modifier is not supported".into(),
-                    ));
+                if let Some(at) = vs.at.take() {
+                    let at = self.at_micros(&at);
+                    if !self.is_pinned_at(at) {
+                        // the raw window ending at the pinned time, functions
+                        // over it are pinned by the `Call` arm
+                        let pinned = PromExpr::MatrixSelector(MatrixSelector { vs, range: *range });
+                        return self.exec_pinned(at, &pinned).await;
+                    }
                 }
                 let data = self.eval_matrix_selector(&vs, *range).await?;
                 if data.is_empty() {
@@ -344,7 +381,20 @@ impl Engine {
                     Value::Matrix(data)
                 }
             }
-            PromExpr::Call(Call { func, args }) => self.call_expr(func, args).await?,
+            PromExpr::Call(Call { func, args }) => match pinned_range_arg(args) {
+                // a range function over a pinned window has the same value at
+                // every step, evaluate it once
+                Some(at) if !self.is_pinned_at(self.at_micros(at)) => {
+                    let at = self.at_micros(at);
+                    let mut engine = self.pinned_engine(at);
+                    let value = engine.call_expr(func, args).await?;
+                    if self.result_type.is_none() {
+                        self.result_type = engine.result_type;
+                    }
+                    self.broadcast_pinned(value)
+                }
+                _ => self.call_expr(func, args).await?,
+            },
             PromExpr::Extension(expr) => {
                 return Err(DataFusionError::NotImplemented(format!(
                     "Unsupported Extension: {expr:?}"
@@ -353,6 +403,141 @@ impl Engine {
         })
     }
 
+    fn at_micros(&self, at: &AtModifier) -> i64 {
+        match at {
+            AtModifier::Start => self.ctx.start,
+            AtModifier::End => self.ctx.end,
+            AtModifier::At(t) => micros_since_epoch(*t),
+        }
+    }
+
+    /// True when this engine evaluates the single instant `at`.
+    fn is_pinned_at(&self, at: i64) -> bool {
+        self.eval_ctx.start == at && self.eval_ctx.end == at
+    }
+
+    /// An engine evaluating the range `[start, end]`, the data of its
+    /// selectors is loaded for that range only.
+    fn sub_engine(&self, start: i64, end: i64, step: i64) -> Engine {
+        let mut ctx = (*self.ctx).clone();
+        ctx.start = start;
+        ctx.end = end;
+        if step > 0 {
+            ctx.interval = step;
+        }
+        Engine {
+            trace_id: self.trace_id.clone(),
+            ctx: Arc::new(ctx),
+            eval_ctx: EvalContext::new(start, end, step, self.eval_ctx.trace_id.clone()),
+            label_selector: self.label_selector.clone(),
+            disable_label_selector: self.disable_label_selector,
+            skip_labels: self.skip_labels,
+            result_type: self.result_type.clone(),
+        }
+    }
+
+    /// An engine evaluating the instant `at` of an `@` modifier.
+    fn pinned_engine(&self, at: i64) -> Engine {
+        self.sub_engine(at, at, 0)
+    }
+
+    async fn exec_pinned(&mut self, at: i64, expr: &PromExpr) -> Result<Value> {
+        let mut engine = self.pinned_engine(at);
+        let value = engine.exec_expr(expr).await?;
+        if self.result_type.is_none() {
+            self.result_type = engine.result_type;
+        }
+        Ok(value)
+    }
+
+    /// Repeats the value of a pinned instant evaluation at every evaluation
+    /// timestamp of this engine.
+    fn broadcast_pinned(&self, value: Value) -> Value {
+        let timestamps = self.eval_ctx.timestamps();
+        match value {
+            Value::Matrix(series) => {
+                let series = series
+                    .into_iter()
+                    .filter_map(|mut rv| {
+                        let value = rv.samples.last()?.value;
+                        rv.samples = timestamps
+                            .iter()
+                            .map(|&ts| Sample::new(ts, value))
+                            .collect();
+                        Some(rv)
+                    })
+                    .collect::<Vec<_>>();
+                if series.is_empty() {
+                    Value::None
+                } else {
+                    Value::Matrix(series)
+                }
+            }
+            v => v,
+        }
+    }
+
+    /// Evaluates `expr[range:step] @ t`, the inner expression is evaluated
+    /// once over the window ending at the pinned time.
+    async fn eval_pinned_subquery(&mut self, expr: &SubqueryExpr) -> Result<Value> {
+        let at = self.at_micros(expr.at.as_ref().unwrap());
+        let offset = get_offset_modifier(expr.offset.clone());
+        let range = micros(expr.range);
+        let step = expr
+            .step
+            .map(micros)
+            .filter(|step| *step > 0)
+            .unwrap_or(self.ctx.interval)
+            .max(1);
+        // subquery steps are aligned to multiples of the step, like Prometheus
+        let end = at - offset;
+        let start = (end - range).div_euclid(step) * step;
+        let start = if start < end - range {
+            start + step
+        } else {
+            start
+        };
+        if start > end {
+            return Ok(Value::None);
+        }
+        let end = start + (end - start) / step * step;
+
+        let mut engine = self.sub_engine(start, end, step);
+        let value = engine.exec_expr(&expr.expr).await?;
+        if self.result_type.is_none() {
+            self.result_type = engine.result_type;
+        }
+        let series = match value {
+            Value::Matrix(series) => series,
+            Value::Float(v) => vec![RangeValue::new(
+                Labels::default(),
+                engine
+                    .eval_ctx
+                    .timestamps()
+                    .into_iter()
+                    .map(|ts| Sample::new(ts, v)),
+            )],
+            Value::None => return Ok(Value::None),
+            v => {
+                return Err(DataFusionError::NotImplemented(format!(
+                    "Unsupported subquery, the return value should have been a matrix but got {:?}",
+                    v.get_type()
+                )));
+            }
+        };
+        let series = series
+            .into_iter()
+            .map(|mut rv| {
+                if offset != 0 {
+                    rv.samples.iter_mut().for_each(|s| s.timestamp += offset);
+                }
+                rv.time_window = Some(TimeWindow::new(expr.range));
+                rv
+            })
+            .collect();
+        Ok(Value::Matrix(series))
+    }
+
     /// Instant vector selector --- select a single sample at each evaluation
     /// timestamp.
     ///
@@ -410,6 +595,7 @@ impl Engine {
         let mut result = Vec::with_capacity(metrics_cache.len());
         for metric in metrics_cache {
             let mut selected_samples = Vec::with_capacity(eval_timestamps.len());
+            let mut selected_histograms = Vec::new();
 
             for &eval_ts in &eval_timestamps {
                 // Calculate lookback window for this evaluation timestamp
@@ -421,7 +607,7 @@ impl Engine {
                     .samples
                     .partition_point(|v| v.timestamp + offset_modifier <= eval_ts);
 
-                let match_sample = if end_index > 0 {
+                let last_sample = if end_index > 0 {
                     metric.samples.get(end_index - 1).and_then(|sample| {
                         let adjusted_ts = sample.timestamp + offset_modifier;
                         if adjusted_ts >= start && adjusted_ts <= eval_ts {
@@ -433,9 +619,28 @@ impl Engine {
                 } else {
                     None
                 };
+                // a stale marker ends the series, nothing before it is looked back to
+                let match_sample = last_sample.filter(|s| !is_stale_nan(s.value));
+
+                // A series may switch between float and native histogram samples,
+                // the most recent one within the lookback window wins
+                let hist_index = metric
+                    .histograms
+                    .partition_point(|v| v.timestamp + offset_modifier <= eval_ts);
+                let match_histogram = if hist_index > 0 {
+                    metric.histograms.get(hist_index - 1).filter(|h| {
+                        let adjusted_ts = h.timestamp + offset_modifier;
+                        adjusted_ts >= start
+                            && last_sample.is_none_or(|s| h.timestamp > s.timestamp)
+                    })
+                } else {
+                    None
+                };
 
                 // Add the matched sample (already validated to be within range)
-                if let Some(sample) = match_sample {
+                if let Some(h) = match_histogram {
+                    selected_histograms.push(HistogramSample::new(eval_ts, h.histogram.clone()));
+                } else if let Some(sample) = match_sample {
                     // Use eval_ts as the timestamp for the selected sample
                     // See https://promlabs.com/blog/2020/06/18/the-anatomy-of-a-promql-query/#instant-queries
                     selected_samples.push(Sample::new(eval_ts, sample.value));
@@ -443,12 +648,13 @@ impl Engine {
             }
 
             // Only include metrics that have at least one sample
-            if !selected_samples.is_empty() {
+            if !selected_samples.is_empty() || !selected_histograms.is_empty() {
                 result.push(RangeValue {
                     labels: metric.labels,
                     samples: selected_samples,
                     exemplars: metric.exemplars,
                     time_window: metric.time_window,
+                    histograms: selected_histograms,
                 });
             }
         }
@@ -504,9 +710,15 @@ impl Engine {
             .into_par_iter()
             .map(|rv| RangeValue {
                 labels: rv.labels,
-                samples: rv.samples,
+                // stale markers only end series, they are not samples of a range
+                samples: rv
+                    .samples
+                    .into_iter()
+                    .filter(|s| !is_stale_nan(s.value))
+                    .collect(),
                 exemplars: rv.exemplars,
                 time_window: Some(TimeWindow::new(range)),
+                histograms: rv.histograms,
             })
             .collect::<Vec<_>>();
 
@@ -523,6 +735,9 @@ impl Engine {
                 rv.samples
                     .iter_mut()
                     .for_each(|s| s.timestamp += offset_modifier);
+                rv.histograms
+                    .iter_mut()
+                    .for_each(|h| h.timestamp += offset_modifier);
             });
         }
 
@@ -554,6 +769,7 @@ impl Engine {
         let start = std::time::Instant::now();
         metric_values.par_iter_mut().for_each(|metric| {
             metric.samples.sort_unstable_by_key(|k| k.timestamp);
+            metric.histograms.sort_unstable_by_key(|k| k.timestamp);
             if self.ctx.query_ctx.query_exemplars
                 && let Some(exemplars) = &mut metric.exemplars
             {
@@ -876,6 +1092,31 @@ impl Engine {
                 };
                 aggregations::bottomk(k, modifier, input, &eval_ctx)?
             }
+            token::T_LIMITK => {
+                let param_expr = param.clone().unwrap();
+                let k_value = self.exec_expr(&param_expr).await?;
+                let k = match k_value {
+                    Value::Float(f) => f as usize,
+                    _ => {
+                        return Err(DataFusionError::Plan(
+                            "[limitk] param must be a number".to_string(),
+                        ));
+                    }
+                };
+                aggregations::limitk(k, modifier, input, &eval_ctx)?
+            }
+            token::T_LIMIT_RATIO => {
+                let param_expr = param.clone().unwrap();
+                let ratio = match self.exec_expr(&param_expr).await? {
+                    Value::Float(f) => f,
+                    _ => {
+                        return Err(DataFusionError::Plan(
+                            "[limit_ratio] param must be a number".to_string(),
+                        ));
+                    }
+                };
+                aggregations::limit_ratio(ratio, input)?
+            }
             token::T_COUNT_VALUES => {
                 let param_expr = param.clone().unwrap();
                 let label_name = self.exec_expr(&param_expr).await?;
@@ -976,6 +1217,10 @@ impl Engine {
             DataFusionError::NotImplemented(format!("Unsupported function: {}", func.name))
         })?;
 
+        if func_name == Func::Pi {
+            return Ok(Value::Float(std::f64::consts::PI));
+        }
+
         // There are a few functions which need no arguments for e.g. time()
         let functions_without_args: HashSet<&str> = HashSet::from_iter(vec![
             "day_of_month",
@@ -1003,6 +1248,7 @@ impl Engine {
                         samples,
                         exemplars: None,
                         time_window: None,
+                        histograms: vec![],
                     }];
                     Value::Matrix(default_now_matrix)
                 }
@@ -1026,6 +1272,12 @@ impl Engine {
             Func::Abs => functions::abs(input)?,
             Func::Absent => functions::absent(input, &self.eval_ctx)?,
             Func::AbsentOverTime => functions::absent_over_time(input, &self.eval_ctx)?,
+            Func::Acos => functions::acos(input)?,
+            Func::Acosh => functions::acosh(input)?,
+            Func::Asin => functions::asin(input)?,
+            Func::Asinh => functions::asinh(input)?,
+            Func::Atan => functions::atan(input)?,
+            Func::Atanh => functions::atanh(input)?,
             Func::AvgOverTime => functions::avg_over_time(input, &self.eval_ctx)?,
             Func::Ceil => functions::ceil(input)?,
             Func::Changes => functions::changes(input, &self.eval_ctx)?,
@@ -1079,24 +1331,58 @@ impl Engine {
                 };
                 functions::clamp(input, min_f, f64::MAX)?
             }
+            Func::Cos => functions::cos(input)?,
+            Func::Cosh => functions::cosh(input)?,
             Func::CountOverTime => functions::count_over_time(input, &self.eval_ctx)?,
             Func::DayOfMonth => functions::day_of_month(input)?,
             Func::DayOfWeek => functions::day_of_week(input)?,
             Func::DayOfYear => functions::day_of_year(input)?,
             Func::DaysInMonth => functions::days_in_month(input)?,
+            Func::Deg => functions::deg(input)?,
             Func::Delta => functions::delta(input, &self.eval_ctx)?,
             Func::Deriv => functions::deriv(input, &self.eval_ctx)?,
+            Func::DoubleExponentialSmoothing => {
+                let err = "Invalid args, expected \"double_exponential_smoothing(v range-vector, sf scalar, tf scalar)\"";
+                self.ensure_three_args(args, err)?;
+
+                let input = self.call_expr_first_arg(args).await?;
+                let sf = self.call_expr_second_arg(args).await?;
+                let tf = self.call_expr_third_arg(args).await?;
+
+                let scaling_factor = self.parse_f64_else_err(&sf, err)?;
+                let trend_factor = self.parse_f64_else_err(&tf, err)?;
+                if scaling_factor <= 0.0 || scaling_factor >= 1.0 {
+                    return Err(DataFusionError::Plan(format!(
+                        "invalid smoothing factor. Expected: 0 < sf < 1, got: {scaling_factor}"
+                    )));
+                }
+                if trend_factor <= 0.0 || trend_factor >= 1.0 {
+                    return Err(DataFusionError::Plan(format!(
+                        "invalid trend factor. Expected: 0 < tf < 1, got: {trend_factor}"
+                    )));
+                }
+
+                functions::double_exponential_smoothing(
+                    input,
+                    scaling_factor,
+                    trend_factor,
+                    &self.eval_ctx,
+                )?
+            }
             Func::Exp => functions::exp(input)?,
             Func::Floor => functions::floor(input)?,
-            Func::HistogramCount => {
-                return Err(DataFusionError::NotImplemented(format!(
-                    "Unsupported Function: {func_name:?}"
-                )));
-            }
+            Func::HistogramAvg => functions::histogram_avg(input)?,
+            Func::HistogramCount => functions::histogram_count(input)?,
             Func::HistogramFraction => {
-                return Err(DataFusionError::NotImplemented(format!(
-                    "Unsupported Function: {func_name:?}"
-                )));
+                let err = "Invalid args, expected \"histogram_fraction(lower scalar, upper scalar, v instant-vector)\"";
+                self.ensure_three_args(args, err)?;
+
+                let lower = self.call_expr_first_arg(args).await?;
+                let upper = self.call_expr_second_arg(args).await?;
+                let lower = self.parse_f64_else_err(&lower, err)?;
+                let upper = self.parse_f64_else_err(&upper, err)?;
+
+                functions::histogram_fraction(lower, upper, input)?
             }
             Func::HistogramQuantile => {
                 let args = &args.args;
@@ -1122,11 +1408,9 @@ impl Engine {
                 // Use range version if we have an eval context
                 functions::histogram_quantile(phi, input, &self.eval_ctx)?
             }
-            Func::HistogramSum => {
-                return Err(DataFusionError::NotImplemented(format!(
-                    "Unsupported Function: {func_name:?}"
-                )));
-            }
+            Func::HistogramStddev => functions::histogram_stddev(input)?,
+            Func::HistogramStdvar => functions::histogram_stdvar(input)?,
+            Func::HistogramSum => functions::histogram_sum(input)?,
             Func::HoltWinters => {
                 let err =
                     "Invalid args, expected \"holt_winters(v range-vector, sf scalar, tf scalar)\"";
@@ -1144,6 +1428,48 @@ impl Engine {
             Func::Hour => functions::hour(input)?,
             Func::Idelta => functions::idelta(input, &self.eval_ctx)?,
             Func::Increase => functions::increase(input, &self.eval_ctx)?,
+            Func::Info => {
+                let input = self.call_expr_first_arg(args).await?;
+                let mut selector = match args.args.get(1).map(|arg| arg.as_ref()) {
+                    None => VectorSelector {
+                        name: None,
+                        matchers: Matchers {
+                            matchers: vec![],
+                            or_matchers: vec![],
+                        },
+                        offset: None,
+                        at: None,
+                    },
+                    Some(PromExpr::VectorSelector(vs)) => vs.clone(),
+                    Some(_) => {
+                        return Err(DataFusionError::Plan(
+                            "info: the second argument must be a vector selector".into(),
+                        ));
+                    }
+                };
+                if selector.name.is_none() && selector.matchers.find_matchers(NAME_LABEL).is_empty()
+                {
+                    selector.name = Some(DEFAULT_INFO_METRIC.to_string());
+                }
+                let data_matchers = selector
+                    .matchers
+                    .matchers
+                    .iter()
+                    .filter(|mat| mat.name != NAME_LABEL);
+                // a series without info is only kept if it could satisfy the
+                // data label matchers with empty values
+                let keep_unmatched = data_matchers.clone().all(|mat| mat.is_match(""));
+                let data_labels: HashSet<String> =
+                    data_matchers.map(|mat| mat.name.clone()).collect();
+
+                let info_series = self.eval_vector_selector(&selector).await?;
+                functions::info(
+                    input,
+                    info_series,
+                    (!data_labels.is_empty()).then_some(&data_labels),
+                    keep_unmatched,
+                )?
+            }
             Func::Irate => functions::irate(input, &self.eval_ctx)?,
             Func::LabelJoin => {
                 let err = "Invalid args, expected \"label_join(v instant-vector, dst string, sep string, src_1 string, src_2 string, ...)\"";
@@ -1197,10 +1523,12 @@ impl Engine {
             Func::Ln => functions::ln(input)?,
             Func::Log10 => functions::log10(input)?,
             Func::Log2 => functions::log2(input)?,
+            Func::MadOverTime => functions::mad_over_time(input, &self.eval_ctx)?,
             Func::MaxOverTime => functions::max_over_time(input, &self.eval_ctx)?,
             Func::MinOverTime => functions::min_over_time(input, &self.eval_ctx)?,
             Func::Minute => functions::minute(input)?,
             Func::Month => functions::month(input)?,
+            Func::Pi => unreachable!("pi is evaluated before its arguments"),
             Func::PredictLinear => {
                 let err = "Invalid args, expected \"predict_linear(v range-vector, t scalar)\"";
 
@@ -1214,6 +1542,7 @@ impl Engine {
                 )?;
                 functions::predict_linear(input, prediction_steps, &self.eval_ctx)?
             }
+            Func::PresentOverTime => functions::present_over_time(input, &self.eval_ctx)?,
             Func::QuantileOverTime => {
                 let err = "Invalid args, expected \"quantile_over_time(scalar, range-vector)\"";
 
@@ -1229,28 +1558,30 @@ impl Engine {
                 let input = self.call_expr_second_arg(args).await?;
                 functions::quantile_over_time(phi_quantile, input, &self.eval_ctx)?
             }
+            Func::Rad => functions::rad(input)?,
             Func::Rate => functions::rate(input, &self.eval_ctx)?,
             Func::Resets => functions::resets(input, &self.eval_ctx)?,
             Func::Round => functions::round(input)?,
             Func::Scalar => functions::scalar(input, &self.eval_ctx)?,
             Func::Sgn => functions::sgn(input)?,
-            Func::Sort => {
-                return Err(DataFusionError::NotImplemented(format!(
-                    "Unsupported Function: {func_name:?}"
-                )));
-            }
-            Func::SortDesc => {
-                return Err(DataFusionError::NotImplemented(format!(
-                    "Unsupported Function: {func_name:?}"
-                )));
-            }
+            Func::Sin => functions::sin(input)?,
+            Func::Sinh => functions::sinh(input)?,
+            // Sorting only affects the final result of instant queries, see
+            // `promql::result_order`
+            Func::Sort | Func::SortDesc => input,
+            Func::SortByLabel | Func::SortByLabelDesc => self.call_expr_first_arg(args).await?,
             Func::Sqrt => functions::sqrt(input)?,
             Func::StddevOverTime => functions::stddev_over_time(input, &self.eval_ctx)?,
             Func::StdvarOverTime => functions::stdvar_over_time(input, &self.eval_ctx)?,
             Func::SumOverTime => functions::sum_over_time(input, &self.eval_ctx)?,
+            Func::Tan => functions::tan(input)?,
+            Func::Tanh => functions::tanh(input)?,
             // TODO: check this implementation
             Func::Time => Value::Float((self.eval_ctx.start / 1_000_000) as f64),
             Func::Timestamp => functions::timestamp(input)?,
+            Func::TsOfLastOverTime => functions::ts_of_last_over_time(input, &self.eval_ctx)?,
+            Func::TsOfMaxOverTime => functions::ts_of_max_over_time(input, &self.eval_ctx)?,
+            Func::TsOfMinOverTime => functions::ts_of_min_over_time(input, &self.eval_ctx)?,
             Func::Vector => functions::vector(input, &self.eval_ctx)?,
             Func::Year => functions::year(input)?,
         })
@@ -1326,6 +1657,21 @@ fn get_offset_modifier(offset: Option<Offset>) -> i64 {
     }
 }
 
+/// The `@` modifier of the first range vector argument of a function call.
+fn pinned_range_arg(args: &FunctionArgs) -> Option<&AtModifier> {
+    args.args.iter().find_map(|arg| {
+        let mut arg = arg.as_ref();
+        while let PromExpr::Paren(ParenExpr { expr }) = arg {
+            arg = expr;
+        }
+        match arg {
+            PromExpr::MatrixSelector(MatrixSelector { vs, .. }) => vs.at.as_ref(),
+            PromExpr::Subquery(SubqueryExpr { at, .. }) => at.as_ref(),
+            _ => None,
+        }
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use std::sync::Arc;
@@ -1369,6 +1715,7 @@ mod tests {
             samples: vec![Sample::new(timestamp, value)],
             exemplars: None,
             time_window: None,
+            histograms: vec![],
         }
     }
 
@@ -1940,18 +2287,10 @@ mod tests {
         assert!(engine.label_selector.is_empty());
     }
 
-    /// The `@` modifier PARSES (the fork supports the syntax) but nothing in the
-    /// engine ever reads `vs.at` — every selector is evaluated at the step's own
-    /// timestamp. So before this guard, `foo @ 1600000000` did not pin anything:
-    /// it silently returned UNPINNED results, and the user got a plausible wrong
-    /// number with no error. An unsupported feature must fail loudly; returning
-    /// the wrong data quietly is the worst outcome in a metrics engine.
-    ///
-    /// Rejected at both selector arms — a range selector carries its own `at`
-    /// (`foo[5m] @ end()`), and a subquery recurses through `exec_expr`, so a
-    /// nested `@` lands on one of these two.
+    /// `@` pins instant selectors, range selectors and subqueries, including
+    /// when they are nested in functions and aggregations.
     #[tokio::test]
-    async fn test_exec_expr_rejects_at_modifier() {
+    async fn test_exec_expr_accepts_at_modifier() {
         let trace_id = "test_trace";
         let org_id = "test_org";
 
@@ -1959,9 +2298,20 @@ mod tests {
         let cases = [
             ("foo @ 1600000000", "instant selector, absolute @"),
             ("foo @ start()", "instant selector, @ start()"),
-            ("foo @ end()", "instant selector, @ end()"),
+            (
+                "foo @ end() offset 5m",
+                "instant selector, @ end() with offset",
+            ),
             ("rate(foo[5m] @ 1600000000)", "range selector inside a call"),
-            ("sum_over_time(foo[5m] @ end())", "range selector, @ end()"),
+            (
+                "topk(5, rate(foo[5m] @ end()))",
+                "pinned call inside an aggregation",
+            ),
+            (
+                "max_over_time(rate(foo[1m])[10m:1m] @ end())",
+                "subquery, @ end()",
+            ),
+            ("foo @ start() - foo @ end()", "two different pinned times"),
         ];
 
         for (query, what) in cases {
@@ -1972,24 +2322,97 @@ mod tests {
                     SimpleMockProvider,
                     vec![],
                 )),
-                create_test_eval_ctx(),
+                EvalContext::new(
+                    1640995200000000,
+                    1640998800000000,
+                    60_000_000,
+                    trace_id.to_string(),
+                ),
             );
 
             let expr = promql_parser::parser::parse(query)
                 .unwrap_or_else(|e| panic!("{what}: `{query}` should parse: {e}"));
-            let err = engine.exec_expr(&expr).await.err().unwrap_or_else(|| {
-                panic!("{what}: `{query}` must be rejected, not silently ignored")
-            });
-
-            assert!(
-                err.to_string().contains("@ modifier is not supported"),
-                "{what}: `{query}` should fail with the @-modifier error, got: {err}",
-            );
+            if let Err(e) = engine.exec(&expr).await {
+                panic!("{what}: `{query}` should be evaluated, got: {e}");
+            }
         }
     }
 
-    /// The guard must not fire on a query that merely LOOKS adjacent — `offset`
-    /// is genuinely implemented and must keep working.
+    /// A pinned selector loads the window before the pinned time once, not
+    /// the whole range of the query.
+    #[tokio::test]
+    async fn test_at_modifier_loads_pinned_window() {
+        let trace_id = "test_trace";
+        let captured = Arc::new(std::sync::Mutex::new(vec![]));
+        let mut ctx = PromqlContext::new(
+            create_test_query_ctx(trace_id, "test_org", 30),
+            TimeRangeCapturingProvider {
+                captured: captured.clone(),
+            },
+            vec![],
+        );
+        ctx.start = 1640995200000000;
+        ctx.end = 1640998800000000;
+        let mut engine = Engine::new(
+            trace_id,
+            Arc::new(ctx),
+            EvalContext::new(
+                1640995200000000,
+                1640998800000000,
+                60_000_000,
+                trace_id.to_string(),
+            ),
+        );
+
+        let expr = promql_parser::parser::parse("rate(foo[5m] @ 1600000000)").unwrap();
+        engine.exec(&expr).await.unwrap();
+        assert_eq!(
+            *captured.lock().unwrap(),
+            vec![(1600000000000000 - 300_000_000, 1600000000000000)]
+        );
+
+        captured.lock().unwrap().clear();
+        let expr = promql_parser::parser::parse("foo @ end()").unwrap();
+        engine.exec(&expr).await.unwrap();
+        assert_eq!(
+            *captured.lock().unwrap(),
+            vec![(1640998800000000 - 300_000_000, 1640998800000000)]
+        );
+    }
+
+    #[test]
+    fn test_broadcast_pinned() {
+        let engine = Engine::new(
+            "test_trace",
+            Arc::new(PromqlContext::new(
+                create_test_query_ctx("test_trace", "test_org", 30),
+                SimpleMockProvider,
+                vec![],
+            )),
+            EvalContext::new(0, 120, 60, "test_trace".to_string()),
+        );
+
+        let value = engine.broadcast_pinned(Value::Matrix(vec![range_value(1000, 7.0)]));
+        let Value::Matrix(series) = value else {
+            panic!("expected a matrix");
+        };
+        let samples = series[0]
+            .samples
+            .iter()
+            .map(|s| (s.timestamp, s.value))
+            .collect::<Vec<_>>();
+        assert_eq!(samples, vec![(0, 7.0), (60, 7.0), (120, 7.0)]);
+        assert!(matches!(
+            engine.broadcast_pinned(Value::Float(1.0)),
+            Value::Float(1.0)
+        ));
+        assert!(matches!(
+            engine.broadcast_pinned(Value::Matrix(vec![])),
+            Value::None
+        ));
+    }
+
+    /// `offset` without `@` shifts every step and must keep working.
     #[tokio::test]
     async fn test_exec_expr_allows_offset_without_at_modifier() {
         let trace_id = "test_trace";
@@ -2007,14 +2430,7 @@ mod tests {
         let expr = promql_parser::parser::parse("foo offset 5m").expect("should parse");
         let err = engine.exec_expr(&expr).await.err();
 
-        // The mock provider returns no data, so this may or may not error — what
-        // matters is that it is never the @-modifier rejection.
-        if let Some(err) = err {
-            assert!(
-                !err.to_string().contains("@ modifier"),
-                "offset must not be caught by the @-modifier guard, got: {err}",
-            );
-        }
+        assert!(err.is_none(), "offset should be evaluated, got: {err:?}");
     }
 
     #[tokio::test]
@@ -2145,6 +2561,7 @@ mod tests {
         let instant = InstantValue {
             labels: vec![Arc::new(Label::new("env", "prod"))],
             sample,
+            histogram: None,
         };
         let _vector = Value::Vector(vec![instant]);
         let vector_expr = PromExpr::Extension(Extension {
@@ -2312,6 +2729,7 @@ mod tests {
         let _instant = InstantValue {
             labels: vec![Arc::new(Label::new("env", "prod"))],
             sample,
+            histogram: None,
         };
         let vector = PromExpr::Extension(Extension {
             expr: Arc::new(TestExtension),
@@ -2882,6 +3300,7 @@ mod tests {
             regions: vec![],
             clusters: vec![],
             is_super_cluster: false,
+            shard: None,
         })
     }
 
@@ -2920,6 +3339,34 @@ mod tests {
         }
     }
 
+    /// Mock provider that records the time ranges the engine loads.
+    struct TimeRangeCapturingProvider {
+        captured: Arc<std::sync::Mutex<Vec<(i64, i64)>>>,
+    }
+
+    #[async_trait::async_trait]
+    impl crate::TableProvider for TimeRangeCapturingProvider {
+        async fn create_context(
+            &self,
+            _org_id: &str,
+            _stream_name: &str,
+            time_range: (i64, i64),
+            _machers: promql_parser::label::Matchers,
+            _label_selector: HashSet<String>,
+            _filters: &mut [(String, Vec<String>)],
+        ) -> datafusion::error::Result<
+            Vec<(
+                datafusion::prelude::SessionContext,
+                std::sync::Arc<datafusion::arrow::datatypes::Schema>,
+                config::meta::search::ScanStats,
+                bool,
+            )>,
+        > {
+            self.captured.lock().unwrap().push(time_range);
+            Ok(vec![])
+        }
+    }
+
     /// Mock provider that records the matchers the engine hands to storage.
     struct MatcherCapturingProvider {
         captured: Arc<std::sync::Mutex<Option<Matchers>>>,
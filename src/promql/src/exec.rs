@@ -27,7 +27,7 @@ use tokio::sync::{RwLock, Semaphore};
 use super::engine::Engine;
 use crate::{
     DEFAULT_LOOKBACK, TableProvider, micros, micros_since_epoch,
-    promql::selector_visitor::MetricSelectorVisitor,
+    promql::{result_order::sort_result, selector_visitor::MetricSelectorVisitor},
 };
 
 #[derive(Clone)]
@@ -95,14 +95,21 @@ impl PromqlContext {
             // For instant queries, convert Matrix to Vector format
             match value {
                 Value::Matrix(matrix) => {
-                    // Convert each RangeValue to InstantValue (take first sample)
+                    // Convert each RangeValue to InstantValue (take first sample,
+                    // or the first native histogram of a histogram series)
                     let vector: Vec<InstantValue> = matrix
                         .into_iter()
-                        .filter_map(|range_val| {
-                            range_val.samples.first().map(|sample| InstantValue {
+                        .filter_map(|range_val| match range_val.samples.first() {
+                            Some(sample) => Some(InstantValue {
                                 labels: range_val.labels.clone(),
                                 sample: *sample,
-                            })
+                                histogram: None,
+                            }),
+                            None => range_val.histograms.first().map(|h| InstantValue {
+                                labels: range_val.labels.clone(),
+                                sample: Sample::new(h.timestamp, h.histogram.count),
+                                histogram: Some(h.clone()),
+                            }),
                         })
                         .collect();
                     (Value::Vector(vector), Some("vector".to_string()))
@@ -134,6 +141,7 @@ impl PromqlContext {
                         samples,
                         exemplars: None,
                         time_window: None,
+                        histograms: vec![],
                     };
 
                     (Value::Matrix(vec![range_value]), Some("matrix".to_string()))
@@ -145,7 +153,7 @@ impl PromqlContext {
         };
 
         let mut sorted_value = final_value;
-        sorted_value.sort();
+        sort_result(&expr, &mut sorted_value);
         Ok((
             sorted_value,
             final_result_type,
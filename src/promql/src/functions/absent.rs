@@ -32,6 +32,7 @@ fn generate_absent_matrix(eval_ctx: &EvalContext) -> Value {
         samples,
         exemplars: None,
         time_window: None,
+        histograms: vec![],
     };
 
     Value::Matrix(vec![range_value])
@@ -76,6 +77,7 @@ pub(crate) fn absent(data: Value, eval_ctx: &EvalContext) -> Result<Value> {
                 samples: absent_samples,
                 exemplars: None,
                 time_window: None,
+                histograms: vec![],
             };
 
             Ok(Value::Matrix(vec![range_value]))
@@ -158,6 +160,7 @@ mod tests {
             ],
             exemplars: None,
             time_window: None,
+            histograms: vec![],
         }]);
         let result = absent(value, &eval_ctx).unwrap();
         assert!(matches!(result, Value::None));
@@ -176,6 +179,7 @@ mod tests {
             ],
             exemplars: None,
             time_window: None,
+            histograms: vec![],
         }]);
         let result = absent(value, &eval_ctx).unwrap();
 
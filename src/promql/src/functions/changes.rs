@@ -103,6 +103,7 @@ mod tests {
                 range: Duration::from_secs(3),
                 offset: Duration::ZERO,
             }),
+            histograms: vec![],
         };
 
         let matrix = Value::Matrix(vec![range_value]);
@@ -39,6 +39,7 @@ pub(crate) fn clamp(data: Value, min: f64, max: f64) -> Result<Value> {
                         samples,
                         exemplars: range_value.exemplars,
                         time_window: range_value.time_window,
+                        histograms: vec![],
                     }
                 })
                 .collect();
@@ -72,6 +73,7 @@ mod tests {
                     range: Duration::from_secs(5),
                     offset: Duration::ZERO,
                 }),
+                histograms: vec![],
             })
             .collect();
         Value::Matrix(range_values)
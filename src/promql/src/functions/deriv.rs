@@ -101,6 +101,7 @@ mod tests {
                 range: Duration::from_secs(2),
                 offset: Duration::ZERO,
             }),
+            histograms: vec![],
         };
 
         let matrix = Value::Matrix(vec![range_value]);
@@ -16,7 +16,10 @@
 use config::{
     meta::promql::{
         BUCKET_LABEL, HASH_LABEL, NAME_LABEL,
-        value::{EvalContext, LabelsExt, RangeValue, Sample, Value, signature_without_labels},
+        value::{
+            EvalContext, LabelsExt, NativeHistogram, RangeValue, Sample, Value,
+            signature_without_labels,
+        },
     },
     utils::sort::sort_float,
 };
@@ -56,8 +59,15 @@ pub(crate) fn histogram_quantile(phi: f64, data: Value, eval_ctx: &EvalContext)
 
     // Group metrics by their signature (without bucket label)
     let mut metrics_by_sig: HashMap<u64, Vec<RangeValue>> = HashMap::default();
+    let mut range_values = Vec::new();
 
     for rv in in_matrix {
+        // Native histograms carry their own buckets
+        if !rv.histograms.is_empty() {
+            range_values.extend(map_native(rv, |h| h.quantile(phi)));
+            continue;
+        }
+
         // Verify this metric has a bucket label
         if rv.labels.get_value(BUCKET_LABEL).parse::<f64>().is_err() {
             continue;
@@ -67,8 +77,6 @@ pub(crate) fn histogram_quantile(phi: f64, data: Value, eval_ctx: &EvalContext)
         metrics_by_sig.entry(sig).or_default().push(rv);
     }
 
-    let mut range_values = Vec::new();
-
     for (_sig, bucket_series) in metrics_by_sig {
         // Get the labels (without bucket label) from the first series
         let mut base_labels = bucket_series[0].labels.clone();
@@ -111,6 +119,7 @@ pub(crate) fn histogram_quantile(phi: f64, data: Value, eval_ctx: &EvalContext)
                 samples,
                 exemplars: None,
                 time_window: None,
+                histograms: vec![],
             });
         }
     }
@@ -118,6 +127,81 @@ pub(crate) fn histogram_quantile(phi: f64, data: Value, eval_ctx: &EvalContext)
     Ok(Value::Matrix(range_values))
 }
 
+/// `histogram_fraction(lower, upper, v)`: the estimated fraction of observations
+/// between `lower` and `upper` in each native histogram of `v`.
+pub(crate) fn histogram_fraction(lower: f64, upper: f64, data: Value) -> Result<Value> {
+    eval_native(data, "histogram_fraction", |h| h.fraction(lower, upper))
+}
+
+/// `histogram_count(v)`: the count of observations of each native histogram.
+pub(crate) fn histogram_count(data: Value) -> Result<Value> {
+    eval_native(data, "histogram_count", |h| h.count)
+}
+
+/// `histogram_sum(v)`: the sum of observations of each native histogram.
+pub(crate) fn histogram_sum(data: Value) -> Result<Value> {
+    eval_native(data, "histogram_sum", |h| h.sum)
+}
+
+/// `histogram_avg(v)`: the arithmetic average of observed values.
+pub(crate) fn histogram_avg(data: Value) -> Result<Value> {
+    eval_native(data, "histogram_avg", NativeHistogram::avg)
+}
+
+/// `histogram_stddev(v)`: the estimated standard deviation of observations.
+pub(crate) fn histogram_stddev(data: Value) -> Result<Value> {
+    eval_native(data, "histogram_stddev", NativeHistogram::stddev)
+}
+
+/// `histogram_stdvar(v)`: the estimated standard variance of observations.
+pub(crate) fn histogram_stdvar(data: Value) -> Result<Value> {
+    eval_native(data, "histogram_stdvar", NativeHistogram::stdvar)
+}
+
+/// Applies `f` to every native histogram sample; series without native histograms
+/// are dropped.
+fn eval_native<F>(data: Value, name: &str, f: F) -> Result<Value>
+where
+    F: Fn(&NativeHistogram) -> f64,
+{
+    let in_matrix = match data {
+        Value::Matrix(m) => m,
+        Value::None => return Ok(Value::None),
+        _ => {
+            return Err(DataFusionError::Plan(format!(
+                "{name}: vector argument expected"
+            )));
+        }
+    };
+    Ok(Value::Matrix(
+        in_matrix
+            .into_iter()
+            .filter_map(|rv| map_native(rv, &f))
+            .collect(),
+    ))
+}
+
+fn map_native<F>(rv: RangeValue, f: F) -> Option<RangeValue>
+where
+    F: Fn(&NativeHistogram) -> f64,
+{
+    if rv.histograms.is_empty() {
+        return None;
+    }
+    let samples = rv
+        .histograms
+        .iter()
+        .map(|h| Sample::new(h.timestamp, f(&h.histogram)))
+        .collect();
+    Some(RangeValue {
+        labels: rv.labels.without_metric_name(),
+        samples,
+        exemplars: None,
+        time_window: None,
+        histograms: vec![],
+    })
+}
+
 // cf. https://github.com/prometheus/prometheus/blob/cf1bea344a3c390a90c35ea8764c4a468b345d5e/promql/quantile.go#L76
 fn bucket_quantile(phi: f64, mut buckets: Vec<Bucket>) -> f64 {
     if phi.is_nan() || buckets.is_empty() {
@@ -217,10 +301,58 @@ fn ensure_monotonic(buckets: &mut [Bucket]) {
 
 #[cfg(test)]
 mod tests {
+    use std::sync::Arc;
+
+    use config::meta::promql::value::{HistogramSample, Label};
     use expect_test::expect;
 
     use super::*;
 
+    #[test]
+    fn test_native_histogram_functions() {
+        let histogram = NativeHistogram {
+            count: 4.0,
+            sum: 6.0,
+            // schema 0: (0.5, 1], (1, 2], (2, 4]
+            positive: vec![(0, 1.0), (1, 2.0), (2, 1.0)],
+            ..Default::default()
+        };
+        let input = || {
+            Value::Matrix(vec![
+                RangeValue {
+                    labels: vec![
+                        Arc::new(Label::new(NAME_LABEL, "latency")),
+                        Arc::new(Label::new("job", "api")),
+                    ],
+                    samples: vec![],
+                    exemplars: None,
+                    time_window: None,
+                    histograms: vec![HistogramSample::new(1_000_000, Arc::new(histogram.clone()))],
+                },
+                RangeValue::new(vec![], [Sample::new(1_000_000, 1.0)]),
+            ])
+        };
+        let single = |v: Value| match v {
+            Value::Matrix(m) => {
+                assert_eq!(m.len(), 1);
+                assert!(m[0].labels.get_value(NAME_LABEL).is_empty());
+                assert_eq!(m[0].samples.len(), 1);
+                m[0].samples[0].value
+            }
+            v => panic!("unexpected value: {v:?}"),
+        };
+
+        assert_eq!(single(histogram_count(input()).unwrap()), 4.0);
+        assert_eq!(single(histogram_sum(input()).unwrap()), 6.0);
+        assert_eq!(single(histogram_avg(input()).unwrap()), 1.5);
+        assert_eq!(single(histogram_fraction(0.0, 2.0, input()).unwrap()), 0.75);
+        assert!(single(histogram_stddev(input()).unwrap()) > 0.0);
+
+        let ctx = EvalContext::new(1_000_000, 1_000_000, 0, "test".to_string());
+        let median = single(histogram_quantile(0.5, input(), &ctx).unwrap());
+        assert!(median > 1.0 && median <= 2.0);
+    }
+
     #[test]
     fn test_coalesce_buckets() {
         let buckets = vec![
@@ -34,6 +34,19 @@ pub(crate) fn holt_winters(
     )
 }
 
+/// https://prometheus.io/docs/prometheus/latest/querying/functions/#double_exponential_smoothing
+///
+/// Prometheus 3.0 renamed `holt_winters` to `double_exponential_smoothing`; both names
+/// compute the same thing.
+pub(crate) fn double_exponential_smoothing(
+    data: Value,
+    scaling_factor: f64,
+    trend_factor: f64,
+    eval_ctx: &EvalContext,
+) -> Result<Value> {
+    holt_winters(data, scaling_factor, trend_factor, eval_ctx)
+}
+
 pub struct HoltWintersFunc {
     scaling_factor: f64,
     trend_factor: f64,
@@ -142,6 +155,7 @@ mod tests {
                 range: Duration::from_secs(2),
                 offset: Duration::ZERO,
             }),
+            histograms: vec![],
         };
 
         let matrix = Value::Matrix(vec![range_value]);
@@ -0,0 +1,250 @@
+// Copyright 2026 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+use std::sync::Arc;
+
+use config::meta::promql::{
+    HASH_LABEL, NAME_LABEL,
+    value::{Label, Labels, LabelsExt, RangeValue, Value},
+};
+use datafusion::error::{DataFusionError, Result};
+use hashbrown::{HashMap, HashSet};
+
+/// Labels shared by a series and the info series that describes it.
+const IDENTIFYING_LABELS: [&str; 2] = ["instance", "job"];
+
+/// https://prometheus.io/docs/prometheus/latest/querying/functions/#info
+///
+/// Adds the data labels of the `info_series` matching each series of `data` on
+/// `instance` and `job` at the same timestamp. Labels already set on the series
+/// take precedence. `data_labels` restricts the copied labels to the given names,
+/// and series without a matching info series are dropped unless `keep_unmatched`.
+pub(crate) fn info(
+    data: Value,
+    info_series: Vec<RangeValue>,
+    data_labels: Option<&HashSet<String>>,
+    keep_unmatched: bool,
+) -> Result<Value> {
+    let matrix = match data {
+        Value::Matrix(matrix) => matrix,
+        Value::None => return Ok(Value::None),
+        _ => {
+            return Err(DataFusionError::Plan(
+                "info: matrix argument expected".into(),
+            ));
+        }
+    };
+
+    let mut info_by_identity: HashMap<Vec<String>, Vec<RangeValue>> = HashMap::new();
+    for series in info_series {
+        info_by_identity
+            .entry(identity(&series.labels))
+            .or_default()
+            .push(series);
+    }
+    // pick the same info series on every run when several match
+    for group in info_by_identity.values_mut() {
+        group.sort_by(|a, b| {
+            a.labels
+                .partial_cmp(&b.labels)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+    }
+
+    let mut out: Vec<RangeValue> = Vec::with_capacity(matrix.len());
+    let mut out_index: HashMap<u64, usize> = HashMap::new();
+    for series in matrix {
+        let Some(candidates) = info_by_identity.get(&identity(&series.labels)) else {
+            if keep_unmatched {
+                push_series(&mut out, &mut out_index, series);
+            }
+            continue;
+        };
+        let extra_labels_at = |ts: i64| -> Option<Labels> {
+            let info = candidates.iter().find(|info| {
+                info.samples
+                    .binary_search_by_key(&ts, |s| s.timestamp)
+                    .is_ok()
+            })?;
+            Some(
+                info.labels
+                    .iter()
+                    .filter(|l| {
+                        l.name != NAME_LABEL
+                            && l.name != HASH_LABEL
+                            && !IDENTIFYING_LABELS.contains(&l.name.as_str())
+                            && data_labels.is_none_or(|names| names.contains(&l.name))
+                            && series.labels.iter().all(|own| own.name != l.name)
+                    })
+                    .cloned()
+                    .collect(),
+            )
+        };
+
+        // The matching info series can change over time, so the enriched samples
+        // are split by their resulting label set.
+        let mut parts: Vec<(Option<Labels>, RangeValue)> = vec![];
+        let part_for =
+            |extra: Option<Labels>, parts: &mut Vec<(Option<Labels>, RangeValue)>| match parts
+                .iter()
+                .position(|(labels, _)| *labels == extra)
+            {
+                Some(i) => i,
+                None => {
+                    let mut labels = series.labels.clone();
+                    if let Some(extra) = &extra {
+                        labels.extend(extra.iter().cloned());
+                        labels.sort_by(|a, b| a.name.cmp(&b.name));
+                    }
+                    parts.push((
+                        extra,
+                        RangeValue {
+                            labels,
+                            samples: vec![],
+                            exemplars: None,
+                            time_window: series.time_window.clone(),
+                            histograms: vec![],
+                        },
+                    ));
+                    parts.len() - 1
+                }
+            };
+        for sample in &series.samples {
+            let extra = extra_labels_at(sample.timestamp);
+            if extra.is_none() && !keep_unmatched {
+                continue;
+            }
+            let i = part_for(extra, &mut parts);
+            parts[i].1.samples.push(*sample);
+        }
+        for histogram in &series.histograms {
+            let extra = extra_labels_at(histogram.timestamp);
+            if extra.is_none() && !keep_unmatched {
+                continue;
+            }
+            let i = part_for(extra, &mut parts);
+            parts[i].1.histograms.push(histogram.clone());
+        }
+        for (_, part) in parts {
+            push_series(&mut out, &mut out_index, part);
+        }
+    }
+    Ok(Value::Matrix(out))
+}
+
+fn identity(labels: &Labels) -> Vec<String> {
+    IDENTIFYING_LABELS
+        .iter()
+        .map(|name| labels.get_value(name))
+        .collect()
+}
+
+/// Appends `series`, merging it into an earlier output series with the same labels.
+fn push_series(out: &mut Vec<RangeValue>, out_index: &mut HashMap<u64, usize>, series: RangeValue) {
+    let signature = series.labels.signature();
+    match out_index.get(&signature) {
+        Some(&i) => {
+            out[i].samples.extend(series.samples);
+            out[i].samples.sort_by_key(|s| s.timestamp);
+            out[i].histograms.extend(series.histograms);
+            out[i].histograms.sort_by_key(|h| h.timestamp);
+        }
+        None => {
+            out_index.insert(signature, out.len());
+            out.push(series);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use config::meta::promql::value::Sample;
+
+    use super::*;
+
+    fn series(labels: &[(&str, &str)], samples: &[(i64, f64)]) -> RangeValue {
+        RangeValue {
+            labels: labels
+                .iter()
+                .map(|(name, value)| Arc::new(Label::new(*name, *value)))
+                .collect(),
+            samples: samples
+                .iter()
+                .map(|&(ts, value)| Sample::new(ts, value))
+                .collect(),
+            exemplars: None,
+            time_window: None,
+            histograms: vec![],
+        }
+    }
+
+    #[test]
+    fn test_info_adds_data_labels() {
+        let data = Value::Matrix(vec![
+            series(
+                &[("__name__", "up"), ("instance", "a"), ("job", "node")],
+                &[(1, 1.0), (2, 1.0)],
+            ),
+            series(
+                &[("__name__", "up"), ("instance", "b"), ("job", "node")],
+                &[(1, 0.0)],
+            ),
+        ]);
+        let target_info = vec![series(
+            &[
+                ("__name__", "target_info"),
+                ("instance", "a"),
+                ("job", "node"),
+                ("region", "eu"),
+                ("version", "1.2"),
+            ],
+            &[(1, 1.0), (2, 1.0)],
+        )];
+
+        let Value::Matrix(out) = info(data.clone(), target_info.clone(), None, true).unwrap()
+        else {
+            panic!("matrix expected");
+        };
+        assert_eq!(out.len(), 2);
+        assert_eq!(out[0].labels.get_value("region"), "eu");
+        assert_eq!(out[0].labels.get_value("version"), "1.2");
+        assert_eq!(out[0].labels.get_value(NAME_LABEL), "up");
+        assert_eq!(out[0].samples.len(), 2);
+        assert_eq!(out[1].labels.get_value("region"), "");
+
+        let names = HashSet::from(["region".to_string()]);
+        let Value::Matrix(out) = info(data, target_info, Some(&names), false).unwrap() else {
+            panic!("matrix expected");
+        };
+        assert_eq!(out.len(), 1);
+        assert_eq!(out[0].labels.get_value("region"), "eu");
+        assert_eq!(out[0].labels.get_value("version"), "");
+    }
+
+    #[test]
+    fn test_info_existing_labels_win() {
+        let data = Value::Matrix(vec![series(
+            &[("instance", "a"), ("job", "node"), ("region", "us")],
+            &[(1, 1.0)],
+        )]);
+        let target_info = vec![series(
+            &[("instance", "a"), ("job", "node"), ("region", "eu")],
+            &[(1, 1.0)],
+        )];
+        let Value::Matrix(out) = info(data, target_info, None, true).unwrap() else {
+            panic!("matrix expected");
+        };
+        assert_eq!(out[0].labels.get_value("region"), "us");
+    }
+}
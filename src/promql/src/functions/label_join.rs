@@ -56,6 +56,7 @@ pub(crate) fn label_join(
                         samples: range_value.samples,
                         exemplars: range_value.exemplars,
                         time_window: range_value.time_window,
+                        histograms: vec![],
                     }
                 })
                 .collect();
@@ -101,6 +102,7 @@ mod tests {
             samples: vec![Sample::new(eval_ts, 42.0)],
             exemplars: None,
             time_window: None,
+            histograms: vec![],
         };
 
         let labels2 = vec![
@@ -113,6 +115,7 @@ mod tests {
             samples: vec![Sample::new(eval_ts, 43.0)],
             exemplars: None,
             time_window: None,
+            histograms: vec![],
         };
 
         let matrix = Value::Matrix(vec![range_value1, range_value2]);
@@ -62,6 +62,7 @@ pub(crate) fn label_replace(
                         samples: range_value.samples,
                         exemplars: range_value.exemplars,
                         time_window: range_value.time_window,
+                        histograms: vec![],
                     }
                 })
                 .collect();
@@ -107,6 +108,7 @@ mod tests {
             samples: vec![Sample::new(eval_ts, 42.0)],
             exemplars: None,
             time_window: None,
+            histograms: vec![],
         };
 
         let matrix = Value::Matrix(vec![range_value]);
@@ -147,6 +149,7 @@ mod tests {
             samples: vec![Sample::new(1000, 1.0)],
             exemplars: None,
             time_window: None,
+            histograms: vec![],
         };
         let matrix = Value::Matrix(vec![range_value]);
         let result = label_replace(matrix, "dst", "", "src", ".*").unwrap();
@@ -171,6 +174,7 @@ mod tests {
             samples: vec![Sample::new(1000, 1.0)],
             exemplars: None,
             time_window: None,
+            histograms: vec![],
         };
         let matrix = Value::Matrix(vec![range_value]);
         // Regex won't match "prod-server"
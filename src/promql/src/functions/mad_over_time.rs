@@ -0,0 +1,70 @@
+// Copyright 2026 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::time::Duration;
+
+use config::meta::promql::value::{EvalContext, Sample, Value};
+use datafusion::error::Result;
+
+use crate::{common::quantile, functions::RangeFunc};
+
+/// https://prometheus.io/docs/prometheus/latest/querying/functions/#aggregation_over_time
+pub(crate) fn mad_over_time(data: Value, eval_ctx: &EvalContext) -> Result<Value> {
+    super::eval_range(data, MadOverTimeFunc::new(), eval_ctx)
+}
+
+/// The median absolute deviation of all points in the window.
+pub struct MadOverTimeFunc;
+
+impl MadOverTimeFunc {
+    pub fn new() -> Self {
+        MadOverTimeFunc {}
+    }
+}
+
+impl RangeFunc for MadOverTimeFunc {
+    fn name(&self) -> &'static str {
+        "mad_over_time"
+    }
+
+    fn exec(&self, samples: &[Sample], _eval_ts: i64, _range: &Duration) -> Option<f64> {
+        let values: Vec<f64> = samples.iter().map(|x| x.value).collect();
+        let median = quantile(&values, 0.5)?;
+        let deviations: Vec<f64> = values.iter().map(|v| (v - median).abs()).collect();
+        quantile(&deviations, 0.5)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mad_over_time() {
+        let func = MadOverTimeFunc::new();
+        assert!(func.exec(&[], 0, &Duration::from_secs(60)).is_none());
+
+        // median 2, deviations [1, 1, 0, 0, 2, 4, 7] -> median 1
+        let samples: Vec<Sample> = [1.0, 1.0, 2.0, 2.0, 4.0, 6.0, 9.0]
+            .iter()
+            .enumerate()
+            .map(|(i, &v)| Sample::new(i as i64 * 1_000_000, v))
+            .collect();
+        assert_eq!(
+            func.exec(&samples, 6_000_000, &Duration::from_secs(60)),
+            Some(1.0)
+        );
+    }
+}
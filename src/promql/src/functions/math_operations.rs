@@ -21,15 +21,29 @@ use strum::EnumIter;
 #[derive(Debug, EnumIter)]
 pub enum MathOperationsType {
     Abs,
+    Acos,
+    Acosh,
+    Asin,
+    Asinh,
+    Atan,
+    Atanh,
     Ceil,
+    Cos,
+    Cosh,
+    Deg,
     Exp,
     Floor,
     Ln,
     Log10,
     Log2,
+    Rad,
     Round,
     Sgn,
+    Sin,
+    Sinh,
     Sqrt,
+    Tan,
+    Tanh,
 }
 
 impl MathOperationsType {
@@ -37,15 +51,29 @@ impl MathOperationsType {
     pub fn apply(&self, input: f64) -> f64 {
         match self {
             Self::Abs => input.abs(),
+            Self::Acos => input.acos(),
+            Self::Acosh => input.acosh(),
+            Self::Asin => input.asin(),
+            Self::Asinh => input.asinh(),
+            Self::Atan => input.atan(),
+            Self::Atanh => input.atanh(),
             Self::Ceil => input.ceil(),
+            Self::Cos => input.cos(),
+            Self::Cosh => input.cosh(),
+            Self::Deg => input.to_degrees(),
             Self::Exp => input.exp(),
             Self::Floor => input.floor(),
             Self::Ln => input.ln(),
             Self::Log2 => input.log2(),
             Self::Log10 => input.log10(),
+            Self::Rad => input.to_radians(),
             Self::Sgn => input.signum(),
+            Self::Sin => input.sin(),
+            Self::Sinh => input.sinh(),
             Self::Sqrt => input.sqrt(),
             Self::Round => input.round(),
+            Self::Tan => input.tan(),
+            Self::Tanh => input.tanh(),
         }
     }
 }
@@ -90,6 +118,62 @@ pub(crate) fn sgn(data: Value) -> Result<Value> {
     exec(data, &MathOperationsType::Sgn)
 }
 
+pub(crate) fn acos(data: Value) -> Result<Value> {
+    exec(data, &MathOperationsType::Acos)
+}
+
+pub(crate) fn acosh(data: Value) -> Result<Value> {
+    exec(data, &MathOperationsType::Acosh)
+}
+
+pub(crate) fn asin(data: Value) -> Result<Value> {
+    exec(data, &MathOperationsType::Asin)
+}
+
+pub(crate) fn asinh(data: Value) -> Result<Value> {
+    exec(data, &MathOperationsType::Asinh)
+}
+
+pub(crate) fn atan(data: Value) -> Result<Value> {
+    exec(data, &MathOperationsType::Atan)
+}
+
+pub(crate) fn atanh(data: Value) -> Result<Value> {
+    exec(data, &MathOperationsType::Atanh)
+}
+
+pub(crate) fn cos(data: Value) -> Result<Value> {
+    exec(data, &MathOperationsType::Cos)
+}
+
+pub(crate) fn cosh(data: Value) -> Result<Value> {
+    exec(data, &MathOperationsType::Cosh)
+}
+
+pub(crate) fn deg(data: Value) -> Result<Value> {
+    exec(data, &MathOperationsType::Deg)
+}
+
+pub(crate) fn rad(data: Value) -> Result<Value> {
+    exec(data, &MathOperationsType::Rad)
+}
+
+pub(crate) fn sin(data: Value) -> Result<Value> {
+    exec(data, &MathOperationsType::Sin)
+}
+
+pub(crate) fn sinh(data: Value) -> Result<Value> {
+    exec(data, &MathOperationsType::Sinh)
+}
+
+pub(crate) fn tan(data: Value) -> Result<Value> {
+    exec(data, &MathOperationsType::Tan)
+}
+
+pub(crate) fn tanh(data: Value) -> Result<Value> {
+    exec(data, &MathOperationsType::Tanh)
+}
+
 fn exec(data: Value, op: &MathOperationsType) -> Result<Value> {
     match data {
         Value::Matrix(matrix) => {
@@ -111,6 +195,7 @@ fn exec(data: Value, op: &MathOperationsType) -> Result<Value> {
                         samples,
                         exemplars: range_value.exemplars,
                         time_window: range_value.time_window,
+                        histograms: vec![],
                     }
                 })
                 .collect();
@@ -144,6 +229,7 @@ mod tests {
                     range: Duration::from_secs(5),
                     offset: Duration::ZERO,
                 }),
+                histograms: vec![],
             })
             .collect();
         Value::Matrix(range_values)
@@ -351,6 +437,27 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_trigonometric_operations() {
+        use std::f64::consts::{FRAC_PI_2, PI};
+
+        assert_eq!(MathOperationsType::Sin.apply(0.0), 0.0);
+        assert_eq!(MathOperationsType::Cos.apply(0.0), 1.0);
+        assert_eq!(MathOperationsType::Tan.apply(0.0), 0.0);
+        assert_eq!(MathOperationsType::Asin.apply(1.0), FRAC_PI_2);
+        assert_eq!(MathOperationsType::Acos.apply(1.0), 0.0);
+        assert!(MathOperationsType::Acos.apply(2.0).is_nan());
+        assert_eq!(MathOperationsType::Atan.apply(0.0), 0.0);
+        assert_eq!(MathOperationsType::Sinh.apply(0.0), 0.0);
+        assert_eq!(MathOperationsType::Cosh.apply(0.0), 1.0);
+        assert_eq!(MathOperationsType::Tanh.apply(0.0), 0.0);
+        assert_eq!(MathOperationsType::Asinh.apply(0.0), 0.0);
+        assert_eq!(MathOperationsType::Acosh.apply(1.0), 0.0);
+        assert_eq!(MathOperationsType::Atanh.apply(1.0), f64::INFINITY);
+        assert_eq!(MathOperationsType::Deg.apply(PI), 180.0);
+        assert_eq!(MathOperationsType::Rad.apply(180.0), PI);
+    }
+
     #[test]
     fn test_none_value() {
         let value = Value::None;
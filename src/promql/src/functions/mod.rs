@@ -13,9 +13,15 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
-use std::{collections::HashSet, sync::LazyLock as Lazy, time::Duration};
+use std::{
+    collections::HashSet,
+    sync::{Arc, LazyLock as Lazy},
+    time::Duration,
+};
 
-use config::meta::promql::value::{EvalContext, LabelsExt, RangeValue, Sample, Value};
+use config::meta::promql::value::{
+    EvalContext, HistogramSample, LabelsExt, NativeHistogram, RangeValue, Sample, Value,
+};
 use datafusion::error::{DataFusionError, Result};
 use rayon::prelude::*;
 use strum::EnumString;
@@ -34,14 +40,17 @@ mod histogram;
 mod holt_winters;
 mod idelta;
 mod increase;
+mod info;
 mod irate;
 mod label_join;
 mod label_replace;
 mod last_over_time;
+mod mad_over_time;
 mod math_operations;
 mod max_over_time;
 mod min_over_time;
 mod predict_linear;
+mod present_over_time;
 mod quantile_over_time;
 mod rate;
 mod resets;
@@ -50,6 +59,7 @@ mod stddev_over_time;
 mod stdvar_over_time;
 mod sum_over_time;
 mod time_operations;
+mod ts_of_over_time;
 mod vector;
 
 pub(crate) use absent::absent;
@@ -60,18 +70,24 @@ pub(crate) use clamp::clamp;
 pub(crate) use count_over_time::count_over_time;
 pub(crate) use delta::delta;
 pub(crate) use deriv::deriv;
-pub(crate) use histogram::histogram_quantile;
-pub(crate) use holt_winters::holt_winters;
+pub(crate) use histogram::{
+    histogram_avg, histogram_count, histogram_fraction, histogram_quantile, histogram_stddev,
+    histogram_stdvar, histogram_sum,
+};
+pub(crate) use holt_winters::{double_exponential_smoothing, holt_winters};
 pub(crate) use idelta::idelta;
 pub(crate) use increase::increase;
+pub(crate) use info::info;
 pub(crate) use irate::irate;
 pub(crate) use label_join::label_join;
 pub(crate) use label_replace::label_replace;
 pub(crate) use last_over_time::last_over_time;
+pub(crate) use mad_over_time::mad_over_time;
 pub(crate) use math_operations::*;
 pub(crate) use max_over_time::max_over_time;
 pub(crate) use min_over_time::min_over_time;
 pub(crate) use predict_linear::predict_linear;
+pub(crate) use present_over_time::present_over_time;
 pub(crate) use quantile_over_time::quantile_over_time;
 pub(crate) use rate::rate;
 pub(crate) use resets::resets;
@@ -80,6 +96,7 @@ pub(crate) use stddev_over_time::stddev_over_time;
 pub(crate) use stdvar_over_time::stdvar_over_time;
 pub(crate) use sum_over_time::sum_over_time;
 pub(crate) use time_operations::*;
+pub(crate) use ts_of_over_time::{ts_of_last_over_time, ts_of_max_over_time, ts_of_min_over_time};
 pub(crate) use vector::vector;
 
 /// Reference: https://prometheus.io/docs/prometheus/latest/querying/functions/
@@ -89,29 +106,43 @@ pub(crate) enum Func {
     Abs,
     Absent,
     AbsentOverTime,
+    Acos,
+    Acosh,
+    Asin,
+    Asinh,
+    Atan,
+    Atanh,
     AvgOverTime,
     Ceil,
     Changes,
     Clamp,
     ClampMax,
     ClampMin,
+    Cos,
+    Cosh,
     CountOverTime,
     DayOfMonth,
     DayOfWeek,
     DayOfYear,
     DaysInMonth,
+    Deg,
     Delta,
     Deriv,
+    DoubleExponentialSmoothing,
     Exp,
     Floor,
+    HistogramAvg,
     HistogramCount,
     HistogramFraction,
     HistogramQuantile,
+    HistogramStddev,
+    HistogramStdvar,
     HistogramSum,
     HoltWinters,
     Hour,
     Idelta,
     Increase,
+    Info,
     Irate,
     LabelJoin,
     LabelReplace,
@@ -119,25 +150,38 @@ pub(crate) enum Func {
     Ln,
     Log10,
     Log2,
+    MadOverTime,
     MaxOverTime,
     MinOverTime,
     Minute,
     Month,
+    Pi,
     PredictLinear,
+    PresentOverTime,
     QuantileOverTime,
+    Rad,
     Rate,
     Resets,
     Round,
     Scalar,
     Sgn,
+    Sin,
+    Sinh,
     Sort,
+    SortByLabel,
+    SortByLabelDesc,
     SortDesc,
     Sqrt,
     StddevOverTime,
     StdvarOverTime,
     SumOverTime,
+    Tan,
+    Tanh,
     Time,
     Timestamp,
+    TsOfLastOverTime,
+    TsOfMaxOverTime,
+    TsOfMinOverTime,
     Vector,
     Year,
 }
@@ -208,6 +252,19 @@ pub trait RangeFunc: Sync {
     /// * `None` - If the function cannot produce a value (e.g., insufficient samples, invalid data,
     ///   or the result should be omitted)
     fn exec(&self, samples: &[Sample], eval_ts: i64, range: &Duration) -> Option<f64>;
+
+    /// Executes the range function on native histogram samples within a time window.
+    ///
+    /// Functions that are undefined for native histograms keep the default, which drops
+    /// the histogram samples of the series.
+    fn exec_histogram(
+        &self,
+        _samples: &[HistogramSample],
+        _eval_ts: i64,
+        _range: &Duration,
+    ) -> Option<NativeHistogram> {
+        None
+    }
 }
 
 pub(crate) fn eval_range<F>(data: Value, func: F, eval_ctx: &EvalContext) -> Result<Value>
@@ -254,8 +311,11 @@ where
             let range = time_window.range;
             let range_micros = micros(range);
             let mut result_samples = Vec::with_capacity(timestamps.len());
+            let mut result_histograms = Vec::new();
             let mut start_index = 0;
             let mut end_index = 0;
+            let mut hist_start_index = 0;
+            let mut hist_end_index = 0;
 
             // For each eval timestamp, compute the function value
             for &eval_ts in &timestamps {
@@ -271,21 +331,37 @@ where
                     &mut end_index,
                 );
 
-                if window_samples.is_empty() {
-                    continue;
+                if !window_samples.is_empty()
+                    && let Some(value) = func.exec(window_samples, eval_ts, &range)
+                {
+                    result_samples.push(Sample::new(eval_ts, value));
                 }
 
-                if let Some(value) = func.exec(window_samples, eval_ts, &range) {
-                    result_samples.push(Sample::new(eval_ts, value));
+                if metric.histograms.is_empty() {
+                    continue;
+                }
+                let window_histograms = advance_window(
+                    &metric.histograms,
+                    |h| h.timestamp,
+                    window_start,
+                    window_end,
+                    &mut hist_start_index,
+                    &mut hist_end_index,
+                );
+                if !window_histograms.is_empty()
+                    && let Some(h) = func.exec_histogram(window_histograms, eval_ts, &range)
+                {
+                    result_histograms.push(HistogramSample::new(eval_ts, Arc::new(h)));
                 }
             }
 
-            if !result_samples.is_empty() {
+            if !result_samples.is_empty() || !result_histograms.is_empty() {
                 Some(RangeValue {
                     labels,
                     samples: result_samples,
                     exemplars: None,
                     time_window: metric.time_window,
+                    histograms: result_histograms,
                 })
             } else {
                 None
@@ -312,16 +388,34 @@ fn advance_sample_window<'a>(
     start_index: &mut usize,
     end_index: &mut usize,
 ) -> &'a [Sample] {
-    while *start_index < samples.len() && samples[*start_index].timestamp < window_start {
+    advance_window(
+        samples,
+        |s| s.timestamp,
+        window_start,
+        window_end,
+        start_index,
+        end_index,
+    )
+}
+
+fn advance_window<'a, T>(
+    items: &'a [T],
+    timestamp: impl Fn(&T) -> i64,
+    window_start: i64,
+    window_end: i64,
+    start_index: &mut usize,
+    end_index: &mut usize,
+) -> &'a [T] {
+    while *start_index < items.len() && timestamp(&items[*start_index]) < window_start {
         *start_index += 1;
     }
     if *end_index < *start_index {
         *end_index = *start_index;
     }
-    while *end_index < samples.len() && samples[*end_index].timestamp <= window_end {
+    while *end_index < items.len() && timestamp(&items[*end_index]) <= window_end {
         *end_index += 1;
     }
-    &samples[*start_index..*end_index]
+    &items[*start_index..*end_index]
 }
 
 #[cfg(test)]
@@ -337,8 +431,27 @@ mod tests {
             "histogram_quantile".parse::<Func>().unwrap(),
             Func::HistogramQuantile
         );
+        assert_eq!(
+            "histogram_stddev".parse::<Func>().unwrap(),
+            Func::HistogramStddev
+        );
         assert_eq!("label_join".parse::<Func>().unwrap(), Func::LabelJoin);
         assert_eq!("label_replace".parse::<Func>().unwrap(), Func::LabelReplace);
+        assert_eq!("atan".parse::<Func>().unwrap(), Func::Atan);
+        assert_eq!("pi".parse::<Func>().unwrap(), Func::Pi);
+        assert_eq!(
+            "sort_by_label_desc".parse::<Func>().unwrap(),
+            Func::SortByLabelDesc
+        );
+        assert_eq!(
+            "double_exponential_smoothing".parse::<Func>().unwrap(),
+            Func::DoubleExponentialSmoothing
+        );
+        assert_eq!(
+            "ts_of_max_over_time".parse::<Func>().unwrap(),
+            Func::TsOfMaxOverTime
+        );
+        assert_eq!("info".parse::<Func>().unwrap(), Func::Info);
     }
 
     #[test]
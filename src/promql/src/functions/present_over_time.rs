@@ -0,0 +1,63 @@
+// Copyright 2026 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::time::Duration;
+
+use config::meta::promql::value::{EvalContext, Sample, Value};
+use datafusion::error::Result;
+
+use crate::functions::RangeFunc;
+
+/// https://prometheus.io/docs/prometheus/latest/querying/functions/#aggregation_over_time
+pub(crate) fn present_over_time(data: Value, eval_ctx: &EvalContext) -> Result<Value> {
+    super::eval_range(data, PresentOverTimeFunc::new(), eval_ctx)
+}
+
+pub struct PresentOverTimeFunc;
+
+impl PresentOverTimeFunc {
+    pub fn new() -> Self {
+        PresentOverTimeFunc {}
+    }
+}
+
+impl RangeFunc for PresentOverTimeFunc {
+    fn name(&self) -> &'static str {
+        "present_over_time"
+    }
+
+    fn exec(&self, samples: &[Sample], _eval_ts: i64, _range: &Duration) -> Option<f64> {
+        (!samples.is_empty()).then_some(1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_present_over_time() {
+        let func = PresentOverTimeFunc::new();
+        assert!(func.exec(&[], 0, &Duration::from_secs(60)).is_none());
+        let samples = [
+            Sample::new(1_000_000, 5.0),
+            Sample::new(2_000_000, f64::NAN),
+        ];
+        assert_eq!(
+            func.exec(&samples, 2_000_000, &Duration::from_secs(60)),
+            Some(1.0)
+        );
+    }
+}
@@ -16,7 +16,8 @@
 use std::time::Duration;
 
 use config::meta::promql::value::{
-    EvalContext, ExtrapolationKind, Sample, Value, extrapolated_rate,
+    EvalContext, ExtrapolationKind, HistogramSample, NativeHistogram, Sample, Value,
+    extrapolated_histogram_rate, extrapolated_rate,
 };
 use datafusion::error::Result;
 
@@ -48,6 +49,15 @@ impl RangeFunc for RateFunc {
             ExtrapolationKind::Rate,
         )
     }
+
+    fn exec_histogram(
+        &self,
+        samples: &[HistogramSample],
+        eval_ts: i64,
+        range: &Duration,
+    ) -> Option<NativeHistogram> {
+        extrapolated_histogram_rate(samples, eval_ts, *range, ExtrapolationKind::Rate)
+    }
 }
 
 #[cfg(test)]
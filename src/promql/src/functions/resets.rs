@@ -66,6 +66,7 @@ mod tests {
             exemplars: None,
             time_window: None,
             labels: Default::default(),
+            histograms: vec![],
         }
     }
 
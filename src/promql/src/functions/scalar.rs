@@ -32,6 +32,7 @@ pub(crate) fn scalar(data: Value, eval_ctx: &EvalContext) -> Result<Value> {
                 samples,
                 exemplars: None,
                 time_window: None,
+                histograms: vec![],
             }]))
         }
         _ => Err(DataFusionError::Plan(
@@ -117,6 +117,7 @@ pub(crate) fn timestamp(data: Value) -> Result<Value> {
                         samples,
                         exemplars: range_value.exemplars,
                         time_window: range_value.time_window,
+                        histograms: vec![],
                     }
                 })
                 .collect();
@@ -151,6 +152,7 @@ fn exec(data: Value, op: &TimeOperationType) -> Result<Value> {
                         samples,
                         exemplars: range_value.exemplars,
                         time_window: range_value.time_window,
+                        histograms: vec![],
                     }
                 })
                 .collect();
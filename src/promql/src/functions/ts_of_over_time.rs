@@ -0,0 +1,104 @@
+// Copyright 2026 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::time::Duration;
+
+use config::meta::promql::value::{EvalContext, Sample, Value};
+use datafusion::error::Result;
+
+use crate::functions::RangeFunc;
+
+/// https://prometheus.io/docs/prometheus/latest/querying/functions/#aggregation_over_time
+pub(crate) fn ts_of_max_over_time(data: Value, eval_ctx: &EvalContext) -> Result<Value> {
+    super::eval_range(data, TsOfOverTimeFunc::Max, eval_ctx)
+}
+
+pub(crate) fn ts_of_min_over_time(data: Value, eval_ctx: &EvalContext) -> Result<Value> {
+    super::eval_range(data, TsOfOverTimeFunc::Min, eval_ctx)
+}
+
+pub(crate) fn ts_of_last_over_time(data: Value, eval_ctx: &EvalContext) -> Result<Value> {
+    super::eval_range(data, TsOfOverTimeFunc::Last, eval_ctx)
+}
+
+/// The timestamp, in seconds, of the last sample in the window holding the
+/// maximum/minimum value, or of the last sample at all. NaN only wins when every
+/// sample is NaN, like `max_over_time`/`min_over_time`.
+pub enum TsOfOverTimeFunc {
+    Max,
+    Min,
+    Last,
+}
+
+impl RangeFunc for TsOfOverTimeFunc {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Max => "ts_of_max_over_time",
+            Self::Min => "ts_of_min_over_time",
+            Self::Last => "ts_of_last_over_time",
+        }
+    }
+
+    fn exec(&self, samples: &[Sample], _eval_ts: i64, _range: &Duration) -> Option<f64> {
+        let mut selected: Option<&Sample> = None;
+        for sample in samples {
+            let replace = match (self, selected) {
+                (_, None) | (Self::Last, _) => true,
+                (_, Some(best)) if best.value.is_nan() => true,
+                (_, Some(_)) if sample.value.is_nan() => false,
+                (Self::Max, Some(best)) => sample.value >= best.value,
+                (Self::Min, Some(best)) => sample.value <= best.value,
+            };
+            if replace {
+                selected = Some(sample);
+            }
+        }
+        selected.map(|s| s.timestamp as f64 / 1_000_000.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ts_of_over_time() {
+        let range = Duration::from_secs(60);
+        let samples = [
+            Sample::new(10_000_000, 3.0),
+            Sample::new(20_000_000, 1.0),
+            Sample::new(30_000_000, 3.0),
+            Sample::new(40_000_000, f64::NAN),
+            Sample::new(50_000_000, 2.0),
+        ];
+        assert_eq!(
+            TsOfOverTimeFunc::Max.exec(&samples, 60_000_000, &range),
+            Some(30.0)
+        );
+        assert_eq!(
+            TsOfOverTimeFunc::Min.exec(&samples, 60_000_000, &range),
+            Some(20.0)
+        );
+        assert_eq!(
+            TsOfOverTimeFunc::Last.exec(&samples, 60_000_000, &range),
+            Some(50.0)
+        );
+        assert!(
+            TsOfOverTimeFunc::Max
+                .exec(&[], 60_000_000, &range)
+                .is_none()
+        );
+    }
+}
@@ -39,6 +39,7 @@ pub(crate) fn vector(data: Value, eval_ctx: &EvalContext) -> Result<Value> {
         samples,
         exemplars: None,
         time_window: None,
+        histograms: vec![],
     };
 
     Ok(Value::Matrix(vec![range_value]))
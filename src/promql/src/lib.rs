@@ -37,8 +37,10 @@ pub mod common;
 pub mod engine;
 pub mod exec;
 mod functions;
-mod load_series;
+pub mod load_series;
 pub mod promql;
+#[cfg(test)]
+mod promqltest;
 pub mod utils;
 
 pub const DEFAULT_LOOKBACK: Duration = Duration::from_secs(300); // 5m
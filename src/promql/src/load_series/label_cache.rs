@@ -13,7 +13,8 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
-//! Process-wide cache used while loading PromQL series labels.
+//! Process-wide cache used while loading PromQL series labels, and the
+//! active series of every metric seen by the ingest path.
 
 use std::sync::{Arc, LazyLock as Lazy};
 
@@ -21,7 +22,7 @@ use config::{
     meta::promql::value::Labels,
     utils::hash::{Sum64, gxhash},
 };
-use hashbrown::HashSet;
+use hashbrown::{HashMap, HashSet};
 use hashlink::lru_cache::LruCache;
 use parking_lot::Mutex;
 use rayon::iter::{IntoParallelRefMutIterator, ParallelIterator};
@@ -79,6 +80,103 @@ static LABEL_CACHE: Lazy<LabelCache> = Lazy::new(|| {
     LabelCache::new(max_bytes, shard_count)
 });
 
+static ACTIVE_SERIES: Lazy<ActiveSeries> = Lazy::new(|| {
+    let cfg = config::get_config();
+    ActiveSeries::new(
+        cfg.limit.metrics_active_series_window * 1_000_000,
+        (cfg.limit.cpu_num * 4)
+            .next_power_of_two()
+            .clamp(MIN_SHARDS, MAX_SHARDS),
+    )
+});
+
+/// Series of each metric written within the active window, keyed by the same
+/// context fingerprint as the label cache, so the ingest-time series limits
+/// know whether a sample starts a new series. Every metric keeps its series in
+/// LRU order of their last sample, so expired ones are evicted from the front.
+struct ActiveSeries {
+    shards: Vec<Mutex<HashMap<u64, LruCache<u64, i64>>>>,
+    shard_mask: u64,
+    window: i64,
+}
+
+/// Records a sample of series `series_hash` of a metric at `now`
+/// (microseconds). Returns false, recording nothing, when it would be a new
+/// series beyond `limit` active ones; 0 is unlimited.
+pub fn admit_series(
+    org_id: &str,
+    stream_name: &str,
+    series_hash: u64,
+    limit: usize,
+    now: i64,
+) -> bool {
+    let metric_fp = context_fingerprint(org_id, stream_name, &[]);
+    ACTIVE_SERIES.admit(metric_fp, series_hash, limit, now)
+}
+
+/// Number of active series of a metric at `now` (microseconds).
+pub fn active_series(org_id: &str, stream_name: &str, now: i64) -> usize {
+    let metric_fp = context_fingerprint(org_id, stream_name, &[]);
+    ACTIVE_SERIES.count(metric_fp, now)
+}
+
+impl ActiveSeries {
+    fn new(window: i64, shard_count: usize) -> Self {
+        debug_assert!(shard_count.is_power_of_two());
+        Self {
+            shards: (0..shard_count)
+                .map(|_| Mutex::new(HashMap::new()))
+                .collect(),
+            shard_mask: shard_count as u64 - 1,
+            window,
+        }
+    }
+
+    fn shard(&self, metric_fp: u64) -> &Mutex<HashMap<u64, LruCache<u64, i64>>> {
+        &self.shards[(metric_fp & self.shard_mask) as usize]
+    }
+
+    fn admit(&self, metric_fp: u64, series_hash: u64, limit: usize, now: i64) -> bool {
+        let mut shard = self.shard(metric_fp).lock();
+        let series = shard
+            .entry(metric_fp)
+            .or_insert_with(LruCache::new_unbounded);
+        if let Some(seen) = series.get_mut(&series_hash) {
+            *seen = now;
+            return true;
+        }
+        self.expire(series, now);
+        if limit > 0 && series.len() >= limit {
+            return false;
+        }
+        series.insert(series_hash, now);
+        true
+    }
+
+    fn count(&self, metric_fp: u64, now: i64) -> usize {
+        let mut shard = self.shard(metric_fp).lock();
+        let Some(series) = shard.get_mut(&metric_fp) else {
+            return 0;
+        };
+        self.expire(series, now);
+        let count = series.len();
+        if count == 0 {
+            shard.remove(&metric_fp);
+        }
+        count
+    }
+
+    fn expire(&self, series: &mut LruCache<u64, i64>, now: i64) {
+        while series
+            .iter()
+            .next()
+            .is_some_and(|(_, seen)| *seen + self.window < now)
+        {
+            series.remove_lru();
+        }
+    }
+}
+
 /// The series a query still has to recover from a label scan after the cache
 /// was consulted, and the cache decisions that follow from them.
 pub(super) struct CacheMisses {
@@ -318,6 +416,26 @@ mod tests {
         )
     }
 
+    #[test]
+    fn test_active_series_limit_and_expiry() {
+        let active = ActiveSeries::new(100, 32);
+        let metric = context_fingerprint("org", "http_requests_total", &[]);
+        assert!(active.admit(metric, 1, 2, 0));
+        assert!(active.admit(metric, 2, 2, 10));
+        // a third series is over the limit, known ones are still admitted
+        assert!(!active.admit(metric, 3, 2, 20));
+        assert!(active.admit(metric, 1, 2, 50));
+        assert_eq!(active.count(metric, 50), 2);
+        // series 2 expired, which makes room for series 3
+        assert!(active.admit(metric, 3, 2, 120));
+        assert_eq!(active.count(metric, 120), 2);
+        // other metrics have their own limit
+        let other = context_fingerprint("org", "up", &[]);
+        assert!(active.admit(other, 3, 1, 120));
+        assert!(active.admit(other, 4, 0, 120));
+        assert_eq!(active.count(metric, 1000), 0);
+    }
+
     #[test]
     fn test_label_cache_get_put() {
         let cache = LabelCache::new(1024 * 1024, TEST_SHARDS);
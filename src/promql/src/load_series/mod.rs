@@ -15,7 +15,7 @@
 
 //! Loads PromQL series samples, exemplars, and labels from DataFusion.
 
-mod label_cache;
+pub mod label_cache;
 mod labels;
 mod load_labels;
 
@@ -24,8 +24,12 @@ use std::sync::Arc;
 use config::{
     TIMESTAMP_COL_NAME,
     meta::promql::{
-        EXEMPLARS_LABEL, HASH_LABEL, VALUE_LABEL,
-        value::{Exemplar, Label, Labels, QueryContext, RangeValue, Sample},
+        EXEMPLARS_LABEL, HASH_LABEL, NATIVE_HISTOGRAM_LABEL, VALUE_LABEL,
+        histogram::STALE_NAN_BITS,
+        value::{
+            Exemplar, HistogramSample, Label, Labels, NativeHistogram, QueryContext, RangeValue,
+            Sample,
+        },
     },
     utils::{
         hash::{Sum64, gxhash},
@@ -34,7 +38,7 @@ use config::{
 };
 use datafusion::{
     arrow::{
-        array::{Float64Array, Int64Array, StringArray, UInt64Array},
+        array::{Array, Float64Array, Int64Array, StringArray, UInt64Array},
         datatypes::{DataType, Schema},
     },
     error::{DataFusionError, Result},
@@ -167,7 +171,11 @@ pub(super) async fn selector_load_data_from_datafusion(
         .iter()
         .filter_map(|field| {
             let name = field.name();
-            if name == TIMESTAMP_COL_NAME || name == VALUE_LABEL || name == EXEMPLARS_LABEL {
+            if name == TIMESTAMP_COL_NAME
+                || name == VALUE_LABEL
+                || name == EXEMPLARS_LABEL
+                || name == NATIVE_HISTOGRAM_LABEL
+            {
                 None
             } else {
                 Some(name.to_string())
@@ -180,7 +188,14 @@ pub(super) async fn selector_load_data_from_datafusion(
     // get hash & timestamp
     let start1 = std::time::Instant::now();
     let hash_field_type = schema.field_with_name(HASH_LABEL)?.data_type();
-    let (metrics, timestamp_set) = if query_ctx.query_exemplars {
+    // a sharded query only reads the series of its shard, numeric hashes are
+    // filtered in the scan, string hashes once they are fingerprinted
+    if let Some(shard) = query_ctx.shard
+        && hash_field_type == &DataType::UInt64
+    {
+        df_group = df_group.filter((col(HASH_LABEL) % lit(shard.count)).eq(lit(shard.index)))?;
+    }
+    let (mut metrics, timestamp_set) = if query_ctx.query_exemplars {
         load_exemplars_from_datafusion(
             &query_ctx.trace_id,
             hash_field_type,
@@ -197,6 +212,13 @@ pub(super) async fn selector_load_data_from_datafusion(
         )
         .await?
     };
+    if let Some(shard) = query_ctx.shard
+        && hash_field_type != &DataType::UInt64
+    {
+        metrics
+            .iter_mut()
+            .for_each(|m| m.retain(|hash, _| shard.contains(*hash)));
+    }
     let metrics_count = metrics.iter().map(HashMap::len).sum::<usize>();
 
     log::info!(
@@ -249,10 +271,23 @@ pub(super) async fn load_samples_from_datafusion(
 ) -> Result<(PartitionedMetrics, HashSet<i64>)> {
     let ctx = Arc::new(df.task_ctx());
     let target_partitions = ctx.session_config().target_partitions();
-    let plan = df
-        .select_columns(&[TIMESTAMP_COL_NAME, HASH_LABEL, VALUE_LABEL])?
-        .create_physical_plan()
-        .await?;
+    // streams that received native histograms carry them in an extra column
+    let with_histograms = df
+        .schema()
+        .as_arrow()
+        .field_with_name(NATIVE_HISTOGRAM_LABEL)
+        .is_ok();
+    let columns = if with_histograms {
+        vec![
+            TIMESTAMP_COL_NAME,
+            HASH_LABEL,
+            VALUE_LABEL,
+            NATIVE_HISTOGRAM_LABEL,
+        ]
+    } else {
+        vec![TIMESTAMP_COL_NAME, HASH_LABEL, VALUE_LABEL]
+    };
+    let plan = df.select_columns(&columns)?.create_physical_plan().await?;
     let schema = plan.schema();
     let plan = Arc::new(RepartitionExec::try_new(
         plan,
@@ -290,6 +325,9 @@ pub(super) async fn load_samples_from_datafusion(
                             .as_any()
                             .downcast_ref::<Float64Array>()
                             .unwrap();
+                        let histogram_values = batch
+                            .column_by_name(NATIVE_HISTOGRAM_LABEL)
+                            .and_then(|col| col.as_any().downcast_ref::<StringArray>());
 
                         if hash_field_type == DataType::UInt64 {
                             let hash_values = batch
@@ -306,10 +344,15 @@ pub(super) async fn load_samples_from_datafusion(
                                     samples: vec![],
                                     exemplars: None,
                                     time_window: None,
+                                    histograms: vec![],
                                 });
-                                entry
-                                    .samples
-                                    .push(Sample::new(timestamp, value_values.value(i)));
+                                push_sample(
+                                    entry,
+                                    timestamp,
+                                    sample_value(value_values, i),
+                                    histogram_values,
+                                    i,
+                                );
                             }
                         } else {
                             let hash_values = batch
@@ -326,10 +369,15 @@ pub(super) async fn load_samples_from_datafusion(
                                     samples: vec![],
                                     exemplars: None,
                                     time_window: None,
+                                    histograms: vec![],
                                 });
-                                entry
-                                    .samples
-                                    .push(Sample::new(timestamp, value_values.value(i)));
+                                push_sample(
+                                    entry,
+                                    timestamp,
+                                    sample_value(value_values, i),
+                                    histogram_values,
+                                    i,
+                                );
                             }
                         }
                     }
@@ -343,8 +391,12 @@ pub(super) async fn load_samples_from_datafusion(
             let mut unique_timestamps = HashSet::new();
             if collect_timestamps {
                 for metric in metrics.values() {
-                    if let Some(max_timestamp) =
-                        metric.samples.iter().map(|sample| sample.timestamp).max()
+                    if let Some(max_timestamp) = metric
+                        .samples
+                        .iter()
+                        .map(|sample| sample.timestamp)
+                        .chain(metric.histograms.iter().map(|h| h.timestamp))
+                        .max()
                     {
                         unique_timestamps.insert(max_timestamp);
                     }
@@ -368,6 +420,38 @@ pub(super) async fn load_samples_from_datafusion(
     Ok((metrics, all_unique_timestamps))
 }
 
+/// The float value of row `i`, a null value is a stored stale marker.
+fn sample_value(values: &Float64Array, i: usize) -> f64 {
+    if values.is_null(i) {
+        f64::from_bits(STALE_NAN_BITS)
+    } else {
+        values.value(i)
+    }
+}
+
+/// Appends row `i` as a native histogram sample when it carries one, as a float
+/// sample otherwise.
+fn push_sample(
+    entry: &mut RangeValue,
+    timestamp: i64,
+    value: f64,
+    histograms: Option<&StringArray>,
+    i: usize,
+) {
+    if let Some(histograms) = histograms
+        && histograms.is_valid(i)
+    {
+        match json::from_str::<NativeHistogram>(histograms.value(i)) {
+            Ok(histogram) => entry
+                .histograms
+                .push(HistogramSample::new(timestamp, Arc::new(histogram))),
+            Err(e) => log::warn!("skip invalid native histogram at {timestamp}: {e}"),
+        }
+        return;
+    }
+    entry.samples.push(Sample::new(timestamp, value));
+}
+
 async fn load_exemplars_from_datafusion(
     trace_id: &str,
     hash_field_type: &DataType,
@@ -429,6 +513,7 @@ async fn load_exemplars_from_datafusion(
                                         samples: vec![],
                                         exemplars: Some(vec![]),
                                         time_window: None,
+                                        histograms: vec![],
                                     });
                                     let entry = entry.exemplars.as_mut().unwrap();
                                     for exemplar in exemplars {
@@ -455,6 +540,7 @@ async fn load_exemplars_from_datafusion(
                                         samples: vec![],
                                         exemplars: Some(vec![]),
                                         time_window: None,
+                                        histograms: vec![],
                                     });
                                     let entry = entry.exemplars.as_mut().unwrap();
                                     for exemplar in exemplars {
@@ -588,6 +674,48 @@ mod tests {
         assert_eq!(metrics[&22].samples.len(), 1);
     }
 
+    #[tokio::test]
+    async fn test_load_samples_reads_native_histograms() {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new(TIMESTAMP_COL_NAME, DataType::Int64, false),
+            Field::new(HASH_LABEL, DataType::UInt64, false),
+            Field::new(VALUE_LABEL, DataType::Float64, false),
+            Field::new(NATIVE_HISTOGRAM_LABEL, DataType::Utf8, true),
+        ]));
+        let histogram = json::to_string(&NativeHistogram {
+            count: 3.0,
+            sum: 4.5,
+            positive: vec![(0, 1.0), (1, 2.0)],
+            ..Default::default()
+        })
+        .unwrap();
+        let batch = RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::new(Int64Array::from(vec![100, 200])),
+                Arc::new(UInt64Array::from(vec![11, 11])),
+                Arc::new(Float64Array::from(vec![1.0, 3.0])),
+                Arc::new(StringArray::from(vec![None, Some(histogram.as_str())])),
+            ],
+        )
+        .unwrap();
+        let ctx = SessionContext::new_with_config(SessionConfig::new().with_target_partitions(1));
+        let df = ctx.read_batch(batch).unwrap();
+
+        let (metrics, timestamps) =
+            load_samples_from_datafusion("test", &DataType::UInt64, df, true)
+                .await
+                .unwrap();
+        let metrics = merge_partitioned_metrics(metrics);
+
+        assert_eq!(timestamps, HashSet::from([200]));
+        assert_eq!(metrics[&11].samples.len(), 1);
+        assert_eq!(metrics[&11].samples[0].timestamp, 100);
+        assert_eq!(metrics[&11].histograms.len(), 1);
+        assert_eq!(metrics[&11].histograms[0].timestamp, 200);
+        assert_eq!(metrics[&11].histograms[0].histogram.count, 3.0);
+    }
+
     #[tokio::test]
     async fn test_load_exemplars_returns_max_timestamp_per_series() {
         let schema = Arc::new(Schema::new(vec![
@@ -34,7 +34,7 @@ const LABEL_DROPPING_AGGS: [u8; 8] = [
 /// Functions that neither read nor create label values — they only transform
 /// per-series samples. Anything label-sensitive (`label_replace`,
 /// `histogram_quantile`, `absent`, ...) must NOT be listed here.
-const LABEL_AGNOSTIC_FUNCS: [&str; 32] = [
+const LABEL_AGNOSTIC_FUNCS: [&str; 52] = [
     "rate",
     "irate",
     "increase",
@@ -52,8 +52,14 @@ const LABEL_AGNOSTIC_FUNCS: [&str; 32] = [
     "stddev_over_time",
     "stdvar_over_time",
     "quantile_over_time",
+    "mad_over_time",
+    "present_over_time",
+    "ts_of_max_over_time",
+    "ts_of_min_over_time",
+    "ts_of_last_over_time",
     "predict_linear",
     "holt_winters",
+    "double_exponential_smoothing",
     "abs",
     "ceil",
     "floor",
@@ -67,6 +73,20 @@ const LABEL_AGNOSTIC_FUNCS: [&str; 32] = [
     "clamp",
     "clamp_max",
     "clamp_min",
+    "acos",
+    "acosh",
+    "asin",
+    "asinh",
+    "atan",
+    "atanh",
+    "cos",
+    "cosh",
+    "sin",
+    "sinh",
+    "tan",
+    "tanh",
+    "deg",
+    "rad",
 ];
 
 /// Returns true when the query's root aggregation discards all labels and the
@@ -15,5 +15,7 @@
 
 pub(crate) mod label_usage;
 pub mod name_visitor;
-pub(crate) mod rewrite;
+pub mod result_order;
+pub mod rewrite;
 pub(crate) mod selector_visitor;
+pub mod shard;
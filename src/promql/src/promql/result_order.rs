@@ -0,0 +1,199 @@
+// Copyright 2026 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//! Ordering of instant query results.
+//!
+//! Results are ordered by value by default. `sort`, `sort_desc`, `sort_by_label` and
+//! `sort_by_label_desc` at the root of the query decide the order instead; deeper in
+//! the expression they have no effect, like in Prometheus.
+
+use std::cmp::Ordering;
+
+use config::{
+    meta::promql::value::{InstantValue, LabelsExt, Value},
+    utils::sort::sort_float,
+};
+use promql_parser::parser::{Call, Expr, ParenExpr, StringLiteral};
+
+/// The order requested by a sorting function at the root of a query.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ResultOrder {
+    Value { desc: bool },
+    Labels { names: Vec<String>, desc: bool },
+}
+
+impl ResultOrder {
+    /// The order requested by `expr`, if it is a sorting function call.
+    pub fn from_expr(expr: &Expr) -> Option<Self> {
+        match expr {
+            Expr::Paren(ParenExpr { expr }) => Self::from_expr(expr),
+            Expr::Call(Call { func, args }) => {
+                let label_names = || {
+                    args.args
+                        .iter()
+                        .skip(1)
+                        .filter_map(|arg| match arg.as_ref() {
+                            Expr::StringLiteral(StringLiteral { val }) => Some(val.clone()),
+                            _ => None,
+                        })
+                        .collect()
+                };
+                match func.name {
+                    "sort" => Some(Self::Value { desc: false }),
+                    "sort_desc" => Some(Self::Value { desc: true }),
+                    "sort_by_label" => Some(Self::Labels {
+                        names: label_names(),
+                        desc: false,
+                    }),
+                    "sort_by_label_desc" => Some(Self::Labels {
+                        names: label_names(),
+                        desc: true,
+                    }),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
+    pub fn sort(&self, vector: &mut [InstantValue]) {
+        match self {
+            Self::Value { desc } => vector.sort_by(|a, b| {
+                let (a, b) = (a.sample.value, b.sample.value);
+                // NaN sorts last in both directions
+                match (a.is_nan(), b.is_nan()) {
+                    (true, true) => Ordering::Equal,
+                    (true, false) => Ordering::Greater,
+                    (false, true) => Ordering::Less,
+                    _ if *desc => sort_float(&b, &a),
+                    _ => sort_float(&a, &b),
+                }
+            }),
+            Self::Labels { names, desc } => vector.sort_by(|a, b| {
+                let ord = names
+                    .iter()
+                    .map(|name| natural_cmp(&a.labels.get_value(name), &b.labels.get_value(name)))
+                    .find(|ord| ord.is_ne())
+                    .unwrap_or_else(|| a.labels.partial_cmp(&b.labels).unwrap_or(Ordering::Equal));
+                if *desc { ord.reverse() } else { ord }
+            }),
+        }
+    }
+}
+
+/// Sorts the final result of `expr`: as requested by a root sorting function for
+/// instant queries, by [`Value::sort`] otherwise.
+pub fn sort_result(expr: &Expr, value: &mut Value) {
+    match (ResultOrder::from_expr(expr), value) {
+        (Some(order), Value::Vector(vector)) => order.sort(vector),
+        (_, value) => value.sort(),
+    }
+}
+
+/// Compares strings treating runs of digits as numbers, so `a2` sorts before `a10`.
+pub fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let (mut a, mut b) = (a, b);
+    loop {
+        match (a.chars().next(), b.chars().next()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(ca), Some(cb)) if ca.is_ascii_digit() && cb.is_ascii_digit() => {
+                let (na, rest_a) = split_digits(a);
+                let (nb, rest_b) = split_digits(b);
+                let (ta, tb) = (na.trim_start_matches('0'), nb.trim_start_matches('0'));
+                let ord = ta.len().cmp(&tb.len()).then_with(|| ta.cmp(tb));
+                if ord.is_ne() {
+                    return ord;
+                }
+                (a, b) = (rest_a, rest_b);
+            }
+            (Some(ca), Some(cb)) => {
+                if ca != cb {
+                    return ca.cmp(&cb);
+                }
+                (a, b) = (&a[ca.len_utf8()..], &b[cb.len_utf8()..]);
+            }
+        }
+    }
+}
+
+fn split_digits(s: &str) -> (&str, &str) {
+    let end = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    s.split_at(end)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use config::meta::promql::value::{Label, Sample};
+
+    use super::*;
+
+    fn instant(instance: &str, value: f64) -> InstantValue {
+        InstantValue {
+            labels: vec![Arc::new(Label::new("instance", instance))],
+            sample: Sample::new(0, value),
+            histogram: None,
+        }
+    }
+
+    fn instances(vector: &[InstantValue]) -> Vec<String> {
+        vector
+            .iter()
+            .map(|v| v.labels.get_value("instance"))
+            .collect()
+    }
+
+    #[test]
+    fn test_natural_cmp() {
+        assert_eq!(natural_cmp("a2", "a10"), Ordering::Less);
+        assert_eq!(natural_cmp("a10", "a10"), Ordering::Equal);
+        assert_eq!(natural_cmp("a010", "a9"), Ordering::Greater);
+        assert_eq!(natural_cmp("a", "a1"), Ordering::Less);
+        assert_eq!(natural_cmp("b", "a1"), Ordering::Greater);
+    }
+
+    #[test]
+    fn test_sort_result() {
+        let vector = || {
+            Value::Vector(vec![
+                instant("host10", 1.0),
+                instant("host2", f64::NAN),
+                instant("host1", 3.0),
+            ])
+        };
+        let sorted = |query: &str| {
+            let expr = promql_parser::parser::parse(query).unwrap();
+            let mut value = vector();
+            sort_result(&expr, &mut value);
+            match value {
+                Value::Vector(v) => instances(&v),
+                _ => unreachable!(),
+            }
+        };
+
+        assert_eq!(sorted("sort(up)"), ["host10", "host1", "host2"]);
+        assert_eq!(sorted("(sort_desc(up))"), ["host1", "host10", "host2"]);
+        assert_eq!(
+            sorted(r#"sort_by_label(up, "instance")"#),
+            ["host1", "host2", "host10"]
+        );
+        assert_eq!(
+            sorted(r#"sort_by_label_desc(up, "instance")"#),
+            ["host10", "host2", "host1"]
+        );
+    }
+}
@@ -13,10 +13,15 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
+use std::time::{Duration, UNIX_EPOCH};
+
 use config::get_config;
 use promql_parser::{
     label::{MatchOp, Matcher},
-    parser::VectorSelector,
+    parser::{
+        AggregateExpr, AtModifier, BinaryExpr, Call, Expr, MatrixSelector, ParenExpr, UnaryExpr,
+        VectorSelector,
+    },
 };
 
 struct RemoveFilterAllRewriter {}
@@ -54,6 +59,58 @@ pub fn remove_filter_all(vs: &mut VectorSelector) {
     RemoveFilterAllRewriter::new().rewrite(vs);
 }
 
+/// Replaces `@ start()` and `@ end()` with the absolute query boundaries,
+/// returns true when a modifier was replaced.
+pub fn resolve_at_modifiers(expr: &mut Expr, start: i64, end: i64) -> bool {
+    let resolve = |at: &mut Option<AtModifier>| {
+        let t = match at {
+            Some(AtModifier::Start) => start,
+            Some(AtModifier::End) => end,
+            _ => return false,
+        };
+        *at = Some(AtModifier::At(
+            UNIX_EPOCH + Duration::from_micros(t.max(0) as u64),
+        ));
+        true
+    };
+    match expr {
+        Expr::Aggregate(AggregateExpr { expr, param, .. }) => {
+            let changed = resolve_at_modifiers(expr, start, end);
+            match param {
+                Some(param) => resolve_at_modifiers(param, start, end) | changed,
+                None => changed,
+            }
+        }
+        Expr::Unary(UnaryExpr { expr }) | Expr::Paren(ParenExpr { expr }) => {
+            resolve_at_modifiers(expr, start, end)
+        }
+        Expr::Binary(BinaryExpr { lhs, rhs, .. }) => {
+            resolve_at_modifiers(lhs, start, end) | resolve_at_modifiers(rhs, start, end)
+        }
+        Expr::Subquery(subquery) => {
+            resolve(&mut subquery.at) | resolve_at_modifiers(&mut subquery.expr, start, end)
+        }
+        Expr::VectorSelector(vs) => resolve(&mut vs.at),
+        Expr::MatrixSelector(MatrixSelector { vs, .. }) => resolve(&mut vs.at),
+        Expr::Call(Call { args, .. }) => args.args.iter_mut().fold(false, |changed, arg| {
+            resolve_at_modifiers(arg, start, end) | changed
+        }),
+        Expr::NumberLiteral(_) | Expr::StringLiteral(_) | Expr::Extension(_) => false,
+    }
+}
+
+/// Returns the query with `@ start()` and `@ end()` pinned to the absolute
+/// query boundaries, `None` when there is nothing to pin. A range query is
+/// split between queriers and cache windows, each of them has to see the
+/// boundaries of the whole range.
+pub fn resolve_at_modifiers_in_query(query: &str, start: i64, end: i64) -> Option<String> {
+    if !query.contains('@') {
+        return None;
+    }
+    let mut expr = promql_parser::parser::parse(query).ok()?;
+    resolve_at_modifiers(&mut expr, start, end).then(|| expr.to_string())
+}
+
 #[cfg(test)]
 mod tests {
     use promql_parser::label::Matchers;
@@ -229,4 +286,34 @@ mod tests {
         assert_eq!(vs.matchers.matchers.len(), 0);
         assert_eq!(vs.matchers.or_matchers.len(), 0);
     }
+
+    #[test]
+    fn test_resolve_at_modifiers_in_query() {
+        let start = 1_600_000_000_000_000;
+        let end = 1_600_003_600_000_000;
+        assert_eq!(
+            resolve_at_modifiers_in_query("rate(foo[5m])", start, end),
+            None
+        );
+        assert_eq!(resolve_at_modifiers_in_query("foo @ 100", start, end), None);
+
+        let query = resolve_at_modifiers_in_query(
+            "sum(rate(foo[5m] @ start())) / sum(foo @ end())",
+            start,
+            end,
+        )
+        .unwrap();
+        assert!(
+            !query.contains("start()") && !query.contains("end()"),
+            "{query}"
+        );
+        assert!(
+            query.contains("1600000000") && query.contains("1600003600"),
+            "{query}"
+        );
+
+        let query = resolve_at_modifiers_in_query("max_over_time(foo[10m:1m] @ end())", start, end)
+            .unwrap();
+        assert!(query.contains("1600003600"), "{query}");
+    }
 }
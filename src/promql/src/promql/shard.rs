@@ -0,0 +1,282 @@
+// Copyright 2026 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Planning of horizontally sharded PromQL queries.
+//!
+//! Each series lands in exactly one shard of its `__hash__` label, so an
+//! expression that is evaluated series by series gives the same result when the
+//! shards are evaluated apart and their results are concatenated. An
+//! aggregation over such an expression is evaluated per shard and the partial
+//! aggregates are combined afterwards.
+
+use promql_parser::parser::{
+    AggregateExpr, BinaryExpr, Call, Expr, ParenExpr, UnaryExpr,
+    token::{self, TokenType},
+};
+
+/// Functions evaluated on each input series on its own.
+const SERIES_LOCAL_FUNCS: [&str; 59] = [
+    "rate",
+    "irate",
+    "increase",
+    "delta",
+    "idelta",
+    "deriv",
+    "changes",
+    "resets",
+    "avg_over_time",
+    "min_over_time",
+    "max_over_time",
+    "sum_over_time",
+    "count_over_time",
+    "last_over_time",
+    "stddev_over_time",
+    "stdvar_over_time",
+    "quantile_over_time",
+    "mad_over_time",
+    "present_over_time",
+    "ts_of_max_over_time",
+    "ts_of_min_over_time",
+    "ts_of_last_over_time",
+    "predict_linear",
+    "holt_winters",
+    "double_exponential_smoothing",
+    "abs",
+    "ceil",
+    "floor",
+    "exp",
+    "sqrt",
+    "ln",
+    "log2",
+    "log10",
+    "round",
+    "sgn",
+    "clamp",
+    "clamp_max",
+    "clamp_min",
+    "acos",
+    "acosh",
+    "asin",
+    "asinh",
+    "atan",
+    "atanh",
+    "cos",
+    "cosh",
+    "sin",
+    "sinh",
+    "tan",
+    "tanh",
+    "deg",
+    "rad",
+    "timestamp",
+    "label_replace",
+    "label_join",
+    "histogram_count",
+    "histogram_sum",
+    "histogram_avg",
+    "histogram_fraction",
+];
+
+/// How the results of the shards are combined.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShardMerge {
+    /// The shards hold disjoint series.
+    Concat,
+    /// Samples with the same labels and timestamp are added.
+    Sum,
+    Min,
+    Max,
+    /// The first query sums, the second one counts, the sums are divided by the
+    /// counts.
+    Avg,
+}
+
+/// The queries every shard evaluates and how their results are combined.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShardPlan {
+    pub queries: Vec<String>,
+    pub merge: ShardMerge,
+}
+
+/// Plans the sharded evaluation of `expr`, `None` if it cannot be sharded.
+pub fn plan(expr: &Expr) -> Option<ShardPlan> {
+    match expr {
+        Expr::Paren(ParenExpr { expr }) => plan(expr),
+        Expr::Aggregate(agg) if agg.param.is_none() && series_local(&agg.expr) => {
+            let single = |merge| {
+                Some(ShardPlan {
+                    queries: vec![expr.to_string()],
+                    merge,
+                })
+            };
+            match agg.op.id() {
+                token::T_SUM | token::T_COUNT => single(ShardMerge::Sum),
+                token::T_MIN => single(ShardMerge::Min),
+                token::T_MAX | token::T_GROUP => single(ShardMerge::Max),
+                token::T_AVG => Some(ShardPlan {
+                    queries: vec![
+                        with_op(agg, token::T_SUM).to_string(),
+                        with_op(agg, token::T_COUNT).to_string(),
+                    ],
+                    merge: ShardMerge::Avg,
+                }),
+                _ => None,
+            }
+        }
+        expr if series_local(expr) => Some(ShardPlan {
+            queries: vec![expr.to_string()],
+            merge: ShardMerge::Concat,
+        }),
+        _ => None,
+    }
+}
+
+fn with_op(agg: &AggregateExpr, op: u8) -> Expr {
+    Expr::Aggregate(AggregateExpr {
+        op: TokenType::new(op),
+        ..agg.clone()
+    })
+}
+
+/// Whether `expr` reads at least one selector and evaluates each input series
+/// independently of the others.
+fn series_local(expr: &Expr) -> bool {
+    has_selector(expr) && evaluated_per_series(expr)
+}
+
+fn evaluated_per_series(expr: &Expr) -> bool {
+    match expr {
+        Expr::VectorSelector(_) | Expr::MatrixSelector(_) => true,
+        Expr::NumberLiteral(_) | Expr::StringLiteral(_) => true,
+        Expr::Paren(ParenExpr { expr }) | Expr::Unary(UnaryExpr { expr }) => {
+            evaluated_per_series(expr)
+        }
+        Expr::Subquery(subquery) => evaluated_per_series(&subquery.expr),
+        Expr::Call(Call { func, args }) => {
+            SERIES_LOCAL_FUNCS.contains(&func.name)
+                && args.args.iter().all(|arg| evaluated_per_series(arg))
+        }
+        // a vector matched against a scalar, two vectors are matched by labels
+        // and their series may sit in different shards
+        Expr::Binary(BinaryExpr { lhs, rhs, .. }) => {
+            (is_number(lhs) && evaluated_per_series(rhs))
+                || (is_number(rhs) && evaluated_per_series(lhs))
+        }
+        _ => false,
+    }
+}
+
+fn is_number(expr: &Expr) -> bool {
+    match expr {
+        Expr::NumberLiteral(_) => true,
+        Expr::Paren(ParenExpr { expr }) | Expr::Unary(UnaryExpr { expr }) => is_number(expr),
+        _ => false,
+    }
+}
+
+fn has_selector(expr: &Expr) -> bool {
+    match expr {
+        Expr::VectorSelector(_) | Expr::MatrixSelector(_) => true,
+        Expr::Paren(ParenExpr { expr }) | Expr::Unary(UnaryExpr { expr }) => has_selector(expr),
+        Expr::Subquery(subquery) => has_selector(&subquery.expr),
+        Expr::Aggregate(AggregateExpr { expr, .. }) => has_selector(expr),
+        Expr::Binary(BinaryExpr { lhs, rhs, .. }) => has_selector(lhs) || has_selector(rhs),
+        Expr::Call(Call { args, .. }) => args.args.iter().any(|arg| has_selector(arg)),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use promql_parser::parser;
+
+    use super::*;
+
+    fn plan_query(query: &str) -> Option<ShardPlan> {
+        plan(&parser::parse(query).unwrap())
+    }
+
+    #[test]
+    fn test_plan_aggregations() {
+        let cases = [
+            ("sum by (job) (rate(http_requests[5m]))", ShardMerge::Sum),
+            ("sum(http_requests)", ShardMerge::Sum),
+            ("count without (instance) (up)", ShardMerge::Sum),
+            ("min by (job) (up)", ShardMerge::Min),
+            ("max by (job) (abs(temperature))", ShardMerge::Max),
+            ("group by (job) (up)", ShardMerge::Max),
+            ("(sum by (job) (up * 2))", ShardMerge::Sum),
+        ];
+        for (query, merge) in cases {
+            let plan = plan_query(query).unwrap_or_else(|| panic!("{query} not sharded"));
+            assert_eq!(plan.merge, merge, "{query}");
+            assert_eq!(plan.queries.len(), 1, "{query}");
+        }
+    }
+
+    #[test]
+    fn test_plan_avg() {
+        let plan = plan_query("avg by (job) (rate(http_requests[5m]))").unwrap();
+        assert_eq!(plan.merge, ShardMerge::Avg);
+        let aggs = plan
+            .queries
+            .iter()
+            .map(|q| match parser::parse(q).unwrap() {
+                Expr::Aggregate(agg) => agg,
+                _ => panic!("{q} is not an aggregation"),
+            })
+            .collect::<Vec<_>>();
+        let ops = aggs.iter().map(|agg| agg.op.id()).collect::<Vec<_>>();
+        assert_eq!(ops, vec![token::T_SUM, token::T_COUNT]);
+        // the partial queries keep the grouping
+        assert_eq!(aggs[0].modifier, aggs[1].modifier);
+        assert!(aggs[0].modifier.is_some());
+    }
+
+    #[test]
+    fn test_plan_series_local() {
+        for query in [
+            "up",
+            "rate(http_requests[5m])",
+            "clamp_min(up, 0) > 0",
+            "label_replace(up, \"host\", \"$1\", \"instance\", \"(.*):.*\")",
+            "max_over_time(rate(http_requests[1m])[10m:1m])",
+        ] {
+            let plan = plan_query(query).unwrap_or_else(|| panic!("{query} not sharded"));
+            assert_eq!(plan.merge, ShardMerge::Concat, "{query}");
+        }
+    }
+
+    #[test]
+    fn test_plan_not_shardable() {
+        for query in [
+            "topk(5, up)",
+            "quantile(0.9, up)",
+            "stddev by (job) (up)",
+            "count_values(\"version\", build_info)",
+            "sum(up) / count(up)",
+            "up / on (instance) node_up",
+            "histogram_quantile(0.9, sum by (le) (rate(latency_bucket[5m])))",
+            "sum(histogram_quantile(0.9, rate(latency_bucket[5m])))",
+            "absent(up)",
+            "sort(up)",
+            "sum(sum by (job) (up))",
+            "vector(1)",
+            "1 + 2",
+        ] {
+            assert_eq!(plan_query(query), None, "{query}");
+        }
+    }
+}
@@ -0,0 +1,660 @@
+// Copyright 2026 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//! Conformance tests in the `promqltest` format of the Prometheus repository.
+//!
+//! The `testdata/local_*.test` files are cases written for this engine in that
+//! format. `testdata/vendor.sh` copies the files of a pinned Prometheus commit,
+//! unchanged, into `testdata/upstream/`, and every file found there is run as
+//! well. Nothing upstream is run until the files are vendored.
+//!
+//! The files use `load <step>` blocks of series, `clear`, and `eval`,
+//! `eval_ordered`, `eval_fail`, `eval_warn` and `eval_info` commands for
+//! instant (`instant at <t>`) and range (`range from <t> to <t> step <d>`)
+//! queries, each followed by `expect` lines and the expected series. Warning
+//! and info annotations are not checked.
+//!
+//! Upstream cases the engine does not support, such as native histogram series,
+//! are listed in [`SKIPPED`] instead of being edited out of the files.
+//!
+//! Loaded series are served by an in-memory [`TableProvider`] with one table
+//! per metric, laid out like the metrics streams.
+
+use std::{
+    collections::BTreeMap,
+    sync::Arc,
+    time::{Duration, UNIX_EPOCH},
+};
+
+use config::{
+    TIMESTAMP_COL_NAME,
+    meta::{
+        promql::{
+            HASH_LABEL, NAME_LABEL, VALUE_LABEL,
+            histogram::{STALE_NAN_BITS, is_stale_nan},
+            value::{Label, Labels, QueryContext, Value, signature},
+        },
+        search::ScanStats,
+    },
+};
+use datafusion::{
+    arrow::{
+        array::{ArrayRef, Float64Array, Int64Array, StringArray, UInt64Array},
+        datatypes::{DataType, Field, Schema},
+        record_batch::RecordBatch,
+    },
+    error::{DataFusionError, Result},
+    prelude::SessionContext,
+};
+use hashbrown::{HashMap, HashSet};
+use promql_parser::{
+    label::{MatchOp, Matchers},
+    parser::{self, EvalStmt, Expr},
+};
+use regex::Regex;
+
+use crate::{DEFAULT_LOOKBACK, TableProvider, exec::PromqlContext, micros};
+
+/// Commands that are not run, by file, line of the command and the reason the
+/// engine can't run it. A skipped `load` skips its series, so evals reading
+/// them have to be listed as well. An entry that no longer points at a command
+/// fails the test so the list is updated with the vendored files.
+const SKIPPED: &[(&str, usize, &str)] = &[
+    // Format: ("<file>", <line>, "<reason>"),
+];
+
+/// Relative tolerance when comparing sample values, as in upstream.
+const EPSILON: f64 = 1e-6;
+
+type LabelSet = BTreeMap<String, String>;
+
+#[derive(Debug)]
+struct Series {
+    labels: LabelSet,
+    /// `(timestamp in microseconds, value)` pairs
+    samples: Vec<(i64, f64)>,
+}
+
+#[derive(Debug)]
+enum Expected {
+    Series(LabelSet, Vec<Option<f64>>),
+    Scalar(f64),
+}
+
+#[derive(Debug)]
+struct Eval {
+    line: usize,
+    query: String,
+    start: Duration,
+    end: Duration,
+    step: Duration,
+    ordered: bool,
+    fail: bool,
+    expected: Vec<Expected>,
+}
+
+#[derive(Debug)]
+enum Command {
+    Load(Vec<Series>),
+    Clear,
+    Eval(Eval),
+}
+
+/// Serves the loaded series, one table per metric name.
+#[derive(Clone, Default)]
+struct MemoryProvider {
+    tables: Arc<HashMap<String, RecordBatch>>,
+}
+
+impl MemoryProvider {
+    fn new(series: &[Series]) -> Self {
+        let mut by_name: HashMap<&str, Vec<&Series>> = HashMap::new();
+        for s in series {
+            let name = s.labels.get(NAME_LABEL).map_or("", String::as_str);
+            by_name.entry(name).or_default().push(s);
+        }
+        let tables = by_name
+            .into_iter()
+            .map(|(name, series)| (name.to_string(), record_batch(&series)))
+            .collect();
+        Self {
+            tables: Arc::new(tables),
+        }
+    }
+}
+
+fn record_batch(series: &[&Series]) -> RecordBatch {
+    let label_names: Vec<&String> = series
+        .iter()
+        .flat_map(|s| s.labels.keys())
+        .collect::<std::collections::BTreeSet<_>>()
+        .into_iter()
+        .collect();
+
+    let mut timestamps = vec![];
+    let mut hashes = vec![];
+    let mut values = vec![];
+    let mut label_values: Vec<Vec<Option<&str>>> = vec![vec![]; label_names.len()];
+    for s in series {
+        let labels: Labels = s
+            .labels
+            .iter()
+            .map(|(name, value)| Arc::new(Label::new(name.as_str(), value.as_str())))
+            .collect();
+        let hash = signature(&labels);
+        for &(ts, value) in &s.samples {
+            timestamps.push(ts);
+            hashes.push(hash);
+            // stale markers are stored as a null value
+            values.push((!is_stale_nan(value)).then_some(value));
+            for (column, name) in label_values.iter_mut().zip(&label_names) {
+                column.push(s.labels.get(*name).map(String::as_str));
+            }
+        }
+    }
+
+    let mut fields = vec![
+        Field::new(TIMESTAMP_COL_NAME, DataType::Int64, false),
+        Field::new(HASH_LABEL, DataType::UInt64, false),
+        Field::new(VALUE_LABEL, DataType::Float64, true),
+    ];
+    let mut columns: Vec<ArrayRef> = vec![
+        Arc::new(Int64Array::from(timestamps)),
+        Arc::new(UInt64Array::from(hashes)),
+        Arc::new(Float64Array::from(values)),
+    ];
+    for (name, column) in label_names.into_iter().zip(label_values) {
+        fields.push(Field::new(name, DataType::Utf8, true));
+        columns.push(Arc::new(StringArray::from(column)));
+    }
+    RecordBatch::try_new(Arc::new(Schema::new(fields)), columns).unwrap()
+}
+
+#[async_trait::async_trait]
+impl TableProvider for MemoryProvider {
+    async fn create_context(
+        &self,
+        _org_id: &str,
+        stream_name: &str,
+        _time_range: (i64, i64),
+        _matchers: Matchers,
+        _label_selector: HashSet<String>,
+        _filters: &mut [(String, Vec<String>)],
+    ) -> Result<Vec<(SessionContext, Arc<Schema>, ScanStats, bool)>> {
+        let Some(batch) = self.tables.get(stream_name) else {
+            return Ok(vec![]);
+        };
+        let ctx = SessionContext::new();
+        ctx.register_batch(stream_name, batch.clone())?;
+        Ok(vec![(ctx, batch.schema(), ScanStats::default(), true)])
+    }
+}
+
+/// Parses a duration such as `5m`, `1h30m` or `90` (seconds).
+fn parse_duration(s: &str) -> std::result::Result<Duration, String> {
+    if let Ok(secs) = s.parse::<u64>() {
+        return Ok(Duration::from_secs(secs));
+    }
+    let re = Regex::new(r"(\d+)(ms|s|m|h|d|w|y)").unwrap();
+    let mut total = Duration::ZERO;
+    let mut consumed = 0;
+    for cap in re.captures_iter(s) {
+        let whole = cap.get(0).unwrap();
+        if whole.start() != consumed {
+            break;
+        }
+        consumed = whole.end();
+        let n: u64 = cap[1].parse().map_err(|e| format!("{s}: {e}"))?;
+        total += match &cap[2] {
+            "ms" => Duration::from_millis(n),
+            "s" => Duration::from_secs(n),
+            "m" => Duration::from_secs(n * 60),
+            "h" => Duration::from_secs(n * 3600),
+            "d" => Duration::from_secs(n * 86400),
+            "w" => Duration::from_secs(n * 7 * 86400),
+            _ => Duration::from_secs(n * 365 * 86400),
+        };
+    }
+    if consumed == 0 || consumed != s.len() {
+        return Err(format!("invalid duration: {s}"));
+    }
+    Ok(total)
+}
+
+/// Splits `metric{label="value"} 1 2 3` into the series and the values part.
+fn split_series(line: &str) -> (&str, &str) {
+    let Some(open) = line.find('{') else {
+        return line.split_once(char::is_whitespace).unwrap_or((line, ""));
+    };
+    if line[..open].contains(char::is_whitespace) {
+        return line.split_once(char::is_whitespace).unwrap();
+    }
+    let mut in_quotes = false;
+    let mut escaped = false;
+    for (i, c) in line[open..].char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '"' => in_quotes = !in_quotes,
+            '}' if !in_quotes => {
+                let end = open + i + 1;
+                return (&line[..end], line[end..].trim());
+            }
+            _ => {}
+        }
+    }
+    (line, "")
+}
+
+fn parse_labels(series: &str) -> std::result::Result<LabelSet, String> {
+    if series == "{}" {
+        return Ok(LabelSet::new());
+    }
+    let Expr::VectorSelector(selector) = parser::parse(series)? else {
+        return Err(format!("invalid series: {series}"));
+    };
+    let mut labels = LabelSet::new();
+    if let Some(name) = selector.name {
+        labels.insert(NAME_LABEL.to_string(), name);
+    }
+    for matcher in selector.matchers.matchers {
+        if !matches!(matcher.op, MatchOp::Equal) {
+            return Err(format!("series labels must use '=': {series}"));
+        }
+        labels.insert(matcher.name, matcher.value);
+    }
+    Ok(labels)
+}
+
+/// Expands the series value notation: `1 2 3`, `_`, `stale`, `1+2x3` (1 3 5 7),
+/// `1-1x2` (1 0 -1), `5x2` (5 5 5) and `_x3` (three missing steps).
+fn parse_values(values: &str) -> std::result::Result<Vec<Option<f64>>, String> {
+    let number = r"[-+]?(?:[0-9]*\.?[0-9]+(?:[eE][-+]?[0-9]+)?|Inf|inf|NaN)";
+    let re = Regex::new(&format!(
+        r"^(?:(?P<a>{number})(?:(?P<op>[-+])(?P<b>{number}))?|(?P<blank>_))x(?P<n>[0-9]+)$"
+    ))
+    .unwrap();
+    let parse_f64 = |s: &str| {
+        s.parse::<f64>()
+            .map_err(|e| format!("invalid value {s}: {e}"))
+    };
+
+    let mut out = vec![];
+    for item in values.split_whitespace() {
+        match item {
+            "_" => out.push(None),
+            "stale" => out.push(Some(f64::from_bits(STALE_NAN_BITS))),
+            _ => match re.captures(item) {
+                Some(cap) => {
+                    let n: usize = cap["n"].parse().map_err(|e| format!("{item}: {e}"))?;
+                    if cap.name("blank").is_some() {
+                        out.extend(std::iter::repeat_n(None, n));
+                        continue;
+                    }
+                    let a = parse_f64(&cap["a"])?;
+                    let b = match cap.name("b") {
+                        Some(b) if &cap["op"] == "-" => -parse_f64(b.as_str())?,
+                        Some(b) => parse_f64(b.as_str())?,
+                        None => 0.0,
+                    };
+                    out.extend((0..=n).map(|i| Some(a + b * i as f64)));
+                }
+                None => out.push(Some(parse_f64(item)?)),
+            },
+        }
+    }
+    Ok(out)
+}
+
+fn parse(content: &str, skipped: &[usize]) -> std::result::Result<Vec<Command>, String> {
+    let eval_re = Regex::new(
+        r"^eval(?P<mod>_ordered|_fail|_warn|_info)?\s+(?:instant\s+at\s+(?P<at>\S+)|range\s+from\s+(?P<from>\S+)\s+to\s+(?P<to>\S+)\s+step\s+(?P<step>\S+))\s+(?P<expr>.+)$",
+    )
+    .unwrap();
+
+    let lines: Vec<&str> = content.lines().collect();
+    let mut commands = vec![];
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i].trim();
+        let line_no = i + 1;
+        i += 1;
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let err = |e: String| format!("line {line_no}: {e}");
+
+        // the indented block following a command
+        let mut block = vec![];
+        while i < lines.len() && lines[i].starts_with([' ', '\t']) {
+            let body = lines[i].trim();
+            if !body.is_empty() && !body.starts_with('#') {
+                block.push(body);
+            }
+            i += 1;
+        }
+
+        if skipped.contains(&line_no) {
+            continue;
+        }
+        if line == "clear" {
+            commands.push(Command::Clear);
+        } else if let Some(step) = line.strip_prefix("load ") {
+            let step = micros(parse_duration(step.trim()).map_err(err)?);
+            let mut series = vec![];
+            for body in block {
+                let (labels, values) = split_series(body);
+                let samples = parse_values(values)
+                    .map_err(err)?
+                    .into_iter()
+                    .enumerate()
+                    .filter_map(|(i, v)| v.map(|v| (i as i64 * step, v)))
+                    .collect();
+                series.push(Series {
+                    labels: parse_labels(labels).map_err(err)?,
+                    samples,
+                });
+            }
+            commands.push(Command::Load(series));
+        } else if let Some(cap) = eval_re.captures(line) {
+            let duration = |name: &str| parse_duration(&cap[name]).map_err(err);
+            let (start, end, step) = match cap.name("at") {
+                Some(_) => {
+                    let at = duration("at")?;
+                    (at, at, Duration::ZERO)
+                }
+                None => (duration("from")?, duration("to")?, duration("step")?),
+            };
+            let mut ordered = cap.name("mod").is_some_and(|m| m.as_str() == "_ordered");
+            let mut fail = cap.name("mod").is_some_and(|m| m.as_str() == "_fail");
+            let mut expected = vec![];
+            for body in block {
+                if let Some(expect) = body.strip_prefix("expect ") {
+                    match expect.split_whitespace().next() {
+                        Some("fail") => fail = true,
+                        Some("ordered") => ordered = true,
+                        // annotations are not checked
+                        Some("warn" | "no_warn" | "info" | "no_info") => {}
+                        _ => return Err(err(format!("unsupported expectation: {body}"))),
+                    }
+                    continue;
+                }
+                // the error message of `eval_fail` is not checked
+                if body.starts_with("expected_fail_") {
+                    continue;
+                }
+                if let Ok(value) = body.parse::<f64>() {
+                    expected.push(Expected::Scalar(value));
+                    continue;
+                }
+                let (labels, values) = split_series(body);
+                expected.push(Expected::Series(
+                    parse_labels(labels).map_err(err)?,
+                    parse_values(values).map_err(err)?,
+                ));
+            }
+            commands.push(Command::Eval(Eval {
+                line: line_no,
+                query: cap["expr"].to_string(),
+                start,
+                end,
+                step,
+                ordered,
+                fail,
+                expected,
+            }));
+        } else {
+            return Err(err(format!("unsupported command: {line}")));
+        }
+    }
+    Ok(commands)
+}
+
+fn almost_equal(a: f64, b: f64) -> bool {
+    if a.is_nan() || b.is_nan() {
+        return a.is_nan() && b.is_nan();
+    }
+    if a == b {
+        return true;
+    }
+    let diff = (a - b).abs();
+    diff <= EPSILON * a.abs().max(b.abs()) || diff < f64::MIN_POSITIVE
+}
+
+fn label_set(labels: &Labels) -> LabelSet {
+    labels
+        .iter()
+        .filter(|l| l.name != HASH_LABEL)
+        .map(|l| (l.name.clone(), l.value.clone()))
+        .collect()
+}
+
+async fn run_eval(org_id: &str, provider: &MemoryProvider, eval: &Eval) -> Result<Value> {
+    let expr = parser::parse(&eval.query).map_err(DataFusionError::Plan)?;
+    let stmt = EvalStmt {
+        expr,
+        start: UNIX_EPOCH + eval.start,
+        end: UNIX_EPOCH + eval.end,
+        interval: eval.step,
+        lookback_delta: DEFAULT_LOOKBACK,
+    };
+    let query_ctx = Arc::new(QueryContext {
+        trace_id: "promqltest".to_string(),
+        org_id: org_id.to_string(),
+        query_exemplars: false,
+        query_data: false,
+        need_wal: false,
+        use_cache: false,
+        timeout: 60,
+        search_event_type: None,
+        regions: vec![],
+        clusters: vec![],
+        is_super_cluster: false,
+        shard: None,
+    });
+    let mut ctx = PromqlContext::new(query_ctx, provider.clone(), vec![]);
+    let (value, ..) = ctx.exec("promqltest", stmt).await?;
+    Ok(value)
+}
+
+/// Compares a query result with the expectation, returning a description of the
+/// first mismatch.
+fn check(eval: &Eval, value: Value) -> std::result::Result<(), String> {
+    let start = micros(eval.start);
+    let step = micros(eval.step);
+
+    if let [Expected::Scalar(expected)] = eval.expected.as_slice() {
+        return match value {
+            Value::Sample(s) if almost_equal(s.value, *expected) => Ok(()),
+            Value::Float(f) if almost_equal(f, *expected) => Ok(()),
+            other => Err(format!("expected scalar {expected}, got {other:?}")),
+        };
+    }
+
+    let actual: Vec<(LabelSet, Vec<(i64, f64)>)> = match value {
+        Value::Vector(v) => v
+            .into_iter()
+            .map(|v| (label_set(&v.labels), vec![(start, v.sample.value)]))
+            .collect(),
+        Value::Matrix(m) => m
+            .into_iter()
+            .map(|s| {
+                let samples = s.samples.iter().map(|s| (s.timestamp, s.value)).collect();
+                (label_set(&s.labels), samples)
+            })
+            .collect(),
+        Value::None => vec![],
+        other => return Err(format!("unexpected result {other:?}")),
+    };
+
+    if actual.len() != eval.expected.len() {
+        return Err(format!(
+            "expected {} series, got {}: {actual:?}",
+            eval.expected.len(),
+            actual.len()
+        ));
+    }
+    for (i, expected) in eval.expected.iter().enumerate() {
+        let Expected::Series(labels, values) = expected else {
+            return Err("a scalar must be the only expected value".to_string());
+        };
+        let found = if eval.ordered {
+            actual.get(i).filter(|(l, _)| l == labels)
+        } else {
+            actual.iter().find(|(l, _)| l == labels)
+        };
+        let Some((_, samples)) = found else {
+            return Err(format!(
+                "series {labels:?} not found at position {i} in {actual:?}"
+            ));
+        };
+        // instant results carry the evaluation time, ranges one sample per step
+        let expected: Vec<(i64, f64)> = values
+            .iter()
+            .enumerate()
+            .filter_map(|(i, v)| v.map(|v| (start + i as i64 * step, v)))
+            .collect();
+        let matches = expected.len() == samples.len()
+            && expected
+                .iter()
+                .zip(samples)
+                .all(|(e, a)| (step == 0 || e.0 == a.0) && almost_equal(e.1, a.1));
+        if !matches {
+            return Err(format!(
+                "series {labels:?}: expected {expected:?}, got {samples:?}"
+            ));
+        }
+    }
+    Ok(())
+}
+
+async fn run(name: &str, content: &str) {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut skipped = vec![];
+    for &(_, line, reason) in SKIPPED.iter().filter(|(file, ..)| *file == name) {
+        let command = lines.get(line.wrapping_sub(1)).map_or("", |l| l.trim());
+        assert!(
+            command.starts_with("load") || command.starts_with("eval"),
+            "{name}:{line}: skipped line is not a command: {command:?}"
+        );
+        assert!(
+            !reason.is_empty(),
+            "{name}:{line}: skipped without a reason"
+        );
+        skipped.push(line);
+    }
+    let commands = parse(content, &skipped).unwrap_or_else(|e| panic!("{name}: {e}"));
+    let mut series: Vec<Series> = vec![];
+    let mut failures = vec![];
+    for command in commands {
+        match command {
+            Command::Clear => series.clear(),
+            Command::Load(loaded) => series.extend(loaded),
+            Command::Eval(eval) => {
+                let provider = MemoryProvider::new(&series);
+                let result = run_eval(name, &provider, &eval).await;
+                let outcome = match (result, eval.fail) {
+                    (Ok(value), false) => check(&eval, value),
+                    (Err(e), false) => Err(format!("unexpected error: {e}")),
+                    (Ok(_), true) => Err("expected an error".to_string()),
+                    (Err(_), true) => Ok(()),
+                };
+                if let Err(e) = outcome {
+                    failures.push(format!("{name}:{} `{}`: {e}", eval.line, eval.query));
+                }
+            }
+        }
+    }
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
+
+#[test]
+fn test_parse_values() {
+    let values = parse_values("1+2x3 _ stale 5x1 1-1x2 _x2 NaN").unwrap();
+    assert_eq!(
+        values[..5],
+        [Some(1.0), Some(3.0), Some(5.0), Some(7.0), None]
+    );
+    assert!(values[5].is_some_and(is_stale_nan));
+    assert_eq!(
+        values[6..11],
+        [Some(5.0), Some(5.0), Some(1.0), Some(0.0), Some(-1.0)]
+    );
+    assert!(parse_values("1+x").is_err());
+}
+
+#[test]
+fn test_parse_duration() {
+    assert_eq!(parse_duration("0").unwrap(), Duration::ZERO);
+    assert_eq!(parse_duration("1h30m").unwrap(), Duration::from_secs(5400));
+    assert!(parse_duration("5x").is_err());
+}
+
+#[test]
+fn test_parse_expect() {
+    let content = "load 1m\n  up 1\n\neval instant at 0 up\n  expect ordered\n  expect no_warn\n  up 1\n\neval instant at 0 foo(\n  expect fail msg: bad\n";
+    let commands = parse(content, &[]).unwrap();
+    let [
+        Command::Load(_),
+        Command::Eval(first),
+        Command::Eval(second),
+    ] = commands.as_slice()
+    else {
+        panic!("unexpected commands: {commands:?}");
+    };
+    assert!(first.ordered && !first.fail);
+    assert_eq!(first.expected.len(), 1);
+    assert!(second.fail);
+
+    // a skipped load or eval is left out, with the indented lines below it
+    assert_eq!(parse(content, &[1, 9]).unwrap().len(), 1);
+    assert!(parse("eval instant at 0 up\n  expect range vector\n", &[]).is_err());
+}
+
+macro_rules! promqltest {
+    ($($test:ident => $file:literal),* $(,)?) => {
+        $(
+            #[tokio::test]
+            async fn $test() {
+                run($file, include_str!(concat!("../testdata/", $file))).await;
+            }
+        )*
+    };
+}
+
+promqltest! {
+    test_local_aggregators => "local_aggregators.test",
+    test_local_functions => "local_functions.test",
+    test_local_info => "local_info.test",
+    test_local_trig_functions => "local_trig_functions.test",
+}
+
+#[tokio::test]
+async fn test_upstream() {
+    let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("testdata/upstream");
+    // not vendored yet, see testdata/vendor.sh
+    let Ok(entries) = std::fs::read_dir(&dir) else {
+        return;
+    };
+    let mut files: Vec<_> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "test"))
+        .collect();
+    files.sort();
+    for path in files {
+        let name = path.file_name().unwrap().to_string_lossy().into_owned();
+        let content = std::fs::read_to_string(&path).unwrap();
+        run(&name, &content).await;
+    }
+}
@@ -0,0 +1,39 @@
+#!/usr/bin/env bash
+# Copies the promqltest files of the pinned Prometheus commit, unchanged, into
+# upstream/. The runner in src/promqltest.rs runs every file found there, and
+# cases the engine does not support are listed in SKIPPED, keyed by file and
+# line, so update that list together with the commit. The local_*.test files
+# next to this script are written for this engine and are not touched.
+#
+#   ./vendor.sh              fetch the files of the pinned commit
+#   ./vendor.sh --pin <tag>  pin the commit of a release tag, then fetch
+#
+# Both need network access to github.com.
+set -euo pipefail
+
+# Full hash of the pinned upstream commit, set by --pin.
+COMMIT=""
+REPO="https://github.com/prometheus/prometheus"
+FILES="aggregators.test functions.test info.test trig_functions.test"
+
+cd "$(dirname "$0")"
+if [[ "${1:-}" == "--pin" ]]; then
+    tag="${2:?usage: $0 --pin <tag>}"
+    COMMIT="$(git ls-remote "${REPO}" "refs/tags/${tag}^{}" | cut -f1)"
+    if [[ -z "${COMMIT}" ]]; then
+        echo "tag ${tag} not found in ${REPO}" >&2
+        exit 1
+    fi
+    sed -i.bak "s/^COMMIT=.*/COMMIT=\"${COMMIT}\"/" vendor.sh
+    rm vendor.sh.bak
+fi
+if [[ ! "${COMMIT}" =~ ^[0-9a-f]{40}$ ]]; then
+    echo "no upstream commit pinned, run $0 --pin <tag>" >&2
+    exit 1
+fi
+
+mkdir -p upstream
+for f in ${FILES}; do
+    curl -fsSL "${REPO/github.com/raw.githubusercontent.com}/${COMMIT}/promql/promqltest/testdata/${f}" \
+        -o "upstream/${f}"
+done
@@ -19,5 +19,6 @@
 
 pub mod search;
 mod service;
+pub mod status;
 
 pub use service::MetricsQueryRequest;
@@ -23,14 +23,20 @@ use std::{
 
 use config::{
     get_config,
-    meta::promql::value::{RangeValue, Value},
+    meta::{
+        promql::value::{RangeValue, Value},
+        stream::StreamType,
+    },
     utils::{
         hash::{Sum64, gxhash},
+        schema::format_stream_name,
         time::{HourFormat, get_ymdh_from_micros, now_micros, second_micros},
     },
 };
 use hashbrown::HashMap;
 use infra::errors::{Error, Result};
+use promql::promql::name_visitor;
+use promql_parser::parser;
 use prost::Message;
 use tokio::sync::RwLock;
 
@@ -100,14 +106,20 @@ pub async fn init() -> Result<()> {
 ///
 /// This function will return the samples from the cache if the samples are found.
 /// If the samples are not found, it will return None.
+///
+/// The returned start is the first step which has to be evaluated again. When the query
+/// reaches past the cached range, the last `ZO_METRICS_CACHE_LOOKBACK_SECS` of the cached
+/// samples are dropped as well, so samples which arrived late are picked up.
 pub async fn get(
+    org: &str,
     query: &str,
     start: i64,
     end: i64,
     step: i64,
 ) -> Result<Option<(i64, Vec<proto::cluster_rpc::Series>)>> {
     // get the bucket cache
-    let key = get_hash_key(query, step);
+    let (query, _) = normalize_query(query);
+    let key = get_hash_key(org, &query, step);
     let bucket_id = get_bucket_id(&key);
     let r = GLOBAL_CACHE[bucket_id].read().await;
     let Some(index) = r.data.get(&key) else {
@@ -126,7 +138,8 @@ pub async fn get(
     let mut best_key = String::new();
     let mut best_diff = 0;
     for entry in index.entries.iter() {
-        if start < entry.start {
+        // the cached samples can only be stitched with the new ones on the same step grid
+        if start < entry.start || (start - entry.start) % step != 0 {
             continue;
         }
         let mut d = entry.end - start;
@@ -222,6 +235,29 @@ pub async fn get(
 
     // if new_start > start, it means we have data in cache, so we need to add step for next query
     new_start += step;
+
+    // evaluate the tail of the cached range again, late samples may have landed there
+    let lookback = second_micros(get_config().limit.metrics_cache_lookback_secs);
+    if new_start <= end && lookback > 0 {
+        new_start -= (lookback + step - 1) / step * step;
+        if new_start <= start {
+            return Ok(None);
+        }
+        for series in resp.series.iter_mut() {
+            series.samples.retain(|v| v.time < new_start);
+            if let Some(exemplars) = series.exemplars.as_mut() {
+                exemplars.exemplars.retain(|v| v.time < new_start);
+            }
+        }
+        resp.series.retain(|series| {
+            !series.samples.is_empty()
+                || series
+                    .exemplars
+                    .as_ref()
+                    .is_some_and(|v| !v.exemplars.is_empty())
+        });
+    }
+
     Ok(Some((new_start, resp.series)))
 }
 
@@ -244,9 +280,15 @@ pub async fn set(
         // all of the data in retention time, no need to store
         return Ok(());
     }
+    // the cache trims and splices float samples only, a cached native histogram
+    // series would come back without its histograms
+    if range_values.iter().any(|v| !v.histograms.is_empty()) {
+        return Ok(());
+    }
 
     // get the bucket cache
-    let key = get_hash_key(query, step);
+    let (query, streams) = normalize_query(query);
+    let key = get_hash_key(org, &query, step);
     let bucket_id = get_bucket_id(&key);
     let r = GLOBAL_CACHE[bucket_id].read().await;
     if let Some(index) = r.data.get(&key) {
@@ -350,7 +392,10 @@ pub async fn set(
     let cache_item = MetricsIndexCacheItem::new(&cache_key, start, new_end);
     let mut w = GLOBAL_CACHE[bucket_id].write().await;
     w.cacher.push_back(key.to_string());
-    let index = w.data.entry(key).or_insert(MetricsIndexCache::new(query));
+    let index = w
+        .data
+        .entry(key)
+        .or_insert_with(|| MetricsIndexCache::new(org, &query, streams));
     if index.entries.len() >= METRICS_INDEX_CACHE_MAX_ITEMS {
         // remove the first half items
         index.entries.drain(0..METRICS_INDEX_CACHE_MAX_ITEMS / 2);
@@ -370,11 +415,18 @@ pub async fn load(cache_key: &str) -> Result<()> {
     let Some((key, start, end)) = parse_cache_item_key(cache_key) else {
         return Ok(());
     };
+    // the key format is: metrics_results/{org}/{ymdh}/{prefix}_{start}_{end}_{suffix}.pb
+    let org = cache_key.split('/').nth(1).unwrap_or_default();
     let bucket_id = get_bucket_id(&key);
     let cache_item = MetricsIndexCacheItem::new(cache_key, start, end);
     let mut w = GLOBAL_CACHE[bucket_id].write().await;
     w.cacher.push_back(key.to_string());
-    let index = w.data.entry(key).or_insert(MetricsIndexCache::new(""));
+    // the query and its streams are unknown until the next set, such an entry is dropped
+    // by the invalidation of any metrics stream of the org
+    let index = w
+        .data
+        .entry(key)
+        .or_insert_with(|| MetricsIndexCache::new(org, "", Vec::new()));
     index.entries.push(Arc::new(cache_item));
     drop(w);
 
@@ -396,8 +448,81 @@ async fn gc(bucket_id: usize) -> Result<()> {
     Ok(())
 }
 
-fn get_hash_key(query: &str, step: i64) -> String {
-    config::utils::md5::hash(&format!("{query}-{step}"))
+/// Drop the cached results which may be stale after data of metrics streams was deleted or
+/// backfilled out of order.
+///
+/// `path` is `{org}`, `{org}/{stream_type}` or `{org}/{stream_type}/{stream}`, the same as for
+/// the search result cache. Entries starting at or before `ts` are removed, a `ts` of 0
+/// removes all entries matching the path. Returns the number of removed entries.
+pub async fn delete_cache(path: &str, ts: i64) -> Result<usize> {
+    let mut parts = path.split('/');
+    let org = parts.next().unwrap_or_default();
+    if org.is_empty() {
+        return Ok(0);
+    }
+    if let Some(stream_type) = parts.next()
+        && StreamType::from(stream_type) != StreamType::Metrics
+    {
+        return Ok(0);
+    }
+    let stream = parts.next();
+
+    let mut removed = Vec::new();
+    for bucket in GLOBAL_CACHE.iter() {
+        let mut w = bucket.write().await;
+        for index in w.data.values_mut() {
+            if index.org != org || stream.is_some_and(|stream| !index.reads_stream(stream)) {
+                continue;
+            }
+            index.entries.retain(|entry| {
+                if ts > 0 && entry.start > ts {
+                    return true;
+                }
+                removed.push(entry.key.clone());
+                false
+            });
+        }
+        drop(w);
+    }
+
+    for key in removed.iter() {
+        if let Err(e) = infra::cache::file_data::disk::remove(key).await {
+            log::error!("promql->search->cache: delete cache item {key} err: {e}");
+        }
+    }
+    Ok(removed.len())
+}
+
+/// Normalizes the query so differently formatted but equal queries share the cache, and
+/// returns the metric streams it reads. An empty stream name means the query selects
+/// metrics by matchers only.
+fn normalize_query(query: &str) -> (String, Vec<String>) {
+    match parser::parse(query) {
+        Ok(expr) => {
+            let mut visitor = name_visitor::MetricNameVisitor::default();
+            let _ = promql_parser::util::walk_expr(&mut visitor, &expr);
+            // the metric names as stored, so they compare with the streams being deleted
+            let mut streams = visitor
+                .into_names()
+                .into_iter()
+                .map(|name| {
+                    if name.is_empty() {
+                        name
+                    } else {
+                        format_stream_name(name)
+                    }
+                })
+                .collect::<Vec<_>>();
+            streams.sort();
+            streams.dedup();
+            (expr.to_string(), streams)
+        }
+        Err(_) => (query.trim().to_string(), Vec::new()),
+    }
+}
+
+fn get_hash_key(org: &str, query: &str, step: i64) -> String {
+    config::utils::md5::hash(&format!("{org}/{query}-{step}"))
 }
 
 fn get_cache_item_key(prefix: &str, org: &str, start: i64, end: i64) -> String {
@@ -470,22 +595,39 @@ impl MetricsIndex {
 }
 
 struct MetricsIndexCache {
+    org: String,
     query: String,
+    streams: Vec<String>,
     entries: Vec<Arc<MetricsIndexCacheItem>>,
 }
 
 impl MetricsIndexCache {
-    fn new(query: &str) -> Self {
+    fn new(org: &str, query: &str, streams: Vec<String>) -> Self {
         Self {
+            org: org.to_string(),
             query: query.to_string(),
+            streams,
             entries: Vec::new(),
         }
     }
 
+    /// Whether the cached query may read the stream, a query selecting metrics by
+    /// matchers only or one loaded from disk may read any of them.
+    fn reads_stream(&self, stream: &str) -> bool {
+        if self.streams.is_empty() {
+            return true;
+        }
+        let stream = format_stream_name(stream.to_string());
+        self.streams.iter().any(|s| s.is_empty() || *s == stream)
+    }
+
     fn stats(&self) -> (usize, usize, usize) {
         let len = self.entries.len();
         let cap = self.entries.capacity();
-        let mem_size = std::mem::size_of::<MetricsIndexCacheItem>() * len + self.query.len();
+        let mem_size = std::mem::size_of::<MetricsIndexCacheItem>() * len
+            + self.org.len()
+            + self.query.len()
+            + self.streams.iter().map(|s| s.len()).sum::<usize>();
         (len, cap, mem_size)
     }
 }
@@ -518,8 +660,35 @@ mod tests {
         let query = "test_query";
         let step = 60000000; // 60 seconds in microseconds
 
-        let key = get_hash_key(query, step);
-        assert_eq!(key, "b235015c612525ad7c11c109e3fdc261");
+        let key = get_hash_key("default", query, step);
+        assert_eq!(key, "350f441cb6ea1b5aecbaca88251b74d7");
+        assert_ne!(key, get_hash_key("other", query, step));
+    }
+
+    #[test]
+    fn test_promql_cache_normalize_query() {
+        let (a, streams) = normalize_query("sum by (job) (rate(http_requests_total[5m]))");
+        let (b, _) = normalize_query("sum   by(job)(\n  rate(http_requests_total[5m])\n)");
+        assert_eq!(a, b);
+        assert_eq!(streams, vec!["http_requests_total".to_string()]);
+
+        let (_, streams) = normalize_query(r#"{__name__=~"up|down"}"#);
+        assert_eq!(streams, vec!["".to_string()]);
+
+        let (query, streams) = normalize_query("  not a query(  ");
+        assert_eq!(query, "not a query(");
+        assert!(streams.is_empty());
+    }
+
+    #[test]
+    fn test_promql_cache_reads_formatted_stream() {
+        let (query, streams) = normalize_query("rate(http_requests[5m])");
+        let index = MetricsIndexCache::new("default", &query, streams);
+        assert!(index.reads_stream("http_requests"));
+        // the stream of a delete request is named as it was ingested
+        assert!(index.reads_stream("http.requests"));
+        assert!(index.reads_stream("http-requests"));
+        assert!(!index.reads_stream("http_requests_total"));
     }
 
     #[test]
@@ -550,6 +719,7 @@ mod tests {
             samples: vec![],
             exemplars: None,
             time_window: None,
+            histograms: vec![],
         }];
         let max_ts = end - second_micros(get_config().limit.cache_delay_secs as i64);
         let mut valid_max_ts = 0;
@@ -571,7 +741,7 @@ mod tests {
         assert!(set_result.is_ok());
 
         // Test getting cache
-        let get_result = get(query, start, end, step).await;
+        let get_result = get(org, query, start, end, step).await;
         assert!(get_result.is_ok());
 
         if let Ok(Some((new_start, cached_range_values))) = get_result {
@@ -580,7 +750,16 @@ mod tests {
                 cached_range_values[0].samples[0].value,
                 expected_value.samples[0].value
             );
-            assert_eq!(new_start, valid_max_ts + step);
+            // the tail of the cached range is evaluated again
+            let lookback = second_micros(get_config().limit.metrics_cache_lookback_secs);
+            let lookback = (lookback + step - 1) / step * step;
+            assert_eq!(new_start, valid_max_ts + step - lookback);
+            assert!(
+                cached_range_values[0]
+                    .samples
+                    .iter()
+                    .all(|v| v.time < new_start)
+            );
         } else {
             panic!("Failed to get cached values");
         }
@@ -607,6 +786,7 @@ mod tests {
                 }],
                 exemplars: None,
                 time_window: None,
+                histograms: vec![],
             }];
 
             let set_result = set(
@@ -624,7 +804,8 @@ mod tests {
         }
 
         // Verify that the cache size is maintained
-        let key = get_hash_key(query, step);
+        let (query, _) = normalize_query(query);
+        let key = get_hash_key(org, &query, step);
         let bucket_id = get_bucket_id(&key);
         let metrics = GLOBAL_CACHE[bucket_id].read().await;
 
@@ -710,6 +891,7 @@ mod tests {
             }],
             exemplars: None,
             time_window: None,
+            histograms: vec![],
         }];
 
         let _ = set(trace_id, org, query, start, end, step, range_values, false).await;
@@ -757,7 +939,7 @@ mod tests {
         assert_eq!(mem, 0);
 
         // Add an entry
-        let cache = MetricsIndexCache::new("test_query");
+        let cache = MetricsIndexCache::new("default", "test_query", vec![]);
         metrics.data.insert("test_key".to_string(), cache);
 
         // Stats should reflect the added entry
@@ -770,7 +952,7 @@ mod tests {
     #[tokio::test]
     async fn test_metrics_index_cache_stats() {
         // Test the MetricsIndexCache::stats() method directly
-        let mut cache = MetricsIndexCache::new("test_query");
+        let mut cache = MetricsIndexCache::new("default", "test_query", vec![]);
 
         // Initial stats
         let (len, cap, mem) = cache.stats();
@@ -799,4 +981,145 @@ mod tests {
         // The function should complete without panicking
         assert!(len <= cap); // Length should not exceed capacity
     }
+
+    fn cache_test_values(start: i64, end: i64, step: i64) -> Vec<RangeValue> {
+        let mut value = RangeValue {
+            labels: Labels::new(),
+            samples: vec![],
+            exemplars: None,
+            time_window: None,
+            histograms: vec![],
+        };
+        let mut ts = start;
+        while ts <= end {
+            value.samples.push(Sample {
+                timestamp: ts,
+                value: 1.0,
+            });
+            ts += step;
+        }
+        vec![value]
+    }
+
+    #[tokio::test]
+    async fn test_promql_cache_isolated_by_org() {
+        let query = "test_query_org_isolation";
+        let step = second_micros(15);
+        let (start, end) = adjust_start_end(
+            now_micros() - second_micros(7200),
+            now_micros() - second_micros(3600),
+            step,
+        );
+        let values = cache_test_values(start, end, step);
+        set("trace", "org_a", query, start, end, step, values, false)
+            .await
+            .unwrap();
+
+        assert!(
+            get("org_a", query, start, end, step)
+                .await
+                .unwrap()
+                .is_some()
+        );
+        assert!(
+            get("org_b", query, start, end, step)
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_promql_cache_skips_other_step_grid() {
+        let query = "test_query_step_grid";
+        let step = second_micros(60);
+        let (start, end) = adjust_start_end(
+            now_micros() - second_micros(7200),
+            now_micros() - second_micros(3600),
+            step,
+        );
+        let values = cache_test_values(start, end, step);
+        set("trace", "default", query, start, end, step, values, false)
+            .await
+            .unwrap();
+
+        let shifted = start + second_micros(30);
+        assert!(
+            get("default", query, shifted, end, step)
+                .await
+                .unwrap()
+                .is_none()
+        );
+        assert!(
+            get("default", query, start + step, end, step)
+                .await
+                .unwrap()
+                .is_some()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_promql_cache_delete() {
+        let org = "test_org_cache_delete";
+        let step = second_micros(15);
+        let (start, end) = adjust_start_end(
+            now_micros() - second_micros(7200),
+            now_micros() - second_micros(3600),
+            step,
+        );
+        for query in ["cpu_usage", "rate(mem_usage[5m])"] {
+            let values = cache_test_values(start, end, step);
+            set("trace", org, query, start, end, step, values, false)
+                .await
+                .unwrap();
+        }
+
+        // other stream types and streams are not touched
+        assert_eq!(
+            delete_cache(&format!("{org}/logs/cpu_usage"), 0)
+                .await
+                .unwrap(),
+            0
+        );
+        assert_eq!(
+            delete_cache(&format!("{org}/metrics/disk_usage"), 0)
+                .await
+                .unwrap(),
+            0
+        );
+        // entries starting after the timestamp stay
+        assert_eq!(
+            delete_cache(&format!("{org}/metrics/cpu_usage"), start - 1)
+                .await
+                .unwrap(),
+            0
+        );
+
+        assert_eq!(
+            delete_cache(&format!("{org}/metrics/cpu_usage"), start)
+                .await
+                .unwrap(),
+            1
+        );
+        assert!(
+            get(org, "cpu_usage", start, end, step)
+                .await
+                .unwrap()
+                .is_none()
+        );
+        assert!(
+            get(org, "rate(mem_usage[5m])", start, end, step)
+                .await
+                .unwrap()
+                .is_some()
+        );
+
+        assert_eq!(delete_cache(org, 0).await.unwrap(), 1);
+        assert!(
+            get(org, "rate(mem_usage[5m])", start, end, step)
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
 }
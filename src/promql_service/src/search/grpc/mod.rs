@@ -365,6 +365,7 @@ pub async fn search_inner(
         regions: req.regions.clone(),
         clusters: req.clusters.clone(),
         is_super_cluster: req.is_super_cluster,
+        shard: value::SeriesShard::new(query.shard as u64, query.shard_count as u64),
     });
     let mut ctx = PromqlContext::new(
         query_ctx,
@@ -492,6 +493,7 @@ pub(crate) fn add_value(resp: &mut cluster_rpc::MetricsQueryResponse, value: val
             resp.series.push(cluster_rpc::Series {
                 metric: v.labels.iter().map(|x| x.as_ref().into()).collect(),
                 sample: Some((&v.sample).into()),
+                histogram: v.histogram.as_ref().map(|x| x.into()),
                 ..Default::default()
             });
         }
@@ -499,6 +501,7 @@ pub(crate) fn add_value(resp: &mut cluster_rpc::MetricsQueryResponse, value: val
             resp.series.push(cluster_rpc::Series {
                 metric: v.labels.iter().map(|x| x.as_ref().into()).collect(),
                 samples: v.samples.iter().map(|x| x.into()).collect(),
+                histograms: v.histograms.iter().map(|x| x.into()).collect(),
                 ..Default::default()
             });
         }
@@ -507,6 +510,7 @@ pub(crate) fn add_value(resp: &mut cluster_rpc::MetricsQueryResponse, value: val
                 resp.series.push(cluster_rpc::Series {
                     metric: v.labels.iter().map(|x| x.as_ref().into()).collect(),
                     sample: Some((&v.sample).into()),
+                    histogram: v.histogram.as_ref().map(|x| x.into()),
                     ..Default::default()
                 });
             });
@@ -514,14 +518,16 @@ pub(crate) fn add_value(resp: &mut cluster_rpc::MetricsQueryResponse, value: val
         value::Value::Matrix(v) => {
             v.iter().for_each(|v| {
                 let samples = v.samples.iter().map(|x| x.into()).collect::<Vec<_>>();
+                let histograms = v.histograms.iter().map(|x| x.into()).collect::<Vec<_>>();
                 let exemplars = v.exemplars.as_ref().map(|v| {
                     let exemplars = v.iter().map(|x| x.as_ref().into()).collect::<Vec<_>>();
                     cluster_rpc::Exemplars { exemplars }
                 });
-                if !samples.is_empty() || exemplars.is_some() {
+                if !samples.is_empty() || !histograms.is_empty() || exemplars.is_some() {
                     resp.series.push(cluster_rpc::Series {
                         metric: v.labels.iter().map(|x| x.as_ref().into()).collect(),
                         samples,
+                        histograms,
                         exemplars,
                         ..Default::default()
                     });
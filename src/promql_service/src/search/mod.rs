@@ -39,7 +39,10 @@ use infra::{
     errors::{Error, ErrorCodes, Result},
     runtime::DATAFUSION_RUNTIME,
 };
-use promql::{DEFAULT_LOOKBACK, DEFAULT_MAX_POINTS_PER_SERIES, adjust_start_end, micros};
+use promql::{
+    DEFAULT_LOOKBACK, DEFAULT_MAX_POINTS_PER_SERIES, adjust_start_end, micros,
+    promql::{result_order::ResultOrder, rewrite::resolve_at_modifiers_in_query},
+};
 use proto::cluster_rpc;
 use search_service::server_internal_error;
 use tracing::{Instrument, info_span};
@@ -54,7 +57,21 @@ use crate::MetricsQueryRequest;
 
 mod cache;
 pub mod grpc;
-pub use cache::get_cache_stats;
+mod shard;
+pub use cache::{delete_cache, get_cache_stats};
+
+/// Deletes the cached search and PromQL results under `path` on all queriers.
+///
+/// See [`search_service::cluster::cacher::delete_cached_results`] for `path` and `ts`, the
+/// other queriers drop their PromQL results when they serve the delete request.
+pub async fn delete_cached_results(path: String, ts: i64) -> bool {
+    if config::cluster::LOCAL_NODE.is_querier()
+        && let Err(e) = cache::delete_cache(&path, ts).await
+    {
+        log::error!("delete metrics result cache for {path} error: {e}");
+    }
+    search_service::cluster::cacher::delete_cached_results(path, ts).await
+}
 
 pub async fn init() -> Result<()> {
     if !config::cluster::LOCAL_NODE.is_querier() {
@@ -267,14 +284,22 @@ pub async fn search(
 #[tracing::instrument(name = "promql:search:cluster", skip_all, fields(org_id = req.org_id))]
 async fn search_in_cluster(
     trace_id: &str,
-    req: cluster_rpc::MetricsQueryRequest,
+    mut req: cluster_rpc::MetricsQueryRequest,
     user_email: &str,
     nodes: &[Node],
 ) -> Result<Value> {
     let start_ins = std::time::Instant::now();
     let started_at = now_micros();
     let cfg = get_config();
-    let timeout = req.timeout as u64;
+
+    // the range is split between queriers and the cache, pin `@ start()` and
+    // `@ end()` to the boundaries of the whole range first
+    if let Some(stmt) = req.query.as_mut() {
+        let (start, end) = adjust_start_end(stmt.start, stmt.end, stmt.step);
+        if let Some(query) = resolve_at_modifiers_in_query(&stmt.query, start, end) {
+            stmt.query = query;
+        }
+    }
 
     let &cluster_rpc::MetricsQueryStmt {
         ref query,
@@ -284,6 +309,8 @@ async fn search_in_cluster(
         query_exemplars,
         query_data: _,
         label_selector: _,
+        shard: _,
+        shard_count: _,
     } = req.query.as_ref().unwrap();
     let nr_queriers = nodes.len() as i64;
 
@@ -313,7 +340,7 @@ async fn search_in_cluster(
         (start, vec![])
     } else {
         let start_time = std::time::Instant::now();
-        match cache::get(query, start, end, step).await {
+        match cache::get(&req.org_id, query, start, end, step).await {
             Ok(Some((new_start, values))) => {
                 let took = start_time.elapsed().as_millis() as i32;
                 let cache_ratio = (new_start - start) as f64 / (end - start) as f64;
@@ -364,6 +391,142 @@ async fn search_in_cluster(
         partition_step
     };
 
+    // aggregations over series evaluated one by one are split by series hash,
+    // other queries by time
+    let shard_plan = if cfg.limit.metrics_query_shards > 1 && !query_exemplars {
+        promql_parser::parser::parse(query)
+            .ok()
+            .and_then(|expr| promql::promql::shard::plan(&expr))
+    } else {
+        None
+    };
+    let results = match shard_plan {
+        Some(plan) => vec![
+            shard::search(
+                trace_id,
+                &req,
+                (start, end),
+                &plan,
+                cfg.limit.metrics_query_shards,
+                nodes,
+            )
+            .await?,
+        ],
+        None => search_partitions(trace_id, &req, (start, end), worker_dt, nodes).await?,
+    };
+
+    // merge multiple instances data
+    let mut scan_stats = ScanStats::new();
+    let mut result_type = String::new();
+    let mut series_data: Vec<cluster_rpc::Series> = Vec::new();
+    for resp in results {
+        scan_stats.add(&resp.scan_stats.as_ref().unwrap().into());
+        if result_type.is_empty() {
+            result_type = resp.result_type.clone();
+        }
+        resp.series.into_iter().for_each(|series| {
+            series_data.push(series);
+        });
+    }
+
+    // add cached values to series_data
+    cached_values.into_iter().for_each(|series| {
+        series_data.push(series);
+    });
+
+    // with cache maybe we only get the last point from original data, then the result_type will
+    // return as vector, but if the query is range query, the result_type should be matrix
+    if result_type == "vector" && original_start != end {
+        result_type = "matrix".to_string();
+    }
+
+    // merge result
+    let mut values = if result_type == "matrix" {
+        merge_matrix_query(&series_data, &req.org_id).await?
+    } else if result_type == "vector" {
+        merge_vector_query(&series_data, &req.org_id).await?
+    } else if result_type == "scalar" {
+        merge_scalar_query(&series_data)
+    } else if result_type == "exemplars" {
+        merge_exemplars_query(&series_data, &req.org_id).await?
+    } else {
+        return Err(server_internal_error(format!(
+            "invalid result type: {result_type}"
+        )));
+    };
+    // the merge orders vectors by value, restore the order asked for by a
+    // sorting function at the root of the query
+    if let Value::Vector(vector) = &mut values
+        && let Some(order) = promql_parser::parser::parse(query)
+            .ok()
+            .and_then(|expr| ResultOrder::from_expr(&expr))
+    {
+        order.sort(vector);
+    }
+    let took = start_ins.elapsed().as_millis() as f64;
+    log::info!(
+        "[trace_id {trace_id}] promql->search->result: files: {}, scan_size: {} mb, took: {took} ms",
+        scan_stats.files,
+        scan_stats.original_size,
+    );
+
+    let req_stats = RequestStats {
+        records: scan_stats.records,
+        size: scan_stats.original_size as f64,
+        response_time: start_ins.elapsed().as_secs_f64(),
+        request_body: Some(query.to_string()),
+        user_email: Some(user_email.to_string()),
+        min_ts: Some(start),
+        max_ts: Some(end),
+        trace_id: Some(trace_id.to_string()),
+        ..Default::default()
+    };
+
+    usage_reporting::report_request_usage_stats(
+        req_stats,
+        &req.org_id,
+        "", // TODO see if we can add metric name
+        StreamType::Metrics,
+        UsageType::MetricSearch,
+        0,
+        started_at,
+    )
+    .await;
+
+    // cache the result
+    if cfg.common.result_cache_enabled
+        && let Some(matrix) = values.get_ref_matrix_values()
+        && let Err(err) = cache::set(
+            trace_id,
+            &req.org_id,
+            query,
+            original_start,
+            end,
+            step,
+            matrix.to_vec(),
+            !use_cache, // if the query with use_cache=false, we should update the exist cache
+        )
+        .await
+    {
+        log::error!("[trace_id {trace_id}] promql->search->cache: set cache err: {err:?}");
+    }
+
+    let took = start_ins.elapsed().as_millis() as f64;
+    log::info!("[trace_id {trace_id}] promql->search search finished took: {took} ms",);
+
+    Ok(values)
+}
+
+/// Splits the range into one slice per querier and searches the slices.
+async fn search_partitions(
+    trace_id: &str,
+    req: &cluster_rpc::MetricsQueryRequest,
+    (start, end): (i64, i64),
+    worker_dt: i64,
+    nodes: &[Node],
+) -> Result<Vec<cluster_rpc::MetricsQueryResponse>> {
+    let cfg = get_config();
+    let timeout = req.timeout as u64;
     let job = cluster_rpc::Job {
         trace_id: trace_id.to_string(),
         job: trace_id[..7].to_string(),
@@ -460,101 +623,12 @@ async fn search_in_cluster(
         }
     }
 
-    // merge multiple instances data
-    let mut scan_stats = ScanStats::new();
-    let mut result_type = String::new();
-    let mut series_data: Vec<cluster_rpc::Series> = Vec::new();
-    for resp in results {
-        scan_stats.add(&resp.scan_stats.as_ref().unwrap().into());
-        if result_type.is_empty() {
-            result_type = resp.result_type.clone();
-        }
-        resp.series.into_iter().for_each(|series| {
-            series_data.push(series);
-        });
-    }
-
-    // add cached values to series_data
-    cached_values.into_iter().for_each(|series| {
-        series_data.push(series);
-    });
-
-    // with cache maybe we only get the last point from original data, then the result_type will
-    // return as vector, but if the query is range query, the result_type should be matrix
-    if result_type == "vector" && original_start != end {
-        result_type = "matrix".to_string();
-    }
-
-    // merge result
-    let values = if result_type == "matrix" {
-        merge_matrix_query(&series_data, &req.org_id).await?
-    } else if result_type == "vector" {
-        merge_vector_query(&series_data, &req.org_id).await?
-    } else if result_type == "scalar" {
-        merge_scalar_query(&series_data)
-    } else if result_type == "exemplars" {
-        merge_exemplars_query(&series_data, &req.org_id).await?
-    } else {
-        return Err(server_internal_error(format!(
-            "invalid result type: {result_type}"
-        )));
-    };
-    let took = start_ins.elapsed().as_millis() as f64;
-    log::info!(
-        "[trace_id {trace_id}] promql->search->result: files: {}, scan_size: {} mb, took: {took} ms",
-        scan_stats.files,
-        scan_stats.original_size,
-    );
-
-    let req_stats = RequestStats {
-        records: scan_stats.records,
-        size: scan_stats.original_size as f64,
-        response_time: start_ins.elapsed().as_secs_f64(),
-        request_body: Some(query.to_string()),
-        user_email: Some(user_email.to_string()),
-        min_ts: Some(start),
-        max_ts: Some(end),
-        trace_id: Some(trace_id.to_string()),
-        ..Default::default()
-    };
-
-    usage_reporting::report_request_usage_stats(
-        req_stats,
-        &req.org_id,
-        "", // TODO see if we can add metric name
-        StreamType::Metrics,
-        UsageType::MetricSearch,
-        0,
-        started_at,
-    )
-    .await;
-
-    // cache the result
-    if cfg.common.result_cache_enabled
-        && let Some(matrix) = values.get_ref_matrix_values()
-        && let Err(err) = cache::set(
-            trace_id,
-            &req.org_id,
-            query,
-            original_start,
-            end,
-            step,
-            matrix.to_vec(),
-            !use_cache, // if the query with use_cache=false, we should update the exist cache
-        )
-        .await
-    {
-        log::error!("[trace_id {trace_id}] promql->search->cache: set cache err: {err:?}");
-    }
-
-    let took = start_ins.elapsed().as_millis() as f64;
-    log::info!("[trace_id {trace_id}] promql->search search finished took: {took} ms",);
-
-    Ok(values)
+    Ok(results)
 }
 
 async fn merge_matrix_query(series: &[cluster_rpc::Series], org_id: &str) -> Result<Value> {
     let mut merged_data = HashMap::new();
+    let mut merged_histograms = HashMap::new();
     let mut merged_metrics = HashMap::new();
     for ser in series {
         let labels: Labels = ser
@@ -568,6 +642,14 @@ async fn merge_matrix_query(series: &[cluster_rpc::Series], org_id: &str) -> Res
         ser.samples.iter().for_each(|v| {
             entry.insert(v.time, v.value);
         });
+        if !ser.histograms.is_empty() {
+            let entry = merged_histograms
+                .entry(signature(&labels))
+                .or_insert_with(HashMap::new);
+            ser.histograms.iter().for_each(|v| {
+                entry.insert(v.time, v);
+            });
+        }
         merged_metrics.insert(signature(&labels), labels);
     }
     let mut merged_data = merged_data
@@ -581,7 +663,16 @@ async fn merge_matrix_query(series: &[cluster_rpc::Series], org_id: &str) -> Res
                 })
                 .collect::<Vec<_>>();
             samples.sort_by_key(|k| k.timestamp);
-            RangeValue::new(merged_metrics.get(&sig).unwrap().to_owned(), samples)
+            let mut range_value =
+                RangeValue::new(merged_metrics.get(&sig).unwrap().to_owned(), samples);
+            if let Some(histograms) = merged_histograms.remove(&sig) {
+                range_value.histograms = histograms
+                    .into_values()
+                    .map(HistogramSample::from)
+                    .collect();
+                range_value.histograms.sort_by_key(|k| k.timestamp);
+            }
+            range_value
         })
         .collect::<Vec<_>>();
 
@@ -607,15 +698,17 @@ async fn merge_vector_query(series: &[cluster_rpc::Series], org_id: &str) -> Res
             .collect();
         if let Some(sample) = ser.sample.as_ref() {
             let sample: Sample = sample.into();
-            merged_data.insert(signature(&labels), sample);
+            let histogram = ser.histogram.as_ref().map(HistogramSample::from);
+            merged_data.insert(signature(&labels), (sample, histogram));
             merged_metrics.insert(signature(&labels), labels);
         }
     }
     let mut merged_data = merged_data
         .into_iter()
-        .map(|(sig, sample)| InstantValue {
+        .map(|(sig, (sample, histogram))| InstantValue {
             labels: merged_metrics.get(&sig).unwrap().to_owned(),
             sample,
+            histogram,
         })
         .collect::<Vec<_>>();
 
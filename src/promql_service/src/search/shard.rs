@@ -0,0 +1,448 @@
+// Copyright 2026 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Fan-out of a PromQL query sharded by series hash, see [`promql::promql::shard`].
+//!
+//! Every shard of every planned query is streamed from a querier with the
+//! `Metrics/Data` service, the shards are then combined into one response that
+//! goes through the regular merge like the time partitioned responses.
+
+use std::{
+    collections::{BTreeMap, btree_map},
+    sync::Arc,
+};
+
+use config::{
+    get_config,
+    meta::{
+        cluster::Node,
+        promql::value::{HistogramSample, Label, Labels, NativeHistogram, signature},
+        search::ScanStats,
+    },
+    utils::time::{now_micros, second_micros},
+};
+use futures::future::try_join_all;
+use hashbrown::HashMap;
+use infra::{
+    client::grpc::make_grpc_metrics_client,
+    errors::{Error, ErrorCodes, Result},
+};
+use promql::promql::shard::{ShardMerge, ShardPlan};
+use proto::cluster_rpc;
+use tracing::{Instrument, info_span};
+
+/// Evaluates the queries of `plan` on `nr_shards` shards each, spread over the
+/// queriers, and combines the shards into one response.
+pub(super) async fn search(
+    trace_id: &str,
+    req: &cluster_rpc::MetricsQueryRequest,
+    (start, end): (i64, i64),
+    plan: &ShardPlan,
+    nr_shards: usize,
+    nodes: &[Node],
+) -> Result<cluster_rpc::MetricsQueryResponse> {
+    let cfg = get_config();
+    let timeout = req.timeout as u64;
+    // if the end time is within the last 3 retention time, we need to fetch wal data
+    let need_wal = req.need_wal
+        || end >= now_micros() - second_micros(cfg.limit.max_file_retention_time as i64 * 3);
+
+    let mut tasks = Vec::with_capacity(plan.queries.len() * nr_shards);
+    for (query_idx, query) in plan.queries.iter().enumerate() {
+        for shard in 0..nr_shards {
+            let node = nodes[(query_idx * nr_shards + shard) % nodes.len()].clone();
+            let job = Some(cluster_rpc::Job {
+                trace_id: trace_id.to_string(),
+                job: trace_id[..7].to_string(),
+                stage: 0,
+                partition: node.id as _,
+            });
+            let mut req = cluster_rpc::MetricsQueryRequest {
+                job,
+                need_wal,
+                ..req.clone()
+            };
+            let req_query = req.query.as_mut().unwrap();
+            req_query.query = query.clone();
+            req_query.start = start;
+            req_query.end = end;
+            req_query.shard = shard as u32;
+            req_query.shard_count = nr_shards as u32;
+
+            log::info!(
+                "[trace_id {trace_id}] promql->search->shard: node: {}, need_wal: {need_wal}, shard: {shard}/{nr_shards}, query: {query}",
+                node.grpc_addr,
+            );
+
+            let trace_id = trace_id.to_string();
+            let grpc_span = info_span!("promql:search:cluster:grpc_data", org_id = req.org_id);
+            let task = tokio::task::spawn(
+                async move {
+                    let node = Arc::new(node) as _;
+                    let org_id = req.org_id.clone();
+                    let mut request = tonic::Request::new(req);
+                    let mut client =
+                        make_grpc_metrics_client(&trace_id, &org_id, &mut request, &node, timeout)
+                            .await?;
+                    let mut stream = match client.data(request).await {
+                        Ok(res) => res.into_inner(),
+                        Err(err) => {
+                            log::error!(
+                                "[trace_id {trace_id}] promql->search->shard: node: {}, search err: {err:?}",
+                                node.get_grpc_addr(),
+                            );
+                            return Err(status_error(err));
+                        }
+                    };
+                    let mut responses = Vec::new();
+                    loop {
+                        match stream.message().await {
+                            Ok(Some(resp)) => responses.push(resp),
+                            Ok(None) => break,
+                            Err(err) => {
+                                log::error!(
+                                    "[trace_id {trace_id}] promql->search->shard: node: {}, stream err: {err:?}",
+                                    node.get_grpc_addr(),
+                                );
+                                return Err(status_error(err));
+                            }
+                        }
+                    }
+                    Ok((query_idx, responses))
+                }
+                .instrument(grpc_span),
+            );
+            tasks.push(task);
+        }
+    }
+
+    let task_results = try_join_all(tasks)
+        .await
+        .map_err(|err| Error::ErrorCode(ErrorCodes::ServerInternalError(err.to_string())))?;
+
+    let mut scan_stats = ScanStats::new();
+    let mut result_type = String::new();
+    let mut took = 0;
+    let mut partials = (0..plan.queries.len())
+        .map(|_| Partial::default())
+        .collect::<Vec<_>>();
+    for res in task_results {
+        let (query_idx, responses) = res?;
+        for resp in responses {
+            if let Some(stats) = resp.scan_stats.as_ref() {
+                scan_stats.add(&stats.into());
+            }
+            if result_type.is_empty() {
+                result_type = resp.result_type;
+            }
+            took = took.max(resp.took);
+            for series in resp.series {
+                partials[query_idx].add(series, plan.merge);
+            }
+        }
+    }
+    log::info!(
+        "[trace_id {trace_id}] promql->search->shard: merged {} shards, files: {}, scan_size: {} mb",
+        plan.queries.len() * nr_shards,
+        scan_stats.files,
+        scan_stats.original_size,
+    );
+
+    if result_type.is_empty() {
+        result_type = if start == end { "vector" } else { "matrix" }.to_string();
+    }
+    let instant = result_type == "vector";
+    Ok(cluster_rpc::MetricsQueryResponse {
+        job: req.job.clone(),
+        took,
+        result_type,
+        series: finish(partials, plan.merge, instant),
+        scan_stats: Some(cluster_rpc::ScanStats::from(&scan_stats)),
+    })
+}
+
+fn status_error(err: tonic::Status) -> Error {
+    let err = ErrorCodes::from_json(err.message())
+        .unwrap_or(ErrorCodes::ServerInternalError(err.to_string()));
+    Error::ErrorCode(err)
+}
+
+/// The shards of one planned query, combined series by series.
+#[derive(Default)]
+struct Partial {
+    series: HashMap<u64, PartialSeries>,
+}
+
+#[derive(Default)]
+struct PartialSeries {
+    metric: Vec<cluster_rpc::Label>,
+    samples: BTreeMap<i64, f64>,
+    /// `None` once histograms that cannot be added met.
+    histograms: BTreeMap<i64, Option<NativeHistogram>>,
+}
+
+impl Partial {
+    fn add(&mut self, series: cluster_rpc::Series, merge: ShardMerge) {
+        let labels: Labels = series
+            .metric
+            .iter()
+            .map(|v| Arc::new(Label::from(v)))
+            .collect();
+        let entry = self
+            .series
+            .entry(signature(&labels))
+            .or_insert_with(|| PartialSeries {
+                metric: series.metric.clone(),
+                ..Default::default()
+            });
+        for sample in series.samples.iter().chain(series.sample.iter()) {
+            entry
+                .samples
+                .entry(sample.time)
+                .and_modify(|v| *v = combine(merge, *v, sample.value))
+                .or_insert(sample.value);
+        }
+        for histogram in series.histograms.iter().chain(series.histogram.iter()) {
+            let HistogramSample {
+                timestamp,
+                histogram,
+            } = HistogramSample::from(histogram);
+            match entry.histograms.entry(timestamp) {
+                btree_map::Entry::Vacant(e) => {
+                    e.insert(Some(histogram.as_ref().clone()));
+                }
+                btree_map::Entry::Occupied(mut e) => {
+                    let combined = e
+                        .get()
+                        .as_ref()
+                        .and_then(|acc| combine_histograms(merge, acc, &histogram));
+                    e.insert(combined);
+                }
+            }
+        }
+    }
+}
+
+fn combine(merge: ShardMerge, acc: f64, v: f64) -> f64 {
+    match merge {
+        ShardMerge::Concat => v,
+        ShardMerge::Sum | ShardMerge::Avg => acc + v,
+        // NaN only wins when every shard is NaN, like in the aggregation itself
+        ShardMerge::Min if acc.is_nan() || v < acc => v,
+        ShardMerge::Max if acc.is_nan() || v > acc => v,
+        ShardMerge::Min | ShardMerge::Max => acc,
+    }
+}
+
+fn combine_histograms(
+    merge: ShardMerge,
+    acc: &NativeHistogram,
+    h: &NativeHistogram,
+) -> Option<NativeHistogram> {
+    match merge {
+        ShardMerge::Concat => Some(h.clone()),
+        ShardMerge::Sum | ShardMerge::Avg => acc.add(h),
+        // min and max ignore histograms
+        ShardMerge::Min | ShardMerge::Max => Some(acc.clone()),
+    }
+}
+
+/// Turns the combined shards into series, the sums are divided by the counts for
+/// [`ShardMerge::Avg`].
+fn finish(partials: Vec<Partial>, merge: ShardMerge, instant: bool) -> Vec<cluster_rpc::Series> {
+    let mut partials = partials.into_iter();
+    let Some(mut result) = partials.next() else {
+        return vec![];
+    };
+    if merge == ShardMerge::Avg {
+        let counts = partials.next().unwrap_or_default();
+        for (sig, series) in result.series.iter_mut() {
+            let Some(count) = counts.series.get(sig) else {
+                series.samples.clear();
+                series.histograms.clear();
+                continue;
+            };
+            series.samples = std::mem::take(&mut series.samples)
+                .into_iter()
+                .filter_map(|(ts, sum)| count.samples.get(&ts).map(|c| (ts, sum / c)))
+                .collect();
+            series.histograms = std::mem::take(&mut series.histograms)
+                .into_iter()
+                .filter_map(|(ts, sum)| {
+                    let c = count.samples.get(&ts)?;
+                    Some((ts, sum.map(|h| h.mul(1.0 / c))))
+                })
+                .collect();
+        }
+    }
+
+    result
+        .series
+        .into_values()
+        .filter_map(|series| {
+            let mut samples = series
+                .samples
+                .into_iter()
+                .map(|(time, value)| cluster_rpc::Sample { time, value })
+                .collect::<Vec<_>>();
+            let mut histograms = series
+                .histograms
+                .into_iter()
+                .filter_map(|(timestamp, histogram)| {
+                    let histogram = HistogramSample {
+                        timestamp,
+                        histogram: Arc::new(histogram?),
+                    };
+                    Some(cluster_rpc::HistogramSample::from(&histogram))
+                })
+                .collect::<Vec<_>>();
+            if samples.is_empty() && histograms.is_empty() {
+                return None;
+            }
+            Some(if instant {
+                cluster_rpc::Series {
+                    metric: series.metric,
+                    sample: samples.pop(),
+                    histogram: histograms.pop(),
+                    ..Default::default()
+                }
+            } else {
+                cluster_rpc::Series {
+                    metric: series.metric,
+                    samples,
+                    histograms,
+                    ..Default::default()
+                }
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn series(instance: &str, samples: &[(i64, f64)]) -> cluster_rpc::Series {
+        cluster_rpc::Series {
+            metric: vec![cluster_rpc::Label {
+                name: "instance".to_string(),
+                value: instance.to_string(),
+            }],
+            samples: samples
+                .iter()
+                .map(|&(time, value)| cluster_rpc::Sample { time, value })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    fn merge_shards(
+        merge: ShardMerge,
+        shards: Vec<(usize, Vec<cluster_rpc::Series>)>,
+    ) -> Vec<(String, Vec<(i64, f64)>)> {
+        let mut partials = vec![Partial::default(), Partial::default()];
+        for (query_idx, shard) in shards {
+            for s in shard {
+                partials[query_idx].add(s, merge);
+            }
+        }
+        let mut result = finish(partials, merge, false)
+            .into_iter()
+            .map(|s| {
+                (
+                    s.metric[0].value.clone(),
+                    s.samples.iter().map(|s| (s.time, s.value)).collect(),
+                )
+            })
+            .collect::<Vec<_>>();
+        result.sort_by(|a, b| a.0.cmp(&b.0));
+        result
+    }
+
+    #[test]
+    fn test_merge_sum() {
+        let result = merge_shards(
+            ShardMerge::Sum,
+            vec![
+                (0, vec![series("a", &[(1, 1.0), (2, 2.0)])]),
+                (
+                    0,
+                    vec![series("a", &[(1, 3.0), (3, 4.0)]), series("b", &[(1, 5.0)])],
+                ),
+            ],
+        );
+        assert_eq!(
+            result,
+            vec![
+                ("a".to_string(), vec![(1, 4.0), (2, 2.0), (3, 4.0)]),
+                ("b".to_string(), vec![(1, 5.0)]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_merge_min_max() {
+        let shards = vec![
+            (0, vec![series("a", &[(1, f64::NAN), (2, 2.0)])]),
+            (0, vec![series("a", &[(1, 3.0), (2, 1.0)])]),
+        ];
+        assert_eq!(
+            merge_shards(ShardMerge::Min, shards.clone()),
+            vec![("a".to_string(), vec![(1, 3.0), (2, 1.0)])]
+        );
+        assert_eq!(
+            merge_shards(ShardMerge::Max, shards),
+            vec![("a".to_string(), vec![(1, 3.0), (2, 2.0)])]
+        );
+    }
+
+    #[test]
+    fn test_merge_avg() {
+        let result = merge_shards(
+            ShardMerge::Avg,
+            vec![
+                (0, vec![series("a", &[(1, 6.0), (2, 4.0)])]),
+                (0, vec![series("a", &[(1, 3.0)])]),
+                (1, vec![series("a", &[(1, 2.0), (2, 1.0)])]),
+                (1, vec![series("a", &[(1, 1.0)])]),
+            ],
+        );
+        assert_eq!(result, vec![("a".to_string(), vec![(1, 3.0), (2, 4.0)])]);
+    }
+
+    #[test]
+    fn test_merge_instant() {
+        let mut partial = Partial::default();
+        for value in [1.0, 2.0] {
+            partial.add(
+                cluster_rpc::Series {
+                    sample: Some(cluster_rpc::Sample { time: 5, value }),
+                    ..series("a", &[])
+                },
+                ShardMerge::Sum,
+            );
+        }
+        let result = finish(vec![partial], ShardMerge::Sum, true);
+        assert_eq!(result.len(), 1);
+        assert_eq!(
+            result[0].sample,
+            Some(cluster_rpc::Sample {
+                time: 5,
+                value: 3.0
+            })
+        );
+        assert!(result[0].samples.is_empty());
+    }
+}
@@ -41,6 +41,8 @@ impl From<MetricsQueryRequest> for proto::cluster_rpc::MetricsQueryRequest {
             query_exemplars: req.query_exemplars,
             query_data: false,
             label_selector: vec![],
+            shard: 0,
+            shard_count: 0,
         };
         let trace_id = config::ider::generate_trace_id();
         Self {
@@ -0,0 +1,275 @@
+// Copyright 2026 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//! Cardinality statistics and series churn of an organization's metrics, read
+//! from the `.midx` metrics indexes instead of scanning the data files.
+
+use std::collections::{HashMap, HashSet};
+
+use config::{
+    get_config,
+    meta::{
+        promql::status::{HeadStats, SeriesChurn, SeriesChurnHour, StatusEntry, TsdbStatus},
+        stream::{FileKey, PartitionTimeLevel, StreamType},
+    },
+};
+use infra::errors::Result;
+use metrics_index::SeriesStats;
+use search::datafusion::udaf::hll::HllSketch;
+
+/// Top metric names by series count, label names by value count and label
+/// pairs by series count over `[start, end]`, in the shape of the Prometheus
+/// `/api/v1/status/tsdb` response.
+pub async fn tsdb_status(
+    trace_id: &str,
+    org_id: &str,
+    start: i64,
+    end: i64,
+    limit: usize,
+) -> Result<TsdbStatus> {
+    let mut num_series = 0;
+    let mut min_time = i64::MAX;
+    let mut max_time = i64::MIN;
+    let mut unindexed_files = 0;
+    let mut series_by_metric = Vec::new();
+    // distinct values and pairs are counted with sketches and only the
+    // candidates for the top `limit` pairs are kept, the exact sets of all the
+    // streams of an organization don't fit in memory
+    let mut label_values: HashMap<String, LabelValues> = HashMap::new();
+    let mut label_pairs = HllSketch::new();
+    let mut top_pairs = TopCandidates::new(limit);
+    for stream_name in db::schema::list_streams_from_cache(org_id, StreamType::Metrics).await {
+        let (files, stats) =
+            stream_series_stats(trace_id, org_id, &stream_name, start, end).await?;
+        unindexed_files += stats.unindexed_files;
+        if stats.series_count() == 0 {
+            continue;
+        }
+        for file in files.iter() {
+            min_time = min_time.min(file.meta.min_ts);
+            max_time = max_time.max(file.meta.max_ts);
+        }
+        num_series += stats.series_count() as u64;
+        series_by_metric.push((stream_name, stats.series_count() as u64));
+        for (name, values) in stats.label_values {
+            label_values.entry(name).or_default().add(&values);
+        }
+        for ((name, value), series) in stats.label_pairs {
+            let pair = format!("{name}={value}");
+            label_pairs.insert(&pair);
+            top_pairs.add(pair, series as u64);
+        }
+        top_pairs.prune();
+    }
+
+    let (min_time, max_time) = if num_series == 0 {
+        (0, 0)
+    } else {
+        (min_time / 1000, max_time / 1000)
+    };
+    Ok(TsdbStatus {
+        head_stats: HeadStats {
+            num_series,
+            num_label_pairs: label_pairs.estimate(),
+            min_time,
+            max_time,
+        },
+        series_count_by_metric_name: StatusEntry::top(series_by_metric, limit),
+        label_value_count_by_label_name: StatusEntry::top(
+            label_values
+                .iter()
+                .map(|(name, values)| (name.clone(), values.count())),
+            limit,
+        ),
+        memory_in_bytes_by_label_name: StatusEntry::top(
+            label_values
+                .iter()
+                .map(|(name, values)| (name.clone(), values.bytes())),
+            limit,
+        ),
+        series_count_by_label_value_pair: StatusEntry::top(top_pairs.counts, limit),
+        unindexed_files,
+    })
+}
+
+/// Approximate distinct values of a label name over all the streams.
+#[derive(Default)]
+struct LabelValues {
+    values: HllSketch,
+    // length and number of the distinct values of each stream, a value shared
+    // by several streams is counted once per stream
+    value_bytes: u64,
+    stream_values: u64,
+}
+
+impl LabelValues {
+    fn add(&mut self, values: &HashSet<String>) {
+        for value in values.iter() {
+            self.values.insert(value);
+            self.value_bytes += value.len() as u64;
+        }
+        self.stream_values += values.len() as u64;
+    }
+
+    fn count(&self) -> u64 {
+        self.values.estimate()
+    }
+
+    /// Distinct values times their average length.
+    fn bytes(&self) -> u64 {
+        if self.stream_values == 0 {
+            return 0;
+        }
+        (self.count() as f64 * self.value_bytes as f64 / self.stream_values as f64).round() as u64
+    }
+}
+
+/// Candidates for the top `limit` entries by summed value. Once there are more
+/// than twice `capacity` candidates the smallest are dropped, so an entry
+/// dropped early and seen again later undercounts.
+struct TopCandidates {
+    capacity: usize,
+    counts: HashMap<String, u64>,
+}
+
+impl TopCandidates {
+    fn new(limit: usize) -> Self {
+        Self {
+            capacity: limit.saturating_mul(4).max(1000),
+            counts: HashMap::new(),
+        }
+    }
+
+    fn add(&mut self, name: String, value: u64) {
+        *self.counts.entry(name).or_default() += value;
+        if self.counts.len() > self.capacity.saturating_mul(2) {
+            self.prune();
+        }
+    }
+
+    fn prune(&mut self) {
+        if self.counts.len() <= self.capacity {
+            return;
+        }
+        let mut values = self.counts.values().copied().collect::<Vec<_>>();
+        let (_, min, _) = values.select_nth_unstable_by(self.capacity - 1, |a, b| b.cmp(a));
+        let min = *min;
+        self.counts.retain(|_, value| *value >= min);
+        if self.counts.len() > self.capacity {
+            // ties at the cut off, keep the smallest names like StatusEntry::top
+            let mut entries = std::mem::take(&mut self.counts)
+                .into_iter()
+                .collect::<Vec<_>>();
+            entries.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+            entries.truncate(self.capacity);
+            self.counts = entries.into_iter().collect();
+        }
+    }
+}
+
+/// New series per hour of each metric over `[start, end]`, the `limit` metrics
+/// with the most new series first. `metric` restricts the result to a single
+/// metric.
+pub async fn series_churn(
+    trace_id: &str,
+    org_id: &str,
+    metric: Option<&str>,
+    start: i64,
+    end: i64,
+    limit: usize,
+) -> Result<Vec<SeriesChurn>> {
+    let stream_names = match metric {
+        Some(metric) => vec![metric.to_string()],
+        None => db::schema::list_streams_from_cache(org_id, StreamType::Metrics).await,
+    };
+    let mut churn = Vec::with_capacity(stream_names.len());
+    for stream_name in stream_names {
+        let (_, stats) = stream_series_stats(trace_id, org_id, &stream_name, start, end).await?;
+        if stats.series_count() == 0 {
+            continue;
+        }
+        let hours = stats
+            .hourly_series
+            .iter()
+            .map(|(hour, series)| SeriesChurnHour {
+                time: hour / 1_000_000,
+                new_series: stats.new_series.get(hour).copied().unwrap_or_default() as u64,
+                series: *series as u64,
+            })
+            .collect();
+        churn.push(SeriesChurn {
+            metric: stream_name,
+            new_series: stats.series_count() as u64,
+            hours,
+        });
+    }
+    churn.sort_by(|a, b| {
+        b.new_series
+            .cmp(&a.new_series)
+            .then_with(|| a.metric.cmp(&b.metric))
+    });
+    churn.truncate(limit);
+    Ok(churn)
+}
+
+async fn stream_series_stats(
+    trace_id: &str,
+    org_id: &str,
+    stream_name: &str,
+    start: i64,
+    end: i64,
+) -> Result<(Vec<FileKey>, SeriesStats)> {
+    let files = search_service::file_list::query(
+        trace_id,
+        org_id,
+        StreamType::Metrics,
+        stream_name,
+        PartitionTimeLevel::default(),
+        start,
+        end,
+    )
+    .await?;
+    let concurrency = get_config().limit.cpu_num.max(1).saturating_mul(2).min(64);
+    let stats = metrics_index::series_stats(trace_id, &files, concurrency).await;
+    Ok((files, stats))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_top_candidates_are_bounded() {
+        let mut top = TopCandidates::new(1);
+        for i in 0..5000u64 {
+            top.add(format!("pair{i}"), i);
+            assert!(top.counts.len() <= top.capacity * 2);
+        }
+        top.prune();
+        assert_eq!(top.counts.len(), top.capacity);
+        let best = StatusEntry::top(top.counts, 1);
+        assert_eq!(best[0].name, "pair4999");
+        assert_eq!(best[0].value, 4999);
+    }
+
+    #[test]
+    fn test_label_values_dedupe_across_streams() {
+        let mut values = LabelValues::default();
+        values.add(&HashSet::from(["a".to_string(), "bb".to_string()]));
+        values.add(&HashSet::from(["bb".to_string(), "ccc".to_string()]));
+        assert_eq!(values.count(), 3);
+        // average length 2 over the two streams
+        assert_eq!(values.bytes(), 6);
+    }
+}
@@ -524,6 +524,12 @@ pub struct MetricsQueryStmt {
     pub query_data: bool,
     #[prost(string, repeated, tag = "7")]
     pub label_selector: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    /// series shard index, by __hash__
+    #[prost(uint32, tag = "8")]
+    pub shard: u32,
+    /// 0 or 1 means not sharded
+    #[prost(uint32, tag = "9")]
+    pub shard_count: u32,
 }
 #[derive(serde::Serialize)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -1136,6 +1142,8 @@ pub struct QueryStatus {
     pub search_event_context: ::core::option::Option<SearchEventContext>,
     #[prost(string, optional, tag = "13")]
     pub work_group: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(int64, optional, tag = "14")]
+    pub queue_position: ::core::option::Option<i64>,
 }
 #[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
 pub struct Query {
@@ -2621,6 +2629,29 @@ pub struct IngestionResponse {
     #[prost(string, tag = "2")]
     pub message: ::prost::alloc::string::String,
 }
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct TailRequest {
+    #[prost(string, tag = "1")]
+    pub trace_id: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub org_id: ::prost::alloc::string::String,
+    #[prost(string, tag = "3")]
+    pub stream_type: ::prost::alloc::string::String,
+    #[prost(string, tag = "4")]
+    pub stream_name: ::prost::alloc::string::String,
+    /// SQL WHERE clause, empty means all records
+    #[prost(string, tag = "5")]
+    pub filter: ::prost::alloc::string::String,
+}
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct TailResponse {
+    /// json array of records
+    #[prost(bytes = "vec", tag = "1")]
+    pub data: ::prost::alloc::vec::Vec<u8>,
+    /// records written while the subscriber lagged, before filtering
+    #[prost(uint64, tag = "2")]
+    pub dropped: u64,
+}
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
 #[repr(i32)]
 pub enum IngestionType {
@@ -2771,6 +2802,28 @@ pub mod ingest_client {
             req.extensions_mut().insert(GrpcMethod::new("cluster.Ingest", "Ingest"));
             self.inner.unary(req, path, codec).await
         }
+        /// Server-streaming RPC: Tail streams newly written records matching the filter
+        pub async fn tail(
+            &mut self,
+            request: impl tonic::IntoRequest<super::TailRequest>,
+        ) -> std::result::Result<
+            tonic::Response<tonic::codec::Streaming<super::TailResponse>>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic_prost::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/cluster.Ingest/Tail");
+            let mut req = request.into_request();
+            req.extensions_mut().insert(GrpcMethod::new("cluster.Ingest", "Tail"));
+            self.inner.server_streaming(req, path, codec).await
+        }
     }
 }
 /// Generated server implementations.
@@ -2793,6 +2846,17 @@ pub mod ingest_server {
             tonic::Response<super::IngestionResponse>,
             tonic::Status,
         >;
+        /// Server streaming response type for the Tail method.
+        type TailStream: tonic::codegen::tokio_stream::Stream<
+                Item = std::result::Result<super::TailResponse, tonic::Status>,
+            >
+            + std::marker::Send
+            + 'static;
+        /// Server-streaming RPC: Tail streams newly written records matching the filter
+        async fn tail(
+            &self,
+            request: tonic::Request<super::TailRequest>,
+        ) -> std::result::Result<tonic::Response<Self::TailStream>, tonic::Status>;
     }
     #[derive(Debug)]
     pub struct IngestServer<T> {
@@ -2913,6 +2977,52 @@ pub mod ingest_server {
                     };
                     Box::pin(fut)
                 }
+                "/cluster.Ingest/Tail" => {
+                    #[allow(non_camel_case_types)]
+                    struct TailSvc<T: Ingest>(pub Arc<T>);
+                    impl<
+                        T: Ingest,
+                    > tonic::server::ServerStreamingService<super::TailRequest>
+                    for TailSvc<T> {
+                        type Response = super::TailResponse;
+                        type ResponseStream = T::TailStream;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::ResponseStream>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::TailRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as Ingest>::tail(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = TailSvc(inner);
+                        let codec = tonic_prost::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.server_streaming(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
                 _ => {
                     Box::pin(async move {
                         let mut response = http::Response::new(
@@ -19,7 +19,7 @@ use std::{
 };
 
 use anyhow::Result;
-use arrow_schema::{Field, Schema};
+use arrow_schema::{DataType, Field, Schema};
 use common::meta::{authz::Authz, stream::SchemaEvolution};
 use config::{
     ALL_VALUES_COL_NAME, ID_COL_NAME, O2_INGEST_TS_COL_NAME, ORIGINAL_DATA_COL_NAME,
@@ -30,17 +30,19 @@ use config::{
     is_uds_internal_column,
     meta::{
         promql::{
-            BUCKET_LABEL, EXEMPLARS_LABEL, HASH_LABEL, METADATA_LABEL, NAME_LABEL, QUANTILE_LABEL,
-            VALUE_LABEL,
+            BUCKET_LABEL, EXEMPLARS_LABEL, HASH_LABEL, METADATA_LABEL, NAME_LABEL,
+            NATIVE_HISTOGRAM_LABEL, QUANTILE_LABEL, VALUE_LABEL,
         },
-        stream::{StreamSettings, StreamType},
+        stream::{FtsTokenizer, MAX_VECTOR_DIMENSIONS, StreamSettings, StreamType, VectorField},
     },
     metrics,
+    tantivy::tokenizer::NGRAM_MAX_GRAM,
     utils::{
         json,
         schema::{infer_json_schema_from_map, schema_eq},
         schema_ext::SchemaExt,
         time::now_micros,
+        vector::{vector_data_type, vector_dimensions},
     },
 };
 #[cfg(feature = "enterprise")]
@@ -59,6 +61,9 @@ pub use watcher::{
 };
 
 const SCHEMA_CONFORMANCE_FAILED: &str = "schema_conformance_failed";
+/// Each extra sort key dilutes the clustering of the ones after it; a handful
+/// is all that still helps pruning.
+const MAX_SORT_KEYS: usize = 4;
 
 pub fn get_upto_discard_error() -> anyhow::Error {
     anyhow::anyhow!(
@@ -163,6 +168,14 @@ fn validate_stream_settings(
         ));
     }
 
+    if let FtsTokenizer::Ngram { min_gram, max_gram } = settings.fts_tokenizer
+        && (min_gram == 0 || min_gram > max_gram || max_gram > NGRAM_MAX_GRAM)
+    {
+        return Err(StreamSettingsError::BadRequest(format!(
+            "fts_tokenizer n-gram length must satisfy 1 <= min_gram <= max_gram <= {NGRAM_MAX_GRAM}"
+        )));
+    }
+
     #[cfg(feature = "enterprise")]
     if org_id == META_ORG_ID && stream_name == USAGE_STREAM && settings.data_retention < 32 {
         settings.data_retention = 0;
@@ -299,6 +312,60 @@ async fn persist_stream_settings(
             "partition key [{name}] cannot also be a bloom filter field"
         )));
     }
+    for name in &settings.range_index_fields {
+        if strict_reserved.contains(&name.as_str()) || no_search_reserved.contains(&name.as_str()) {
+            return Err(StreamSettingsError::BadRequest(format!(
+                "field [{name}] is reserved and cannot be used for range index"
+            )));
+        }
+        if let Ok(field) = schema.field_with_name(name)
+            && !matches!(
+                field.data_type(),
+                DataType::Int64 | DataType::UInt64 | DataType::Float64
+            )
+        {
+            return Err(StreamSettingsError::BadRequest(format!(
+                "range index field [{name}] must be numeric, found {}",
+                field.data_type()
+            )));
+        }
+    }
+    if settings.sort_keys.len() > MAX_SORT_KEYS {
+        return Err(StreamSettingsError::BadRequest(format!(
+            "at most {MAX_SORT_KEYS} sort keys are allowed"
+        )));
+    }
+    for name in &settings.sort_keys {
+        if strict_reserved.contains(&name.as_str()) || no_search_reserved.contains(&name.as_str()) {
+            return Err(StreamSettingsError::BadRequest(format!(
+                "field [{name}] is reserved and cannot be used as a sort key"
+            )));
+        }
+    }
+
+    for field in &settings.vector_fields {
+        let name = &field.name;
+        if strict_reserved.contains(&name.as_str()) || no_search_reserved.contains(&name.as_str()) {
+            return Err(StreamSettingsError::BadRequest(format!(
+                "field [{name}] is reserved and cannot be used as a vector field"
+            )));
+        }
+        if field.dimensions == 0 || field.dimensions > MAX_VECTOR_DIMENSIONS {
+            return Err(StreamSettingsError::BadRequest(format!(
+                "vector field [{name}] dimensions must be between 1 and {MAX_VECTOR_DIMENSIONS}"
+            )));
+        }
+        // the column type is fixed when the field is first ingested, so the
+        // field has to be declared before any data arrives for it
+        if let Ok(schema_field) = schema.field_with_name(name)
+            && vector_dimensions(schema_field.data_type()) != Some(field.dimensions)
+        {
+            return Err(StreamSettingsError::BadRequest(format!(
+                "field [{name}] already holds {} values, vector fields must be declared before data is ingested into them",
+                schema_field.data_type()
+            )));
+        }
+    }
 
     let mut old_partition_keys = previous_settings
         .as_ref()
@@ -355,6 +422,8 @@ fn normalize_stream_settings(settings: &mut StreamSettings) {
     dedup_preserve_order(&mut settings.full_text_search_keys);
     dedup_preserve_order(&mut settings.index_fields);
     dedup_preserve_order(&mut settings.bloom_filter_fields);
+    dedup_preserve_order(&mut settings.range_index_fields);
+    dedup_preserve_order(&mut settings.sort_keys);
     dedup_preserve_order(&mut settings.defined_schema_fields);
 
     let mut seen = HashSet::new();
@@ -370,6 +439,10 @@ fn normalize_stream_settings(settings: &mut StreamSettings) {
         .extended_retention_days
         .retain(|range| seen.insert(range.to_string()));
     seen.clear();
+    settings
+        .vector_fields
+        .retain(|field| seen.insert(field.name.clone()));
+    seen.clear();
     settings
         .cross_links
         .retain(|link| seen.insert(link.to_string()));
@@ -424,7 +497,12 @@ pub async fn check_for_schema(
 
     // get infer schema
     let value_iter = record_vals.into_iter();
-    let inferred_schema = infer_json_schema_from_map(stream_name, stream_type, value_iter)?;
+    let mut inferred_schema = infer_json_schema_from_map(stream_name, stream_type, value_iter)?;
+    if let Some(settings) = infra::schema::get_settings(org_id, stream_name, stream_type).await
+        && !settings.vector_fields.is_empty()
+    {
+        inferred_schema = with_vector_fields(inferred_schema, &settings.vector_fields);
+    }
 
     // fast path
     if schema_eq(schema.schema(), &inferred_schema) {
@@ -536,6 +614,26 @@ pub async fn check_for_schema(
     Ok((ret, Some(inferred_schema)))
 }
 
+// vector fields are stored as fixed-size float lists, whatever type their
+// values (usually the strings the flattener makes of arrays) are inferred as
+fn with_vector_fields(schema: Schema, vector_fields: &[VectorField]) -> Schema {
+    let fields = schema
+        .fields()
+        .iter()
+        .map(
+            |field| match vector_fields.iter().find(|v| v.name == *field.name()) {
+                Some(vector) => Arc::new(Field::new(
+                    field.name(),
+                    vector_data_type(vector.dimensions),
+                    true,
+                )),
+                None => field.clone(),
+            },
+        )
+        .collect::<Vec<_>>();
+    Schema::new(fields).with_metadata(schema.metadata)
+}
+
 pub async fn get_merged_schema(
     org_id: &str,
     stream_name: &str,
@@ -866,6 +964,7 @@ pub fn check_schema_for_defined_schema_fields(
             fields.insert(BUCKET_LABEL.to_string());
             fields.insert(QUANTILE_LABEL.to_string());
             fields.insert(EXEMPLARS_LABEL.to_string());
+            fields.insert(NATIVE_HISTOGRAM_LABEL.to_string());
             fields.insert(VALUE_LABEL.to_string());
             fields.insert("trace_id".to_string());
             fields.insert("span_id".to_string());
@@ -987,8 +1086,6 @@ pub async fn stream_schema_exists(
 mod tests {
     use std::str::FromStr;
 
-    use arrow_schema::DataType;
-
     use super::*;
 
     #[test]
@@ -1019,6 +1116,26 @@ mod tests {
         assert_eq!(settings.index_fields_updated_at.get("a"), Some(&100));
     }
 
+    #[test]
+    fn test_with_vector_fields() {
+        let schema = Schema::new(vec![
+            Field::new(TIMESTAMP_COL_NAME, DataType::Int64, true),
+            Field::new("embedding", DataType::Utf8, true),
+        ]);
+        let vector_fields = vec![VectorField {
+            name: "embedding".to_string(),
+            dimensions: 3,
+        }];
+        let schema = with_vector_fields(schema, &vector_fields);
+        assert_eq!(
+            schema.field(0).data_type(),
+            &DataType::Int64,
+            "other fields are kept"
+        );
+        assert_eq!(schema.field(1).data_type(), &vector_data_type(3));
+        assert_eq!(vector_dimensions(schema.field(1).data_type()), Some(3));
+    }
+
     #[test]
     fn test_generate_schema_for_defined_schema_fields_includes_internal_columns() {
         // internal columns must be part of the effective schema even when they
@@ -45,7 +45,14 @@ use infra::bloom::{
 };
 use object_store::{GetOptions, GetRange};
 
-use super::index::{Condition, IndexCondition};
+use super::{
+    datafusion::udf::ip_udf::Cidr,
+    index::{Condition, IndexCondition},
+};
+
+/// Largest IPv4 network an `ip_in_cidr()` filter is expanded to for the bloom
+/// check, one block row is fetched per address.
+const MAX_CIDR_BLOOM_HOSTS: usize = 256;
 
 /// One bloom-decidable predicate: a field plus the candidate values to test.
 /// Passes if **any** candidate's bloom check returns "maybe" (OR within a
@@ -494,6 +501,8 @@ fn collect_decidable(
 ///
 /// - `Equal(f, v)` → `Predicate { f, [v] }`.
 /// - `In(f, vs, false)` with non-empty `vs` → `Predicate { f, vs }`.
+/// - `IpInCidr(f, cidr)` on an IPv4 network of at most [`MAX_CIDR_BLOOM_HOSTS`] addresses →
+///   `Predicate { f, every address }`.
 /// - `Or(l, r)` whose every leaf folds to the **same** field → one `Predicate` whose `values` is
 ///   the deduped union of leaves' values (semantically `f IN (...)`). Handles arbitrarily nested
 ///   `Or`s by recursion.
@@ -516,6 +525,13 @@ fn try_predicate(cond: &Condition, bloom_indexed_fields: &HashSet<String>) -> Op
                 values: values.clone(),
             })
         }
+        Condition::IpInCidr(field, cidr) if bloom_indexed_fields.contains(field) => {
+            let values = Cidr::parse(cidr)?.ipv4_hosts(MAX_CIDR_BLOOM_HOSTS)?;
+            Some(Predicate {
+                field: field.clone(),
+                values,
+            })
+        }
         Condition::Or(left, right) => {
             let lp = try_predicate(left, bloom_indexed_fields)?;
             let rp = try_predicate(right, bloom_indexed_fields)?;
@@ -612,6 +628,27 @@ mod tests {
         assert!(collect_decidable(&c, &fields(&["trace_id"])).is_empty());
     }
 
+    #[test]
+    fn test_collect_small_ip_in_cidr() {
+        let c = cond(vec![Condition::IpInCidr(
+            "client_ip".into(),
+            "10.0.0.0/31".into(),
+        )]);
+        let p = collect_decidable(&c, &fields(&["client_ip"]));
+        assert_eq!(p.len(), 1);
+        assert_eq!(
+            p[0].values,
+            vec!["10.0.0.0".to_string(), "10.0.0.1".to_string()]
+        );
+
+        // too many addresses to check one by one
+        let c = cond(vec![Condition::IpInCidr(
+            "client_ip".into(),
+            "10.0.0.0/16".into(),
+        )]);
+        assert!(collect_decidable(&c, &fields(&["client_ip"])).is_empty());
+    }
+
     // ---- same-field Or folding ----
 
     #[test]
@@ -17,7 +17,7 @@ use std::{collections::HashSet, sync::Arc};
 
 use config::{datafusion::request::Request, meta::sql::TableReferenceExt};
 use datafusion::prelude::SessionContext;
-use infra::errors::Result;
+use infra::errors::{Error, ErrorCodes, Result};
 
 use super::{
     exec::{DataFusionContextBuilder, register_udf},
@@ -26,13 +26,18 @@ use super::{
         generate_physical_optimizer_rules,
     },
     sort_order::FileSortOrder,
-    table_provider::{catalog::StreamTypeProvider, empty_table::NewEmptyTable},
+    table_provider::{
+        catalog::StreamTypeProvider, empty_table::NewEmptyTable,
+        external_table::ExternalTableProvider,
+    },
+    udtf::TableFunctions,
 };
 use crate::sql::Sql;
 
 pub struct SearchContextBuilder {
     pub target_partitions: usize,
     pub contexts: Vec<PhysicalOptimizerContext>,
+    pub table_functions: Option<TableFunctions>,
 }
 
 impl Default for SearchContextBuilder {
@@ -46,6 +51,7 @@ impl SearchContextBuilder {
         Self {
             target_partitions: 0,
             contexts: vec![],
+            table_functions: None,
         }
     }
 
@@ -59,6 +65,14 @@ impl SearchContextBuilder {
         self
     }
 
+    /// The tables of the table functions, resolved before the search waits in
+    /// the queue, see
+    /// [`resolve_table_functions`](crate::datafusion::udtf::resolve_table_functions).
+    pub fn table_functions(mut self, table_functions: TableFunctions) -> Self {
+        self.table_functions = Some(table_functions);
+        self
+    }
+
     pub async fn build(self, req: &Request, sql: &Arc<Sql>) -> Result<SessionContext> {
         let analyzer_rules = generate_analyzer_rules(sql);
         let optimizer_rules = generate_optimizer_rules(sql);
@@ -75,6 +89,8 @@ impl SearchContextBuilder {
 
         register_udf(&ctx, &sql.org_id)?;
         datafusion_functions_json::register_all(&mut ctx)?;
+        crate::datafusion::udtf::register_table_functions(&ctx, req, sql, self.table_functions)
+            .await?;
 
         Ok(ctx)
     }
@@ -103,6 +119,22 @@ pub async fn register_table(ctx: &SessionContext, sql: &Sql) -> Result<()> {
             .clone()
             .with_metadata(Default::default());
         let stream_name = stream.to_quoted_string();
+        if stream.is_external_table() {
+            let Some(table) =
+                infra::schema::external_table::get(&sql.org_id, &stream.stream_name()).await?
+            else {
+                return Err(Error::ErrorCode(ErrorCodes::SearchStreamNotFound(
+                    stream.stream_name(),
+                )));
+            };
+            // the allowed locations may have changed since the table was registered
+            table
+                .validate(&sql.org_id)
+                .map_err(|e| Error::ErrorCode(ErrorCodes::SearchSQLNotValid(e)))?;
+            let table = Arc::new(ExternalTableProvider::new(table, Arc::new(schema)));
+            ctx.register_table(&stream_name, table)?;
+            continue;
+        }
         let table = Arc::new(
             NewEmptyTable::new(&stream_name, Arc::new(schema))
                 .with_partitions(ctx.state().config().target_partitions())
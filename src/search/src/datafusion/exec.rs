@@ -19,7 +19,7 @@ use arrow_schema::Field;
 use config::{
     FileFormat, TIMESTAMP_COL_NAME, get_batch_size, get_config,
     meta::{
-        promql::{EXEMPLARS_LABEL, HASH_LABEL},
+        promql::{EXEMPLARS_LABEL, HASH_LABEL, NATIVE_HISTOGRAM_LABEL},
         search::{Session as SearchSession, StorageType},
         stream::{FileKey, StreamType},
     },
@@ -42,7 +42,7 @@ use datafusion::{
         runtime_env::{RuntimeEnv, RuntimeEnvBuilder},
         session_state::SessionStateBuilder,
     },
-    logical_expr::AggregateUDF,
+    logical_expr::{AggregateUDF, WindowUDF},
     optimizer::{AnalyzerRule, OptimizerRule},
     physical_expr_adapter::DefaultPhysicalExprAdapterFactory,
     physical_optimizer::PhysicalOptimizerRule,
@@ -59,7 +59,7 @@ use super::{
 };
 use crate::{
     datafusion::{
-        sort_order::FileSortOrder,
+        sort_order::{FileSortOrder, is_clustered_file},
         storage::file_statistics_cache,
         table_provider::{listing_adapter::ListingTableAdapter, uniontable::NewUnionTable},
     },
@@ -315,6 +315,7 @@ pub fn register_builtin_udfs(ctx: &SessionContext) {
     ctx.register_udf(super::udf::str_match_udf::STR_MATCH_UDF.clone());
     ctx.register_udf(super::udf::str_match_udf::STR_MATCH_IGNORE_CASE_UDF.clone());
     ctx.register_udf(super::udf::fuzzy_match_udf::FUZZY_MATCH_UDF.clone());
+    ctx.register_udf(super::udf::match_phrase_udf::MATCH_PHRASE_UDF.clone());
     ctx.register_udf(super::udf::regexp_udf::REGEX_MATCH_UDF.clone());
     ctx.register_udf(super::udf::regexp_udf::REGEX_NOT_MATCH_UDF.clone());
     ctx.register_udf(super::udf::regexp_udf::REGEXP_MATCH_TO_FIELDS_UDF.clone());
@@ -335,6 +336,19 @@ pub fn register_builtin_udfs(ctx: &SessionContext) {
     ctx.register_udf(super::udf::match_all_hash_udf::MATCH_ALL_HASH_UDF.clone());
     ctx.register_udf(super::udf::match_all_udf::MATCH_ALL_UDF.clone());
     ctx.register_udf(super::udf::match_all_udf::FUZZY_MATCH_ALL_UDF.clone());
+    ctx.register_udf(super::udf::ip_udf::IP_IN_CIDR_UDF.clone());
+    ctx.register_udf(super::udf::ip_udf::CIDR_CONTAINS_UDF.clone());
+    ctx.register_udf(super::udf::ip_udf::IP_TO_INT_UDF.clone());
+    ctx.register_udf(super::udf::ip_udf::IP_VERSION_UDF.clone());
+    ctx.register_udf(super::udf::ip_udf::IP_IS_PRIVATE_UDF.clone());
+    ctx.register_udf(super::udf::ip_udf::IPV6_UDF.clone());
+    ctx.register_udf(super::udf::geo_udf::GEOIP_COUNTRY_UDF.clone());
+    ctx.register_udf(super::udf::geo_udf::GEOIP_CITY_UDF.clone());
+    ctx.register_udf(super::udf::geo_udf::GEOIP_ASN_UDF.clone());
+    ctx.register_udf(super::udf::geo_udf::GEO_DISTANCE_UDF.clone());
+    ctx.register_udf(super::udf::geo_udf::GEOHASH_UDF.clone());
+    ctx.register_udf(super::udf::vector_udf::COSINE_SIMILARITY_UDF.clone());
+    ctx.register_udf(super::udf::vector_udf::KNN_UDF.clone());
     ctx.register_udaf(AggregateUDF::from(
         super::udaf::summary_percentile::SummaryPercentile::new(),
     ));
@@ -344,6 +358,21 @@ pub fn register_builtin_udfs(ctx: &SessionContext) {
     ctx.register_udaf(AggregateUDF::from(
         super::udaf::approx_topk_distinct::ApproxTopKDistinct::new(),
     ));
+    ctx.register_udaf(AggregateUDF::from(super::udaf::hll::HllAgg::sketch()));
+    ctx.register_udaf(AggregateUDF::from(super::udaf::hll::HllAgg::merge()));
+    ctx.register_udaf(AggregateUDF::from(super::udaf::hll::HllAgg::estimate()));
+    ctx.register_udaf(AggregateUDF::from(
+        super::udaf::tdigest::TDigestAgg::sketch(),
+    ));
+    ctx.register_udaf(AggregateUDF::from(
+        super::udaf::tdigest::TDigestAgg::quantile(),
+    ));
+    ctx.register_udaf(AggregateUDF::from(
+        super::udaf::window_funnel::WindowFunnel::new(),
+    ));
+    ctx.register_udaf(AggregateUDF::from(super::udaf::retention::Retention::new()));
+    ctx.register_udaf(AggregateUDF::from(super::udaf::sessionize::Sessions::new()));
+    ctx.register_udwf(WindowUDF::from(super::udaf::sessionize::Sessionize::new()));
     ctx.register_udf(super::udf::cast_to_timestamp_udf::CAST_TO_TIMESTAMP_UDF.clone());
 
     #[cfg(feature = "enterprise")]
@@ -552,6 +581,7 @@ fn metrics_query_schema_with_utf8_view(
             if matches!(field.data_type(), DataType::Utf8 | DataType::LargeUtf8)
                 && field.name() != HASH_LABEL
                 && field.name() != EXEMPLARS_LABEL
+                && field.name() != NATIVE_HISTOGRAM_LABEL
             {
                 Arc::new(
                     Field::new(field.name(), DataType::Utf8View, field.is_nullable())
@@ -663,33 +693,45 @@ impl TableBuilder {
             vortex_files.len()
         );
 
-        // Build table providers for each format
+        // Build table providers for each format. Files clustered by the stream
+        // sort keys do not carry the declared order: they get their own table
+        // that sorts them back, so the union keeps the ordering.
         let mut tables: Vec<Arc<dyn TableProvider>> = Vec::new();
-
-        if !parquet_files.is_empty() {
-            let table = self
-                .build_table_for_format(
-                    session.clone(),
-                    parquet_files,
-                    schema.clone(),
-                    FileFormat::Parquet,
-                    target_partitions,
-                )
-                .await?;
-            tables.push(table);
-        }
-
-        if !vortex_files.is_empty() {
-            let table = self
-                .build_table_for_format(
-                    session.clone(),
-                    vortex_files,
-                    schema.clone(),
-                    FileFormat::Vortex,
-                    target_partitions,
-                )
-                .await?;
-            tables.push(table);
+        for (files, format) in [
+            (parquet_files, FileFormat::Parquet),
+            (vortex_files, FileFormat::Vortex),
+        ] {
+            let (clustered_files, files): (Vec<_>, Vec<_>) = if self.sort_order.is_sorted() {
+                files.into_iter().partition(|f| is_clustered_file(&f.key))
+            } else {
+                (Vec::new(), files)
+            };
+            if !files.is_empty() {
+                let table = self
+                    .build_table_for_format(
+                        session.clone(),
+                        files,
+                        schema.clone(),
+                        format,
+                        target_partitions,
+                        false,
+                    )
+                    .await?;
+                tables.push(table);
+            }
+            if !clustered_files.is_empty() {
+                let table = self
+                    .build_table_for_format(
+                        session.clone(),
+                        clustered_files,
+                        schema.clone(),
+                        format,
+                        target_partitions,
+                        true,
+                    )
+                    .await?;
+                tables.push(table);
+            }
         }
 
         Ok(tables)
@@ -702,7 +744,14 @@ impl TableBuilder {
         schema: Arc<Schema>,
         format: FileFormat,
         target_partitions: usize,
+        clustered: bool,
     ) -> Result<Arc<dyn TableProvider>> {
+        let (sort_order, restore_order) = if clustered {
+            (FileSortOrder::None, Some(self.sort_order))
+        } else {
+            (self.sort_order, None)
+        };
+
         // Configure listing options with the appropriate file format
         let file_format: Arc<dyn DataFusionFileFormat> = match format {
             FileFormat::Parquet => Arc::new(ParquetFormat::default()),
@@ -716,14 +765,19 @@ impl TableBuilder {
             .with_target_partitions(target_partitions)
             .with_collect_stat(true);
 
-        if self.sort_order.is_sorted() {
+        if sort_order.is_sorted() {
             // specify sort columns for parquet file
             listing_options =
-                listing_options.with_file_sort_order(vec![self.sort_order.logical_sort_exprs()]);
+                listing_options.with_file_sort_order(vec![sort_order.logical_sort_exprs()]);
         }
 
         let schema_key = schema.hash_key();
-        let format = format.extension();
+        let format = if clustered {
+            format!("{}-clustered", format.extension())
+        } else {
+            format.extension().to_string()
+        };
+        let format = format.as_str();
         let trace_id = &session.id;
         let prefix = match session.storage_type {
             StorageType::Memory => {
@@ -771,11 +825,12 @@ impl TableBuilder {
         let mut table = ListingTableAdapter::try_new(
             config,
             session.id.clone(),
-            self.sort_order,
+            sort_order,
             self.index_condition.clone(),
             self.fst_fields.clone(),
             self.timestamp_filter,
-        )?;
+        )?
+        .with_restore_order(restore_order);
         if self.file_stat_cache.is_some() {
             table = table.with_cache(self.file_stat_cache.clone());
         }
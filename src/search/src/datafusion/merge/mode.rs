@@ -36,7 +36,7 @@ use metrics_index::{MetricsFileLayout, metrics_index_stream};
 #[cfg(feature = "enterprise")]
 use o2_enterprise::enterprise::common::downsampling::get_largest_downsampling_rule;
 
-use crate::datafusion::sort_order::FileSortOrder;
+use crate::datafusion::sort_order::{FileSortOrder, is_clustered_file};
 
 /// The kind of merge, decided once per batch.
 #[derive(Debug, Clone)]
@@ -44,6 +44,11 @@ pub enum MergeMode {
     /// `SELECT * FROM tbl ORDER BY _timestamp DESC` into one file: logs,
     /// traces, plain metrics — the ingester and the compactor default.
     Classic,
+    /// [`MergeMode::Classic`] for a stream with sort keys:
+    /// `ORDER BY key1, …, _timestamp DESC` into one `clustered-v1-*` file, so
+    /// row group and page statistics of the keys are tight enough to prune.
+    /// Holds the keys that exist in the schema, in their configured order.
+    Clustered(Vec<String>),
     /// The trace time-index metadata stream: one row per `trace_id`
     /// (`MIN(_timestamp)`, `MIN(min_ts)`, `MAX(max_ts)`, …), one file.
     TraceTimeIndex,
@@ -109,6 +114,25 @@ impl MergeMode {
         }
     }
 
+    /// Cluster a classic merge by the stream's `sort_keys`. Keys missing from
+    /// `schema` are skipped; every other mode keeps the order it needs.
+    pub fn with_sort_keys(self, sort_keys: &[String], schema: &Schema) -> Self {
+        let keys = sort_keys
+            .iter()
+            .filter(|key| schema.field_with_name(key).is_ok())
+            .cloned()
+            .collect::<Vec<_>>();
+        match self {
+            Self::Classic if !keys.is_empty() => Self::Clustered(keys),
+            mode => mode,
+        }
+    }
+
+    /// True when the merge writes a `clustered-v1-*` file.
+    pub fn is_clustered(&self) -> bool {
+        matches!(self, Self::Clustered(_))
+    }
+
     /// True for the indexed metrics hour-end merge.
     pub fn is_metrics_indexed(&self) -> bool {
         matches!(self, Self::MetricsIndexed)
@@ -130,6 +154,8 @@ impl MergeMode {
     pub fn output_sort_order(&self) -> FileSortOrder {
         match self {
             Self::MetricsHashSorted | Self::MetricsIndexed => FileSortOrder::HashTimestampAsc,
+            // the key order is per stream, not one of the static orders
+            Self::Clustered(_) => FileSortOrder::None,
             _ => FileSortOrder::TimestampDesc,
         }
     }
@@ -147,18 +173,19 @@ impl MergeMode {
     ///
     /// A hash-ordered merge whose inputs are all hash-ordered declares that
     /// order, so DataFusion merges the pre-sorted files instead of a full
-    /// sort. Any mix with other layouts declares no order; otherwise the
-    /// classic `_timestamp DESC`.
+    /// sort. Any mix with other layouts, or any clustered input, declares no
+    /// order; otherwise the classic `_timestamp DESC`.
     pub fn input_sort_order(&self, files: &[FileKey]) -> FileSortOrder {
         let hash_ordered = files
             .iter()
             .filter(|f| MetricsFileLayout::of(&f.key).is_hash_ordered())
             .count();
+        let clustered = files.iter().filter(|f| is_clustered_file(&f.key)).count();
         match self {
             Self::MetricsHashSorted | Self::MetricsIndexed if hash_ordered == files.len() => {
                 FileSortOrder::HashTimestampAsc
             }
-            _ if hash_ordered > 0 => FileSortOrder::None,
+            _ if hash_ordered > 0 || clustered > 0 => FileSortOrder::None,
             Self::MetricsHashSorted | Self::MetricsIndexed => FileSortOrder::None,
             _ => FileSortOrder::TimestampDesc,
         }
@@ -168,6 +195,14 @@ impl MergeMode {
     pub(super) fn sql(&self, schema: &Schema) -> String {
         match self {
             Self::Classic => format!("SELECT * FROM tbl ORDER BY {TIMESTAMP_COL_NAME} DESC"),
+            Self::Clustered(keys) => {
+                let keys = keys
+                    .iter()
+                    .map(|key| format!("\"{}\" ASC NULLS LAST", key.replace('"', "\"\"")))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                format!("SELECT * FROM tbl ORDER BY {keys}, {TIMESTAMP_COL_NAME} DESC")
+            }
             Self::TraceTimeIndex => {
                 // Files whose records all had a null session_id were persisted
                 // without the column (all-null columns are pruned), so only
@@ -200,6 +235,7 @@ impl fmt::Display for MergeMode {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Self::Classic => write!(f, "classic"),
+            Self::Clustered(keys) => write!(f, "clustered({})", keys.join(",")),
             Self::TraceTimeIndex => write!(f, "trace_time_index"),
             Self::FileList => write!(f, "file_list"),
             #[cfg(feature = "enterprise")]
@@ -326,6 +362,55 @@ mod tests {
         );
     }
 
+    #[test]
+    fn clustered_by_sort_keys() {
+        let schema = Schema::new(vec![
+            Field::new(TIMESTAMP_COL_NAME, DataType::Int64, false),
+            Field::new("service", DataType::Utf8, true),
+            Field::new("host", DataType::Utf8, true),
+        ]);
+        let keys = vec![
+            "service".to_string(),
+            "missing".to_string(),
+            "host".to_string(),
+        ];
+        let mode = MergeMode::Classic.with_sort_keys(&keys, &schema);
+        assert!(mode.is_clustered());
+        assert_eq!(mode.to_string(), "clustered(service,host)");
+        assert_eq!(
+            mode.sql(&schema),
+            r#"SELECT * FROM tbl ORDER BY "service" ASC NULLS LAST, "host" ASC NULLS LAST, _timestamp DESC"#
+        );
+        assert_eq!(mode.output_sort_order(), FileSortOrder::None);
+        assert_eq!(mode.file_layout(), MetricsFileLayout::Legacy);
+        // no key in the schema, or a mode with its own order: unchanged
+        assert!(matches!(
+            MergeMode::Classic.with_sort_keys(&["missing".to_string()], &schema),
+            MergeMode::Classic
+        ));
+        assert!(matches!(
+            MergeMode::FileList.with_sort_keys(&keys, &schema),
+            MergeMode::FileList
+        ));
+
+        // a clustered input is not `_timestamp DESC`, whatever the mode
+        let legacy = FileKey::from_file_name("files/o/logs/app/2026/08/18/10/1.parquet");
+        let clustered =
+            FileKey::from_file_name("files/o/logs/app/2026/08/18/10/clustered-v1-2.parquet");
+        assert_eq!(
+            mode.input_sort_order(std::slice::from_ref(&legacy)),
+            FileSortOrder::TimestampDesc
+        );
+        assert_eq!(
+            mode.input_sort_order(&[legacy.clone(), clustered.clone()]),
+            FileSortOrder::None
+        );
+        assert_eq!(
+            MergeMode::Classic.input_sort_order(&[legacy, clustered]),
+            FileSortOrder::None
+        );
+    }
+
     #[test]
     fn output_file_format_uses_parquet_for_ingester_metrics() {
         let configured = "parquet,metrics=vortex"
@@ -30,6 +30,7 @@ pub mod storage;
 pub mod table_provider;
 pub mod udaf;
 pub mod udf;
+pub mod udtf;
 pub mod vortex;
 
 #[derive(PartialEq, Debug)]
@@ -0,0 +1,212 @@
+// Copyright 2026 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! `knn(field, '[..]', k)` selects the k rows of the queried time range,
+//! among the rows matching the other conditions of the same WHERE clause,
+//! whose `field` is most similar to the query vector.
+//!
+//! [`RewriteKnnRule`] turns `Filter(knn(f, v, k), input)` into
+//! `Limit(k, Sort(cosine_similarity(f, v) DESC, Filter(knn(f, v, k), input)))`.
+//! The inner filter only picks candidates, per file from the vector index or
+//! per batch without one, and the sort with limit above it merges them into
+//! the global top k.
+//!
+//! With other conditions, `Filter(knn(f, v, k) AND others, input)` becomes
+//! `Limit(k, Sort(cosine_similarity(f, v) DESC, Filter(others, input)))`. The
+//! candidates of a file or batch are picked before the other conditions are
+//! applied, so they can all be filtered out while matching rows outside them
+//! belong to the top k. The rows matching the other conditions are ranked
+//! instead, without the vector index.
+
+use std::sync::Arc;
+
+use datafusion::{
+    common::{
+        Result, plan_err,
+        tree_node::{Transformed, TreeNode},
+    },
+    config::ConfigOptions,
+    logical_expr::{
+        Expr, Filter, LogicalPlan, LogicalPlanBuilder,
+        expr::ScalarFunction,
+        utils::{conjunction, split_conjunction_owned},
+    },
+    optimizer::AnalyzerRule,
+};
+
+use crate::datafusion::udf::vector_udf::{COSINE_SIMILARITY_UDF, KNN_UDF_NAME, knn_params};
+
+/// Analyzer rule that rewrites `knn()` conditions into a global top k.
+///
+/// Note: an analyzer rule runs once, an optimizer rule would wrap the
+/// candidate filter it leaves behind again on every pass.
+#[derive(Default, Debug)]
+pub struct RewriteKnnRule {}
+
+impl RewriteKnnRule {
+    #[allow(missing_docs)]
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl AnalyzerRule for RewriteKnnRule {
+    fn name(&self) -> &str {
+        "rewrite_knn"
+    }
+
+    fn analyze(&self, plan: LogicalPlan, _config: &ConfigOptions) -> Result<LogicalPlan> {
+        Ok(plan.transform_up(rewrite_knn)?.data)
+    }
+}
+
+fn rewrite_knn(plan: LogicalPlan) -> Result<Transformed<LogicalPlan>> {
+    let LogicalPlan::Filter(filter) = plan else {
+        if plan.expressions().iter().any(contains_knn) {
+            return plan_err!("knn() can only be used as a condition of the WHERE clause");
+        }
+        return Ok(Transformed::no(plan));
+    };
+    if !contains_knn(&filter.predicate) {
+        return Ok(Transformed::no(LogicalPlan::Filter(filter)));
+    }
+
+    let (knn, others): (Vec<_>, Vec<_>) = split_conjunction_owned(filter.predicate)
+        .into_iter()
+        .partition(is_knn);
+    if knn.len() > 1 || others.iter().any(contains_knn) {
+        return plan_err!("knn() can only be used once, as an AND condition of the WHERE clause");
+    }
+    let knn = knn.into_iter().next().unwrap();
+    let Expr::ScalarFunction(ScalarFunction { args, .. }) = &knn else {
+        unreachable!("is_knn only matches knn() calls");
+    };
+    let k = match args.as_slice() {
+        [_, Expr::Literal(query, _), Expr::Literal(k, _)] => knn_params(query, k).map(|(_, k)| k),
+        _ => None,
+    };
+    let Some(k) = k else {
+        return plan_err!(
+            "knn() params should be: knn(vector_field, '[1.0, 2.0, ...]', k) with k > 0"
+        );
+    };
+
+    let similarity = Expr::ScalarFunction(ScalarFunction::new_udf(
+        Arc::new(COSINE_SIMILARITY_UDF.clone()),
+        vec![args[0].clone(), args[1].clone()],
+    ));
+    let candidates = Filter::try_new(conjunction(others).unwrap_or(knn), filter.input)?;
+    let plan = LogicalPlanBuilder::from(LogicalPlan::Filter(candidates))
+        .sort_with_limit(vec![similarity.sort(false, false)], Some(k))?
+        .limit(0, Some(k))?
+        .build()?;
+    Ok(Transformed::yes(plan))
+}
+
+fn is_knn(expr: &Expr) -> bool {
+    matches!(expr, Expr::ScalarFunction(f) if f.name() == KNN_UDF_NAME)
+}
+
+fn contains_knn(expr: &Expr) -> bool {
+    expr.exists(|e| Ok(is_knn(e))).unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use datafusion::{
+        arrow::{
+            array::{ArrayRef, StringArray, make_builder},
+            datatypes::{DataType, Field, Schema},
+            record_batch::RecordBatch,
+        },
+        assert_batches_eq,
+        datasource::MemTable,
+        execution::SessionStateBuilder,
+        prelude::SessionContext,
+    };
+
+    use super::*;
+    use crate::datafusion::udf::vector_udf::KNN_UDF;
+
+    async fn create_context() -> SessionContext {
+        use config::utils::vector::{append_vector, vector_data_type};
+
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Utf8, false),
+            Field::new("level", DataType::Utf8, false),
+            Field::new("embedding", vector_data_type(2), true),
+        ]));
+        // two batches, so that a top k per batch would return 2 * k rows
+        let mut batches = Vec::new();
+        for rows in [
+            [("a", "info", [1.0, 0.0]), ("b", "error", [0.8, 0.6])],
+            [("c", "info", [0.6, 0.8]), ("d", "error", [0.0, 1.0])],
+        ] {
+            let mut embedding = make_builder(&vector_data_type(2), rows.len());
+            for (_, _, vector) in rows.iter() {
+                append_vector(embedding.as_mut(), 2, Some(vector));
+            }
+            let columns: Vec<ArrayRef> = vec![
+                Arc::new(StringArray::from_iter_values(rows.iter().map(|r| r.0))),
+                Arc::new(StringArray::from_iter_values(rows.iter().map(|r| r.1))),
+                embedding.finish(),
+            ];
+            batches.push(RecordBatch::try_new(schema.clone(), columns).unwrap());
+        }
+
+        let state = SessionStateBuilder::new()
+            .with_default_features()
+            .with_analyzer_rule(Arc::new(RewriteKnnRule::new()))
+            .build();
+        let ctx = SessionContext::new_with_state(state);
+        ctx.register_udf(COSINE_SIMILARITY_UDF.clone());
+        ctx.register_udf(KNN_UDF.clone());
+        let provider = MemTable::try_new(schema, vec![batches]).unwrap();
+        ctx.register_table("t", Arc::new(provider)).unwrap();
+        ctx
+    }
+
+    #[tokio::test]
+    async fn test_rewrite_knn() {
+        let ctx = create_context().await;
+
+        // the global top 2, not the top 2 of each batch
+        let sql = "select id from t where knn(embedding, '[1, 0]', 2) order by id";
+        let batches = ctx.sql(sql).await.unwrap().collect().await.unwrap();
+        let expected = ["+----+", "| id |", "+----+", "| a  |", "| b  |", "+----+"];
+        assert_batches_eq!(expected, &batches);
+
+        // the other conditions apply before the ranking: the unfiltered top 2
+        // are c and d, d is an error and a is the next info row
+        let sql =
+            "select id from t where knn(embedding, '[0, 1]', 2) and level = 'info' order by id";
+        let batches = ctx.sql(sql).await.unwrap().collect().await.unwrap();
+        let expected = ["+----+", "| id |", "+----+", "| a  |", "| c  |", "+----+"];
+        assert_batches_eq!(expected, &batches);
+
+        for sql in [
+            "select id from t where knn(embedding, '[1, 0]', 2) or level = 'info'",
+            "select id from t where knn(embedding, '[1, 0]', 1) and knn(embedding, '[0, 1]', 1)",
+            "select knn(embedding, '[1, 0]', 2) from t",
+            "select id from t where knn(embedding, '[1, 0]', 0)",
+        ] {
+            let result = match ctx.sql(sql).await {
+                Ok(df) => df.collect().await.map(|_| ()),
+                Err(e) => Err(e),
+            };
+            assert!(result.is_err(), "{sql}");
+        }
+    }
+}
@@ -47,7 +47,7 @@ use crate::datafusion::optimizer::logical_optimizer::cipher::{
 };
 use crate::{
     datafusion::optimizer::{
-        analyze::remove_index_fields::RemoveIndexFieldsRule,
+        analyze::{remove_index_fields::RemoveIndexFieldsRule, rewrite_knn::RewriteKnnRule},
         context::{PhysicalOptimizerContext, generate_streaming_agg_rules},
         eliminate_aggregate::EliminateAggregateRule,
         logical_optimizer::{
@@ -72,14 +72,17 @@ pub mod stream_aggregate;
 pub mod utils;
 
 pub fn generate_analyzer_rules(sql: &Sql) -> Vec<Arc<dyn AnalyzerRule + Send + Sync>> {
-    vec![Arc::new(RemoveIndexFieldsRule::new(
-        sql.columns
-            .iter()
-            .any(|(_, columns)| columns.contains(ORIGINAL_DATA_COL_NAME)),
-        sql.columns
-            .iter()
-            .any(|(_, columns)| columns.contains(ALL_VALUES_COL_NAME)),
-    ))]
+    vec![
+        Arc::new(RemoveIndexFieldsRule::new(
+            sql.columns
+                .iter()
+                .any(|(_, columns)| columns.contains(ORIGINAL_DATA_COL_NAME)),
+            sql.columns
+                .iter()
+                .any(|(_, columns)| columns.contains(ALL_VALUES_COL_NAME)),
+        )),
+        Arc::new(RewriteKnnRule::new()),
+    ]
 }
 
 pub fn generate_optimizer_rules(sql: &Sql) -> Vec<Arc<dyn OptimizerRule + Send + Sync>> {
@@ -18,7 +18,10 @@ use std::sync::{
     atomic::{AtomicBool, Ordering},
 };
 
-use config::tantivy::tokenizer::o2_collect_search_tokens;
+use config::{
+    meta::stream::FtsTokenizer,
+    tantivy::tokenizer::{collect_phrase_tokens, collect_search_tokens},
+};
 use datafusion::{
     common::{
         Result,
@@ -32,11 +35,12 @@ use datafusion::{
     physical_optimizer::PhysicalOptimizerRule,
     physical_plan::{
         ExecutionPlan, PhysicalExpr,
-        expressions::{BinaryExpr, Column, InListExpr, NotExpr},
+        expressions::{BinaryExpr, Column, InListExpr, Literal, NotExpr},
         filter::{FilterExec, FilterExecBuilder},
         limit::LocalLimitExec,
         projection::ProjectionExec,
     },
+    scalar::ScalarValue,
 };
 use hashbrown::HashSet;
 use parking_lot::Mutex;
@@ -47,17 +51,29 @@ use crate::{
             extract_string_literal, get_column_name, is_column, is_only_timestamp_filter, is_value,
         },
         udf::{
-            MATCH_FIELD_IGNORE_CASE_UDF_NAME, MATCH_FIELD_UDF_NAME, STR_MATCH_UDF_IGNORE_CASE_NAME,
-            STR_MATCH_UDF_NAME,
+            FUZZY_MATCH_UDF_NAME, MATCH_FIELD_IGNORE_CASE_UDF_NAME, MATCH_FIELD_UDF_NAME,
+            MATCH_PHRASE_UDF_NAME, STR_MATCH_UDF_IGNORE_CASE_NAME, STR_MATCH_UDF_NAME,
+            ip_udf::{Cidr, IP_IN_CIDR_UDF_NAME},
             match_all_udf::{FUZZY_MATCH_ALL_UDF_NAME, MATCH_ALL_UDF_NAME},
+            vector_udf::KNN_UDF_NAME,
         },
     },
-    index::{Condition, IndexCondition},
+    index::{Condition, IndexCondition, get_knn_params},
 };
 
 #[derive(Default, Debug)]
 pub struct IndexRule {
     index_fields: HashSet<String>,
+    // full text search fields, their tokens are all indexed in one field
+    fts_fields: HashSet<String>,
+    fts_tokenizer: FtsTokenizer,
+    // false when some searched files may have been indexed with a previous,
+    // inexact tokenizer
+    fts_index_exact: bool,
+    // numeric fields indexed as fast fields for range queries
+    range_fields: HashSet<String>,
+    // string fields with a vector (ANN) index
+    vector_fields: HashSet<String>,
     index_condition: Arc<Mutex<Option<IndexCondition>>>,
     // this set to true when all filter can be extract to
     // index condition(except _timestamp filter)
@@ -71,11 +87,50 @@ impl IndexRule {
     ) -> Self {
         Self {
             index_fields,
+            fts_fields: HashSet::new(),
+            fts_tokenizer: FtsTokenizer::default(),
+            fts_index_exact: true,
+            range_fields: HashSet::new(),
+            vector_fields: HashSet::new(),
             index_condition,
             can_optimize: Arc::new(AtomicBool::new(false)),
         }
     }
 
+    /// Allow field scoped full text functions, like match_phrase() and
+    /// fuzzy_match(), on these fields to use the index.
+    pub fn with_fts_fields(mut self, fts_fields: HashSet<String>) -> Self {
+        self.fts_fields = fts_fields;
+        self
+    }
+
+    /// The tokenizer the stream indexes its full text search fields with.
+    pub fn with_fts_tokenizer(mut self, fts_tokenizer: FtsTokenizer) -> Self {
+        self.fts_tokenizer = fts_tokenizer;
+        self
+    }
+
+    /// Whether the full text indexes of all searched files were built with
+    /// the current tokenizer. When they may not be, full text hits are
+    /// verified by the filter even if the current tokenizer is exact.
+    pub fn with_fts_index_exact(mut self, fts_index_exact: bool) -> Self {
+        self.fts_index_exact = fts_index_exact;
+        self
+    }
+
+    /// Allow `<`, `<=`, `>` and `>=` comparisons on these numeric fields to
+    /// use the range index.
+    pub fn with_range_fields(mut self, range_fields: HashSet<String>) -> Self {
+        self.range_fields = range_fields;
+        self
+    }
+
+    /// Allow `knn()` on these fields to be answered by the vector index.
+    pub fn with_vector_fields(mut self, vector_fields: HashSet<String>) -> Self {
+        self.vector_fields = vector_fields;
+        self
+    }
+
     pub fn can_optimize(&self) -> bool {
         self.can_optimize.load(Ordering::Relaxed)
     }
@@ -91,8 +146,15 @@ impl PhysicalOptimizerRule for IndexRule {
             return Ok(plan);
         }
 
-        let mut rewriter =
-            IndexOptimizer::new(self.index_fields.clone(), self.index_condition.clone());
+        let mut rewriter = IndexOptimizer::new(
+            self.index_fields.clone(),
+            self.fts_fields.clone(),
+            self.fts_tokenizer,
+            self.fts_tokenizer.is_exact() && self.fts_index_exact,
+            self.range_fields.clone(),
+            self.vector_fields.clone(),
+            self.index_condition.clone(),
+        );
         let plan = plan.rewrite(&mut rewriter).data()?;
 
         // If no filter was found at all (e.g., SELECT count(*) FROM table),
@@ -127,6 +189,12 @@ impl PhysicalOptimizerRule for IndexRule {
 
 struct IndexOptimizer {
     index_fields: HashSet<String>,
+    fts_fields: HashSet<String>,
+    fts_tokenizer: FtsTokenizer,
+    // full text hits of every searched file match exactly
+    fts_index_exact: bool,
+    range_fields: HashSet<String>,
+    vector_fields: HashSet<String>,
     index_condition: Arc<Mutex<Option<IndexCondition>>>,
     // set to true when the filter only have _timestamp filter
     can_optimize: bool,
@@ -139,10 +207,20 @@ struct IndexOptimizer {
 impl IndexOptimizer {
     pub fn new(
         index_fields: HashSet<String>,
+        fts_fields: HashSet<String>,
+        fts_tokenizer: FtsTokenizer,
+        fts_index_exact: bool,
+        range_fields: HashSet<String>,
+        vector_fields: HashSet<String>,
         index_condition: Arc<Mutex<Option<IndexCondition>>>,
     ) -> Self {
         Self {
             index_fields,
+            fts_fields,
+            fts_tokenizer,
+            fts_index_exact,
+            range_fields,
+            vector_fields,
             index_condition,
             can_optimize: false,
             has_filter: false,
@@ -164,6 +242,11 @@ impl IndexOptimizer {
     ) -> Self {
         Self {
             index_fields,
+            fts_fields: HashSet::new(),
+            fts_tokenizer: FtsTokenizer::default(),
+            fts_index_exact: true,
+            range_fields: HashSet::new(),
+            vector_fields: HashSet::new(),
             index_condition,
             can_optimize: false,
             has_filter: false,
@@ -182,7 +265,14 @@ impl TreeNodeRewriter for IndexOptimizer {
             let mut index_conditions = IndexCondition::new();
             let mut other_conditions = Vec::new();
             for expr in split_conjunction(filter.predicate()) {
-                if is_expr_valid_for_index(expr, &self.index_fields) {
+                if is_expr_valid_for_index(
+                    expr,
+                    &self.index_fields,
+                    &self.fts_fields,
+                    &self.fts_tokenizer,
+                ) || is_range_expr_valid_for_index(expr, &self.range_fields)
+                    || is_knn_expr_valid_for_index(expr, &self.vector_fields)
+                {
                     let condition = Condition::from_physical_expr(expr);
                     index_conditions.add_condition(condition);
                 } else {
@@ -190,8 +280,12 @@ impl TreeNodeRewriter for IndexOptimizer {
                 }
             }
 
-            // check if we can remove the filter
-            let is_remove_filter = self.is_remove_filter || index_conditions.can_remove_filter();
+            // check if we can remove the filter, n-gram and bigram tokens
+            // can match out of order so their full text hits are verified,
+            // also when older files may still carry such an index
+            let is_remove_filter = (self.fts_index_exact || !index_conditions.has_full_text())
+                && ((self.is_remove_filter && !index_conditions.need_verify())
+                    || index_conditions.can_remove_filter());
 
             // set the index condition
             if !index_conditions.is_empty() {
@@ -253,7 +347,12 @@ fn construct_filter_exec(
 }
 
 // Check if the expression is valid for the index.
-fn is_expr_valid_for_index(expr: &Arc<dyn PhysicalExpr>, index_fields: &HashSet<String>) -> bool {
+fn is_expr_valid_for_index(
+    expr: &Arc<dyn PhysicalExpr>,
+    index_fields: &HashSet<String>,
+    fts_fields: &HashSet<String>,
+    fts_tokenizer: &FtsTokenizer,
+) -> bool {
     if let Some(expr) = expr.downcast_ref::<BinaryExpr>() {
         match expr.op() {
             Operator::Eq | Operator::NotEq => {
@@ -270,8 +369,17 @@ fn is_expr_valid_for_index(expr: &Arc<dyn PhysicalExpr>, index_fields: &HashSet<
                 }
             }
             Operator::And | Operator::Or => {
-                return is_expr_valid_for_index(expr.left(), index_fields)
-                    && is_expr_valid_for_index(expr.right(), index_fields);
+                return is_expr_valid_for_index(
+                    expr.left(),
+                    index_fields,
+                    fts_fields,
+                    fts_tokenizer,
+                ) && is_expr_valid_for_index(
+                    expr.right(),
+                    index_fields,
+                    fts_fields,
+                    fts_tokenizer,
+                );
             }
             _ => return false,
         }
@@ -291,26 +399,106 @@ fn is_expr_valid_for_index(expr: &Arc<dyn PhysicalExpr>, index_fields: &HashSet<
             MATCH_ALL_UDF_NAME => {
                 expr.args().len() == 1
                     && extract_string_literal(&expr.args()[0])
-                        .map(|s| !o2_collect_search_tokens(&s).is_empty())
+                        .map(|s| !collect_search_tokens(fts_tokenizer, &s).is_empty())
                         .unwrap_or(false)
             }
             FUZZY_MATCH_ALL_UDF_NAME => expr.args().len() == 2,
+            FUZZY_MATCH_UDF_NAME => {
+                expr.args().len() == 3
+                    && is_column(&expr.args()[0])
+                    && fts_fields.contains(get_column_name(&expr.args()[0]))
+                    && is_value(&expr.args()[1])
+                    && is_value(&expr.args()[2])
+            }
+            MATCH_PHRASE_UDF_NAME => {
+                expr.args().len() == 3
+                    && is_column(&expr.args()[0])
+                    && fts_fields.contains(get_column_name(&expr.args()[0]))
+                    && extract_string_literal(&expr.args()[1])
+                        .map(|s| !collect_phrase_tokens(fts_tokenizer, &s).is_empty())
+                        .unwrap_or(false)
+                    && is_value(&expr.args()[2])
+            }
             STR_MATCH_UDF_NAME
             | STR_MATCH_UDF_IGNORE_CASE_NAME
             | MATCH_FIELD_UDF_NAME
             | MATCH_FIELD_IGNORE_CASE_UDF_NAME => {
                 expr.args().len() == 2 && index_fields.contains(get_column_name(&expr.args()[0]))
             }
+            // only IPv4 networks can be looked up in the index, by the regex of their addresses
+            IP_IN_CIDR_UDF_NAME => {
+                expr.args().len() == 2
+                    && is_column(&expr.args()[0])
+                    && index_fields.contains(get_column_name(&expr.args()[0]))
+                    && extract_string_literal(&expr.args()[1])
+                        .and_then(|s| Cidr::parse(&s))
+                        .is_some_and(|cidr| cidr.ipv4_regex().is_some())
+            }
             _ => false,
         };
     } else if let Some(expr) = expr.downcast_ref::<NotExpr>() {
-        return is_expr_valid_for_index(expr.arg(), index_fields);
+        // the index only returns a superset of the rows for conditions that
+        // need verify, excluding that superset would drop matching rows
+        return is_expr_valid_for_index(expr.arg(), index_fields, fts_fields, fts_tokenizer)
+            && !Condition::from_physical_expr(expr.arg()).need_verify();
     } else {
         return false;
     }
     true
 }
 
+// Check if the expression is a `knn(field, '[..]', k)` the vector index can
+// answer. Only top level conjuncts get here, the index can't answer a knn()
+// under OR or NOT.
+fn is_knn_expr_valid_for_index(
+    expr: &Arc<dyn PhysicalExpr>,
+    vector_fields: &HashSet<String>,
+) -> bool {
+    let Some(expr) = expr.downcast_ref::<ScalarFunctionExpr>() else {
+        return false;
+    };
+    expr.name() == KNN_UDF_NAME
+        && expr.args().len() == 3
+        && is_column(&expr.args()[0])
+        && vector_fields.contains(get_column_name(&expr.args()[0]))
+        && get_knn_params(&expr.args()[1], &expr.args()[2]).is_some()
+}
+
+// Check if the expression is a comparison the range index can answer: a
+// numeric column without cast against a non-null literal of the same type.
+fn is_range_expr_valid_for_index(
+    expr: &Arc<dyn PhysicalExpr>,
+    range_fields: &HashSet<String>,
+) -> bool {
+    let Some(expr) = expr.downcast_ref::<BinaryExpr>() else {
+        return false;
+    };
+    if !matches!(
+        expr.op(),
+        Operator::Lt | Operator::LtEq | Operator::Gt | Operator::GtEq
+    ) {
+        return false;
+    }
+    let (column, value) = if expr.left().downcast_ref::<Column>().is_some() {
+        (expr.left(), expr.right())
+    } else {
+        (expr.right(), expr.left())
+    };
+    let Some(column) = column.downcast_ref::<Column>() else {
+        return false;
+    };
+    let Some(value) = value.downcast_ref::<Literal>() else {
+        return false;
+    };
+    range_fields.contains(column.name())
+        && matches!(
+            value.value(),
+            ScalarValue::Int64(Some(_))
+                | ScalarValue::UInt64(Some(_))
+                | ScalarValue::Float64(Some(_))
+        )
+}
+
 #[cfg(test)]
 mod tests {
     use std::sync::Arc;
@@ -319,7 +507,7 @@ mod tests {
     use arrow_schema::{DataType, Field, FieldRef, Schema};
     use datafusion::{
         catalog::MemTable,
-        logical_expr::Operator,
+        logical_expr::{Operator, ScalarUDF},
         physical_expr::{
             PhysicalExpr,
             expressions::{BinaryExpr, Column, Literal},
@@ -329,13 +517,19 @@ mod tests {
         scalar::ScalarValue,
     };
 
-    use super::{is_expr_valid_for_index, *};
+    use super::{
+        is_expr_valid_for_index, is_knn_expr_valid_for_index, is_range_expr_valid_for_index, *,
+    };
     use crate::{
         datafusion::{
             optimizer::physical_optimizer::utils::is_only_timestamp_filter,
             udf::{
+                fuzzy_match_udf::FUZZY_MATCH_UDF,
+                ip_udf::IP_IN_CIDR_UDF,
                 match_all_udf::{self, MATCH_ALL_UDF},
+                match_phrase_udf::MATCH_PHRASE_UDF,
                 str_match_udf::{self, STR_MATCH_UDF},
+                vector_udf::KNN_UDF,
             },
         },
         index::Condition,
@@ -524,9 +718,19 @@ mod tests {
 
         for (expr, is_valid, condition) in case {
             if is_valid {
-                assert!(is_expr_valid_for_index(&expr, &index_fields));
+                assert!(is_expr_valid_for_index(
+                    &expr,
+                    &index_fields,
+                    &HashSet::new(),
+                    &FtsTokenizer::O2
+                ));
             } else {
-                assert!(!is_expr_valid_for_index(&expr, &index_fields));
+                assert!(!is_expr_valid_for_index(
+                    &expr,
+                    &index_fields,
+                    &HashSet::new(),
+                    &FtsTokenizer::O2
+                ));
             }
             if let Some(condition) = condition {
                 assert_eq!(Condition::from_physical_expr(&expr), condition);
@@ -534,6 +738,164 @@ mod tests {
         }
     }
 
+    fn field_fn(
+        name: &str,
+        udf: ScalarUDF,
+        field: &str,
+        lit: &str,
+        n: i64,
+    ) -> Arc<dyn PhysicalExpr> {
+        Arc::new(ScalarFunctionExpr::new(
+            name,
+            Arc::new(udf),
+            vec![
+                column(field),
+                literal(lit),
+                Arc::new(Literal::new(ScalarValue::Int64(Some(n)))),
+            ],
+            FieldRef::new(Field::new(name, DataType::Boolean, true)),
+            Arc::new(ConfigOptions::default()),
+        ))
+    }
+
+    #[test]
+    fn test_is_expr_valid_for_index_fts_field_functions() {
+        let index_fields = HashSet::from(["name".to_string()]);
+        let fts_fields = HashSet::from(["log".to_string()]);
+        let phrase = |field: &str, lit: &str| {
+            field_fn(
+                MATCH_PHRASE_UDF_NAME,
+                MATCH_PHRASE_UDF.clone(),
+                field,
+                lit,
+                1,
+            )
+        };
+        let fuzzy = |field: &str, lit: &str| {
+            field_fn(FUZZY_MATCH_UDF_NAME, FUZZY_MATCH_UDF.clone(), field, lit, 2)
+        };
+
+        let expr = phrase("log", "connection reset");
+        assert!(is_expr_valid_for_index(
+            &expr,
+            &index_fields,
+            &fts_fields,
+            &FtsTokenizer::O2
+        ));
+        assert_eq!(
+            Condition::from_physical_expr(&expr),
+            Condition::MatchPhrase("log".to_string(), "connection reset".to_string(), 1)
+        );
+        let expr = fuzzy("log", "conection");
+        assert!(is_expr_valid_for_index(
+            &expr,
+            &index_fields,
+            &fts_fields,
+            &FtsTokenizer::O2
+        ));
+        assert_eq!(
+            Condition::from_physical_expr(&expr),
+            Condition::FuzzyMatch("log".to_string(), "conection".to_string(), 2)
+        );
+
+        // not a full text search field
+        assert!(!is_expr_valid_for_index(
+            &phrase("name", "connection reset"),
+            &index_fields,
+            &fts_fields,
+            &FtsTokenizer::O2
+        ));
+        assert!(!is_expr_valid_for_index(
+            &fuzzy("name", "conection"),
+            &index_fields,
+            &fts_fields,
+            &FtsTokenizer::O2
+        ));
+        // no tokens to look up
+        assert!(!is_expr_valid_for_index(
+            &phrase("log", "a"),
+            &index_fields,
+            &fts_fields,
+            &FtsTokenizer::O2
+        ));
+        // the index over-matches, so the negation can't be answered by it
+        assert!(!is_expr_valid_for_index(
+            &not(phrase("log", "connection reset")),
+            &index_fields,
+            &fts_fields,
+            &FtsTokenizer::O2
+        ));
+        assert!(!is_expr_valid_for_index(
+            &not(fuzzy("log", "conection")),
+            &index_fields,
+            &fts_fields,
+            &FtsTokenizer::O2
+        ));
+        assert!(is_expr_valid_for_index(
+            &not(eq(column("name"), literal("openobserve"))),
+            &index_fields,
+            &fts_fields,
+            &FtsTokenizer::O2
+        ));
+    }
+
+    #[test]
+    fn test_is_expr_valid_for_index_uses_stream_tokenizer() {
+        let index_fields = HashSet::new();
+        let fts_fields = HashSet::from(["log".to_string()]);
+        let expr = field_fn(
+            MATCH_PHRASE_UDF_NAME,
+            MATCH_PHRASE_UDF.clone(),
+            "log",
+            "a",
+            0,
+        );
+        // too short for the default tokenizer, but a valid unigram
+        assert!(!is_expr_valid_for_index(
+            &expr,
+            &index_fields,
+            &fts_fields,
+            &FtsTokenizer::O2
+        ));
+        assert!(is_expr_valid_for_index(
+            &expr,
+            &index_fields,
+            &fts_fields,
+            &FtsTokenizer::Ngram {
+                min_gram: 1,
+                max_gram: 3
+            }
+        ));
+    }
+
+    #[test]
+    fn test_is_expr_valid_for_index_ip_in_cidr() {
+        let index_fields = HashSet::from(["client_ip".to_string()]);
+        let ip_in_cidr = |field: &str, lit: &str| -> Arc<dyn PhysicalExpr> {
+            Arc::new(ScalarFunctionExpr::new(
+                IP_IN_CIDR_UDF_NAME,
+                Arc::new(IP_IN_CIDR_UDF.clone()),
+                vec![column(field), literal(lit)],
+                FieldRef::new(Field::new(IP_IN_CIDR_UDF_NAME, DataType::Boolean, true)),
+                Arc::new(ConfigOptions::default()),
+            ))
+        };
+        let valid = |expr: &Arc<dyn PhysicalExpr>| {
+            is_expr_valid_for_index(expr, &index_fields, &HashSet::new(), &FtsTokenizer::O2)
+        };
+
+        let expr = ip_in_cidr("client_ip", "10.0.0.0/8");
+        assert!(valid(&expr));
+        assert_eq!(
+            Condition::from_physical_expr(&expr),
+            Condition::IpInCidr("client_ip".to_string(), "10.0.0.0/8".to_string())
+        );
+        // not an index field, an IPv6 network or not a network
+        assert!(!valid(&ip_in_cidr("server_ip", "10.0.0.0/8")));
+        assert!(!valid(&ip_in_cidr("client_ip", "2001:db8::/32")));
+        assert!(!valid(&ip_in_cidr("client_ip", "10.0.0.0/40")));
+    }
+
     #[test]
     fn test_index_rule_name_returns_expected() {
         let rule = IndexRule::new(HashSet::new(), Arc::new(parking_lot::Mutex::new(None)));
@@ -630,6 +992,232 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_is_range_expr_valid_for_index() {
+        let range_fields = HashSet::from(["latency".to_string(), "name".to_string()]);
+        let int = |n: i64| -> Arc<dyn PhysicalExpr> {
+            Arc::new(Literal::new(ScalarValue::Int64(Some(n))))
+        };
+
+        assert!(is_range_expr_valid_for_index(
+            &gt(column("latency"), int(5000)),
+            &range_fields
+        ));
+        // literal on the left
+        assert!(is_range_expr_valid_for_index(
+            &lt(int(5000), column("latency")),
+            &range_fields
+        ));
+        // not a range indexed field
+        assert!(!is_range_expr_valid_for_index(
+            &gt(column("other"), int(5000)),
+            &range_fields
+        ));
+        // not a numeric literal
+        assert!(!is_range_expr_valid_for_index(
+            &gt(column("name"), literal("a")),
+            &range_fields
+        ));
+        let null: Arc<dyn PhysicalExpr> = Arc::new(Literal::new(ScalarValue::Int64(None)));
+        assert!(!is_range_expr_valid_for_index(
+            &gt(column("latency"), null),
+            &range_fields
+        ));
+        // equality goes through the term index
+        assert!(!is_range_expr_valid_for_index(
+            &eq(column("latency"), int(5000)),
+            &range_fields
+        ));
+    }
+
+    #[test]
+    fn test_is_knn_expr_valid_for_index() {
+        let knn = |field: &str, vector: &str, k: i64| -> Arc<dyn PhysicalExpr> {
+            Arc::new(ScalarFunctionExpr::new(
+                KNN_UDF_NAME,
+                Arc::new(KNN_UDF.clone()),
+                vec![
+                    column(field),
+                    literal(vector),
+                    Arc::new(Literal::new(ScalarValue::Int64(Some(k)))),
+                ],
+                FieldRef::new(Field::new("knn", DataType::Boolean, true)),
+                Arc::new(ConfigOptions::default()),
+            ))
+        };
+        let vector_fields = HashSet::from(["embedding".to_string()]);
+        assert!(is_knn_expr_valid_for_index(
+            &knn("embedding", "[1.0, 0.5]", 10),
+            &vector_fields
+        ));
+        // not a vector field
+        assert!(!is_knn_expr_valid_for_index(
+            &knn("name", "[1.0, 0.5]", 10),
+            &vector_fields
+        ));
+        // invalid vector or k
+        assert!(!is_knn_expr_valid_for_index(
+            &knn("embedding", "not a vector", 10),
+            &vector_fields
+        ));
+        assert!(!is_knn_expr_valid_for_index(
+            &knn("embedding", "[1.0, 0.5]", 0),
+            &vector_fields
+        ));
+        // the index can't answer knn() under OR
+        assert!(!is_expr_valid_for_index(
+            &or(
+                knn("embedding", "[1.0, 0.5]", 10),
+                eq(column("name"), literal("a"))
+            ),
+            &HashSet::from(["name".to_string()]),
+            &HashSet::new(),
+            &FtsTokenizer::default(),
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_index_optimizer_range_fields() {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("_timestamp", DataType::Int64, false),
+            Field::new("name", DataType::Utf8, false),
+            Field::new("latency", DataType::Int64, false),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(Int64Array::from(vec![1])),
+                Arc::new(StringArray::from(vec!["openobserve"])),
+                Arc::new(Int64Array::from(vec![550])),
+            ],
+        )
+        .unwrap();
+        let ctx = SessionContext::new();
+        let provider = MemTable::try_new(schema, vec![vec![batch]]).unwrap();
+        ctx.register_table("t", Arc::new(provider)).unwrap();
+
+        let sql = "SELECT count(*) from t where latency BETWEEN 500 AND 599 and name = 'openobserve' and _timestamp > 1715395200000";
+        let plan = ctx.state().create_logical_plan(sql).await.unwrap();
+        let physical_plan = ctx.state().create_physical_plan(&plan).await.unwrap();
+        let index_condition = Arc::new(Mutex::new(None));
+        let mut rewriter = IndexOptimizer::new_with_config(
+            HashSet::from(["name".to_string()]),
+            index_condition.clone(),
+            false,
+            true,
+        );
+        rewriter.range_fields = HashSet::from(["latency".to_string()]);
+        let _physical_plan = physical_plan.rewrite(&mut rewriter).unwrap().data;
+
+        let conditions = index_condition.lock().clone().unwrap().conditions;
+        assert_eq!(conditions.len(), 3);
+        assert!(conditions.contains(&Condition::Range(
+            "latency".to_string(),
+            Operator::GtEq,
+            "500".to_string()
+        )));
+        assert!(conditions.contains(&Condition::Range(
+            "latency".to_string(),
+            Operator::LtEq,
+            "599".to_string()
+        )));
+        // the range conditions are exact, so the filter is removed
+        assert!(rewriter.can_optimize);
+    }
+
+    #[tokio::test]
+    async fn test_index_optimizer_keeps_full_text_filter_over_older_indexes() {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("_timestamp", DataType::Int64, false),
+            Field::new("log", DataType::Utf8, false),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(Int64Array::from(vec![1])),
+                Arc::new(StringArray::from(vec!["openobserve"])),
+            ],
+        )
+        .unwrap();
+        let ctx = SessionContext::new();
+        ctx.register_udf(match_all_udf::MATCH_ALL_UDF.clone());
+        let provider = MemTable::try_new(schema, vec![vec![batch]]).unwrap();
+        ctx.register_table("t", Arc::new(provider)).unwrap();
+
+        let sql =
+            "SELECT count(*) from t where match_all('openobserve') and _timestamp > 1715395200000";
+        for (fts_index_exact, removed) in [(true, true), (false, false)] {
+            let plan = ctx.state().create_logical_plan(sql).await.unwrap();
+            let physical_plan = ctx.state().create_physical_plan(&plan).await.unwrap();
+            let index_condition = Arc::new(Mutex::new(None));
+            let mut rewriter = IndexOptimizer::new_with_config(
+                HashSet::new(),
+                index_condition.clone(),
+                true,
+                true,
+            );
+            // files indexed before a switch from an n-gram tokenizer
+            rewriter.fts_index_exact = fts_index_exact;
+            let _physical_plan = physical_plan.rewrite(&mut rewriter).unwrap().data;
+            assert!(index_condition.lock().is_some());
+            assert_eq!(rewriter.can_optimize, removed, "{fts_index_exact}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_index_optimizer_keeps_not_match_phrase_filter() {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("_timestamp", DataType::Int64, false),
+            Field::new("log", DataType::Utf8, false),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(Int64Array::from(vec![1, 2])),
+                // the index finds both terms of the phrase in the second row
+                Arc::new(StringArray::from(vec![
+                    "connection reset by peer",
+                    "reset the connection",
+                ])),
+            ],
+        )
+        .unwrap();
+        let ctx = SessionContext::new();
+        ctx.register_udf(MATCH_PHRASE_UDF.clone());
+        let provider = MemTable::try_new(schema, vec![vec![batch]]).unwrap();
+        ctx.register_table("t", Arc::new(provider)).unwrap();
+
+        let sql = "SELECT log from t where not match_phrase(log, 'connection reset', 0)";
+        let plan = ctx.state().create_logical_plan(sql).await.unwrap();
+        let physical_plan = ctx.state().create_physical_plan(&plan).await.unwrap();
+        let index_condition = Arc::new(Mutex::new(None));
+        let mut rewriter =
+            IndexOptimizer::new_with_config(HashSet::new(), index_condition.clone(), true, true);
+        rewriter.fts_fields = HashSet::from(["log".to_string()]);
+        let physical_plan = physical_plan.rewrite(&mut rewriter).unwrap().data;
+        assert!(index_condition.lock().is_none());
+        assert!(!rewriter.can_optimize);
+
+        let batches = datafusion::physical_plan::collect(physical_plan, ctx.task_ctx())
+            .await
+            .unwrap();
+        let logs = batches
+            .iter()
+            .flat_map(|batch| {
+                let array = batch
+                    .column(0)
+                    .as_any()
+                    .downcast_ref::<StringArray>()
+                    .unwrap();
+                array
+                    .iter()
+                    .map(|v| v.unwrap().to_string())
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(logs, vec!["reset the connection".to_string()]);
+    }
+
     #[tokio::test]
     async fn test_index_optimizer_remove_filter_disabled() {
         let schema = Arc::new(Schema::new(vec![
@@ -17,11 +17,13 @@ use std::sync::Arc;
 
 use config::{datafusion::request::Request, meta::cluster::NodeInfo};
 use datafusion::{
+    catalog::memory::DataSourceExec,
     common::{
         Result, TableReference,
         tree_node::{Transformed, TreeNode, TreeNodeRecursion, TreeNodeRewriter, TreeNodeVisitor},
     },
     config::ConfigOptions,
+    datasource::physical_plan::FileScanConfig,
     physical_expr::{LexOrdering, PhysicalExpr, expressions::Column as PhysicalColumn},
     physical_optimizer::PhysicalOptimizerRule,
     physical_plan::{
@@ -162,22 +164,31 @@ impl PhysicalOptimizerRule for RemoteScanRule {
             return Ok(plan);
         }
 
-        if config::get_config()
-            .search
-            .feature_enrichment_broadcast_join_enabled
+        // external tables and table functions are scanned by the leader, only
+        // the stream scans can be sent to the remote nodes
+        let mut visitor = TableNameVisitor::new();
+        plan.visit(&mut visitor)?;
+        let has_external_scan = visitor.has_external_scan;
+
+        if !has_external_scan
+            && config::get_config()
+                .search
+                .feature_enrichment_broadcast_join_enabled
             && should_use_enrichment_broadcast_join(&plan)
         {
             return enrichment_broadcast_join_rewrite(plan, self.remote_scan_nodes.clone());
         }
 
-        if config::get_config().search.feature_broadcast_join_enabled
+        if !has_external_scan
+            && config::get_config().search.feature_broadcast_join_enabled
             && should_use_broadcast_join(&plan)
         {
             return broadcast_join_rewrite(plan, self.remote_scan_nodes.clone());
         }
 
         // if single node and can optimize, add remote scan to top
-        if self.single_node_optimizer_enable && is_single_node_optimize(&plan) {
+        if self.single_node_optimizer_enable && !has_external_scan && is_single_node_optimize(&plan)
+        {
             return remote_scan_to_top_if_needed(plan, self.remote_scan_nodes.clone());
         }
 
@@ -224,7 +235,7 @@ impl TreeNodeRewriter for RemoteScanRewriter {
         if node.name() == "RepartitionExec" || node.name() == "CoalescePartitionsExec" {
             let mut visitor = TableNameVisitor::new();
             node.visit(&mut visitor)?;
-            if !visitor.has_remote_scan {
+            if !visitor.has_remote_scan && !visitor.has_external_scan {
                 let table_name = visitor.table_name.clone().unwrap();
                 let input = node.children()[0];
                 let remote_scan_input =
@@ -247,7 +258,7 @@ impl TreeNodeRewriter for RemoteScanRewriter {
         } else if node.name() == "SortPreservingMergeExec" {
             let mut visitor = TableNameVisitor::new();
             node.visit(&mut visitor)?;
-            if !visitor.has_remote_scan {
+            if !visitor.has_remote_scan && !visitor.has_external_scan {
                 let table_name = visitor.table_name.clone().unwrap();
                 let follow_merge_node = node.clone();
                 let new_input =
@@ -271,7 +282,9 @@ impl TreeNodeRewriter for RemoteScanRewriter {
                     let mut visitor = TableNameVisitor::new();
                     child.visit(&mut visitor)?;
                     // For sort, we should add a SortPreservingMergeExec
-                    if child.name() == "SortExec" {
+                    if visitor.has_external_scan {
+                        new_children.push(child.clone());
+                    } else if child.name() == "SortExec" {
                         let table_name = visitor.table_name.clone().unwrap();
                         let sort = child.downcast_ref::<SortExec>().unwrap();
                         let sort_merge = Arc::new(
@@ -304,7 +317,7 @@ impl TreeNodeRewriter for RemoteScanRewriter {
             for child in node.children() {
                 let mut visitor = TableNameVisitor::new();
                 child.visit(&mut visitor)?;
-                if !visitor.has_remote_scan {
+                if !visitor.has_remote_scan && !visitor.has_external_scan {
                     let table_name = visitor.table_name.clone().unwrap();
                     let remote_scan = Arc::new(RemoteScanExec::new(
                         child.clone(),
@@ -421,7 +434,7 @@ pub fn remote_scan_to_top_if_needed(
 ) -> Result<Arc<dyn ExecutionPlan>> {
     let mut visitor = TableNameVisitor::new();
     plan.visit(&mut visitor)?;
-    if !visitor.has_remote_scan {
+    if !visitor.has_remote_scan && !visitor.has_external_scan {
         let table_name = visitor.table_name.clone().unwrap();
         let remote_scan = Arc::new(RemoteScanExec::new(
             plan,
@@ -438,6 +451,9 @@ struct TableNameVisitor {
     table_name: Option<TableReference>,
     // if RemoteScanExec appear in the physical plan
     has_remote_scan: bool,
+    // if the files of an external table or a table function are scanned in
+    // the physical plan
+    has_external_scan: bool,
 }
 
 impl TableNameVisitor {
@@ -445,6 +461,7 @@ impl TableNameVisitor {
         Self {
             table_name: None,
             has_remote_scan: false,
+            has_external_scan: false,
         }
     }
 }
@@ -461,6 +478,20 @@ impl<'n> TreeNodeVisitor<'n> for TableNameVisitor {
             let table = node.downcast_ref::<NewEmptyExec>().unwrap();
             self.table_name = Some(TableReference::from(table.name()));
             Ok(TreeNodeRecursion::Continue)
+        } else if let Some(data_source_exec) = node.downcast_ref::<DataSourceExec>()
+            && data_source_exec
+                .data_source()
+                .downcast_ref::<FileScanConfig>()
+                .is_some()
+        {
+            // the streams are NewEmptyExec before the rewrite, a file scan is
+            // always an external table
+            self.has_external_scan = true;
+            Ok(TreeNodeRecursion::Continue)
+        } else if name == "StreamingTableExec" {
+            // table functions run their own searches from the leader
+            self.has_external_scan = true;
+            Ok(TreeNodeRecursion::Continue)
         } else {
             Ok(TreeNodeRecursion::Continue)
         }
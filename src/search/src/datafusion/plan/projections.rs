@@ -20,6 +20,7 @@ use config::{
     meta::search::{HavingNode, LogicalOperator},
 };
 use datafusion::{
+    arrow::datatypes::SchemaRef,
     common::tree_node::{TreeNode, TreeNodeRecursion, TreeNodeVisitor},
     logical_expr::{LogicalPlan, Operator},
     prelude::Expr,
@@ -590,6 +591,18 @@ pub async fn get_result_schema(
     Ok(visitor)
 }
 
+/// The arrow schema of the rows `sql` returns, from its logical plan and
+/// without running it.
+pub async fn get_output_schema(sql: Sql) -> Result<SchemaRef, anyhow::Error> {
+    let sql = Arc::new(sql);
+    let ctx = SearchContextBuilder::new()
+        .build(&Request::default(), &sql)
+        .await?;
+    register_table(&ctx, &sql).await?;
+    let plan = ctx.state().create_logical_plan(&sql.sql).await?;
+    Ok(Arc::new(plan.schema().as_arrow().clone()))
+}
+
 #[cfg(test)]
 mod tests {
     use arrow_schema::{DataType, Field, Schema};
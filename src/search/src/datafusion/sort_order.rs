@@ -33,6 +33,11 @@ use datafusion::{
     prelude::col,
 };
 
+/// File-name prefix of files the compactor clustered by the stream sort keys
+/// (`clustered-v1-{id}.parquet`). Their rows are ordered by those keys before
+/// `_timestamp`, so they never carry [`FileSortOrder::TimestampDesc`].
+const CLUSTERED_FILE_PREFIX: &str = "clustered-v1-";
+
 const TIMESTAMP_DESC_COLUMNS: &[SortColumn] = &[SortColumn::desc(TIMESTAMP_COL_NAME)];
 const HASH_TIMESTAMP_ASC_COLUMNS: &[SortColumn] = &[
     SortColumn::asc(HASH_LABEL),
@@ -158,6 +163,18 @@ impl FileSortOrder {
     }
 }
 
+/// True when the file at `path` (a full object key or a bare file name) was
+/// clustered by sort keys.
+pub fn is_clustered_file(path: &str) -> bool {
+    let file_name = path.rsplit('/').next().unwrap_or(path);
+    file_name.starts_with(CLUSTERED_FILE_PREFIX)
+}
+
+/// Mark a new file name (`7099.parquet`) as clustered.
+pub fn clustered_file_name(file_name: &str) -> String {
+    format!("{CLUSTERED_FILE_PREFIX}{file_name}")
+}
+
 impl fmt::Display for FileSortOrder {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -267,4 +284,23 @@ mod tests {
                 .is_some()
         );
     }
+
+    #[test]
+    fn test_clustered_file_marker() {
+        assert_eq!(
+            clustered_file_name("7099.parquet"),
+            "clustered-v1-7099.parquet"
+        );
+        assert!(is_clustered_file(
+            "files/default/logs/app/2026/08/18/10/clustered-v1-7099.parquet"
+        ));
+        assert!(is_clustered_file("clustered-v1-7099.vortex"));
+        assert!(!is_clustered_file(
+            "files/default/logs/app/2026/08/18/10/7099.parquet"
+        ));
+        // only the file name is checked, not the stream name
+        assert!(!is_clustered_file(
+            "files/default/logs/clustered-v1-app/2026/08/18/10/7099.parquet"
+        ));
+    }
 }
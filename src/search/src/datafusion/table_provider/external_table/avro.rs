@@ -0,0 +1,418 @@
+// Copyright 2026 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! A minimal reader of Avro object container files, enough for the manifest
+//! lists and the manifests of Iceberg tables.
+
+use std::{collections::HashMap, io::Read};
+
+use anyhow::{Result, anyhow, bail};
+use config::utils::json;
+use flate2::read::DeflateDecoder;
+
+const MAGIC: &[u8] = b"Obj\x01";
+const SYNC_SIZE: usize = 16;
+
+#[derive(Debug, Clone)]
+enum Schema {
+    Null,
+    Boolean,
+    Int,
+    Long,
+    Float,
+    Double,
+    Bytes,
+    String,
+    Record(Vec<(String, Schema)>),
+    Enum(Vec<String>),
+    Array(Box<Schema>),
+    Map(Box<Schema>),
+    Union(Vec<Schema>),
+    Fixed(usize),
+    Named(String),
+}
+
+/// Read all the records of an Avro object container file as JSON values.
+pub fn read_records(data: &[u8]) -> Result<Vec<json::Value>> {
+    let mut reader = Reader::new(data);
+    if reader.read_fixed(MAGIC.len())? != MAGIC {
+        bail!("not an avro object container file");
+    }
+    let mut metadata = HashMap::new();
+    reader.read_blocks(|r| {
+        let key = r.read_string()?;
+        let value = r.read_bytes()?.to_vec();
+        metadata.insert(key, value);
+        Ok(())
+    })?;
+    let sync = reader.read_fixed(SYNC_SIZE)?;
+
+    let schema = metadata
+        .get("avro.schema")
+        .ok_or_else(|| anyhow!("avro schema is missing"))?;
+    let mut names = HashMap::new();
+    let schema = parse_schema(&json::from_slice(schema)?, None, &mut names)?;
+    let codec = metadata
+        .get("avro.codec")
+        .map(|v| String::from_utf8_lossy(v).to_string())
+        .unwrap_or_else(|| "null".to_string());
+
+    let mut records = Vec::new();
+    while !reader.is_empty() {
+        let count = reader.read_long()?;
+        let block = reader.read_bytes()?;
+        if reader.read_fixed(SYNC_SIZE)? != sync {
+            bail!("invalid avro sync marker");
+        }
+        let block = match codec.as_str() {
+            "null" => block.to_vec(),
+            "deflate" => {
+                let mut buf = Vec::new();
+                DeflateDecoder::new(block).read_to_end(&mut buf)?;
+                buf
+            }
+            codec => bail!("unsupported avro codec: {codec}"),
+        };
+        let mut block_reader = Reader::new(&block);
+        for _ in 0..count {
+            records.push(block_reader.read_value(&schema, &names)?);
+        }
+    }
+    Ok(records)
+}
+
+fn parse_schema(
+    value: &json::Value,
+    namespace: Option<&str>,
+    names: &mut HashMap<String, Schema>,
+) -> Result<Schema> {
+    let schema = match value {
+        json::Value::String(name) => match name.as_str() {
+            "null" => Schema::Null,
+            "boolean" => Schema::Boolean,
+            "int" => Schema::Int,
+            "long" => Schema::Long,
+            "float" => Schema::Float,
+            "double" => Schema::Double,
+            "bytes" => Schema::Bytes,
+            "string" => Schema::String,
+            name => Schema::Named(full_name(name, namespace)),
+        },
+        json::Value::Array(types) => Schema::Union(
+            types
+                .iter()
+                .map(|t| parse_schema(t, namespace, names))
+                .collect::<Result<_>>()?,
+        ),
+        json::Value::Object(obj) => {
+            let kind = obj
+                .get("type")
+                .ok_or_else(|| anyhow!("avro schema type is missing"))?;
+            let name = obj.get("name").and_then(|v| v.as_str());
+            let namespace = obj.get("namespace").and_then(|v| v.as_str()).or(namespace);
+            let schema = match kind.as_str() {
+                Some("record") | Some("error") => {
+                    let fields = obj
+                        .get("fields")
+                        .and_then(|v| v.as_array())
+                        .ok_or_else(|| anyhow!("avro record fields are missing"))?;
+                    let mut record = Vec::with_capacity(fields.len());
+                    for field in fields {
+                        let field_name = field
+                            .get("name")
+                            .and_then(|v| v.as_str())
+                            .ok_or_else(|| anyhow!("avro field name is missing"))?;
+                        let field_type = field
+                            .get("type")
+                            .ok_or_else(|| anyhow!("avro field type is missing"))?;
+                        record.push((
+                            field_name.to_string(),
+                            parse_schema(field_type, namespace, names)?,
+                        ));
+                    }
+                    Schema::Record(record)
+                }
+                Some("enum") => Schema::Enum(
+                    obj.get("symbols")
+                        .and_then(|v| v.as_array())
+                        .map(|v| {
+                            v.iter()
+                                .filter_map(|s| s.as_str().map(|s| s.to_string()))
+                                .collect()
+                        })
+                        .unwrap_or_default(),
+                ),
+                Some("array") => Schema::Array(Box::new(parse_schema(
+                    obj.get("items")
+                        .ok_or_else(|| anyhow!("avro array items are missing"))?,
+                    namespace,
+                    names,
+                )?)),
+                Some("map") => Schema::Map(Box::new(parse_schema(
+                    obj.get("values")
+                        .ok_or_else(|| anyhow!("avro map values are missing"))?,
+                    namespace,
+                    names,
+                )?)),
+                Some("fixed") => Schema::Fixed(
+                    obj.get("size")
+                        .and_then(|v| v.as_u64())
+                        .ok_or_else(|| anyhow!("avro fixed size is missing"))?
+                        as usize,
+                ),
+                // a primitive type with attributes, `{"type": "long", "logicalType": ...}`
+                _ => return parse_schema(kind, namespace, names),
+            };
+            if let Some(name) = name {
+                names.insert(full_name(name, namespace), schema.clone());
+            }
+            schema
+        }
+        _ => bail!("invalid avro schema: {value}"),
+    };
+    Ok(schema)
+}
+
+fn full_name(name: &str, namespace: Option<&str>) -> String {
+    match namespace {
+        Some(ns) if !name.contains('.') && !ns.is_empty() => format!("{ns}.{name}"),
+        _ => name.to_string(),
+    }
+}
+
+struct Reader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(buf: &'a [u8]) -> Self {
+        Self { buf, pos: 0 }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.pos >= self.buf.len()
+    }
+
+    fn read_fixed(&mut self, len: usize) -> Result<&'a [u8]> {
+        let end = self.pos + len;
+        if end > self.buf.len() {
+            bail!("unexpected end of avro data");
+        }
+        let data = &self.buf[self.pos..end];
+        self.pos = end;
+        Ok(data)
+    }
+
+    fn read_long(&mut self) -> Result<i64> {
+        let mut value: u64 = 0;
+        let mut shift = 0;
+        loop {
+            let byte = self.read_fixed(1)?[0];
+            value |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                break;
+            }
+            shift += 7;
+            if shift > 63 {
+                bail!("invalid avro varint");
+            }
+        }
+        Ok((value >> 1) as i64 ^ -((value & 1) as i64))
+    }
+
+    fn read_bytes(&mut self) -> Result<&'a [u8]> {
+        let len = self.read_long()?;
+        if len < 0 {
+            bail!("invalid avro bytes length: {len}");
+        }
+        self.read_fixed(len as usize)
+    }
+
+    fn read_string(&mut self) -> Result<String> {
+        Ok(String::from_utf8_lossy(self.read_bytes()?).to_string())
+    }
+
+    /// Read the blocks of an array or a map, a negative count is followed by
+    /// the size of the block in bytes.
+    fn read_blocks(&mut self, mut f: impl FnMut(&mut Self) -> Result<()>) -> Result<()> {
+        loop {
+            let mut count = self.read_long()?;
+            if count == 0 {
+                return Ok(());
+            }
+            if count < 0 {
+                count = -count;
+                self.read_long()?;
+            }
+            for _ in 0..count {
+                f(self)?;
+            }
+        }
+    }
+
+    fn read_value(
+        &mut self,
+        schema: &Schema,
+        names: &HashMap<String, Schema>,
+    ) -> Result<json::Value> {
+        let value = match schema {
+            Schema::Null => json::Value::Null,
+            Schema::Boolean => json::Value::Bool(self.read_fixed(1)?[0] != 0),
+            Schema::Int | Schema::Long => json::Value::from(self.read_long()?),
+            Schema::Float => {
+                let bytes = self.read_fixed(4)?;
+                json::Value::from(f32::from_le_bytes(bytes.try_into()?) as f64)
+            }
+            Schema::Double => {
+                let bytes = self.read_fixed(8)?;
+                json::Value::from(f64::from_le_bytes(bytes.try_into()?))
+            }
+            Schema::Bytes | Schema::String => json::Value::String(self.read_string()?),
+            Schema::Fixed(size) => {
+                json::Value::String(String::from_utf8_lossy(self.read_fixed(*size)?).to_string())
+            }
+            Schema::Record(fields) => {
+                let mut record = json::Map::with_capacity(fields.len());
+                for (name, field) in fields {
+                    record.insert(name.clone(), self.read_value(field, names)?);
+                }
+                json::Value::Object(record)
+            }
+            Schema::Enum(symbols) => {
+                let idx = self.read_long()?;
+                match symbols.get(idx as usize) {
+                    Some(symbol) => json::Value::String(symbol.clone()),
+                    None => bail!("invalid avro enum index: {idx}"),
+                }
+            }
+            Schema::Array(items) => {
+                let mut values = Vec::new();
+                self.read_blocks(|r| {
+                    values.push(r.read_value(items, names)?);
+                    Ok(())
+                })?;
+                json::Value::Array(values)
+            }
+            Schema::Map(values) => {
+                let mut map = json::Map::new();
+                self.read_blocks(|r| {
+                    let key = r.read_string()?;
+                    map.insert(key, r.read_value(values, names)?);
+                    Ok(())
+                })?;
+                json::Value::Object(map)
+            }
+            Schema::Union(types) => {
+                let idx = self.read_long()?;
+                match types.get(idx as usize) {
+                    Some(schema) => self.read_value(schema, names)?,
+                    None => bail!("invalid avro union index: {idx}"),
+                }
+            }
+            Schema::Named(name) => {
+                let schema = names
+                    .get(name)
+                    .ok_or_else(|| anyhow!("unknown avro type: {name}"))?;
+                self.read_value(schema, names)?
+            }
+        };
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_long(buf: &mut Vec<u8>, v: i64) {
+        let mut n = ((v << 1) ^ (v >> 63)) as u64;
+        while n >= 0x80 {
+            buf.push((n as u8 & 0x7f) | 0x80);
+            n >>= 7;
+        }
+        buf.push(n as u8);
+    }
+
+    fn write_bytes(buf: &mut Vec<u8>, v: &[u8]) {
+        write_long(buf, v.len() as i64);
+        buf.extend_from_slice(v);
+    }
+
+    #[test]
+    fn test_read_records() {
+        let schema = r#"{
+            "type": "record",
+            "name": "manifest_file",
+            "fields": [
+                {"name": "manifest_path", "type": "string"},
+                {"name": "content", "type": "int"},
+                {"name": "added_rows_count", "type": ["null", "long"]},
+                {"name": "partitions", "type": {"type": "array", "items": {
+                    "type": "record", "name": "field_summary",
+                    "fields": [{"name": "contains_null", "type": "boolean"}]
+                }}},
+                {"name": "summary", "type": ["null", "field_summary"]}
+            ]
+        }"#;
+        let sync = [7u8; SYNC_SIZE];
+        let mut data = MAGIC.to_vec();
+        write_long(&mut data, 1);
+        write_bytes(&mut data, b"avro.schema");
+        write_bytes(&mut data, schema.as_bytes());
+        write_long(&mut data, 0);
+        data.extend_from_slice(&sync);
+
+        let mut block = Vec::new();
+        // first record
+        write_bytes(&mut block, b"s3://bucket/t/metadata/m0.avro");
+        write_long(&mut block, 0);
+        write_long(&mut block, 1);
+        write_long(&mut block, -300);
+        write_long(&mut block, 1);
+        block.push(1);
+        write_long(&mut block, 0);
+        write_long(&mut block, 1);
+        block.push(0);
+        // second record
+        write_bytes(&mut block, b"m1.avro");
+        write_long(&mut block, 1);
+        write_long(&mut block, 0);
+        write_long(&mut block, 0);
+        write_long(&mut block, 0);
+        write_long(&mut data, 2);
+        write_bytes(&mut data, &block);
+        data.extend_from_slice(&sync);
+
+        let records = read_records(&data).unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(
+            records[0]["manifest_path"],
+            "s3://bucket/t/metadata/m0.avro"
+        );
+        assert_eq!(records[0]["content"], 0);
+        assert_eq!(records[0]["added_rows_count"], -300);
+        assert_eq!(records[0]["partitions"][0]["contains_null"], true);
+        assert_eq!(records[0]["summary"]["contains_null"], false);
+        assert_eq!(records[1]["content"], 1);
+        assert!(records[1]["added_rows_count"].is_null());
+        assert!(records[1]["summary"].is_null());
+    }
+
+    #[test]
+    fn test_read_records_invalid() {
+        assert!(read_records(b"PAR1").is_err());
+    }
+}
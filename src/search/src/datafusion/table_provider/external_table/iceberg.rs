@@ -0,0 +1,312 @@
+// Copyright 2026 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! List the data files of the current snapshot of an Iceberg table, reading
+//! the table metadata, the manifest list and the manifests.
+
+use std::collections::HashMap;
+
+use anyhow::{Result, anyhow, bail};
+use config::{
+    meta::external_table::{ExternalTable, object_key},
+    utils::json,
+};
+
+use super::{DataFile, avro};
+
+const METADATA_SUFFIX: &str = ".metadata.json";
+
+/// The manifest entries with this status are removed from the snapshot.
+const STATUS_DELETED: i64 = 2;
+
+pub async fn list_data_files(table: &ExternalTable) -> Result<Vec<DataFile>> {
+    let account = &table.account;
+    let metadata_key = metadata_location(table).await?;
+    check_key(table, &metadata_key)?;
+    let data = infra::storage::get_bytes(account, &metadata_key).await?;
+    let metadata: json::Value = json::from_slice(&data)?;
+    let Some(snapshot) = current_snapshot(&metadata) else {
+        return Ok(vec![]);
+    };
+    let partitions = identity_partitions(&metadata);
+
+    let manifests = match snapshot.get("manifest-list").and_then(|v| v.as_str()) {
+        Some(manifest_list) => {
+            let data =
+                infra::storage::get_bytes(account, &check_key(table, manifest_list)?).await?;
+            let mut manifests = Vec::new();
+            for manifest in avro::read_records(&data)? {
+                if manifest["content"].as_i64().unwrap_or_default() != 0 {
+                    bail!(
+                        "iceberg table {} has delete files which are not supported",
+                        table.name
+                    );
+                }
+                let path = manifest["manifest_path"]
+                    .as_str()
+                    .ok_or_else(|| anyhow!("manifest_path is missing in the manifest list"))?;
+                manifests.push(path.to_string());
+            }
+            manifests
+        }
+        // format v1 may list the manifests in the snapshot
+        None => snapshot
+            .get("manifests")
+            .and_then(|v| v.as_array())
+            .map(|v| {
+                v.iter()
+                    .filter_map(|m| m.as_str().map(|m| m.to_string()))
+                    .collect()
+            })
+            .unwrap_or_default(),
+    };
+
+    let mut files = Vec::new();
+    for manifest in manifests {
+        let data = infra::storage::get_bytes(account, &check_key(table, &manifest)?).await?;
+        for entry in avro::read_records(&data)? {
+            if entry["status"].as_i64() == Some(STATUS_DELETED) {
+                continue;
+            }
+            let file = &entry["data_file"];
+            files.push(data_file(table, file, &partitions)?);
+        }
+    }
+    Ok(files)
+}
+
+fn data_file(
+    table: &ExternalTable,
+    file: &json::Value,
+    partitions: &HashMap<String, String>,
+) -> Result<DataFile> {
+    if file["content"].as_i64().unwrap_or_default() != 0 {
+        bail!(
+            "iceberg table {} has delete files which are not supported",
+            table.name
+        );
+    }
+    let path = file["file_path"]
+        .as_str()
+        .ok_or_else(|| anyhow!("file_path is missing in the manifest"))?;
+    let format = file["file_format"].as_str().unwrap_or_default();
+    if !format.eq_ignore_ascii_case("parquet") {
+        bail!("iceberg data file format {format} is not supported");
+    }
+    let mut partition_values = HashMap::new();
+    if let Some(values) = file["partition"].as_object() {
+        for (field, value) in values {
+            let Some(column) = partitions.get(field) else {
+                continue;
+            };
+            let value = match value {
+                json::Value::String(v) => v.clone(),
+                json::Value::Number(v) => v.to_string(),
+                json::Value::Bool(v) => v.to_string(),
+                _ => continue,
+            };
+            partition_values.insert(column.clone(), value);
+        }
+    }
+    Ok(DataFile {
+        key: check_key(table, path)?,
+        size: file["file_size_in_bytes"].as_i64().unwrap_or_default(),
+        records: file["record_count"].as_i64().unwrap_or_default(),
+        partition_values,
+    })
+}
+
+/// The key of a file the metadata refers to, it must be under the table
+/// location so the metadata can't point to the files of another table.
+fn check_key(table: &ExternalTable, path: &str) -> Result<String> {
+    let key = object_key(path);
+    if !table.contains_key(&key) {
+        bail!(
+            "iceberg table {} refers to {path} outside of its location",
+            table.name
+        );
+    }
+    Ok(key)
+}
+
+/// Find the current metadata file, from the location, the version hint or
+/// the latest version in the metadata directory.
+async fn metadata_location(table: &ExternalTable) -> Result<String> {
+    let key = table.location_key();
+    if key.ends_with(METADATA_SUFFIX) {
+        return Ok(key);
+    }
+    let dir = format!("{}/metadata/", key.trim_end_matches('/'));
+    if let Ok(hint) =
+        infra::storage::get_bytes(&table.account, &format!("{dir}version-hint.text")).await
+        && let Ok(version) = String::from_utf8_lossy(&hint).trim().parse::<u64>()
+    {
+        return Ok(format!("{dir}v{version}{METADATA_SUFFIX}"));
+    }
+    let objects = infra::storage::list_objects(&table.account, &dir).await?;
+    objects
+        .into_iter()
+        .map(|o| o.location.to_string())
+        .filter(|key| key.ends_with(METADATA_SUFFIX))
+        .max_by_key(|key| metadata_version(key))
+        .ok_or_else(|| anyhow!("no iceberg metadata found at {}", table.location))
+}
+
+/// The version of `v3.metadata.json` or `00003-<uuid>.metadata.json`.
+fn metadata_version(key: &str) -> u64 {
+    let name = key.rsplit('/').next().unwrap_or_default();
+    let name = name.strip_prefix('v').unwrap_or(name);
+    let digits = name
+        .chars()
+        .take_while(|c| c.is_ascii_digit())
+        .collect::<String>();
+    digits.parse().unwrap_or_default()
+}
+
+fn current_snapshot(metadata: &json::Value) -> Option<&json::Value> {
+    let snapshot_id = metadata["current-snapshot-id"].as_i64()?;
+    if snapshot_id == -1 {
+        return None;
+    }
+    metadata["snapshots"]
+        .as_array()?
+        .iter()
+        .find(|s| s["snapshot-id"].as_i64() == Some(snapshot_id))
+}
+
+/// Map the identity partition fields to the source columns. Only the string,
+/// integer and boolean columns are mapped, the partition values of the other
+/// types are not in the format of the SQL literals.
+fn identity_partitions(metadata: &json::Value) -> HashMap<String, String> {
+    let mut columns = HashMap::new();
+    let schemas = match metadata["schemas"].as_array() {
+        Some(schemas) => schemas.iter().collect::<Vec<_>>(),
+        None => vec![&metadata["schema"]],
+    };
+    for schema in schemas {
+        for field in schema["fields"].as_array().into_iter().flatten() {
+            if let (Some(id), Some(name), Some(ty)) = (
+                field["id"].as_i64(),
+                field["name"].as_str(),
+                field["type"].as_str(),
+            ) && matches!(ty, "string" | "int" | "long" | "boolean")
+            {
+                columns.insert(id, name.to_string());
+            }
+        }
+    }
+
+    let specs = match metadata["partition-specs"].as_array() {
+        Some(specs) => specs.iter().map(|s| &s["fields"]).collect::<Vec<_>>(),
+        None => vec![&metadata["partition-spec"]],
+    };
+    let mut partitions = HashMap::new();
+    for fields in specs {
+        for field in fields.as_array().into_iter().flatten() {
+            if field["transform"].as_str() != Some("identity") {
+                continue;
+            }
+            if let (Some(name), Some(column)) = (
+                field["name"].as_str(),
+                field["source-id"].as_i64().and_then(|id| columns.get(&id)),
+            ) {
+                partitions.insert(name.to_string(), column.clone());
+            }
+        }
+    }
+    partitions
+}
+
+#[cfg(test)]
+mod tests {
+    use config::meta::external_table::ExternalTableFormat;
+
+    use super::*;
+
+    #[test]
+    fn test_metadata_version() {
+        assert_eq!(metadata_version("t/metadata/v12.metadata.json"), 12);
+        assert_eq!(
+            metadata_version("t/metadata/00003-5b1d0c8e.metadata.json"),
+            3
+        );
+        assert_eq!(metadata_version("t/metadata/snap.metadata.json"), 0);
+    }
+
+    #[test]
+    fn test_current_snapshot() {
+        let metadata = json::json!({
+            "current-snapshot-id": 2,
+            "snapshots": [
+                {"snapshot-id": 1, "manifest-list": "s3://b/t/metadata/snap-1.avro"},
+                {"snapshot-id": 2, "manifest-list": "s3://b/t/metadata/snap-2.avro"}
+            ]
+        });
+        let snapshot = current_snapshot(&metadata).unwrap();
+        assert_eq!(snapshot["manifest-list"], "s3://b/t/metadata/snap-2.avro");
+
+        let empty = json::json!({"current-snapshot-id": -1, "snapshots": []});
+        assert!(current_snapshot(&empty).is_none());
+    }
+
+    #[test]
+    fn test_identity_partitions() {
+        let metadata = json::json!({
+            "current-schema-id": 0,
+            "schemas": [{"schema-id": 0, "fields": [
+                {"id": 1, "name": "region", "type": "string"},
+                {"id": 2, "name": "ts", "type": "timestamp"},
+                {"id": 3, "name": "day", "type": "date"}
+            ]}],
+            "partition-specs": [{"spec-id": 0, "fields": [
+                {"name": "region", "transform": "identity", "source-id": 1, "field-id": 1000},
+                {"name": "ts_day", "transform": "day", "source-id": 2, "field-id": 1001},
+                {"name": "day", "transform": "identity", "source-id": 3, "field-id": 1002}
+            ]}]
+        });
+        let partitions = identity_partitions(&metadata);
+        assert_eq!(partitions.len(), 1);
+        assert_eq!(partitions["region"], "region");
+
+        let table = ExternalTable {
+            name: "t".to_string(),
+            format: ExternalTableFormat::Iceberg,
+            location: "s3://b/t".to_string(),
+            ..Default::default()
+        };
+        let outside = json::json!({
+            "content": 0,
+            "file_path": "s3://b/files/org/logs/default/0.parquet",
+            "file_format": "PARQUET"
+        });
+        assert!(data_file(&table, &outside, &partitions).is_err());
+        let file = json::json!({
+            "content": 0,
+            "file_path": "s3://b/t/data/region=eu/0.parquet",
+            "file_format": "PARQUET",
+            "partition": {"region": "eu", "ts_day": 20000, "day": 20000},
+            "record_count": 10,
+            "file_size_in_bytes": 1024
+        });
+        let file = data_file(&table, &file, &partitions).unwrap();
+        assert_eq!(file.key, "t/data/region=eu/0.parquet");
+        assert_eq!(file.records, 10);
+        assert_eq!(file.size, 1024);
+        assert_eq!(
+            file.partition_values,
+            HashMap::from([("region".to_string(), "eu".to_string())])
+        );
+    }
+}
@@ -0,0 +1,307 @@
+// Copyright 2026 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::{collections::HashMap, sync::Arc};
+
+use async_trait::async_trait;
+use config::meta::{
+    external_table::{ExternalTable, ExternalTableFormat},
+    search::{Session as SearchSession, StorageType},
+    stream::{FileKey, FileMeta},
+};
+use datafusion::{
+    arrow::datatypes::{Schema, SchemaRef},
+    catalog::Session,
+    common::{DataFusionError, Result, project_schema},
+    datasource::{TableProvider, TableType},
+    logical_expr::{BinaryExpr, Operator, TableProviderFilterPushDown, expr::InList},
+    physical_plan::{ExecutionPlan, empty::EmptyExec},
+    prelude::Expr,
+    scalar::ScalarValue,
+};
+
+use crate::datafusion::{
+    exec::TableBuilder, storage::file_list, table_provider::uniontable::NewUnionTable,
+};
+
+mod avro;
+pub mod iceberg;
+
+/// A table over the Parquet files of an [`ExternalTable`], the data files are
+/// listed when the table is scanned and pruned by the partition values.
+///
+/// The partition columns are only used for pruning the files, they are read
+/// from the files like any other column.
+#[derive(Debug)]
+pub struct ExternalTableProvider {
+    table: ExternalTable,
+    schema: SchemaRef,
+}
+
+impl ExternalTableProvider {
+    pub fn new(table: ExternalTable, schema: SchemaRef) -> Self {
+        Self { table, schema }
+    }
+}
+
+#[async_trait]
+impl TableProvider for ExternalTableProvider {
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    fn table_type(&self) -> TableType {
+        TableType::Base
+    }
+
+    async fn scan(
+        &self,
+        state: &dyn Session,
+        projection: Option<&Vec<usize>>,
+        filters: &[Expr],
+        limit: Option<usize>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        let files = list_data_files(&self.table)
+            .await
+            .map_err(|e| DataFusionError::External(e.into()))?;
+        let total = files.len();
+        let files = files
+            .into_iter()
+            .filter(|f| filters.iter().all(|e| may_match(e, &f.partition_values)))
+            .enumerate()
+            .map(|(id, f)| {
+                let meta = FileMeta {
+                    records: f.records,
+                    compressed_size: f.size,
+                    ..Default::default()
+                };
+                FileKey::new(id as i64, self.table.account.clone(), f.key, meta, false)
+            })
+            .collect::<Vec<_>>();
+        log::info!(
+            "[external_table] {} scan {} of {} data files",
+            self.table.name,
+            files.len(),
+            total
+        );
+        if files.is_empty() {
+            let schema = project_schema(&self.schema, projection)?;
+            return Ok(Arc::new(EmptyExec::new(schema)));
+        }
+
+        let trace_id = format!("external-{}", config::ider::uuid());
+        let session = SearchSession {
+            id: trace_id.clone(),
+            storage_type: StorageType::Memory,
+            work_group: None,
+            target_partitions: state.config().target_partitions(),
+        };
+        let tables = TableBuilder::new()
+            .build(session, files, self.schema.clone())
+            .await?;
+        let table = NewUnionTable::new(self.schema.clone(), tables);
+        // the listing table resolves the file list when building the plan
+        let plan = table.scan(state, projection, filters, limit).await;
+        file_list::clear(&trace_id);
+        plan
+    }
+
+    fn supports_filters_pushdown(
+        &self,
+        filters: &[&Expr],
+    ) -> Result<Vec<TableProviderFilterPushDown>> {
+        Ok(vec![TableProviderFilterPushDown::Inexact; filters.len()])
+    }
+}
+
+/// A data file of an external table.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DataFile {
+    pub key: String,
+    pub size: i64,
+    pub records: i64,
+    pub partition_values: HashMap<String, String>,
+}
+
+/// List the data files of the table.
+pub async fn list_data_files(table: &ExternalTable) -> Result<Vec<DataFile>, anyhow::Error> {
+    match table.format {
+        ExternalTableFormat::Parquet => {
+            let objects =
+                infra::storage::list_objects(&table.account, &table.location_key()).await?;
+            Ok(objects
+                .into_iter()
+                .filter(|o| o.location.as_ref().ends_with(".parquet"))
+                .map(|o| {
+                    let key = o.location.to_string();
+                    DataFile {
+                        partition_values: table.partition_values(&key),
+                        key,
+                        size: o.size as i64,
+                        records: 0,
+                    }
+                })
+                .collect())
+        }
+        ExternalTableFormat::Iceberg => iceberg::list_data_files(table).await,
+    }
+}
+
+/// Infer the schema of the table from the first data file.
+pub async fn infer_schema(table: &ExternalTable) -> Result<Schema, anyhow::Error> {
+    let files = list_data_files(table).await?;
+    let Some(file) = files.first() else {
+        return Err(anyhow::anyhow!(
+            "no parquet files found at {}",
+            table.location
+        ));
+    };
+    infra::storage::get_file_schema(&table.account, &file.key).await
+}
+
+/// Check if a file with the partition values may contain rows matching the
+/// filter, filters which can't be evaluated on the partition values match.
+fn may_match(expr: &Expr, values: &HashMap<String, String>) -> bool {
+    if values.is_empty() {
+        return true;
+    }
+    match expr {
+        Expr::BinaryExpr(BinaryExpr { left, op, right }) => match op {
+            Operator::And => may_match(left, values) && may_match(right, values),
+            Operator::Or => may_match(left, values) || may_match(right, values),
+            _ => {
+                let (column, literal, op) = match (column_name(left), literal_value(right)) {
+                    (Some(c), Some(v)) => (c, v, *op),
+                    _ => match (column_name(right), literal_value(left)) {
+                        (Some(c), Some(v)) => match op.swap() {
+                            Some(op) => (c, v, op),
+                            None => return true,
+                        },
+                        _ => return true,
+                    },
+                };
+                let Some(value) = values.get(column) else {
+                    return true;
+                };
+                let ordering = compare_values(value, &literal);
+                match op {
+                    Operator::Eq => ordering.is_eq(),
+                    Operator::NotEq => ordering.is_ne(),
+                    Operator::Lt => ordering.is_lt(),
+                    Operator::LtEq => ordering.is_le(),
+                    Operator::Gt => ordering.is_gt(),
+                    Operator::GtEq => ordering.is_ge(),
+                    _ => true,
+                }
+            }
+        },
+        Expr::InList(InList {
+            expr,
+            list,
+            negated: false,
+        }) => {
+            let Some(value) = column_name(expr).and_then(|c| values.get(c)) else {
+                return true;
+            };
+            list.iter().any(|e| match literal_value(e) {
+                Some(v) => compare_values(value, &v).is_eq(),
+                None => true,
+            })
+        }
+        _ => true,
+    }
+}
+
+fn column_name(expr: &Expr) -> Option<&str> {
+    match expr {
+        Expr::Column(c) => Some(c.name.as_str()),
+        Expr::Cast(c) => column_name(&c.expr),
+        Expr::TryCast(c) => column_name(&c.expr),
+        _ => None,
+    }
+}
+
+fn literal_value(expr: &Expr) -> Option<String> {
+    match expr {
+        Expr::Literal(v, _) if v.is_null() => None,
+        Expr::Literal(
+            ScalarValue::Utf8(Some(v))
+            | ScalarValue::Utf8View(Some(v))
+            | ScalarValue::LargeUtf8(Some(v)),
+            _,
+        ) => Some(v.clone()),
+        Expr::Literal(v, _) => Some(v.to_string()),
+        Expr::Cast(c) => literal_value(&c.expr),
+        Expr::TryCast(c) => literal_value(&c.expr),
+        _ => None,
+    }
+}
+
+// compare as numbers when both are numbers, `month=02` equals `2`
+fn compare_values(value: &str, literal: &str) -> std::cmp::Ordering {
+    match (value.parse::<f64>(), literal.parse::<f64>()) {
+        (Ok(a), Ok(b)) => a.total_cmp(&b),
+        _ => value.cmp(literal),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use datafusion::prelude::{col, lit};
+
+    use super::*;
+
+    fn values() -> HashMap<String, String> {
+        HashMap::from([
+            ("year".to_string(), "2026".to_string()),
+            ("region".to_string(), "eu".to_string()),
+        ])
+    }
+
+    #[test]
+    fn test_may_match() {
+        let values = values();
+        assert!(may_match(&col("year").eq(lit(2026)), &values));
+        assert!(!may_match(&col("year").eq(lit(2025)), &values));
+        assert!(may_match(&lit(2025).lt(col("year")), &values));
+        assert!(!may_match(&col("year").gt(lit("2026")), &values));
+        assert!(!may_match(
+            &col("year").eq(lit(2026)).and(col("region").eq(lit("us"))),
+            &values
+        ));
+        assert!(may_match(
+            &col("region").eq(lit("us")).or(col("region").eq(lit("eu"))),
+            &values
+        ));
+        assert!(may_match(
+            &col("region").in_list(vec![lit("us"), lit("eu")], false),
+            &values
+        ));
+        assert!(!may_match(
+            &col("region").in_list(vec![lit("us")], false),
+            &values
+        ));
+        // filters on other columns can't prune the file
+        assert!(may_match(&col("level").eq(lit("error")), &values));
+        assert!(may_match(&col("year").eq(lit(2025)), &HashMap::new()));
+    }
+
+    #[test]
+    fn test_compare_values() {
+        assert!(compare_values("02", "2").is_eq());
+        assert!(compare_values("10", "9").is_gt());
+        assert!(compare_values("eu", "us").is_lt());
+    }
+}
@@ -27,7 +27,7 @@ use datafusion::{
     },
     execution::cache::cache_manager::FileStatisticsCache,
     logical_expr::TableProviderFilterPushDown,
-    physical_plan::ExecutionPlan,
+    physical_plan::{ExecutionPlan, sorts::sort::SortExec},
     prelude::Expr,
 };
 use rayon::prelude::*;
@@ -49,6 +49,9 @@ pub struct ListingTableAdapter {
     /// the listing options; it is used to regroup files by statistics when
     /// DataFusion could not prove the ordering itself.
     sort_order: FileSortOrder,
+    /// Order the scan must produce although the files do not carry it
+    /// (files clustered by the stream sort keys); each partition is sorted.
+    restore_order: Option<FileSortOrder>,
     index_condition: Option<IndexCondition>,
     fst_fields: Vec<String>,
     timestamp_filter: Option<(i64, i64)>,
@@ -68,12 +71,18 @@ impl ListingTableAdapter {
             listing_table,
             trace_id,
             sort_order,
+            restore_order: None,
             index_condition,
             fst_fields,
             timestamp_filter,
         })
     }
 
+    pub fn with_restore_order(mut self, restore_order: Option<FileSortOrder>) -> Self {
+        self.restore_order = restore_order;
+        self
+    }
+
     pub fn with_cache(mut self, cache: Option<Arc<dyn FileStatisticsCache>>) -> Self {
         self.listing_table = self.listing_table.with_cache(cache);
         self
@@ -160,12 +169,18 @@ impl TableProvider for ListingTableAdapter {
             .as_ref()
             .map(|v| v.can_remove_filter())
             .unwrap_or(true);
-        let index_condition =
-            if can_remove_filter || get_config().search.feature_query_remove_filter_with_index {
-                self.index_condition.as_ref()
-            } else {
-                None
-            };
+        // conditions needing verification keep their filter in the plan
+        let need_verify = self
+            .index_condition
+            .as_ref()
+            .is_some_and(|v| v.need_verify());
+        let index_condition = if can_remove_filter
+            || (get_config().search.feature_query_remove_filter_with_index && !need_verify)
+        {
+            self.index_condition.as_ref()
+        } else {
+            None
+        };
         let plan = apply_combined_filter(
             index_condition,
             self.timestamp_filter,
@@ -175,6 +190,15 @@ impl TableProvider for ListingTableAdapter {
             filter_projection,
         )?;
 
+        if let Some(ordering) = self
+            .restore_order
+            .and_then(|order| order.physical_ordering(&plan.schema()))
+        {
+            return Ok(Arc::new(
+                SortExec::new(ordering, plan).with_preserve_partitioning(true),
+            ));
+        }
+
         Ok(plan)
     }
 
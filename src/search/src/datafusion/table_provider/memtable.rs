@@ -194,12 +194,18 @@ impl TableProvider for NewMemTable {
             .as_ref()
             .map(|v| v.can_remove_filter())
             .unwrap_or(true);
-        let index_condition =
-            if can_remove_filter || get_config().search.feature_query_remove_filter_with_index {
-                self.index_condition.as_ref()
-            } else {
-                None
-            };
+        // conditions needing verification keep their filter in the plan
+        let need_verify = self
+            .index_condition
+            .as_ref()
+            .is_some_and(|v| v.need_verify());
+        let index_condition = if can_remove_filter
+            || (get_config().search.feature_query_remove_filter_with_index && !need_verify)
+        {
+            self.index_condition.as_ref()
+        } else {
+            None
+        };
         let filter_exec = apply_combined_filter(
             index_condition,
             Some(self.timestamp_filter),
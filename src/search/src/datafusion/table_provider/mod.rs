@@ -33,6 +33,7 @@
 pub mod catalog;
 pub mod empty_table;
 pub mod enrich_table;
+pub mod external_table;
 mod helpers;
 pub mod listing_adapter;
 pub mod memtable;
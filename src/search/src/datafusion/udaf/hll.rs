@@ -0,0 +1,441 @@
+// Copyright 2026 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::sync::Arc;
+
+use arrow::{
+    array::{Array, ArrayRef, AsArray},
+    compute::cast,
+};
+use config::utils::hash::{Sum64, cityhash};
+use datafusion::{
+    arrow::datatypes::{DataType, Field, FieldRef},
+    common::{exec_err, plan_err},
+    error::Result,
+    logical_expr::{
+        Accumulator, AggregateUDFImpl, Signature, Volatility,
+        function::{AccumulatorArgs, StateFieldsArgs},
+        utils::format_state_name,
+    },
+    scalar::ScalarValue,
+};
+
+pub const HLL_SKETCH: &str = "hll_sketch";
+pub const HLL_MERGE: &str = "hll_merge";
+pub const HLL_ESTIMATE: &str = "hll_estimate";
+
+/// Number of index bits, 2^14 registers give a standard error of ~0.8%.
+const PRECISION: u8 = 14;
+const NUM_REGISTERS: usize = 1 << PRECISION;
+/// The guard bit caps the rank of a hash at the number of remaining bits + 1.
+const MAX_RANK: u8 = 64 - PRECISION + 1;
+const FORMAT_DENSE: u8 = 1;
+const FORMAT_SPARSE: u8 = 2;
+
+/// HyperLogLog distinct count sketch.
+///
+/// Values are hashed with cityhash, which is stable across nodes and versions,
+/// so sketches stored in the streaming aggregation cache or in rollup streams
+/// can be merged with new ones. Merging is exact: the merged sketch is the
+/// same as the sketch of all the values.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HllSketch {
+    // allocated on the first insert, empty means all the registers are zero
+    registers: Vec<u8>,
+}
+
+impl HllSketch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, value: &str) {
+        self.insert_hash(cityhash::new().sum64(value));
+    }
+
+    fn insert_hash(&mut self, hash: u64) {
+        let index = (hash >> (64 - PRECISION)) as usize;
+        // the guard bit caps the rank when the remaining bits are all zero
+        let rank = ((hash << PRECISION) | (1 << (PRECISION - 1))).leading_zeros() as u8 + 1;
+        self.set_register(index, rank);
+    }
+
+    fn set_register(&mut self, index: usize, rank: u8) {
+        if self.registers.is_empty() {
+            self.registers = vec![0; NUM_REGISTERS];
+        }
+        if self.registers[index] < rank {
+            self.registers[index] = rank;
+        }
+    }
+
+    pub fn merge(&mut self, other: &HllSketch) {
+        if other.registers.is_empty() {
+            return;
+        }
+        if self.registers.is_empty() {
+            self.registers = other.registers.clone();
+            return;
+        }
+        for (r, o) in self.registers.iter_mut().zip(other.registers.iter()) {
+            *r = (*r).max(*o);
+        }
+    }
+
+    pub fn estimate(&self) -> u64 {
+        if self.registers.is_empty() {
+            return 0;
+        }
+        let m = NUM_REGISTERS as f64;
+        let alpha = 0.7213 / (1.0 + 1.079 / m);
+        let mut sum = 0.0;
+        let mut zeros = 0;
+        for r in self.registers.iter() {
+            sum += 1.0 / (1u64 << r) as f64;
+            if *r == 0 {
+                zeros += 1;
+            }
+        }
+        let estimate = alpha * m * m / sum;
+        // linear counting is more accurate for small cardinalities
+        if estimate <= 2.5 * m && zeros > 0 {
+            (m * (m / zeros as f64).ln()).round() as u64
+        } else {
+            estimate.round() as u64
+        }
+    }
+
+    /// Serialize the sketch, sparse sketches only store the non zero registers.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let non_zero = self.registers.iter().filter(|r| **r > 0).count();
+        if non_zero * 3 < self.registers.len() || self.registers.is_empty() {
+            let mut buf = Vec::with_capacity(2 + non_zero * 3);
+            buf.push(FORMAT_SPARSE);
+            buf.push(PRECISION);
+            for (i, r) in self.registers.iter().enumerate() {
+                if *r > 0 {
+                    buf.extend_from_slice(&(i as u16).to_le_bytes());
+                    buf.push(*r);
+                }
+            }
+            buf
+        } else {
+            let mut buf = Vec::with_capacity(2 + self.registers.len());
+            buf.push(FORMAT_DENSE);
+            buf.push(PRECISION);
+            buf.extend_from_slice(&self.registers);
+            buf
+        }
+    }
+
+    pub fn from_bytes(buf: &[u8]) -> Result<Self> {
+        let (format, precision, data) = match buf {
+            [format, precision, data @ ..] => (*format, *precision, data),
+            _ => return exec_err!("invalid hll sketch: too short"),
+        };
+        if precision != PRECISION {
+            return exec_err!("invalid hll sketch: unsupported precision {precision}");
+        }
+        let mut sketch = HllSketch::new();
+        match format {
+            FORMAT_DENSE => {
+                if data.len() != NUM_REGISTERS {
+                    return exec_err!("invalid hll sketch: wrong number of registers");
+                }
+                if data.iter().any(|r| *r > MAX_RANK) {
+                    return exec_err!("invalid hll sketch: register value out of range");
+                }
+                sketch.registers = data.to_vec();
+            }
+            FORMAT_SPARSE => {
+                if data.len() % 3 != 0 {
+                    return exec_err!("invalid hll sketch: truncated sparse registers");
+                }
+                for chunk in data.chunks_exact(3) {
+                    let index = u16::from_le_bytes([chunk[0], chunk[1]]) as usize;
+                    if index >= NUM_REGISTERS {
+                        return exec_err!("invalid hll sketch: register out of range");
+                    }
+                    if chunk[2] > MAX_RANK {
+                        return exec_err!("invalid hll sketch: register value out of range");
+                    }
+                    sketch.set_register(index, chunk[2]);
+                }
+            }
+            v => return exec_err!("invalid hll sketch: unknown format {v}"),
+        }
+        Ok(sketch)
+    }
+
+    fn merge_array(&mut self, array: &ArrayRef) -> Result<()> {
+        let array = cast(array, &DataType::Binary)?;
+        for buf in array.as_binary::<i32>().iter().flatten() {
+            self.merge(&HllSketch::from_bytes(buf)?);
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+enum HllMode {
+    /// Build a sketch from raw values.
+    Sketch,
+    /// Merge sketches into one sketch.
+    Merge,
+    /// Merge sketches and return the distinct count.
+    Estimate,
+}
+
+/// The HyperLogLog aggregate functions:
+///
+/// - `hll_sketch(field)` builds a sketch of the distinct values of a field
+/// - `hll_merge(sketch)` merges sketches, e.g. the per-minute sketches of a rollup into a per-hour
+///   sketch
+/// - `hll_estimate(sketch)` merges sketches and returns the distinct count
+///
+/// SELECT histogram(_timestamp) AS ts, hll_estimate(users) AS users
+/// FROM (SELECT histogram(_timestamp) AS ts, hll_sketch(user_id) AS users FROM default GROUP BY ts)
+/// GROUP BY ts
+#[derive(Debug, Hash, PartialEq, Eq)]
+pub struct HllAgg {
+    mode: HllMode,
+    signature: Signature,
+}
+
+impl HllAgg {
+    pub fn sketch() -> Self {
+        Self {
+            mode: HllMode::Sketch,
+            signature: Signature::any(1, Volatility::Immutable),
+        }
+    }
+
+    pub fn merge() -> Self {
+        Self {
+            mode: HllMode::Merge,
+            signature: sketch_signature(),
+        }
+    }
+
+    pub fn estimate() -> Self {
+        Self {
+            mode: HllMode::Estimate,
+            signature: sketch_signature(),
+        }
+    }
+}
+
+fn sketch_signature() -> Signature {
+    Signature::uniform(
+        1,
+        vec![
+            DataType::Binary,
+            DataType::LargeBinary,
+            DataType::BinaryView,
+        ],
+        Volatility::Immutable,
+    )
+}
+
+impl AggregateUDFImpl for HllAgg {
+    fn name(&self) -> &str {
+        match self.mode {
+            HllMode::Sketch => HLL_SKETCH,
+            HllMode::Merge => HLL_MERGE,
+            HllMode::Estimate => HLL_ESTIMATE,
+        }
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, arg_types: &[DataType]) -> Result<DataType> {
+        if self.mode != HllMode::Sketch && !is_binary(&arg_types[0]) {
+            return plan_err!("{} requires a sketch argument", self.name());
+        }
+        Ok(match self.mode {
+            HllMode::Sketch | HllMode::Merge => DataType::Binary,
+            HllMode::Estimate => DataType::UInt64,
+        })
+    }
+
+    fn state_fields(&self, args: StateFieldsArgs) -> Result<Vec<FieldRef>> {
+        Ok(vec![Arc::new(Field::new(
+            format_state_name(args.name, "sketch"),
+            DataType::Binary,
+            true,
+        ))])
+    }
+
+    fn accumulator(&self, _args: AccumulatorArgs) -> Result<Box<dyn Accumulator>> {
+        Ok(Box::new(HllAccumulator {
+            mode: self.mode,
+            sketch: HllSketch::new(),
+        }))
+    }
+}
+
+fn is_binary(data_type: &DataType) -> bool {
+    matches!(
+        data_type,
+        DataType::Binary | DataType::LargeBinary | DataType::BinaryView
+    )
+}
+
+#[derive(Debug)]
+struct HllAccumulator {
+    mode: HllMode,
+    sketch: HllSketch,
+}
+
+impl Accumulator for HllAccumulator {
+    fn update_batch(&mut self, values: &[ArrayRef]) -> Result<()> {
+        if self.mode != HllMode::Sketch {
+            return self.sketch.merge_array(&values[0]);
+        }
+        let values = cast(&values[0], &DataType::Utf8)?;
+        for value in values.as_string::<i32>().iter().flatten() {
+            self.sketch.insert(value);
+        }
+        Ok(())
+    }
+
+    fn merge_batch(&mut self, states: &[ArrayRef]) -> Result<()> {
+        if states.is_empty() {
+            return Ok(());
+        }
+        self.sketch.merge_array(&states[0])
+    }
+
+    fn state(&mut self) -> Result<Vec<ScalarValue>> {
+        Ok(vec![ScalarValue::Binary(Some(self.sketch.to_bytes()))])
+    }
+
+    fn evaluate(&mut self) -> Result<ScalarValue> {
+        Ok(match self.mode {
+            HllMode::Sketch | HllMode::Merge => ScalarValue::Binary(Some(self.sketch.to_bytes())),
+            HllMode::Estimate => ScalarValue::UInt64(Some(self.sketch.estimate())),
+        })
+    }
+
+    fn size(&self) -> usize {
+        std::mem::size_of_val(self) + self.sketch.registers.capacity()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow::array::{BinaryArray, Int64Array, RecordBatch, StringArray, UInt64Array};
+    use arrow_schema::Schema;
+    use datafusion::{datasource::MemTable, logical_expr::AggregateUDF, prelude::SessionContext};
+
+    use super::*;
+
+    fn sketch_of(range: std::ops::Range<i64>) -> HllSketch {
+        let mut sketch = HllSketch::new();
+        for i in range {
+            sketch.insert(&i.to_string());
+        }
+        sketch
+    }
+
+    fn assert_close(estimate: u64, expected: u64) {
+        let error = (estimate as f64 - expected as f64).abs() / expected as f64;
+        assert!(error < 0.03, "estimate {estimate} expected {expected}");
+    }
+
+    #[test]
+    fn test_hll_estimate() {
+        assert_eq!(HllSketch::new().estimate(), 0);
+        assert_close(sketch_of(0..1000).estimate(), 1000);
+        assert_close(sketch_of(0..100_000).estimate(), 100_000);
+    }
+
+    #[test]
+    fn test_hll_merge_is_exact() {
+        let mut merged = sketch_of(0..60_000);
+        merged.merge(&sketch_of(40_000..100_000));
+        assert_eq!(merged, sketch_of(0..100_000));
+    }
+
+    #[test]
+    fn test_hll_bytes_round_trip() {
+        for sketch in [HllSketch::new(), sketch_of(0..10), sketch_of(0..100_000)] {
+            let bytes = sketch.to_bytes();
+            assert_eq!(HllSketch::from_bytes(&bytes).unwrap(), sketch);
+        }
+        // small sketches are stored sparse
+        assert_eq!(sketch_of(0..10).to_bytes().len(), 2 + 10 * 3);
+        assert!(HllSketch::from_bytes(&[FORMAT_DENSE, PRECISION, 1]).is_err());
+        assert!(HllSketch::from_bytes(&[]).is_err());
+    }
+
+    #[test]
+    fn test_hll_rejects_corrupt_registers() {
+        let mut bytes = sketch_of(0..100_000).to_bytes();
+        assert_eq!(bytes[0], FORMAT_DENSE);
+        bytes[2] = MAX_RANK;
+        assert!(HllSketch::from_bytes(&bytes).is_ok());
+        // a rank above 64 would overflow the shift in estimate()
+        bytes[2] = 65;
+        assert!(HllSketch::from_bytes(&bytes).is_err());
+        bytes[2] = MAX_RANK + 1;
+        assert!(HllSketch::from_bytes(&bytes).is_err());
+
+        let mut bytes = sketch_of(0..10).to_bytes();
+        assert_eq!(bytes[0], FORMAT_SPARSE);
+        bytes[4] = 200;
+        assert!(HllSketch::from_bytes(&bytes).is_err());
+        let states: ArrayRef = Arc::new(BinaryArray::from(vec![bytes.as_slice()]));
+        let mut sketch = HllSketch::new();
+        assert!(sketch.merge_array(&states).is_err());
+        assert_eq!(sketch.estimate(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_hll_udafs() {
+        let ctx = SessionContext::new();
+        ctx.register_udaf(AggregateUDF::from(HllAgg::sketch()));
+        ctx.register_udaf(AggregateUDF::from(HllAgg::merge()));
+        ctx.register_udaf(AggregateUDF::from(HllAgg::estimate()));
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("bucket", DataType::Int64, false),
+            Field::new("user", DataType::Utf8, false),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(Int64Array::from(vec![1, 1, 1, 2, 2])),
+                Arc::new(StringArray::from(vec!["a", "b", "a", "b", "c"])),
+            ],
+        )
+        .unwrap();
+        let table = MemTable::try_new(schema, vec![vec![batch]]).unwrap();
+        ctx.register_table("t", Arc::new(table)).unwrap();
+
+        let sql = "SELECT hll_estimate(s) AS users FROM \
+                   (SELECT bucket, hll_sketch(user) AS s FROM t GROUP BY bucket)";
+        let batches = ctx.sql(sql).await.unwrap().collect().await.unwrap();
+        let users = batches[0].column(0).as_any().downcast_ref::<UInt64Array>();
+        assert_eq!(users.unwrap().value(0), 3);
+
+        let sql = "SELECT bucket, hll_sketch(user) AS s FROM t GROUP BY bucket ORDER BY bucket";
+        let batches = ctx.sql(sql).await.unwrap().collect().await.unwrap();
+        let sketches = batches[0].column(1).as_any().downcast_ref::<BinaryArray>();
+        let sketch = HllSketch::from_bytes(sketches.unwrap().value(0)).unwrap();
+        assert_eq!(sketch.estimate(), 2);
+    }
+}
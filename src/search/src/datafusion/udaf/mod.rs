@@ -17,7 +17,12 @@ use arrow_schema::DataType;
 
 pub mod approx_topk;
 pub mod approx_topk_distinct;
+pub mod hll;
+pub mod retention;
+pub mod sessionize;
 pub mod summary_percentile;
+pub mod tdigest;
+pub mod window_funnel;
 
 pub static NUMERICS: &[DataType] = &[
     DataType::Int8,
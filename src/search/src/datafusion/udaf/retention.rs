@@ -0,0 +1,215 @@
+// Copyright 2026 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::sync::Arc;
+
+use arrow::{
+    array::{Array, ArrayRef, AsArray},
+    datatypes::UInt32Type,
+};
+use datafusion::{
+    arrow::datatypes::{DataType, Field, FieldRef},
+    common::plan_err,
+    error::Result,
+    logical_expr::{
+        Accumulator, AggregateUDFImpl, Signature, Volatility,
+        function::{AccumulatorArgs, StateFieldsArgs},
+        utils::format_state_name,
+    },
+    scalar::ScalarValue,
+};
+
+pub const RETENTION: &str = "retention";
+
+/// The maximum number of retention conditions.
+const MAX_CONDITIONS: usize = 32;
+
+/// `retention(cond1, cond2, ...)` returns an array of 0 and 1 per condition.
+///
+/// The first element is 1 when any row of the group matches `cond1`, and
+/// element `n` is 1 when rows match both `cond1` and `condn`. Summing the
+/// arrays of every user gives the retention of the cohort.
+///
+/// SELECT sum(r[1]) AS day0, sum(r[2]) AS day1, sum(r[3]) AS day7 FROM (
+///   SELECT user_id, retention(date = '2026-01-01', date = '2026-01-02', date = '2026-01-08')
+///   AS r FROM rum GROUP BY user_id
+/// )
+#[derive(Debug, Hash, PartialEq, Eq)]
+pub struct Retention {
+    signature: Signature,
+}
+
+impl Default for Retention {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Retention {
+    pub fn new() -> Self {
+        Self {
+            signature: Signature::variadic(vec![DataType::Boolean], Volatility::Immutable),
+        }
+    }
+}
+
+impl AggregateUDFImpl for Retention {
+    fn name(&self) -> &str {
+        RETENTION
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, arg_types: &[DataType]) -> Result<DataType> {
+        if arg_types.is_empty() || arg_types.len() > MAX_CONDITIONS {
+            return plan_err!("retention requires 1 to {MAX_CONDITIONS} conditions");
+        }
+        Ok(DataType::List(Arc::new(Field::new_list_field(
+            DataType::UInt8,
+            true,
+        ))))
+    }
+
+    fn state_fields(&self, args: StateFieldsArgs) -> Result<Vec<FieldRef>> {
+        Ok(vec![Arc::new(Field::new(
+            format_state_name(args.name, "matched"),
+            DataType::UInt32,
+            true,
+        ))])
+    }
+
+    fn accumulator(&self, args: AccumulatorArgs) -> Result<Box<dyn Accumulator>> {
+        Ok(Box::new(RetentionAccumulator {
+            conditions: args.exprs.len(),
+            matched: 0,
+        }))
+    }
+}
+
+/// Convert the matched conditions to the retention array.
+pub fn retention_values(matched: u32, conditions: usize) -> Vec<u8> {
+    let first = matched & 1 == 1;
+    (0..conditions)
+        .map(|n| (first && matched & (1 << n) != 0) as u8)
+        .collect()
+}
+
+#[derive(Debug)]
+struct RetentionAccumulator {
+    conditions: usize,
+    // bit n is set when any row matched condition n
+    matched: u32,
+}
+
+impl Accumulator for RetentionAccumulator {
+    fn update_batch(&mut self, values: &[ArrayRef]) -> Result<()> {
+        for (n, values) in values.iter().enumerate() {
+            let values = values.as_boolean();
+            if values.true_count() > 0 {
+                self.matched |= 1 << n;
+            }
+        }
+        Ok(())
+    }
+
+    fn merge_batch(&mut self, states: &[ArrayRef]) -> Result<()> {
+        if states.is_empty() {
+            return Ok(());
+        }
+        for matched in states[0].as_primitive::<UInt32Type>().iter().flatten() {
+            self.matched |= matched;
+        }
+        Ok(())
+    }
+
+    fn state(&mut self) -> Result<Vec<ScalarValue>> {
+        Ok(vec![ScalarValue::UInt32(Some(self.matched))])
+    }
+
+    fn evaluate(&mut self) -> Result<ScalarValue> {
+        let values = retention_values(self.matched, self.conditions)
+            .into_iter()
+            .map(|v| ScalarValue::UInt8(Some(v)))
+            .collect::<Vec<_>>();
+        Ok(ScalarValue::List(ScalarValue::new_list_nullable(
+            &values,
+            &DataType::UInt8,
+        )))
+    }
+
+    fn size(&self) -> usize {
+        std::mem::size_of_val(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow::array::{Int64Array, ListArray, RecordBatch, StringArray, UInt8Array};
+    use arrow_schema::Schema;
+    use datafusion::{datasource::MemTable, logical_expr::AggregateUDF, prelude::SessionContext};
+
+    use super::*;
+
+    #[test]
+    fn test_retention_values() {
+        assert_eq!(retention_values(0b101, 3), vec![1, 0, 1]);
+        assert_eq!(retention_values(0b110, 3), vec![0, 0, 0]);
+        assert_eq!(retention_values(0, 2), vec![0, 0]);
+    }
+
+    #[tokio::test]
+    async fn test_retention_udaf() {
+        let ctx = SessionContext::new();
+        ctx.register_udaf(AggregateUDF::from(Retention::new()));
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("user", DataType::Utf8, false),
+            Field::new("day", DataType::Int64, false),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(StringArray::from(vec!["a", "a", "b", "b", "c"])),
+                Arc::new(Int64Array::from(vec![0, 1, 0, 7, 1])),
+            ],
+        )
+        .unwrap();
+        let table = MemTable::try_new(
+            schema,
+            vec![vec![batch.slice(0, 3)], vec![batch.slice(3, 2)]],
+        )
+        .unwrap();
+        ctx.register_table("t", Arc::new(table)).unwrap();
+
+        let sql = "SELECT user, retention(day = 0, day = 1, day = 7) AS r \
+                   FROM t GROUP BY user ORDER BY user";
+        let batches = ctx.sql(sql).await.unwrap().collect().await.unwrap();
+        let lists = batches[0]
+            .column(1)
+            .as_any()
+            .downcast_ref::<ListArray>()
+            .unwrap();
+        let rows: Vec<Vec<u8>> = lists
+            .iter()
+            .map(|list| {
+                let list = list.unwrap();
+                let values = list.as_any().downcast_ref::<UInt8Array>().unwrap();
+                values.values().to_vec()
+            })
+            .collect();
+        assert_eq!(rows, vec![vec![1, 1, 0], vec![1, 0, 1], vec![0, 0, 0]]);
+    }
+}
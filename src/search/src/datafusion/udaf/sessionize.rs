@@ -0,0 +1,512 @@
+// Copyright 2026 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::sync::Arc;
+
+use arrow::{
+    array::{
+        Array, ArrayRef, AsArray, Int64Array, ListArray, RecordBatch, StructArray, UInt64Array,
+    },
+    buffer::OffsetBuffer,
+    compute::cast,
+    datatypes::{Fields, Int64Type},
+};
+use arrow_schema::Schema;
+use datafusion::{
+    arrow::datatypes::{DataType, Field, FieldRef, TimeUnit},
+    common::{exec_err, internal_err, plan_err},
+    error::Result,
+    logical_expr::{
+        Accumulator, AggregateUDFImpl, ColumnarValue, PartitionEvaluator, Signature, TypeSignature,
+        Volatility, WindowUDFImpl,
+        function::{AccumulatorArgs, PartitionEvaluatorArgs, StateFieldsArgs, WindowUDFFieldArgs},
+        utils::format_state_name,
+    },
+    physical_plan::PhysicalExpr,
+    scalar::ScalarValue,
+};
+
+pub const SESSIONIZE: &str = "sessionize";
+pub const SESSIONS: &str = "sessions";
+// start (i64) + end (i64) + events (u64)
+const SESSION_SIZE: usize = 8 + 8 + 8;
+
+/// `sessionize(_timestamp, gap)` numbers the sessions of the window partition.
+///
+/// The rows must be ordered by the timestamp, a new session starts when the
+/// time since the previous row is more than `gap`, an integer literal in the
+/// unit of the timestamp. The sessions are numbered from 1 in each partition,
+/// so partitioning by the user gives the sessions of every user. The window
+/// needs all the rows of a user in one partition, the `sessions` aggregate
+/// returns the same sessions from partial states.
+///
+/// SELECT user_id, session, min(_timestamp) AS start, max(_timestamp) AS end FROM (
+///   SELECT user_id, _timestamp, sessionize(_timestamp, 1800000000)
+///   OVER (PARTITION BY user_id ORDER BY _timestamp) AS session FROM rum
+/// ) GROUP BY user_id, session
+#[derive(Debug, Hash, PartialEq, Eq)]
+pub struct Sessionize {
+    signature: Signature,
+}
+
+impl Default for Sessionize {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Sessionize {
+    pub fn new() -> Self {
+        let variants = [
+            DataType::Int64,
+            DataType::Timestamp(TimeUnit::Microsecond, None),
+        ]
+        .into_iter()
+        .map(|t| TypeSignature::Exact(vec![t, DataType::Int64]))
+        .collect();
+        Self {
+            signature: Signature::one_of(variants, Volatility::Immutable),
+        }
+    }
+}
+
+impl WindowUDFImpl for Sessionize {
+    fn name(&self) -> &str {
+        SESSIONIZE
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn partition_evaluator(
+        &self,
+        _partition_evaluator_args: PartitionEvaluatorArgs,
+    ) -> Result<Box<dyn PartitionEvaluator>> {
+        Ok(Box::new(SessionizeEvaluator))
+    }
+
+    fn field(&self, field_args: WindowUDFFieldArgs) -> Result<FieldRef> {
+        Ok(Arc::new(Field::new(
+            field_args.name(),
+            DataType::UInt64,
+            false,
+        )))
+    }
+}
+
+/// Number the sessions of the ordered timestamps, a null timestamp stays in
+/// the current session.
+pub fn session_numbers(timestamps: impl Iterator<Item = Option<i64>>, gap: i64) -> Vec<u64> {
+    let mut session = 0;
+    let mut last: Option<i64> = None;
+    timestamps
+        .map(|ts| {
+            if let Some(ts) = ts {
+                if last.is_none_or(|last| ts - last > gap) {
+                    session += 1;
+                }
+                last = Some(ts);
+            }
+            session.max(1)
+        })
+        .collect()
+}
+
+#[derive(Debug)]
+struct SessionizeEvaluator;
+
+impl PartitionEvaluator for SessionizeEvaluator {
+    fn evaluate_all(&mut self, values: &[ArrayRef], num_rows: usize) -> Result<ArrayRef> {
+        if num_rows == 0 {
+            return Ok(Arc::new(UInt64Array::from(Vec::<u64>::new())));
+        }
+        let gap = values[1].as_primitive::<Int64Type>();
+        if gap.is_null(0) || gap.value(0) < 0 {
+            return exec_err!("sessionize requires a non negative gap");
+        }
+        let timestamps = cast(&values[0], &DataType::Int64)?;
+        let timestamps = timestamps.as_primitive::<Int64Type>();
+        Ok(Arc::new(UInt64Array::from(session_numbers(
+            timestamps.iter(),
+            gap.value(0),
+        ))))
+    }
+}
+
+/// `sessions(_timestamp, gap)` returns the sessions of the group as a list of
+/// `{start, end, events}`, ordered by the start.
+///
+/// A new session starts when the time since the previous event is more than
+/// `gap`, an integer literal in the unit of the timestamp, like `sessionize`.
+///
+/// SELECT user_id, unnest(s) FROM (
+///   SELECT user_id, sessions(_timestamp, 1800000000) AS s FROM rum GROUP BY user_id
+/// )
+///
+/// The partial state is the list of the sessions found so far, merging the
+/// states joins the sessions of the partitions that are no more than `gap`
+/// apart, so a session spanning partitions is counted once.
+#[derive(Debug, Hash, PartialEq, Eq)]
+pub struct Sessions {
+    signature: Signature,
+}
+
+impl Default for Sessions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Sessions {
+    pub fn new() -> Self {
+        Self {
+            signature: Signature::any(2, Volatility::Immutable),
+        }
+    }
+}
+
+fn session_fields() -> Fields {
+    Fields::from(vec![
+        Field::new("start", DataType::Int64, false),
+        Field::new("end", DataType::Int64, false),
+        Field::new("events", DataType::UInt64, false),
+    ])
+}
+
+impl AggregateUDFImpl for Sessions {
+    fn name(&self) -> &str {
+        SESSIONS
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, arg_types: &[DataType]) -> Result<DataType> {
+        if !arg_types[1].is_integer() {
+            return plan_err!("sessions requires an integer gap");
+        }
+        if !matches!(
+            arg_types[0],
+            DataType::Int64 | DataType::Timestamp(TimeUnit::Microsecond, _)
+        ) {
+            return plan_err!("sessions requires an Int64 or a timestamp column");
+        }
+        Ok(DataType::List(Arc::new(Field::new_list_field(
+            DataType::Struct(session_fields()),
+            true,
+        ))))
+    }
+
+    fn state_fields(&self, args: StateFieldsArgs) -> Result<Vec<FieldRef>> {
+        Ok(vec![Arc::new(Field::new(
+            format_state_name(args.name, "sessions"),
+            DataType::Binary,
+            true,
+        ))])
+    }
+
+    fn accumulator(&self, args: AccumulatorArgs) -> Result<Box<dyn Accumulator>> {
+        Ok(Box::new(SessionsAccumulator {
+            gap: validate_input_gap_expr(&args.exprs[1])?,
+            sessions: Vec::new(),
+        }))
+    }
+}
+
+fn validate_input_gap_expr(expr: &Arc<dyn PhysicalExpr>) -> Result<i64> {
+    let batch = RecordBatch::new_empty(Arc::new(Schema::empty()));
+    let ColumnarValue::Scalar(value) = expr.evaluate(&batch)? else {
+        return internal_err!("Didn't expect ColumnarValue::Array");
+    };
+    let gap = match value.cast_to(&DataType::Int64)? {
+        ScalarValue::Int64(Some(gap)) => gap,
+        sv => return plan_err!("Gap for 'SESSIONS' must be an integer literal (got {sv})"),
+    };
+    if gap < 0 {
+        return plan_err!("Gap for 'SESSIONS' must not be negative, got {gap}");
+    }
+    Ok(gap)
+}
+
+/// A session, the first and the last event time and the number of events.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Session {
+    pub start: i64,
+    pub end: i64,
+    pub events: u64,
+}
+
+/// Sort the sessions and join the ones no more than `gap` apart. The sessions
+/// of different partitions are disjoint sets of events, so joining them gives
+/// the sessions of all the events.
+pub fn stitch_sessions(sessions: &mut Vec<Session>, gap: i64) {
+    sessions.sort_unstable();
+    let mut stitched: Vec<Session> = Vec::with_capacity(sessions.len());
+    for session in sessions.drain(..) {
+        match stitched.last_mut() {
+            Some(last) if session.start - last.end <= gap => {
+                last.end = last.end.max(session.end);
+                last.events += session.events;
+            }
+            _ => stitched.push(session),
+        }
+    }
+    *sessions = stitched;
+}
+
+#[derive(Debug)]
+struct SessionsAccumulator {
+    gap: i64,
+    // stitched after every batch
+    sessions: Vec<Session>,
+}
+
+impl SessionsAccumulator {
+    fn merge_array(&mut self, array: &ArrayRef) -> Result<()> {
+        let bytes: Vec<Option<&[u8]>> = match array.data_type() {
+            DataType::Binary => array.as_binary::<i32>().iter().collect(),
+            DataType::LargeBinary => array.as_binary::<i64>().iter().collect(),
+            DataType::BinaryView => array.as_binary_view().iter().collect(),
+            t => return exec_err!("sessions state must be binary, got {t}"),
+        };
+        for bytes in bytes.into_iter().flatten() {
+            if bytes.len() % SESSION_SIZE != 0 {
+                return exec_err!("Invalid sessions state of {} bytes", bytes.len());
+            }
+            self.sessions
+                .extend(bytes.chunks_exact(SESSION_SIZE).map(|chunk| Session {
+                    start: i64::from_le_bytes(chunk[..8].try_into().unwrap()),
+                    end: i64::from_le_bytes(chunk[8..16].try_into().unwrap()),
+                    events: u64::from_le_bytes(chunk[16..].try_into().unwrap()),
+                }));
+        }
+        stitch_sessions(&mut self.sessions, self.gap);
+        Ok(())
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.sessions.len() * SESSION_SIZE);
+        for session in self.sessions.iter() {
+            bytes.extend_from_slice(&session.start.to_le_bytes());
+            bytes.extend_from_slice(&session.end.to_le_bytes());
+            bytes.extend_from_slice(&session.events.to_le_bytes());
+        }
+        bytes
+    }
+}
+
+impl Accumulator for SessionsAccumulator {
+    fn update_batch(&mut self, values: &[ArrayRef]) -> Result<()> {
+        let ts = cast(&values[0], &DataType::Int64)?;
+        let ts = ts.as_primitive::<Int64Type>();
+        self.sessions.extend(ts.iter().flatten().map(|ts| Session {
+            start: ts,
+            end: ts,
+            events: 1,
+        }));
+        stitch_sessions(&mut self.sessions, self.gap);
+        Ok(())
+    }
+
+    fn merge_batch(&mut self, states: &[ArrayRef]) -> Result<()> {
+        if states.is_empty() {
+            return Ok(());
+        }
+        self.merge_array(&states[0])
+    }
+
+    fn state(&mut self) -> Result<Vec<ScalarValue>> {
+        Ok(vec![ScalarValue::Binary(Some(self.to_bytes()))])
+    }
+
+    fn evaluate(&mut self) -> Result<ScalarValue> {
+        let sessions = StructArray::new(
+            session_fields(),
+            vec![
+                Arc::new(Int64Array::from_iter_values(
+                    self.sessions.iter().map(|s| s.start),
+                )),
+                Arc::new(Int64Array::from_iter_values(
+                    self.sessions.iter().map(|s| s.end),
+                )),
+                Arc::new(UInt64Array::from_iter_values(
+                    self.sessions.iter().map(|s| s.events),
+                )),
+            ],
+            None,
+        );
+        let list = ListArray::new(
+            Arc::new(Field::new_list_field(
+                DataType::Struct(session_fields()),
+                true,
+            )),
+            OffsetBuffer::from_lengths([sessions.len()]),
+            Arc::new(sessions),
+            None,
+        );
+        Ok(ScalarValue::List(Arc::new(list)))
+    }
+
+    fn size(&self) -> usize {
+        std::mem::size_of_val(self) + self.sessions.capacity() * std::mem::size_of::<Session>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow::array::{BinaryArray, StringArray};
+    use datafusion::{
+        datasource::MemTable,
+        logical_expr::{AggregateUDF, WindowUDF},
+        prelude::SessionContext,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_session_numbers() {
+        let ts = [Some(0), Some(5), Some(20), None, Some(21), Some(40)];
+        assert_eq!(session_numbers(ts.into_iter(), 10), vec![1, 1, 2, 2, 2, 3]);
+        assert_eq!(session_numbers([None, Some(3)].into_iter(), 10), vec![1, 1]);
+    }
+
+    #[tokio::test]
+    async fn test_sessionize_udwf() {
+        let ctx = SessionContext::new();
+        ctx.register_udwf(WindowUDF::from(Sessionize::new()));
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("user", DataType::Utf8, false),
+            Field::new("_timestamp", DataType::Int64, false),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(StringArray::from(vec!["a", "b", "a", "a", "b"])),
+                Arc::new(Int64Array::from(vec![100, 0, 0, 5, 50])),
+            ],
+        )
+        .unwrap();
+        let table = MemTable::try_new(schema, vec![vec![batch]]).unwrap();
+        ctx.register_table("t", Arc::new(table)).unwrap();
+
+        let sql = "SELECT user, count(DISTINCT session) AS sessions FROM (\
+                   SELECT user, sessionize(_timestamp, 10) OVER (PARTITION BY user \
+                   ORDER BY _timestamp) AS session FROM t) GROUP BY user ORDER BY user";
+        let batches = ctx.sql(sql).await.unwrap().collect().await.unwrap();
+        let sessions = batches[0].column(1).as_primitive::<Int64Type>();
+        assert_eq!(sessions.values().to_vec(), vec![2, 2]);
+    }
+
+    fn session(start: i64, end: i64, events: u64) -> Session {
+        Session { start, end, events }
+    }
+
+    #[test]
+    fn test_stitch_sessions() {
+        let mut sessions = vec![session(20, 25, 2), session(0, 5, 2), session(3, 12, 3)];
+        stitch_sessions(&mut sessions, 10);
+        assert_eq!(sessions, vec![session(0, 25, 7)]);
+        let mut sessions = vec![session(40, 40, 1), session(0, 5, 2)];
+        stitch_sessions(&mut sessions, 10);
+        assert_eq!(sessions, vec![session(0, 5, 2), session(40, 40, 1)]);
+    }
+
+    #[test]
+    fn test_sessions_merge_across_partitions() {
+        let new_accumulator = || SessionsAccumulator {
+            gap: 10,
+            sessions: Vec::new(),
+        };
+        let values = |ts: Vec<i64>| -> Vec<ArrayRef> {
+            vec![
+                Arc::new(Int64Array::from(ts.clone())),
+                Arc::new(Int64Array::from(vec![10; ts.len()])),
+            ]
+        };
+        // the session [0, 12] is cut between the two partitions
+        let mut first = new_accumulator();
+        first.update_batch(&values(vec![0, 5])).unwrap();
+        let mut second = new_accumulator();
+        second.update_batch(&values(vec![100, 12, 105])).unwrap();
+        assert_eq!(
+            second.sessions,
+            vec![session(12, 12, 1), session(100, 105, 2)]
+        );
+
+        let states = [first.state().unwrap(), second.state().unwrap()]
+            .into_iter()
+            .map(|state| match &state[0] {
+                ScalarValue::Binary(Some(bytes)) => bytes.clone(),
+                v => panic!("unexpected state {v:?}"),
+            })
+            .collect::<Vec<_>>();
+        let states: ArrayRef = Arc::new(BinaryArray::from_iter_values(states));
+        let mut merged = new_accumulator();
+        merged.merge_batch(&[states]).unwrap();
+        assert_eq!(
+            merged.sessions,
+            vec![session(0, 12, 3), session(100, 105, 2)]
+        );
+
+        let states: ArrayRef = Arc::new(BinaryArray::from_iter_values([[0u8; 5]]));
+        assert!(new_accumulator().merge_batch(&[states]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_sessions_udaf() {
+        let ctx = SessionContext::new();
+        ctx.register_udaf(AggregateUDF::from(Sessions::new()));
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("user", DataType::Utf8, false),
+            Field::new("_timestamp", DataType::Int64, false),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(StringArray::from(vec!["a", "b", "a", "a", "b"])),
+                Arc::new(Int64Array::from(vec![0, 0, 5, 12, 50])),
+            ],
+        )
+        .unwrap();
+        // the first session of a spans both partitions
+        let table = MemTable::try_new(
+            schema,
+            vec![vec![batch.slice(0, 3)], vec![batch.slice(3, 2)]],
+        )
+        .unwrap();
+        ctx.register_table("t", Arc::new(table)).unwrap();
+
+        let sql = "SELECT user, sessions(_timestamp, 10) AS s FROM t GROUP BY user ORDER BY user";
+        let batches = ctx.sql(sql).await.unwrap().collect().await.unwrap();
+        let lists = batches[0].column(1).as_list::<i32>();
+        let a = lists.value(0);
+        let a = a.as_struct();
+        assert_eq!(a.len(), 1);
+        assert_eq!(a.column(0).as_primitive::<Int64Type>().value(0), 0);
+        assert_eq!(a.column(1).as_primitive::<Int64Type>().value(0), 12);
+        assert_eq!(
+            a.column(2)
+                .as_primitive::<arrow::datatypes::UInt64Type>()
+                .value(0),
+            3
+        );
+        assert_eq!(lists.value(1).len(), 2);
+
+        let sql = "SELECT sessions(_timestamp, user) FROM t";
+        assert!(ctx.sql(sql).await.is_err());
+    }
+}
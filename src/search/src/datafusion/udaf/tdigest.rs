@@ -0,0 +1,519 @@
+// Copyright 2026 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::{f64::consts::PI, sync::Arc};
+
+use arrow::{
+    array::{Array, ArrayRef, AsArray, RecordBatch},
+    compute::cast,
+    datatypes::Float64Type,
+};
+use arrow_schema::Schema;
+use datafusion::{
+    arrow::datatypes::{DataType, Field, FieldRef},
+    common::{exec_err, internal_err, not_impl_err, plan_err},
+    error::Result,
+    logical_expr::{
+        Accumulator, AggregateUDFImpl, ColumnarValue, Signature, TypeSignature, Volatility,
+        function::{AccumulatorArgs, StateFieldsArgs},
+        utils::format_state_name,
+    },
+    physical_plan::PhysicalExpr,
+    scalar::ScalarValue,
+};
+
+use super::NUMERICS;
+
+pub const TDIGEST_SKETCH: &str = "tdigest_sketch";
+pub const QUANTILE_FROM_SKETCH: &str = "quantile_from_sketch";
+
+const COMPRESSION: f64 = 100.0;
+const FORMAT_VERSION: u8 = 1;
+const HEADER_SIZE: usize = 1 + 8 * 3 + 4;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Centroid {
+    mean: f64,
+    weight: f64,
+}
+
+/// Merging t-digest for percentiles.
+///
+/// Centroids are kept small at the tails and large around the median, so
+/// extreme percentiles stay accurate while the sketch size is bounded by the
+/// compression. Sketches with the same compression can be merged, which lets
+/// the streaming aggregation cache and rollup streams store per-bucket sketches
+/// and combine them at query time.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TDigest {
+    compression: f64,
+    centroids: Vec<Centroid>,
+    // values not yet merged into the centroids
+    buffer: Vec<Centroid>,
+    min: f64,
+    max: f64,
+}
+
+impl Default for TDigest {
+    fn default() -> Self {
+        Self::new(COMPRESSION)
+    }
+}
+
+impl TDigest {
+    pub fn new(compression: f64) -> Self {
+        Self {
+            compression,
+            centroids: Vec::new(),
+            buffer: Vec::new(),
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+        }
+    }
+
+    pub fn insert(&mut self, value: f64) {
+        if value.is_nan() {
+            return;
+        }
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+        self.buffer.push(Centroid {
+            mean: value,
+            weight: 1.0,
+        });
+        if self.buffer.len() >= self.buffer_limit() {
+            self.compress();
+        }
+    }
+
+    pub fn merge(&mut self, other: &TDigest) {
+        if other.is_empty() {
+            return;
+        }
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+        self.buffer.extend_from_slice(&other.centroids);
+        self.buffer.extend_from_slice(&other.buffer);
+        if self.buffer.len() >= self.buffer_limit() {
+            self.compress();
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.centroids.is_empty() && self.buffer.is_empty()
+    }
+
+    pub fn count(&self) -> f64 {
+        self.centroids
+            .iter()
+            .chain(self.buffer.iter())
+            .map(|c| c.weight)
+            .sum()
+    }
+
+    fn buffer_limit(&self) -> usize {
+        (self.compression as usize) * 5
+    }
+
+    fn k_to_q(&self, k: f64) -> f64 {
+        ((k * 2.0 * PI / self.compression).sin() + 1.0) / 2.0
+    }
+
+    fn q_to_k(&self, q: f64) -> f64 {
+        self.compression / (2.0 * PI) * (2.0 * q - 1.0).clamp(-1.0, 1.0).asin()
+    }
+
+    /// Merge the buffered values into the centroids.
+    fn compress(&mut self) {
+        if self.buffer.is_empty() {
+            return;
+        }
+        let mut all = std::mem::take(&mut self.centroids);
+        all.append(&mut self.buffer);
+        all.sort_by(|a, b| a.mean.total_cmp(&b.mean));
+
+        let total: f64 = all.iter().map(|c| c.weight).sum();
+        let mut merged = Vec::with_capacity(self.compression as usize);
+        let mut current = all[0];
+        let mut weight_so_far = 0.0;
+        let mut q_limit = self.k_to_q(self.q_to_k(0.0) + 1.0) * total;
+        for c in all.into_iter().skip(1) {
+            if weight_so_far + current.weight + c.weight <= q_limit {
+                let weight = current.weight + c.weight;
+                current.mean += (c.mean - current.mean) * c.weight / weight;
+                current.weight = weight;
+            } else {
+                weight_so_far += current.weight;
+                q_limit = self.k_to_q(self.q_to_k(weight_so_far / total) + 1.0) * total;
+                merged.push(current);
+                current = c;
+            }
+        }
+        merged.push(current);
+        self.centroids = merged;
+    }
+
+    /// Estimate the value at quantile `q`, `None` for an empty sketch.
+    pub fn quantile(&mut self, q: f64) -> Option<f64> {
+        self.compress();
+        let centroids = &self.centroids;
+        let (first, last) = (centroids.first()?, centroids.last()?);
+        if centroids.len() == 1 {
+            return Some(first.mean);
+        }
+        let total: f64 = centroids.iter().map(|c| c.weight).sum();
+        let target = q.clamp(0.0, 1.0) * total;
+
+        // each centroid is centered at its cumulative weight plus half its weight
+        if target < first.weight / 2.0 {
+            return Some(interpolate(
+                self.min,
+                first.mean,
+                target / (first.weight / 2.0),
+            ));
+        }
+        let mut cumulative = 0.0;
+        for pair in centroids.windows(2) {
+            let left = cumulative + pair[0].weight / 2.0;
+            let right = cumulative + pair[0].weight + pair[1].weight / 2.0;
+            if target <= right {
+                return Some(interpolate(
+                    pair[0].mean,
+                    pair[1].mean,
+                    (target - left) / (right - left),
+                ));
+            }
+            cumulative += pair[0].weight;
+        }
+        let left = total - last.weight / 2.0;
+        Some(interpolate(
+            last.mean,
+            self.max,
+            (target - left) / (last.weight / 2.0),
+        ))
+    }
+
+    pub fn to_bytes(&mut self) -> Vec<u8> {
+        self.compress();
+        let mut buf = Vec::with_capacity(HEADER_SIZE + self.centroids.len() * 16);
+        buf.push(FORMAT_VERSION);
+        buf.extend_from_slice(&self.compression.to_le_bytes());
+        buf.extend_from_slice(&self.min.to_le_bytes());
+        buf.extend_from_slice(&self.max.to_le_bytes());
+        buf.extend_from_slice(&(self.centroids.len() as u32).to_le_bytes());
+        for c in self.centroids.iter() {
+            buf.extend_from_slice(&c.mean.to_le_bytes());
+            buf.extend_from_slice(&c.weight.to_le_bytes());
+        }
+        buf
+    }
+
+    pub fn from_bytes(buf: &[u8]) -> Result<Self> {
+        if buf.len() < HEADER_SIZE {
+            return exec_err!("invalid tdigest sketch: too short");
+        }
+        if buf[0] != FORMAT_VERSION {
+            return exec_err!("invalid tdigest sketch: unknown version {}", buf[0]);
+        }
+        let read_f64 = |pos: usize| f64::from_le_bytes(buf[pos..pos + 8].try_into().unwrap());
+        let compression = read_f64(1);
+        if compression != COMPRESSION {
+            return exec_err!("invalid tdigest sketch: unsupported compression {compression}");
+        }
+        let len = u32::from_le_bytes(buf[25..29].try_into().unwrap()) as usize;
+        if buf.len() != HEADER_SIZE + len * 16 {
+            return exec_err!("invalid tdigest sketch: wrong number of centroids");
+        }
+        let mut digest = TDigest::new(compression);
+        digest.min = read_f64(9);
+        digest.max = read_f64(17);
+        digest.centroids = (0..len)
+            .map(|i| {
+                let pos = HEADER_SIZE + i * 16;
+                Centroid {
+                    mean: read_f64(pos),
+                    weight: read_f64(pos + 8),
+                }
+            })
+            .collect();
+        Ok(digest)
+    }
+
+    fn merge_array(&mut self, array: &ArrayRef) -> Result<()> {
+        let array = cast(array, &DataType::Binary)?;
+        for buf in array.as_binary::<i32>().iter().flatten() {
+            self.merge(&TDigest::from_bytes(buf)?);
+        }
+        Ok(())
+    }
+}
+
+fn interpolate(from: f64, to: f64, ratio: f64) -> f64 {
+    from + (to - from) * ratio.clamp(0.0, 1.0)
+}
+
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+enum TDigestMode {
+    /// Build a sketch from numeric values.
+    Sketch,
+    /// Merge sketches and return the value at a quantile.
+    Quantile,
+}
+
+/// The t-digest aggregate functions:
+///
+/// - `tdigest_sketch(field)` builds a percentile sketch of a numeric field
+/// - `quantile_from_sketch(sketch, 0.99)` merges sketches and returns the estimated value at the
+///   quantile, the quantile must be a float literal between 0.0 and 1.0
+///
+/// SELECT quantile_from_sketch(latency, 0.99) AS p99
+/// FROM (SELECT histogram(_timestamp) AS ts, tdigest_sketch(took) AS latency FROM default GROUP BY
+/// ts)
+#[derive(Debug, Hash, PartialEq, Eq)]
+pub struct TDigestAgg {
+    mode: TDigestMode,
+    signature: Signature,
+}
+
+impl TDigestAgg {
+    pub fn sketch() -> Self {
+        Self {
+            mode: TDigestMode::Sketch,
+            signature: Signature::uniform(1, NUMERICS.to_vec(), Volatility::Immutable),
+        }
+    }
+
+    pub fn quantile() -> Self {
+        let variants = [
+            DataType::Binary,
+            DataType::LargeBinary,
+            DataType::BinaryView,
+        ]
+        .into_iter()
+        .map(|t| TypeSignature::Exact(vec![t, DataType::Float64]))
+        .collect();
+        Self {
+            mode: TDigestMode::Quantile,
+            signature: Signature::one_of(variants, Volatility::Immutable),
+        }
+    }
+}
+
+impl AggregateUDFImpl for TDigestAgg {
+    fn name(&self) -> &str {
+        match self.mode {
+            TDigestMode::Sketch => TDIGEST_SKETCH,
+            TDigestMode::Quantile => QUANTILE_FROM_SKETCH,
+        }
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, arg_types: &[DataType]) -> Result<DataType> {
+        match self.mode {
+            TDigestMode::Sketch => {
+                if !arg_types[0].is_numeric() {
+                    return plan_err!("tdigest_sketch requires numeric input types");
+                }
+                Ok(DataType::Binary)
+            }
+            TDigestMode::Quantile => Ok(DataType::Float64),
+        }
+    }
+
+    fn state_fields(&self, args: StateFieldsArgs) -> Result<Vec<FieldRef>> {
+        Ok(vec![Arc::new(Field::new(
+            format_state_name(args.name, "sketch"),
+            DataType::Binary,
+            true,
+        ))])
+    }
+
+    fn accumulator(&self, args: AccumulatorArgs) -> Result<Box<dyn Accumulator>> {
+        let quantile = match self.mode {
+            TDigestMode::Sketch => None,
+            TDigestMode::Quantile => Some(validate_input_quantile_expr(&args.exprs[1])?),
+        };
+        Ok(Box::new(TDigestAccumulator {
+            quantile,
+            digest: TDigest::default(),
+        }))
+    }
+}
+
+fn validate_input_quantile_expr(expr: &Arc<dyn PhysicalExpr>) -> Result<f64> {
+    let empty_schema = Arc::new(Schema::empty());
+    let batch = RecordBatch::new_empty(Arc::clone(&empty_schema));
+    let ColumnarValue::Scalar(value) = expr.evaluate(&batch)? else {
+        return internal_err!("Didn't expect ColumnarValue::Array");
+    };
+    let quantile = match value {
+        ScalarValue::Float32(Some(value)) => value as f64,
+        ScalarValue::Float64(Some(value)) => value,
+        sv => {
+            return not_impl_err!(
+                "Quantile value for 'QUANTILE_FROM_SKETCH' must be Float32 or Float64 literal (got data type {})",
+                sv.data_type()
+            );
+        }
+    };
+    if !(0.0..=1.0).contains(&quantile) {
+        return plan_err!(
+            "Quantile value must be between 0.0 and 1.0 inclusive, {quantile} is invalid"
+        );
+    }
+    Ok(quantile)
+}
+
+#[derive(Debug)]
+struct TDigestAccumulator {
+    // None when building a sketch
+    quantile: Option<f64>,
+    digest: TDigest,
+}
+
+impl Accumulator for TDigestAccumulator {
+    fn update_batch(&mut self, values: &[ArrayRef]) -> Result<()> {
+        if self.quantile.is_some() {
+            return self.digest.merge_array(&values[0]);
+        }
+        let values = cast(&values[0], &DataType::Float64)?;
+        for value in values.as_primitive::<Float64Type>().iter().flatten() {
+            self.digest.insert(value);
+        }
+        Ok(())
+    }
+
+    fn merge_batch(&mut self, states: &[ArrayRef]) -> Result<()> {
+        if states.is_empty() {
+            return Ok(());
+        }
+        self.digest.merge_array(&states[0])
+    }
+
+    fn state(&mut self) -> Result<Vec<ScalarValue>> {
+        Ok(vec![ScalarValue::Binary(Some(self.digest.to_bytes()))])
+    }
+
+    fn evaluate(&mut self) -> Result<ScalarValue> {
+        Ok(match self.quantile {
+            None => ScalarValue::Binary(Some(self.digest.to_bytes())),
+            Some(q) => ScalarValue::Float64(self.digest.quantile(q)),
+        })
+    }
+
+    fn size(&self) -> usize {
+        std::mem::size_of_val(self)
+            + (self.digest.centroids.capacity() + self.digest.buffer.capacity())
+                * std::mem::size_of::<Centroid>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow::array::{Float64Array, Int64Array};
+    use datafusion::{datasource::MemTable, logical_expr::AggregateUDF, prelude::SessionContext};
+
+    use super::*;
+
+    fn digest_of(values: impl Iterator<Item = f64>) -> TDigest {
+        let mut digest = TDigest::default();
+        for v in values {
+            digest.insert(v);
+        }
+        digest
+    }
+
+    fn assert_close(value: f64, expected: f64, tolerance: f64) {
+        assert!(
+            (value - expected).abs() <= tolerance,
+            "value {value} expected {expected}"
+        );
+    }
+
+    #[test]
+    fn test_tdigest_quantile() {
+        assert_eq!(TDigest::default().quantile(0.5), None);
+        assert_eq!(digest_of([7.0].into_iter()).quantile(0.9), Some(7.0));
+
+        let mut digest = digest_of((0..100_000).map(|i| i as f64));
+        assert_close(digest.quantile(0.0).unwrap(), 0.0, 1.0);
+        assert_close(digest.quantile(0.5).unwrap(), 50_000.0, 500.0);
+        assert_close(digest.quantile(0.99).unwrap(), 99_000.0, 100.0);
+        assert_close(digest.quantile(1.0).unwrap(), 99_999.0, 1.0);
+        assert!(digest.centroids.len() <= COMPRESSION as usize);
+    }
+
+    #[test]
+    fn test_tdigest_merge() {
+        let mut merged = TDigest::default();
+        for part in 0..10 {
+            merged.merge(&digest_of(
+                (part * 10_000..(part + 1) * 10_000).map(|i| i as f64),
+            ));
+        }
+        assert_eq!(merged.count(), 100_000.0);
+        assert_close(merged.quantile(0.5).unwrap(), 50_000.0, 500.0);
+        assert_close(merged.quantile(0.99).unwrap(), 99_000.0, 100.0);
+    }
+
+    #[test]
+    fn test_tdigest_bytes_round_trip() {
+        let mut digest = digest_of((0..1000).map(|i| i as f64));
+        let bytes = digest.to_bytes();
+        let mut decoded = TDigest::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded, digest);
+        assert_eq!(decoded.quantile(0.5), digest.quantile(0.5));
+        assert!(TDigest::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        assert!(TDigest::from_bytes(&[]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_tdigest_udafs() {
+        let ctx = SessionContext::new();
+        ctx.register_udaf(AggregateUDF::from(TDigestAgg::sketch()));
+        ctx.register_udaf(AggregateUDF::from(TDigestAgg::quantile()));
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("bucket", DataType::Int64, false),
+            Field::new("took", DataType::Int64, false),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(Int64Array::from_iter_values((0..1000).map(|i| i % 10))),
+                Arc::new(Int64Array::from_iter_values(0..1000)),
+            ],
+        )
+        .unwrap();
+        let table = MemTable::try_new(schema, vec![vec![batch]]).unwrap();
+        ctx.register_table("t", Arc::new(table)).unwrap();
+
+        let sql = "SELECT quantile_from_sketch(s, 0.5) AS p50 FROM \
+                   (SELECT bucket, tdigest_sketch(took) AS s FROM t GROUP BY bucket)";
+        let batches = ctx.sql(sql).await.unwrap().collect().await.unwrap();
+        let p50 = batches[0].column(0).as_any().downcast_ref::<Float64Array>();
+        assert_close(p50.unwrap().value(0), 500.0, 10.0);
+
+        let sql = "SELECT quantile_from_sketch(s, 1.5) FROM \
+                   (SELECT tdigest_sketch(took) AS s FROM t)";
+        let result = match ctx.sql(sql).await {
+            Ok(df) => df.collect().await.map(|_| ()),
+            Err(e) => Err(e),
+        };
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,329 @@
+// Copyright 2026 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::sync::Arc;
+
+use arrow::{
+    array::{Array, ArrayRef, AsArray, RecordBatch},
+    compute::cast,
+    datatypes::Int64Type,
+};
+use arrow_schema::Schema;
+use datafusion::{
+    arrow::datatypes::{DataType, Field, FieldRef, TimeUnit},
+    common::{exec_err, internal_err, plan_err},
+    error::Result,
+    logical_expr::{
+        Accumulator, AggregateUDFImpl, ColumnarValue, Signature, Volatility,
+        function::{AccumulatorArgs, StateFieldsArgs},
+        utils::format_state_name,
+    },
+    physical_plan::PhysicalExpr,
+    scalar::ScalarValue,
+};
+
+pub const WINDOW_FUNNEL: &str = "window_funnel";
+
+/// The maximum number of funnel steps.
+const MAX_STEPS: usize = 32;
+// timestamp (i64) + matched steps (u32)
+const EVENT_SIZE: usize = 8 + 4;
+
+/// `window_funnel(window, _timestamp, cond1, cond2, ...)` returns the number
+/// of consecutive funnel steps reached within the window.
+///
+/// A chain starts at an event matching `cond1`, and step `n` is reached by an
+/// event matching `condn` after step `n - 1` and no later than `window` after
+/// the chain started. The window is an integer literal in the unit of the
+/// timestamp, microseconds for `_timestamp`.
+///
+/// SELECT level, count(*) FROM (
+///   SELECT user_id, window_funnel(3600000000, _timestamp, page = 'home', page = 'cart',
+///   page = 'checkout') AS level FROM rum GROUP BY user_id
+/// ) GROUP BY level
+///
+/// The partial state is the list of the matching events, so the groups are
+/// merged across the partitions of the distributed plan before the funnel is
+/// computed.
+#[derive(Debug, Hash, PartialEq, Eq)]
+pub struct WindowFunnel {
+    signature: Signature,
+}
+
+impl Default for WindowFunnel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl WindowFunnel {
+    pub fn new() -> Self {
+        Self {
+            signature: Signature::variadic_any(Volatility::Immutable),
+        }
+    }
+}
+
+impl AggregateUDFImpl for WindowFunnel {
+    fn name(&self) -> &str {
+        WINDOW_FUNNEL
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, arg_types: &[DataType]) -> Result<DataType> {
+        if arg_types.len() < 3 || arg_types.len() > MAX_STEPS + 2 {
+            return plan_err!(
+                "window_funnel requires a window, a timestamp and 1 to {MAX_STEPS} conditions"
+            );
+        }
+        if !arg_types[0].is_integer() {
+            return plan_err!("window_funnel requires an integer window");
+        }
+        if !matches!(
+            arg_types[1],
+            DataType::Int64 | DataType::Timestamp(TimeUnit::Microsecond, _)
+        ) {
+            return plan_err!("window_funnel requires an Int64 or a timestamp column");
+        }
+        if arg_types[2..].iter().any(|t| t != &DataType::Boolean) {
+            return plan_err!("window_funnel requires boolean conditions");
+        }
+        Ok(DataType::UInt8)
+    }
+
+    fn state_fields(&self, args: StateFieldsArgs) -> Result<Vec<FieldRef>> {
+        Ok(vec![Arc::new(Field::new(
+            format_state_name(args.name, "events"),
+            DataType::Binary,
+            true,
+        ))])
+    }
+
+    fn accumulator(&self, args: AccumulatorArgs) -> Result<Box<dyn Accumulator>> {
+        let window = validate_input_window_expr(&args.exprs[0])?;
+        Ok(Box::new(WindowFunnelAccumulator {
+            window,
+            steps: args.exprs.len() - 2,
+            events: Vec::new(),
+        }))
+    }
+}
+
+fn validate_input_window_expr(expr: &Arc<dyn PhysicalExpr>) -> Result<i64> {
+    let empty_schema = Arc::new(Schema::empty());
+    let batch = RecordBatch::new_empty(Arc::clone(&empty_schema));
+    let ColumnarValue::Scalar(value) = expr.evaluate(&batch)? else {
+        return internal_err!("Didn't expect ColumnarValue::Array");
+    };
+    let window = match value.cast_to(&DataType::Int64)? {
+        ScalarValue::Int64(Some(window)) => window,
+        sv => {
+            return plan_err!("Window for 'WINDOW_FUNNEL' must be an integer literal (got {sv})");
+        }
+    };
+    if window < 0 {
+        return plan_err!("Window for 'WINDOW_FUNNEL' must not be negative, got {window}");
+    }
+    Ok(window)
+}
+
+/// Compute the funnel level of the events, the bit `n` of the mask is set
+/// when the event matches the condition of step `n`.
+pub fn funnel_level(events: &mut [(i64, u32)], window: i64, steps: usize) -> u8 {
+    // the start of the earliest chain reaching each step, the latest start
+    // of a chain keeps the most room to reach the next steps
+    let mut starts: Vec<Option<i64>> = vec![None; steps];
+    events.sort_unstable();
+    for &(ts, mask) in events.iter() {
+        // from the last step, an event can't advance a chain it just started
+        for step in (0..steps).rev() {
+            if mask & (1 << step) == 0 {
+                continue;
+            }
+            if step == 0 {
+                starts[0] = Some(ts);
+            } else if let Some(start) = starts[step - 1]
+                && ts - start <= window
+            {
+                starts[step] = Some(start);
+                if step == steps - 1 {
+                    return steps as u8;
+                }
+            }
+        }
+    }
+    starts.iter().take_while(|s| s.is_some()).count() as u8
+}
+
+#[derive(Debug)]
+struct WindowFunnelAccumulator {
+    window: i64,
+    steps: usize,
+    // only the events matching at least one step
+    events: Vec<(i64, u32)>,
+}
+
+impl WindowFunnelAccumulator {
+    fn merge_array(&mut self, array: &ArrayRef) -> Result<()> {
+        let bytes: Vec<Option<&[u8]>> = match array.data_type() {
+            DataType::Binary => array.as_binary::<i32>().iter().collect(),
+            DataType::LargeBinary => array.as_binary::<i64>().iter().collect(),
+            DataType::BinaryView => array.as_binary_view().iter().collect(),
+            t => return exec_err!("window_funnel state must be binary, got {t}"),
+        };
+        for bytes in bytes.into_iter().flatten() {
+            if bytes.len() % EVENT_SIZE != 0 {
+                return exec_err!("Invalid window_funnel state of {} bytes", bytes.len());
+            }
+            self.events
+                .extend(bytes.chunks_exact(EVENT_SIZE).map(|chunk| {
+                    let ts = i64::from_le_bytes(chunk[..8].try_into().unwrap());
+                    let mask = u32::from_le_bytes(chunk[8..].try_into().unwrap());
+                    (ts, mask)
+                }));
+        }
+        Ok(())
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.events.len() * EVENT_SIZE);
+        for (ts, mask) in self.events.iter() {
+            bytes.extend_from_slice(&ts.to_le_bytes());
+            bytes.extend_from_slice(&mask.to_le_bytes());
+        }
+        bytes
+    }
+}
+
+impl Accumulator for WindowFunnelAccumulator {
+    fn update_batch(&mut self, values: &[ArrayRef]) -> Result<()> {
+        let ts = cast(&values[1], &DataType::Int64)?;
+        let ts = ts.as_primitive::<Int64Type>();
+        let conditions: Vec<_> = values[2..].iter().map(|v| v.as_boolean()).collect();
+        for row in 0..ts.len() {
+            if ts.is_null(row) {
+                continue;
+            }
+            let mask = conditions
+                .iter()
+                .enumerate()
+                .filter(|(_, cond)| cond.is_valid(row) && cond.value(row))
+                .fold(0u32, |mask, (step, _)| mask | (1 << step));
+            if mask != 0 {
+                self.events.push((ts.value(row), mask));
+            }
+        }
+        Ok(())
+    }
+
+    fn merge_batch(&mut self, states: &[ArrayRef]) -> Result<()> {
+        if states.is_empty() {
+            return Ok(());
+        }
+        self.merge_array(&states[0])
+    }
+
+    fn state(&mut self) -> Result<Vec<ScalarValue>> {
+        Ok(vec![ScalarValue::Binary(Some(self.to_bytes()))])
+    }
+
+    fn evaluate(&mut self) -> Result<ScalarValue> {
+        Ok(ScalarValue::UInt8(Some(funnel_level(
+            &mut self.events,
+            self.window,
+            self.steps,
+        ))))
+    }
+
+    fn size(&self) -> usize {
+        std::mem::size_of_val(self) + self.events.capacity() * std::mem::size_of::<(i64, u32)>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow::array::{Int64Array, StringArray, UInt8Array};
+    use datafusion::{datasource::MemTable, logical_expr::AggregateUDF, prelude::SessionContext};
+
+    use super::*;
+
+    #[test]
+    fn test_funnel_level() {
+        // home -> cart -> checkout
+        let mut events = vec![(10, 0b001), (20, 0b010), (30, 0b100)];
+        assert_eq!(funnel_level(&mut events, 100, 3), 3);
+        assert_eq!(funnel_level(&mut events, 15, 3), 2);
+        assert_eq!(funnel_level(&mut events, 5, 3), 1);
+
+        // out of order and the second step before the first
+        let mut events = vec![(30, 0b100), (5, 0b010), (10, 0b001)];
+        assert_eq!(funnel_level(&mut events, 100, 3), 1);
+
+        // a later start is still within the window
+        let mut events = vec![(0, 0b01), (50, 0b01), (120, 0b10)];
+        assert_eq!(funnel_level(&mut events, 100, 2), 2);
+
+        // one event can't reach two steps
+        let mut events = vec![(0, 0b11)];
+        assert_eq!(funnel_level(&mut events, 100, 2), 1);
+
+        assert_eq!(funnel_level(&mut [], 100, 2), 0);
+    }
+
+    #[tokio::test]
+    async fn test_window_funnel_udaf() {
+        let ctx = SessionContext::new();
+        ctx.register_udaf(AggregateUDF::from(WindowFunnel::new()));
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("user", DataType::Utf8, false),
+            Field::new("_timestamp", DataType::Int64, false),
+            Field::new("page", DataType::Utf8, false),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(StringArray::from(vec!["a", "a", "a", "b", "b", "c"])),
+                Arc::new(Int64Array::from(vec![1, 2, 3, 1, 20, 5])),
+                Arc::new(StringArray::from(vec![
+                    "home", "cart", "checkout", "home", "cart", "cart",
+                ])),
+            ],
+        )
+        .unwrap();
+        // split the rows so the states of a group are merged
+        let table = MemTable::try_new(
+            schema,
+            vec![vec![batch.slice(0, 2)], vec![batch.slice(2, 4)]],
+        )
+        .unwrap();
+        ctx.register_table("t", Arc::new(table)).unwrap();
+
+        let sql = "SELECT user, window_funnel(10, _timestamp, page = 'home', page = 'cart', \
+                   page = 'checkout') AS level FROM t GROUP BY user ORDER BY user";
+        let batches = ctx.sql(sql).await.unwrap().collect().await.unwrap();
+        let level = batches[0]
+            .column(1)
+            .as_any()
+            .downcast_ref::<UInt8Array>()
+            .unwrap();
+        assert_eq!(level.values().to_vec(), vec![3, 1, 0]);
+
+        let sql = "SELECT window_funnel(10, _timestamp, page) FROM t";
+        assert!(ctx.sql(sql).await.is_err());
+    }
+}
@@ -0,0 +1,394 @@
+// Copyright 2026 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::sync::{Arc, LazyLock as Lazy};
+
+use config::{GEO_IP_ASN_ENRICHMENT_TABLE, GEO_IP_CITY_ENRICHMENT_TABLE};
+use datafusion::{
+    arrow::{
+        array::{ArrayRef, Float64Array, Int64Array, StringArray},
+        datatypes::DataType,
+    },
+    common::cast::{as_float64_array, as_int64_array, as_string_array},
+    error::DataFusionError,
+    logical_expr::{ColumnarValue, ScalarUDF, Volatility},
+    prelude::create_udf,
+    sql::sqlparser::parser::ParserError,
+};
+use vector_enrichment::{Case, Condition, Table};
+use vrl::value::Value;
+
+/// The name of the geoip_country UDF given to DataFusion.
+pub const GEOIP_COUNTRY_UDF_NAME: &str = "geoip_country";
+/// The name of the geoip_city UDF given to DataFusion.
+pub const GEOIP_CITY_UDF_NAME: &str = "geoip_city";
+/// The name of the geoip_asn UDF given to DataFusion.
+pub const GEOIP_ASN_UDF_NAME: &str = "geoip_asn";
+/// The name of the geo_distance UDF given to DataFusion.
+pub const GEO_DISTANCE_UDF_NAME: &str = "geo_distance";
+/// The name of the geohash UDF given to DataFusion.
+pub const GEOHASH_UDF_NAME: &str = "geohash";
+
+/// Mean earth radius in kilometers.
+const EARTH_RADIUS_KM: f64 = 6371.0088;
+const GEOHASH_BASE32: &[u8; 32] = b"0123456789bcdefghjkmnpqrstuvwxyz";
+const GEOHASH_MAX_PRECISION: i64 = 12;
+
+fn params_error(usage: &str) -> DataFusionError {
+    DataFusionError::SQL(
+        Box::new(ParserError::ParserError(format!(
+            "UDF params should be: {usage}"
+        ))),
+        None,
+    )
+}
+
+/// Implementation of geoip_country
+pub static GEOIP_COUNTRY_UDF: Lazy<ScalarUDF> = Lazy::new(|| {
+    create_udf(
+        GEOIP_COUNTRY_UDF_NAME,
+        vec![DataType::Utf8],
+        DataType::Utf8,
+        // the database can be reloaded between queries
+        Volatility::Stable,
+        Arc::new(geoip_country_impl),
+    )
+});
+
+/// Implementation of geoip_city
+pub static GEOIP_CITY_UDF: Lazy<ScalarUDF> = Lazy::new(|| {
+    create_udf(
+        GEOIP_CITY_UDF_NAME,
+        vec![DataType::Utf8],
+        DataType::Utf8,
+        Volatility::Stable,
+        Arc::new(geoip_city_impl),
+    )
+});
+
+/// Implementation of geoip_asn
+pub static GEOIP_ASN_UDF: Lazy<ScalarUDF> = Lazy::new(|| {
+    create_udf(
+        GEOIP_ASN_UDF_NAME,
+        vec![DataType::Utf8],
+        DataType::Int64,
+        Volatility::Stable,
+        Arc::new(geoip_asn_impl),
+    )
+});
+
+/// Implementation of geo_distance
+pub static GEO_DISTANCE_UDF: Lazy<ScalarUDF> = Lazy::new(|| {
+    create_udf(
+        GEO_DISTANCE_UDF_NAME,
+        // lat1, lon1, lat2, lon2 in degrees
+        vec![DataType::Float64; 4],
+        DataType::Float64,
+        Volatility::Immutable,
+        Arc::new(geo_distance_impl),
+    )
+});
+
+/// Implementation of geohash
+pub static GEOHASH_UDF: Lazy<ScalarUDF> = Lazy::new(|| {
+    create_udf(
+        GEOHASH_UDF_NAME,
+        vec![DataType::Float64, DataType::Float64, DataType::Int64],
+        DataType::Utf8,
+        Volatility::Immutable,
+        Arc::new(geohash_impl),
+    )
+});
+
+// the enterprise database has both the city and the asn fields
+fn geoip_table(name: &str) -> Option<Box<dyn Table + Send + Sync>> {
+    #[cfg(feature = "enterprise")]
+    if o2_enterprise::enterprise::common::config::get_config()
+        .common
+        .enable_enterprise_mmdb
+        && let Some(table) = transform::get_global_enrichment_table(
+            o2_enterprise::enterprise::common::config::GEO_IP_ENTERPRISE_ENRICHMENT_TABLE,
+        )
+    {
+        return Some(table);
+    }
+    transform::get_global_enrichment_table(name)
+}
+
+fn geoip_lookup(table: &dyn Table, ip: &str, field: &str) -> Option<Value> {
+    let ip = ip.trim();
+    if ip.is_empty() {
+        return None;
+    }
+    let condition = [Condition::Equals {
+        field: "ip",
+        value: Value::from(ip),
+    }];
+    let mut row = table
+        .find_table_row(
+            Case::Sensitive,
+            &condition,
+            Some(&[field.to_string()]),
+            None,
+            None,
+        )
+        .ok()?;
+    match row.remove(field)? {
+        Value::Null => None,
+        v => Some(v),
+    }
+}
+
+fn geoip_string_impl(
+    args: &[ColumnarValue],
+    usage: &str,
+    table: &str,
+    field: &str,
+) -> datafusion::error::Result<ColumnarValue> {
+    if args.len() != 1 {
+        return Err(params_error(usage));
+    }
+    let args = ColumnarValue::values_to_arrays(args)?;
+    let ips = as_string_array(&args[0])?;
+    let array = match geoip_table(table) {
+        Some(table) => ips
+            .iter()
+            .map(|ip| {
+                geoip_lookup(table.as_ref(), ip?, field).map(|v| v.to_string_lossy().into_owned())
+            })
+            .collect::<StringArray>(),
+        // no database is loaded
+        None => StringArray::new_null(ips.len()),
+    };
+    Ok(ColumnarValue::from(Arc::new(array) as ArrayRef))
+}
+
+/// geoip_country(ip) returns the country name of the address in the loaded
+/// city database, null when the address is unknown.
+pub fn geoip_country_impl(args: &[ColumnarValue]) -> datafusion::error::Result<ColumnarValue> {
+    geoip_string_impl(
+        args,
+        "geoip_country(ip_field)",
+        GEO_IP_CITY_ENRICHMENT_TABLE,
+        "country_name",
+    )
+}
+
+/// geoip_city(ip) returns the city name of the address in the loaded city
+/// database, null when the address is unknown.
+pub fn geoip_city_impl(args: &[ColumnarValue]) -> datafusion::error::Result<ColumnarValue> {
+    geoip_string_impl(
+        args,
+        "geoip_city(ip_field)",
+        GEO_IP_CITY_ENRICHMENT_TABLE,
+        "city_name",
+    )
+}
+
+/// geoip_asn(ip) returns the autonomous system number of the address in the
+/// loaded asn database, null when the address is unknown.
+pub fn geoip_asn_impl(args: &[ColumnarValue]) -> datafusion::error::Result<ColumnarValue> {
+    if args.len() != 1 {
+        return Err(params_error("geoip_asn(ip_field)"));
+    }
+    let args = ColumnarValue::values_to_arrays(args)?;
+    let ips = as_string_array(&args[0])?;
+    let array = match geoip_table(GEO_IP_ASN_ENRICHMENT_TABLE) {
+        Some(table) => ips
+            .iter()
+            .map(
+                |ip| match geoip_lookup(table.as_ref(), ip?, "autonomous_system_number")? {
+                    Value::Integer(v) => Some(v),
+                    _ => None,
+                },
+            )
+            .collect::<Int64Array>(),
+        None => Int64Array::new_null(ips.len()),
+    };
+    Ok(ColumnarValue::from(Arc::new(array) as ArrayRef))
+}
+
+/// The great-circle distance in kilometers between two points.
+pub fn haversine_distance(lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> Option<f64> {
+    if !valid_coordinate(lat1, lon1) || !valid_coordinate(lat2, lon2) {
+        return None;
+    }
+    let (lat1, lat2) = (lat1.to_radians(), lat2.to_radians());
+    let d_lat = lat2 - lat1;
+    let d_lon = (lon2 - lon1).to_radians();
+    let a = (d_lat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (d_lon / 2.0).sin().powi(2);
+    Some(2.0 * EARTH_RADIUS_KM * a.sqrt().min(1.0).asin())
+}
+
+fn valid_coordinate(lat: f64, lon: f64) -> bool {
+    (-90.0..=90.0).contains(&lat) && (-180.0..=180.0).contains(&lon)
+}
+
+/// geo_distance(lat1, lon1, lat2, lon2) returns the distance in kilometers,
+/// null when a coordinate is out of range.
+pub fn geo_distance_impl(args: &[ColumnarValue]) -> datafusion::error::Result<ColumnarValue> {
+    if args.len() != 4 {
+        return Err(params_error("geo_distance(lat1, lon1, lat2, lon2)"));
+    }
+    let args = ColumnarValue::values_to_arrays(args)?;
+    let lat1 = as_float64_array(&args[0])?;
+    let lon1 = as_float64_array(&args[1])?;
+    let lat2 = as_float64_array(&args[2])?;
+    let lon2 = as_float64_array(&args[3])?;
+    let array = (0..lat1.len())
+        .map(|i| {
+            if lat1.is_null(i) || lon1.is_null(i) || lat2.is_null(i) || lon2.is_null(i) {
+                return None;
+            }
+            haversine_distance(lat1.value(i), lon1.value(i), lat2.value(i), lon2.value(i))
+        })
+        .collect::<Float64Array>();
+    Ok(ColumnarValue::from(Arc::new(array) as ArrayRef))
+}
+
+/// Encodes the point as a geohash of `precision` characters.
+pub fn encode_geohash(lat: f64, lon: f64, precision: usize) -> Option<String> {
+    if !valid_coordinate(lat, lon) || precision == 0 {
+        return None;
+    }
+    let (mut lat_range, mut lon_range) = ((-90.0, 90.0), (-180.0, 180.0));
+    let mut hash = String::with_capacity(precision);
+    let mut even = true;
+    let (mut bits, mut ch) = (0, 0usize);
+    while hash.len() < precision {
+        // even bits refine the longitude, odd bits the latitude
+        let (range, v): (&mut (f64, f64), f64) = if even {
+            (&mut lon_range, lon)
+        } else {
+            (&mut lat_range, lat)
+        };
+        let mid = (range.0 + range.1) / 2.0;
+        ch <<= 1;
+        if v >= mid {
+            ch |= 1;
+            range.0 = mid;
+        } else {
+            range.1 = mid;
+        }
+        even = !even;
+        bits += 1;
+        if bits == 5 {
+            hash.push(GEOHASH_BASE32[ch] as char);
+            bits = 0;
+            ch = 0;
+        }
+    }
+    Some(hash)
+}
+
+/// geohash(lat, lon, precision) returns the geohash of the point, the
+/// precision is clamped to 1..=12 characters.
+pub fn geohash_impl(args: &[ColumnarValue]) -> datafusion::error::Result<ColumnarValue> {
+    if args.len() != 3 {
+        return Err(params_error("geohash(lat, lon, precision)"));
+    }
+    let args = ColumnarValue::values_to_arrays(args)?;
+    let lat = as_float64_array(&args[0])?;
+    let lon = as_float64_array(&args[1])?;
+    let precision = as_int64_array(&args[2])?;
+    let array = (0..lat.len())
+        .map(|i| {
+            if lat.is_null(i) || lon.is_null(i) || precision.is_null(i) {
+                return None;
+            }
+            let precision = precision.value(i).clamp(1, GEOHASH_MAX_PRECISION) as usize;
+            encode_geohash(lat.value(i), lon.value(i), precision)
+        })
+        .collect::<StringArray>();
+    Ok(ColumnarValue::from(Arc::new(array) as ArrayRef))
+}
+
+#[cfg(test)]
+mod tests {
+    use datafusion::{
+        arrow::{
+            datatypes::{Field, Schema},
+            record_batch::RecordBatch,
+        },
+        assert_batches_eq,
+        datasource::MemTable,
+        prelude::SessionContext,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_haversine_distance() {
+        // Paris -> London
+        let d = haversine_distance(48.8566, 2.3522, 51.5074, -0.1278).unwrap();
+        assert!((d - 343.5).abs() < 1.0, "{d}");
+        assert_eq!(haversine_distance(10.0, 10.0, 10.0, 10.0), Some(0.0));
+        assert!(haversine_distance(91.0, 0.0, 0.0, 0.0).is_none());
+    }
+
+    #[test]
+    fn test_encode_geohash() {
+        assert_eq!(
+            encode_geohash(57.64911, 10.40744, 11).as_deref(),
+            Some("u4pruydqqvj")
+        );
+        assert_eq!(encode_geohash(42.6, -5.6, 5).as_deref(), Some("ezs42"));
+        assert!(encode_geohash(0.0, 181.0, 5).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_geo_udfs_sql() {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("lat", DataType::Float64, true),
+            Field::new("lon", DataType::Float64, true),
+            Field::new("ip", DataType::Utf8, true),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(Float64Array::from(vec![Some(42.6), None])),
+                Arc::new(Float64Array::from(vec![Some(-5.6), Some(1.0)])),
+                Arc::new(StringArray::from(vec![Some("8.8.8.8"), None])),
+            ],
+        )
+        .unwrap();
+
+        let ctx = SessionContext::new();
+        ctx.register_udf(GEOHASH_UDF.clone());
+        ctx.register_udf(GEO_DISTANCE_UDF.clone());
+        ctx.register_udf(GEOIP_COUNTRY_UDF.clone());
+        let provider = MemTable::try_new(schema, vec![vec![batch]]).unwrap();
+        ctx.register_table("t", Arc::new(provider)).unwrap();
+
+        let df = ctx
+            .sql(
+                "SELECT geohash(lat, lon, 5) AS h, geo_distance(lat, lon, lat, lon) AS d, \
+                 geoip_country(ip) AS c FROM t",
+            )
+            .await
+            .unwrap();
+        let data = df.collect().await.unwrap();
+        // no database is loaded in the tests
+        let expected = [
+            "+-------+-----+---+",
+            "| h     | d   | c |",
+            "+-------+-----+---+",
+            "| ezs42 | 0.0 |   |",
+            "|       |     |   |",
+            "+-------+-----+---+",
+        ];
+        assert_batches_eq!(expected, &data);
+    }
+}
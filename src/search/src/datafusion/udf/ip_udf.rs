@@ -0,0 +1,548 @@
+// Copyright 2026 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::{
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+    sync::{Arc, LazyLock as Lazy},
+};
+
+use datafusion::{
+    arrow::{
+        array::{ArrayRef, BooleanArray, StringArray, UInt8Array, UInt64Array},
+        datatypes::DataType,
+    },
+    common::cast::as_string_array,
+    error::DataFusionError,
+    logical_expr::{ColumnarValue, ScalarUDF, Volatility},
+    prelude::create_udf,
+    scalar::ScalarValue,
+    sql::sqlparser::parser::ParserError,
+};
+
+/// The name of the ip_in_cidr UDF given to DataFusion.
+pub const IP_IN_CIDR_UDF_NAME: &str = "ip_in_cidr";
+/// The name of the cidr_contains UDF given to DataFusion.
+pub const CIDR_CONTAINS_UDF_NAME: &str = "cidr_contains";
+/// The name of the ip_to_int UDF given to DataFusion.
+pub const IP_TO_INT_UDF_NAME: &str = "ip_to_int";
+/// The name of the ip_version UDF given to DataFusion.
+pub const IP_VERSION_UDF_NAME: &str = "ip_version";
+/// The name of the ip_is_private UDF given to DataFusion.
+pub const IP_IS_PRIVATE_UDF_NAME: &str = "ip_is_private";
+/// The name of the ipv6 UDF given to DataFusion.
+pub const IPV6_UDF_NAME: &str = "ipv6";
+
+/// The octet alternatives matching any value from 0 to 255 without leading zeros.
+const ANY_OCTET_REGEX: &str = "(25[0-5]|2[0-4][0-9]|1[0-9][0-9]|[1-9]?[0-9])";
+
+/// An IPv4 or IPv6 network, a bare address is a network of one host.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cidr {
+    addr: IpAddr,
+    prefix: u8,
+}
+
+impl Cidr {
+    pub fn parse(s: &str) -> Option<Self> {
+        let s = s.trim();
+        let (addr, prefix) = match s.split_once('/') {
+            Some((addr, prefix)) => (addr.parse::<IpAddr>().ok()?, Some(prefix.parse().ok()?)),
+            None => (s.parse::<IpAddr>().ok()?, None),
+        };
+        let max_prefix = if addr.is_ipv4() { 32 } else { 128 };
+        let prefix = prefix.unwrap_or(max_prefix);
+        if prefix > max_prefix {
+            return None;
+        }
+        // keep the network address only, 10.1.2.3/8 is 10.0.0.0/8
+        let addr = match addr {
+            IpAddr::V4(v4) => IpAddr::V4(Ipv4Addr::from(u32::from(v4) & v4_mask(prefix))),
+            IpAddr::V6(v6) => IpAddr::V6(Ipv6Addr::from(u128::from(v6) & v6_mask(prefix))),
+        };
+        Some(Self { addr, prefix })
+    }
+
+    pub fn contains(&self, ip: &IpAddr) -> bool {
+        match (self.addr, ip) {
+            (IpAddr::V4(net), IpAddr::V4(ip)) => {
+                u32::from(*ip) & v4_mask(self.prefix) == u32::from(net)
+            }
+            (IpAddr::V6(net), IpAddr::V6(ip)) => {
+                u128::from(*ip) & v6_mask(self.prefix) == u128::from(net)
+            }
+            _ => false,
+        }
+    }
+
+    pub fn contains_cidr(&self, other: &Cidr) -> bool {
+        self.prefix <= other.prefix && self.contains(&other.addr)
+    }
+
+    /// The regex matching the canonical text of every IPv4 address in the
+    /// network, used to look up a CIDR filter in the inverted index. Like
+    /// `ip_in_cidr()` it accepts surrounding whitespace.
+    pub fn ipv4_regex(&self) -> Option<String> {
+        let IpAddr::V4(net) = self.addr else {
+            return None;
+        };
+        let octets = net.octets().map(|octet| octet as u16);
+        let mut parts = Vec::with_capacity(4);
+        for (i, octet) in octets.into_iter().enumerate() {
+            let fixed_bits = self.prefix.saturating_sub(i as u8 * 8).min(8);
+            let part = match fixed_bits {
+                8 => octet.to_string(),
+                0 => ANY_OCTET_REGEX.to_string(),
+                bits => {
+                    let values = (octet..octet + (1 << (8 - bits)))
+                        .map(|v| v.to_string())
+                        .collect::<Vec<_>>();
+                    format!("({})", values.join("|"))
+                }
+            };
+            parts.push(part);
+        }
+        Some(format!("\\s*{}\\s*", parts.join("\\.")))
+    }
+
+    /// Every IPv4 address of the network, `None` for IPv6 or when the
+    /// network has more than `limit` addresses.
+    pub fn ipv4_hosts(&self, limit: usize) -> Option<Vec<String>> {
+        let IpAddr::V4(net) = self.addr else {
+            return None;
+        };
+        let size = 1u64 << (32 - self.prefix);
+        if size > limit as u64 {
+            return None;
+        }
+        let start = u32::from(net);
+        Some(
+            (0..size as u32)
+                .map(|i| Ipv4Addr::from(start + i).to_string())
+                .collect(),
+        )
+    }
+}
+
+fn v4_mask(prefix: u8) -> u32 {
+    u32::MAX.checked_shl(32 - prefix as u32).unwrap_or(0)
+}
+
+fn v6_mask(prefix: u8) -> u128 {
+    u128::MAX.checked_shl(128 - prefix as u32).unwrap_or(0)
+}
+
+fn parse_ip(s: &str) -> Option<IpAddr> {
+    s.trim().parse().ok()
+}
+
+/// Private, loopback and link local addresses.
+pub fn is_private_ip(ip: &IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => v4.is_private() || v4.is_loopback() || v4.is_link_local(),
+        IpAddr::V6(v6) => match v6.to_ipv4_mapped() {
+            Some(v4) => is_private_ip(&IpAddr::V4(v4)),
+            None => v6.is_loopback() || v6.is_unique_local() || v6.is_unicast_link_local(),
+        },
+    }
+}
+
+fn params_error(usage: &str) -> DataFusionError {
+    DataFusionError::SQL(
+        Box::new(ParserError::ParserError(format!(
+            "UDF params should be: {usage}"
+        ))),
+        None,
+    )
+}
+
+/// Implementation of ip_in_cidr
+pub static IP_IN_CIDR_UDF: Lazy<ScalarUDF> = Lazy::new(|| {
+    create_udf(
+        IP_IN_CIDR_UDF_NAME,
+        // expects two string - the ip and the cidr
+        vec![DataType::Utf8, DataType::Utf8],
+        // returns boolean
+        DataType::Boolean,
+        Volatility::Immutable,
+        Arc::new(ip_in_cidr_impl),
+    )
+});
+
+/// Implementation of cidr_contains
+pub static CIDR_CONTAINS_UDF: Lazy<ScalarUDF> = Lazy::new(|| {
+    create_udf(
+        CIDR_CONTAINS_UDF_NAME,
+        // expects two string - the network and the ip or network
+        vec![DataType::Utf8, DataType::Utf8],
+        // returns boolean
+        DataType::Boolean,
+        Volatility::Immutable,
+        Arc::new(cidr_contains_impl),
+    )
+});
+
+/// Implementation of ip_to_int
+pub static IP_TO_INT_UDF: Lazy<ScalarUDF> = Lazy::new(|| {
+    create_udf(
+        IP_TO_INT_UDF_NAME,
+        vec![DataType::Utf8],
+        DataType::UInt64,
+        Volatility::Immutable,
+        Arc::new(ip_to_int_impl),
+    )
+});
+
+/// Implementation of ip_version
+pub static IP_VERSION_UDF: Lazy<ScalarUDF> = Lazy::new(|| {
+    create_udf(
+        IP_VERSION_UDF_NAME,
+        vec![DataType::Utf8],
+        DataType::UInt8,
+        Volatility::Immutable,
+        Arc::new(ip_version_impl),
+    )
+});
+
+/// Implementation of ip_is_private
+pub static IP_IS_PRIVATE_UDF: Lazy<ScalarUDF> = Lazy::new(|| {
+    create_udf(
+        IP_IS_PRIVATE_UDF_NAME,
+        vec![DataType::Utf8],
+        DataType::Boolean,
+        Volatility::Immutable,
+        Arc::new(ip_is_private_impl),
+    )
+});
+
+/// Implementation of ipv6
+pub static IPV6_UDF: Lazy<ScalarUDF> = Lazy::new(|| {
+    create_udf(
+        IPV6_UDF_NAME,
+        vec![DataType::Utf8],
+        DataType::Utf8,
+        Volatility::Immutable,
+        Arc::new(ipv6_impl),
+    )
+});
+
+/// ip_in_cidr(ip, cidr) returns true when the ip is in the network, an ip
+/// never matches a network of the other address family.
+pub fn ip_in_cidr_impl(args: &[ColumnarValue]) -> datafusion::error::Result<ColumnarValue> {
+    if args.len() != 2 {
+        return Err(params_error("ip_in_cidr(ip_field, 'cidr')"));
+    }
+    // the network is usually a literal, parse it once
+    if let ColumnarValue::Scalar(cidr) = &args[1] {
+        let cidr = match cidr {
+            ScalarValue::Utf8(v) | ScalarValue::Utf8View(v) | ScalarValue::LargeUtf8(v) => {
+                v.as_deref().and_then(Cidr::parse)
+            }
+            _ => None,
+        };
+        let ips = ColumnarValue::values_to_arrays(&args[..1])?;
+        let ips = as_string_array(&ips[0])?;
+        let array = ips
+            .iter()
+            .map(|ip| {
+                let cidr = cidr?;
+                Some(ip.and_then(parse_ip).is_some_and(|ip| cidr.contains(&ip)))
+            })
+            .collect::<BooleanArray>();
+        return Ok(ColumnarValue::from(Arc::new(array) as ArrayRef));
+    }
+
+    let args = ColumnarValue::values_to_arrays(args)?;
+    let ips = as_string_array(&args[0])?;
+    let cidrs = as_string_array(&args[1])?;
+    let array = ips
+        .iter()
+        .zip(cidrs.iter())
+        .map(|(ip, cidr)| {
+            let cidr = cidr.and_then(Cidr::parse)?;
+            Some(ip.and_then(parse_ip).is_some_and(|ip| cidr.contains(&ip)))
+        })
+        .collect::<BooleanArray>();
+    Ok(ColumnarValue::from(Arc::new(array) as ArrayRef))
+}
+
+/// cidr_contains(cidr, ip_or_cidr) returns true when the first network
+/// contains the address or the whole second network.
+pub fn cidr_contains_impl(args: &[ColumnarValue]) -> datafusion::error::Result<ColumnarValue> {
+    if args.len() != 2 {
+        return Err(params_error("cidr_contains('cidr', ip_or_cidr)"));
+    }
+    let args = ColumnarValue::values_to_arrays(args)?;
+    let networks = as_string_array(&args[0])?;
+    let others = as_string_array(&args[1])?;
+    let array = networks
+        .iter()
+        .zip(others.iter())
+        .map(|(network, other)| {
+            let network = network.and_then(Cidr::parse)?;
+            Some(
+                other
+                    .and_then(Cidr::parse)
+                    .is_some_and(|other| network.contains_cidr(&other)),
+            )
+        })
+        .collect::<BooleanArray>();
+    Ok(ColumnarValue::from(Arc::new(array) as ArrayRef))
+}
+
+/// ip_to_int(ip) returns the IPv4 address as a number, IPv4-mapped IPv6
+/// addresses are converted and other IPv6 addresses return null.
+pub fn ip_to_int_impl(args: &[ColumnarValue]) -> datafusion::error::Result<ColumnarValue> {
+    if args.len() != 1 {
+        return Err(params_error("ip_to_int(ip_field)"));
+    }
+    let args = ColumnarValue::values_to_arrays(args)?;
+    let ips = as_string_array(&args[0])?;
+    let array = ips
+        .iter()
+        .map(|ip| match ip.and_then(parse_ip)? {
+            IpAddr::V4(v4) => Some(u32::from(v4) as u64),
+            IpAddr::V6(v6) => v6.to_ipv4_mapped().map(|v4| u32::from(v4) as u64),
+        })
+        .collect::<UInt64Array>();
+    Ok(ColumnarValue::from(Arc::new(array) as ArrayRef))
+}
+
+/// ip_version(ip) returns 4 or 6, null when the value is not an ip.
+pub fn ip_version_impl(args: &[ColumnarValue]) -> datafusion::error::Result<ColumnarValue> {
+    if args.len() != 1 {
+        return Err(params_error("ip_version(ip_field)"));
+    }
+    let args = ColumnarValue::values_to_arrays(args)?;
+    let ips = as_string_array(&args[0])?;
+    let array = ips
+        .iter()
+        .map(|ip| match ip.and_then(parse_ip)? {
+            IpAddr::V4(_) => Some(4),
+            IpAddr::V6(_) => Some(6),
+        })
+        .collect::<UInt8Array>();
+    Ok(ColumnarValue::from(Arc::new(array) as ArrayRef))
+}
+
+/// ip_is_private(ip) returns true for private, loopback and link local
+/// addresses, null when the value is not an ip.
+pub fn ip_is_private_impl(args: &[ColumnarValue]) -> datafusion::error::Result<ColumnarValue> {
+    if args.len() != 1 {
+        return Err(params_error("ip_is_private(ip_field)"));
+    }
+    let args = ColumnarValue::values_to_arrays(args)?;
+    let ips = as_string_array(&args[0])?;
+    let array = ips
+        .iter()
+        .map(|ip| ip.and_then(parse_ip).map(|ip| is_private_ip(&ip)))
+        .collect::<BooleanArray>();
+    Ok(ColumnarValue::from(Arc::new(array) as ArrayRef))
+}
+
+/// ipv6(ip) returns the canonical IPv6 text of the address, IPv4 addresses
+/// are mapped to `::ffff:a.b.c.d`, null when the value is not an ip.
+pub fn ipv6_impl(args: &[ColumnarValue]) -> datafusion::error::Result<ColumnarValue> {
+    if args.len() != 1 {
+        return Err(params_error("ipv6(ip_field)"));
+    }
+    let args = ColumnarValue::values_to_arrays(args)?;
+    let ips = as_string_array(&args[0])?;
+    let array = ips
+        .iter()
+        .map(|ip| {
+            let v6 = match ip.and_then(parse_ip)? {
+                IpAddr::V4(v4) => v4.to_ipv6_mapped(),
+                IpAddr::V6(v6) => v6,
+            };
+            Some(v6.to_string())
+        })
+        .collect::<StringArray>();
+    Ok(ColumnarValue::from(Arc::new(array) as ArrayRef))
+}
+
+#[cfg(test)]
+mod tests {
+    use datafusion::{
+        arrow::{
+            datatypes::{Field, Schema},
+            record_batch::RecordBatch,
+        },
+        assert_batches_eq,
+        datasource::MemTable,
+        prelude::SessionContext,
+    };
+    use regex::Regex;
+
+    use super::*;
+
+    #[test]
+    fn test_cidr_parse_and_contains() {
+        let cidr = Cidr::parse("10.1.2.3/8").unwrap();
+        assert_eq!(cidr, Cidr::parse("10.0.0.0/8").unwrap());
+        assert!(cidr.contains(&"10.255.0.1".parse().unwrap()));
+        assert!(!cidr.contains(&"11.0.0.1".parse().unwrap()));
+        assert!(!cidr.contains(&"::ffff:10.0.0.1".parse().unwrap()));
+        assert!(
+            Cidr::parse("0.0.0.0/0")
+                .unwrap()
+                .contains(&"8.8.8.8".parse().unwrap())
+        );
+
+        let cidr = Cidr::parse("2001:db8::/32").unwrap();
+        assert!(cidr.contains(&"2001:db8:ffff::1".parse().unwrap()));
+        assert!(!cidr.contains(&"2001:db9::1".parse().unwrap()));
+
+        assert!(Cidr::parse("10.0.0.0/33").is_none());
+        assert!(Cidr::parse("10.0.0/8").is_none());
+        assert!(Cidr::parse("10.0.0.0/").is_none());
+
+        let network = Cidr::parse("10.0.0.0/8").unwrap();
+        assert!(network.contains_cidr(&Cidr::parse("10.1.0.0/16").unwrap()));
+        assert!(!network.contains_cidr(&Cidr::parse("10.0.0.0/7").unwrap()));
+    }
+
+    #[test]
+    fn test_cidr_ipv4_regex() {
+        for cidr in [
+            "10.0.0.0/8",
+            "172.16.0.0/12",
+            "192.168.1.0/24",
+            "1.2.3.4/30",
+            "0.0.0.0/0",
+        ] {
+            let network = Cidr::parse(cidr).unwrap();
+            let re = Regex::new(&format!("^{}$", network.ipv4_regex().unwrap())).unwrap();
+            for ip in [
+                "10.0.0.1",
+                "10.255.255.255",
+                "11.0.0.0",
+                "172.15.255.255",
+                "172.16.0.0",
+                "172.31.9.9",
+                "172.32.0.0",
+                "192.168.1.77",
+                "192.168.2.1",
+                "1.2.3.3",
+                "1.2.3.4",
+                "1.2.3.7",
+                "1.2.3.8",
+                "8.8.8.8",
+            ] {
+                let expected = network.contains(&ip.parse().unwrap());
+                assert_eq!(re.is_match(ip), expected, "{ip} in {cidr}");
+                assert_eq!(re.is_match(&format!(" {ip}\t")), expected, "{ip} in {cidr}");
+            }
+            assert!(!re.is_match("10.0.0.01"));
+        }
+        assert!(Cidr::parse("::1/128").unwrap().ipv4_regex().is_none());
+    }
+
+    #[test]
+    fn test_cidr_ipv4_hosts() {
+        let hosts = Cidr::parse("10.0.0.4/30").unwrap().ipv4_hosts(256).unwrap();
+        assert_eq!(hosts, vec!["10.0.0.4", "10.0.0.5", "10.0.0.6", "10.0.0.7"]);
+        assert_eq!(
+            Cidr::parse("10.0.0.1").unwrap().ipv4_hosts(256).unwrap(),
+            vec!["10.0.0.1"]
+        );
+        assert!(
+            Cidr::parse("10.0.0.0/16")
+                .unwrap()
+                .ipv4_hosts(256)
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_is_private_ip() {
+        for ip in [
+            "10.1.1.1",
+            "172.16.0.1",
+            "192.168.0.1",
+            "127.0.0.1",
+            "169.254.1.1",
+        ] {
+            assert!(is_private_ip(&ip.parse().unwrap()), "{ip}");
+        }
+        for ip in ["fd00::1", "::1", "fe80::1", "::ffff:192.168.0.1"] {
+            assert!(is_private_ip(&ip.parse().unwrap()), "{ip}");
+        }
+        for ip in ["8.8.8.8", "172.32.0.1", "2001:4860:4860::8888"] {
+            assert!(!is_private_ip(&ip.parse().unwrap()), "{ip}");
+        }
+    }
+
+    #[test]
+    fn test_ip_udf_wrong_arg_count_errors() {
+        assert!(ip_in_cidr_impl(&[]).is_err());
+        assert!(cidr_contains_impl(&[]).is_err());
+        assert!(ip_to_int_impl(&[]).is_err());
+        assert!(ip_version_impl(&[]).is_err());
+        assert!(ip_is_private_impl(&[]).is_err());
+        assert!(ipv6_impl(&[]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_ip_udfs() {
+        let schema = Arc::new(Schema::new(vec![Field::new("ip", DataType::Utf8, true)]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![Arc::new(StringArray::from(vec![
+                Some("10.0.0.1"),
+                Some("8.8.8.8"),
+                Some("2001:DB8:0:0::1"),
+                Some("not-an-ip"),
+                None,
+            ]))],
+        )
+        .unwrap();
+
+        let ctx = SessionContext::new();
+        for udf in [
+            &IP_IN_CIDR_UDF,
+            &CIDR_CONTAINS_UDF,
+            &IP_TO_INT_UDF,
+            &IP_VERSION_UDF,
+            &IP_IS_PRIVATE_UDF,
+            &IPV6_UDF,
+        ] {
+            ctx.register_udf((*udf).clone());
+        }
+        let provider = MemTable::try_new(schema, vec![vec![batch]]).unwrap();
+        ctx.register_table("t", Arc::new(provider)).unwrap();
+
+        let sql = "select ip, ip_in_cidr(ip, '10.0.0.0/8') as in_net, \
+                   cidr_contains('2001:db8::/32', ip) as contains, ip_to_int(ip) as num, \
+                   ip_version(ip) as ver, ip_is_private(ip) as private, ipv6(ip) as v6 from t";
+        let batches = ctx.sql(sql).await.unwrap().collect().await.unwrap();
+        let expected = [
+            "+-----------------+--------+----------+-----------+-----+---------+------------------+",
+            "| ip              | in_net | contains | num       | ver | private | v6               |",
+            "+-----------------+--------+----------+-----------+-----+---------+------------------+",
+            "| 10.0.0.1        | true   | false    | 167772161 | 4   | true    | ::ffff:10.0.0.1  |",
+            "| 8.8.8.8         | false  | false    | 134744072 | 4   | false   | ::ffff:8.8.8.8   |",
+            "| 2001:DB8:0:0::1 | false  | true     |           | 6   | false   | 2001:db8::1      |",
+            "| not-an-ip       | false  | false    |           |     |         |                  |",
+            "|                 | false  | false    |           |     |         |                  |",
+            "+-----------------+--------+----------+-----------+-----+---------+------------------+",
+        ];
+        assert_batches_eq!(expected, &batches);
+
+        let sql = "select count(*) as cnt from t where ip_in_cidr(ip, ip)";
+        let batches = ctx.sql(sql).await.unwrap().collect().await.unwrap();
+        let expected = ["+-----+", "| cnt |", "+-----+", "| 3   |", "+-----+"];
+        assert_batches_eq!(expected, &batches);
+    }
+}
@@ -0,0 +1,227 @@
+// Copyright 2026 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::{
+    iter::zip,
+    sync::{Arc, LazyLock as Lazy},
+};
+
+use config::tantivy::tokenizer::o2_collect_phrase_tokens;
+use datafusion::{
+    arrow::{
+        array::{ArrayRef, BooleanArray},
+        datatypes::DataType,
+    },
+    common::cast::{as_int64_array, as_string_array},
+    error::DataFusionError,
+    logical_expr::{ColumnarValue, ScalarFunctionImplementation, ScalarUDF, Volatility},
+    prelude::create_udf,
+    sql::sqlparser::parser::ParserError,
+};
+
+/// Implementation of match_phrase
+pub static MATCH_PHRASE_UDF: Lazy<ScalarUDF> = Lazy::new(|| {
+    create_udf(
+        super::MATCH_PHRASE_UDF_NAME,
+        // expects field, phrase and slop
+        vec![DataType::Utf8, DataType::Utf8, DataType::Int64],
+        // returns boolean
+        DataType::Boolean,
+        Volatility::Stable,
+        match_phrase_expr_impl(),
+    )
+});
+
+/// match_phrase function for datafusion
+pub fn match_phrase_expr_impl() -> ScalarFunctionImplementation {
+    Arc::new(move |args: &[ColumnarValue]| {
+        if args.len() != 3 {
+            return Err(DataFusionError::SQL(
+                Box::new(ParserError::ParserError(
+                    "match_phrase UDF expects a field, a phrase and a slop".to_string(),
+                )),
+                None,
+            ));
+        }
+        let args = ColumnarValue::values_to_arrays(args)?;
+
+        let haystack = as_string_array(&args[0])?;
+        let phrase = as_string_array(&args[1])?;
+        let slop = as_int64_array(&args[2])?;
+
+        // the phrase is a literal in practice, only tokenize it again when it changes
+        let mut phrase_tokens: Option<(&str, Vec<(usize, String)>)> = None;
+        let array = zip(haystack.iter(), zip(phrase.iter(), slop.iter()))
+            .map(
+                |(haystack, (phrase, slop))| match (haystack, phrase, slop) {
+                    (Some(haystack), Some(phrase), Some(slop)) => {
+                        if phrase_tokens.as_ref().is_none_or(|(p, _)| *p != phrase) {
+                            phrase_tokens = Some((phrase, o2_collect_phrase_tokens(phrase)));
+                        }
+                        let (_, tokens) = phrase_tokens.as_ref().unwrap();
+                        let slop = slop.max(0) as usize;
+                        Some(phrase_match(
+                            &o2_collect_phrase_tokens(haystack),
+                            tokens,
+                            slop,
+                        ))
+                    }
+                    _ => None,
+                },
+            )
+            .collect::<BooleanArray>();
+
+        Ok(ColumnarValue::from(Arc::new(array) as ArrayRef))
+    })
+}
+
+/// Check if `phrase` occurs in `tokens` in order, allowing at most `slop` extra
+/// positions in total between its tokens. Both sides are `(position, token)`
+/// lists as produced by the ingestion tokenizer.
+pub fn phrase_match(tokens: &[(usize, String)], phrase: &[(usize, String)], slop: usize) -> bool {
+    let Some((first_offset, first)) = phrase.first() else {
+        return false;
+    };
+    tokens
+        .iter()
+        .enumerate()
+        .filter(|(_, (_, text))| text == first)
+        .any(|(start, (start_pos, _))| {
+            // greedily take the earliest position for every following token,
+            // which keeps the total gap as small as possible
+            let mut prev_pos = *start_pos;
+            let mut prev_offset = *first_offset;
+            let mut idx = start + 1;
+            for (offset, text) in phrase.iter().skip(1) {
+                let min_pos = prev_pos + (offset - prev_offset);
+                let Some(found) = tokens[idx..]
+                    .iter()
+                    .position(|(pos, t)| *pos >= min_pos && t == text)
+                else {
+                    return false;
+                };
+                prev_pos = tokens[idx + found].0;
+                prev_offset = *offset;
+                idx += found + 1;
+            }
+            let span = prev_pos - start_pos;
+            let phrase_span = prev_offset - first_offset;
+            span - phrase_span <= slop
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow::array::{Int64Array, StringArray};
+    use datafusion::{
+        arrow::{
+            datatypes::{Field, Schema},
+            record_batch::RecordBatch,
+        },
+        datasource::MemTable,
+        prelude::SessionContext,
+    };
+
+    use super::*;
+
+    fn matches(haystack: &str, phrase: &str, slop: usize) -> bool {
+        phrase_match(
+            &o2_collect_phrase_tokens(haystack),
+            &o2_collect_phrase_tokens(phrase),
+            slop,
+        )
+    }
+
+    #[test]
+    fn test_phrase_match_exact() {
+        assert!(matches(
+            "read failed: Connection reset by peer",
+            "connection reset by peer",
+            0
+        ));
+        assert!(!matches("peer reset by connection", "connection reset", 0));
+        assert!(!matches("connection was reset", "connection reset", 0));
+    }
+
+    #[test]
+    fn test_phrase_match_slop() {
+        assert!(matches("connection was reset", "connection reset", 1));
+        assert!(!matches("connection was then reset", "connection reset", 1));
+        assert!(matches("connection was then reset", "connection reset", 2));
+        // slop does not allow reordering
+        assert!(!matches("reset connection", "connection reset", 2));
+    }
+
+    #[test]
+    fn test_phrase_match_camel_case() {
+        assert!(matches(
+            "got NullPointerException here",
+            "NullPointerException here",
+            0
+        ));
+        assert!(matches(
+            "got NullPointerException here",
+            "exception here",
+            0
+        ));
+    }
+
+    #[test]
+    fn test_phrase_match_empty_phrase() {
+        assert!(!matches("connection reset", "", 0));
+    }
+
+    #[test]
+    fn test_match_phrase_impl_wrong_arg_count_errors() {
+        let f = match_phrase_expr_impl();
+        let haystack = StringArray::from(vec!["hello"]);
+        let args = [ColumnarValue::Array(Arc::new(haystack))];
+        assert!(f(&args).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_match_phrase_udf() {
+        let sql = "select * from t where match_phrase(log, 'connection reset', 1)";
+
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int64, false),
+            Field::new("log", DataType::Utf8, false),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(Int64Array::from(vec![1, 2, 3, 4])),
+                Arc::new(StringArray::from(vec![
+                    "connection reset by peer",
+                    "connection was reset",
+                    "reset connection",
+                    "",
+                ])),
+            ],
+        )
+        .unwrap();
+
+        let ctx = SessionContext::new();
+        ctx.register_udf(MATCH_PHRASE_UDF.clone());
+
+        let provider = MemTable::try_new(schema, vec![vec![batch]]).unwrap();
+        ctx.register_table("t", Arc::new(provider)).unwrap();
+
+        let df = ctx.sql(sql).await.unwrap();
+        let result = df.collect().await.unwrap();
+        let count = result.iter().map(|batch| batch.num_rows()).sum::<usize>();
+        assert_eq!(count, 2);
+    }
+}
@@ -27,9 +27,12 @@ pub mod cast_to_timestamp_udf;
 pub mod cipher_udf;
 pub mod date_format_udf;
 pub mod fuzzy_match_udf;
+pub mod geo_udf;
 pub mod histogram_udf;
+pub mod ip_udf;
 pub mod match_all_hash_udf;
 pub mod match_all_udf;
+pub mod match_phrase_udf;
 pub mod regexp_matches_udf;
 pub mod regexp_udf;
 pub mod spath_udf;
@@ -38,6 +41,7 @@ pub mod string_to_array_v2_udf;
 pub mod time_range_udf;
 pub mod to_arr_string_udf;
 pub mod transform_udf;
+pub mod vector_udf;
 
 /// The name of the str_match UDF given to DataFusion.
 pub const STR_MATCH_UDF_NAME: &str = "str_match";
@@ -49,6 +53,8 @@ pub const MATCH_FIELD_UDF_NAME: &str = "match_field";
 pub const MATCH_FIELD_IGNORE_CASE_UDF_NAME: &str = "match_field_ignore_case";
 /// The name of the fuzzy_match UDF given to DataFusion.
 pub const FUZZY_MATCH_UDF_NAME: &str = "fuzzy_match";
+/// The name of the match_phrase UDF given to DataFusion.
+pub const MATCH_PHRASE_UDF_NAME: &str = "match_phrase";
 /// The name of the regex_match UDF given to DataFusion.
 pub const REGEX_MATCH_UDF_NAME: &str = "re_match";
 /// The name of the not_regex_match UDF given to DataFusion.
@@ -56,7 +62,7 @@ pub const REGEX_NOT_MATCH_UDF_NAME: &str = "re_not_match";
 /// The name of the regex_matches UDF given to DataFusion.
 pub const REGEX_MATCHES_UDF_NAME: &str = "re_matches";
 
-pub const DEFAULT_FUNCTIONS: [ZoFunction; 11] = [
+pub const DEFAULT_FUNCTIONS: [ZoFunction; 17] = [
     ZoFunction {
         name: "match_all",
         text: "match_all('v')",
@@ -85,6 +91,10 @@ pub const DEFAULT_FUNCTIONS: [ZoFunction; 11] = [
         name: FUZZY_MATCH_UDF_NAME,
         text: "fuzzy_match(field, 'v', 1)",
     },
+    ZoFunction {
+        name: MATCH_PHRASE_UDF_NAME,
+        text: "match_phrase(field, 'v', 0)",
+    },
     ZoFunction {
         name: REGEX_MATCH_UDF_NAME,
         text: "re_match(field, 'pattern')",
@@ -97,10 +107,30 @@ pub const DEFAULT_FUNCTIONS: [ZoFunction; 11] = [
         name: REGEX_MATCHES_UDF_NAME,
         text: "re_matches(field, 'pattern')",
     },
+    ZoFunction {
+        name: ip_udf::IP_IN_CIDR_UDF_NAME,
+        text: "ip_in_cidr(field, '10.0.0.0/8')",
+    },
+    ZoFunction {
+        name: geo_udf::GEOIP_COUNTRY_UDF_NAME,
+        text: "geoip_country(field)",
+    },
+    ZoFunction {
+        name: geo_udf::GEO_DISTANCE_UDF_NAME,
+        text: "geo_distance(lat1, lon1, lat2, lon2)",
+    },
     ZoFunction {
         name: cast_to_timestamp_udf::CAST_TO_TIMESTAMP_UDF_NAME,
         text: "cast_to_timestamp('pattern')",
     },
+    ZoFunction {
+        name: vector_udf::KNN_UDF_NAME,
+        text: "knn(field, '[0.1, 0.2]', 10)",
+    },
+    ZoFunction {
+        name: vector_udf::COSINE_SIMILARITY_UDF_NAME,
+        text: "cosine_similarity(field, '[0.1, 0.2]')",
+    },
 ];
 
 pub fn stringify_json_value(field: &json::Value) -> String {
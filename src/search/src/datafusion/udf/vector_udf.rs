@@ -0,0 +1,318 @@
+// Copyright 2026 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//! UDFs over vector (embedding) fields, see [`config::utils::vector`]. Both
+//! take the `FixedSizeList<Float32>` column of a vector field, and the query
+//! vector as a `'[1.0, 2.0, ...]'` string or an array literal.
+
+use std::sync::{Arc, LazyLock as Lazy};
+
+use config::utils::vector::{array_to_vectors, cosine_similarity, parse_vector};
+use datafusion::{
+    arrow::{
+        array::{ArrayRef, BooleanArray, Float64Array},
+        datatypes::DataType,
+    },
+    error::{DataFusionError, Result},
+    logical_expr::{
+        ColumnarValue, ScalarFunctionArgs, ScalarUDF, ScalarUDFImpl, Signature, Volatility,
+    },
+    scalar::ScalarValue,
+    sql::sqlparser::parser::ParserError,
+};
+
+/// The name of the cosine_similarity UDF given to DataFusion.
+pub const COSINE_SIMILARITY_UDF_NAME: &str = "cosine_similarity";
+/// The name of the knn UDF given to DataFusion.
+pub const KNN_UDF_NAME: &str = "knn";
+
+fn params_error(usage: &str) -> DataFusionError {
+    DataFusionError::SQL(
+        Box::new(ParserError::ParserError(format!(
+            "UDF params should be: {usage}"
+        ))),
+        None,
+    )
+}
+
+/// The vector of a literal: a `'[..]'` string or an array of numbers.
+fn scalar_vector(value: &ScalarValue) -> Option<Vec<f32>> {
+    match value {
+        ScalarValue::Utf8(v) | ScalarValue::Utf8View(v) | ScalarValue::LargeUtf8(v) => {
+            v.as_deref().and_then(parse_vector)
+        }
+        _ => array_to_vectors(&value.to_array().ok()?)
+            .ok()?
+            .into_iter()
+            .next()
+            .flatten(),
+    }
+}
+
+/// Implementation of cosine_similarity
+pub static COSINE_SIMILARITY_UDF: Lazy<ScalarUDF> =
+    Lazy::new(|| ScalarUDF::from(CosineSimilarityUdf::new()));
+
+/// Implementation of knn
+pub static KNN_UDF: Lazy<ScalarUDF> = Lazy::new(|| ScalarUDF::from(KnnUdf::new()));
+
+#[derive(Debug, Clone, Hash, Eq, PartialEq)]
+struct CosineSimilarityUdf {
+    signature: Signature,
+}
+
+impl CosineSimilarityUdf {
+    fn new() -> Self {
+        Self {
+            // expects two vectors, as lists or strings
+            signature: Signature::any(2, Volatility::Immutable),
+        }
+    }
+}
+
+impl ScalarUDFImpl for CosineSimilarityUdf {
+    fn name(&self) -> &str {
+        COSINE_SIMILARITY_UDF_NAME
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        // the similarity in [-1, 1]
+        Ok(DataType::Float64)
+    }
+
+    fn invoke_with_args(&self, args: ScalarFunctionArgs) -> Result<ColumnarValue> {
+        cosine_similarity_impl(&args.args)
+    }
+}
+
+#[derive(Debug, Clone, Hash, Eq, PartialEq)]
+struct KnnUdf {
+    signature: Signature,
+}
+
+impl KnnUdf {
+    fn new() -> Self {
+        Self {
+            // expects the vector field, the query vector and k
+            signature: Signature::any(3, Volatility::Immutable),
+        }
+    }
+}
+
+impl ScalarUDFImpl for KnnUdf {
+    fn name(&self) -> &str {
+        KNN_UDF_NAME
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        Ok(DataType::Boolean)
+    }
+
+    fn invoke_with_args(&self, args: ScalarFunctionArgs) -> Result<ColumnarValue> {
+        knn_impl(&args.args)
+    }
+}
+
+/// cosine_similarity(a, b) returns the cosine similarity of two vectors, null
+/// when either value is not a vector, the dimensions differ or a vector is
+/// zero.
+pub fn cosine_similarity_impl(args: &[ColumnarValue]) -> Result<ColumnarValue> {
+    if args.len() != 2 {
+        return Err(params_error(
+            "cosine_similarity(vector_field, '[1.0, 2.0, ...]')",
+        ));
+    }
+    // the query vector is usually a literal, parse it once
+    let query = match &args[1] {
+        ColumnarValue::Scalar(query) => Some(scalar_vector(query)),
+        ColumnarValue::Array(_) => None,
+    };
+    let arrays = ColumnarValue::values_to_arrays(args)?;
+    let left = array_to_vectors(&arrays[0])?;
+    let array = match query {
+        Some(query) => left
+            .iter()
+            .map(|v| similarity(query.as_deref()?, v.as_deref()?))
+            .collect::<Float64Array>(),
+        None => left
+            .iter()
+            .zip(array_to_vectors(&arrays[1])?.iter())
+            .map(|(a, b)| similarity(b.as_deref()?, a.as_deref()?))
+            .collect::<Float64Array>(),
+    };
+    Ok(ColumnarValue::from(Arc::new(array) as ArrayRef))
+}
+
+fn similarity(query: &[f32], value: &[f32]) -> Option<f64> {
+    cosine_similarity(query, value).map(|v| v as f64)
+}
+
+/// The literal arguments of `knn(field, '[..]', k)`: the query vector and k.
+pub fn knn_params(query: &ScalarValue, k: &ScalarValue) -> Option<(Vec<f32>, usize)> {
+    let query = scalar_vector(query)?;
+    let k = match k {
+        ScalarValue::Int64(Some(k)) if *k > 0 => *k as usize,
+        ScalarValue::UInt64(Some(k)) if *k > 0 => *k as usize,
+        _ => return None,
+    };
+    Some((query, k))
+}
+
+/// knn(field, '[..]', k) selects the k rows of the queried time range whose
+/// vector is most similar (by cosine) to the query vector.
+///
+/// The planner rewrites the condition into a global top k, see
+/// [`crate::datafusion::optimizer::analyze::rewrite_knn`]; this function only
+/// picks the candidates of one batch for it. The k best rows of a batch (or,
+/// with a vector index, of a file) are a superset of the rows of that batch
+/// in the global top k, so the merge above it is exact.
+pub fn knn_impl(args: &[ColumnarValue]) -> Result<ColumnarValue> {
+    let usage = "knn(vector_field, '[1.0, 2.0, ...]', k)";
+    let [
+        values,
+        ColumnarValue::Scalar(query),
+        ColumnarValue::Scalar(k),
+    ] = args
+    else {
+        return Err(params_error(usage));
+    };
+    let Some((query, k)) = knn_params(query, k) else {
+        return Err(params_error(usage));
+    };
+    let values = ColumnarValue::values_to_arrays(std::slice::from_ref(values))?;
+    let vectors = array_to_vectors(&values[0])?;
+    let mut scored = vectors
+        .iter()
+        .enumerate()
+        .filter_map(|(i, v)| Some((i, similarity(&query, v.as_deref()?)?)))
+        .collect::<Vec<_>>();
+    scored.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+    let mut matched = vec![false; vectors.len()];
+    for (i, _) in scored.into_iter().take(k) {
+        matched[i] = true;
+    }
+    Ok(ColumnarValue::from(
+        Arc::new(BooleanArray::from(matched)) as ArrayRef
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use config::utils::vector::{append_vector, vector_data_type};
+    use datafusion::{
+        arrow::{
+            array::{StringArray, make_builder},
+            datatypes::{Field, Schema},
+            record_batch::RecordBatch,
+        },
+        assert_batches_eq,
+        datasource::MemTable,
+        prelude::SessionContext,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_vector_udf_params() {
+        assert!(cosine_similarity_impl(&[]).is_err());
+        assert!(knn_impl(&[]).is_err());
+        let query = ScalarValue::Utf8(Some("[1, 0]".to_string()));
+        assert_eq!(
+            knn_params(&query, &ScalarValue::Int64(Some(3))),
+            Some((vec![1.0, 0.0], 3))
+        );
+        assert!(knn_params(&query, &ScalarValue::Int64(Some(0))).is_none());
+        assert!(
+            knn_params(
+                &ScalarValue::Utf8(Some("nope".to_string())),
+                &ScalarValue::Int64(Some(3))
+            )
+            .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_vector_udfs() {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Utf8, false),
+            Field::new("embedding", vector_data_type(2), true),
+        ]));
+        let mut embedding = make_builder(&vector_data_type(2), 5);
+        for vector in [
+            Some(vec![1.0, 0.0]),
+            Some(vec![0.6, 0.8]),
+            Some(vec![0.0, 1.0]),
+            Some(vec![0.0, 0.0]),
+            None,
+        ] {
+            append_vector(embedding.as_mut(), 2, vector.as_deref());
+        }
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(StringArray::from(vec!["a", "b", "c", "d", "e"])),
+                embedding.finish(),
+            ],
+        )
+        .unwrap();
+
+        let ctx = SessionContext::new();
+        ctx.register_udf(COSINE_SIMILARITY_UDF.clone());
+        ctx.register_udf(KNN_UDF.clone());
+        let provider = MemTable::try_new(schema, vec![vec![batch]]).unwrap();
+        ctx.register_table("t", Arc::new(provider)).unwrap();
+
+        let sql = "select id, round(cosine_similarity(embedding, '[1, 0]'), 2) as sim, \
+                   round(cosine_similarity(embedding, embedding), 2) as self_sim from t";
+        let batches = ctx.sql(sql).await.unwrap().collect().await.unwrap();
+        let expected = [
+            "+----+-----+----------+",
+            "| id | sim | self_sim |",
+            "+----+-----+----------+",
+            "| a  | 1.0 | 1.0      |",
+            "| b  | 0.6 | 1.0      |",
+            "| c  | 0.0 | 1.0      |",
+            "| d  |     |          |",
+            "| e  |     |          |",
+            "+----+-----+----------+",
+        ];
+        assert_batches_eq!(expected, &batches);
+
+        let sql = "select id, round(cosine_similarity(embedding, [0, 1]), 2) as sim from t \
+                   where id = 'b'";
+        let batches = ctx.sql(sql).await.unwrap().collect().await.unwrap();
+        let expected = [
+            "+----+-----+",
+            "| id | sim |",
+            "+----+-----+",
+            "| b  | 0.8 |",
+            "+----+-----+",
+        ];
+        assert_batches_eq!(expected, &batches);
+
+        let sql = "select id from t where knn(embedding, '[0, 1]', 2) \
+                   order by cosine_similarity(embedding, '[0, 1]') desc";
+        let batches = ctx.sql(sql).await.unwrap().collect().await.unwrap();
+        let expected = ["+----+", "| id |", "+----+", "| c  |", "| b  |", "+----+"];
+        assert_batches_eq!(expected, &batches);
+    }
+}
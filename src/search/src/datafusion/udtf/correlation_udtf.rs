@@ -0,0 +1,459 @@
+// Copyright 2026 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! `trace_spans(trace_id [, traces_stream])`,
+//! `logs_for_trace(trace_id, window [, logs_stream [, traces_stream]])` and
+//! `promql_range(query, step)`.
+
+use std::sync::Arc;
+
+use arrow::{
+    array::{Array, Float64Array, Int64Array, RecordBatch, StringArray},
+    compute::cast,
+};
+use arrow_schema::{DataType, Field, Schema, SchemaRef};
+use config::{
+    TIMESTAMP_COL_NAME,
+    meta::{
+        promql::value::{Labels, Sample, Value},
+        stream::StreamType,
+    },
+    utils::{
+        json,
+        record_batch_ext::format_recordbatch_by_schema,
+        sql::table_function::{
+            LOGS_FOR_TRACE_UDTF_NAME, PROMQL_RANGE_UDTF_NAME, TRACE_SPANS_UDTF_NAME,
+            TableFunctionCall,
+        },
+        time::parse_milliseconds,
+    },
+};
+use datafusion::{
+    catalog::{TableFunctionImpl, TableProvider},
+    common::{Result as DataFusionResult, plan_err},
+    datasource::MemTable,
+    logical_expr::Expr,
+};
+use hashbrown::HashMap;
+use infra::errors::{Error, ErrorCodes, Result};
+
+use super::{CorrelationContext, CorrelationSource, get_correlation_source};
+
+const DEFAULT_STREAM: &str = "default";
+pub const PROMQL_VALUE_COL_NAME: &str = "value";
+pub const PROMQL_LABELS_COL_NAME: &str = "labels";
+
+#[derive(Clone, Debug, PartialEq)]
+enum Correlation {
+    TraceSpans {
+        trace_id: String,
+        stream: String,
+    },
+    LogsForTrace {
+        trace_id: String,
+        window: i64, // microseconds
+        stream: String,
+        trace_stream: String,
+    },
+    PromqlRange {
+        query: String,
+        step: i64, // microseconds
+    },
+}
+
+impl Correlation {
+    fn try_from_call(call: &TableFunctionCall) -> std::result::Result<Self, String> {
+        let args = &call.args;
+        match call.name.as_str() {
+            TRACE_SPANS_UDTF_NAME => {
+                if args.is_empty() || args.len() > 2 {
+                    return Err(format!(
+                        "usage: {TRACE_SPANS_UDTF_NAME}(trace_id [, traces_stream])"
+                    ));
+                }
+                Ok(Self::TraceSpans {
+                    trace_id: check_identifier(&args[0])?,
+                    stream: check_stream_name(args.get(1))?,
+                })
+            }
+            LOGS_FOR_TRACE_UDTF_NAME => {
+                if args.len() < 2 || args.len() > 4 {
+                    return Err(format!(
+                        "usage: {LOGS_FOR_TRACE_UDTF_NAME}(trace_id, window [, logs_stream [, traces_stream]])"
+                    ));
+                }
+                let window = parse_milliseconds(&args[1])
+                    .map_err(|e| format!("{LOGS_FOR_TRACE_UDTF_NAME}: invalid window: {e}"))?;
+                Ok(Self::LogsForTrace {
+                    trace_id: check_identifier(&args[0])?,
+                    window: window as i64 * 1000,
+                    stream: check_stream_name(args.get(2))?,
+                    trace_stream: check_stream_name(args.get(3))?,
+                })
+            }
+            PROMQL_RANGE_UDTF_NAME => {
+                if args.len() != 2 {
+                    return Err(format!("usage: {PROMQL_RANGE_UDTF_NAME}(query, step)"));
+                }
+                let step = parse_milliseconds(&args[1])
+                    .map_err(|e| format!("{PROMQL_RANGE_UDTF_NAME}: invalid step: {e}"))?;
+                if step == 0 {
+                    return Err(format!("{PROMQL_RANGE_UDTF_NAME}: step must be positive"));
+                }
+                Ok(Self::PromqlRange {
+                    query: args[0].clone(),
+                    step: step as i64 * 1000,
+                })
+            }
+            name => Err(format!("unknown table function {name}")),
+        }
+    }
+
+    /// The streams searched by the call, the metrics of a PromQL query are
+    /// checked when it runs.
+    fn streams(&self) -> Vec<(StreamType, &str)> {
+        match self {
+            Self::TraceSpans { stream, .. } => vec![(StreamType::Traces, stream)],
+            Self::LogsForTrace {
+                stream,
+                trace_stream,
+                ..
+            } => vec![
+                (StreamType::Traces, trace_stream),
+                (StreamType::Logs, stream),
+            ],
+            Self::PromqlRange { .. } => vec![],
+        }
+    }
+
+    async fn schema(&self, org_id: &str) -> Result<SchemaRef> {
+        let (stream_type, stream) = match self {
+            Self::TraceSpans { stream, .. } => (StreamType::Traces, stream),
+            Self::LogsForTrace { stream, .. } => (StreamType::Logs, stream),
+            Self::PromqlRange { .. } => return Ok(promql_range_schema()),
+        };
+        let schema = infra::schema::get(org_id, stream, stream_type)
+            .await
+            .unwrap_or_else(|_| Schema::empty());
+        if schema.fields().is_empty() {
+            return Err(Error::ErrorCode(ErrorCodes::SearchStreamNotFound(
+                stream.to_string(),
+            )));
+        }
+        Ok(Arc::new(schema.with_metadata(Default::default())))
+    }
+
+    async fn fetch(
+        &self,
+        ctx: &CorrelationContext,
+        time_range: (i64, i64),
+    ) -> Result<Vec<RecordBatch>> {
+        let source = correlation_source()?;
+        match self {
+            Self::TraceSpans { trace_id, stream } => {
+                let sql = format!("SELECT * FROM \"{stream}\" WHERE trace_id = '{trace_id}'");
+                source
+                    .search(ctx, StreamType::Traces, stream, &sql, time_range)
+                    .await
+            }
+            Self::LogsForTrace {
+                trace_id,
+                window,
+                stream,
+                trace_stream,
+            } => {
+                // span times are in nanoseconds
+                let sql = format!(
+                    "SELECT min(start_time) AS trace_start, max(end_time) AS trace_end \
+                     FROM \"{trace_stream}\" WHERE trace_id = '{trace_id}'"
+                );
+                let batches = source
+                    .search(ctx, StreamType::Traces, trace_stream, &sql, time_range)
+                    .await?;
+                let Some((start, end)) = first_row_range(&batches)? else {
+                    return Ok(vec![]);
+                };
+                let sql = format!("SELECT * FROM \"{stream}\" WHERE trace_id = '{trace_id}'");
+                source
+                    .search(
+                        ctx,
+                        StreamType::Logs,
+                        stream,
+                        &sql,
+                        (start / 1000 - window, end / 1000 + window),
+                    )
+                    .await
+            }
+            Self::PromqlRange { query, step } => {
+                let value = source.promql_range(ctx, query, time_range, *step).await?;
+                Ok(vec![promql_to_record_batch(value)?])
+            }
+        }
+    }
+}
+
+fn check_identifier(v: &str) -> std::result::Result<String, String> {
+    if v.is_empty()
+        || !v
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(format!("invalid trace_id `{v}`"));
+    }
+    Ok(v.to_string())
+}
+
+fn check_stream_name(v: Option<&String>) -> std::result::Result<String, String> {
+    match v {
+        None => Ok(DEFAULT_STREAM.to_string()),
+        Some(v) if v.is_empty() || v.contains('"') => Err(format!("invalid stream name `{v}`")),
+        Some(v) => Ok(v.to_string()),
+    }
+}
+
+// the (min start_time, max end_time) of a trace
+fn first_row_range(batches: &[RecordBatch]) -> Result<Option<(i64, i64)>> {
+    let Some(batch) = batches.iter().find(|b| b.num_rows() > 0) else {
+        return Ok(None);
+    };
+    let value = |idx: usize| -> Result<Option<i64>> {
+        let col =
+            cast(batch.column(idx), &DataType::Int64).map_err(|e| Error::Message(e.to_string()))?;
+        let col = col.as_any().downcast_ref::<Int64Array>().unwrap();
+        Ok((!col.is_null(0)).then(|| col.value(0)))
+    };
+    if batch.num_columns() < 2 {
+        return Ok(None);
+    }
+    match (value(0)?, value(1)?) {
+        (Some(start), Some(end)) => Ok(Some((start, end))),
+        _ => Ok(None),
+    }
+}
+
+/// `_timestamp`, `value` and `labels` as a JSON object, one row per sample.
+pub fn promql_range_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new(TIMESTAMP_COL_NAME, DataType::Int64, false),
+        Field::new(PROMQL_VALUE_COL_NAME, DataType::Float64, false),
+        Field::new(PROMQL_LABELS_COL_NAME, DataType::Utf8, false),
+    ]))
+}
+
+fn promql_to_record_batch(value: Value) -> Result<RecordBatch> {
+    let mut timestamps = Vec::new();
+    let mut values = Vec::new();
+    let mut labels = Vec::new();
+    let mut push = |series_labels: &Labels, samples: &[Sample]| {
+        let series_labels = json::Value::Object(
+            series_labels
+                .iter()
+                .map(|l| (l.name.clone(), json::Value::String(l.value.clone())))
+                .collect(),
+        )
+        .to_string();
+        for sample in samples {
+            timestamps.push(sample.timestamp);
+            values.push(sample.value);
+            labels.push(series_labels.clone());
+        }
+    };
+    match value {
+        Value::Matrix(series) => series.iter().for_each(|s| push(&s.labels, &s.samples)),
+        Value::Vector(series) => series
+            .iter()
+            .for_each(|s| push(&s.labels, std::slice::from_ref(&s.sample))),
+        Value::Range(s) => push(&s.labels, &s.samples),
+        Value::Instant(s) => push(&s.labels, std::slice::from_ref(&s.sample)),
+        Value::Sample(s) => push(&vec![], &[s]),
+        Value::Float(_) | Value::String(_) => {
+            return Err(Error::ErrorCode(ErrorCodes::SearchSQLNotValid(format!(
+                "{PROMQL_RANGE_UDTF_NAME}: the query must return series"
+            ))));
+        }
+        Value::None => {}
+    }
+    RecordBatch::try_new(
+        promql_range_schema(),
+        vec![
+            Arc::new(Int64Array::from(timestamps)),
+            Arc::new(Float64Array::from(values)),
+            Arc::new(StringArray::from(labels)),
+        ],
+    )
+    .map_err(|e| Error::Message(e.to_string()))
+}
+
+fn correlation_source() -> Result<&'static dyn CorrelationSource> {
+    get_correlation_source().ok_or_else(|| {
+        Error::Message("correlation table functions are not available on this node".to_string())
+    })
+}
+
+/// Creates the table behind one call. The data is searched up front when
+/// `fetch` is set, otherwise the table is empty and only good for planning.
+pub async fn new_table(
+    ctx: &CorrelationContext,
+    call: &TableFunctionCall,
+    time_range: (i64, i64),
+    fetch: bool,
+) -> Result<Arc<dyn TableProvider>> {
+    let correlation = Correlation::try_from_call(call)
+        .map_err(|e| Error::ErrorCode(ErrorCodes::SearchSQLNotValid(e)))?;
+    // the schema of a stream is only read for users who may search it
+    let source = correlation_source()?;
+    for (stream_type, stream) in correlation.streams() {
+        source
+            .check_stream_permission(ctx, stream_type, stream)
+            .await?;
+    }
+    let schema = correlation.schema(&ctx.org_id).await?;
+    let batches = if fetch {
+        correlation
+            .fetch(ctx, time_range)
+            .await?
+            .into_iter()
+            .map(|batch| format_recordbatch_by_schema(schema.clone(), batch))
+            .collect()
+    } else {
+        vec![]
+    };
+    Ok(Arc::new(MemTable::try_new(schema, vec![batches])?))
+}
+
+/// Hands out the tables resolved for the calls of the current query.
+#[derive(Debug)]
+pub struct CorrelationTableFunction {
+    name: String,
+    tables: HashMap<Vec<String>, Arc<dyn TableProvider>>,
+}
+
+impl CorrelationTableFunction {
+    pub fn new(name: &str, tables: HashMap<Vec<String>, Arc<dyn TableProvider>>) -> Self {
+        Self {
+            name: name.to_string(),
+            tables,
+        }
+    }
+}
+
+impl TableFunctionImpl for CorrelationTableFunction {
+    fn call(&self, args: &[Expr]) -> DataFusionResult<Arc<dyn TableProvider>> {
+        let mut values = Vec::with_capacity(args.len());
+        for arg in args {
+            match arg {
+                Expr::Literal(v, _) if !v.is_null() => values.push(v.to_string()),
+                _ => return plan_err!("{}: arguments must be literals", self.name),
+            }
+        }
+        match self.tables.get(&values) {
+            Some(table) => Ok(table.clone()),
+            None => plan_err!("{}: call with {values:?} was not resolved", self.name),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use config::meta::promql::value::{Label, RangeValue};
+
+    use super::*;
+
+    fn call(name: &str, args: &[&str]) -> TableFunctionCall {
+        TableFunctionCall {
+            name: name.to_string(),
+            args: args.iter().map(|v| v.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_correlation_try_from_call() {
+        assert_eq!(
+            Correlation::try_from_call(&call(TRACE_SPANS_UDTF_NAME, &["abc123"])).unwrap(),
+            Correlation::TraceSpans {
+                trace_id: "abc123".to_string(),
+                stream: DEFAULT_STREAM.to_string(),
+            }
+        );
+        assert_eq!(
+            Correlation::try_from_call(&call(LOGS_FOR_TRACE_UDTF_NAME, &["abc", "5m", "app"]))
+                .unwrap(),
+            Correlation::LogsForTrace {
+                trace_id: "abc".to_string(),
+                window: 300_000_000,
+                stream: "app".to_string(),
+                trace_stream: DEFAULT_STREAM.to_string(),
+            }
+        );
+        assert_eq!(
+            Correlation::try_from_call(&call(PROMQL_RANGE_UDTF_NAME, &["up", "60"])).unwrap(),
+            Correlation::PromqlRange {
+                query: "up".to_string(),
+                step: 60_000_000,
+            }
+        );
+        assert!(Correlation::try_from_call(&call(TRACE_SPANS_UDTF_NAME, &["x' or '1"])).is_err());
+        assert!(Correlation::try_from_call(&call(LOGS_FOR_TRACE_UDTF_NAME, &["abc"])).is_err());
+        assert!(Correlation::try_from_call(&call(PROMQL_RANGE_UDTF_NAME, &["up", "0"])).is_err());
+    }
+
+    #[test]
+    fn test_promql_to_record_batch() {
+        let labels: Labels = vec![
+            Arc::new(Label::new("__name__", "cpu")),
+            Arc::new(Label::new("pod", "api-0")),
+        ];
+        let value = Value::Matrix(vec![RangeValue::new(
+            labels,
+            vec![Sample::new(1_000_000, 0.5), Sample::new(2_000_000, 0.7)],
+        )]);
+        let batch = promql_to_record_batch(value).unwrap();
+        assert_eq!(batch.num_rows(), 2);
+        let labels = batch
+            .column(2)
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .unwrap();
+        let labels: json::Value = json::from_str(labels.value(0)).unwrap();
+        assert_eq!(labels["pod"], "api-0");
+        assert!(promql_to_record_batch(Value::Float(1.0)).is_err());
+    }
+
+    #[test]
+    fn test_first_row_range() {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("trace_start", DataType::Int64, true),
+            Field::new("trace_end", DataType::Int64, true),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(Int64Array::from(vec![Some(1_000)])),
+                Arc::new(Int64Array::from(vec![Some(5_000)])),
+            ],
+        )
+        .unwrap();
+        assert_eq!(first_row_range(&[batch]).unwrap(), Some((1_000, 5_000)));
+        let batch = RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::new(Int64Array::from(vec![None::<i64>])),
+                Arc::new(Int64Array::from(vec![None::<i64>])),
+            ],
+        )
+        .unwrap();
+        assert_eq!(first_row_range(&[batch]).unwrap(), None);
+    }
+}
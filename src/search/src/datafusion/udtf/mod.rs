@@ -0,0 +1,256 @@
+// Copyright 2026 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Table functions, their searches are run by the leader before the query
+//! waits in the search queue, and their tables are scanned on the leader only.
+
+use std::sync::{Arc, OnceLock};
+
+use arrow::array::RecordBatch;
+use async_trait::async_trait;
+use config::{
+    datafusion::request::Request,
+    meta::{promql::value::Value, stream::StreamType},
+    utils::sql::table_function::resolve_table_functions_from_sql,
+};
+use datafusion::{catalog::TableProvider, prelude::SessionContext};
+use hashbrown::HashMap;
+use infra::errors::{Error, ErrorCodes, Result};
+
+use crate::sql::Sql;
+
+pub mod correlation_udtf;
+
+/// Who runs a correlation table function and on behalf of whom.
+#[derive(Clone, Debug, Default)]
+pub struct CorrelationContext {
+    pub trace_id: String,
+    pub org_id: String,
+    pub user_id: Option<String>,
+}
+
+/// Runs the searches behind the correlation table functions. The search
+/// service depends on this crate, so it is injected at startup with
+/// [`set_correlation_source`].
+#[async_trait]
+pub trait CorrelationSource: Send + Sync {
+    /// Fails unless the user of `ctx` may search the stream.
+    async fn check_stream_permission(
+        &self,
+        ctx: &CorrelationContext,
+        stream_type: StreamType,
+        stream_name: &str,
+    ) -> Result<()>;
+
+    /// Runs `sql` against one stream and returns the record batches.
+    async fn search(
+        &self,
+        ctx: &CorrelationContext,
+        stream_type: StreamType,
+        stream_name: &str,
+        sql: &str,
+        time_range: (i64, i64),
+    ) -> Result<Vec<RecordBatch>>;
+
+    /// Runs a PromQL range query, `step` is in microseconds. Every metric of
+    /// the query is checked with the permissions of the user.
+    async fn promql_range(
+        &self,
+        ctx: &CorrelationContext,
+        query: &str,
+        time_range: (i64, i64),
+        step: i64,
+    ) -> Result<Value>;
+}
+
+static CORRELATION_SOURCE: OnceLock<Box<dyn CorrelationSource>> = OnceLock::new();
+
+pub fn set_correlation_source(source: Box<dyn CorrelationSource>) {
+    if CORRELATION_SOURCE.set(source).is_err() {
+        log::warn!("correlation source is already set");
+    }
+}
+
+pub fn get_correlation_source() -> Option<&'static dyn CorrelationSource> {
+    CORRELATION_SOURCE.get().map(|s| s.as_ref())
+}
+
+/// The tables of the table functions of one query, by function name and
+/// arguments.
+pub type TableFunctions = HashMap<String, HashMap<Vec<String>, Arc<dyn TableProvider>>>;
+
+/// Runs the searches behind the table functions used by the sql. The leader
+/// calls this before it waits in the search queue: these searches wait in the
+/// queue on their own, so running them while the query holds its slot would
+/// wait on that slot until the query times out.
+pub async fn resolve_table_functions(req: &Request, sql: &Sql) -> Result<TableFunctions> {
+    resolve(
+        &correlation_context(req, sql),
+        &sql.sql,
+        sql.time_range,
+        true,
+    )
+    .await
+}
+
+/// Registers the table functions used by the sql, with the tables of
+/// [`resolve_table_functions`]. Without them only the schemas are resolved,
+/// which is enough to plan the query, and the tables are empty.
+pub async fn register_table_functions(
+    ctx: &SessionContext,
+    req: &Request,
+    sql: &Sql,
+    resolved: Option<TableFunctions>,
+) -> Result<()> {
+    let functions = match resolved {
+        Some(functions) => functions,
+        None => {
+            resolve(
+                &correlation_context(req, sql),
+                &sql.sql,
+                sql.time_range,
+                false,
+            )
+            .await?
+        }
+    };
+    for (name, tables) in functions {
+        ctx.register_udtf(
+            &name,
+            Arc::new(correlation_udtf::CorrelationTableFunction::new(
+                &name, tables,
+            )),
+        );
+    }
+    Ok(())
+}
+
+fn correlation_context(req: &Request, sql: &Sql) -> CorrelationContext {
+    CorrelationContext {
+        trace_id: req.trace_id.clone(),
+        org_id: sql.org_id.clone(),
+        user_id: req.user_id.clone(),
+    }
+}
+
+async fn resolve(
+    ctx: &CorrelationContext,
+    sql: &str,
+    time_range: (i64, i64),
+    fetch: bool,
+) -> Result<TableFunctions> {
+    let calls = resolve_table_functions_from_sql(sql)
+        .map_err(|e| Error::ErrorCode(ErrorCodes::SearchSQLNotValid(e)))?;
+    let mut functions = TableFunctions::new();
+    for call in calls {
+        if functions
+            .get(&call.name)
+            .is_some_and(|tables| tables.contains_key(&call.args))
+        {
+            continue;
+        }
+        let table = correlation_udtf::new_table(ctx, &call, time_range, fetch).await?;
+        functions
+            .entry(call.name)
+            .or_default()
+            .insert(call.args, table);
+    }
+    Ok(functions)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::LazyLock;
+
+    use arrow::array::{Array, Int64Array};
+    use config::meta::promql::value::{Label, RangeValue, Sample};
+    use tokio::sync::Mutex;
+
+    use super::*;
+
+    /// Stands for the search queue with a single slot: the query holds it while
+    /// it runs and every search of a table function has to take it.
+    static QUEUE: LazyLock<Mutex<()>> = LazyLock::new(Default::default);
+
+    struct QueuedSource;
+
+    #[async_trait]
+    impl CorrelationSource for QueuedSource {
+        async fn check_stream_permission(
+            &self,
+            _ctx: &CorrelationContext,
+            _stream_type: StreamType,
+            _stream_name: &str,
+        ) -> Result<()> {
+            Ok(())
+        }
+
+        async fn search(
+            &self,
+            _ctx: &CorrelationContext,
+            _stream_type: StreamType,
+            _stream_name: &str,
+            _sql: &str,
+            _time_range: (i64, i64),
+        ) -> Result<Vec<RecordBatch>> {
+            Ok(vec![])
+        }
+
+        async fn promql_range(
+            &self,
+            _ctx: &CorrelationContext,
+            _query: &str,
+            _time_range: (i64, i64),
+            _step: i64,
+        ) -> Result<Value> {
+            let _slot = QUEUE
+                .try_lock()
+                .map_err(|_| Error::Message("request timeout in queue".to_string()))?;
+            Ok(Value::Matrix(vec![RangeValue::new(
+                vec![Arc::new(Label::new("__name__", "up"))],
+                vec![Sample::new(1_000_000, 1.0), Sample::new(2_000_000, 1.0)],
+            )]))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_table_functions_resolved_before_queue() {
+        set_correlation_source(Box::new(QueuedSource));
+        let sql = "SELECT count(*) AS cnt FROM promql_range('up', '60')";
+        let functions = resolve(&CorrelationContext::default(), sql, (0, 3_000_000), true)
+            .await
+            .unwrap();
+
+        // the query holds its slot while it runs, so a table function that
+        // searched only when it is scanned would never get one
+        let _slot = QUEUE.lock().await;
+        let ctx = SessionContext::new();
+        for (name, tables) in functions {
+            ctx.register_udtf(
+                &name,
+                Arc::new(correlation_udtf::CorrelationTableFunction::new(
+                    &name, tables,
+                )),
+            );
+        }
+        let batches = ctx.sql(sql).await.unwrap().collect().await.unwrap();
+        let count = batches[0]
+            .column(0)
+            .as_any()
+            .downcast_ref::<Int64Array>()
+            .unwrap();
+        assert_eq!(count.value(0), 2);
+    }
+}
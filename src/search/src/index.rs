@@ -15,13 +15,21 @@
 
 use std::{
     fmt::{self, Debug, Formatter},
+    ops::Bound,
     sync::Arc,
 };
 
 use config::{
     INDEX_FIELD_NAME_FOR_ALL, get_config,
-    meta::inverted_index::UNKNOWN_NAME,
-    tantivy::{query::contains_query::ContainsQuery, tokenizer::o2_collect_search_tokens},
+    meta::{inverted_index::UNKNOWN_NAME, stream::FtsTokenizer},
+    tantivy::{
+        query::contains_query::ContainsQuery,
+        tokenizer::{
+            collect_phrase_tokens, collect_search_tokens, o2_collect_search_tokens,
+            schema_fts_tokenizer,
+        },
+    },
+    utils::{inverted_index::to_range_index_field_name, vector::format_vector},
 };
 use datafusion::{
     arrow::datatypes::{DataType, SchemaRef},
@@ -40,18 +48,20 @@ use hashbrown::HashSet;
 use tantivy::{
     Term,
     query::{
-        AllQuery, BooleanQuery, FuzzyTermQuery, Occur, PhrasePrefixQuery, Query, RegexQuery,
-        TermQuery,
+        AllQuery, BooleanQuery, FuzzyTermQuery, Occur, PhrasePrefixQuery, PhraseQuery, Query,
+        RangeQuery, RegexQuery, TermQuery,
     },
-    schema::{Field, IndexRecordOption, Schema},
+    schema::{Field, FieldType, IndexRecordOption, Schema},
 };
 
 use super::datafusion::udf::fuzzy_match_udf;
 use crate::datafusion::udf::{
-    MATCH_FIELD_IGNORE_CASE_UDF_NAME, MATCH_FIELD_UDF_NAME, STR_MATCH_UDF_IGNORE_CASE_NAME,
-    STR_MATCH_UDF_NAME,
+    FUZZY_MATCH_UDF_NAME, MATCH_FIELD_IGNORE_CASE_UDF_NAME, MATCH_FIELD_UDF_NAME,
+    MATCH_PHRASE_UDF_NAME, STR_MATCH_UDF_IGNORE_CASE_NAME, STR_MATCH_UDF_NAME,
+    ip_udf::{self, Cidr, IP_IN_CIDR_UDF_NAME},
     match_all_udf::{FUZZY_MATCH_ALL_UDF_NAME, MATCH_ALL_UDF_NAME},
-    str_match_udf,
+    match_phrase_udf, str_match_udf,
+    vector_udf::{self, KNN_UDF_NAME},
 };
 
 // note the condition in IndexCondition is connection by AND operator
@@ -160,6 +170,15 @@ impl IndexCondition {
             .collect()
     }
 
+    pub fn need_fast_fields(&self) -> Vec<String> {
+        self.conditions
+            .iter()
+            .flat_map(|condition| condition.need_fast_fields())
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect()
+    }
+
     pub fn to_physical_expr(
         &self,
         schema: &arrow_schema::Schema,
@@ -179,6 +198,23 @@ impl IndexCondition {
             .all(|condition| condition.can_remove_filter())
     }
 
+    // the index only narrows down the candidates for these conditions, the
+    // filter must be kept to verify the hits against the data
+    pub fn need_verify(&self) -> bool {
+        self.conditions
+            .iter()
+            .any(|condition| condition.need_verify())
+    }
+
+    // whether any condition is looked up in the full text search field
+    pub fn has_full_text(&self) -> bool {
+        self.conditions.iter().any(|condition| {
+            condition
+                .get_tantivy_fields()
+                .contains(INDEX_FIELD_NAME_FOR_ALL)
+        })
+    }
+
     // use for simple distinct optimization
     pub fn get_str_match_condition(&self) -> Option<(String, bool)> {
         match &self.conditions[0] {
@@ -225,6 +261,16 @@ pub enum Condition {
     MatchAll(String),
     // term, distance
     FuzzyMatchAll(String, u8),
+    // field, term, distance
+    FuzzyMatch(String, String, u8),
+    // field, phrase, slop
+    MatchPhrase(String, String, u32),
+    // field, cidr
+    IpInCidr(String, String),
+    // field, comparison operator (<, <=, >, >=) with the field on the left, value
+    Range(String, Operator, String),
+    // field, query vector (canonical form), k; answered by the vector index
+    Knn(String, String, usize),
     All(),
     Or(Box<Condition>, Box<Condition>),
     And(Box<Condition>, Box<Condition>),
@@ -252,6 +298,9 @@ impl Condition {
                 }
             }
             Condition::Regex(field, value) => format!("{field}=~{value}"),
+            Condition::IpInCidr(field, cidr) => format!("ip_in_cidr({field}, {cidr})"),
+            Condition::Range(field, op, value) => format!("{field}{op}{value}"),
+            Condition::Knn(field, vector, k) => format!("knn({field}, '{vector}', {k})"),
             Condition::MatchAll(value) => {
                 let tokens = o2_collect_search_tokens(value);
                 format!("({INDEX_FIELD_NAME_FOR_ALL}:{value}):({tokens:?})")
@@ -259,6 +308,12 @@ impl Condition {
             Condition::FuzzyMatchAll(value, distance) => {
                 format!("{INDEX_FIELD_NAME_FOR_ALL}:fuzzy({value}, {distance})")
             }
+            Condition::FuzzyMatch(field, value, distance) => {
+                format!("fuzzy_match({field}, '{value}', {distance})")
+            }
+            Condition::MatchPhrase(field, value, slop) => {
+                format!("match_phrase({field}, '{value}', {slop})")
+            }
             Condition::All() => "ALL".to_string(),
             Condition::Or(left, right) => format!("({} OR {})", left.to_query(), right.to_query()),
             Condition::And(left, right) => {
@@ -294,6 +349,24 @@ impl Condition {
                         Condition::NotEqual(field, value)
                     }
                 }
+                Operator::Lt | Operator::LtEq | Operator::Gt | Operator::GtEq => {
+                    if is_physical_value(expr.left()) && is_physical_column(expr.right()) {
+                        // `5000 < latency_ms` is kept as `latency_ms > 5000`
+                        Condition::Range(
+                            get_physical_column_name(expr.right()).to_string(),
+                            expr.op().swap().unwrap(),
+                            get_physical_value(expr.left()),
+                        )
+                    } else if is_physical_value(expr.right()) && is_physical_column(expr.left()) {
+                        Condition::Range(
+                            get_physical_column_name(expr.left()).to_string(),
+                            *expr.op(),
+                            get_physical_value(expr.right()),
+                        )
+                    } else {
+                        unreachable!()
+                    }
+                }
                 Operator::And => Condition::And(
                     Box::new(Condition::from_physical_expr(expr.left())),
                     Box::new(Condition::from_physical_expr(expr.right())),
@@ -317,6 +390,18 @@ impl Condition {
                     let distance = get_physical_value(&expr.args()[1]).parse().unwrap_or(1);
                     Condition::FuzzyMatchAll(value, distance)
                 }
+                FUZZY_MATCH_UDF_NAME => {
+                    let field = get_physical_column_name(&expr.args()[0]).to_string();
+                    let value = get_physical_value(&expr.args()[1]);
+                    let distance = get_physical_value(&expr.args()[2]).parse().unwrap_or(1);
+                    Condition::FuzzyMatch(field, value, distance)
+                }
+                MATCH_PHRASE_UDF_NAME => {
+                    let field = get_physical_column_name(&expr.args()[0]).to_string();
+                    let value = get_physical_value(&expr.args()[1]);
+                    let slop = get_physical_value(&expr.args()[2]).parse().unwrap_or(0);
+                    Condition::MatchPhrase(field, value, slop)
+                }
                 STR_MATCH_UDF_NAME | MATCH_FIELD_UDF_NAME => {
                     let field = get_physical_column_name(&expr.args()[0]).to_string();
                     let value = get_physical_value(&expr.args()[1]);
@@ -327,6 +412,17 @@ impl Condition {
                     let value = get_physical_value(&expr.args()[1]);
                     Condition::StrMatch(field, value, false)
                 }
+                IP_IN_CIDR_UDF_NAME => {
+                    let field = get_physical_column_name(&expr.args()[0]).to_string();
+                    let value = get_physical_value(&expr.args()[1]);
+                    Condition::IpInCidr(field, value)
+                }
+                KNN_UDF_NAME => {
+                    let field = get_physical_column_name(&expr.args()[0]).to_string();
+                    let (vector, k) = get_knn_params(&expr.args()[1], &expr.args()[2])
+                        .expect("knn() params are checked by the optimizer");
+                    Condition::Knn(field, format_vector(&vector), k)
+                }
                 _ => unreachable!(),
             }
         } else if let Some(expr) = expr.downcast_ref::<NotExpr>() {
@@ -383,6 +479,33 @@ impl Condition {
                 let field = schema.get_field(field)?;
                 Box::new(ContainsQuery::new(value, field, *case_sensitive)?)
             }
+            Condition::IpInCidr(field, cidr) => {
+                let field = schema.get_field(field)?;
+                let pattern = Cidr::parse(cidr)
+                    .and_then(|cidr| cidr.ipv4_regex())
+                    .ok_or_else(|| anyhow::anyhow!("ip_in_cidr() needs an IPv4 network"))?;
+                Box::new(RegexQuery::from_pattern(&pattern, field)?)
+            }
+            Condition::Range(field, op, value) => {
+                let field = schema.get_field(&to_range_index_field_name(field))?;
+                let term = match schema.get_field_entry(field).field_type() {
+                    FieldType::I64(_) => Term::from_field_i64(field, value.parse()?),
+                    FieldType::U64(_) => Term::from_field_u64(field, value.parse()?),
+                    FieldType::F64(_) => Term::from_field_f64(field, value.parse()?),
+                    other => anyhow::bail!("range index does not support {:?}", other.value_type()),
+                };
+                let (lower, upper) = match op {
+                    Operator::Gt => (Bound::Excluded(term), Bound::Unbounded),
+                    Operator::GtEq => (Bound::Included(term), Bound::Unbounded),
+                    Operator::Lt => (Bound::Unbounded, Bound::Excluded(term)),
+                    Operator::LtEq => (Bound::Unbounded, Bound::Included(term)),
+                    _ => anyhow::bail!("range index does not support operator {op}"),
+                };
+                Box::new(RangeQuery::new(lower, upper))
+            }
+            Condition::Knn(..) => {
+                anyhow::bail!("knn() is answered by the vector index, not by tantivy")
+            }
             Condition::MatchAll(value) => {
                 let default_field = default_field.ok_or_else(|| {
                     anyhow::anyhow!("There's no FullTextSearch field for match_all() function")
@@ -390,7 +513,8 @@ impl Condition {
                 if value.is_empty() || value == "*" {
                     Box::new(AllQuery {})
                 } else {
-                    let mut tokens = o2_collect_search_tokens(value);
+                    let tokenizer = schema_fts_tokenizer(schema);
+                    let mut tokens = collect_search_tokens(&tokenizer, value.trim_matches('*'));
                     let contains_search =
                         tokens.len() == 1 && value.starts_with("*") && value.ends_with("*");
                     let first_prefix = if value.starts_with("*") && !tokens.is_empty() {
@@ -451,6 +575,61 @@ impl Condition {
                 let term = Term::from_field_text(default_field, value);
                 Box::new(FuzzyTermQuery::new(term, *distance, false))
             }
+            Condition::FuzzyMatch(_, value, distance) => {
+                // full text search fields share one index field, the filter
+                // is kept to check the hit came from the requested field
+                let default_field = default_field.ok_or_else(|| {
+                    anyhow::anyhow!("There's no FullTextSearch field for fuzzy_match() function")
+                })?;
+                if value.is_empty() {
+                    return Err(anyhow::anyhow!(
+                        "The value of fuzzy_match() function can't be empty"
+                    ));
+                }
+                let term = Term::from_field_text(default_field, value);
+                Box::new(FuzzyTermQuery::new(term, *distance, false))
+            }
+            Condition::MatchPhrase(_, value, slop) => {
+                let default_field = default_field.ok_or_else(|| {
+                    anyhow::anyhow!("There's no FullTextSearch field for match_phrase() function")
+                })?;
+                let tokenizer = schema_fts_tokenizer(schema);
+                // only the default tokenizer emits one token per word, so the
+                // positions of the others can't be compared with the phrase
+                let tokens = if tokenizer == FtsTokenizer::O2 {
+                    collect_phrase_tokens(&tokenizer, value)
+                } else {
+                    collect_search_tokens(&tokenizer, value)
+                        .into_iter()
+                        .enumerate()
+                        .collect()
+                };
+                let mut terms: Vec<(usize, Term)> = tokens
+                    .into_iter()
+                    .map(|(pos, text)| (pos, Term::from_field_text(default_field, &text)))
+                    .collect();
+                if terms.is_empty() {
+                    return Err(anyhow::anyhow!(
+                        "The value of match_phrase() function can't be empty"
+                    ));
+                }
+                if terms.len() == 1 {
+                    Box::new(TermQuery::new(terms.remove(0).1, IndexRecordOption::Basic))
+                } else if tokenizer == FtsTokenizer::O2 && has_positions(schema, default_field) {
+                    Box::new(PhraseQuery::new_with_offset_and_slop(terms, *slop))
+                } else {
+                    // the index was built without positions, narrow down to the
+                    // docs having all the tokens and leave the order to the filter
+                    Box::new(BooleanQuery::intersection(
+                        terms
+                            .into_iter()
+                            .map(|(_, term)| {
+                                Box::new(TermQuery::new(term, IndexRecordOption::Basic)) as _
+                            })
+                            .collect(),
+                    ))
+                }
+            }
             Condition::All() => Box::new(AllQuery {}),
             Condition::Or(left, right) => {
                 let left_query = left.to_tantivy_query(schema, default_field)?;
@@ -477,7 +656,9 @@ impl Condition {
     pub fn need_all_term_fields(&self) -> HashSet<String> {
         let mut fields = HashSet::new();
         match self {
-            Condition::StrMatch(field, ..) | Condition::Regex(field, _) => {
+            Condition::StrMatch(field, ..)
+            | Condition::Regex(field, _)
+            | Condition::IpInCidr(field, _) => {
                 fields.insert(field.clone());
             }
             Condition::MatchAll(value) => {
@@ -485,7 +666,7 @@ impl Condition {
                     fields.insert(INDEX_FIELD_NAME_FOR_ALL.to_string());
                 }
             }
-            Condition::FuzzyMatchAll(..) => {
+            Condition::FuzzyMatchAll(..) | Condition::FuzzyMatch(..) => {
                 fields.insert(INDEX_FIELD_NAME_FOR_ALL.to_string());
             }
             Condition::Or(left, right) | Condition::And(left, right) => {
@@ -498,12 +679,33 @@ impl Condition {
             Condition::All()
             | Condition::Equal(..)
             | Condition::NotEqual(..)
-            | Condition::In(..) => {}
+            | Condition::In(..)
+            | Condition::Range(..)
+            | Condition::Knn(..)
+            | Condition::MatchPhrase(..) => {}
+        }
+        fields
+    }
+
+    /// Fast fields read by the query instead of postings.
+    pub fn need_fast_fields(&self) -> HashSet<String> {
+        let mut fields = HashSet::new();
+        match self {
+            Condition::Range(field, ..) => {
+                fields.insert(to_range_index_field_name(field));
+            }
+            Condition::Or(left, right) | Condition::And(left, right) => {
+                fields.extend(left.need_fast_fields());
+                fields.extend(right.need_fast_fields());
+            }
+            Condition::Not(condition) => {
+                fields.extend(condition.need_fast_fields());
+            }
+            _ => {}
         }
         fields
     }
 
-    #[allow(dead_code)]
     pub fn get_tantivy_fields(&self) -> HashSet<String> {
         let mut fields = HashSet::new();
         match self {
@@ -511,13 +713,20 @@ impl Condition {
             | Condition::NotEqual(field, _)
             | Condition::In(field, ..)
             | Condition::Regex(field, _)
-            | Condition::StrMatch(field, ..) => {
+            | Condition::StrMatch(field, ..)
+            | Condition::IpInCidr(field, _) => {
                 fields.insert(field.clone());
             }
-            Condition::MatchAll(_) | Condition::FuzzyMatchAll(..) => {
+            Condition::MatchAll(_)
+            | Condition::FuzzyMatchAll(..)
+            | Condition::FuzzyMatch(..)
+            | Condition::MatchPhrase(..) => {
                 fields.insert(INDEX_FIELD_NAME_FOR_ALL.to_string());
             }
-            Condition::All() => {}
+            Condition::Range(field, ..) => {
+                fields.insert(to_range_index_field_name(field));
+            }
+            Condition::Knn(..) | Condition::All() => {}
             Condition::Or(left, right) | Condition::And(left, right) => {
                 fields.extend(left.get_tantivy_fields());
                 fields.extend(right.get_tantivy_fields());
@@ -537,7 +746,12 @@ impl Condition {
             | Condition::NotEqual(field, _)
             | Condition::StrMatch(field, ..)
             | Condition::In(field, ..)
-            | Condition::Regex(field, _) => {
+            | Condition::Regex(field, _)
+            | Condition::FuzzyMatch(field, ..)
+            | Condition::MatchPhrase(field, ..)
+            | Condition::IpInCidr(field, _)
+            | Condition::Range(field, ..)
+            | Condition::Knn(field, ..) => {
                 fields.insert(field.clone());
             }
             Condition::MatchAll(_) | Condition::FuzzyMatchAll(..) => {
@@ -594,6 +808,15 @@ impl Condition {
             Condition::Regex(..) => {
                 unreachable!("Condition::Regex query only support for promql")
             }
+            Condition::IpInCidr(name, cidr) => create_ip_in_cidr_expr(schema, name, cidr),
+            Condition::Range(name, op, value) => {
+                let index = schema.index_of(name).unwrap();
+                let left = Arc::new(Column::new(name, index));
+                let field = schema.field(index);
+                let right = get_scalar_value(value, field.data_type())?;
+                Ok(Arc::new(BinaryExpr::new(left, *op, right)))
+            }
+            Condition::Knn(name, vector, k) => create_knn_expr(schema, name, vector, *k),
             Condition::MatchAll(value) => {
                 let value = value
                     .trim_start_matches("re:") // regex
@@ -669,6 +892,20 @@ impl Condition {
                 }
                 Ok(disjunction(expr_list))
             }
+            Condition::FuzzyMatch(name, value, distance) => create_field_udf_expr(
+                schema,
+                Arc::new(fuzzy_match_udf::FUZZY_MATCH_UDF.clone()),
+                name,
+                value,
+                *distance as i64,
+            ),
+            Condition::MatchPhrase(name, value, slop) => create_field_udf_expr(
+                schema,
+                Arc::new(match_phrase_udf::MATCH_PHRASE_UDF.clone()),
+                name,
+                value,
+                *slop as i64,
+            ),
             Condition::All() => Ok(Arc::new(Literal::new(ScalarValue::Boolean(Some(true))))),
             Condition::Or(left, right) => {
                 let left = left.to_physical_expr(schema, fst_fields)?;
@@ -687,6 +924,19 @@ impl Condition {
         }
     }
 
+    pub fn need_verify(&self) -> bool {
+        match self {
+            // the full text search fields share one index field, a hit may come from
+            // another field, and phrases fall back to plain terms without positions
+            Condition::FuzzyMatch(..) | Condition::MatchPhrase(..) => true,
+            Condition::Or(left, right) | Condition::And(left, right) => {
+                left.need_verify() || right.need_verify()
+            }
+            Condition::Not(condition) => condition.need_verify(),
+            _ => false,
+        }
+    }
+
     pub fn can_remove_filter(&self) -> bool {
         match self {
             Condition::Equal(..) => true,
@@ -694,8 +944,18 @@ impl Condition {
             Condition::StrMatch(..) => true,
             Condition::In(..) => true,
             Condition::Regex(..) => false,
+            // ip_in_cidr() accepts addresses with surrounding whitespace that
+            // the regex does not match, the filter verifies the index hits
+            Condition::IpInCidr(..) => false,
+            // the fast field holds the exact column values, the files with
+            // values that couldn't be indexed add the filter back
+            Condition::Range(..) => true,
+            // the index hits are the candidates of the file, the global top k
+            // is picked above the filter (see RewriteKnnRule)
+            Condition::Knn(..) => true,
             Condition::MatchAll(v) => is_alphanumeric(v),
             Condition::FuzzyMatchAll(..) => false,
+            Condition::FuzzyMatch(..) | Condition::MatchPhrase(..) => false,
             Condition::All() => true,
             Condition::Or(left, right) => left.can_remove_filter() && right.can_remove_filter(),
             Condition::And(left, right) => left.can_remove_filter() && right.can_remove_filter(),
@@ -748,6 +1008,16 @@ fn get_physical_value(expr: &Arc<dyn PhysicalExpr>) -> String {
     }
 }
 
+// the literal query vector and k of `knn(field, '[..]', k)`
+pub(crate) fn get_knn_params(
+    vector: &Arc<dyn PhysicalExpr>,
+    k: &Arc<dyn PhysicalExpr>,
+) -> Option<(Vec<f32>, usize)> {
+    let vector = vector.downcast_ref::<Literal>()?;
+    let k = k.downcast_ref::<Literal>()?;
+    vector_udf::knn_params(vector.value(), k.value())
+}
+
 // combine all exprs with OR operator
 fn disjunction(exprs: Vec<Arc<dyn PhysicalExpr>>) -> Arc<dyn PhysicalExpr> {
     if exprs.len() == 1 {
@@ -835,6 +1105,87 @@ fn create_str_match_expr(
     Ok(udf_expr)
 }
 
+// create `ip_in_cidr(field, 'cidr')`
+fn create_ip_in_cidr_expr(
+    schema: &arrow_schema::Schema,
+    name: &str,
+    cidr: &str,
+) -> Result<Arc<dyn PhysicalExpr>, anyhow::Error> {
+    let index = schema.index_of(name)?;
+    let field = schema.field(index);
+    let col = Arc::new(Column::new(name, index));
+    // the udf signature is Utf8, cast the other string types
+    let left: Arc<dyn PhysicalExpr> = if *field.data_type() == DataType::Utf8 {
+        col
+    } else {
+        Arc::new(CastExpr::new(col, DataType::Utf8, None))
+    };
+    let cidr = Arc::new(Literal::new(ScalarValue::Utf8(Some(cidr.to_string()))));
+    Ok(Arc::new(ScalarFunctionExpr::try_new(
+        Arc::new(ip_udf::IP_IN_CIDR_UDF.clone()),
+        vec![left, cidr],
+        schema,
+        Arc::new(ConfigOptions::default()),
+    )?))
+}
+
+// create `knn(field, '[..]', k)`
+fn create_knn_expr(
+    schema: &arrow_schema::Schema,
+    name: &str,
+    vector: &str,
+    k: usize,
+) -> Result<Arc<dyn PhysicalExpr>, anyhow::Error> {
+    // the vector column is passed as is, knn() accepts any vector type
+    let index = schema.index_of(name)?;
+    let col = Arc::new(Column::new(name, index));
+    let vector = Arc::new(Literal::new(ScalarValue::Utf8(Some(vector.to_string()))));
+    let k = Arc::new(Literal::new(ScalarValue::Int64(Some(k as i64))));
+    Ok(Arc::new(ScalarFunctionExpr::try_new(
+        Arc::new(vector_udf::KNN_UDF.clone()),
+        vec![col, vector, k],
+        schema,
+        Arc::new(ConfigOptions::default()),
+    )?))
+}
+
+// create `udf(field, 'value', n)`, used by fuzzy_match and match_phrase
+fn create_field_udf_expr(
+    schema: &arrow_schema::Schema,
+    udf: Arc<datafusion::logical_expr::ScalarUDF>,
+    name: &str,
+    value: &str,
+    n: i64,
+) -> Result<Arc<dyn PhysicalExpr>, anyhow::Error> {
+    let index = schema.index_of(name)?;
+    let field = schema.field(index);
+    let col = Arc::new(Column::new(name, index));
+    // the udf signature is Utf8, cast the other string types
+    let left: Arc<dyn PhysicalExpr> = if *field.data_type() == DataType::Utf8 {
+        col
+    } else {
+        Arc::new(CastExpr::new(col, DataType::Utf8, None))
+    };
+    let value = Arc::new(Literal::new(ScalarValue::Utf8(Some(value.to_string()))));
+    let n = Arc::new(Literal::new(ScalarValue::Int64(Some(n))));
+    Ok(Arc::new(ScalarFunctionExpr::try_new(
+        udf,
+        vec![left, value, n],
+        schema,
+        Arc::new(ConfigOptions::default()),
+    )?))
+}
+
+// check if the index field stores token positions, which phrase queries need
+fn has_positions(schema: &Schema, field: Field) -> bool {
+    match schema.get_field_entry(field).field_type() {
+        FieldType::Str(opts) => opts
+            .get_indexing_options()
+            .is_some_and(|opts| opts.index_option().has_positions()),
+        _ => false,
+    }
+}
+
 fn is_alphanumeric(s: &str) -> bool {
     s.chars().all(|c| c.is_ascii_alphanumeric())
 }
@@ -1465,4 +1816,222 @@ mod tests {
             "should return error when the only field is missing"
         );
     }
+
+    /// Collect whether each term of the query needs positions.
+    fn collect_position_needs(query: &dyn Query) -> Vec<bool> {
+        let mut needs = Vec::new();
+        query.query_terms(&mut |_, need_positions| needs.push(need_positions));
+        needs
+    }
+
+    #[test]
+    fn test_match_phrase_uses_positions_when_indexed() {
+        let schema = build_tantivy_schema(&[INDEX_FIELD_NAME_FOR_ALL]);
+        let default_field = schema.get_field(INDEX_FIELD_NAME_FOR_ALL).ok();
+        let cond = Condition::MatchPhrase("log".into(), "connection reset by peer".into(), 0);
+        let query = cond.to_tantivy_query(&schema, default_field).unwrap();
+        assert_eq!(collect_position_needs(query.as_ref()), vec![true; 4]);
+    }
+
+    #[test]
+    fn test_match_phrase_without_positions_falls_back_to_terms() {
+        let mut builder = Schema::builder();
+        builder.add_text_field(
+            INDEX_FIELD_NAME_FOR_ALL,
+            tantivy::schema::TextOptions::default().set_indexing_options(
+                tantivy::schema::TextFieldIndexing::default()
+                    .set_index_option(IndexRecordOption::Basic),
+            ),
+        );
+        let schema = builder.build();
+        let default_field = schema.get_field(INDEX_FIELD_NAME_FOR_ALL).ok();
+        let cond = Condition::MatchPhrase("log".into(), "connection reset".into(), 0);
+        let query = cond.to_tantivy_query(&schema, default_field).unwrap();
+        assert_eq!(collect_position_needs(query.as_ref()), vec![false; 2]);
+    }
+
+    #[test]
+    fn test_match_all_uses_index_tokenizer() {
+        let mut builder = Schema::builder();
+        builder.add_text_field(
+            INDEX_FIELD_NAME_FOR_ALL,
+            tantivy::schema::TextOptions::default().set_indexing_options(
+                tantivy::schema::TextFieldIndexing::default()
+                    .set_index_option(IndexRecordOption::WithFreqsAndPositions)
+                    .set_tokenizer("o2_cjk_bigram"),
+            ),
+        );
+        let schema = builder.build();
+        let default_field = schema.get_field(INDEX_FIELD_NAME_FOR_ALL).ok();
+        let mut terms = Vec::new();
+        let cond = Condition::MatchAll("数据库".into());
+        let query = cond.to_tantivy_query(&schema, default_field).unwrap();
+        query.query_terms(&mut |term, _| terms.push(term.value().as_str().unwrap().to_string()));
+        assert_eq!(terms, vec!["数据", "据库"]);
+
+        // bigram positions don't line up with the phrase, use plain terms
+        let cond = Condition::MatchPhrase("log".into(), "数据库".into(), 0);
+        let query = cond.to_tantivy_query(&schema, default_field).unwrap();
+        assert_eq!(collect_position_needs(query.as_ref()), vec![false; 2]);
+    }
+
+    #[test]
+    fn test_match_phrase_requires_fts_field_and_tokens() {
+        let schema = build_tantivy_schema(&[INDEX_FIELD_NAME_FOR_ALL]);
+        let default_field = schema.get_field(INDEX_FIELD_NAME_FOR_ALL).ok();
+        let cond = Condition::MatchPhrase("log".into(), "connection reset".into(), 0);
+        assert!(cond.to_tantivy_query(&schema, None).is_err());
+        let cond = Condition::MatchPhrase("log".into(), "!".into(), 0);
+        assert!(cond.to_tantivy_query(&schema, default_field).is_err());
+    }
+
+    #[test]
+    fn test_fts_field_conditions_keep_filter() {
+        let phrase = Condition::MatchPhrase("log".into(), "connection reset".into(), 1);
+        let fuzzy = Condition::FuzzyMatch("log".into(), "conection".into(), 1);
+        assert!(!phrase.can_remove_filter());
+        assert!(!fuzzy.can_remove_filter());
+        assert!(phrase.need_verify());
+        assert!(Condition::Not(Box::new(fuzzy.clone())).need_verify());
+        assert!(!Condition::MatchAll("error".into()).need_verify());
+        assert_eq!(
+            phrase.to_query(),
+            "match_phrase(log, 'connection reset', 1)"
+        );
+        assert!(phrase.need_all_term_fields().is_empty());
+        assert!(
+            fuzzy
+                .need_all_term_fields()
+                .contains(INDEX_FIELD_NAME_FOR_ALL)
+        );
+        assert_eq!(
+            phrase.get_schema_fields(&["log".to_string(), "message".to_string()]),
+            HashSet::from(["log".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_ip_in_cidr_query() {
+        let mut builder = Schema::builder();
+        let field = builder.add_text_field("client_ip", tantivy::schema::STRING);
+        let index = tantivy::Index::create_in_ram(builder.build());
+        let mut writer = index.writer_with_num_threads(1, 15_000_000).unwrap();
+        for ip in [
+            "10.0.0.1",
+            "10.20.30.40",
+            "11.0.0.1",
+            "172.16.5.4",
+            "172.32.0.1",
+        ] {
+            writer.add_document(tantivy::doc!(field => ip)).unwrap();
+        }
+        writer.commit().unwrap();
+        let searcher = index.reader().unwrap().searcher();
+        let count = |cidr: &str| {
+            let cond = Condition::IpInCidr("client_ip".into(), cidr.into());
+            let query = cond.to_tantivy_query(&index.schema(), None).unwrap();
+            searcher.search(&query, &tantivy::collector::Count).unwrap()
+        };
+        assert_eq!(count("10.0.0.0/8"), 2);
+        assert_eq!(count("172.16.0.0/12"), 1);
+        assert_eq!(count("10.0.0.1/32"), 1);
+        assert_eq!(count("0.0.0.0/0"), 5);
+
+        let cond = Condition::IpInCidr("client_ip".into(), "2001:db8::/32".into());
+        assert!(cond.to_tantivy_query(&index.schema(), None).is_err());
+        assert!(!cond.can_remove_filter());
+        assert!(cond.need_all_term_fields().contains("client_ip"));
+        assert_eq!(cond.to_query(), "ip_in_cidr(client_ip, 2001:db8::/32)");
+    }
+
+    #[test]
+    fn test_range_query() {
+        let mut builder = Schema::builder();
+        let field = builder.add_i64_field(
+            &to_range_index_field_name("latency_ms"),
+            tantivy::schema::FAST,
+        );
+        let index = tantivy::Index::create_in_ram(builder.build());
+        let mut writer = index.writer_with_num_threads(1, 15_000_000).unwrap();
+        for latency in [10i64, 500, 5000, 5001, 90000] {
+            writer
+                .add_document(tantivy::doc!(field => latency))
+                .unwrap();
+        }
+        // a row without a value never matches
+        writer.add_document(tantivy::doc!()).unwrap();
+        writer.commit().unwrap();
+        let searcher = index.reader().unwrap().searcher();
+        let count = |op: Operator, value: &str| {
+            let cond = Condition::Range("latency_ms".into(), op, value.into());
+            let query = cond.to_tantivy_query(&index.schema(), None).unwrap();
+            searcher.search(&query, &tantivy::collector::Count).unwrap()
+        };
+        assert_eq!(count(Operator::Gt, "5000"), 2);
+        assert_eq!(count(Operator::GtEq, "5000"), 3);
+        assert_eq!(count(Operator::Lt, "500"), 1);
+        assert_eq!(count(Operator::LtEq, "500"), 2);
+
+        // a value that doesn't fit the indexed type is skipped, keeping the filter
+        let cond = Condition::Range("latency_ms".into(), Operator::Gt, "1.5".into());
+        assert!(cond.to_tantivy_query(&index.schema(), None).is_err());
+        // so is a file indexed before the field got a range index
+        let cond = Condition::Range("other".into(), Operator::Gt, "1".into());
+        assert!(cond.to_tantivy_query(&index.schema(), None).is_err());
+    }
+
+    #[test]
+    fn test_range_condition_from_physical_expr() {
+        let schema = arrow_schema::Schema::new(vec![arrow_schema::Field::new(
+            "latency_ms",
+            DataType::Int64,
+            true,
+        )]);
+        let column: Arc<dyn PhysicalExpr> = Arc::new(Column::new("latency_ms", 0));
+        let value: Arc<dyn PhysicalExpr> = Arc::new(Literal::new(ScalarValue::Int64(Some(5000))));
+
+        let expr: Arc<dyn PhysicalExpr> =
+            Arc::new(BinaryExpr::new(value, Operator::Lt, column.clone()));
+        let cond = Condition::from_physical_expr(&expr);
+        assert_eq!(
+            cond,
+            Condition::Range("latency_ms".into(), Operator::Gt, "5000".into())
+        );
+        assert_eq!(cond.to_query(), "latency_ms>5000");
+        assert!(cond.can_remove_filter());
+        assert!(cond.need_all_term_fields().is_empty());
+        assert_eq!(
+            cond.need_fast_fields(),
+            HashSet::from([to_range_index_field_name("latency_ms")])
+        );
+        assert_eq!(
+            cond.get_schema_fields(&[]),
+            HashSet::from(["latency_ms".to_string()])
+        );
+
+        let expr = cond.to_physical_expr(&schema, &[]).unwrap();
+        assert_eq!(expr.to_string(), "latency_ms@0 > 5000");
+    }
+
+    #[test]
+    fn test_knn_condition() {
+        let schema = arrow_schema::Schema::new(vec![arrow_schema::Field::new(
+            "embedding",
+            config::utils::vector::vector_data_type(2),
+            true,
+        )]);
+        let cond = Condition::Knn("embedding".into(), "[0.5,1]".into(), 3);
+        let expr = cond.to_physical_expr(&schema, &[]).unwrap();
+        // the physical expr round trips to the same condition
+        assert_eq!(Condition::from_physical_expr(&expr), cond);
+        assert_eq!(cond.to_query(), "knn(embedding, '[0.5,1]', 3)");
+        assert!(cond.can_remove_filter());
+        assert!(cond.get_tantivy_fields().is_empty());
+        assert_eq!(
+            cond.get_schema_fields(&[]),
+            HashSet::from(["embedding".to_string()])
+        );
+        let tantivy_schema = build_tantivy_schema(&[INDEX_FIELD_NAME_FOR_ALL]);
+        assert!(cond.to_tantivy_query(&tantivy_schema, None).is_err());
+    }
 }
@@ -26,7 +26,10 @@ use config::{
         sql::{OrderBy, TableReferenceExt, resolve_stream_names_with_type},
         stream::StreamType,
     },
-    utils::{query_select_utils::replace_o2_custom_patterns, sql::is_complex_query_stmt},
+    utils::{
+        query_select_utils::replace_o2_custom_patterns,
+        sql::{is_complex_query_stmt, table_function::resolve_table_functions_from_sql},
+    },
 };
 use datafusion::{arrow::datatypes::Schema, common::TableReference};
 use hashbrown::{HashMap, HashSet};
@@ -121,13 +124,31 @@ impl Sql {
         // 1. get table name
         let stream_names = resolve_stream_names_with_type(&sql)
             .map_err(|e| Error::ErrorCode(ErrorCodes::SearchSQLNotValid(e.to_string())))?;
+        if stream_names.is_empty()
+            && !resolve_table_functions_from_sql(&sql)
+                .map_err(|e| Error::ErrorCode(ErrorCodes::SearchSQLNotValid(e)))?
+                .is_empty()
+        {
+            return Err(Error::ErrorCode(ErrorCodes::SearchSQLNotValid(
+                "table functions have to be used together with a stream".to_string(),
+            )));
+        }
         let mut total_schemas = HashMap::with_capacity(stream_names.len());
         for stream in stream_names.iter() {
             let stream_name = stream.stream_name();
             let stream_type = stream.get_stream_type(stream_type);
-            let schema = infra::schema::get(org_id, &stream_name, stream_type)
-                .await
-                .unwrap_or_else(|_| Schema::empty());
+            let schema = if stream.is_external_table() {
+                infra::schema::external_table::get(org_id, &stream_name)
+                    .await
+                    .ok()
+                    .flatten()
+                    .and_then(|table| table.schema)
+                    .unwrap_or_else(Schema::empty)
+            } else {
+                infra::schema::get(org_id, &stream_name, stream_type)
+                    .await
+                    .unwrap_or_else(|_| Schema::empty())
+            };
             if schema.fields().is_empty() {
                 return Err(Error::ErrorCode(ErrorCodes::SearchStreamNotFound(
                     stream_name,
@@ -259,8 +280,9 @@ impl Sql {
         let timezone = query.timezone.clone();
 
         //********************Change the sql start*********************************//
-        // 11. add _timestamp and _o2_id if need
-        if !is_complex_query_stmt(&statement) {
+        // 11. add _timestamp and _o2_id if need, external tables may not have _timestamp
+        if !is_complex_query_stmt(&statement) && !stream_names.iter().any(|s| s.is_external_table())
+        {
             let mut add_timestamp_visitor = AddTimestampVisitor::new();
             let _ = statement.visit(&mut add_timestamp_visitor);
             if o2_id_is_needed(&used_schemas, &search_event_type) {
@@ -70,8 +70,12 @@ impl CacheAggregationVisitor {
             FunctionPair::new("MAX", "MAX"),   // MAX in outer, MAX in inner
             FunctionPair::new("MIN", "MIN"),   // MIN in outer, MIN in inner
             FunctionPair::new("SUM", "SUM"),   // SUM in outer, SUM in inner
-            FunctionPair::new("", "APPROX_TOPK"), /* Special case: outer query uses alias directly,
-                                                * inner has approx_topk */
+            FunctionPair::new("HLL_ESTIMATE", "HLL_SKETCH"), // sketches merge exactly
+            FunctionPair::new("HLL_MERGE", "HLL_SKETCH"),
+            FunctionPair::new("QUANTILE_FROM_SKETCH", "TDIGEST_SKETCH"),
+            FunctionPair::new("", "APPROX_TOPK"), /* Special case: outer query uses alias
+                                                   * directly,
+                                                   * inner has approx_topk */
             FunctionPair::new("", "APPROX_TOPK_DISTINCT"), /* Special case: outer query uses
                                                             * alias directly, inner has
                                                             * approx_topk_distinct */
@@ -658,7 +662,10 @@ impl CacheAggregationVisitor {
         }
 
         // Define functions that are allowed without GROUP BY when both queries have no GROUP BY
-        let allows_no_group_by = matches!(sub_function.as_str(), "SUM" | "MAX" | "MIN");
+        let allows_no_group_by = matches!(
+            sub_function.as_str(),
+            "SUM" | "MAX" | "MIN" | "HLL_SKETCH" | "TDIGEST_SKETCH"
+        );
 
         // For patterns that allow no GROUP BY: Allow if both have no GROUP BY, or both have same
         // GROUP BY
@@ -946,6 +953,38 @@ mod tests {
         assert!(result);
     }
 
+    #[test]
+    fn test_sketch_patterns() {
+        let sql = r#"
+            SELECT ts, hll_estimate(users) as distinct_users
+            FROM (
+                SELECT histogram(_timestamp) as ts, hll_sketch(user_id) as users
+                FROM "default"
+                GROUP BY ts
+            )
+            GROUP BY ts
+        "#;
+        assert!(matches_streaming_aggregate_pattern(sql).unwrap());
+
+        let sql = r#"
+            SELECT quantile_from_sketch(latency, 0.99) as p99
+            FROM (
+                SELECT tdigest_sketch(took) as latency
+                FROM "default"
+            )
+        "#;
+        assert!(matches_streaming_aggregate_pattern(sql).unwrap());
+
+        let sql = r#"
+            SELECT hll_estimate(users) as distinct_users
+            FROM (
+                SELECT tdigest_sketch(took) as users
+                FROM "default"
+            )
+        "#;
+        assert!(!matches_streaming_aggregate_pattern(sql).unwrap());
+    }
+
     #[test]
     fn test_no_pattern_different_group_by() {
         let sql = r#"
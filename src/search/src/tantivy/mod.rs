@@ -20,7 +20,7 @@ mod result;
 pub mod search;
 mod warm;
 
-use std::{ops::Bound, sync::Arc};
+use std::{ops::Bound, path::Path, sync::Arc};
 
 use arrow::{
     buffer::{BooleanBuffer, MutableBuffer},
@@ -36,22 +36,28 @@ use config::{
         stream::{FileKey, FileSelection, StreamType},
     },
     metrics::{self, QUERY_PARQUET_CACHE_RATIO_NODE},
-    tantivy::tokenizer::{CollectType, O2_TOKENIZER, o2_tokenizer_build},
-    utils::{inverted_index::to_tantivy_name, size::bytes_to_human_readable},
+    tantivy::{
+        query::doc_set_query::DocSetQuery,
+        tokenizer::{CollectType, register_fts_tokenizer},
+    },
+    utils::{inverted_index::to_tantivy_name, size::bytes_to_human_readable, vector::parse_vector},
 };
 use futures::{StreamExt, stream};
-use hashbrown::HashMap;
+use hashbrown::{HashMap, HashSet};
 use infra::{cache::file_data, errors::Error};
 use itertools::Itertools;
 pub use result::{TantivyMultiResult, TantivyMultiResultBuilder};
 pub use search::TantivyResult;
 use tantivy::{
     Directory, ReloadPolicy, Term,
-    query::{BooleanQuery, Occur, RangeQuery},
+    query::{AllQuery, BooleanQuery, Occur, Query, RangeQuery},
 };
-use tantivy_utils::puffin_directory::{
-    PROP_ROW_GROUP_SIZE, caching_directory::CachingDirectory, footer_cache::FooterCache,
-    reader::PuffinDirReader,
+use tantivy_utils::{
+    puffin_directory::{
+        PROP_INEXACT_RANGE_FIELDS, PROP_ROW_GROUP_SIZE, caching_directory::CachingDirectory,
+        footer_cache::FooterCache, reader::PuffinDirReader,
+    },
+    vector_index::{DEFAULT_EF_SEARCH, VectorIndex, vector_index_file_name},
 };
 use tokio::sync::Semaphore;
 use tokio_stream::StreamExt as _;
@@ -451,13 +457,11 @@ async fn search_tantivy_index(
         .get_property(PROP_ROW_GROUP_SIZE)
         .and_then(|s| s.parse::<u32>().ok());
     let footer_cache = FooterCache::from_directory(puffin_dir.clone(), &ttv_file_name).await?;
-    let cache_dir = CachingDirectory::new_with_cacher(puffin_dir, Arc::new(footer_cache));
+    let cache_dir = CachingDirectory::new_with_cacher(puffin_dir.clone(), Arc::new(footer_cache));
     let reader_directory: Box<dyn Directory> = Box::new(cache_dir);
 
     let index = tantivy::Index::open(reader_directory)?;
-    index
-        .tokenizers()
-        .register(O2_TOKENIZER, o2_tokenizer_build(CollectType::Search));
+    register_fts_tokenizer(index.tokenizers(), &index.schema(), CollectType::Search);
     let reader = index
         .reader_builder()
         .reload_policy(ReloadPolicy::Manual)
@@ -480,8 +484,60 @@ async fn search_tantivy_index(
     // generate the tantivy query
     let condition: IndexCondition =
         index_condition.ok_or(anyhow::anyhow!("IndexCondition not found"))?;
-    let (mut query, has_skipped_conditions) =
-        condition.to_tantivy_query(trace_id, tantivy_schema.clone(), fts_field)?;
+    // knn conditions are answered by the vector index stored next to the
+    // tantivy files, a knn filter is only planned when knn() is the only
+    // condition of the WHERE clause so it holds no other index condition
+    let (knn_conditions, mut other_conditions): (Vec<_>, Vec<_>) = condition
+        .conditions
+        .iter()
+        .cloned()
+        .partition(|c| matches!(c, Condition::Knn(..)));
+    // the range index of these fields misses values of this file, their
+    // conditions are evaluated by datafusion
+    let inexact_range_fields = puffin_dir
+        .get_property(PROP_INEXACT_RANGE_FIELDS)
+        .map(|v| v.split(',').map(|f| f.to_string()).collect::<HashSet<_>>())
+        .unwrap_or_default();
+    let num_conditions = other_conditions.len();
+    other_conditions.retain(
+        |c| !matches!(c, Condition::Range(field, ..) if inexact_range_fields.contains(field)),
+    );
+    let has_inexact_conditions = other_conditions.len() < num_conditions;
+    let (mut query, mut has_skipped_conditions) = if other_conditions.is_empty() {
+        (Box::new(AllQuery) as Box<dyn Query>, has_inexact_conditions)
+    } else {
+        IndexCondition {
+            conditions: other_conditions,
+        }
+        .to_tantivy_query(trace_id, tantivy_schema.clone(), fts_field)
+        .map(|(query, skipped)| (query, skipped || has_inexact_conditions))?
+    };
+    for knn in knn_conditions {
+        let Condition::Knn(field, vector, k) = knn else {
+            continue;
+        };
+        let Some(data) = puffin_dir
+            .read_file(Path::new(&vector_index_file_name(&field)))
+            .await?
+        else {
+            log::debug!(
+                "[trace_id {trace_id}] search->tantivy: no vector index for field {field} in {ttv_file_name}"
+            );
+            has_skipped_conditions = true;
+            continue;
+        };
+        let vector_index = VectorIndex::from_bytes(&data)?;
+        let query_vector = parse_vector(&vector).unwrap_or_default();
+        let doc_ids = vector_index
+            .search(&query_vector, k, DEFAULT_EF_SEARCH.max(k))
+            .into_iter()
+            .map(|(doc_id, _)| doc_id)
+            .collect();
+        query = Box::new(BooleanQuery::new(vec![
+            (Occur::Must, query),
+            (Occur::Must, Box::new(DocSetQuery::new(doc_ids))),
+        ]));
+    }
 
     if !file_in_range && let Ok(ts_field) = tantivy_schema.get_field(TIMESTAMP_COL_NAME) {
         let ts_range = RangeQuery::new(
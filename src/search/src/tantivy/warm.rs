@@ -81,9 +81,17 @@ impl WarmPlan {
                 .or_insert(need_position);
         });
 
+        // Range queries scan fast field columns rather than postings.
+        let fast_fields = condition
+            .need_fast_fields()
+            .into_iter()
+            .filter(|field| schema.get_field(field).is_ok())
+            .collect();
+
         Self {
             exact_postings,
             full_posting_fields,
+            fast_fields,
             ..Default::default()
         }
     }
@@ -189,7 +197,8 @@ async fn warm_term_info(segment_reader: &SegmentReader, term: Option<Term>) -> a
 
 #[cfg(test)]
 mod tests {
-    use config::INDEX_FIELD_NAME_FOR_ALL;
+    use config::{INDEX_FIELD_NAME_FOR_ALL, utils::inverted_index::to_range_index_field_name};
+    use datafusion::logical_expr::Operator;
     use tantivy::{
         Index, Searcher,
         collector::Count,
@@ -205,6 +214,7 @@ mod tests {
         let mut builder = Schema::builder();
         builder.add_text_field("tag", TEXT);
         builder.add_text_field(INDEX_FIELD_NAME_FOR_ALL, TEXT);
+        builder.add_i64_field(&to_range_index_field_name("latency"), FAST);
         builder.add_i64_field(TIMESTAMP_COL_NAME, FAST);
         builder.build()
     }
@@ -267,6 +277,22 @@ mod tests {
         assert!(plan.exact_postings.is_empty());
     }
 
+    #[test]
+    fn range_warms_fast_field() {
+        let (plan, _) = build_plan(
+            Condition::Range("latency".into(), Operator::Gt, "5000".into()),
+            None,
+            true,
+            false,
+        );
+        assert!(plan.exact_postings.is_empty());
+        assert!(plan.full_posting_fields.is_empty());
+        assert_eq!(
+            plan.fast_fields,
+            HashSet::from([to_range_index_field_name("latency")])
+        );
+    }
+
     #[test]
     fn not_equal_warms_only_exact_posting() {
         let (plan, schema) = build_plan(
@@ -36,13 +36,13 @@ use config::{
         base64,
         hash::Sum64,
         json,
-        sql::{is_complex_query, is_eligible_for_histogram},
+        sql::{is_complex_query, is_eligible_for_histogram, rewrite_gapfill},
         time::{format_duration, now_micros, second_micros},
     },
 };
 use infra::{
     cache::{file_data::disk::QUERY_RESULT_CACHE, meta::ResultCacheMeta},
-    errors::Error,
+    errors::{Error, ErrorCodes},
 };
 #[cfg(feature = "enterprise")]
 use o2_enterprise::enterprise::common::config::get_config as get_o2_config;
@@ -93,6 +93,23 @@ pub async fn search(
 
     let mut req = in_req.clone();
 
+    // rewrite the gap filling functions, the missing buckets are added after the merge and the
+    // page is taken from the filled result
+    let gapfill = match rewrite_gapfill(&req.query.sql) {
+        Ok(Some((sql, gapfill))) => {
+            req.query.sql = sql;
+            req.query.from = 0;
+            req.query.size = -1;
+            Some(gapfill)
+        }
+        Ok(None) => None,
+        Err(e) => {
+            return Err(Error::ErrorCode(ErrorCodes::SearchSQLNotValid(
+                e.to_string(),
+            )));
+        }
+    };
+
     // check the original query function first
     let mut query_fn = req
         .query
@@ -468,6 +485,29 @@ pub async fn search(
     }
     // result cache save changes Ends
 
+    if let Some(gapfill) = gapfill {
+        let mut end_time = req.query.end_time;
+        if end_time == 0 {
+            end_time = now_micros();
+        }
+        gapfill.fill(
+            &mut res.hits,
+            (req.query.start_time, end_time),
+            c_resp.histogram_interval * second_micros(1),
+            req.query.timezone.as_deref(),
+            c_resp.is_descending,
+        );
+        res.total = res.hits.len();
+        if in_req.query.from > 0 {
+            res.hits
+                .drain(..(in_req.query.from as usize).min(res.hits.len()));
+        }
+        if in_req.query.size > 0 {
+            res.hits.truncate(in_req.query.size as usize);
+        }
+        res.size = res.hits.len() as i64;
+    }
+
     #[cfg(feature = "vectorscan")]
     crate::cache::apply_regex_to_response(
         &req,
@@ -647,6 +687,10 @@ pub async fn prepare_cache_response(
             ts_column,
             is_aggregate: is_complex_query,
             is_descending,
+            histogram_interval: sql
+                .histogram_interval
+                .filter(|_| is_complex_query)
+                .unwrap_or_default(),
             order_by: sql.order_by,
             limit: sql.limit,
             file_path,
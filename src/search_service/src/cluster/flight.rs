@@ -58,6 +58,7 @@ use crate::{
             create_physical_plan,
         },
         plan_metrics::get_peak_memory_from_ctx,
+        udtf::{TableFunctions, resolve_table_functions},
     },
     inspector::{SearchInspectorFieldsBuilder, search_inspector_fields},
     sql::Sql,
@@ -194,6 +195,10 @@ pub async fn search(trace_id: &str, sql: Arc<Sql>, mut req: Request) -> Result<S
         )
     );
 
+    // the searches of the table functions wait in the queue on their own, so
+    // they run before this query takes its slot
+    let table_functions = resolve_table_functions(&req, &sql).await?;
+
     // 4. Wait in DB search queue (org/user concurrency check)
     metrics::QUERY_PENDING_NUMS
         .with_label_values(&[&req.org_id])
@@ -307,9 +312,16 @@ pub async fn search(trace_id: &str, sql: Arc<Sql>, mut req: Request) -> Result<S
 
     let trace_id_move = trace_id.to_string();
     let query_task = DATAFUSION_RUNTIME.spawn(async move {
-        run_datafusion(trace_id_move, req, sql, nodes, partitioned_file_lists)
-            .instrument(datafusion_span)
-            .await
+        run_datafusion(
+            trace_id_move,
+            req,
+            sql,
+            nodes,
+            partitioned_file_lists,
+            table_functions,
+        )
+        .instrument(datafusion_span)
+        .await
     });
     tokio::pin!(query_task);
 
@@ -374,6 +386,7 @@ pub async fn run_datafusion(
     sql: Arc<Sql>,
     nodes: Vec<Node>,
     partitioned_file_lists: HashMap<TableReference, Vec<Vec<i64>>>,
+    table_functions: TableFunctions,
 ) -> Result<(Vec<RecordBatch>, ScanStats, String)> {
     let cfg = get_config();
 
@@ -390,6 +403,7 @@ pub async fn run_datafusion(
             StreamingAggregationContext::new(&req, is_complete_cache_hit.clone()).await?,
         ))
         .add_context(PhysicalOptimizerContext::AggregateTopk)
+        .table_functions(table_functions)
         .build(&req, &sql)
         .await?;
 
@@ -665,6 +679,10 @@ pub async fn get_file_id_lists(
 ) -> Result<HashMap<TableReference, Vec<FileId>>> {
     let mut file_lists = HashMap::with_capacity(stream_names.len());
     for stream in stream_names {
+        // external tables read their own data files at scan time
+        if stream.is_external_table() {
+            continue;
+        }
         let name = stream.stream_name();
         let stream_type = stream.get_stream_type(stream_type);
         // if stream is enrich, rewrite the time_range
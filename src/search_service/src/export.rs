@@ -0,0 +1,758 @@
+// Copyright 2026 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Streams search results out as Parquet, Arrow IPC or gzipped NDJSON.
+//!
+//! Record batches returned by the cluster search are encoded directly, without
+//! the JSON row conversion done for regular `_search` responses, and the query
+//! is run partition by partition so that large downloads are never held in
+//! memory as a whole.
+
+use std::{borrow::Cow, collections::HashSet, io::Write, sync::Arc};
+
+use arrow::{
+    array::{RecordBatch, new_null_array},
+    compute::{CastOptions, cast_with_options},
+    ipc::writer::StreamWriter,
+    json::{
+        LineDelimitedWriter,
+        reader::{ReaderBuilder, infer_json_schema_from_iterator},
+    },
+};
+use arrow_schema::{DataType, Schema, SchemaRef};
+use bytes::Bytes;
+use config::{
+    PARQUET_MAX_ROW_GROUP_SIZE, QUERY_WITH_NO_LIMIT, get_config, get_parquet_compression,
+    meta::{
+        search::{self, ExportFormat, SearchEventType, SearchPartitionRequest},
+        self_reporting::usage::{RequestStats, UsageType},
+        sql::resolve_stream_names,
+        stream::StreamType,
+    },
+    utils::{json, sql::is_complex_query, time::now_micros},
+};
+use flate2::{Compression, write::GzEncoder};
+use infra::errors::{Error, ErrorCodes};
+use parking_lot::Mutex;
+use parquet::{arrow::ArrowWriter, file::properties::WriterProperties};
+use proto::cluster_rpc::SearchQuery;
+use tokio::sync::mpsc;
+use usage_reporting::report_request_usage_stats;
+#[cfg(feature = "enterprise")]
+use {crate::SEARCH_SERVER, o2_enterprise::enterprise::search::TaskStatus};
+
+/// In-memory sink shared between an encoder and the exporter, so that the
+/// bytes produced so far can be drained while the encoder keeps its state.
+#[derive(Clone, Default)]
+struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+impl SharedBuffer {
+    fn take(&self) -> Bytes {
+        Bytes::from(std::mem::take(&mut *self.0.lock()))
+    }
+}
+
+impl Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+enum Encoder {
+    Parquet(ArrowWriter<SharedBuffer>),
+    Arrow(StreamWriter<SharedBuffer>),
+    NdjsonGz(LineDelimitedWriter<GzEncoder<SharedBuffer>>),
+}
+
+/// Incremental encoder for one export. Every call returns the bytes that are
+/// ready to be sent; `finish` returns the trailer.
+pub struct ExportWriter {
+    format: ExportFormat,
+    buf: SharedBuffer,
+    encoder: Encoder,
+    schema: SchemaRef,
+}
+
+impl ExportWriter {
+    /// Parquet and Arrow IPC need a single schema for the whole file, it is
+    /// known before the first batch and every batch is cast to it.
+    pub fn try_new(format: ExportFormat, schema: SchemaRef) -> Result<Self, Error> {
+        // partitions may miss a column, so every column has to accept nulls
+        let schema = Arc::new(Schema::new_with_metadata(
+            schema
+                .fields()
+                .iter()
+                .map(|f| f.as_ref().clone().with_nullable(true))
+                .collect::<Vec<_>>(),
+            schema.metadata().clone(),
+        ));
+        let buf = SharedBuffer::default();
+        let encoder = match format {
+            ExportFormat::Parquet => {
+                let cfg = get_config();
+                let props = WriterProperties::builder()
+                    .set_write_batch_size(cfg.limit.batch_size)
+                    .set_max_row_group_row_count(Some(PARQUET_MAX_ROW_GROUP_SIZE))
+                    .set_compression(get_parquet_compression(&cfg.common.parquet_compression))
+                    .build();
+                Encoder::Parquet(
+                    ArrowWriter::try_new(buf.clone(), schema.clone(), Some(props))
+                        .map_err(|e| Error::Message(e.to_string()))?,
+                )
+            }
+            ExportFormat::Arrow => Encoder::Arrow(
+                StreamWriter::try_new(buf.clone(), &schema)
+                    .map_err(|e| Error::Message(e.to_string()))?,
+            ),
+            ExportFormat::NdjsonGz => Encoder::NdjsonGz(LineDelimitedWriter::new(GzEncoder::new(
+                buf.clone(),
+                Compression::default(),
+            ))),
+        };
+        Ok(Self {
+            format,
+            buf,
+            encoder,
+            schema,
+        })
+    }
+
+    pub fn format(&self) -> ExportFormat {
+        self.format
+    }
+
+    pub fn write(&mut self, batch: &RecordBatch) -> Result<Bytes, Error> {
+        if batch.num_rows() == 0 {
+            return Ok(self.buf.take());
+        }
+        let batch = cast_to_schema(&self.schema, batch)?;
+        match &mut self.encoder {
+            Encoder::Parquet(w) => w.write(&batch).map_err(|e| Error::Message(e.to_string()))?,
+            Encoder::Arrow(w) => w.write(&batch).map_err(|e| Error::Message(e.to_string()))?,
+            Encoder::NdjsonGz(w) => w.write(&batch).map_err(|e| Error::Message(e.to_string()))?,
+        }
+        Ok(self.buf.take())
+    }
+
+    pub fn finish(self) -> Result<Bytes, Error> {
+        match self.encoder {
+            Encoder::Parquet(w) => {
+                w.close().map_err(|e| Error::Message(e.to_string()))?;
+            }
+            Encoder::Arrow(mut w) => {
+                w.finish().map_err(|e| Error::Message(e.to_string()))?;
+            }
+            Encoder::NdjsonGz(mut w) => {
+                w.finish().map_err(|e| Error::Message(e.to_string()))?;
+                w.into_inner()
+                    .finish()
+                    .map_err(|e| Error::Message(e.to_string()))?;
+            }
+        }
+        Ok(self.buf.take())
+    }
+}
+
+/// Aligns `batch` to the export schema. Columns are matched by name and cast
+/// explicitly, a column missing from the batch is null. A column the schema
+/// doesn't know or a value that can't be cast fails the export instead of
+/// being dropped.
+fn cast_to_schema(schema: &SchemaRef, batch: &RecordBatch) -> Result<RecordBatch, Error> {
+    if let Some(field) = batch
+        .schema()
+        .fields()
+        .iter()
+        .find(|f| schema.field_with_name(f.name()).is_err())
+    {
+        return Err(Error::Message(format!(
+            "export: column {} is not part of the export schema",
+            field.name()
+        )));
+    }
+    let options = CastOptions {
+        safe: false,
+        ..Default::default()
+    };
+    let columns = schema
+        .fields()
+        .iter()
+        .map(|field| match batch.column_by_name(field.name()) {
+            Some(column) if column.data_type() == field.data_type() => Ok(column.clone()),
+            Some(column) => cast_with_options(column, field.data_type(), &options).map_err(|e| {
+                Error::Message(format!(
+                    "export: cannot cast column {} from {} to {}: {e}",
+                    field.name(),
+                    column.data_type(),
+                    field.data_type()
+                ))
+            }),
+            None => Ok(new_null_array(field.data_type(), batch.num_rows())),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    RecordBatch::try_new(schema.clone(), columns).map_err(|e| Error::Message(e.to_string()))
+}
+
+/// Infers the schema of stored JSON hits.
+pub fn infer_hits_schema(hits: &[json::Value]) -> Result<Schema, Error> {
+    infer_json_schema_from_iterator(hits.iter().map(|v| Ok(v.clone())))
+        .map_err(|e| Error::Message(e.to_string()))
+}
+
+/// Merges the schemas inferred for different search job partitions. Integers
+/// and floats widen to Float64, any other conflict falls back to Utf8 so that
+/// no value is lost.
+pub fn merge_hits_schema(schema: Schema, other: &Schema) -> Schema {
+    let mut fields = schema
+        .fields()
+        .iter()
+        .map(|f| f.as_ref().clone())
+        .collect::<Vec<_>>();
+    for field in other.fields() {
+        match fields.iter_mut().find(|f| f.name() == field.name()) {
+            Some(f) => {
+                let data_type = match (f.data_type(), field.data_type()) {
+                    (a, b) if a == b => a.clone(),
+                    (DataType::Null, b) => b.clone(),
+                    (a, DataType::Null) => a.clone(),
+                    (
+                        DataType::Int64 | DataType::UInt64 | DataType::Float64,
+                        DataType::Int64 | DataType::UInt64 | DataType::Float64,
+                    ) => DataType::Float64,
+                    _ => DataType::Utf8,
+                };
+                *f = f.clone().with_data_type(data_type).with_nullable(true);
+            }
+            None => fields.push(field.as_ref().clone().with_nullable(true)),
+        }
+    }
+    Schema::new(fields)
+}
+
+/// Converts stored JSON hits back into a record batch of `schema`, used for
+/// search job results which are persisted as JSON responses.
+pub fn hits_to_record_batch(
+    hits: &[json::Value],
+    schema: &SchemaRef,
+) -> Result<Option<RecordBatch>, Error> {
+    if hits.is_empty() {
+        return Ok(None);
+    }
+    // nested values of a field merged to Utf8 are kept as their JSON text
+    let utf8_fields = schema
+        .fields()
+        .iter()
+        .filter(|f| f.data_type() == &DataType::Utf8)
+        .map(|f| f.name().as_str())
+        .collect::<HashSet<_>>();
+    let hits = hits
+        .iter()
+        .map(|hit| match hit.as_object() {
+            Some(obj)
+                if obj.iter().any(|(k, v)| {
+                    (v.is_object() || v.is_array()) && utf8_fields.contains(k.as_str())
+                }) =>
+            {
+                let mut obj = obj.clone();
+                for (k, v) in obj.iter_mut() {
+                    if (v.is_object() || v.is_array()) && utf8_fields.contains(k.as_str()) {
+                        *v = json::Value::String(v.to_string());
+                    }
+                }
+                Cow::Owned(json::Value::Object(obj))
+            }
+            _ => Cow::Borrowed(hit),
+        })
+        .collect::<Vec<_>>();
+    let mut decoder = ReaderBuilder::new(schema.clone())
+        .with_batch_size(hits.len())
+        .with_coerce_primitive(true)
+        .build_decoder()
+        .map_err(|e| Error::Message(e.to_string()))?;
+    decoder
+        .serialize(&hits)
+        .map_err(|e| Error::Message(e.to_string()))?;
+    decoder.flush().map_err(|e| Error::Message(e.to_string()))
+}
+
+/// Plans and starts an export of `in_req`, the encoded bytes are delivered
+/// through the returned channel. Planning errors are returned directly so the
+/// caller can still answer with a proper status code; errors after the first
+/// chunk are sent through the channel and abort the response.
+pub async fn search_export(
+    trace_id: &str,
+    org_id: &str,
+    stream_type: StreamType,
+    user_id: Option<String>,
+    in_req: &search::Request,
+    format: ExportFormat,
+) -> Result<mpsc::Receiver<Result<Bytes, Error>>, Error> {
+    if in_req.query.uses_zo_fn
+        || in_req
+            .query
+            .query_fn
+            .as_ref()
+            .is_some_and(|f| !f.trim().is_empty())
+        || in_req.query.action_id.is_some()
+    {
+        return Err(Error::ErrorCode(ErrorCodes::InvalidParams(format!(
+            "VRL functions and actions are not supported for {format} exports"
+        ))));
+    }
+
+    let mut req = in_req.clone();
+    req.search_type = Some(SearchEventType::Download);
+    req.query.streaming_output = false;
+    req.query.streaming_id = None;
+    req.query.track_total_hits = false;
+
+    let partition_req = SearchPartitionRequest {
+        sql: req.query.sql.clone(),
+        start_time: req.query.start_time,
+        end_time: req.query.end_time,
+        encoding: req.encoding,
+        regions: req.regions.clone(),
+        clusters: req.clusters.clone(),
+        query_fn: None,
+        streaming_output: false,
+        histogram_interval: req.query.histogram_interval,
+        sampling_ratio: req.query.sampling_ratio,
+        search_type: req.search_type,
+    };
+    let partition_resp = crate::search_partition(
+        trace_id,
+        org_id,
+        user_id.as_deref(),
+        stream_type,
+        &partition_req,
+        false,
+        false,
+    )
+    .await?;
+
+    // aggregations and non-timestamp orders need to see the whole range at once
+    let partitions = if partition_resp.partitions.len() <= 1
+        || is_complex_query(&req.query.sql).unwrap_or(true)
+        || partition_resp.streaming_aggs
+        || !partition_resp.non_ts_order_by_cols.is_empty()
+    {
+        vec![[req.query.start_time, req.query.end_time]]
+    } else {
+        partition_resp.partitions
+    };
+
+    // the file schema comes from the plan of the query, so that every
+    // partition is written with the same columns and types
+    let query: SearchQuery = req.query.clone().into();
+    let sql = crate::sql::Sql::new(&query, org_id, stream_type, req.search_type).await?;
+    let schema = crate::datafusion::plan::projections::get_output_schema(sql)
+        .await
+        .map_err(|e| Error::Message(e.to_string()))?;
+    // fail before the response starts if the schema can't be encoded
+    ExportWriter::try_new(format, schema.clone())?;
+
+    // an explicit size wins over the LIMIT of the sql, a negative size exports
+    // everything
+    let limit = if req.query.size > 0 {
+        Some(req.query.size)
+    } else if partition_resp.limit > 0 {
+        Some(partition_resp.limit)
+    } else {
+        None
+    };
+
+    let (tx, rx) = mpsc::channel(2);
+    let trace_id = trace_id.to_string();
+    let org_id = org_id.to_string();
+    tokio::spawn(async move {
+        if let Err(e) = export_partitions(
+            &trace_id,
+            &org_id,
+            stream_type,
+            user_id,
+            req,
+            format,
+            schema,
+            partitions,
+            limit,
+            tx.clone(),
+        )
+        .await
+        {
+            log::error!("[trace_id {trace_id}] search->export: err: {e}");
+            let _ = tx.send(Err(e)).await;
+        }
+    });
+    Ok(rx)
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn export_partitions(
+    trace_id: &str,
+    org_id: &str,
+    stream_type: StreamType,
+    user_id: Option<String>,
+    req: search::Request,
+    format: ExportFormat,
+    schema: SchemaRef,
+    partitions: Vec<[i64; 2]>,
+    limit: Option<i64>,
+    tx: mpsc::Sender<Result<Bytes, Error>>,
+) -> Result<(), Error> {
+    let start = std::time::Instant::now();
+    let started_at = now_micros();
+
+    #[cfg(feature = "enterprise")]
+    SEARCH_SERVER
+        .insert(
+            trace_id.to_string(),
+            TaskStatus::new_leader(
+                vec![],
+                true,
+                user_id.clone(),
+                Some(org_id.to_string()),
+                Some(stream_type.to_string()),
+                Some(req.query.sql.clone()),
+                Some(req.query.start_time),
+                Some(req.query.end_time),
+                req.search_type.map(|t| t.to_string()),
+                req.search_event_context.clone(),
+            ),
+        )
+        .await;
+
+    let ret = export_partitions_inner(
+        trace_id,
+        org_id,
+        stream_type,
+        user_id.clone(),
+        &req,
+        format,
+        schema,
+        partitions,
+        limit,
+        &tx,
+    )
+    .await;
+
+    #[cfg(feature = "enterprise")]
+    SEARCH_SERVER.remove(trace_id, false).await;
+
+    let (records, scan_stats) = ret?;
+    let stream_name = resolve_stream_names(&req.query.sql)
+        .map(|v| v.join(","))
+        .unwrap_or_default();
+    let req_stats = RequestStats {
+        records,
+        response_time: start.elapsed().as_secs_f64(),
+        size: scan_stats.original_size as f64,
+        scan_files: (scan_stats.files > 0).then_some(scan_stats.files),
+        request_body: Some(req.query.sql.clone()),
+        user_email: user_id,
+        min_ts: Some(req.query.start_time),
+        max_ts: Some(req.query.end_time),
+        search_type: req.search_type,
+        search_event_context: req.search_event_context.clone(),
+        trace_id: Some(trace_id.to_string()),
+        took_wait_in_queue: Some(scan_stats.wait_in_queue as usize),
+        peak_memory_usage: Some(scan_stats.peak_memory_usage as f64),
+        ..Default::default()
+    };
+    report_request_usage_stats(
+        req_stats,
+        org_id,
+        &stream_name,
+        stream_type,
+        UsageType::Search,
+        0,
+        started_at,
+    )
+    .await;
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn export_partitions_inner(
+    trace_id: &str,
+    org_id: &str,
+    stream_type: StreamType,
+    user_id: Option<String>,
+    req: &search::Request,
+    format: ExportFormat,
+    schema: SchemaRef,
+    partitions: Vec<[i64; 2]>,
+    limit: Option<i64>,
+    tx: &mpsc::Sender<Result<Bytes, Error>>,
+) -> Result<(i64, search::ScanStats), Error> {
+    let mut writer = ExportWriter::try_new(format, schema)?;
+    let mut scan_stats = search::ScanStats::new();
+    let mut skip = req.query.from.max(0) as usize;
+    let mut remaining = limit.map(|v| v as usize);
+    let mut records = 0;
+
+    #[cfg(not(feature = "enterprise"))]
+    let (req_regions, req_clusters): (Vec<String>, Vec<String>) = (vec![], vec![]);
+    #[cfg(feature = "enterprise")]
+    let (req_regions, req_clusters) = (req.regions.clone(), req.clusters.clone());
+
+    for [start_time, end_time] in partitions {
+        if remaining == Some(0) {
+            break;
+        }
+        #[cfg(feature = "enterprise")]
+        if !SEARCH_SERVER.contain_key(trace_id).await {
+            return Err(Error::ErrorCode(ErrorCodes::SearchCancelQuery(format!(
+                "[trace_id {trace_id}] export cancelled"
+            ))));
+        }
+
+        let mut query: SearchQuery = req.query.clone().into();
+        query.start_time = start_time;
+        query.end_time = end_time;
+        query.from = 0;
+        query.size = match remaining {
+            Some(n) => (n + skip).min(i32::MAX as usize) as i32,
+            None => QUERY_WITH_NO_LIMIT as i32,
+        };
+        let mut request = config::datafusion::request::Request::new(
+            trace_id.to_string(),
+            org_id.to_string(),
+            stream_type,
+            req.timeout,
+            user_id.clone(),
+            Some((start_time, end_time)),
+            req.search_type.map(|v| v.to_string()),
+            req.query.histogram_interval,
+            req.clear_cache,
+        );
+        if let Some(v) = req.local_mode {
+            request.set_local_mode(Some(v));
+        }
+        request.set_use_cache(req.use_cache);
+
+        let (batches, stats, ..) = crate::cluster::http::search_inner(
+            request,
+            query,
+            req_regions.clone(),
+            req_clusters.clone(),
+            true,
+            None,
+        )
+        .await?;
+        scan_stats.add(&stats);
+
+        for mut batch in batches {
+            if skip > 0 {
+                let n = skip.min(batch.num_rows());
+                skip -= n;
+                batch = batch.slice(n, batch.num_rows() - n);
+            }
+            if let Some(n) = remaining.as_mut() {
+                let take = (*n).min(batch.num_rows());
+                *n -= take;
+                batch = batch.slice(0, take);
+            }
+            if batch.num_rows() == 0 {
+                continue;
+            }
+            records += batch.num_rows() as i64;
+            let chunk = writer.write(&batch)?;
+            if !chunk.is_empty() && tx.send(Ok(chunk)).await.is_err() {
+                // the client went away
+                return Ok((records, scan_stats));
+            }
+        }
+    }
+
+    let chunk = writer.finish()?;
+    if !chunk.is_empty() {
+        let _ = tx.send(Ok(chunk)).await;
+    }
+    Ok((records, scan_stats))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use arrow::{
+        array::{Array, Int64Array, StringArray},
+        ipc::reader::StreamReader,
+    };
+    use arrow_schema::{DataType, Field};
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+    use super::*;
+
+    fn schema() -> SchemaRef {
+        Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int64, false),
+            Field::new("name", DataType::Utf8, false),
+        ]))
+    }
+
+    fn batch(ids: Vec<i64>, names: Vec<&str>) -> RecordBatch {
+        RecordBatch::try_new(
+            schema(),
+            vec![
+                Arc::new(Int64Array::from(ids)),
+                Arc::new(StringArray::from(names)),
+            ],
+        )
+        .unwrap()
+    }
+
+    fn encode(format: ExportFormat) -> Vec<u8> {
+        let mut writer = ExportWriter::try_new(format, schema()).unwrap();
+        let mut out = Vec::new();
+        out.extend(writer.write(&batch(vec![1, 2], vec!["a", "b"])).unwrap());
+        out.extend(writer.write(&batch(vec![3], vec!["c"])).unwrap());
+        out.extend(writer.finish().unwrap());
+        out
+    }
+
+    #[test]
+    fn test_export_parquet() {
+        let data = Bytes::from(encode(ExportFormat::Parquet));
+        let reader = ParquetRecordBatchReaderBuilder::try_new(data)
+            .unwrap()
+            .build()
+            .unwrap();
+        let rows: usize = reader.map(|b| b.unwrap().num_rows()).sum();
+        assert_eq!(rows, 3);
+    }
+
+    #[test]
+    fn test_export_arrow() {
+        let data = encode(ExportFormat::Arrow);
+        let reader = StreamReader::try_new(data.as_slice(), None).unwrap();
+        let batches = reader.map(|b| b.unwrap()).collect::<Vec<_>>();
+        assert_eq!(batches.len(), 2);
+        assert_eq!(batches[0].schema().field(1).name(), "name");
+        assert_eq!(batches.iter().map(|b| b.num_rows()).sum::<usize>(), 3);
+    }
+
+    #[test]
+    fn test_export_ndjson_gz() {
+        let data = encode(ExportFormat::NdjsonGz);
+        let mut text = String::new();
+        flate2::read::GzDecoder::new(data.as_slice())
+            .read_to_string(&mut text)
+            .unwrap();
+        let lines = text.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[2], r#"{"id":3,"name":"c"}"#);
+    }
+
+    #[test]
+    fn test_export_empty() {
+        let data = ExportWriter::try_new(ExportFormat::Arrow, schema())
+            .unwrap()
+            .finish()
+            .unwrap();
+        let reader = StreamReader::try_new(data.as_ref(), None).unwrap();
+        assert_eq!(reader.schema().fields().len(), 2);
+        assert_eq!(reader.count(), 0);
+    }
+
+    #[test]
+    fn test_export_casts_to_schema() {
+        let mut writer = ExportWriter::try_new(ExportFormat::Arrow, schema()).unwrap();
+        let mut out = Vec::new();
+        out.extend(writer.write(&batch(vec![1], vec!["a"])).unwrap());
+        // a later partition with another type and a missing column
+        let ids = RecordBatch::try_new(
+            Arc::new(Schema::new(vec![Field::new("id", DataType::Utf8, false)])),
+            vec![Arc::new(StringArray::from(vec!["2"]))],
+        )
+        .unwrap();
+        out.extend(writer.write(&ids).unwrap());
+        out.extend(writer.finish().unwrap());
+
+        let reader = StreamReader::try_new(out.as_slice(), None).unwrap();
+        let batches = reader.map(|b| b.unwrap()).collect::<Vec<_>>();
+        let last = &batches[1];
+        assert_eq!(last.schema().field(0).data_type(), &DataType::Int64);
+        assert_eq!(
+            last.column(0)
+                .as_any()
+                .downcast_ref::<Int64Array>()
+                .unwrap()
+                .value(0),
+            2
+        );
+        assert!(last.column(1).is_null(0));
+    }
+
+    #[test]
+    fn test_export_cast_errors() {
+        let mut writer = ExportWriter::try_new(ExportFormat::Arrow, schema()).unwrap();
+        let ids = RecordBatch::try_new(
+            Arc::new(Schema::new(vec![Field::new("id", DataType::Utf8, false)])),
+            vec![Arc::new(StringArray::from(vec!["x"]))],
+        )
+        .unwrap();
+        assert!(writer.write(&ids).is_err());
+        let extra = RecordBatch::try_new(
+            Arc::new(Schema::new(vec![Field::new(
+                "code",
+                DataType::Int64,
+                false,
+            )])),
+            vec![Arc::new(Int64Array::from(vec![500]))],
+        )
+        .unwrap();
+        assert!(writer.write(&extra).is_err());
+    }
+
+    #[test]
+    fn test_hits_to_record_batch() {
+        let first = vec![
+            json::json!({"_timestamp": 1, "msg": "a"}),
+            json::json!({"_timestamp": 2, "code": 500}),
+        ];
+        let second = vec![json::json!({"_timestamp": 3, "code": 1.5, "msg": {"k": "v"}})];
+        let schema = merge_hits_schema(
+            infer_hits_schema(&first).unwrap(),
+            &infer_hits_schema(&second).unwrap(),
+        );
+        assert_eq!(
+            schema.field_with_name("code").unwrap().data_type(),
+            &DataType::Float64
+        );
+        assert_eq!(
+            schema.field_with_name("msg").unwrap().data_type(),
+            &DataType::Utf8
+        );
+
+        let schema = Arc::new(schema);
+        let batch = hits_to_record_batch(&first, &schema).unwrap().unwrap();
+        assert_eq!(batch.num_rows(), 2);
+        assert_eq!(batch.num_columns(), 3);
+        let batch = hits_to_record_batch(&second, &schema).unwrap().unwrap();
+        let msg = batch
+            .column_by_name("msg")
+            .unwrap()
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .unwrap();
+        assert_eq!(msg.value(0), r#"{"k":"v"}"#);
+        assert!(hits_to_record_batch(&[], &schema).unwrap().is_none());
+    }
+}
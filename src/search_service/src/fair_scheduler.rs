@@ -0,0 +1,707 @@
+// Copyright 2026 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Weighted fair admission of searches on the node coordinating them.
+//!
+//! Waiting searches are admitted in this order:
+//! 1. searches that waited longer than the queue time SLO of their event type,
+//! 2. higher priority event types: alerts, then interactive, then scheduled, then bulk,
+//! 3. the org with the least weighted service so far (stride scheduling, the weight is the org
+//!    weight times the user role weight),
+//! 4. arrival order.
+//!
+//! A user never runs more than `user_max_concurrent` searches at a time. When an interactive
+//! search or alert misses its SLO and all slots are taken, a long running scheduled or bulk
+//! search can be preempted according to the [`PreemptPolicy`].
+
+use std::{
+    cmp::Ordering,
+    str::FromStr,
+    sync::LazyLock as Lazy,
+    time::{Duration, Instant},
+};
+
+use config::{Config, get_config, meta::search::SearchEventType};
+use hashbrown::HashMap;
+use parking_lot::Mutex;
+use tokio::sync::Notify;
+
+// waiters re-check their rank at least this often, SLOs expire without any release
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+pub static FAIR_SCHEDULER: Lazy<FairScheduler> =
+    Lazy::new(|| FairScheduler::new(SchedulerConfig::from_config(&get_config())));
+
+/// Priority class of a search, the smaller one is admitted first.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Priority {
+    Alert,
+    Interactive,
+    Scheduled,
+    Bulk,
+}
+
+impl From<Option<SearchEventType>> for Priority {
+    fn from(event_type: Option<SearchEventType>) -> Self {
+        match event_type {
+            Some(SearchEventType::Alerts) => Self::Alert,
+            Some(SearchEventType::Reports | SearchEventType::DerivedStream) => Self::Scheduled,
+            Some(SearchEventType::Download | SearchEventType::SearchJob) => Self::Bulk,
+            _ => Self::Interactive,
+        }
+    }
+}
+
+impl Priority {
+    // only background work gives its slot away
+    fn is_preemptible(&self) -> bool {
+        *self >= Self::Scheduled
+    }
+}
+
+/// What happens to a long running background search when a higher priority search misses its
+/// queue time SLO.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PreemptPolicy {
+    #[default]
+    None,
+    /// The background search keeps running but no longer holds a slot.
+    Release,
+    /// The background search is canceled and its slot is handed over.
+    Cancel,
+}
+
+impl FromStr for PreemptPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "" | "none" => Ok(Self::None),
+            "release" => Ok(Self::Release),
+            "cancel" => Ok(Self::Cancel),
+            s => Err(format!(
+                "invalid preempt policy `{s}`, expected one of `none`, `release`, `cancel`"
+            )),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct SchedulerConfig {
+    pub max_concurrent: usize,
+    /// 0 is unlimited
+    pub user_max_concurrent: usize,
+    pub org_weights: HashMap<String, f64>,
+    pub role_weights: HashMap<String, f64>,
+    pub queue_slo: HashMap<SearchEventType, Duration>,
+    pub preempt_policy: PreemptPolicy,
+    pub preempt_min_runtime: Duration,
+}
+
+impl SchedulerConfig {
+    pub fn from_config(cfg: &Config) -> Self {
+        let queue_slo = parse_pairs(&cfg.search.fair_scheduler_queue_slo)
+            .filter_map(
+                |(event_type, secs)| match SearchEventType::try_from(event_type) {
+                    Ok(event_type) => Some((event_type, Duration::from_secs_f64(secs))),
+                    Err(e) => {
+                        log::warn!("[FAIR_SCHEDULER] skip queue slo: {e}");
+                        None
+                    }
+                },
+            )
+            .collect();
+        let preempt_policy = cfg
+            .search
+            .fair_scheduler_preempt_policy
+            .parse()
+            .unwrap_or_else(|e| {
+                log::warn!("[FAIR_SCHEDULER] {e}, preemption is disabled");
+                PreemptPolicy::None
+            });
+        Self {
+            max_concurrent: cfg.search.fair_scheduler_max_concurrent.max(1),
+            user_max_concurrent: cfg.search.fair_scheduler_user_max_concurrent,
+            org_weights: parse_pairs(&cfg.search.fair_scheduler_org_weights)
+                .map(|(org_id, weight)| (org_id.to_string(), weight))
+                .collect(),
+            role_weights: parse_pairs(&cfg.search.fair_scheduler_role_weights)
+                .map(|(role, weight)| (role.to_lowercase(), weight))
+                .collect(),
+            queue_slo,
+            preempt_policy,
+            preempt_min_runtime: Duration::from_secs(cfg.search.fair_scheduler_preempt_min_runtime),
+        }
+    }
+
+    fn weight(&self, ticket: &Ticket) -> f64 {
+        let org_weight = self.org_weights.get(&ticket.org_id).copied().unwrap_or(1.0);
+        let role_weight = ticket
+            .role
+            .as_deref()
+            .and_then(|role| self.role_weights.get(&role.to_lowercase()))
+            .copied()
+            .unwrap_or(1.0);
+        org_weight * role_weight
+    }
+}
+
+// parses `key=value,key=value`, malformed pairs and non positive values are skipped
+fn parse_pairs(s: &str) -> impl Iterator<Item = (&str, f64)> {
+    s.split(',')
+        .map(str::trim)
+        .filter(|pair| !pair.is_empty())
+        .filter_map(|pair| {
+            let value = pair
+                .split_once('=')
+                .and_then(|(key, value)| Some((key.trim(), value.trim().parse::<f64>().ok()?)))
+                .filter(|(key, value)| !key.is_empty() && value.is_finite() && *value > 0.0);
+            if value.is_none() {
+                log::warn!("[FAIR_SCHEDULER] skip invalid setting `{pair}`, expected key=value");
+            }
+            value
+        })
+}
+
+/// A search asking for a slot.
+#[derive(Clone, Debug, Default)]
+pub struct Ticket {
+    pub trace_id: String,
+    pub org_id: String,
+    pub user_id: Option<String>,
+    pub role: Option<String>,
+    pub event_type: Option<SearchEventType>,
+}
+
+#[derive(Debug)]
+struct Waiter {
+    id: u64,
+    trace_id: String,
+    org_id: String,
+    user_id: Option<String>,
+    priority: Priority,
+    weight: f64,
+    slo: Option<Duration>,
+    enqueued_at: Instant,
+}
+
+impl Waiter {
+    fn is_overdue(&self, now: Instant) -> bool {
+        self.slo
+            .is_some_and(|slo| now.duration_since(self.enqueued_at) >= slo)
+    }
+}
+
+#[derive(Debug)]
+struct Running {
+    trace_id: String,
+    org_id: String,
+    user_id: Option<String>,
+    priority: Priority,
+    started_at: Instant,
+    // preempted searches don't hold a slot anymore
+    preempted: bool,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    next_id: u64,
+    waiting: Vec<Waiter>,
+    running: HashMap<u64, Running>,
+    // every admission moves the pass of the org forward by 1 / weight
+    org_pass: HashMap<String, f64>,
+    // pass of the latest admission, an org coming back from idle starts here so it can't bank
+    // credit while it has nothing to run
+    virtual_time: f64,
+}
+
+impl State {
+    fn enqueue(&mut self, mut waiter: Waiter) -> u64 {
+        self.next_id += 1;
+        waiter.id = self.next_id;
+        self.waiting.push(waiter);
+        self.next_id
+    }
+
+    fn leave(&mut self, id: u64) {
+        self.waiting.retain(|w| w.id != id);
+    }
+
+    fn release(&mut self, id: u64) {
+        self.running.remove(&id);
+    }
+
+    fn pass(&self, org_id: &str) -> f64 {
+        self.org_pass
+            .get(org_id)
+            .copied()
+            .unwrap_or_default()
+            .max(self.virtual_time)
+    }
+
+    fn slots_in_use(&self) -> usize {
+        self.running.values().filter(|r| !r.preempted).count()
+    }
+
+    fn user_allowed(&self, user_id: Option<&str>, cfg: &SchedulerConfig) -> bool {
+        match user_id {
+            Some(user_id) if cfg.user_max_concurrent > 0 => {
+                self.running
+                    .values()
+                    .filter(|r| !r.preempted && r.user_id.as_deref() == Some(user_id))
+                    .count()
+                    < cfg.user_max_concurrent
+            }
+            _ => true,
+        }
+    }
+
+    fn cmp_waiters(&self, a: &Waiter, b: &Waiter, now: Instant) -> Ordering {
+        b.is_overdue(now)
+            .cmp(&a.is_overdue(now))
+            .then(a.priority.cmp(&b.priority))
+            .then(self.pass(&a.org_id).total_cmp(&self.pass(&b.org_id)))
+            .then(a.id.cmp(&b.id))
+    }
+
+    fn next(&self, cfg: &SchedulerConfig, now: Instant) -> Option<&Waiter> {
+        self.waiting
+            .iter()
+            .filter(|w| self.user_allowed(w.user_id.as_deref(), cfg))
+            .min_by(|a, b| self.cmp_waiters(a, b, now))
+    }
+
+    fn queue_position(&self, trace_id: &str, now: Instant) -> Option<usize> {
+        let mut waiting = self.waiting.iter().collect::<Vec<_>>();
+        waiting.sort_by(|a, b| self.cmp_waiters(a, b, now));
+        waiting
+            .iter()
+            .position(|w| w.trace_id == trace_id)
+            .map(|pos| pos + 1)
+    }
+
+    // marks the longest running background search with a lower priority as preempted and
+    // returns its (org_id, trace_id)
+    fn preempt(
+        &mut self,
+        priority: Priority,
+        cfg: &SchedulerConfig,
+        now: Instant,
+    ) -> Option<(String, String)> {
+        if cfg.preempt_policy == PreemptPolicy::None || priority.is_preemptible() {
+            return None;
+        }
+        let victim = self
+            .running
+            .values_mut()
+            .filter(|r| {
+                !r.preempted
+                    && r.priority.is_preemptible()
+                    && r.priority > priority
+                    && now.duration_since(r.started_at) >= cfg.preempt_min_runtime
+            })
+            .min_by_key(|r| r.started_at)?;
+        victim.preempted = true;
+        Some((victim.org_id.clone(), victim.trace_id.clone()))
+    }
+
+    /// Admits the waiter if it is the next one and a slot is free, preempting a background search
+    /// if needed. Returns whether it was admitted and the (org_id, trace_id) it preempted.
+    fn try_admit(
+        &mut self,
+        id: u64,
+        cfg: &SchedulerConfig,
+        now: Instant,
+    ) -> (bool, Option<(String, String)>) {
+        let Some(next) = self.next(cfg, now) else {
+            return (false, None);
+        };
+        if next.id != id {
+            return (false, None);
+        }
+        let (priority, overdue) = (next.priority, next.is_overdue(now));
+        let mut preempted = None;
+        if self.slots_in_use() >= cfg.max_concurrent {
+            if !overdue {
+                return (false, None);
+            }
+            preempted = self.preempt(priority, cfg, now);
+            if preempted.is_none() {
+                return (false, None);
+            }
+        }
+
+        let Some(pos) = self.waiting.iter().position(|w| w.id == id) else {
+            return (false, preempted);
+        };
+        let waiter = self.waiting.swap_remove(pos);
+        let pass = self.pass(&waiter.org_id);
+        self.virtual_time = self.virtual_time.max(pass);
+        let virtual_time = self.virtual_time;
+        self.org_pass
+            .insert(waiter.org_id.clone(), pass + 1.0 / waiter.weight);
+        self.org_pass.retain(|_, pass| *pass > virtual_time);
+        self.running.insert(
+            id,
+            Running {
+                trace_id: waiter.trace_id,
+                org_id: waiter.org_id,
+                user_id: waiter.user_id,
+                priority: waiter.priority,
+                started_at: now,
+                preempted: false,
+            },
+        );
+        (true, preempted)
+    }
+}
+
+pub struct FairScheduler {
+    config: SchedulerConfig,
+    state: Mutex<State>,
+    notify: Notify,
+}
+
+impl FairScheduler {
+    pub fn new(config: SchedulerConfig) -> Self {
+        Self {
+            config,
+            state: Mutex::new(State::default()),
+            notify: Notify::new(),
+        }
+    }
+
+    pub fn config(&self) -> &SchedulerConfig {
+        &self.config
+    }
+
+    /// Waits for a slot. Returns None when `timeout` passes first.
+    ///
+    /// `on_preempt` is called with the (org_id, trace_id) of the search that gave its slot away
+    /// for this one.
+    pub async fn acquire(
+        &self,
+        ticket: Ticket,
+        timeout: Duration,
+        on_preempt: impl Fn(&str, &str),
+    ) -> Option<SchedulerGuard<'_>> {
+        let enqueued_at = Instant::now();
+        let deadline = enqueued_at + timeout;
+        let slo = ticket
+            .event_type
+            .and_then(|event_type| self.config.queue_slo.get(&event_type).copied());
+        let waiter = Waiter {
+            id: 0,
+            weight: self.config.weight(&ticket),
+            priority: Priority::from(ticket.event_type),
+            trace_id: ticket.trace_id,
+            org_id: ticket.org_id,
+            user_id: ticket.user_id,
+            slo,
+            enqueued_at,
+        };
+        let trace_id = waiter.trace_id.clone();
+        let id = self.state.lock().enqueue(waiter);
+        let mut pending = Pending {
+            scheduler: self,
+            id,
+            admitted: false,
+        };
+
+        loop {
+            // registered before checking, so a release in between still wakes us up
+            let notified = self.notify.notified();
+            let (admitted, preempted) =
+                self.state
+                    .lock()
+                    .try_admit(id, &self.config, Instant::now());
+            if let Some((org_id, preempted_trace_id)) = preempted {
+                log::warn!(
+                    "[trace_id {trace_id}] fair_scheduler: preempted {preempted_trace_id} of org {org_id}, policy: {:?}",
+                    self.config.preempt_policy
+                );
+                on_preempt(&org_id, &preempted_trace_id);
+            }
+            if admitted {
+                pending.admitted = true;
+                // the next waiter may fit in a slot that is still free
+                self.notify.notify_waiters();
+                let waited = enqueued_at.elapsed();
+                if slo.is_some_and(|slo| waited > slo) {
+                    log::warn!(
+                        "[trace_id {trace_id}] fair_scheduler: missed queue slo, waited {} ms",
+                        waited.as_millis()
+                    );
+                }
+                return Some(SchedulerGuard {
+                    scheduler: self,
+                    id,
+                });
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                return None;
+            }
+            let _ = tokio::time::timeout(POLL_INTERVAL.min(deadline - now), notified).await;
+        }
+    }
+
+    /// 1 based position of the search in the queue, None when it is not waiting here.
+    pub fn queue_position(&self, trace_id: &str) -> Option<usize> {
+        self.state.lock().queue_position(trace_id, Instant::now())
+    }
+}
+
+/// Holds a slot until dropped.
+pub struct SchedulerGuard<'a> {
+    scheduler: &'a FairScheduler,
+    id: u64,
+}
+
+impl Drop for SchedulerGuard<'_> {
+    fn drop(&mut self) {
+        self.scheduler.state.lock().release(self.id);
+        self.scheduler.notify.notify_waiters();
+    }
+}
+
+// takes the waiter out of the queue when the search gives up before it is admitted
+struct Pending<'a> {
+    scheduler: &'a FairScheduler,
+    id: u64,
+    admitted: bool,
+}
+
+impl Drop for Pending<'_> {
+    fn drop(&mut self) {
+        if !self.admitted {
+            self.scheduler.state.lock().leave(self.id);
+            self.scheduler.notify.notify_waiters();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_config(max_concurrent: usize) -> SchedulerConfig {
+        SchedulerConfig {
+            max_concurrent,
+            user_max_concurrent: 0,
+            org_weights: HashMap::new(),
+            role_weights: HashMap::new(),
+            queue_slo: HashMap::new(),
+            preempt_policy: PreemptPolicy::None,
+            preempt_min_runtime: Duration::ZERO,
+        }
+    }
+
+    fn enqueue(
+        state: &mut State,
+        cfg: &SchedulerConfig,
+        trace_id: &str,
+        org_id: &str,
+        user_id: Option<&str>,
+        event_type: Option<SearchEventType>,
+    ) -> u64 {
+        let ticket = Ticket {
+            trace_id: trace_id.to_string(),
+            org_id: org_id.to_string(),
+            user_id: user_id.map(str::to_string),
+            event_type,
+            ..Default::default()
+        };
+        state.enqueue(Waiter {
+            id: 0,
+            weight: cfg.weight(&ticket),
+            priority: Priority::from(event_type),
+            slo: event_type.and_then(|t| cfg.queue_slo.get(&t).copied()),
+            trace_id: ticket.trace_id,
+            org_id: ticket.org_id,
+            user_id: ticket.user_id,
+            enqueued_at: Instant::now(),
+        })
+    }
+
+    // admits whichever waiter is next, returns its trace_id
+    fn admit_next(state: &mut State, cfg: &SchedulerConfig) -> Option<(u64, String)> {
+        let now = Instant::now();
+        let ids = state.waiting.iter().map(|w| w.id).collect::<Vec<_>>();
+        ids.into_iter().find_map(|id| {
+            state
+                .try_admit(id, cfg, now)
+                .0
+                .then(|| (id, state.running[&id].trace_id.clone()))
+        })
+    }
+
+    #[test]
+    fn test_parse_settings() {
+        let pairs = parse_pairs("org_a=4, org_b = 0.5,bad,zero=0,=3").collect::<Vec<_>>();
+        assert_eq!(pairs, vec![("org_a", 4.0), ("org_b", 0.5)]);
+        assert_eq!("Cancel".parse::<PreemptPolicy>(), Ok(PreemptPolicy::Cancel));
+        assert!("kill".parse::<PreemptPolicy>().is_err());
+
+        let mut cfg = Config::default();
+        cfg.search.fair_scheduler_queue_slo = "alerts=5,ui=30,nope=1".to_string();
+        let cfg = SchedulerConfig::from_config(&cfg);
+        assert_eq!(cfg.queue_slo.len(), 2);
+        assert_eq!(
+            cfg.queue_slo[&SearchEventType::Alerts],
+            Duration::from_secs(5)
+        );
+    }
+
+    #[test]
+    fn test_priority_order() {
+        let cfg = test_config(1);
+        let mut state = State::default();
+        let busy = enqueue(&mut state, &cfg, "busy", "a", None, None);
+        assert!(state.try_admit(busy, &cfg, Instant::now()).0);
+
+        enqueue(
+            &mut state,
+            &cfg,
+            "download",
+            "a",
+            None,
+            Some(SearchEventType::Download),
+        );
+        enqueue(&mut state, &cfg, "ui", "a", None, Some(SearchEventType::UI));
+        enqueue(
+            &mut state,
+            &cfg,
+            "alert",
+            "a",
+            None,
+            Some(SearchEventType::Alerts),
+        );
+        // no free slot
+        assert!(admit_next(&mut state, &cfg).is_none());
+        assert_eq!(state.queue_position("alert", Instant::now()), Some(1));
+        assert_eq!(state.queue_position("download", Instant::now()), Some(3));
+
+        let mut order = vec![];
+        state.release(busy);
+        while let Some((id, trace_id)) = admit_next(&mut state, &cfg) {
+            order.push(trace_id);
+            state.release(id);
+        }
+        assert_eq!(order, vec!["alert", "ui", "download"]);
+    }
+
+    #[test]
+    fn test_weighted_fair_share() {
+        let mut cfg = test_config(1);
+        cfg.org_weights.insert("heavy".to_string(), 1.0);
+        cfg.org_weights.insert("light".to_string(), 3.0);
+        let mut state = State::default();
+        // the heavy org queued everything first
+        for i in 0..12 {
+            enqueue(&mut state, &cfg, &format!("heavy-{i}"), "heavy", None, None);
+        }
+        for i in 0..12 {
+            enqueue(&mut state, &cfg, &format!("light-{i}"), "light", None, None);
+        }
+        let mut light = 0;
+        for _ in 0..8 {
+            let (id, trace_id) = admit_next(&mut state, &cfg).unwrap();
+            if trace_id.starts_with("light") {
+                light += 1;
+            }
+            state.release(id);
+        }
+        assert_eq!(light, 6);
+    }
+
+    #[test]
+    fn test_user_max_concurrent() {
+        let mut cfg = test_config(4);
+        cfg.user_max_concurrent = 1;
+        let mut state = State::default();
+        enqueue(&mut state, &cfg, "u1-a", "a", Some("u1"), None);
+        enqueue(&mut state, &cfg, "u1-b", "a", Some("u1"), None);
+        enqueue(&mut state, &cfg, "u2-a", "a", Some("u2"), None);
+        assert_eq!(admit_next(&mut state, &cfg).unwrap().1, "u1-a");
+        // u1 is at its cap, u2 goes ahead
+        assert_eq!(admit_next(&mut state, &cfg).unwrap().1, "u2-a");
+        assert!(admit_next(&mut state, &cfg).is_none());
+    }
+
+    #[test]
+    fn test_preempt_background_for_overdue_alert() {
+        let mut cfg = test_config(1);
+        cfg.queue_slo
+            .insert(SearchEventType::Alerts, Duration::ZERO);
+        let mut state = State::default();
+        let download = enqueue(
+            &mut state,
+            &cfg,
+            "download",
+            "a",
+            None,
+            Some(SearchEventType::Download),
+        );
+        assert!(state.try_admit(download, &cfg, Instant::now()).0);
+        let alert = enqueue(
+            &mut state,
+            &cfg,
+            "alert",
+            "b",
+            None,
+            Some(SearchEventType::Alerts),
+        );
+
+        // preemption is disabled by default
+        assert_eq!(state.try_admit(alert, &cfg, Instant::now()), (false, None));
+
+        cfg.preempt_policy = PreemptPolicy::Release;
+        assert_eq!(
+            state.try_admit(alert, &cfg, Instant::now()),
+            (true, Some(("a".to_string(), "download".to_string())))
+        );
+        assert_eq!(state.slots_in_use(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_acquire_and_timeout() {
+        let scheduler = FairScheduler::new(test_config(1));
+        let ticket = |trace_id: &str| Ticket {
+            trace_id: trace_id.to_string(),
+            org_id: "a".to_string(),
+            ..Default::default()
+        };
+        let guard = scheduler
+            .acquire(ticket("t1"), Duration::from_secs(1), |_, _| {})
+            .await
+            .unwrap();
+        assert!(
+            scheduler
+                .acquire(ticket("t2"), Duration::from_millis(50), |_, _| {})
+                .await
+                .is_none()
+        );
+        // the timed out waiter left the queue
+        assert_eq!(scheduler.queue_position("t2"), None);
+        drop(guard);
+        assert!(
+            scheduler
+                .acquire(ticket("t3"), Duration::from_millis(50), |_, _| {})
+                .await
+                .is_some()
+        );
+    }
+}
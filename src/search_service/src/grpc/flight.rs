@@ -28,8 +28,9 @@ use config::{
         inverted_index::IndexOptimizeMode,
         search::ScanStats,
         sql::TableReferenceExt,
-        stream::{FileKey, StreamType},
+        stream::{FileKey, FtsTokenizer, StreamType},
     },
+    utils::vector::vector_dimensions,
 };
 use datafusion::{
     common::TableReference,
@@ -44,7 +45,9 @@ use infra::{
     errors::{Error, ErrorCodes},
     schema::{
         get_stream_setting_bloom_filter_fields, get_stream_setting_fts_fields,
+        get_stream_setting_fts_index_exact, get_stream_setting_fts_tokenizer,
         get_stream_setting_index_fields, get_stream_setting_index_updated_at_for_fields,
+        get_stream_setting_range_index_fields, get_stream_setting_vector_fields,
         unwrap_stream_created_at, unwrap_stream_settings,
     },
 };
@@ -165,10 +168,33 @@ pub async fn search(
                 .unwrap_or_default()
         })
         .collect_vec();
+    let fts_tokenizer = get_stream_setting_fts_tokenizer(&stream_settings);
+    let fts_index_exact =
+        get_stream_setting_fts_index_exact(&stream_settings, req.search_info.start_time);
     let index_fields = get_stream_setting_index_fields(&stream_settings)
         .into_iter()
         .filter(|v| latest_schema_map.contains_key(v))
         .collect_vec();
+    let range_index_fields = get_stream_setting_range_index_fields(&stream_settings)
+        .into_iter()
+        .filter(|v| {
+            latest_schema_map
+                .get(v)
+                .map(|f| {
+                    [DataType::Int64, DataType::UInt64, DataType::Float64].contains(f.data_type())
+                })
+                .unwrap_or_default()
+        })
+        .collect_vec();
+    let vector_fields = get_stream_setting_vector_fields(&stream_settings)
+        .into_iter()
+        .filter(|v| {
+            latest_schema_map
+                .get(&v.name)
+                .is_some_and(|f| vector_dimensions(f.data_type()) == Some(v.dimensions))
+        })
+        .map(|v| v.name)
+        .collect_vec();
     let bloom_indexed_fields = get_stream_setting_bloom_filter_fields(&stream_settings)
         .into_iter()
         .filter(|v| latest_schema_map.contains_key(v))
@@ -201,7 +227,11 @@ pub async fn search(
         stream_type,
         (req.search_info.start_time, req.search_info.end_time),
         fst_fields.clone(),
+        fts_tokenizer,
+        fts_index_exact,
         index_fields,
+        range_index_fields,
+        vector_fields,
         index_condition_ref.clone(),
         index_optimizer_rule_ref.clone(),
     )?;
@@ -632,12 +662,21 @@ fn optimizer_physical_plan(
     stream_type: StreamType,
     time_range: (i64, i64),
     fst_fields: Vec<String>,
+    fts_tokenizer: FtsTokenizer,
+    fts_index_exact: bool,
     index_fields: Vec<String>,
+    range_index_fields: Vec<String>,
+    vector_fields: Vec<String>,
     index_condition_ref: Arc<Mutex<Option<IndexCondition>>>,
     index_optimizer_rule_ref: Arc<Mutex<Option<IndexOptimizeMode>>>,
 ) -> Result<Arc<dyn ExecutionPlan>, Error> {
     let index_fields: HashSet<String> = index_fields.iter().cloned().collect();
-    let index_rule = IndexRule::new(index_fields.clone(), index_condition_ref.clone());
+    let index_rule = IndexRule::new(index_fields.clone(), index_condition_ref.clone())
+        .with_fts_fields(fst_fields.iter().cloned().collect())
+        .with_fts_tokenizer(fts_tokenizer)
+        .with_fts_index_exact(fts_index_exact)
+        .with_range_fields(range_index_fields.into_iter().collect())
+        .with_vector_fields(vector_fields.into_iter().collect());
     let original_plan = Arc::clone(&plan);
     let plan = index_rule.optimize(plan, ctx.state().config_options())?;
 
@@ -988,7 +1027,11 @@ mod tests {
             StreamType::Logs,
             (start_time, end_time),
             vec![],
+            FtsTokenizer::O2,
+            true,
             vec!["kubernetes_namespace_name".to_string()],
+            vec![],
+            vec![],
             index_condition_ref.clone(),
             index_optimizer_rule_ref.clone(),
         )
@@ -1074,6 +1117,10 @@ mod tests {
                 stream_type,
                 (0, 100),
                 vec![],
+                FtsTokenizer::O2,
+                true,
+                vec![],
+                vec![],
                 vec![],
                 index_condition_ref.clone(),
                 index_optimizer_rule_ref.clone(),
@@ -64,6 +64,8 @@ use crate::{
 pub mod cache;
 pub mod cardinality;
 pub mod cluster;
+pub mod export;
+pub mod fair_scheduler;
 pub mod file_list;
 pub mod file_list_dump;
 pub mod grpc;
@@ -77,6 +79,7 @@ pub mod searcher;
 pub mod streaming;
 #[cfg(feature = "enterprise")]
 pub mod super_cluster;
+pub mod tail;
 pub mod work_group;
 
 use ::search::{
@@ -781,7 +784,7 @@ pub async fn query_status() -> Result<search::QueryStatusResponse, Error> {
                 peak_memory_usage: scan_stats.peak_memory_usage / 1024 / 1024, // change to MB
                 wait_in_queue: scan_stats.wait_in_queue,
             });
-        let query_status = if result.is_queue {
+        let query_status = if result.is_queue || result.queue_position.is_some() {
             "waiting"
         } else {
             "processing"
@@ -804,6 +807,7 @@ pub async fn query_status() -> Result<search::QueryStatusResponse, Error> {
             work_group,
             search_type,
             search_event_context: result.search_event_context.map(Into::into),
+            queue_position: result.queue_position,
         });
     }
 
@@ -20,7 +20,7 @@ use config::{
     },
     utils::{
         base64,
-        sql::{is_complex_query, is_eligible_for_histogram, is_explain_query},
+        sql::{is_complex_query, is_eligible_for_histogram, is_explain_query, rewrite_gapfill},
     },
 };
 use infra::errors::Error;
@@ -50,19 +50,25 @@ impl PartitionSqlContext {
         org_id: &str,
         stream_type: StreamType,
     ) -> Result<Self, Error> {
+        // partition the gap filling queries as the histogram they are rewritten to, the
+        // missing buckets are filled once the partitions are merged
+        let req_sql = match rewrite_gapfill(&req.sql) {
+            Ok(Some((sql, _))) => sql,
+            _ => req.sql.to_string(),
+        };
         let query = cluster_rpc::SearchQuery {
             start_time: req.start_time,
             end_time: req.end_time,
-            sql: req.sql.to_string(),
+            sql: req_sql.clone(),
             histogram_interval: req.histogram_interval,
             ..Default::default()
         };
         let sql = Sql::new(&query, org_id, stream_type, None).await?;
 
-        let is_explain = is_explain_query(&req.sql);
-        let is_complex = is_complex_query(&req.sql).unwrap_or(false);
+        let is_explain = is_explain_query(&req_sql);
+        let is_complex = is_complex_query(&req_sql).unwrap_or(false);
         let ts_column = get_ts_col_order_by(&sql, TIMESTAMP_COL_NAME, is_complex).map(|(v, _)| v);
-        let is_streaming_agg = is_streaming_aggregate(&req.sql, ts_column.as_deref());
+        let is_streaming_agg = is_streaming_aggregate(&req_sql, ts_column.as_deref());
         let apply_over_hits = req.query_fn.as_ref().is_some_and(|v| {
             !v.is_empty() && RESULT_ARRAY.is_match(&base64::decode_url(v).unwrap_or(v.to_string()))
         });
@@ -72,7 +78,7 @@ impl PartitionSqlContext {
                 && !(req.streaming_output && is_streaming_agg));
 
         let (is_histogram_eligible, _) =
-            is_eligible_for_histogram(&req.sql, false).unwrap_or((false, false));
+            is_eligible_for_histogram(&req_sql, false).unwrap_or((false, false));
 
         let sql_order_by = sql
             .order_by
@@ -58,6 +58,9 @@ pub async fn collect_stream_files(
     let mut max_query_range_in_hour = 0;
 
     for (stream, schema) in schemas.iter() {
+        if stream.is_external_table() {
+            continue;
+        }
         let stream_type = stream.get_stream_type(stream_type);
         let stream_name = stream.stream_name();
         let stream_settings = unwrap_stream_settings(schema.schema()).unwrap_or_default();
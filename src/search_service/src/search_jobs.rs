@@ -13,11 +13,15 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
+use std::sync::Arc;
+
+use arrow_schema::Schema;
+use bytes::Bytes;
 use chrono::{DateTime, Datelike, TimeZone, Utc};
 use config::{
     meta::{
         cluster::RoleGroup,
-        search::{self, Response, SearchPartitionRequest},
+        search::{self, ExportFormat, Response, SearchPartitionRequest},
         stream::StreamType,
     },
     utils::json,
@@ -38,7 +42,10 @@ use o2_enterprise::enterprise::{
 };
 use tokio::sync::mpsc;
 
-use crate::grpc_search::{grpc_search, grpc_search_partition};
+use crate::{
+    export::{ExportWriter, hits_to_record_batch, infer_hits_schema, merge_hits_schema},
+    grpc_search::{grpc_search, grpc_search_partition},
+};
 
 // 1. get the oldest job from `search_jobs` table
 // 2. check if the job is previous running (get error then retry, be cancel then retry) (case 1) or
@@ -466,6 +473,85 @@ pub async fn merge_response(
     Ok(resp)
 }
 
+/// Streams the stored results of a finished job as a file. Job results are
+/// persisted as JSON responses, so every partition result is decoded back into
+/// a record batch before it is encoded, one partition at a time. The schemas
+/// of all partitions are merged in a first pass so the file has one schema.
+pub fn export_result(
+    results: Vec<(String, String)>,
+    offset: i64,
+    limit: Option<i64>,
+    format: ExportFormat,
+) -> mpsc::Receiver<Result<Bytes, Error>> {
+    let (tx, rx) = mpsc::channel(2);
+    tokio::spawn(async move {
+        if let Err(e) = export_result_inner(&results, offset, limit, format, &tx).await {
+            let _ = tx.send(Err(e)).await;
+        }
+    });
+    rx
+}
+
+async fn export_result_inner(
+    results: &[(String, String)],
+    offset: i64,
+    limit: Option<i64>,
+    format: ExportFormat,
+    tx: &mpsc::Sender<Result<Bytes, Error>>,
+) -> Result<(), Error> {
+    let mut schema = Schema::empty();
+    let (mut skip, mut remaining) = (offset.max(0), limit);
+    for (path, cluster) in results {
+        let hits = get_result_hits(path, cluster, &mut skip, &mut remaining).await?;
+        if !hits.is_empty() {
+            schema = merge_hits_schema(schema, &infer_hits_schema(&hits)?);
+        }
+    }
+
+    let schema = Arc::new(schema);
+    let mut writer = ExportWriter::try_new(format, schema.clone())?;
+    let (mut skip, mut remaining) = (offset.max(0), limit);
+    for (path, cluster) in results {
+        let hits = get_result_hits(path, cluster, &mut skip, &mut remaining).await?;
+        let Some(batch) = hits_to_record_batch(&hits, &schema)? else {
+            continue;
+        };
+        let chunk = writer.write(&batch)?;
+        if !chunk.is_empty() && tx.send(Ok(chunk)).await.is_err() {
+            // the client went away
+            return Ok(());
+        }
+    }
+    let _ = tx.send(writer.finish()).await;
+    Ok(())
+}
+
+/// The hits of one stored partition result that fall into the requested
+/// window, `skip` and `remaining` carry the window over to the next partition.
+async fn get_result_hits(
+    path: &str,
+    cluster: &str,
+    skip: &mut i64,
+    remaining: &mut Option<i64>,
+) -> Result<Vec<json::Value>, Error> {
+    if *remaining == Some(0) {
+        return Ok(vec![]);
+    }
+    let size = remaining.map(|n| n + *skip).unwrap_or(i64::MAX);
+    let res = get_result(path, cluster, 0, size)
+        .await
+        .map_err(|e| Error::Message(e.to_string()))?;
+    let mut hits = res.hits;
+    let n = (*skip as usize).min(hits.len());
+    hits.drain(..n);
+    *skip -= n as i64;
+    if let Some(r) = remaining.as_mut() {
+        hits.truncate(*r as usize);
+        *r -= hits.len() as i64;
+    }
+    Ok(hits)
+}
+
 // get the response in this cluster or other cluster
 pub async fn get_result(
     path: &str,
@@ -73,6 +73,13 @@ impl Searcher {
     pub async fn get_task_status(&self) -> Vec<proto::cluster_rpc::QueryStatus> {
         let mut status = self.query_manager.get_task_status().await;
 
+        // searches waiting for the fair scheduler on this node
+        for query_status in &mut status {
+            query_status.queue_position = crate::fair_scheduler::FAIR_SCHEDULER
+                .queue_position(&query_status.trace_id)
+                .map(|pos| pos as i64);
+        }
+
         // Enrich dashboard searches submitted without names so query management can display the
         // dashboard and folder instead of only their IDs.
         for query_status in &mut status {
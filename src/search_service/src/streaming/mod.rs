@@ -29,12 +29,17 @@ use config::{
         dashboards::usage_report::DashboardInfo,
         function::{RESULT_ARRAY, VRLResultResolver},
         search::{
-            Request, ScanStats, SearchEventType, StreamResponses, TimeOffset, ValuesEventContext,
+            Query, Request, Response, ScanStats, SearchEventType, StreamResponses, TimeOffset,
+            ValuesEventContext,
         },
         sql::OrderBy,
         stream::StreamType,
     },
-    utils::{flatten, json, time::hour_micros},
+    utils::{
+        flatten, json,
+        sql::{GapFill, rewrite_gapfill},
+        time::{hour_micros, now_micros, second_micros},
+    },
 };
 use log;
 #[cfg(feature = "enterprise")]
@@ -179,6 +184,20 @@ pub async fn process_search_stream_request(
         );
     }
 
+    // The gap filling queries are searched as the histogram they are rewritten to, the cached
+    // hits and the partitions are merged before the missing buckets are filled once over the
+    // whole time range. The invalid ones fail in the search.
+    let gapfill = rewrite_gapfill(&req.query.sql)
+        .ok()
+        .flatten()
+        .map(|(sql, gapfill)| {
+            let query = req.query.clone();
+            req.query.sql = sql;
+            req.query.from = 0;
+            req.query.size = -1;
+            (gapfill, query)
+        });
+
     // Parse once: apply custom pattern replacement and pre-compute histogram_interval
     // from the full query time range so all partition clones inherit a consistent value.
     // Guard both checks to skip the parse entirely for non-histogram / non-custom queries.
@@ -219,6 +238,23 @@ pub async fn process_search_stream_request(
         }
     }
 
+    // hold the hits of a gap filling query back until they are all merged
+    let sender = match gapfill {
+        Some((gapfill, mut query)) => {
+            query.histogram_interval = req.query.histogram_interval;
+            let (gapfill_sender, receiver) = mpsc::channel(100);
+            tokio::spawn(forward_gapfill_responses(
+                receiver,
+                sender,
+                gapfill,
+                query,
+                req_order_by == OrderBy::Desc,
+            ));
+            gapfill_sender
+        }
+        None => sender,
+    };
+
     let started_at = chrono::Utc::now().timestamp_micros();
     let start = Instant::now();
     let mut accumulated_results: Vec<SearchResultType> = Vec::new();
@@ -808,6 +844,86 @@ pub async fn process_search_stream_request(
     }
 }
 
+/// Forwards the responses of a gap filling query. The hits of the cached
+/// responses and of the partitions are held back, and when the search is done
+/// they are sent as one response with the missing buckets filled over the
+/// whole time range, then the page of the request is taken.
+async fn forward_gapfill_responses(
+    mut receiver: mpsc::Receiver<Result<StreamResponses, infra::errors::Error>>,
+    sender: mpsc::Sender<Result<StreamResponses, infra::errors::Error>>,
+    gapfill: GapFill,
+    query: Query,
+    descending: bool,
+) {
+    let mut hits = Vec::new();
+    let mut last_response_meta: Option<Response> = None;
+    while let Some(result) = receiver.recv().await {
+        let response = match result {
+            Ok(StreamResponses::SearchResponse {
+                mut results,
+                streaming_aggs,
+                ..
+            }) => {
+                // the streaming aggregations respond with all the results so far
+                if streaming_aggs {
+                    hits.clear();
+                }
+                hits.append(&mut results.hits);
+                last_response_meta = Some(results);
+                continue;
+            }
+            Ok(StreamResponses::Done) => {
+                let mut results = last_response_meta.take().unwrap_or_default();
+                let start_time = results.new_start_time.unwrap_or(query.start_time);
+                let mut end_time = results.new_end_time.unwrap_or(query.end_time);
+                if end_time == 0 {
+                    end_time = now_micros();
+                }
+                let interval = if query.histogram_interval > 0 {
+                    query.histogram_interval
+                } else {
+                    results.histogram_interval.unwrap_or_default()
+                };
+                gapfill.fill(
+                    &mut hits,
+                    (start_time, end_time),
+                    interval * second_micros(1),
+                    query.timezone.as_deref(),
+                    descending,
+                );
+                if query.from > 0 {
+                    hits.drain(..(query.from as usize).min(hits.len()));
+                }
+                if query.size > 0 {
+                    hits.truncate(query.size as usize);
+                }
+                results.hits = std::mem::take(&mut hits);
+                results.total = results.hits.len();
+                results.size = results.hits.len() as i64;
+                let response = StreamResponses::SearchResponse {
+                    results,
+                    streaming_aggs: false,
+                    streaming_id: None,
+                    time_offset: TimeOffset {
+                        start_time,
+                        end_time,
+                    },
+                };
+                if sender.send(Ok(response)).await.is_err() {
+                    log::warn!("[HTTP2_STREAM] Sender is closed, stop sending gap filled response");
+                    return;
+                }
+                Ok(StreamResponses::Done)
+            }
+            other => other,
+        };
+        if sender.send(response).await.is_err() {
+            log::warn!("[HTTP2_STREAM] Sender is closed, stop forwarding gap fill responses");
+            return;
+        }
+    }
+}
+
 /// Multi-stream search processing function that handles multiple independent queries
 /// and streams results as they become available from each query
 #[allow(clippy::too_many_arguments)]
@@ -1381,12 +1497,12 @@ async fn apply_vrl_to_multi_results(
 #[cfg(test)]
 mod tests {
     use config::{
-        meta::search::{Response, StreamResponses},
-        utils::json,
+        meta::search::{Query, Response, StreamResponses, TimeOffset},
+        utils::{json, sql::rewrite_gapfill},
     };
     use tokio::sync::mpsc;
 
-    use super::apply_vrl_to_multi_results;
+    use super::{apply_vrl_to_multi_results, forward_gapfill_responses};
 
     /// Helper: collect all StreamResponses::SearchResponse from the receiver channel.
     async fn collect_responses(
@@ -1567,4 +1683,81 @@ for_each(result) -> |_idx, query_hits| {
             "Empty query should produce empty hits"
         );
     }
+
+    #[tokio::test]
+    async fn test_gapfill_fills_buckets_across_partitions() {
+        const MIN: i64 = 60_000_000;
+        // 2024-01-01T00:00:00Z
+        const T0: i64 = 1_704_067_200_000_000;
+        let sql = "SELECT time_bucket_gapfill('1 minute', _timestamp) AS ts, \
+                   interpolate(max(b)) AS b FROM t GROUP BY ts";
+        let (_, gapfill) = rewrite_gapfill(sql).unwrap().unwrap();
+        let query = Query {
+            start_time: T0,
+            end_time: T0 + 6 * MIN,
+            from: 0,
+            size: -1,
+            histogram_interval: 60,
+            ..Default::default()
+        };
+        let (tx, rx) = mpsc::channel(100);
+        let (out_tx, mut out_rx) = mpsc::channel(100);
+        let forward = tokio::spawn(forward_gapfill_responses(rx, out_tx, gapfill, query, true));
+
+        let partition = |hit: json::Value, start_time: i64| {
+            let mut results = Response::new(0, -1);
+            results.hits = vec![hit];
+            StreamResponses::SearchResponse {
+                results,
+                streaming_aggs: false,
+                streaming_id: None,
+                time_offset: TimeOffset {
+                    start_time,
+                    end_time: start_time + 3 * MIN,
+                },
+            }
+        };
+        // the latest partition is searched first, the missing buckets from 00:01 to 00:04
+        // straddle the partition boundary at 00:03
+        tx.send(Ok(partition(
+            json::json!({"ts": T0 + 5 * MIN, "b": 50}),
+            T0 + 3 * MIN,
+        )))
+        .await
+        .unwrap();
+        tx.send(Ok(StreamResponses::Progress { percent: 50 }))
+            .await
+            .unwrap();
+        tx.send(Ok(partition(json::json!({"ts": T0, "b": 0}), T0)))
+            .await
+            .unwrap();
+        tx.send(Ok(StreamResponses::Done)).await.unwrap();
+        drop(tx);
+        forward.await.unwrap();
+
+        // the progress is forwarded right away, the hits once they are all merged
+        assert!(matches!(
+            out_rx.recv().await,
+            Some(Ok(StreamResponses::Progress { percent: 50 }))
+        ));
+        let Some(Ok(StreamResponses::SearchResponse { results, .. })) = out_rx.recv().await else {
+            panic!("expected the gap filled response");
+        };
+        let buckets = results
+            .hits
+            .iter()
+            .map(|hit| (hit["ts"].as_i64().unwrap(), hit["b"].as_f64().unwrap()))
+            .collect::<Vec<_>>();
+        let expected = (0..6)
+            .rev()
+            .map(|i| (T0 + i * MIN, i as f64 * 10.0))
+            .collect::<Vec<_>>();
+        assert_eq!(buckets, expected);
+        assert_eq!(results.total, 6);
+        assert!(matches!(
+            out_rx.recv().await,
+            Some(Ok(StreamResponses::Done))
+        ));
+        assert!(out_rx.recv().await.is_none());
+    }
 }
@@ -50,6 +50,7 @@ use crate::{
             context::{PhysicalOptimizerContext, RemoteScanContext, StreamingAggregationContext},
             create_physical_plan,
         },
+        udtf::{TableFunctions, resolve_table_functions},
     },
     inspector::{SearchInspectorFieldsBuilder, search_inspector_fields},
     sql::Sql,
@@ -89,6 +90,10 @@ pub async fn search(
         return Ok((vec![], ScanStats::new(), 0, false, "".to_string()));
     }
 
+    // the searches of the table functions wait in the queue on their own, so
+    // they run before this query does
+    let table_functions = resolve_table_functions(&req, &sql).await?;
+
     // 2. get clusters
     let get_cluster_start = std::time::Instant::now();
     let role_group = req
@@ -160,7 +165,7 @@ pub async fn search(
 
     let trace_id_move = trace_id.to_string();
     let query_task = DATAFUSION_RUNTIME.spawn(async move {
-        run_datafusion(trace_id_move, req, sql, clusters)
+        run_datafusion(trace_id_move, req, sql, clusters, table_functions)
             .instrument(datafusion_span)
             .await
     });
@@ -220,6 +225,7 @@ async fn run_datafusion(
     mut req: Request,
     sql: Arc<Sql>,
     nodes: Vec<Arc<dyn NodeInfo>>,
+    table_functions: TableFunctions,
 ) -> Result<(Vec<RecordBatch>, ScanStats, String)> {
     let cfg = get_config();
     // set work group
@@ -244,6 +250,7 @@ async fn run_datafusion(
             StreamingAggregationContext::new(&req, is_complete_cache_hit.clone()).await?,
         ))
         .add_context(PhysicalOptimizerContext::AggregateTopk)
+        .table_functions(table_functions)
         .build(&req, &sql)
         .await?;
 
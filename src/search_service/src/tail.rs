@@ -0,0 +1,459 @@
+// Copyright 2026 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Live tail: push records to a client as they are written on the ingesters.
+//!
+//! Each ingester subscribes to its own memtable write path (see
+//! [`ingester::tail`]), evaluates the filter against every written batch and
+//! streams the matching records over the `Ingest/Tail` gRPC method. The
+//! querier that serves the HTTP request fans in the streams of all online
+//! ingesters and rate-limits what it forwards to the client. Unlike
+//! `_search_stream` nothing is read back from the WAL or from storage.
+
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use arrow::{
+    array::{AsArray, RecordBatch},
+    compute::filter_record_batch,
+};
+use arrow_schema::SchemaRef;
+use config::{
+    get_config,
+    meta::{cluster::NodeInfo, stream::StreamType},
+    utils::{arrow::record_batches_to_json_rows, json},
+};
+use datafusion::{common::DFSchema, physical_expr::PhysicalExpr, prelude::SessionContext};
+use infra::{
+    client::grpc::make_grpc_ingest_client,
+    cluster,
+    errors::{Error, ErrorCodes, Result},
+};
+use proto::cluster_rpc::{TailRequest, TailResponse};
+use sqlparser::{dialect::PostgreSqlDialect, parser::Parser};
+use tokio::sync::mpsc;
+use tonic::Status;
+use tracing::{Instrument, info_span};
+
+/// Events emitted to a live tail client.
+#[derive(Debug)]
+pub enum TailEvent {
+    /// Records that matched the filter, in the order they were received.
+    Hits(Vec<json::Value>),
+    /// Records dropped by the per-client rate limit, or written while the
+    /// client could not keep up with an ingester, since the last `Dropped`
+    /// event.
+    Dropped(u64),
+    /// An ingester stream failed; the remaining ingesters keep streaming.
+    Error(String),
+}
+
+/// Check that `filter` is a valid SQL WHERE clause. Column names are resolved
+/// per batch on the ingesters, so only the syntax is checked here.
+pub fn validate_filter(filter: &str) -> Result<()> {
+    if filter.trim().is_empty() {
+        return Ok(());
+    }
+    Parser::new(&PostgreSqlDialect {})
+        .try_with_sql(filter)
+        .and_then(|mut p| p.parse_expr())
+        .map_err(|e| Error::ErrorCode(ErrorCodes::SearchSQLNotValid(e.to_string())))?;
+    Ok(())
+}
+
+/// Evaluates a SQL WHERE clause against written record batches.
+///
+/// The physical expression is compiled against the schema of the batch and
+/// reused as long as the following batches share that schema. A batch whose
+/// schema lacks a column referenced by the filter matches nothing.
+pub struct TailFilter {
+    filter: String,
+    ctx: SessionContext,
+    compiled: Option<(SchemaRef, Option<Arc<dyn PhysicalExpr>>)>,
+}
+
+impl TailFilter {
+    pub fn new(filter: &str) -> Self {
+        let ctx = SessionContext::new();
+        ::search::datafusion::exec::register_builtin_udfs(&ctx);
+        Self {
+            filter: filter.trim().to_string(),
+            ctx,
+            compiled: None,
+        }
+    }
+
+    /// Returns the rows of `batch` matching the filter.
+    pub fn apply(&mut self, batch: RecordBatch) -> Result<RecordBatch> {
+        if self.filter.is_empty() || batch.num_rows() == 0 {
+            return Ok(batch);
+        }
+        let schema = batch.schema();
+        let expr = match &self.compiled {
+            Some((cached, expr)) if *cached == schema => expr.clone(),
+            _ => {
+                let expr = self.compile(&schema);
+                self.compiled = Some((schema.clone(), expr.clone()));
+                expr
+            }
+        };
+        let Some(expr) = expr else {
+            return Ok(batch.slice(0, 0));
+        };
+        let mask = expr
+            .evaluate(&batch)
+            .and_then(|v| v.into_array(batch.num_rows()))
+            .map_err(|e| Error::Message(e.to_string()))?;
+        let Some(mask) = mask.as_boolean_opt() else {
+            return Err(Error::ErrorCode(ErrorCodes::SearchSQLNotValid(format!(
+                "live tail filter must be a boolean expression: {}",
+                self.filter
+            ))));
+        };
+        Ok(filter_record_batch(&batch, mask)?)
+    }
+
+    fn compile(&self, schema: &SchemaRef) -> Option<Arc<dyn PhysicalExpr>> {
+        let df_schema = DFSchema::try_from(schema.as_ref().clone()).ok()?;
+        let expr = match self.ctx.parse_sql_expr(&self.filter, &df_schema) {
+            Ok(expr) => expr,
+            Err(e) => {
+                log::debug!("[LIVE_TAIL] filter does not apply to schema: {e}");
+                return None;
+            }
+        };
+        match self.ctx.create_physical_expr(expr, &df_schema) {
+            Ok(expr) => Some(expr),
+            Err(e) => {
+                log::debug!("[LIVE_TAIL] filter does not apply to schema: {e}");
+                None
+            }
+        }
+    }
+}
+
+/// Ingester side of the `Ingest/Tail` gRPC method: forward the records written
+/// on this node that match the filter until the caller goes away or the
+/// session reaches `ZO_LIVE_TAIL_MAX_DURATION`.
+pub async fn serve(req: TailRequest, tx: mpsc::Sender<Result<TailResponse, Status>>) {
+    let trace_id = req.trace_id;
+    let mut filter = TailFilter::new(&req.filter);
+    let mut sub = ingester::tail::subscribe(&req.org_id, &req.stream_type, &req.stream_name);
+    let deadline = tokio::time::sleep(Duration::from_secs(
+        get_config().http_streaming.live_tail_max_duration,
+    ));
+    tokio::pin!(deadline);
+
+    log::info!(
+        "[trace_id {trace_id}] live tail started on ingester: {}/{}/{}, filter: {}",
+        req.org_id,
+        req.stream_type,
+        req.stream_name,
+        req.filter
+    );
+    loop {
+        let (batch, skipped) = tokio::select! {
+            _ = tx.closed() => break,
+            _ = &mut deadline => break,
+            v = sub.recv() => match v {
+                Some(v) => v,
+                None => break,
+            },
+        };
+        let batch = match filter.apply(batch) {
+            Ok(v) => v,
+            Err(e) => {
+                let _ = tx.send(Err(Status::invalid_argument(e.to_string()))).await;
+                break;
+            }
+        };
+        if batch.num_rows() == 0 && skipped == 0 {
+            continue;
+        }
+        let data = match record_batches_to_json_rows(&[&batch]) {
+            Ok(rows) if rows.is_empty() => Vec::new(),
+            Ok(rows) => json::to_vec(&rows).unwrap_or_default(),
+            Err(e) => {
+                log::error!("[trace_id {trace_id}] live tail convert batch error: {e}");
+                continue;
+            }
+        };
+        let resp = TailResponse {
+            data,
+            dropped: skipped,
+        };
+        if tx.send(Ok(resp)).await.is_err() {
+            break;
+        }
+    }
+    log::info!("[trace_id {trace_id}] live tail stopped on ingester");
+}
+
+/// Querier side: subscribe to every online ingester and fan the matching
+/// records into a single channel, rate-limited to
+/// `ZO_LIVE_TAIL_MAX_RECORDS_PER_SEC`. The session ends when the returned
+/// receiver is dropped or after `ZO_LIVE_TAIL_MAX_DURATION`.
+pub async fn tail(
+    trace_id: &str,
+    org_id: &str,
+    stream_type: StreamType,
+    stream_name: &str,
+    filter: &str,
+) -> Result<mpsc::Receiver<TailEvent>> {
+    validate_filter(filter)?;
+    let mut nodes = cluster::get_cached_online_ingester_nodes()
+        .await
+        .unwrap_or_default();
+    nodes.sort_by(|a, b| a.grpc_addr.cmp(&b.grpc_addr));
+    nodes.dedup_by(|a, b| a.grpc_addr == b.grpc_addr);
+    if nodes.is_empty() {
+        log::error!("[trace_id {trace_id}] live tail: no ingester node online");
+        return Err(super::server_internal_error("no ingester node online"));
+    }
+
+    let cfg = get_config();
+    let max_duration = cfg.http_streaming.live_tail_max_duration;
+    let (in_tx, mut in_rx) = mpsc::channel::<TailEvent>(nodes.len() * 4);
+    for node in nodes {
+        let grpc_span = info_span!(
+            "service:search:tail:grpc",
+            node_id = node.id,
+            node_addr = node.grpc_addr.as_str(),
+        );
+        let trace_id = trace_id.to_string();
+        let req = TailRequest {
+            trace_id: trace_id.clone(),
+            org_id: org_id.to_string(),
+            stream_type: stream_type.as_str().to_string(),
+            stream_name: stream_name.to_string(),
+            filter: filter.to_string(),
+        };
+        let tx = in_tx.clone();
+        tokio::spawn(
+            async move {
+                let node = Arc::new(node) as Arc<dyn NodeInfo>;
+                if let Err(e) = tail_ingester(&trace_id, &node, req, max_duration, &tx).await {
+                    log::error!(
+                        "[trace_id {trace_id}] live tail: node: {}, err: {e}",
+                        node.get_grpc_addr()
+                    );
+                    let _ = tx.send(TailEvent::Error(e.to_string())).await;
+                }
+            }
+            .instrument(grpc_span),
+        );
+    }
+    drop(in_tx);
+
+    let (out_tx, out_rx) = mpsc::channel::<TailEvent>(64);
+    let trace_id = trace_id.to_string();
+    tokio::spawn(async move {
+        let mut limiter = RateLimiter::new(cfg.http_streaming.live_tail_max_records_per_sec);
+        let mut dropped = 0;
+        let mut report = tokio::time::interval(Duration::from_secs(1));
+        loop {
+            let event = tokio::select! {
+                _ = out_tx.closed() => break,
+                _ = report.tick() => {
+                    if dropped > 0 {
+                        if out_tx.send(TailEvent::Dropped(dropped)).await.is_err() {
+                            break;
+                        }
+                        dropped = 0;
+                    }
+                    continue;
+                }
+                v = in_rx.recv() => match v {
+                    Some(v) => v,
+                    None => break,
+                },
+            };
+            let event = match event {
+                TailEvent::Hits(mut hits) => {
+                    let allowed = limiter.take(hits.len());
+                    dropped += (hits.len() - allowed) as u64;
+                    hits.truncate(allowed);
+                    if hits.is_empty() {
+                        continue;
+                    }
+                    TailEvent::Hits(hits)
+                }
+                TailEvent::Dropped(n) => {
+                    dropped += n;
+                    continue;
+                }
+                e => e,
+            };
+            if out_tx.send(event).await.is_err() {
+                break;
+            }
+        }
+        log::info!("[trace_id {trace_id}] live tail session closed");
+    });
+
+    Ok(out_rx)
+}
+
+async fn tail_ingester(
+    trace_id: &str,
+    node: &Arc<dyn NodeInfo>,
+    req: TailRequest,
+    timeout: u64,
+    tx: &mpsc::Sender<TailEvent>,
+) -> Result<()> {
+    let mut request = tonic::Request::new(req);
+    let mut client = make_grpc_ingest_client(trace_id, &mut request, node, timeout).await?;
+    let mut stream = client
+        .tail(request)
+        .await
+        .map_err(|e| Error::Message(e.message().to_string()))?
+        .into_inner();
+    loop {
+        let resp = tokio::select! {
+            _ = tx.closed() => return Ok(()),
+            v = stream.message() => v,
+        };
+        let resp = match resp {
+            Ok(Some(v)) => v,
+            Ok(None) => return Ok(()),
+            // the session deadline is enforced by the request timeout
+            Err(e) if e.code() == tonic::Code::DeadlineExceeded => return Ok(()),
+            Err(e) => return Err(Error::Message(e.message().to_string())),
+        };
+        if resp.dropped > 0 && tx.send(TailEvent::Dropped(resp.dropped)).await.is_err() {
+            return Ok(());
+        }
+        if resp.data.is_empty() {
+            continue;
+        }
+        let hits: Vec<json::Value> = json::from_slice(&resp.data)?;
+        if tx.send(TailEvent::Hits(hits)).await.is_err() {
+            return Ok(());
+        }
+    }
+}
+
+/// Token bucket limiting the records forwarded to one live tail client.
+/// The bucket holds at most one second worth of records.
+struct RateLimiter {
+    rate: f64,
+    tokens: f64,
+    last: Instant,
+}
+
+impl RateLimiter {
+    fn new(records_per_sec: usize) -> Self {
+        let rate = records_per_sec as f64;
+        Self {
+            rate,
+            tokens: rate,
+            last: Instant::now(),
+        }
+    }
+
+    /// Take up to `n` tokens, returns how many records may be forwarded.
+    fn take(&mut self, n: usize) -> usize {
+        self.take_at(n, Instant::now())
+    }
+
+    fn take_at(&mut self, n: usize, now: Instant) -> usize {
+        if self.rate <= 0.0 {
+            return n;
+        }
+        let elapsed = now.saturating_duration_since(self.last).as_secs_f64();
+        self.last = now;
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.rate);
+        let allowed = (n as f64).min(self.tokens.floor());
+        self.tokens -= allowed;
+        allowed as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow::array::{Int64Array, StringArray};
+    use arrow_schema::{DataType, Field, Schema};
+
+    use super::*;
+
+    fn batch() -> RecordBatch {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("_timestamp", DataType::Int64, false),
+            Field::new("level", DataType::Utf8, true),
+        ]));
+        RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::new(Int64Array::from(vec![1, 2, 3])),
+                Arc::new(StringArray::from(vec!["info", "error", "error"])),
+            ],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_validate_filter() {
+        assert!(validate_filter("").is_ok());
+        assert!(validate_filter("level = 'error' AND code >= 500").is_ok());
+        assert!(validate_filter("level = ").is_err());
+    }
+
+    #[test]
+    fn test_tail_filter_matches_rows() {
+        let mut filter = TailFilter::new("level = 'error'");
+        assert_eq!(filter.apply(batch()).unwrap().num_rows(), 2);
+        // the compiled expression is reused for the same schema
+        assert_eq!(filter.apply(batch()).unwrap().num_rows(), 2);
+    }
+
+    #[test]
+    fn test_tail_filter_empty_passes_everything() {
+        let mut filter = TailFilter::new("  ");
+        assert_eq!(filter.apply(batch()).unwrap().num_rows(), 3);
+    }
+
+    #[test]
+    fn test_tail_filter_unknown_column_matches_nothing() {
+        let mut filter = TailFilter::new("status_code = 500");
+        assert_eq!(filter.apply(batch()).unwrap().num_rows(), 0);
+    }
+
+    #[test]
+    fn test_tail_filter_non_boolean_is_error() {
+        let mut filter = TailFilter::new("_timestamp + 1");
+        assert!(filter.apply(batch()).is_err());
+    }
+
+    #[test]
+    fn test_rate_limiter() {
+        let mut limiter = RateLimiter::new(10);
+        let start = limiter.last;
+        assert_eq!(limiter.take_at(4, start), 4);
+        assert_eq!(limiter.take_at(10, start), 6);
+        assert_eq!(limiter.take_at(1, start), 0);
+        // half a second refills half of the bucket
+        assert_eq!(limiter.take_at(10, start + Duration::from_millis(500)), 5);
+        // the bucket never holds more than one second worth of records
+        assert_eq!(limiter.take_at(100, start + Duration::from_secs(60)), 10);
+    }
+
+    #[test]
+    fn test_rate_limiter_disabled() {
+        let mut limiter = RateLimiter::new(0);
+        assert_eq!(limiter.take(1_000_000), 1_000_000);
+    }
+}
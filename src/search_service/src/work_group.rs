@@ -14,7 +14,10 @@
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
 use config::{
-    datafusion::request::Request, get_config, meta::cluster::Node, metrics,
+    datafusion::request::Request,
+    get_config,
+    meta::{cluster::Node, search::SearchEventType},
+    metrics,
     utils::took_watcher::TookWatcher,
 };
 use infra::{
@@ -23,11 +26,12 @@ use infra::{
 };
 #[cfg(feature = "enterprise")]
 use {
-    crate::SEARCH_SERVER, config::meta::search::SearchEventType, infra::dist_lock,
-    infra::errors::ErrorCodes, o2_enterprise::enterprise::search::WorkGroup,
+    crate::SEARCH_SERVER, infra::dist_lock, infra::errors::ErrorCodes,
+    o2_enterprise::enterprise::search::WorkGroup,
 };
 
 use super::utils::AsyncDefer;
+use crate::fair_scheduler::{FAIR_SCHEDULER, PreemptPolicy, SchedulerGuard, Ticket};
 
 /// Guard that automatically releases work group lock when dropped
 pub struct DeferredLock {
@@ -36,6 +40,8 @@ pub struct DeferredLock {
     pub work_group: Option<WorkGroup>,
     pub work_group_str: String,
     _guard: AsyncDefer,
+    // slot of the fair scheduler, released after the work group
+    _fair_share: Option<SchedulerGuard<'static>>,
 }
 
 /// OSS version: Uses distributed lock for concurrency control
@@ -87,6 +93,7 @@ pub async fn check_work_group(
         took_wait,
         work_group_str,
         _guard: guard,
+        _fair_share: None,
     })
 }
 
@@ -195,6 +202,7 @@ pub async fn check_work_group(
         work_group: Some(work_group),
         work_group_str,
         _guard: guard,
+        _fair_share: None,
     })
 }
 
@@ -333,14 +341,20 @@ pub async fn acquire_work_group_lock(
     _nodes: &[Node],
     _file_id_list_vec: &[&FileId],
 ) -> Result<DeferredLock> {
-    check_work_group(
+    let fair_share = acquire_fair_share(trace_id, req, stop_watch, caller).await?;
+    let mut lock = check_work_group(
         trace_id,
         &req.org_id,
         req.timeout as u64,
         stop_watch,
         caller,
     )
-    .await
+    .await?;
+    if let Some((guard, took_wait)) = fair_share {
+        lock.took_wait += took_wait;
+        lock._fair_share = Some(guard);
+    }
+    Ok(lock)
 }
 
 #[cfg(feature = "enterprise")]
@@ -352,6 +366,8 @@ pub async fn acquire_work_group_lock(
     nodes: &[Node],
     file_id_list_vec: &[&FileId],
 ) -> Result<DeferredLock> {
+    let fair_share = acquire_fair_share(trace_id, req, stop_watch, caller).await?;
+
     let is_background_task = req
         .search_event_type
         .as_ref()
@@ -371,7 +387,7 @@ pub async fn acquire_work_group_lock(
 
     let user_id = req.user_id.as_deref();
 
-    check_work_group(
+    let mut lock = check_work_group(
         trace_id,
         &req.org_id,
         user_id,
@@ -380,5 +396,91 @@ pub async fn acquire_work_group_lock(
         stop_watch,
         caller,
     )
-    .await
+    .await?;
+    if let Some((guard, took_wait)) = fair_share {
+        lock.took_wait += took_wait;
+        lock._fair_share = Some(guard);
+    }
+    Ok(lock)
+}
+
+/// Wait for a slot of the fair scheduler, when it is enabled
+#[tracing::instrument(
+    name = "service:search:work_group:fair_share",
+    skip_all,
+    fields(caller = caller)
+)]
+async fn acquire_fair_share(
+    trace_id: &str,
+    req: &Request,
+    stop_watch: &mut TookWatcher,
+    caller: &str,
+) -> Result<Option<(SchedulerGuard<'static>, usize)>> {
+    if !get_config().search.fair_scheduler_enabled {
+        return Ok(None);
+    }
+
+    let org_id = req.org_id.as_str();
+    let role = req.user_id.as_ref().and_then(|user_id| {
+        db::org_users::get_cached_user_org(org_id, user_id).map(|user| user.role.to_string())
+    });
+    let ticket = Ticket {
+        trace_id: trace_id.to_string(),
+        org_id: org_id.to_string(),
+        user_id: req.user_id.clone(),
+        role,
+        event_type: req
+            .search_event_type
+            .as_ref()
+            .and_then(|st| SearchEventType::try_from(st.as_str()).ok()),
+    };
+    let remaining = std::time::Duration::from_millis(
+        (req.timeout as u64 * 1000).saturating_sub(stop_watch.total_millis()),
+    );
+    let policy = FAIR_SCHEDULER.config().preempt_policy;
+    let guard = FAIR_SCHEDULER
+        .acquire(ticket, remaining, |org_id, preempted_trace_id| {
+            if policy == PreemptPolicy::Cancel {
+                cancel_preempted(org_id, preempted_trace_id);
+            }
+        })
+        .await;
+    let Some(guard) = guard else {
+        metrics::QUERY_TIMEOUT_NUMS
+            .with_label_values(&[org_id])
+            .inc();
+        metrics::QUERY_PENDING_NUMS
+            .with_label_values(&[org_id])
+            .dec();
+        return Err(Error::Message(format!(
+            "[trace_id {trace_id}] {caller}->search: request timeout in fair scheduler queue"
+        )));
+    };
+
+    let took_wait = stop_watch.record_split("fair_share_wait").as_millis() as usize;
+    log::info!(
+        "[trace_id {trace_id}] {caller}->search: wait in fair scheduler took: {took_wait} ms"
+    );
+    Ok(Some((guard, took_wait)))
+}
+
+#[cfg(feature = "enterprise")]
+fn cancel_preempted(org_id: &str, trace_id: &str) {
+    let org_id = org_id.to_string();
+    let trace_id = trace_id.to_string();
+    tokio::spawn(async move {
+        if let Err(e) = crate::cancel_query(&org_id, &trace_id).await {
+            log::error!(
+                "[trace_id {trace_id}] fair_scheduler: failed to cancel preempted search: {e}"
+            );
+        }
+    });
+}
+
+// canceling a running search needs the query manager, the search only gives its slot away
+#[cfg(not(feature = "enterprise"))]
+fn cancel_preempted(_org_id: &str, trace_id: &str) {
+    log::warn!(
+        "[trace_id {trace_id}] fair_scheduler: cancel is not supported, the preempted search keeps running without a slot"
+    );
 }
@@ -520,6 +520,15 @@ pub async fn update_stream_settings(
     if let Some(v) = new_settings.storage_type {
         settings.storage_type = v;
     }
+    if let Some(v) = new_settings.fts_tokenizer
+        && v != settings.fts_tokenizer
+    {
+        settings.fts_tokenizer = v;
+        settings.fts_tokenizer_updated_at = now_micros();
+    }
+    if let Some(v) = new_settings.sort_keys {
+        settings.sort_keys = v;
+    }
     if let Some(v) = new_settings.data_retention {
         settings.data_retention = v;
     }
@@ -610,6 +619,34 @@ pub async fn update_stream_settings(
             .extend(new_settings.bloom_filter_fields.add);
     }
 
+    // range_index_fields: remove first, then add
+    if !new_settings.range_index_fields.remove.is_empty() {
+        settings
+            .range_index_fields
+            .retain(|f| !new_settings.range_index_fields.remove.contains(f));
+    }
+    if !new_settings.range_index_fields.add.is_empty() {
+        settings
+            .range_index_fields
+            .extend(new_settings.range_index_fields.add);
+    }
+
+    // vector_fields: remove by name first, then add (re-adding a field with a
+    // new dimension replaces the old entry)
+    if !new_settings.vector_fields.remove.is_empty() {
+        settings.vector_fields.retain(|f| {
+            !new_settings
+                .vector_fields
+                .remove
+                .iter()
+                .any(|r| r.name == f.name)
+        });
+    }
+    for field in new_settings.vector_fields.add {
+        settings.vector_fields.retain(|f| f.name != field.name);
+        settings.vector_fields.push(field);
+    }
+
     // check for user defined schema
     if !new_settings.defined_schema_fields.remove.is_empty() {
         settings
@@ -19,26 +19,41 @@ mod sequential;
 use std::{collections::HashMap, sync::Arc};
 
 use arrow::array::{
-    Array, BooleanArray, Float64Array, Int64Array, LargeStringArray, RecordBatch, StringArray,
-    StringViewArray, UInt64Array,
+    Array, AsArray, BooleanArray, Float64Array, Int64Array, LargeStringArray, RecordBatch,
+    StringArray, StringViewArray, UInt64Array,
 };
 use arrow_schema::{DataType, Schema};
 use bytes::Bytes;
 use config::{
     FileFormat, INDEX_FIELD_NAME_FOR_ALL, PARQUET_MAX_ROW_GROUP_SIZE, TIMESTAMP_COL_NAME,
-    get_config, tantivy::tokenizer::O2_TOKENIZER, utils::inverted_index::to_tantivy_name,
+    get_config,
+    meta::stream::{FtsTokenizer, VectorField},
+    tantivy::tokenizer::fts_tokenizer_name,
+    utils::{
+        inverted_index::{to_range_index_field_name, to_tantivy_name},
+        vector::array_to_vectors,
+    },
 };
+use futures::TryStreamExt;
 use hashbrown::HashSet;
 use infra::storage;
+use parking_lot::Mutex;
+use tantivy::Directory;
 
-use crate::puffin_directory::{PROP_ROW_GROUP_SIZE, writer::PuffinDirWriter};
+use crate::{
+    puffin_directory::{PROP_INEXACT_RANGE_FIELDS, PROP_ROW_GROUP_SIZE, writer::PuffinDirWriter},
+    vector_index::{VectorIndex, vector_index_file_name},
+};
 
 pub async fn create_tantivy_index(
     caller: &str,
     org_id: &str,
     parquet_file_name: &str,
     fts_fields: &[String],
+    fts_tokenizer: &FtsTokenizer,
     index_fields: &[String],
+    range_fields: &[String],
+    vector_fields: &[VectorField],
     schema: Arc<Schema>,
     buf: Bytes,
 ) -> Result<usize, anyhow::Error> {
@@ -48,23 +63,49 @@ pub async fn create_tantivy_index(
     let file_format = FileFormat::from_extension(parquet_file_name).unwrap_or_default();
     let thread_num = cfg.compact.tantivy_builder_thread_num;
 
-    let Some(index_schema) = build_tantivy_schema(fts_fields, index_fields, &schema) else {
+    let Some(index_schema) = build_tantivy_schema(
+        fts_fields,
+        fts_tokenizer,
+        index_fields,
+        range_fields,
+        vector_fields,
+        &schema,
+    ) else {
         return Ok(0);
     };
 
     let dir = PuffinDirWriter::new();
+    let vector_fields = index_schema.vector_fields.clone();
+    let inexact_range_fields = index_schema.inexact_range_fields.clone();
     let index = if thread_num > 1 {
-        parallel::build_index(dir.clone(), file_format, buf, index_schema, thread_num).await?
+        parallel::build_index(
+            dir.clone(),
+            file_format,
+            buf.clone(),
+            index_schema,
+            thread_num,
+        )
+        .await?
     } else {
-        sequential::build_index(dir.clone(), file_format, buf, index_schema).await?
+        sequential::build_index(dir.clone(), file_format, buf.clone(), index_schema).await?
     };
     if index.is_none() {
         return Ok(0);
     }
+    build_vector_indexes(&dir, file_format, buf, &vector_fields).await?;
 
     // Record the parquet row group size in effect at index build time so the
     // reader can map doc_ids back to row groups even if the constant changes.
     dir.set_property(PROP_ROW_GROUP_SIZE, PARQUET_MAX_ROW_GROUP_SIZE.to_string());
+    let inexact_range_fields = inexact_range_fields.lock().clone();
+    if !inexact_range_fields.is_empty() {
+        log::warn!(
+            "{caller} range index of {parquet_file_name} is inexact for fields: {inexact_range_fields:?}"
+        );
+        let mut fields = inexact_range_fields.into_iter().collect::<Vec<_>>();
+        fields.sort();
+        dir.set_property(PROP_INEXACT_RANGE_FIELDS, fields.join(","));
+    }
     let puffin_bytes = dir.to_puffin_bytes()?;
     let index_size = puffin_bytes.len();
 
@@ -99,6 +140,67 @@ pub async fn create_tantivy_index(
     Ok(index_size)
 }
 
+/// Builds the ANN index of every vector field from a second pass over the
+/// file and stores it as `{field}.ann` next to the tantivy files. Node doc ids
+/// are row numbers, the same as the tantivy doc ids.
+async fn build_vector_indexes(
+    dir: &PuffinDirWriter,
+    file_format: FileFormat,
+    buf: Bytes,
+    vector_fields: &[VectorField],
+) -> Result<(), anyhow::Error> {
+    if vector_fields.is_empty() {
+        return Ok(());
+    }
+    let start = std::time::Instant::now();
+    let projection = vector_fields
+        .iter()
+        .map(|f| f.name.clone())
+        .collect::<Vec<_>>();
+    let mut reader = reader::file_stream(file_format, buf, Some(&projection)).await?;
+    let mut indexes = vector_fields
+        .iter()
+        .map(|f| VectorIndex::new(f.dimensions))
+        .collect::<Vec<_>>();
+    let mut row_offset = 0u32;
+    while let Some(batch) = reader.try_next().await? {
+        let num_rows = batch.num_rows() as u32;
+        let fields = vector_fields.to_vec();
+        indexes = tokio::task::spawn_blocking(move || {
+            for (field, index) in fields.iter().zip(indexes.iter_mut()) {
+                let Some(column) = batch.column_by_name(&field.name) else {
+                    continue;
+                };
+                let Ok(vectors) = array_to_vectors(column) else {
+                    continue;
+                };
+                for (i, vector) in vectors.into_iter().enumerate() {
+                    if let Some(vector) = vector {
+                        index.insert(row_offset + i as u32, vector);
+                    }
+                }
+            }
+            indexes
+        })
+        .await?;
+        row_offset += num_rows;
+    }
+
+    for (field, index) in vector_fields.iter().zip(indexes) {
+        if index.is_empty() {
+            continue;
+        }
+        let file_name = vector_index_file_name(&field.name);
+        dir.atomic_write(std::path::Path::new(&file_name), &index.to_bytes())?;
+    }
+    log::info!(
+        "build_vector_indexes: built {} vector indexes in {} ms",
+        vector_fields.len(),
+        start.elapsed().as_millis()
+    );
+    Ok(())
+}
+
 /// Bundle of the tantivy schema and the per-row metadata both build paths need.
 ///
 /// Built once by [`build_tantivy_schema`] and shared by the
@@ -113,6 +215,24 @@ pub(super) struct TantivyIndexSchema {
     /// Field handle for `INDEX_FIELD_NAME_FOR_ALL`, present when there is at
     /// least one full-text-search field.
     pub(super) fts_field: Option<tantivy::schema::Field>,
+    /// Numeric columns indexed as fast fields for range queries, filtered by
+    /// data type.
+    pub(super) range_fields: HashSet<String>,
+    /// Range fields with values that couldn't be cast to the indexed type,
+    /// shared by the workers, they are recorded in the index properties.
+    pub(super) inexact_range_fields: Arc<Mutex<HashSet<String>>>,
+    /// Vector fields present in the file as strings. They get an ANN index of
+    /// their own instead of tantivy fields, see [`build_vector_indexes`].
+    pub(super) vector_fields: Vec<VectorField>,
+}
+
+impl TantivyIndexSchema {
+    /// Columns that must be read from the data file to build the documents.
+    pub(super) fn projection(&self) -> Vec<String> {
+        let mut projection: Vec<String> = (&self.fields | &self.range_fields).into_iter().collect();
+        projection.push(TIMESTAMP_COL_NAME.to_string());
+        projection
+    }
 }
 
 /// Builds the tantivy `Schema` shared by both index-build paths.
@@ -121,7 +241,10 @@ pub(super) struct TantivyIndexSchema {
 /// intersect with the stream schema).
 fn build_tantivy_schema(
     fts_fields: &[String],
+    fts_tokenizer: &FtsTokenizer,
     index_fields: &[String],
+    range_fields: &[String],
+    vector_fields: &[VectorField],
     arrow_schema: &Schema,
 ) -> Option<TantivyIndexSchema> {
     let mut tantivy_schema_builder = tantivy::schema::SchemaBuilder::new();
@@ -146,16 +269,51 @@ fn build_tantivy_schema(
         .map(String::from)
         .collect::<HashSet<_>>();
     let tantivy_fields: HashSet<_> = &fts_fields_filtered | &index_fields_filtered;
+    let range_fields_filtered = range_fields
+        .iter()
+        .filter(|f| {
+            f.as_str() != TIMESTAMP_COL_NAME
+                && schema_fields.get(f).is_some_and(|v| {
+                    matches!(
+                        v.data_type(),
+                        DataType::Int64 | DataType::UInt64 | DataType::Float64
+                    )
+                })
+        })
+        .map(String::from)
+        .collect::<HashSet<_>>();
 
-    if tantivy_fields.is_empty() {
+    let vector_fields_filtered = vector_fields
+        .iter()
+        .filter(|f| {
+            schema_fields.get(&f.name).is_some_and(|v| {
+                matches!(
+                    v.data_type(),
+                    DataType::Utf8 | DataType::LargeUtf8 | DataType::Utf8View
+                )
+            })
+        })
+        .cloned()
+        .collect::<Vec<_>>();
+
+    if tantivy_fields.is_empty()
+        && range_fields_filtered.is_empty()
+        && vector_fields_filtered.is_empty()
+    {
         return None;
     }
 
     if !fts_fields_filtered.is_empty() {
+        // positions are only needed to answer phrase queries from the index
+        let fts_record_option = if get_config().limit.inverted_index_positions_enabled {
+            tantivy::schema::IndexRecordOption::WithFreqsAndPositions
+        } else {
+            tantivy::schema::IndexRecordOption::Basic
+        };
         let fts_opts = tantivy::schema::TextOptions::default().set_indexing_options(
             tantivy::schema::TextFieldIndexing::default()
-                .set_index_option(tantivy::schema::IndexRecordOption::Basic)
-                .set_tokenizer(O2_TOKENIZER)
+                .set_index_option(fts_record_option)
+                .set_tokenizer(&fts_tokenizer_name(fts_tokenizer))
                 .set_fieldnorms(false),
         );
         tantivy_schema_builder.add_text_field(INDEX_FIELD_NAME_FOR_ALL, fts_opts);
@@ -176,6 +334,17 @@ fn build_tantivy_schema(
         tantivy_schema_builder.add_text_field(field, index_opts.clone());
     }
 
+    // range queries on a fast field scan the columnar values, no postings needed
+    let range_opts = tantivy::schema::NumericOptions::default().set_fast();
+    for field in range_fields_filtered.iter() {
+        let name = to_range_index_field_name(field);
+        match schema_fields[field].data_type() {
+            DataType::Int64 => tantivy_schema_builder.add_i64_field(&name, range_opts.clone()),
+            DataType::UInt64 => tantivy_schema_builder.add_u64_field(&name, range_opts.clone()),
+            _ => tantivy_schema_builder.add_f64_field(&name, range_opts.clone()),
+        };
+    }
+
     tantivy_schema_builder.add_i64_field(TIMESTAMP_COL_NAME, tantivy::schema::FAST);
 
     let tantivy_schema = tantivy_schema_builder.build();
@@ -185,6 +354,9 @@ fn build_tantivy_schema(
         schema: tantivy_schema,
         fields: tantivy_fields,
         fts_field,
+        range_fields: range_fields_filtered,
+        inexact_range_fields: Arc::new(Mutex::new(HashSet::new())),
+        vector_fields: vector_fields_filtered,
     })
 }
 
@@ -255,6 +427,54 @@ pub(super) fn convert_batch_to_docs_sync(
         }
     }
 
+    for column_name in index_schema.range_fields.iter() {
+        let field = tantivy_schema
+            .get_field(&to_range_index_field_name(column_name))
+            .expect("range field must exist in tantivy schema");
+        let Some(data) = batch.column_by_name(column_name) else {
+            continue;
+        };
+        // older files may hold the column with another type, cast it the same
+        // way the query would so the index never drops a matching row
+        let data_type = match tantivy_schema.get_field_entry(field).field_type() {
+            tantivy::schema::FieldType::I64(_) => DataType::Int64,
+            tantivy::schema::FieldType::U64(_) => DataType::UInt64,
+            _ => DataType::Float64,
+        };
+        // a value the cast turns into null would be missing from the index,
+        // the file is marked so range conditions on the field keep their filter
+        let data = match arrow::compute::cast(data, &data_type) {
+            Ok(cast) if cast.null_count() == data.null_count() => cast,
+            _ => {
+                index_schema
+                    .inexact_range_fields
+                    .lock()
+                    .insert(column_name.to_string());
+                continue;
+            }
+        };
+        // nulls are left out so they never match a range
+        if let Some(array) = data.as_any().downcast_ref::<Int64Array>() {
+            for (i, doc) in docs.iter_mut().enumerate() {
+                if array.is_valid(i) {
+                    doc.add_i64(field, array.value(i));
+                }
+            }
+        } else if let Some(array) = data.as_any().downcast_ref::<UInt64Array>() {
+            for (i, doc) in docs.iter_mut().enumerate() {
+                if array.is_valid(i) {
+                    doc.add_u64(field, array.value(i));
+                }
+            }
+        } else if let Some(array) = data.as_any().downcast_ref::<Float64Array>() {
+            for (i, doc) in docs.iter_mut().enumerate() {
+                if array.is_valid(i) {
+                    doc.add_f64(field, array.value(i));
+                }
+            }
+        }
+    }
+
     let ts_data: &Int64Array = batch
         .column_by_name(TIMESTAMP_COL_NAME)
         .and_then(|c| c.as_any().downcast_ref::<Int64Array>())
@@ -359,14 +579,17 @@ pub(super) mod tests {
         index: &[String],
         arrow_schema: &Schema,
     ) -> TantivyIndexSchema {
-        build_tantivy_schema(fts, index, arrow_schema).expect("schema helper returned None")
+        build_tantivy_schema(fts, &FtsTokenizer::O2, index, &[], &[], arrow_schema)
+            .expect("schema helper returned None")
     }
 
     #[tokio::test]
     async fn test_build_tantivy_schema_no_fields() {
         let batch = create_test_batch(10, true, true, true);
         // No fields to index → helper returns None and the orchestrator short-circuits.
-        assert!(build_tantivy_schema(&[], &[], &batch.schema()).is_none());
+        assert!(
+            build_tantivy_schema(&[], &FtsTokenizer::O2, &[], &[], &[], &batch.schema()).is_none()
+        );
     }
 
     #[tokio::test]
@@ -376,12 +599,152 @@ pub(super) mod tests {
         // and the orchestrator returns 0 without creating an index.
         let result = build_tantivy_schema(
             &["nonexistent_field".to_string()],
+            &FtsTokenizer::O2,
             &["another_nonexistent_field".to_string()],
+            &["missing_range_field".to_string()],
+            &[VectorField {
+                name: "missing_vector_field".to_string(),
+                dimensions: 3,
+            }],
             &batch.schema(),
         );
         assert!(result.is_none());
     }
 
+    #[tokio::test]
+    async fn test_build_tantivy_schema_fts_tokenizer() {
+        let batch = create_test_batch(10, true, true, true);
+        let tokenizer = FtsTokenizer::Ngram {
+            min_gram: 2,
+            max_gram: 3,
+        };
+        let index_schema = build_tantivy_schema(
+            &["content".to_string()],
+            &tokenizer,
+            &[],
+            &[],
+            &[],
+            &batch.schema(),
+        )
+        .unwrap();
+        // the tokenizer is recorded in the index, the search side reads it back
+        assert_eq!(
+            config::tantivy::tokenizer::schema_fts_tokenizer(&index_schema.schema),
+            tokenizer
+        );
+    }
+
+    #[test]
+    fn test_build_tantivy_schema_range_fields() {
+        let schema = Schema::new(vec![
+            Field::new(TIMESTAMP_COL_NAME, DataType::Int64, false),
+            Field::new("latency_ms", DataType::Int64, true),
+            Field::new("ratio", DataType::Float64, true),
+            Field::new("status", DataType::Utf8, true),
+        ]);
+        let range_fields = ["latency_ms", "ratio", "status", TIMESTAMP_COL_NAME]
+            .map(String::from)
+            .to_vec();
+        // a range-only configuration still builds an index
+        let index_schema =
+            build_tantivy_schema(&[], &FtsTokenizer::O2, &[], &range_fields, &[], &schema).unwrap();
+        assert!(index_schema.fields.is_empty());
+        assert_eq!(
+            index_schema.range_fields,
+            HashSet::from_iter(["latency_ms".to_string(), "ratio".to_string()])
+        );
+        let field = index_schema
+            .schema
+            .get_field(&to_range_index_field_name("latency_ms"))
+            .unwrap();
+        let entry = index_schema.schema.get_field_entry(field);
+        assert!(entry.is_fast());
+        assert!(
+            index_schema
+                .schema
+                .get_field(&to_range_index_field_name("status"))
+                .is_err()
+        );
+
+        let batch = RecordBatch::try_new(
+            Arc::new(schema),
+            vec![
+                Arc::new(Int64Array::from(vec![1, 2])),
+                Arc::new(Int64Array::from(vec![Some(10), None])),
+                Arc::new(Float64Array::from(vec![0.5, 1.5])),
+                Arc::new(StringArray::from(vec!["a", "b"])),
+            ],
+        )
+        .unwrap();
+        let docs = convert_batch_to_docs_sync(&batch, &index_schema);
+        assert_eq!(docs[0].get_all(field).count(), 1);
+        // null values are not indexed
+        assert_eq!(docs[1].get_all(field).count(), 0);
+        assert!(index_schema.inexact_range_fields.lock().is_empty());
+
+        // an older file holds strings, the one that doesn't cast marks the field
+        let old_schema = Schema::new(vec![
+            Field::new(TIMESTAMP_COL_NAME, DataType::Int64, false),
+            Field::new("latency_ms", DataType::Utf8, true),
+        ]);
+        let batch = RecordBatch::try_new(
+            Arc::new(old_schema),
+            vec![
+                Arc::new(Int64Array::from(vec![1, 2, 3])),
+                Arc::new(StringArray::from(vec![Some("10"), None, Some("slow")])),
+            ],
+        )
+        .unwrap();
+        convert_batch_to_docs_sync(&batch, &index_schema);
+        assert_eq!(
+            *index_schema.inexact_range_fields.lock(),
+            HashSet::from_iter(["latency_ms".to_string()])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_build_vector_indexes() {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new(TIMESTAMP_COL_NAME, DataType::Int64, false),
+            Field::new("embedding", DataType::Utf8, true),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(Int64Array::from(vec![1, 2, 3, 4])),
+                Arc::new(StringArray::from(vec![
+                    Some("[1,0]"),
+                    None,
+                    Some("[0,1]"),
+                    Some("not a vector"),
+                ])),
+            ],
+        )
+        .unwrap();
+        let fields = vec![VectorField {
+            name: "embedding".to_string(),
+            dimensions: 2,
+        }];
+        // a vector-only configuration still builds an index, but the vectors
+        // are not read by the tantivy builder
+        let index_schema =
+            build_tantivy_schema(&[], &FtsTokenizer::O2, &[], &[], &fields, &schema).unwrap();
+        assert_eq!(index_schema.vector_fields, fields);
+        assert!(!index_schema.projection().contains(&"embedding".to_string()));
+
+        let buf = create_test_parquet_bytes(vec![batch]).await;
+        let dir = PuffinDirWriter::new();
+        build_vector_indexes(&dir, FileFormat::Parquet, buf, &fields)
+            .await
+            .unwrap();
+        let data = dir
+            .atomic_read(std::path::Path::new(&vector_index_file_name("embedding")))
+            .unwrap();
+        let index = VectorIndex::from_bytes(&data).unwrap();
+        assert_eq!(index.len(), 2);
+        assert_eq!(index.search(&[0.1, 1.0], 1, 10)[0].0, 2);
+    }
+
     #[tokio::test]
     async fn test_create_tantivy_index_with_empty_data() {
         let empty_batch = create_test_batch(0, true, true, true);
@@ -393,7 +756,10 @@ pub(super) mod tests {
             "default",
             "test_file.parquet",
             &["content".to_string()],
+            &FtsTokenizer::O2,
             &["status".to_string()],
+            &[],
+            &[],
             empty_batch.schema(),
             buf,
         )
@@ -414,7 +780,10 @@ pub(super) mod tests {
             "default",
             "test_file.parquet",
             &[], // No FTS fields
+            &FtsTokenizer::O2,
             &[], // No index fields
+            &[], // No range fields
+            &[], // No vector fields
             batch.schema(),
             buf,
         )
@@ -435,7 +804,10 @@ pub(super) mod tests {
             "default",
             "invalid_filename", // This won't convert to a valid tantivy filename
             &["content".to_string()],
+            &FtsTokenizer::O2,
             &["status".to_string()],
+            &[],
+            &[],
             batch.schema(),
             buf,
         )
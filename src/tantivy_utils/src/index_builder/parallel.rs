@@ -18,8 +18,8 @@ use std::sync::Arc;
 use anyhow::{Context, Error};
 use bytes::Bytes;
 use config::{
-    FileFormat, PARQUET_MAX_ROW_GROUP_SIZE, TIMESTAMP_COL_NAME,
-    tantivy::tokenizer::{CollectType, O2_TOKENIZER, o2_tokenizer_build},
+    FileFormat, PARQUET_MAX_ROW_GROUP_SIZE,
+    tantivy::tokenizer::{CollectType, register_fts_tokenizer},
 };
 use futures::future::join_all;
 use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
@@ -231,14 +231,17 @@ fn build_segment<D: tantivy::Directory>(
     index_schema: TantivyIndexSchema,
     dir: D,
 ) -> Result<SegmentOutput, Error> {
-    let mut projection: Vec<String> = index_schema.fields.iter().cloned().collect();
-    projection.push(TIMESTAMP_COL_NAME.to_string());
+    let projection = index_schema.projection();
 
     let iter = chunk_iter(selector, buf, Some(&projection))
         .with_context(|| format!("chunk {chunk_idx}: reader build failed"))?;
 
     let tokenizer_manager = tantivy::tokenizer::TokenizerManager::default();
-    tokenizer_manager.register(O2_TOKENIZER, o2_tokenizer_build(CollectType::Ingest));
+    register_fts_tokenizer(
+        &tokenizer_manager,
+        &index_schema.schema,
+        CollectType::Ingest,
+    );
     let mut writer = tantivy::IndexBuilder::new()
         .schema(index_schema.schema.clone())
         .tokenizers(tokenizer_manager)
@@ -17,8 +17,8 @@ use anyhow::Context;
 use arrow::array::RecordBatch;
 use bytes::Bytes;
 use config::{
-    FileFormat, TIMESTAMP_COL_NAME,
-    tantivy::tokenizer::{CollectType, O2_TOKENIZER, o2_tokenizer_build},
+    FileFormat,
+    tantivy::tokenizer::{CollectType, register_fts_tokenizer},
 };
 use futures::TryStreamExt;
 use tokio::task::JoinHandle;
@@ -34,11 +34,14 @@ pub(super) async fn build_index<D: tantivy::Directory>(
     index_schema: TantivyIndexSchema,
 ) -> Result<Option<tantivy::Index>, anyhow::Error> {
     let start = std::time::Instant::now();
-    let mut projection: Vec<String> = index_schema.fields.iter().cloned().collect();
-    projection.push(TIMESTAMP_COL_NAME.to_string());
+    let projection = index_schema.projection();
     let reader = file_stream(file_format, buf, Some(&projection)).await?;
     let tokenizer_manager = tantivy::tokenizer::TokenizerManager::default();
-    tokenizer_manager.register(O2_TOKENIZER, o2_tokenizer_build(CollectType::Ingest));
+    register_fts_tokenizer(
+        &tokenizer_manager,
+        &index_schema.schema,
+        CollectType::Ingest,
+    );
     let index_writer = tantivy::IndexBuilder::new()
         .schema(index_schema.schema.clone())
         .tokenizers(tokenizer_manager)
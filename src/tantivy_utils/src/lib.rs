@@ -16,3 +16,4 @@
 pub mod index_builder;
 pub mod puffin;
 pub mod puffin_directory;
+pub mod vector_index;
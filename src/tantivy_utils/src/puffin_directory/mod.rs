@@ -37,6 +37,9 @@ pub mod writer;
 const ALLOWED_FILE_EXT: &[&str] = &["term", "idx", "pos", "fast"];
 const EMPTY_FILE_EXT: &[&str] = &["fieldnorm", "store"];
 const META_JSON: &str = "meta.json";
+/// Extension of the per-field vector (ANN) index files stored next to the
+/// tantivy files, see [`crate::vector_index`].
+pub const VECTOR_INDEX_EXT: &str = "ann";
 const FOOTER_CACHE: &str = "footer_cache";
 
 /// Puffin file-level property name carrying the parquet row group size in
@@ -44,6 +47,11 @@ const FOOTER_CACHE: &str = "footer_cache";
 /// parquet row groups even if `PARQUET_MAX_ROW_GROUP_SIZE` changes later.
 pub const PROP_ROW_GROUP_SIZE: &str = "row_group_size";
 
+/// Puffin file-level property name listing, comma separated, the range index
+/// fields with values that couldn't be indexed, e.g. strings in a numeric
+/// column of an older file. Range conditions on them keep their filter.
+pub const PROP_INEXACT_RANGE_FIELDS: &str = "inexact_range_fields";
+
 // Lazy loaded global instance of RAM directory which will contain
 // all the files of an empty tantivy index. This instance will be used to fill the missing files
 // from the `.ttv` file, as tantivy needs them regardless of the configuration of a field.
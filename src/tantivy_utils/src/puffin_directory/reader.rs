@@ -73,6 +73,20 @@ impl PuffinDirReader {
     pub fn get_property(&self, key: &str) -> Option<&str> {
         self.properties.get(key).map(|s| s.as_str())
     }
+
+    /// Read a whole blob that is not a tantivy file (e.g. a vector index).
+    /// Returns `None` when the puffin file has no blob for `path`.
+    pub async fn read_file(&self, path: &Path) -> io::Result<Option<bytes::Bytes>> {
+        let Some(meta) = self.blobs_metadata.get(path) else {
+            return Ok(None);
+        };
+        let data = self
+            .source
+            .read_blob_bytes(meta, None)
+            .await
+            .map_err(|e| io::Error::other(format!("Error reading blob {path:?}: {e}")))?;
+        Ok(Some(data))
+    }
 }
 
 impl Clone for PuffinDirReader {
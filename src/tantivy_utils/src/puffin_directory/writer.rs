@@ -29,7 +29,7 @@ use tantivy::{
 use super::{FOOTER_CACHE, footer_cache::build_footer_cache};
 use crate::{
     puffin::{BlobTypes, writer::PuffinBytesWriter},
-    puffin_directory::{ALLOWED_FILE_EXT, META_JSON},
+    puffin_directory::{ALLOWED_FILE_EXT, META_JSON, VECTOR_INDEX_EXT},
 };
 /// Puffin directory is a puffin file which contains all the tantivy files.
 /// Each tantivy file is stored as a blob in the puffin file, along with their file name.
@@ -99,7 +99,8 @@ impl PuffinDirWriter {
         let allowed_file_paths = file_paths.iter().filter(|path| {
             let mut allowed = false;
             if let Some(path_ext) = path.extension()
-                && ALLOWED_FILE_EXT.contains(&path_ext.to_str().unwrap())
+                && (ALLOWED_FILE_EXT.contains(&path_ext.to_str().unwrap())
+                    || path_ext == VECTOR_INDEX_EXT)
             {
                 allowed = true;
             }
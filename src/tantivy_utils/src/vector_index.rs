@@ -0,0 +1,411 @@
+// Copyright 2026 OpenObserve Inc.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+//! Per-file approximate nearest-neighbour index over a vector field.
+//!
+//! The index is a small HNSW graph over the unit-normalised vectors of one
+//! data file, so the distance between two nodes is `1 - dot(a, b)` and the
+//! similarity reported for a hit is the cosine similarity. Each node keeps the
+//! tantivy doc id (= row number in the data file) of the row it came from.
+//!
+//! The graph is stored as `{field}.ann` inside the tantivy puffin file.
+
+use std::{cmp::Ordering, collections::BinaryHeap, io::Cursor};
+
+use anyhow::{Result, anyhow, ensure};
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use config::utils::vector::{dot, normalize};
+use hashbrown::HashSet;
+
+use crate::puffin_directory::VECTOR_INDEX_EXT;
+
+const MAGIC: &[u8; 4] = b"OANN";
+const VERSION: u32 = 1;
+/// Max neighbours per node on the upper layers; layer 0 keeps twice as many.
+const MAX_NEIGHBORS: usize = 16;
+const EF_CONSTRUCTION: usize = 100;
+/// Default candidate list size at query time (raised to `k` when smaller).
+pub const DEFAULT_EF_SEARCH: usize = 64;
+const NO_ENTRY: u32 = u32::MAX;
+
+/// Name of the index file for `field` in the puffin directory.
+pub fn vector_index_file_name(field: &str) -> String {
+    format!("{field}.{VECTOR_INDEX_EXT}")
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Scored {
+    dist: f32,
+    node: u32,
+}
+
+impl Eq for Scored {}
+
+impl PartialOrd for Scored {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Scored {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.dist
+            .total_cmp(&other.dist)
+            .then(self.node.cmp(&other.node))
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct VectorIndex {
+    dimensions: usize,
+    entry: Option<u32>,
+    /// Normalised vectors, `dimensions` floats per node.
+    vectors: Vec<f32>,
+    doc_ids: Vec<u32>,
+    /// `neighbors[node][layer]`; a node lives on layers `0..neighbors[node].len()`.
+    neighbors: Vec<Vec<Vec<u32>>>,
+}
+
+impl VectorIndex {
+    pub fn new(dimensions: usize) -> Self {
+        Self {
+            dimensions,
+            ..Default::default()
+        }
+    }
+
+    pub fn dimensions(&self) -> usize {
+        self.dimensions
+    }
+
+    pub fn len(&self) -> usize {
+        self.doc_ids.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.doc_ids.is_empty()
+    }
+
+    /// Add the vector of row `doc_id`. Vectors of the wrong dimension and
+    /// zero vectors are skipped (they can never be a cosine neighbour);
+    /// returns whether the vector was added.
+    pub fn insert(&mut self, doc_id: u32, mut vector: Vec<f32>) -> bool {
+        if vector.len() != self.dimensions || !normalize(&mut vector) {
+            return false;
+        }
+        let node = self.doc_ids.len() as u32;
+        let level = random_level(node);
+        self.doc_ids.push(doc_id);
+        self.vectors.extend_from_slice(&vector);
+        self.neighbors.push(vec![Vec::new(); level + 1]);
+
+        let Some(entry) = self.entry else {
+            self.entry = Some(node);
+            return true;
+        };
+        let top = self.level_of(entry);
+        let mut ep = Scored {
+            dist: self.distance(&vector, entry),
+            node: entry,
+        };
+        for layer in (level + 1..=top).rev() {
+            ep = self.search_layer(&vector, &[ep], 1, layer)[0];
+        }
+        let mut eps = vec![ep];
+        for layer in (0..=level.min(top)).rev() {
+            let candidates = self.search_layer(&vector, &eps, EF_CONSTRUCTION, layer);
+            let cap = max_neighbors(layer);
+            let selected = candidates
+                .iter()
+                .take(cap)
+                .map(|c| c.node)
+                .collect::<Vec<_>>();
+            for &neighbor in &selected {
+                self.connect(neighbor, node, layer);
+            }
+            self.neighbors[node as usize][layer] = selected;
+            eps = candidates;
+        }
+        if level > top {
+            self.entry = Some(node);
+        }
+        true
+    }
+
+    /// The `k` rows most similar to `query`, best first, as
+    /// `(doc_id, cosine similarity)`.
+    pub fn search(&self, query: &[f32], k: usize, ef: usize) -> Vec<(u32, f32)> {
+        let Some(entry) = self.entry else {
+            return vec![];
+        };
+        let mut query = query.to_vec();
+        if k == 0 || query.len() != self.dimensions || !normalize(&mut query) {
+            return vec![];
+        }
+        let mut ep = Scored {
+            dist: self.distance(&query, entry),
+            node: entry,
+        };
+        for layer in (1..=self.level_of(entry)).rev() {
+            ep = self.search_layer(&query, &[ep], 1, layer)[0];
+        }
+        self.search_layer(&query, &[ep], ef.max(k), 0)
+            .into_iter()
+            .take(k)
+            .map(|c| (self.doc_ids[c.node as usize], 1.0 - c.dist))
+            .collect()
+    }
+
+    fn vector(&self, node: u32) -> &[f32] {
+        let start = node as usize * self.dimensions;
+        &self.vectors[start..start + self.dimensions]
+    }
+
+    fn distance(&self, query: &[f32], node: u32) -> f32 {
+        1.0 - dot(query, self.vector(node))
+    }
+
+    fn level_of(&self, node: u32) -> usize {
+        self.neighbors[node as usize].len() - 1
+    }
+
+    /// Link `from -> to` on `layer`, keeping only the closest neighbours of
+    /// `from` once it is over capacity.
+    fn connect(&mut self, from: u32, to: u32, layer: usize) {
+        let cap = max_neighbors(layer);
+        let mut list = std::mem::take(&mut self.neighbors[from as usize][layer]);
+        list.push(to);
+        if list.len() > cap {
+            let base = self.vector(from).to_vec();
+            let mut scored = list
+                .iter()
+                .map(|&n| Scored {
+                    dist: self.distance(&base, n),
+                    node: n,
+                })
+                .collect::<Vec<_>>();
+            scored.sort_unstable();
+            list = scored.into_iter().take(cap).map(|s| s.node).collect();
+        }
+        self.neighbors[from as usize][layer] = list;
+    }
+
+    /// Best-first search on one layer; returns up to `ef` nodes, closest first.
+    fn search_layer(&self, query: &[f32], eps: &[Scored], ef: usize, layer: usize) -> Vec<Scored> {
+        let mut visited = eps.iter().map(|s| s.node).collect::<HashSet<_>>();
+        let mut candidates = eps
+            .iter()
+            .map(|&s| std::cmp::Reverse(s))
+            .collect::<BinaryHeap<_>>();
+        let mut results = eps.iter().copied().collect::<BinaryHeap<_>>();
+        while results.len() > ef {
+            results.pop();
+        }
+        while let Some(std::cmp::Reverse(current)) = candidates.pop() {
+            if results.len() >= ef && results.peek().is_some_and(|w| current.dist > w.dist) {
+                break;
+            }
+            let Some(neighbors) = self.neighbors[current.node as usize].get(layer) else {
+                continue;
+            };
+            for &neighbor in neighbors {
+                if !visited.insert(neighbor) {
+                    continue;
+                }
+                let dist = self.distance(query, neighbor);
+                if results.len() < ef || results.peek().is_some_and(|w| dist < w.dist) {
+                    let scored = Scored {
+                        dist,
+                        node: neighbor,
+                    };
+                    candidates.push(std::cmp::Reverse(scored));
+                    results.push(scored);
+                    if results.len() > ef {
+                        results.pop();
+                    }
+                }
+            }
+        }
+        results.into_sorted_vec()
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(16 + self.vectors.len() * 4 + self.len() * 8);
+        buf.extend_from_slice(MAGIC);
+        buf.write_u32::<LittleEndian>(VERSION).unwrap();
+        buf.write_u32::<LittleEndian>(self.dimensions as u32)
+            .unwrap();
+        buf.write_u32::<LittleEndian>(self.len() as u32).unwrap();
+        buf.write_u32::<LittleEndian>(self.entry.unwrap_or(NO_ENTRY))
+            .unwrap();
+        for node in 0..self.len() {
+            buf.write_u32::<LittleEndian>(self.doc_ids[node]).unwrap();
+            for v in self.vector(node as u32) {
+                buf.write_f32::<LittleEndian>(*v).unwrap();
+            }
+            let layers = &self.neighbors[node];
+            buf.write_u8(layers.len() as u8).unwrap();
+            for list in layers {
+                buf.write_u32::<LittleEndian>(list.len() as u32).unwrap();
+                for n in list {
+                    buf.write_u32::<LittleEndian>(*n).unwrap();
+                }
+            }
+        }
+        buf
+    }
+
+    pub fn from_bytes(data: &[u8]) -> Result<Self> {
+        ensure!(
+            data.len() >= 20 && &data[..4] == MAGIC,
+            "invalid vector index header"
+        );
+        let mut cursor = Cursor::new(&data[4..]);
+        let version = cursor.read_u32::<LittleEndian>()?;
+        ensure!(
+            version == VERSION,
+            "unsupported vector index version {version}"
+        );
+        let dimensions = cursor.read_u32::<LittleEndian>()? as usize;
+        let count = cursor.read_u32::<LittleEndian>()? as usize;
+        let entry = cursor.read_u32::<LittleEndian>()?;
+        // every node takes at least its doc id, vector and layer count
+        ensure!(
+            dimensions > 0 && count.saturating_mul(5 + dimensions * 4) <= data.len(),
+            "vector index is truncated"
+        );
+
+        let mut index = VectorIndex::new(dimensions);
+        index.vectors.reserve(count * dimensions);
+        for _ in 0..count {
+            index.doc_ids.push(cursor.read_u32::<LittleEndian>()?);
+            for _ in 0..dimensions {
+                index.vectors.push(cursor.read_f32::<LittleEndian>()?);
+            }
+            let layers = cursor.read_u8()? as usize;
+            ensure!(layers > 0, "vector index node without layers");
+            let mut node_layers = Vec::with_capacity(layers);
+            for _ in 0..layers {
+                let len = cursor.read_u32::<LittleEndian>()? as usize;
+                ensure!(
+                    len <= max_neighbors(0),
+                    "vector index node has too many neighbours"
+                );
+                let mut list = Vec::with_capacity(len);
+                for _ in 0..len {
+                    let n = cursor.read_u32::<LittleEndian>()?;
+                    ensure!((n as usize) < count, "vector index neighbour out of range");
+                    list.push(n);
+                }
+                node_layers.push(list);
+            }
+            index.neighbors.push(node_layers);
+        }
+        index.entry = match entry {
+            NO_ENTRY => None,
+            e if (e as usize) < count => Some(e),
+            e => return Err(anyhow!("vector index entry point {e} out of range")),
+        };
+        Ok(index)
+    }
+}
+
+fn max_neighbors(layer: usize) -> usize {
+    if layer == 0 {
+        MAX_NEIGHBORS * 2
+    } else {
+        MAX_NEIGHBORS
+    }
+}
+
+/// Deterministic HNSW level for a node, so that rebuilding an index for the
+/// same file gives the same graph.
+fn random_level(node: u32) -> usize {
+    let mut z = (node as u64).wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^= z >> 31;
+    // uniform in (0, 1]
+    let u = ((z >> 11) as f64 + 1.0) / (1u64 << 53) as f64;
+    let level = (-u.ln() / (MAX_NEIGHBORS as f64).ln()).floor() as usize;
+    level.min(16)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_vector(i: u32, dims: usize) -> Vec<f32> {
+        (0..dims)
+            .map(|d| {
+                let mut z = (i as u64 * dims as u64 + d as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+                z ^= z >> 29;
+                (z >> 40) as f32 / (1u64 << 24) as f32 - 0.5
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_search_matches_brute_force() {
+        let dims = 8;
+        let mut index = VectorIndex::new(dims);
+        for i in 0..500 {
+            assert!(index.insert(i * 2, sample_vector(i, dims)));
+        }
+        assert!(!index.insert(1, vec![0.0; dims]));
+        assert!(!index.insert(1, vec![1.0; dims + 1]));
+        assert_eq!(index.len(), 500);
+
+        let query = sample_vector(42, dims);
+        let hits = index.search(&query, 5, DEFAULT_EF_SEARCH);
+        assert_eq!(hits.len(), 5);
+        assert_eq!(hits[0].0, 84);
+        assert!((hits[0].1 - 1.0).abs() < 1e-5);
+        assert!(hits.windows(2).all(|w| w[0].1 >= w[1].1));
+
+        let mut exact = (0..500u32)
+            .map(|i| {
+                let sim = config::utils::vector::cosine_similarity(&query, &sample_vector(i, dims))
+                    .unwrap();
+                (i * 2, sim)
+            })
+            .collect::<Vec<_>>();
+        exact.sort_by(|a, b| b.1.total_cmp(&a.1));
+        let found = hits.iter().map(|h| h.0).collect::<HashSet<_>>();
+        let recall = exact[..5].iter().filter(|e| found.contains(&e.0)).count();
+        assert!(recall >= 4, "recall {recall}/5");
+    }
+
+    #[test]
+    fn test_serialization_roundtrip() {
+        let dims = 4;
+        let mut index = VectorIndex::new(dims);
+        assert!(index.search(&[1.0, 0.0, 0.0, 0.0], 3, 10).is_empty());
+        for i in 0..50 {
+            index.insert(i, sample_vector(i, dims));
+        }
+        let bytes = index.to_bytes();
+        let decoded = VectorIndex::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded, index);
+        assert_eq!(
+            decoded.search(&sample_vector(7, dims), 3, 10),
+            index.search(&sample_vector(7, dims), 3, 10)
+        );
+
+        assert!(VectorIndex::from_bytes(&bytes[..bytes.len() - 3]).is_err());
+        assert!(VectorIndex::from_bytes(b"NOPE").is_err());
+        let empty = VectorIndex::new(dims).to_bytes();
+        assert!(VectorIndex::from_bytes(&empty).unwrap().is_empty());
+    }
+}
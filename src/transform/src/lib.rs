@@ -62,6 +62,12 @@ where
     GLOBAL_ENRICHMENT_TABLES.insert(name.into(), Box::new(table));
 }
 
+pub fn get_global_enrichment_table(name: &str) -> Option<Box<dyn Table + Send + Sync>> {
+    GLOBAL_ENRICHMENT_TABLES
+        .get(name)
+        .map(|t| t.value().clone())
+}
+
 pub fn remove_global_enrichment_table(name: &str) {
     GLOBAL_ENRICHMENT_TABLES.remove(name);
 }